@@ -321,4 +321,38 @@ impl Authenticator {
 
         serde_json::from_slice(&bytes).map_err(|_| XboxAuthenticateError::SerializationError)
     }
+
+    /// Replaces the skin of the profile, returning the profile with the new skin
+    pub async fn upload_skin(
+        &mut self,
+        access_token: &MinecraftAccessToken,
+        slim: bool,
+        png: &[u8],
+    ) -> Result<MinecraftProfileResponse, XboxAuthenticateError> {
+        const BOUNDARY: &str = "----PandoraSkinUpload";
+
+        let variant = if slim { "slim" } else { "classic" };
+        let mut body = Vec::with_capacity(png.len() + 256);
+        body.extend_from_slice(format!("--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"variant\"\r\n\r\n{variant}\r\n").as_bytes());
+        body.extend_from_slice(format!("--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"skin.png\"\r\nContent-Type: image/png\r\n\r\n").as_bytes());
+        body.extend_from_slice(png);
+        body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+
+        let response = self
+            .client
+            .post(constants::MINECRAFT_SKINS_URL)
+            .bearer_auth(access_token.secret())
+            .header(reqwest::header::CONTENT_TYPE, format!("multipart/form-data; boundary={BOUNDARY}"))
+            .body(body)
+            .send()
+            .await?;
+
+        if response.status() != reqwest::StatusCode::OK {
+            return Err(XboxAuthenticateError::NonOkHttpStatus(response.status()));
+        }
+
+        let bytes = response.bytes().await?;
+
+        serde_json::from_slice(&bytes).map_err(|_| XboxAuthenticateError::SerializationError)
+    }
 }
@@ -8,3 +8,4 @@ pub const XBOX_AUTHENTICATE_URL: &str = "https://user.auth.xboxlive.com/user/aut
 pub const XSTS_AUTHORIZE_URL: &str = "https://xsts.auth.xboxlive.com/xsts/authorize";
 pub const MINECRAFT_LOGIN_WITH_XBOX_URL: &str = "https://api.minecraftservices.com/authentication/login_with_xbox";
 pub const MINECRAFT_PROFILE_URL: &str = "https://api.minecraftservices.com/minecraft/profile";
+pub const MINECRAFT_SKINS_URL: &str = "https://api.minecraftservices.com/minecraft/profile/skins";
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use auth::models::{MinecraftAccessToken, MinecraftProfileResponse};
 use bridge::{account::Account, message::MessageToFrontend};
+use md5::{Digest, Md5};
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -26,6 +27,7 @@ impl BackendAccountInfo {
                 uuid: *uuid,
                 username: account.username.clone(),
                 head: account.head.clone(),
+                offline: account.offline,
             });
         }
         accounts.sort_by(|a, b| lexical_sort::natural_lexical_cmp(&a.username, &b.username));
@@ -44,6 +46,15 @@ pub struct BackendAccount {
     pub head: Option<Arc<[u8]>>,
 }
 
+/// Same UUID that vanilla servers in offline mode assign to a player, so worlds and
+/// server data stay attached to the username across machines
+pub fn offline_uuid(username: &str) -> Uuid {
+    let mut hasher = Md5::new();
+    hasher.update(b"OfflinePlayer:");
+    hasher.update(username.as_bytes());
+    uuid::Builder::from_md5_bytes(hasher.finalize().into()).into_uuid()
+}
+
 impl BackendAccount {
     pub fn new_from_profile(profile: &MinecraftProfileResponse) -> Self {
         Self {
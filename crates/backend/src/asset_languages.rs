@@ -0,0 +1,64 @@
+use std::path::Path;
+
+use schema::assets_index::AssetsIndex;
+
+/// Minecraft falls back to this language for any missing translation, so it's always downloaded
+const FALLBACK_LANGUAGE: &str = "en_us";
+
+/// What an entry of the asset index is used for
+#[derive(Debug, PartialEq, Eq)]
+pub enum AssetKind<'a> {
+    /// Translations for one language, e.g. `minecraft/lang/de_de.json` or the legacy `lang/de_DE.lang`
+    Language { locale: &'a str },
+    Other,
+}
+
+pub fn classify(name: &str) -> AssetKind<'_> {
+    let mut parts = name.rsplit('/');
+    let (Some(file_name), Some("lang")) = (parts.next(), parts.next()) else {
+        return AssetKind::Other;
+    };
+    // Only namespace/lang/file and the legacy lang/file are translations
+    if parts.count() > 1 {
+        return AssetKind::Other;
+    }
+    match file_name.strip_suffix(".json").or_else(|| file_name.strip_suffix(".lang")) {
+        Some(locale) if !locale.is_empty() => AssetKind::Language { locale },
+        _ => AssetKind::Other,
+    }
+}
+
+/// The language picked in game, read from options.txt
+pub fn selected_language(dot_minecraft: &Path) -> Option<String> {
+    let options = crate::syncing::read_options_txt(&dot_minecraft.join("options.txt"));
+    options.get("lang").map(|lang| lang.to_ascii_lowercase())
+}
+
+/// Removes the translations of every language except the selected one and the fallback. Picking another language in
+/// game falls back to English until the next launch, which then downloads it
+pub fn without_unused_languages(assets_index: &AssetsIndex, selected_language: Option<&str>) -> AssetsIndex {
+    let mut assets_index = assets_index.clone();
+    assets_index.objects.retain(|name, _| match classify(name) {
+        AssetKind::Language { locale } => {
+            locale.eq_ignore_ascii_case(FALLBACK_LANGUAGE) || selected_language.is_some_and(|selected| locale.eq_ignore_ascii_case(selected))
+        },
+        AssetKind::Other => true,
+    });
+    assets_index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn language_assets_are_classified() {
+        assert_eq!(classify("minecraft/lang/de_de.json"), AssetKind::Language { locale: "de_de" });
+        assert_eq!(classify("realms/lang/fr_fr.lang"), AssetKind::Language { locale: "fr_fr" });
+        assert_eq!(classify("lang/pt_BR.lang"), AssetKind::Language { locale: "pt_BR" });
+        assert_eq!(classify("minecraft/sounds/lang/cow.ogg"), AssetKind::Other);
+        assert_eq!(classify("minecraft/lang/extra/de_de.json"), AssetKind::Other);
+        assert_eq!(classify("minecraft/lang/de_de.png"), AssetKind::Other);
+        assert_eq!(classify("pack.mcmeta"), AssetKind::Other);
+    }
+}
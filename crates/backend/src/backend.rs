@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet}, io::Cursor, path::{Path, PathBuf}, sync::Arc, time::{Duration, SystemTime}
+    collections::{HashMap, HashSet}, io::Cursor, panic::AssertUnwindSafe, path::{Path, PathBuf}, sync::{Arc, atomic::AtomicBool}, time::{Duration, Instant, SystemTime}
 };
 
 use auth::{
@@ -11,37 +11,154 @@ use auth::{
 };
 use base64::Engine;
 use bridge::{
-    handle::{BackendHandle, BackendReceiver, FrontendHandle}, install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath}, instance::{InstanceID, InstanceContentSummary, InstanceServerSummary, InstanceWorldSummary, ContentType}, message::MessageToFrontend, modal_action::{ModalAction, ModalActionVisitUrl, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath
+    handle::{BackendHandle, BackendReceiver, FrontendHandle}, install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath}, instance::{InstanceID, InstanceContentSummary, InstanceServerSummary, InstanceWorldSummary, ContentType}, message::{LaunchMods, MessageToFrontend}, modal_action::{ModalAction, ModalActionVisitUrl, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath
 };
 use indexmap::IndexSet;
 use parking_lot::RwLock;
 use reqwest::{StatusCode, redirect::Policy};
 use rustc_hash::{FxHashMap, FxHashSet};
-use schema::{auxiliary::AuxiliaryContentMeta, backend_config::BackendConfig, instance::InstanceConfiguration, loader::Loader, modrinth::ModrinthSideRequirement};
+use schema::{auxiliary::AuxiliaryContentMeta, backend_config::BackendConfig, instance::{InstanceConfiguration, InstanceJvmFlagsConfiguration}, loader::Loader, modrinth::ModrinthSideRequirement};
 use serde::Deserialize;
 use sha1::{Digest, Sha1};
 use tokio::sync::{mpsc::Receiver, OnceCell};
+use tokio_util::sync::CancellationToken;
 use ustr::Ustr;
 use uuid::Uuid;
 
 use crate::{
-    account::{BackendAccountInfo, MinecraftLoginInfo}, directories::LauncherDirectories, id_slab::IdSlab, instance::{Instance, ContentFolder}, launch::Launcher, metadata::{items::MinecraftVersionManifestMetadataItem, manager::MetadataManager}, mod_metadata::ModMetadataManager, persistent::Persistent
+    account::{BackendAccountInfo, MinecraftLoginInfo}, directories::LauncherDirectories, download_manager::DownloadManager, id_slab::IdSlab, instance::{Instance, ContentFolder}, launch::Launcher, metadata::{items::MinecraftVersionManifestMetadataItem, manager::MetadataManager}, mod_metadata::ModMetadataManager, network::NetworkSettings, pack_watch::PackWatch, persistent::Persistent, playtime::{PlaytimeLimits, PlaytimeTracker, RunningInstance}
 };
 
+/// A backend that crashes this soon after being started isn't restarted automatically, since it would most likely
+/// crash again. It's left for the user to restart from the error screen instead
+const AUTOMATIC_RESTART_COOLDOWN: Duration = Duration::from_secs(60);
+
 pub fn start(launcher_dir: PathBuf, send: FrontendHandle, self_handle: BackendHandle, recv: BackendReceiver) {
+    let backend = load(launcher_dir.clone(), send, self_handle);
+
+    std::thread::Builder::new()
+        .name("backend".into())
+        .spawn(move || supervise(launcher_dir, backend, recv))
+        .expect("Failed to spawn backend thread");
+}
+
+pub(crate) struct LoadedBackend {
+    pub(crate) runtime: tokio::runtime::Runtime,
+    pub(crate) state: BackendState,
+    pub(crate) watcher_rx: Receiver<notify_debouncer_full::DebounceEventResult>,
+}
+
+enum BackendExit {
+    Stopped,
+    RestartRequested,
+    Crashed,
+}
+
+/// Runs the backend until it stops, restarting it when it crashes or when the frontend asks for it
+fn supervise(launcher_dir: PathBuf, mut backend: LoadedBackend, mut recv: BackendReceiver) {
+    let send = backend.state.send.clone();
+    let self_handle = backend.state.self_handle.clone();
+
+    loop {
+        let started_at = Instant::now();
+        let LoadedBackend { runtime, state, watcher_rx } = backend;
+        let shutdown = state.shutdown.clone();
+        let instance_state = state.instance_state.clone();
+
+        let exit = std::panic::catch_unwind(AssertUnwindSafe(|| runtime.block_on(async {
+            tokio::select! {
+                _ = state.start(recv, watcher_rx) => BackendExit::Stopped,
+                _ = self_handle.restart_requested() => BackendExit::RestartRequested,
+                // Tokio catches panics in spawned tasks, so those are reported by the panic hook
+                _ = self_handle.crash_reported() => BackendExit::Crashed,
+            }
+        }))).unwrap_or(BackendExit::Crashed);
+
+        let crashed = match exit {
+            BackendExit::Stopped => {
+                tracing::info!("Shutting down backend runtime");
+                runtime.shutdown_timeout(Duration::from_secs(2));
+                return;
+            },
+            BackendExit::RestartRequested => {
+                tracing::info!("Restarting backend");
+                shutdown.cancel();
+                runtime.shutdown_timeout(Duration::from_secs(2));
+                false
+            },
+            BackendExit::Crashed => {
+                tracing::error!("Backend crashed");
+                // A new backend doesn't know about games that are still running, so the user decides whether to
+                // restart then. The state may be locked by a task that stopped halfway, in which case it's assumed
+                let games_running = instance_state.try_read().is_none_or(|instance_state| {
+                    instance_state.instances.iter().any(|instance| instance.process.is_some() || instance.launching.is_some())
+                });
+                runtime.shutdown_background();
+                // The receiver was dropped with the backend, so the frontend shows that it has shut down
+                send.send(MessageToFrontend::Refresh);
+                if games_running || started_at.elapsed() < AUTOMATIC_RESTART_COOLDOWN {
+                    tracing::info!("Waiting for the backend to be restarted from the frontend");
+                    futures::executor::block_on(self_handle.restart_requested());
+                }
+                true
+            },
+        };
+
+        self_handle.fail_pending_modal_actions(rust_i18n::t!("notification_backend_restarted_during_action").into());
+
+        // Loading sends the accounts and instances to the frontend again, after it has forgotten the old ones
+        loop {
+            let new_recv = self_handle.reconnect();
+            send.send(MessageToFrontend::BackendRestarted);
+            match std::panic::catch_unwind(AssertUnwindSafe(|| load(launcher_dir.clone(), send.clone(), self_handle.clone()))) {
+                Ok(loaded) => {
+                    backend = loaded;
+                    recv = new_recv;
+                    break;
+                },
+                Err(_) => {
+                    tracing::error!("Backend crashed while restarting");
+                    drop(new_recv);
+                    send.send(MessageToFrontend::Refresh);
+                    futures::executor::block_on(self_handle.restart_requested());
+                },
+            }
+        }
+
+        if crashed {
+            send.send_error(rust_i18n::t!("notification_backend_restarted"));
+        }
+        send.send(MessageToFrontend::Refresh);
+    }
+}
+
+/// Creates the runtime and state of the backend, loading the accounts and instances and sending them to the frontend
+pub(crate) fn load(launcher_dir: PathBuf, send: FrontendHandle, self_handle: BackendHandle) -> LoadedBackend {
     let runtime = tokio::runtime::Builder::new_multi_thread()
         .worker_threads(1)
         .enable_all()
         .build()
         .expect("Failed to initialize Tokio runtime");
 
-    let user_agent = if let Some(version) = option_env!("PANDORA_RELEASE_VERSION") {
-        format!("PandoraLauncher/{version} (https://github.com/Moulberry/PandoraLauncher)")
-    } else {
-        "PandoraLauncher/dev (https://github.com/Moulberry/PandoraLauncher)".to_string()
+    let directories = Arc::new(LauncherDirectories::new(launcher_dir));
+
+    // Load config
+    let mut config: Persistent<BackendConfig> = Persistent::load(directories.config_json.clone());
+    crate::endpoints::set_overrides(config.get().endpoint_overrides.clone());
+    crate::offline::set_forced(config.get().offline_mode);
+
+    let user_agent = crate::network::user_agent(config.get().custom_user_agent.as_deref());
+
+    let network_settings = match NetworkSettings::new(&config.get().proxy, config.get().custom_ca_certificates.as_deref()) {
+        Ok(network_settings) => network_settings,
+        Err(error) => {
+            tracing::error!("Ignoring proxy and certificate settings: {error}");
+            send.send_error(format!("Ignoring proxy and certificate settings: {error}"));
+            NetworkSettings::default()
+        },
     };
 
-    let http_client = reqwest::ClientBuilder::new()
+    let http_client = network_settings.apply(reqwest::ClientBuilder::new())
         .connect_timeout(Duration::from_secs(15))
         .read_timeout(Duration::from_secs(15))
         .redirect(Policy::none())
@@ -50,18 +167,19 @@ pub fn start(launcher_dir: PathBuf, send: FrontendHandle, self_handle: BackendHa
         .build()
         .unwrap();
 
-    let redirecting_http_client = reqwest::ClientBuilder::new()
+    let redirecting_http_client = network_settings.apply(reqwest::ClientBuilder::new())
         .use_rustls_tls()
         .user_agent(&user_agent)
         .build()
         .unwrap();
 
-    let directories = Arc::new(LauncherDirectories::new(launcher_dir));
+    let download_manager = DownloadManager::new(config.get().download_limits);
 
     let meta = Arc::new(MetadataManager::new(
         http_client.clone(),
         directories.metadata_dir.clone(),
     ));
+    meta.set_strict_schema(config.get().strict_schema);
 
     let (watcher_tx, watcher_rx) = tokio::sync::mpsc::channel::<notify_debouncer_full::DebounceEventResult>(64);
     let watcher = notify_debouncer_full::new_debouncer(Duration::from_millis(100), None, move |event| {
@@ -85,14 +203,15 @@ pub fn start(launcher_dir: PathBuf, send: FrontendHandle, self_handle: BackendHa
     };
 
     // Create initial directories
-    let _ = std::fs::create_dir_all(&directories.instances_dir);
+    if !crate::data_lock::is_read_only() {
+        let _ = std::fs::create_dir_all(&directories.instances_dir);
+    }
     state_file_watching.watch_filesystem(directories.root_launcher_dir.clone(), WatchTarget::RootDir);
 
     // Load accounts
     let account_info = Persistent::load(directories.accounts_json.clone());
 
-    // Load config
-    let config = Persistent::load(directories.config_json.clone());
+    let playtime = PlaytimeTracker::load(directories.playtime_json.clone());
 
     let mut state = BackendState {
         self_handle,
@@ -103,24 +222,31 @@ pub fn start(launcher_dir: PathBuf, send: FrontendHandle, self_handle: BackendHa
         instance_state: Arc::new(RwLock::new(state_instances)),
         file_watching: Arc::new(RwLock::new(state_file_watching)),
         directories: Arc::clone(&directories),
-        launcher: Launcher::new(meta, directories, send),
+        launcher: Launcher::new(meta, directories, send, download_manager.clone()),
+        download_manager,
         mod_metadata_manager: Arc::new(mod_metadata_manager),
         account_info: Arc::new(RwLock::new(account_info)),
         config: Arc::new(RwLock::new(config)),
+        playtime: Arc::new(RwLock::new(playtime)),
         secret_storage: Arc::new(OnceCell::new()),
         head_cache: Default::default(),
+        pack_watches: Default::default(),
+        launcher_unlocked: Default::default(),
+        shutdown: CancellationToken::new(),
     };
 
-    log::debug!("Doing initial backend load");
+    tracing::debug!("Doing initial backend load");
 
     runtime.block_on(async {
         state.send.send(state.account_info.write().get().create_update_message());
         state.load_all_instances().await;
     });
 
-    runtime.spawn(state.start(recv, watcher_rx));
-
-    std::mem::forget(runtime);
+    LoadedBackend {
+        runtime,
+        state,
+        watcher_rx,
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -134,6 +260,7 @@ pub enum WatchTarget {
     InstanceSavesDir { id: InstanceID },
     ServersDat { id: InstanceID },
     InstanceContentDir { id: InstanceID, folder: ContentFolder },
+    InstanceScreenshotsDir { id: InstanceID },
 }
 
 pub struct BackendStateInstances {
@@ -156,6 +283,7 @@ pub struct BackendState {
     pub send: FrontendHandle,
     pub http_client: reqwest::Client,
     pub redirecting_http_client: reqwest::Client,
+    pub download_manager: DownloadManager,
     pub meta: Arc<MetadataManager>,
     pub instance_state: Arc<RwLock<BackendStateInstances>>,
     pub file_watching: Arc<RwLock<BackendStateFileWatching>>,
@@ -164,8 +292,13 @@ pub struct BackendState {
     pub mod_metadata_manager: Arc<ModMetadataManager>,
     pub account_info: Arc<RwLock<Persistent<BackendAccountInfo>>>,
     pub config: Arc<RwLock<Persistent<BackendConfig>>>,
+    pub playtime: Arc<RwLock<PlaytimeTracker>>,
     pub secret_storage: Arc<OnceCell<Result<PlatformSecretStorage, SecretStorageError>>>,
-    pub head_cache: Arc<RwLock<FxHashMap<Arc<str>, HeadCacheEntry>>>
+    pub head_cache: Arc<RwLock<FxHashMap<Arc<str>, HeadCacheEntry>>>,
+    pub pack_watches: Arc<parking_lot::Mutex<FxHashMap<InstanceID, PackWatch>>>,
+    /// Set once the launcher PIN was entered, see `MessageToBackend::requires_unlock`
+    pub launcher_unlocked: Arc<AtomicBool>,
+    pub shutdown: CancellationToken,
 }
 
 pub enum HeadCacheEntry {
@@ -180,9 +313,33 @@ pub enum HeadCacheEntry {
 
 impl BackendState {
     async fn start(self, recv: BackendReceiver, watcher_rx: Receiver<notify_debouncer_full::DebounceEventResult>) {
-        log::info!("Starting backend");
+        tracing::info!("Starting backend");
+
+        if crate::data_lock::is_read_only() {
+            self.send.send_warning("Another launcher is using this folder, so it was opened read-only. Close the other launcher to make changes");
+        }
+
+        tokio::task::spawn(self.clone().watch_for_updates());
+        tokio::task::spawn(self.clone().refresh_metadata_periodically());
+        tokio::task::spawn(self.clone().watch_connectivity());
+
+        let backend = self.clone();
+        tokio::task::spawn(async move {
+            backend.offer_official_launcher_files().await;
+        });
 
-        tokio::task::spawn(crate::update::check_for_updates(self.redirecting_http_client.clone(), self.send.clone()));
+        // A read-only launcher would take requests meant for the one that holds the launcher directory
+        if crate::ipc::is_server_enabled() && !crate::data_lock::is_read_only() {
+            tokio::task::spawn(self.clone().serve_ipc());
+        }
+
+        let watched: Vec<InstanceID> = self.instance_state.write().instances.iter_mut()
+            .filter(|instance| instance.configuration.get().pack_source.is_some())
+            .map(|instance| instance.id)
+            .collect();
+        for id in watched {
+            self.update_pack_watch(id);
+        }
 
         // Pre-fetch version manifest
         self.meta.load(&MinecraftVersionManifestMetadataItem).await;
@@ -191,14 +348,14 @@ impl BackendState {
     }
 
     pub async fn load_all_instances(&mut self) {
-        log::info!("Loading all instances");
+        tracing::info!("Loading all instances");
 
         let mut paths_with_time = Vec::new();
 
         self.file_watching.write().watch_filesystem(self.directories.instances_dir.clone(), WatchTarget::InstancesDir);
         for entry in std::fs::read_dir(&self.directories.instances_dir).unwrap() {
             let Ok(entry) = entry else {
-                log::warn!("Error reading directory in instances folder: {:?}", entry.unwrap_err());
+                tracing::warn!("Error reading directory in instances folder: {:?}", entry.unwrap_err());
                 continue;
             };
 
@@ -216,8 +373,7 @@ impl BackendState {
 
             // options.txt exists in every minecraft version, so we use its
             // modified time to determine the latest instance as well
-            let mut options_txt = path.join(".minecraft");
-            options_txt.push("options.txt");
+            let options_txt = crate::instance::read_dot_minecraft_path(&path).join("options.txt");
             if let Ok(metadata) = options_txt.metadata() {
                 if let Ok(created) = metadata.created() {
                     time = time.max(created);
@@ -237,10 +393,30 @@ impl BackendState {
                 self.file_watching.write().watch_filesystem(path.into(), WatchTarget::InvalidInstanceDir);
             }
         }
+
+        // The launcher may have exited while an instance was running in safe mode
+        let mods_dirs: Vec<Arc<Path>> = self.instance_state.read().instances.iter()
+            .map(|instance| instance.content_state[ContentFolder::Mods].path.clone())
+            .collect();
+        for mods_dir in mods_dirs {
+            self.restore_safe_mode_mods(&mods_dir);
+        }
+    }
+
+    pub fn restore_safe_mode_mods(&self, mods_dir: &Path) {
+        match crate::safe_mode::restore_mods(mods_dir) {
+            Ok(true) => tracing::info!("Restored mods moved aside for safe mode in {mods_dir:?}"),
+            Ok(false) => {},
+            Err(error) => {
+                tracing::error!("Unable to restore mods moved aside for safe mode: {error}");
+                self.send.send_error(format!("Unable to restore mods after safe mode, they can be found in {:?}: {error}",
+                    crate::safe_mode::aside_path(mods_dir)));
+            },
+        }
     }
 
     pub fn remove_instance(&mut self, id: InstanceID) {
-        log::info!("Removing instance {id:?}");
+        tracing::info!("Removing instance {id:?}");
 
         let mut instance_state = self.instance_state.write();
 
@@ -268,12 +444,36 @@ impl BackendState {
                 if show_errors {
                     let error = instance.unwrap_err();
                     self.send.send_error(format!("Unable to load instance from {:?}:\n{}", &path, &error));
-                    log::error!("Error loading instance: {:?}", &error);
+                    tracing::error!("Error loading instance: {:?}", &error);
                 }
 
                 return false;
             };
 
+            // Copies of an instance folder start out with the uuid of the original
+            let uuid = instance.configuration.get().uuid;
+            let taken = uuid.is_some_and(|uuid| instance_state.instances.iter_mut()
+                .any(|other| &*other.root_path != path && other.configuration.get().uuid == Some(uuid)));
+            if uuid.is_none() || taken {
+                instance.configuration.modify(|configuration| {
+                    configuration.uuid = Some(Uuid::new_v4());
+                });
+                if uuid.is_none() {
+                    // Earlier versions keyed today's playtime by the folder name
+                    self.playtime.write().migrate_key(instance.name.as_str(), instance.playtime_key());
+                }
+            }
+
+            // Earlier versions kept the total in the instance configuration
+            let legacy_playtime = instance.configuration.get().total_playtime;
+            if legacy_playtime > 0 {
+                self.playtime.write().add_total(instance.playtime_key(), legacy_playtime);
+                instance.configuration.modify(|configuration| {
+                    configuration.total_playtime = 0;
+                });
+            }
+            instance.sync_playtime(&mut self.playtime.write());
+
             if let Some(existing) = instance_state.instance_by_path.get(path)
                 && let Some(existing_instance) = instance_state.instances.get_mut(*existing)
             {
@@ -308,6 +508,7 @@ impl BackendState {
                 name: instance.name,
                 dot_minecraft_folder: instance.dot_minecraft_path.clone(),
                 configuration: instance.configuration.get().clone(),
+                total_playtime: instance.total_playtime,
                 worlds_state: Arc::clone(&instance.worlds_state),
                 servers_state: Arc::clone(&instance.servers_state),
                 mods_state: Arc::clone(&instance.content_state[ContentFolder::Mods].load_state),
@@ -317,6 +518,14 @@ impl BackendState {
 
             instance_state.instance_by_path.insert(path.to_owned(), instance.id);
 
+            if let Some(targets) = crate::syncing::restore_membership(path, &instance.dot_minecraft_path, &self.directories) {
+                self.send.send(MessageToFrontend::ForeignSyncMembership {
+                    id: instance.id,
+                    name: instance.name,
+                    targets,
+                });
+            }
+
             instance.id
         };
 
@@ -335,7 +544,7 @@ impl BackendState {
                     if let Some(message) = message {
                         self.handle_message(message).await;
                     } else {
-                        log::info!("Backend receiver has shut down");
+                        tracing::info!("Backend receiver has shut down");
                         break;
                     }
                 },
@@ -343,12 +552,16 @@ impl BackendState {
                     if let Some(instance_change) = instance_change {
                         self.handle_filesystem(instance_change).await;
                     } else {
-                        log::info!("Backend filesystem has shut down");
+                        tracing::info!("Backend filesystem has shut down");
                         break;
                     }
                 },
                 _ = interval.tick() => {
                     self.handle_tick().await;
+                },
+                _ = self.shutdown.cancelled() => {
+                    tracing::info!("Backend is shutting down");
+                    break;
                 }
             }
         }
@@ -359,11 +572,97 @@ impl BackendState {
 
         let mut instance_state = self.instance_state.write();
         for instance in instance_state.instances.iter_mut() {
-            if let Some(child) = &mut instance.child
-                && !matches!(child.try_wait(), Ok(None))
+            if let Some(process) = &instance.process && process.lan_watch.take_changed() {
+                self.send.send(instance.create_modify_message());
+            }
+
+            if let Some(process) = &mut instance.process
+                && let Some(exit_status) = process.try_wait()
             {
-                log::debug!("Child process is no longer alive");
-                instance.child = None;
+                tracing::debug!("Game process {} exited with {exit_status}", process.pid());
+                let started_at = process.info().started_at;
+                if let Some(port_forward) = process.port_forward.take() {
+                    self.remove_port_forward(port_forward);
+                }
+                instance.process = None;
+                instance.sync_playtime(&mut self.playtime.write());
+                instance.crashed = !exit_status.success();
+                self.send.send(instance.create_modify_message());
+                self.restore_safe_mode_mods(&instance.content_state[ContentFolder::Mods].path);
+
+                // The bisection dialog asks whether the game crashed instead of the crash report
+                let bisecting = std::mem::take(&mut instance.launched_mods) == LaunchMods::Bisection;
+                if bisecting {
+                    self.send.send(MessageToFrontend::ModBisectionLaunchExited {
+                        id: instance.id,
+                        name: instance.name,
+                        crashed: instance.crashed,
+                    });
+                }
+
+                if instance.configuration.get().capture_log_events {
+                    let root_path = instance.root_path.clone();
+                    let dot_minecraft = instance.dot_minecraft_path.clone();
+                    tokio::task::spawn_blocking(move || {
+                        let events = crate::timeline::capture_from_latest_log(&dot_minecraft, started_at);
+                        if let Err(error) = crate::timeline::append(&root_path, events) {
+                            tracing::error!("Unable to save instance timeline: {error}");
+                        }
+                    });
+                }
+
+                if instance.crashed && !bisecting {
+                    let id = instance.id;
+                    let name = instance.name;
+                    let dot_minecraft = instance.dot_minecraft_path.clone();
+                    let exit_code = exit_status.code();
+                    let send = self.send.clone();
+
+                    // Querying the GPU runs external tools, so keep it off the tick
+                    tokio::task::spawn_blocking(move || {
+                        let crash_report = crate::crash_report::find_crash_report(&dot_minecraft, started_at);
+                        let system_info = crate::system_info::describe(&crate::system_info::query_gpus());
+                        let bundle = crate::crash_report::write_bundle(&dot_minecraft, exit_code, crash_report.as_ref(), &system_info);
+                        if let Err(error) = &bundle {
+                            tracing::error!("Unable to write crash report bundle: {error}");
+                        }
+
+                        send.send(MessageToFrontend::InstanceCrashed {
+                            id,
+                            name,
+                            exit_code,
+                            crash_report: crash_report.as_ref().map(|report| report.path.as_path().into()),
+                            excerpt: crash_report.map(|report| report.excerpt),
+                            system_info: system_info.into(),
+                            bundle: bundle.ok().map(Arc::from),
+                        });
+                    });
+                }
+            }
+        }
+
+        let global_playtime_limit = self.config.write().get().daily_playtime_limit;
+        let running: Vec<RunningInstance> = instance_state.instances.iter_mut()
+            .filter(|instance| instance.process.is_some())
+            .map(|instance| RunningInstance {
+                key: instance.playtime_key(),
+                name: instance.name,
+                limits: PlaytimeLimits {
+                    instance: instance.configuration.get().daily_playtime_limit,
+                    global: global_playtime_limit,
+                },
+            })
+            .collect();
+
+        let mut playtime = self.playtime.write();
+        for reminder in playtime.tick(&running) {
+            self.send.send_warning(reminder);
+        }
+
+        for instance in instance_state.instances.iter_mut().filter(|instance| instance.process.is_some()) {
+            let previous_minutes = instance.total_playtime / 60;
+            instance.sync_playtime(&mut playtime);
+            if instance.total_playtime / 60 != previous_minutes {
                 self.send.send(instance.create_modify_message());
             }
         }
@@ -375,11 +674,11 @@ impl BackendState {
         login_tracker: &ProgressTracker,
         modal_action: &ModalAction,
     ) -> Result<(MinecraftProfileResponse, MinecraftAccessToken), LoginError> {
-        log::info!("Starting login");
+        tracing::info!("Starting login");
 
         let mut authenticator = Authenticator::new(self.http_client.clone());
 
-        login_tracker.set_total(AUTH_STAGE_COUNT as usize + 1);
+        login_tracker.set_total(AUTH_STAGE_COUNT as u64 + 1);
         login_tracker.notify();
 
         let mut last_auth_stage = None;
@@ -392,20 +691,20 @@ impl BackendState {
             let stage_with_data = credentials.stage();
             let stage = stage_with_data.stage();
 
-            login_tracker.set_count(stage as usize + 1);
+            login_tracker.set_count(stage as u64 + 1);
             login_tracker.notify();
 
             if let Some(last_stage) = last_auth_stage {
                 if stage > last_stage {
                     allow_backwards = false;
                 } else if stage < last_stage && !allow_backwards {
-                    log::error!(
+                    tracing::error!(
                         "Stage {:?} went backwards from {:?} when going backwards isn't allowed. This is most likely a bug with the auth flow!",
                         stage, last_stage
                     );
                     return Err(LoginError::LoginStageErrorBackwards);
                 } else if stage == last_stage {
-                    log::error!("Stage {:?} didn't change. This is most likely a bug with the auth flow!", stage);
+                    tracing::error!("Stage {:?} didn't change. This is most likely a bug with the auth flow!", stage);
                     return Err(LoginError::LoginStageErrorDidntChange);
                 }
             }
@@ -413,7 +712,7 @@ impl BackendState {
 
             match credentials.stage() {
                 auth::credentials::AuthStageWithData::Initial => {
-                    log::debug!("Auth Flow: Initial");
+                    tracing::debug!("Auth Flow: Initial");
 
                     let pending = authenticator.create_authorization();
                     modal_action.set_visit_url(ModalActionVisitUrl {
@@ -423,7 +722,7 @@ impl BackendState {
                     });
                     self.send.send(MessageToFrontend::Refresh);
 
-                    log::debug!("Starting serve_redirect server");
+                    tracing::debug!("Starting serve_redirect server");
                     let finished = tokio::select! {
                         finished = serve_redirect::start_server(pending) => finished?,
                         _ = modal_action.request_cancel.cancelled() => {
@@ -431,19 +730,19 @@ impl BackendState {
                         }
                     };
 
-                    log::debug!("serve_redirect handled successfully");
+                    tracing::debug!("serve_redirect handled successfully");
 
                     modal_action.unset_visit_url();
                     self.send.send(MessageToFrontend::Refresh);
 
-                    log::debug!("Finishing authorization, getting msa tokens");
+                    tracing::debug!("Finishing authorization, getting msa tokens");
                     let msa_tokens = authenticator.finish_authorization(finished).await?;
 
                     credentials.msa_access = Some(msa_tokens.access);
                     credentials.msa_refresh = msa_tokens.refresh;
                 },
                 auth::credentials::AuthStageWithData::MsaRefresh(refresh) => {
-                    log::debug!("Auth Flow: MsaRefresh");
+                    tracing::debug!("Auth Flow: MsaRefresh");
 
                     match authenticator.refresh_msa(&refresh).await {
                         Ok(Some(msa_tokens)) => {
@@ -461,14 +760,14 @@ impl BackendState {
                                 return Err(error.into());
                             }
                             if !matches!(error, MsaAuthorizationError::InvalidGrant) {
-                                log::warn!("Error using msa refresh to get msa access: {:?}", error);
+                                tracing::warn!("Error using msa refresh to get msa access: {:?}", error);
                             }
                             credentials.msa_refresh = None;
                         },
                     }
                 },
                 auth::credentials::AuthStageWithData::MsaAccess(access) => {
-                    log::debug!("Auth Flow: MsaAccess");
+                    tracing::debug!("Auth Flow: MsaAccess");
 
                     match authenticator.authenticate_xbox(&access).await {
                         Ok(xbl) => {
@@ -479,14 +778,14 @@ impl BackendState {
                                 return Err(error.into());
                             }
                             if !matches!(error, XboxAuthenticateError::NonOkHttpStatus(StatusCode::UNAUTHORIZED)) {
-                                log::warn!("Error using msa access to get xbl token: {:?}", error);
+                                tracing::warn!("Error using msa access to get xbl token: {:?}", error);
                             }
                             credentials.msa_access = None;
                         },
                     }
                 },
                 auth::credentials::AuthStageWithData::XboxLive(xbl) => {
-                    log::debug!("Auth Flow: XboxLive");
+                    tracing::debug!("Auth Flow: XboxLive");
 
                     match authenticator.obtain_xsts(&xbl).await {
                         Ok(xsts) => {
@@ -497,14 +796,14 @@ impl BackendState {
                                 return Err(error.into());
                             }
                             if !matches!(error, XboxAuthenticateError::NonOkHttpStatus(StatusCode::UNAUTHORIZED)) {
-                                log::warn!("Error using xbl to get xsts: {:?}", error);
+                                tracing::warn!("Error using xbl to get xsts: {:?}", error);
                             }
                             credentials.xbl = None;
                         },
                     }
                 },
                 auth::credentials::AuthStageWithData::XboxSecure { xsts, userhash } => {
-                    log::debug!("Auth Flow: XboxSecure");
+                    tracing::debug!("Auth Flow: XboxSecure");
 
                     match authenticator.authenticate_minecraft(&xsts, &userhash).await {
                         Ok(token) => {
@@ -515,18 +814,18 @@ impl BackendState {
                                 return Err(error.into());
                             }
                             if !matches!(error, XboxAuthenticateError::NonOkHttpStatus(StatusCode::UNAUTHORIZED)) {
-                                log::warn!("Error using xsts to get minecraft access token: {:?}", error);
+                                tracing::warn!("Error using xsts to get minecraft access token: {:?}", error);
                             }
                             credentials.xsts = None;
                         },
                     }
                 },
                 auth::credentials::AuthStageWithData::AccessToken(access_token) => {
-                    log::debug!("Auth Flow: AccessToken");
+                    tracing::debug!("Auth Flow: AccessToken");
 
                     match authenticator.get_minecraft_profile(&access_token).await {
                         Ok(profile) => {
-                            login_tracker.set_count(AUTH_STAGE_COUNT as usize + 1);
+                            login_tracker.set_count(AUTH_STAGE_COUNT as u64 + 1);
                             login_tracker.notify();
 
                             return Ok((profile, access_token));
@@ -536,7 +835,7 @@ impl BackendState {
                                 return Err(error.into());
                             }
                             if !matches!(error, XboxAuthenticateError::NonOkHttpStatus(StatusCode::UNAUTHORIZED)) {
-                                log::warn!("Error using access token to get profile: {:?}", error);
+                                tracing::warn!("Error using access token to get profile: {:?}", error);
                             }
                             credentials.access_token = None;
                         },
@@ -547,7 +846,7 @@ impl BackendState {
     }
 
     pub fn update_profile_head(&self, profile: &MinecraftProfileResponse) {
-        log::info!("Updating profile head for {}", profile.id);
+        tracing::info!("Updating profile head for {}", profile.id);
 
         let Some(skin) = profile.skins.iter().find(|skin| skin.state == SkinState::Active).cloned() else {
             return;
@@ -582,19 +881,19 @@ impl BackendState {
         let http_client = self.http_client.clone();
 
         tokio::task::spawn(async move {
-            log::info!("Downloading skin from {}", skin_url);
+            tracing::info!("Downloading skin from {}", skin_url);
             let Ok(response) = http_client.get(&*skin_url).send().await else {
-                log::warn!("Http error while requesting skin from {}", skin_url);
+                tracing::warn!("Http error while requesting skin from {}", skin_url);
                 head_cache.write().insert(skin_url.clone(), HeadCacheEntry::Failed);
                 return;
             };
             let Ok(bytes) = response.bytes().await else {
-                log::warn!("Http error while downloading skin bytes from {}", skin_url);
+                tracing::warn!("Http error while downloading skin bytes from {}", skin_url);
                 head_cache.write().insert(skin_url.clone(), HeadCacheEntry::Failed);
                 return;
             };
             let Ok(mut image) = image::load_from_memory(&bytes) else {
-                log::warn!("Image load error for skin from {}", skin_url);
+                tracing::warn!("Image load error for skin from {}", skin_url);
                 head_cache.write().insert(skin_url.clone(), HeadCacheEntry::Failed);
                 return;
             };
@@ -624,7 +923,7 @@ impl BackendState {
                 }
             };
 
-            log::info!("Successfully downloaded skin from {}", skin_url);
+            tracing::info!("Successfully downloaded skin from {}", skin_url);
 
             if accounts.is_empty() {
                 return;
@@ -693,7 +992,7 @@ impl BackendState {
                 };
                 let file_name = entry.file_name();
                 if file_name.to_string_lossy().starts_with(".pandora.") {
-                    log::trace!("Removing temporary mod file {:?}", &file_name);
+                    tracing::trace!("Removing temporary mod file {:?}", &file_name);
                     _ = std::fs::remove_file(entry.path());
                 }
             }
@@ -844,10 +1143,10 @@ impl BackendState {
             }
 
             if !overrides.is_empty() {
-                let tracker = ProgressTracker::new("Copying overrides".into(), self.send.clone());
+                let tracker = ProgressTracker::new(rust_i18n::t!("progress_copying_overrides").into(), self.send.clone());
                 modal_action.trackers.push(tracker.clone());
 
-                tracker.set_total(overrides.len());
+                tracker.set_total(overrides.len() as u64);
                 tracker.notify();
 
                 for (rel_path, file) in overrides.iter() {
@@ -1028,22 +1327,29 @@ impl BackendState {
     }
 
     pub async fn create_instance(&self, name: &str, version: &str, loader: Loader) -> Option<PathBuf> {
-        log::info!("Creating instance {name}");
+        match self.try_create_instance(name, version, loader).await {
+            Ok(instance_dir) => Some(instance_dir),
+            Err(error) => {
+                self.send.send_warning(error);
+                None
+            },
+        }
+    }
+
+    /// Like `create_instance`, but returns the reason instead of showing it, for callers that report it themselves
+    pub async fn try_create_instance(&self, name: &str, version: &str, loader: Loader) -> Result<PathBuf, String> {
+        tracing::info!("Creating instance {name}");
         if loader == Loader::Unknown {
-            self.send.send_warning(format!("Unable to create instance, unknown loader"));
-            return None;
+            return Err("Unable to create instance, unknown loader".to_string());
         }
         if !crate::is_single_component_path(&name) {
-            self.send.send_warning(format!("Unable to create instance, name must not be a path: {}", name));
-            return None;
+            return Err(format!("Unable to create instance, name must not be a path: {}", name));
         }
         if !sanitize_filename::is_sanitized_with_options(&*name, sanitize_filename::OptionsForCheck { windows: true, ..Default::default() }) {
-            self.send.send_warning(format!("Unable to create instance, name is invalid: {}", name));
-            return None;
+            return Err(format!("Unable to create instance, name is invalid: {}", name));
         }
         if self.instance_state.read().instances.iter().any(|i| i.name == name) {
-            self.send.send_warning("Unable to create instance, name is already used".to_string());
-            return None;
+            return Err("Unable to create instance, name is already used".to_string());
         }
 
         self.file_watching.write().watch_filesystem(self.directories.instances_dir.clone(), WatchTarget::InstancesDir);
@@ -1052,23 +1358,50 @@ impl BackendState {
 
         let _ = tokio::fs::create_dir_all(&instance_dir).await;
 
-        let instance_info = InstanceConfiguration {
-            minecraft_version: Ustr::from(version),
-            loader,
-            preferred_loader_version: None,
-            memory: None,
-            jvm_flags: None,
-            jvm_binary: None,
-            linux_wrapper: None,
-        };
+        let instance_info = InstanceConfiguration::new(Ustr::from(version), loader);
 
         let info_path = instance_dir.join("info_v1.json");
-        crate::write_safe(&info_path, serde_json::to_string(&instance_info).unwrap().as_bytes()).unwrap();
+        crate::write_safe(&info_path, serde_json::to_string(&instance_info).unwrap().as_bytes())
+            .map_err(|error| format!("Unable to create instance: {error}"))?;
+
+        Ok(instance_dir.clone())
+    }
+
+    pub fn prefetch_loader_libraries(&self, version: &str, loader: Loader) {
+        if !matches!(loader, Loader::Fabric) {
+            return;
+        }
+
+        let instance_info = InstanceConfiguration::new(Ustr::from(version), loader);
 
-        Some(instance_dir.clone())
+        let launcher = self.launcher.clone();
+        let http_client = self.redirecting_http_client.clone();
+        tokio::task::spawn(async move {
+            match launcher.prefetch_loader_libraries(&http_client, &instance_info).await {
+                Ok(()) | Err(crate::launch::LaunchError::CancelledByUser) => {},
+                Err(error) => tracing::warn!("Unable to prefetch loader libraries: {error}"),
+            }
+        });
+    }
+
+    pub fn prewarm_instance(&self, id: InstanceID) {
+        let Some(minecraft_version) = self.instance_state.write().instances.get_mut(id)
+            .map(|instance| instance.configuration.get().minecraft_version) else {
+            return;
+        };
+
+        let launcher = self.launcher.clone();
+        tokio::task::spawn(async move {
+            if let Err(error) = launcher.prewarm_metadata(minecraft_version).await {
+                tracing::debug!("Unable to prewarm metadata for {minecraft_version}: {error}");
+            }
+        });
     }
 
     pub async fn rename_instance(&self, id: InstanceID, name: &str) {
+        if self.reject_if_locked(id) {
+            return;
+        }
         if !crate::is_single_component_path(&name) {
             self.send.send_warning(format!("Unable to rename instance, name must not be a path: {}", name));
             return;
@@ -1085,6 +1418,11 @@ impl BackendState {
         let new_instance_dir = self.directories.instances_dir.join(name);
 
         if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            if instance.process.is_some() {
+                self.send.send_warning("Unable to rename instance while it is running".to_string());
+                return;
+            }
+
             let result = std::fs::rename(&instance.root_path, new_instance_dir);
             if let Err(err) = result {
                 self.send.send_error(format!("Unable to rename instance folder: {}", err));
@@ -1092,12 +1430,66 @@ impl BackendState {
         }
     }
 
-    pub async fn get_login_info(&self, modal_action: &ModalAction) -> Option<MinecraftLoginInfo> {
-        let selected_account = {
+    pub async fn duplicate_instance(&self, id: InstanceID) {
+        let Some((name, root_path)) = self.instance_state.read().instances.get(id).map(|instance| (instance.name, instance.root_path.clone())) else {
+            return;
+        };
+
+        let original_name = format!("{name} (copy)");
+        let mut new_name = original_name.clone();
+        for i in 2..32 {
+            if !self.instance_state.read().instances.iter().any(|i| i.name == new_name) && !self.directories.instances_dir.join(&new_name).exists() {
+                break;
+            }
+            new_name = format!("{original_name} {i}");
+        }
+
+        tracing::info!("Duplicating instance {name} as {new_name}");
+
+        // Copy into the temp dir first so the instances watcher doesn't pick up a half-copied instance
+        let temp_dir = self.directories.temp_dir.join(format!("duplicate-{:016x}", rand::random::<u64>()));
+        let new_instance_dir = self.directories.instances_dir.join(&new_name);
+        let sync_targets = self.config.write().get().sync_targets;
+        let directories = self.directories.clone();
+
+        let result = tokio::task::spawn_blocking(move || {
+            let result = crate::syncing::copy_instance(&root_path, &temp_dir, sync_targets, &directories)
+                .and_then(|_| std::fs::rename(&temp_dir, &new_instance_dir));
+            if result.is_err() {
+                _ = std::fs::remove_dir_all(&temp_dir);
+            }
+            result
+        }).await.unwrap();
+
+        if let Err(err) = result {
+            self.send.send_error(format!("Unable to duplicate instance: {}", err));
+        }
+    }
+
+    /// Applies the global instance overrides to the settings that the instance doesn't set itself
+    pub fn apply_global_overrides(&self, configuration: &mut InstanceConfiguration) {
+        let backend_config = self.config.write().get().clone();
+        if backend_config.global_memory_enabled && configuration.memory.is_none() {
+            configuration.memory = backend_config.global_memory;
+        }
+        let global_jvm_flags = backend_config.global_jvm_flags.as_ref().filter(|_| backend_config.global_jvm_flags_enabled);
+        configuration.jvm_flags = InstanceJvmFlagsConfiguration::merge(global_jvm_flags, configuration.jvm_flags.as_ref());
+        if backend_config.global_jvm_binary_enabled && configuration.jvm_binary.is_none() {
+            configuration.jvm_binary = backend_config.global_jvm_binary;
+        }
+    }
+
+    pub async fn get_login_info(&self, modal_action: &ModalAction, account_override: Option<Uuid>) -> Option<MinecraftLoginInfo> {
+        let offline_fallback = self.config.write().get().offline_fallback;
+
+        let (selected_account, selected_username) = {
             let mut account_info = self.account_info.write();
             let account_info = account_info.get();
 
-            let mut selected_account = account_info.selected_account;
+            let mut selected_account = account_override
+                .filter(|uuid| account_info.accounts.contains_key(uuid))
+                .or(account_info.selected_account);
+            let mut selected_username = None;
 
             if let Some(uuid) = selected_account {
                 if let Some(account) = account_info.accounts.get(&uuid) {
@@ -1108,14 +1500,38 @@ impl BackendState {
                             access_token: None
                         })
                     }
+                    selected_username = Some(account.username.clone());
                 } else {
                     selected_account = None;
                 }
             }
 
-            selected_account
+            (selected_account, selected_username)
         };
 
+        if crate::offline::is_offline()
+            && let (Some(uuid), Some(username)) = (selected_account, selected_username.clone())
+        {
+            self.send.send_info("Launching without signing in because you're offline");
+            return Some(MinecraftLoginInfo {
+                uuid,
+                username,
+                access_token: None,
+            });
+        }
+
+        if offline_fallback
+            && let (Some(uuid), Some(username)) = (selected_account, selected_username)
+            && !self.is_auth_reachable().await
+        {
+            self.send.send_warning("Unable to reach Microsoft servers, launching in offline mode");
+            return Some(MinecraftLoginInfo {
+                uuid,
+                username,
+                access_token: None,
+            });
+        }
+
         let Some((profile, access_token)) = self.login_flow(modal_action, selected_account).await else {
             return None;
         };
@@ -1126,24 +1542,33 @@ impl BackendState {
             access_token: Some(access_token),
         })
     }
+
+    async fn is_auth_reachable(&self) -> bool {
+        let request = self.http_client.head("https://api.minecraftservices.com")
+            .timeout(Duration::from_secs(5));
+        match request.send().await {
+            Ok(_) => true,
+            Err(error) => !(error.is_connect() || error.is_timeout()),
+        }
+    }
 }
 
 impl BackendStateFileWatching {
     pub fn watch_filesystem(&mut self, path: Arc<Path>, target: WatchTarget) {
         let Ok(canonical) = path.canonicalize() else {
-            log::error!("Unable to watch {:?} because it could not be canonicalized", path);
+            tracing::error!("Unable to watch {:?} because it could not be canonicalized", path);
             return;
         };
         let canonical: Arc<Path> = if canonical == &*path {
-            log::debug!("Watching {:?} as {:?}", path, target);
+            tracing::debug!("Watching {:?} as {:?}", path, target);
             path.clone()
         } else {
-            log::debug!("Watching {:?} (real path {:?}) as {:?}", path, canonical, target);
+            tracing::debug!("Watching {:?} (real path {:?}) as {:?}", path, canonical, target);
             canonical.into()
         };
 
         if let Err(err) = self.watcher.watch(&path, notify::RecursiveMode::NonRecursive) {
-            log::error!("Unable to watch filesystem: {:?}", err);
+            tracing::error!("Unable to watch filesystem: {:?}", err);
             return;
         }
         self.watching.insert(path.clone(), target);
@@ -45,7 +45,7 @@ impl BackendState {
                         continue;
                     };
 
-                    log::trace!("Filesystem event: {:?}", next_event);
+                    tracing::trace!("Filesystem event: {:?}", next_event);
 
                     if let Some(last_event) = last_event.take() {
                         let last_path = last_event.change_or_remove_path();
@@ -65,7 +65,7 @@ impl BackendState {
                 }
             },
             Err(_) => {
-                log::error!("An error occurred while watching the filesystem! The launcher might be out-of-sync with your files!");
+                tracing::error!("An error occurred while watching the filesystem! The launcher might be out-of-sync with your files!");
                 self.send.send_error("An error occurred while watching the filesystem! The launcher might be out-of-sync with your files!");
             },
         }
@@ -246,6 +246,7 @@ impl BackendState {
                 }
                 true
             },
+            WatchTarget::InstanceScreenshotsDir { .. } => true,
         }
     }
 
@@ -418,6 +419,9 @@ impl BackendState {
                     }
                 }
             },
+            WatchTarget::InstanceScreenshotsDir { id } => {
+                self.on_screenshot_changed(id, path);
+            },
         }
     }
 
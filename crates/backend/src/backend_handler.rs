@@ -1,23 +1,37 @@
-use std::{io::{BufRead, Read, Seek, SeekFrom, Write}, path::Path, sync::{atomic::Ordering, Arc}, time::{Duration, SystemTime}};
+use std::{io::{BufRead, Read, Seek, SeekFrom, Write}, path::{Path, PathBuf}, sync::{atomic::Ordering, Arc}, time::{Duration, SystemTime}};
 
 use auth::{credentials::AccountCredentials, models::{MinecraftAccessToken, MinecraftProfileResponse}, secret::PlatformSecretStorage};
 use bridge::{
-    install::{ContentDownload, ContentInstall, ContentInstallFile, InstallTarget}, instance::{InstanceStatus, ContentType, ContentSummary}, message::{LogFiles, MessageToBackend, MessageToFrontend}, meta::MetadataResult, modal_action::{ModalAction, ModalActionVisitUrl, ProgressTracker, ProgressTrackerFinishType}, serial::AtomicOptionSerial
+    install::{ContentDownload, ContentInstall, ContentInstallFile, InstallTarget}, instance::{ContentType, ContentSummary, InstanceContentID, InstanceID}, message::{LaunchMods, LogFiles, MessageToBackend, MessageToFrontend}, meta::MetadataResult, modal_action::{ModalAction, ModalActionVisitUrl, ProgressTracker, ProgressTrackerFinishType}, serial::AtomicOptionSerial
 };
 use futures::TryFutureExt;
 use rustc_hash::{FxHashMap, FxHashSet};
-use schema::{auxiliary::AuxiliaryContentMeta, content::ContentSource, modrinth::ModrinthLoader, version::{LaunchArgument, LaunchArgumentValue}};
+use schema::{auxiliary::AuxiliaryContentMeta, content::ContentSource, instance::{ConfigSnapshotReason, InstanceIcon, InstanceMemoryConfiguration}, modrinth::ModrinthLoader};
 use serde::Deserialize;
 use strum::IntoEnumIterator;
 use tokio::{io::AsyncBufReadExt, sync::Semaphore};
 use ustr::Ustr;
 
 use crate::{
-    BackendState, LoginError, account::{BackendAccount, MinecraftLoginInfo}, arcfactory::ArcStrFactory, instance::ContentFolder, launch::{ArgumentExpansionKey, LaunchError}, log_reader, metadata::{items::{AssetsIndexMetadataItem, FabricLoaderManifestMetadataItem, ForgeInstallerMavenMetadataItem, MinecraftVersionManifestMetadataItem, MinecraftVersionMetadataItem, ModrinthProjectVersionsMetadataItem, ModrinthSearchMetadataItem, ModrinthV3VersionUpdateMetadataItem, ModrinthVersionUpdateMetadataItem, MojangJavaRuntimeComponentMetadataItem, MojangJavaRuntimesMetadataItem, NeoforgeInstallerMavenMetadataItem, VersionUpdateParameters, VersionV3LoaderFields, VersionV3UpdateParameters}, manager::MetaLoadError}, mod_metadata::ModUpdateAction
+    BackendState, LoginError, account::{self, BackendAccount, MinecraftLoginInfo}, arcfactory::ArcStrFactory, content_sources::ContentSourceError, instance::ContentFolder, lan::LanWatch, launch::LaunchError, log_reader, verification_report::VerificationReport, playtime::PlaytimeLimits, startup_watch::{self, StartupWatch}, metadata::{items::{FabricLoaderManifestMetadataItem, ForgeInstallerMavenMetadataItem, MinecraftVersionManifestMetadataItem, ModrinthProjectVersionsMetadataItem, ModrinthSearchMetadataItem, NeoforgeInstallerMavenMetadataItem}}, mod_metadata::ModUpdateAction, process::GameProcess
 };
 
 impl BackendState {
     pub async fn handle_message(&self, message: MessageToBackend) {
+        if crate::data_lock::is_read_only() && message.modifies_data() {
+            self.send.send_warning(rust_i18n::t!("notification_read_only"));
+            return;
+        }
+        if message.requires_unlock() && !self.launcher_unlocked.load(Ordering::Relaxed) && self.config.write().get().launcher_lock.is_some() {
+            let locked: Arc<str> = rust_i18n::t!("notification_launcher_locked").into();
+            self.send.send_warning(locked.clone());
+            if let Some(modal_action) = message.modal_action() {
+                modal_action.set_error_message(locked);
+                modal_action.set_finished();
+            }
+            return;
+        }
+
         match message {
             MessageToBackend::RequestMetadata { request, force_reload } => {
                 let meta = self.meta.clone();
@@ -60,6 +74,22 @@ impl BackendState {
             MessageToBackend::RequestLoadWorlds { id } => {
                 tokio::task::spawn(self.clone().load_instance_worlds(id));
             },
+            MessageToBackend::BackupWorld { id, level_path, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.backup_world(id, &level_path, &modal_action).await;
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::DeleteWorld { id, level_path } => {
+                self.delete_world(id, &level_path).await;
+            },
+            MessageToBackend::GetWorldPreview { id, level_path, channel } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    _ = channel.send(backend.world_preview(id, &level_path).await);
+                });
+            },
             MessageToBackend::RequestLoadServers { id } => {
                 tokio::task::spawn(self.clone().load_instance_servers(id));
             },
@@ -69,28 +99,175 @@ impl BackendState {
             MessageToBackend::RequestLoadResourcePacks { id } => {
                 tokio::task::spawn(self.clone().load_instance_content(id, ContentFolder::ResourcePacks));
             },
-            MessageToBackend::CreateInstance { name, version, loader } => {
-                self.create_instance(&name, &version, loader).await;
+            MessageToBackend::CreateInstance { name, version, loader, modal_action } => {
+                match self.try_create_instance(&name, &version, loader).await {
+                    Ok(_) => self.prefetch_loader_libraries(&version, loader),
+                    Err(error) => {
+                        self.send.send_warning(error.clone());
+                        modal_action.set_error_message(error.into());
+                    },
+                }
+                modal_action.set_finished();
             },
             MessageToBackend::DeleteInstance { id } => {
-                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
-                    let result = std::fs::remove_dir_all(&instance.root_path);
-                    if let Err(err) = result {
-                        self.send.send_error(format!("Unable to delete instance folder: {}", err));
-                    }
-                }
+                self.delete_instances(&[id]).await;
+            },
+            MessageToBackend::DeleteInstances { ids } => {
+                self.delete_instances(&ids).await;
+            },
+            MessageToBackend::PrepareInstances { ids, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.prepare_instances(&ids, &modal_action).await;
+                    modal_action.set_finished();
+                });
             },
             MessageToBackend::RenameInstance { id, name } => {
                 self.rename_instance(id, &name).await;
             },
+            MessageToBackend::DuplicateInstance { id } => {
+                self.duplicate_instance(id).await;
+            },
+            MessageToBackend::CloneInstanceToVersion { id, name, minecraft_version, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.clone_instance_to_version(id, &name, minecraft_version, &modal_action).await;
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::ImportModpack { path, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.import_mrpack(path, &modal_action).await;
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::ImportMultiMcInstances { path, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.import_multimc_instances(path, &modal_action).await;
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::GetOfficialLauncherProfiles { minecraft_dir, channel } => {
+                tokio::task::spawn_blocking(move || {
+                    _ = channel.send(crate::launcher_import::list_official_launcher_profiles(minecraft_dir).map_err(Arc::from));
+                });
+            },
+            MessageToBackend::ImportOfficialLauncherProfiles { minecraft_dir, profile_ids, reuse_game_dir, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.import_official_launcher_profiles(minecraft_dir, profile_ids, reuse_game_dir, &modal_action).await;
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::SeedFromOfficialLauncher { minecraft_dir, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.seed_from_official_launcher(minecraft_dir, &modal_action).await;
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::ExportModpack { id, path, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.export_mrpack(id, path, &modal_action).await;
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::ExportInstance { id, path, folders, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.export_instance(id, path, folders, &modal_action).await;
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::GetModList { id, format, channel } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    _ = channel.send(backend.mod_list(id, format).await);
+                });
+            },
+            MessageToBackend::ExportModList { id, path, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.export_mod_list(id, path, &modal_action).await;
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::CreateDiagnosticsBundle { path, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.create_diagnostics_bundle(path, &modal_action).await;
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::ShareInstanceOnLan { id, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.share_instance_on_lan(id, &modal_action).await;
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::DiscoverLanShares { channel } => {
+                tokio::task::spawn(async move {
+                    let result = crate::lan_share::discover().await
+                        .map_err(|error| Arc::from(format!("Unable to search the local network: {error}")));
+                    _ = channel.send(result);
+                });
+            },
+            MessageToBackend::ImportLanShare { share, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.import_lan_share(share, &modal_action).await;
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::CreateSpeedrunInstance { name, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.create_speedrun_instance(name, modal_action.clone()).await;
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::ResolveSyncMembership { id, targets, adopt } => {
+                let Some((root_path, dot_minecraft_path)) = self.instance_state.read().instances.get(id)
+                    .map(|instance| (instance.root_path.clone(), instance.dot_minecraft_path.clone())) else {
+                    return;
+                };
+
+                if adopt {
+                    if let Err(err) = crate::syncing::adopt_membership(targets, &root_path, &dot_minecraft_path, &self.directories) {
+                        self.send.send_error(format!("Unable to move instance data into the synced folders: {}", err));
+                    }
+                } else {
+                    _ = std::fs::remove_file(root_path.join(crate::syncing::SYNC_MEMBERSHIP_FILENAME));
+                }
+            },
             MessageToBackend::SetInstanceMinecraftVersion { id, version } => {
-                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                if self.reject_if_locked(id) {
+                    return;
+                }
+                let previous = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    let previous = instance.configuration.get().minecraft_version;
                     instance.configuration.modify(|configuration| {
                         configuration.minecraft_version = version;
                     });
+                    Some((previous, instance.dot_minecraft_path.clone()))
+                } else {
+                    None
+                };
+                if let Some((previous, dot_minecraft)) = previous {
+                    let backend = self.clone();
+                    tokio::task::spawn(async move {
+                        backend.migrate_options_txt(&dot_minecraft, &previous, &version).await;
+                    });
                 }
             },
             MessageToBackend::SetInstanceLoader { id, loader } => {
+                if self.reject_if_locked(id) {
+                    return;
+                }
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     instance.configuration.modify(|configuration| {
                         configuration.loader = loader;
@@ -99,6 +276,9 @@ impl BackendState {
                 }
             },
             MessageToBackend::SetInstancePreferredLoaderVersion { id, loader_version } => {
+                if self.reject_if_locked(id) {
+                    return;
+                }
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     instance.configuration.modify(|configuration| {
                         configuration.preferred_loader_version = loader_version.map(Ustr::from);
@@ -112,6 +292,16 @@ impl BackendState {
                     });
                 }
             },
+            MessageToBackend::SetInstancesMemory { ids, memory } => {
+                let mut instance_state = self.instance_state.write();
+                for id in ids {
+                    if let Some(instance) = instance_state.instances.get_mut(id) {
+                        instance.configuration.modify(|configuration| {
+                            configuration.memory = Some(memory);
+                        });
+                    }
+                }
+            },
             MessageToBackend::SetInstanceJvmFlags { id, jvm_flags } => {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     instance.configuration.modify(|configuration| {
@@ -119,13 +309,45 @@ impl BackendState {
                     });
                 }
             },
-            MessageToBackend::SetInstanceJvmBinary { id, jvm_binary } => {
+            MessageToBackend::SetInstanceBranding { id, branding } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.branding = Some(branding);
+                    });
+                }
+            },
+            MessageToBackend::SetInstanceEnvironment { id, environment } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.environment = Some(environment);
+                    });
+                }
+            },
+            MessageToBackend::SetInstanceJvmBinary { id, mut jvm_binary } => {
+                if let Some(path) = jvm_binary.path.clone() {
+                    jvm_binary.major_version = tokio::task::spawn_blocking(move || {
+                        let binary = crate::launch::Launcher::search_for_java_binary(&path)?;
+                        crate::launch::detect_java_major_version(&binary)
+                    }).await.ok().flatten();
+
+                    if jvm_binary.enabled && jvm_binary.major_version.is_none() {
+                        self.send.send_error("The selected Java binary couldn't be run, check that it is a valid Java installation");
+                    }
+                }
+
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     instance.configuration.modify(|configuration| {
                         configuration.jvm_binary = Some(jvm_binary);
                     });
                 }
             },
+            MessageToBackend::SetInstanceAccount { id, account } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.account = account;
+                    });
+                }
+            },
             MessageToBackend::SetInstanceLinuxWrapper { id, linux_wrapper } => {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     instance.configuration.modify(|configuration| {
@@ -133,14 +355,161 @@ impl BackendState {
                     });
                 }
             },
+            MessageToBackend::PrewarmInstance { id } => {
+                self.prewarm_instance(id);
+            },
+            MessageToBackend::SetInstanceLocked { id, locked } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.locked = locked;
+                    });
+                }
+            },
+            MessageToBackend::SetInstanceCaptureLogEvents { id, capture } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.capture_log_events = capture;
+                    });
+                }
+            },
+            MessageToBackend::SetInstancePackSource { id, source } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.pack_source = source.map(Arc::from);
+                    });
+                }
+                self.update_pack_watch(id);
+            },
+            MessageToBackend::SetInstanceIcon { id, icon } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.icon = icon;
+                    });
+                }
+            },
+            MessageToBackend::SetInstanceIconImage { id, path } => {
+                let icons_dir = self.directories.instance_icons_dir.clone();
+                let result = tokio::task::spawn_blocking(move || crate::instance_icons::import(&icons_dir, &path)).await.unwrap();
+                match result {
+                    Ok(icon) => {
+                        if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                            instance.configuration.modify(|configuration| {
+                                configuration.icon = Some(InstanceIcon::Image(icon));
+                            });
+                        }
+                    },
+                    Err(error) => self.send.send_error(format!("Unable to use image as icon: {error}")),
+                }
+            },
+            MessageToBackend::SetInstanceColorTag { id, color_tag } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.color_tag = color_tag;
+                    });
+                }
+            },
+            MessageToBackend::SetInstancesColorTag { ids, color_tag } => {
+                let mut instance_state = self.instance_state.write();
+                for id in ids {
+                    if let Some(instance) = instance_state.instances.get_mut(id) {
+                        instance.configuration.modify(|configuration| {
+                            configuration.color_tag = color_tag;
+                        });
+                    }
+                }
+            },
+            MessageToBackend::GetInstanceTimeline { id, channel } => {
+                let root_path = self.instance_state.read().instances.get(id).map(|instance| instance.root_path.clone());
+                if let Some(root_path) = root_path {
+                    tokio::task::spawn_blocking(move || {
+                        _ = channel.send(crate::timeline::load(&root_path));
+                    });
+                }
+            },
+            MessageToBackend::GetConfigSnapshots { id, channel } => {
+                let root_path = self.instance_state.read().instances.get(id).map(|instance| instance.root_path.clone());
+                if let Some(root_path) = root_path {
+                    tokio::task::spawn_blocking(move || {
+                        _ = channel.send(crate::config_snapshots::load(&root_path).into());
+                    });
+                }
+            },
+            MessageToBackend::GetConfigSnapshotDiff { id, time, channel } => {
+                _ = channel.send(self.config_snapshot_diff(id, time).await);
+            },
+            MessageToBackend::TakeConfigSnapshot { id, channel } => {
+                self.snapshot_configs(id, ConfigSnapshotReason::Manual).await;
+                _ = channel.send(());
+            },
+            MessageToBackend::RestoreConfigSnapshot { id, time, channel } => {
+                self.restore_config_snapshot(id, time).await;
+                _ = channel.send(());
+            },
+            MessageToBackend::SetInstancePlaytimeLimit { id, daily_limit } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.daily_playtime_limit = daily_limit;
+                    });
+                }
+            },
+            MessageToBackend::GetPlaytimeToday { id, channel } => {
+                let key = self.instance_state.write().instances.get_mut(id).map(|instance| instance.playtime_key());
+                let seconds = key.map(|key| self.playtime.write().played_seconds(&key)).unwrap_or(0);
+                _ = channel.send(seconds);
+            },
+            MessageToBackend::GetLaunchPreflight { id, channel } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    _ = channel.send(backend.launch_preflight(id).await);
+                });
+            },
+            MessageToBackend::GetModBisection { id, channel } => {
+                let root_path = self.instance_state.read().instances.get(id).map(|instance| instance.root_path.clone());
+                _ = channel.send(root_path.and_then(|root_path| crate::mod_bisection::load(&root_path)).map(|bisection| bisection.to_state()));
+            },
+            MessageToBackend::StartModBisection { id, channel } => {
+                _ = channel.send(self.start_mod_bisection(id).await.map_err(Arc::from));
+            },
+            MessageToBackend::ReportModBisectionResult { id, crashed, channel } => {
+                let root_path = self.instance_state.read().instances.get(id).map(|instance| instance.root_path.clone());
+                let state = root_path.and_then(|root_path| {
+                    let mut bisection = crate::mod_bisection::load(&root_path)?;
+                    bisection.report(crashed);
+                    if let Err(error) = crate::mod_bisection::save(&root_path, &bisection) {
+                        self.send.send_error(format!("Unable to save mod bisection: {error}"));
+                    }
+                    Some(bisection.to_state())
+                });
+                _ = channel.send(state);
+            },
+            MessageToBackend::StopModBisection { id } => {
+                if let Some(instance) = self.instance_state.read().instances.get(id) {
+                    crate::mod_bisection::clear(&instance.root_path);
+                }
+            },
+            MessageToBackend::GetLaunchProgress { id, channel } => {
+                let launching = self.instance_state.read().instances.get(id).and_then(|instance| instance.launching.clone());
+                _ = channel.send(launching);
+            },
+            MessageToBackend::SetLanPortForwarding { id, enabled } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.set_lan_port_forwarding(id, enabled).await;
+                });
+            },
             MessageToBackend::KillInstance { id } => {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
-                    if let Some(mut child) = instance.child.take() {
-                        let result = child.kill();
+                    if let Some(mut process) = instance.process.take() {
+                        if let Some(port_forward) = process.port_forward.take() {
+                            self.remove_port_forward(port_forward);
+                        }
+                        let result = process.kill();
+                        instance.sync_playtime(&mut self.playtime.write());
                         if result.is_err() {
                             self.send.send_error("Failed to kill instance");
-                            log::error!("Failed to kill instance: {:?}", result.unwrap_err());
+                            tracing::error!("Failed to kill instance: {:?}", result.unwrap_err());
                         }
+                        self.restore_safe_mode_mods(&instance.content_state[ContentFolder::Mods].path);
 
                         self.send.send(instance.create_modify_message());
                     } else {
@@ -154,14 +523,51 @@ impl BackendState {
             MessageToBackend::StartInstance {
                 id,
                 quick_play,
+                mods,
                 modal_action,
             } => {
-                let Some(login_info) = self.get_login_info(&modal_action).await else {
+                if self.is_over_playtime_limit(id) {
+                    self.send.send(MessageToFrontend::CloseModal);
+                    if let Some(instance) = self.instance_state.read().instances.get(id) {
+                        self.send.send(MessageToFrontend::PlaytimeLimitReached {
+                            id,
+                            name: instance.name,
+                            can_override: self.config.write().get().launcher_lock.is_some(),
+                        });
+                    }
+                    return;
+                }
+
+                let configuration = self.instance_state.write().instances.get_mut(id).map(|instance| instance.configuration.get().clone());
+                if let Some(mut configuration) = configuration {
+                    self.apply_global_overrides(&mut configuration);
+                    if let Err(error) = self.check_launch_limits(&configuration) {
+                        modal_action.set_error_message(format!("Can't launch instance: {error}").into());
+                        modal_action.set_finished();
+                        return;
+                    }
+                }
+
+                let account_override = self.instance_state.write().instances.get_mut(id)
+                    .and_then(|instance| instance.configuration.get().account);
+                let Some(login_info) = self.get_login_info(&modal_action, account_override).await else {
                     return;
                 };
 
+                self.snapshot_configs(id, ConfigSnapshotReason::Launch).await;
+
+                // Mods from modpacks are added by prelaunch, so it's skipped when only some mods are loaded
+                let prelaunch = async {
+                    match mods {
+                        LaunchMods::All => self.prelaunch(id, &modal_action).await,
+                        LaunchMods::SafeMode | LaunchMods::Bisection => {
+                            self.prelaunch_apply_syncing(id);
+                            Vec::new()
+                        },
+                    }
+                };
                 let add_mods = tokio::select! {
-                    add_mods = self.prelaunch(id, &modal_action) => add_mods,
+                    add_mods = prelaunch => add_mods,
                     _ = modal_action.request_cancel.cancelled() => {
                         self.send.send(MessageToFrontend::CloseModal);
                         return;
@@ -174,20 +580,36 @@ impl BackendState {
                     return;
                 }
 
-                let (dot_minecraft, mut configuration) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
-                    if instance.child.is_some() {
+                let mut instance_lock = None;
+                let (name, root_path, dot_minecraft, mods_dir, mut configuration) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    if instance.process.is_some() {
                         self.send.send_warning("Can't launch instance, already running");
                         modal_action.set_error_message("Can't launch instance, already running".into());
                         modal_action.set_finished();
                         return;
                     }
 
+                    match crate::data_lock::lock_instance(&instance.root_path) {
+                        Ok(Some(lock)) => instance_lock = Some(lock),
+                        Ok(None) => {
+                            let message = format!("Can't launch {}, it's already running from another launcher", instance.name);
+                            self.send.send_warning(message.clone());
+                            modal_action.set_error_message(message.into());
+                            modal_action.set_finished();
+                            return;
+                        },
+                        // Launching isn't refused when the lock file can't be created, e.g. on a read-only share
+                        Err(error) => tracing::warn!("Unable to lock instance {}: {error}", instance.name),
+                    }
+
                     self.send.send(MessageToFrontend::MoveInstanceToTop {
                         id
                     });
-                    self.send.send(instance.create_modify_message_with_status(InstanceStatus::Launching));
+                    instance.launching = Some(modal_action.clone());
+                    self.send.send(instance.create_modify_message());
 
-                    (instance.dot_minecraft_path.clone(), instance.configuration.get().clone())
+                    (instance.name, instance.root_path.clone(), instance.dot_minecraft_path.clone(),
+                        instance.content_state[ContentFolder::Mods].path.clone(), instance.configuration.get().clone())
                 } else {
                     self.send.send_error("Can't launch instance, unknown id");
                     modal_action.set_error_message("Can't launch instance, unknown id".into());
@@ -195,37 +617,72 @@ impl BackendState {
                     return;
                 };
 
-                // Apply global instance overrides (only if instance doesn't have own setting)
-                let backend_config = self.config.write().get().clone();
-                if backend_config.global_memory_enabled && configuration.memory.is_none() {
-                    configuration.memory = backend_config.global_memory;
-                }
-                if backend_config.global_jvm_flags_enabled && configuration.jvm_flags.is_none() {
-                    configuration.jvm_flags = backend_config.global_jvm_flags;
+                self.apply_global_overrides(&mut configuration);
+                let max_heap_mib = crate::launch_limits::effective_heap_mib(crate::launch_limits::max_heap_mib(&configuration),
+                    crate::system_info::total_memory_mib());
+
+                let keep_mods = match mods {
+                    LaunchMods::All => Ok(None),
+                    LaunchMods::SafeMode => Ok(Some(Vec::new())),
+                    LaunchMods::Bisection => crate::mod_bisection::load(&root_path)
+                        .map(|bisection| Some(bisection.testing))
+                        .ok_or_else(|| "No mod bisection is in progress for this instance".to_string()),
+                };
+                let moved_aside = keep_mods.and_then(|keep_mods| match keep_mods {
+                    Some(keep_mods) => crate::safe_mode::move_mods_aside(&mods_dir, &keep_mods)
+                        .map_err(|error| format!("Unable to move mods aside: {error}")),
+                    None => Ok(()),
+                });
+                if let Err(error) = moved_aside {
+                    tracing::error!("{error}");
+                    modal_action.set_error_message(error.into());
+                    modal_action.set_finished();
+                    if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                        instance.launching = None;
+                        self.send.send(instance.create_modify_message());
+                    }
+                    return;
                 }
-                if backend_config.global_jvm_binary_enabled && configuration.jvm_binary.is_none() {
-                    configuration.jvm_binary = backend_config.global_jvm_binary;
+                if mods == LaunchMods::SafeMode {
+                    self.send.send_info(format!("Launching {name} in safe mode, mods are restored once the game exits"));
                 }
 
-                let launch_tracker = ProgressTracker::new(Arc::from("Launching"), self.send.clone());
+                let launch_tracker = ProgressTracker::new(rust_i18n::t!("progress_launching").into(), self.send.clone());
                 modal_action.trackers.push(launch_tracker.clone());
 
-                let result = self.launcher.launch(&self.redirecting_http_client, dot_minecraft, configuration, quick_play, login_info, add_mods, &launch_tracker, &modal_action).await;
+                let only_selected_language = self.config.write().get().download_only_selected_language;
+                let instance_folder = root_path.file_name().map(|folder| folder.to_string_lossy());
+                let result = self.launcher.launch(&self.redirecting_http_client, dot_minecraft, instance_folder.as_deref(), configuration, quick_play, login_info, add_mods,
+                    only_selected_language, false, &VerificationReport::default(), &launch_tracker, &modal_action).await;
+
+                if result.is_err() {
+                    self.restore_safe_mode_mods(&mods_dir);
+                }
 
                 if matches!(result, Err(LaunchError::CancelledByUser)) {
                     self.send.send(MessageToFrontend::CloseModal);
                     if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                        instance.launching = None;
                         self.send.send(instance.create_modify_message());
                     }
                     return;
                 }
 
-                let is_err = result.is_err();
                 match result {
                     Ok(mut child) => {
+                        let startup_watch = Arc::new(StartupWatch::default());
+                        let lan_watch = Arc::new(LanWatch::default());
                         if !self.config.write().get().dont_open_game_output_when_launching {
                             if let Some(stdout) = child.stdout.take() {
-                                log_reader::start_game_output(stdout, child.stderr.take(), self.send.clone());
+                                log_reader::start_game_output(stdout, child.stderr.take(), self.send.clone(), startup_watch.clone(), lan_watch.clone());
+                            }
+                        } else {
+                            // Stdout is read until the game exits to notice it being opened to LAN
+                            if let Some(stdout) = child.stdout.take() {
+                                crate::lan::watch_output(stdout, startup_watch.clone(), lan_watch.clone());
+                            }
+                            if let Some(stderr) = child.stderr.take() {
+                                startup_watch::watch_until_started(stderr, startup_watch.clone());
                             }
                         }
 
@@ -234,28 +691,65 @@ impl BackendState {
                         child.stdin.take();
                         child.stdout.take();
 
+                        let pid = child.id();
+                        let kill_on_launcher_exit = self.config.write().get().kill_games_on_launcher_exit;
                         if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
-                            instance.child = Some(child);
+                            let mut process = GameProcess::new(child, kill_on_launcher_exit, lan_watch, max_heap_mib);
+                            process.instance_lock = instance_lock;
+                            instance.process = Some(process);
+                            instance.crashed = false;
+                            instance.configuration.modify(|configuration| {
+                                configuration.last_played = Some(chrono::Utc::now().timestamp_millis());
+                            });
+                            instance.launched_mods = mods;
                         }
+                        self.watch_screenshots(id);
+
+                        launch_tracker.set_title(rust_i18n::t!("progress_waiting_for_game").into());
+                        launch_tracker.notify();
+
+                        // Waiting happens in a separate task so the backend can keep handling messages
+                        tokio::task::spawn(startup_watch::wait_for_startup(
+                            self.instance_state.clone(),
+                            self.send.clone(),
+                            id,
+                            pid,
+                            startup_watch,
+                            launch_tracker,
+                            modal_action,
+                        ));
                     },
                     Err(ref err) => {
-                        log::error!("Failed to launch due to error: {:?}", &err);
-                        modal_action.set_error_message(format!("{}", &err).into());
-                    },
-                }
+                        tracing::error!("Failed to launch due to error: {:?}", &err);
+                        tokio::task::spawn_blocking(|| {
+                            tracing::info!("System info at launch failure:\n{}", crate::system_info::describe(&crate::system_info::query_gpus()));
+                        });
+                        let error: Arc<str> = format!("{}", &err).into();
+                        modal_action.set_error_message(error.clone());
+                        self.send.send(MessageToFrontend::LaunchFailed {
+                            id,
+                            name,
+                            error,
+                        });
 
-                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
-                    self.send.send(instance.create_modify_message());
-                }
+                        if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                            instance.launching = None;
+                            self.send.send(instance.create_modify_message());
+                        }
 
-                launch_tracker.set_finished(if is_err { ProgressTrackerFinishType::Error } else { ProgressTrackerFinishType::Normal });
-                launch_tracker.notify();
-                modal_action.set_finished();
+                        launch_tracker.set_finished(ProgressTrackerFinishType::Error);
+                        launch_tracker.notify();
+                        modal_action.set_finished();
+                    },
+                }
 
                 return;
 
             },
             MessageToBackend::SetContentEnabled { id, content_ids: mod_ids, enabled } => {
+                if self.reject_if_locked(id) {
+                    return;
+                }
                 let mut instance_state = self.instance_state.write();
                 let Some(instance) = instance_state.instances.get_mut(id) else {
                     return;
@@ -284,6 +778,9 @@ impl BackendState {
                 instance_state.reload_immediately.extend(reload);
             },
             MessageToBackend::SetContentChildEnabled { id, content_id: mod_id, child_id, child_name, child_filename, enabled } => {
+                if self.reject_if_locked(id) {
+                    return;
+                }
                 let mut instance_state = self.instance_state.write();
                 if let Some(instance) = instance_state.instances.get_mut(id)
                     && let Some((instance_mod, folder)) = instance.try_get_content(mod_id)
@@ -318,321 +815,103 @@ impl BackendState {
                         let bytes = match serde_json::to_vec(&aux) {
                             Ok(bytes) => bytes,
                             Err(err) => {
-                                log::error!("Unable to serialize AuxiliaryContentMeta: {err:?}");
+                                tracing::error!("Unable to serialize AuxiliaryContentMeta: {err:?}");
                                 self.send.send_error("Unable to serialize AuxiliaryContentMeta");
                                 return;
                             },
                         };
                         if let Err(err) = crate::write_safe(&aux_path, &bytes) {
-                            log::error!("Unable to save aux meta: {err:?}");
+                            tracing::error!("Unable to save aux meta: {err:?}");
                             self.send.send_error("Unable to save aux meta");
                         }
                         instance_state.reload_immediately.insert((id, folder));
                     }
                 }
             },
-            MessageToBackend::DownloadAllMetadata => {
-                self.download_all_metadata().await;
+            MessageToBackend::ValidateAllMetadata { report_path, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.validate_all_metadata(report_path, &modal_action).await;
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::SmokeTest { minecraft_version, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.smoke_test(minecraft_version, &modal_action).await;
+                    modal_action.set_finished();
+                });
             },
             MessageToBackend::InstallContent { content, modal_action } => {
+                if let InstallTarget::Instance(id) = content.target && self.reject_if_locked(id) {
+                    modal_action.set_finished();
+                    return;
+                }
                 self.install_content(content, modal_action.clone()).await;
                 modal_action.set_finished();
                 self.send.send(MessageToFrontend::Refresh);
             },
-            MessageToBackend::DeleteContent { id, content_ids: mod_ids } => {
-                let mut instance_state = self.instance_state.write();
-                let Some(instance) = instance_state.instances.get_mut(id) else {
-                    self.send.send_error("Unable to find instance, unknown id");
-                    return;
-                };
-
-                let mut reload = FxHashSet::default();
-
-                for mod_id in mod_ids {
-                    let Some((instance_mod, folder)) = instance.try_get_content(mod_id) else {
-                        self.send.send_error("Unable to delete mod, invalid id");
-                        return;
-                    };
-
-                    _ = std::fs::remove_file(&instance_mod.path);
-
-                    if let Some(aux_path) = crate::pandora_aux_path_for_content(&instance_mod) {
-                        _ = std::fs::remove_file(aux_path);
-                    }
-
-                    reload.insert((id, folder));
-                }
-
-                instance_state.reload_immediately.extend(reload);
-            },
-            MessageToBackend::UpdateCheck { instance: id, modal_action } => {
-                let (loader, version) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
-                    let configuration = instance.configuration.get();
-                    (configuration.loader, configuration.minecraft_version)
-                } else {
-                    self.send.send_error("Can't update instance, unknown id");
-                    modal_action.set_error_message("Can't update instance, unknown id".into());
-                    modal_action.set_finished();
-                    return;
-                };
-
-                let mut content = Vec::new();
-                for folder in ContentFolder::iter() {
-                    let Some(summaries) = self.clone().load_instance_content(id, folder).await else {
-                        modal_action.set_finished();
-                        return;
-                    };
-                    content.extend_from_slice(&*summaries);
-                }
-
-                let modrinth_loader = loader.as_modrinth_loader();
-                if modrinth_loader == ModrinthLoader::Unknown {
-                    modal_action.set_error_message("Unable to update instance, unsupported loader".into());
-                    modal_action.set_finished();
-                    return;
-                }
-
-                let tracker = ProgressTracker::new("Checking content".into(), self.send.clone());
-                tracker.set_total(content.len());
-                modal_action.trackers.push(tracker.clone());
-
-                let semaphore = Semaphore::new(8);
-
-                let mod_params = &VersionUpdateParameters {
-                    loaders: [modrinth_loader].into(),
-                    game_versions: [version].into(),
-                };
-
-                let fabric_mod_params = &VersionUpdateParameters {
-                    loaders: [ModrinthLoader::Fabric].into(),
-                    game_versions: [version].into(),
-                };
-
-                let forge_mod_params = &VersionUpdateParameters {
-                    loaders: [ModrinthLoader::Forge].into(),
-                    game_versions: [version].into(),
-                };
-
-                let neoforge_mod_params = &VersionUpdateParameters {
-                    loaders: [ModrinthLoader::NeoForge].into(),
-                    game_versions: [version].into(),
-                };
-
-                let resourcepack_params = &VersionUpdateParameters {
-                    loaders: [ModrinthLoader::Minecraft].into(),
-                    game_versions: [version].into(),
-                };
-
-                let modrinth_modpack_params = &VersionV3UpdateParameters {
-                    loaders: ["mrpack".into()].into(),
-                    loader_fields: VersionV3LoaderFields {
-                        mrpack_loaders: [modrinth_loader].into(),
-                        game_versions: [version].into(),
-                    },
-                };
-
-                let meta = self.meta.clone();
-
-                let mut futures = Vec::new();
-
-                struct UpdateResult {
-                    mod_summary: Arc<ContentSummary>,
-                    action: ModUpdateAction,
-                }
-
-                { // Scope is needed so await doesn't complain about the non-send RwLockReadGuard
-                    let sources = self.mod_metadata_manager.read_content_sources();
-                    for summary in content.iter() {
-                        let source = sources.get(&summary.content_summary.hash).unwrap_or(ContentSource::Manual);
-                        let semaphore = &semaphore;
-                        let meta = &meta;
-                        let tracker = &tracker;
-                        futures.push(async move {
-                            match source {
-                                ContentSource::Manual => {
-                                    tracker.add_count(1);
-                                    tracker.notify();
-                                    Ok(ModUpdateAction::ManualInstall)
-                                },
-                                ContentSource::ModrinthUnknown | ContentSource::ModrinthProject { .. } => {
-                                    let permit = semaphore.acquire().await.unwrap();
-                                    let result = match summary.content_summary.extra {
-                                        ContentType::Fabric => {
-                                            meta.fetch(&ModrinthVersionUpdateMetadataItem {
-                                                sha1: hex::encode(summary.content_summary.hash).into(),
-                                                params: fabric_mod_params.clone()
-                                            }).await
-                                        },
-                                        ContentType::Forge => {
-                                            meta.fetch(&ModrinthVersionUpdateMetadataItem {
-                                                sha1: hex::encode(summary.content_summary.hash).into(),
-                                                params: forge_mod_params.clone()
-                                            }).await
-                                        },
-                                        ContentType::NeoForge => {
-                                            meta.fetch(&ModrinthVersionUpdateMetadataItem {
-                                                sha1: hex::encode(summary.content_summary.hash).into(),
-                                                params: neoforge_mod_params.clone()
-                                            }).await
-                                        },
-                                        ContentType::JavaModule => {
-                                            meta.fetch(&ModrinthVersionUpdateMetadataItem {
-                                                sha1: hex::encode(summary.content_summary.hash).into(),
-                                                params: mod_params.clone()
-                                            }).await
-                                        },
-                                        ContentType::ModrinthModpack { .. } => {
-                                            meta.fetch(&ModrinthV3VersionUpdateMetadataItem {
-                                                sha1: hex::encode(summary.content_summary.hash).into(),
-                                                params: modrinth_modpack_params.clone()
-                                            }).await
-                                        },
-                                        ContentType::ResourcePack => {
-                                            meta.fetch(&ModrinthVersionUpdateMetadataItem {
-                                                sha1: hex::encode(summary.content_summary.hash).into(),
-                                                params: resourcepack_params.clone()
-                                            }).await
-                                        },
-                                    };
-                                    drop(permit);
-
-                                    tracker.add_count(1);
-                                    tracker.notify();
-
-                                    if let Err(MetaLoadError::NonOK(404)) = result {
-                                        return Ok(ModUpdateAction::ErrorNotFound);
-                                    }
-
-                                    let result = result?;
-
-                                    if let ContentSource::ModrinthProject { ref project } = source {
-                                        if &result.0.project_id != project {
-                                            log::error!("Refusing to update {:?}, mismatched project ids: expected {}, got {}",
-                                                summary.content_summary.hash, &result.0.project_id, &project);
-                                            return Ok(ModUpdateAction::ErrorNotFound);
-                                        }
-                                    }
-
-                                    let install_file = result
-                                        .0
-                                        .files
-                                        .iter()
-                                        .find(|file| file.primary)
-                                        .unwrap_or(result.0.files.first().unwrap());
-
-                                    let mut latest_hash = [0u8; 20];
-                                    let Ok(_) = hex::decode_to_slice(&*install_file.hashes.sha1, &mut latest_hash) else {
-                                        return Ok(ModUpdateAction::ErrorInvalidHash);
-                                    };
-
-                                    if latest_hash == summary.content_summary.hash {
-                                        Ok(ModUpdateAction::AlreadyUpToDate)
-                                    } else {
-                                        Ok(ModUpdateAction::Modrinth {
-                                            file: install_file.clone(),
-                                            project_id: result.0.project_id.clone(),
-                                        })
-                                    }
-                                },
-                            }
-                        }.map_ok(|action| UpdateResult {
-                            mod_summary: summary.content_summary.clone(),
-                            action,
-                        }));
-                    }
-                }
-
-                let results: Result<Vec<UpdateResult>, MetaLoadError> = futures::future::try_join_all(futures).await;
-
-                match results {
-                    Ok(updates) => {
-                        let mut meta_updates = self.mod_metadata_manager.updates.write();
-
-                        for update in updates {
-                            update.mod_summary.update_status.store(update.action.to_status(), Ordering::Relaxed);
-                            meta_updates.insert(update.mod_summary.hash, update.action);
-                        }
-                    },
+            MessageToBackend::SearchContent { source, query, channel } => {
+                let source = match self.content_source(source) {
+                    Ok(source) => source,
                     Err(error) => {
-                        tracker.set_finished(ProgressTrackerFinishType::Error);
-                        modal_action.set_error_message(format!("Error checking for updates: {}", error).into());
-                        modal_action.set_finished();
+                        _ = channel.send(Err(error.to_string().into()));
                         return;
                     },
-                }
-
-                tracker.set_finished(ProgressTrackerFinishType::Normal);
-                modal_action.set_finished();
+                };
+                tokio::task::spawn(async move {
+                    _ = channel.send(source.search(&query).await.map_err(|error| error.to_string().into()));
+                });
             },
-            MessageToBackend::UpdateContent { instance: id, content_id: mod_id, modal_action } => {
-                let content_install = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
-                    let configuration = instance.configuration.get();
-                    let (loader, minecraft_version) = (configuration.loader, configuration.minecraft_version);
-                    let Some((mod_summary, _)) = instance.try_get_content(mod_id) else {
-                        self.send.send_error("Can't update mod in instance, unknown mod id");
-                        modal_action.set_finished();
-                        return;
-                    };
-
-                    let Some(update_info) = self.mod_metadata_manager.updates.read().get(&mod_summary.content_summary.hash).cloned() else {
-                        self.send.send_error("Can't update mod in instance, missing update action");
-                        modal_action.set_finished();
-                        return;
-                    };
-
-                    match update_info {
-                        ModUpdateAction::ErrorNotFound => {
-                            self.send.send_error("Can't update mod in instance, 404 not found");
-                            modal_action.set_finished();
-                            return;
-                        },
-                        ModUpdateAction::ErrorInvalidHash => {
-                            self.send.send_error("Can't update mod in instance, returned invalid hash");
-                            modal_action.set_finished();
-                            return;
-                        },
-                        ModUpdateAction::AlreadyUpToDate => {
-                            self.send.send_error("Can't update mod in instance, already up-to-date");
-                            modal_action.set_finished();
-                            return;
-                        },
-                        ModUpdateAction::ManualInstall => {
-                            self.send.send_error("Can't update mod in instance, mod was manually installed");
-                            modal_action.set_finished();
-                            return;
-                        },
-                        ModUpdateAction::Modrinth { file, project_id } => {
-                            let mut path = mod_summary.path.with_file_name(&*file.filename);
-                            if !mod_summary.enabled {
-                                path.add_extension("disabled");
-                            }
-                            debug_assert!(path.is_absolute());
-                            ContentInstall {
-                                target: InstallTarget::Instance(id),
-                                loader_hint: loader,
-                                version_hint: Some(minecraft_version.into()),
-                                files: [ContentInstallFile {
-                                    replace_old: Some(mod_summary.path.clone()),
-                                    path: bridge::install::ContentInstallPath::Raw(path.into()),
-                                    download: ContentDownload::Url {
-                                        url: file.url.clone(),
-                                        sha1: file.hashes.sha1.clone(),
-                                        size: file.size,
-                                    },
-                                    content_source: ContentSource::ModrinthProject { project: project_id },
-                                }].into(),
-                            }
-                        },
-                    }
-                } else {
-                    self.send.send_error("Can't update mod in instance, unknown instance id");
-                    modal_action.set_finished();
+            MessageToBackend::DeleteContent { id, content_ids: mod_ids } => {
+                if self.reject_if_locked(id) {
+                    return;
+                }
+                let mut instance_state = self.instance_state.write();
+                let Some(instance) = instance_state.instances.get_mut(id) else {
+                    self.send.send_error("Unable to find instance, unknown id");
                     return;
                 };
 
-                self.install_content(content_install, modal_action.clone()).await;
+                let mut reload = FxHashSet::default();
+
+                for mod_id in mod_ids {
+                    let Some((instance_mod, folder)) = instance.try_get_content(mod_id) else {
+                        self.send.send_error("Unable to delete mod, invalid id");
+                        return;
+                    };
+
+                    _ = std::fs::remove_file(&instance_mod.path);
+
+                    if let Some(aux_path) = crate::pandora_aux_path_for_content(&instance_mod) {
+                        _ = std::fs::remove_file(aux_path);
+                    }
+
+                    reload.insert((id, folder));
+                }
+
+                instance_state.reload_immediately.extend(reload);
+            },
+            MessageToBackend::UpdateCheck { instance: id, modal_action } => {
+                self.check_content_updates(id, &modal_action).await;
                 modal_action.set_finished();
-                self.send.send(MessageToFrontend::Refresh);
+            },
+            MessageToBackend::UpdateContent { instance: id, content_id: mod_id, modal_action } => {
+                self.update_content(id, mod_id, &modal_action).await;
+                modal_action.set_finished();
+            },
+            MessageToBackend::GetInstanceModInstalls { id, channel } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    _ = channel.send(backend.instance_mod_installs(id).await);
+                });
+            },
+            MessageToBackend::UpdateContentInInstances { targets, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.update_content_in_instances(targets, &modal_action).await;
+                    modal_action.set_finished();
+                });
             },
             MessageToBackend::Sleep5s => {
                 tokio::time::sleep(Duration::from_secs(5)).await;
@@ -842,10 +1121,109 @@ impl BackendState {
                     });
                 }
             },
+            MessageToBackend::SetInstanceSyncing { target, instance, value, channel } => {
+                let dot_minecraft = {
+                    let instance_state = self.instance_state.read();
+                    let Some(found) = instance_state.instances.iter().find(|i| i.name.as_str() == &*instance) else {
+                        self.send.send_error(rust_i18n::t!("notification_sync_instance_missing", name = instance));
+                        return;
+                    };
+                    // The game holds files of the folder open and would keep writing to the old one
+                    if found.process.is_some() || found.launching.is_some() {
+                        self.send.send_error(rust_i18n::t!("notification_sync_instance_running", name = instance));
+                        return;
+                    }
+                    found.dot_minecraft_path.clone()
+                };
+
+                let directories = self.directories.clone();
+                let send = self.send.clone();
+                tokio::task::spawn_blocking(move || {
+                    let result = if value {
+                        crate::syncing::link_instance(target, &dot_minecraft, &directories)
+                    } else {
+                        crate::syncing::unlink_instance(target, &dot_minecraft, &directories).map(|_| None)
+                    };
+                    match result {
+                        Ok(Some(backup)) => {
+                            send.send_warning(rust_i18n::t!("notification_sync_kept_backup", name = instance, path = backup.display()));
+                        },
+                        Ok(None) => {},
+                        Err(error) => {
+                            let action = if value { "link" } else { "unlink" };
+                            send.send_error(format!("Unable to {action} {instance}: {error}"));
+                        },
+                    }
+                    _ = channel.send(());
+                });
+            },
             MessageToBackend::GetBackendConfiguration { channel } => {
                 let configuration = self.config.write().get().clone();
                 _ = channel.send(configuration);
             },
+            MessageToBackend::GetDownloadConditions { channel } => {
+                let launcher_dir = self.directories.root_launcher_dir.clone();
+                tokio::task::spawn_blocking(move || {
+                    _ = channel.send(crate::download_conditions::query(&launcher_dir));
+                });
+            },
+            MessageToBackend::GetLauncherLog { channel } => {
+                let launcher_dir = self.directories.root_launcher_dir.clone();
+                tokio::task::spawn_blocking(move || {
+                    let log = crate::launcher_log::tail(&launcher_dir, crate::launcher_log::VIEWER_TAIL_BYTES)
+                        .unwrap_or_else(|error| format!("Unable to read {}: {error}", crate::launcher_log::FILE_NAME).into());
+                    _ = channel.send(log);
+                });
+            },
+            MessageToBackend::GetStorageReport { channel } => {
+                let backend = self.clone();
+                tokio::task::spawn_blocking(move || {
+                    _ = channel.send(backend.storage_report());
+                });
+            },
+            MessageToBackend::CleanupStorage { cleanup, channel } => {
+                let backend = self.clone();
+                tokio::task::spawn_blocking(move || {
+                    _ = channel.send(backend.cleanup_storage(cleanup).map_err(|error| Arc::from(error.to_string())));
+                });
+            },
+            MessageToBackend::ScanJavaInstallations { channel } => {
+                let directories = self.directories.clone();
+                tokio::task::spawn_blocking(move || {
+                    _ = channel.send(crate::java_scanner::scan(&directories));
+                });
+            },
+            MessageToBackend::GetSystemMemory { channel } => {
+                _ = channel.send(crate::system_info::total_memory_mib());
+            },
+            MessageToBackend::VerifyInstanceFiles { id, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.verify_instance_files(id, &modal_action).await;
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::GetRecommendedMemory { id, channel } => {
+                let mods_dirs: Vec<PathBuf> = {
+                    let instance_state = self.instance_state.read();
+                    match id {
+                        Some(id) => instance_state.instances.get(id).into_iter().collect::<Vec<_>>(),
+                        None => instance_state.instances.iter().collect(),
+                    }.into_iter().map(|instance| instance.content_state[ContentFolder::Mods].path.to_path_buf()).collect()
+                };
+                tokio::task::spawn_blocking(move || {
+                    let recommended = crate::system_info::total_memory_mib().map(|total| {
+                        mods_dirs.iter()
+                            .map(|mods_dir| {
+                                let (count, bytes) = crate::instance::mod_footprint(mods_dir);
+                                InstanceMemoryConfiguration::recommended_for_mods(total, count, bytes / (1024 * 1024))
+                            })
+                            .max_by_key(|memory| memory.max)
+                            .unwrap_or_else(|| InstanceMemoryConfiguration::recommended_for_mods(total, 0, 0))
+                    });
+                    _ = channel.send(recommended);
+                });
+            },
             MessageToBackend::SetGlobalInstanceOverrides { memory_enabled, memory, jvm_flags_enabled, jvm_flags, jvm_binary_enabled, jvm_binary } => {
                 let mut write = self.config.write();
                 write.modify(|config| {
@@ -896,7 +1274,7 @@ impl BackendState {
                     },
                 };
 
-                let tracker = ProgressTracker::new("Reading log file".into(), self.send.clone());
+                let tracker = ProgressTracker::new(rust_i18n::t!("progress_reading_log").into(), self.send.clone());
                 tracker.set_total(4);
                 tracker.notify();
                 modal_action.trackers.push(tracker.clone());
@@ -927,7 +1305,7 @@ impl BackendState {
                     }
                 }
 
-                tracker.set_title("Redacting sensitive information".into());
+                tracker.set_title(rust_i18n::t!("progress_redacting_log").into());
                 tracker.set_count(1);
                 tracker.notify();
 
@@ -943,7 +1321,7 @@ impl BackendState {
 
                 let replaced = log_reader::replace(&*content);
 
-                tracker.set_title("Uploading to mclo.gs".into());
+                tracker.set_title(rust_i18n::t!("progress_uploading_log").into());
                 tracker.set_count(2);
                 tracker.notify();
 
@@ -1026,6 +1404,7 @@ impl BackendState {
                 self.login_flow(&modal_action, None).await;
             },
             MessageToBackend::AddOfflineAccount { name, uuid } => {
+                let uuid = uuid.unwrap_or_else(|| account::offline_uuid(&name));
                 let mut account_info = self.account_info.write();
                 account_info.modify(|account_info| {
                     account_info.accounts.insert(uuid, BackendAccount {
@@ -1058,11 +1437,187 @@ impl BackendState {
                     }
                 });
             },
+            MessageToBackend::GetSkinProfile { channel } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    _ = channel.send(backend.skin_profile().await.map_err(|error| error.to_string().into()));
+                });
+            },
+            MessageToBackend::GetSkinLibrary { channel } => {
+                let skins_dir = self.directories.skins_dir.clone();
+                tokio::task::spawn_blocking(move || {
+                    _ = channel.send(crate::skins::load_library(&skins_dir).into());
+                });
+            },
+            MessageToBackend::AddSkinToLibrary { source, model, channel } => {
+                _ = channel.send(self.add_skin_to_library(source, model).await.map_err(|error| error.to_string().into()));
+            },
+            MessageToBackend::RemoveSkinFromLibrary { id, channel } => {
+                if let Err(error) = crate::skins::remove_from_library(&self.directories.skins_dir, &id) {
+                    self.send.send_error(format!("Unable to remove skin: {error}"));
+                }
+                _ = channel.send(());
+            },
+            MessageToBackend::UploadSkin { id, modal_action, channel } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    match backend.upload_skin(&id, &modal_action).await {
+                        Ok(true) => {
+                            _ = channel.send(());
+                        },
+                        Ok(false) => {},
+                        Err(error) => {
+                            modal_action.set_error_message(format!("Error uploading skin: {error}").into());
+                        },
+                    }
+                    modal_action.set_finished();
+                });
+            },
             MessageToBackend::SetOpenGameOutputAfterLaunching { value } => {
                 self.config.write().modify(|config| {
                     config.dont_open_game_output_when_launching = !value;
                 });
             },
+            MessageToBackend::SetKillGamesOnLauncherExit { value } => {
+                self.config.write().modify(|config| {
+                    config.kill_games_on_launcher_exit = value;
+                });
+            },
+            MessageToBackend::SetOfflineFallback { value } => {
+                self.config.write().modify(|config| {
+                    config.offline_fallback = value;
+                });
+            },
+            MessageToBackend::SetOfflineMode { value } => {
+                crate::offline::set_forced(value);
+                self.config.write().modify(|config| {
+                    config.offline_mode = value;
+                });
+            },
+            MessageToBackend::SetDownloadOnlySelectedLanguage { value } => {
+                self.config.write().modify(|config| {
+                    config.download_only_selected_language = value;
+                });
+            },
+            MessageToBackend::SetUpdateChannel { channel } => {
+                self.config.write().modify(|config| {
+                    config.update_channel = channel;
+                });
+            },
+            MessageToBackend::SetUpdateCheckFrequency { frequency } => {
+                self.config.write().modify(|config| {
+                    config.update_check_frequency = frequency;
+                });
+            },
+            MessageToBackend::SetMetadataRefreshInterval { interval } => {
+                self.config.write().modify(|config| {
+                    config.metadata_refresh_interval = interval;
+                });
+            },
+            MessageToBackend::SetNewVersionDesktopNotification { value } => {
+                self.config.write().modify(|config| {
+                    config.new_version_desktop_notification = value;
+                });
+            },
+            MessageToBackend::CheckForLauncherUpdate => {
+                let channel = self.config.write().get().update_channel;
+                tokio::task::spawn(crate::update::check_for_updates(self.redirecting_http_client.clone(), self.send.clone(), channel));
+            },
+            MessageToBackend::SetStrictSchema { value } => {
+                self.meta.set_strict_schema(value);
+                self.config.write().modify(|config| {
+                    config.strict_schema = value;
+                });
+            },
+            MessageToBackend::SetNetworkOverrides { user_agent, endpoints, proxy, ca_certificates } => {
+                crate::endpoints::set_overrides(endpoints.clone());
+                self.config.write().modify(|config| {
+                    config.custom_user_agent = user_agent;
+                    config.endpoint_overrides = endpoints;
+                    config.proxy = proxy;
+                    config.custom_ca_certificates = ca_certificates;
+                });
+            },
+            MessageToBackend::TestConnection { user_agent, proxy, ca_certificates, channel } => {
+                tokio::task::spawn(async move {
+                    let user_agent = crate::network::user_agent(user_agent.as_deref());
+                    let result = crate::network::test_connection(&user_agent, &proxy, ca_certificates.as_deref()).await;
+                    _ = channel.send(result.map(|elapsed| elapsed.as_millis() as u64).map_err(Arc::from));
+                });
+            },
+            MessageToBackend::SetDownloadLimits { limits } => {
+                self.download_manager.set_limits(limits);
+                self.config.write().modify(|config| {
+                    config.download_limits = limits;
+                });
+            },
+            MessageToBackend::SetLaunchLimits { limits } => {
+                self.config.write().modify(|config| {
+                    config.launch_limits = limits;
+                });
+            },
+            MessageToBackend::SetCurseForgeApiKey { api_key } => {
+                self.config.write().modify(|config| {
+                    config.curseforge_api_key = api_key;
+                });
+            },
+            MessageToBackend::SetLauncherPin { pin } => {
+                let lock = pin.as_deref().map(crate::launcher_lock::create);
+                self.config.write().modify(|config| {
+                    config.launcher_lock = lock;
+                });
+            },
+            MessageToBackend::SetPlaytimeLimits { daily_limit, refuse_launch } => {
+                self.config.write().modify(|config| {
+                    config.daily_playtime_limit = daily_limit;
+                    config.refuse_launch_over_playtime_limit = refuse_launch;
+                });
+            },
+            MessageToBackend::OverridePlaytimeLimit { id, pin, channel } => {
+                let allowed = match &self.config.write().get().launcher_lock {
+                    Some(lock) => crate::launcher_lock::verify(lock, &pin),
+                    None => false,
+                };
+                if allowed && let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    tracing::info!("Playtime limit overridden for {} until the end of the day", instance.name);
+                    self.playtime.write().set_overridden(instance.playtime_key());
+                }
+                _ = channel.send(allowed);
+            },
+            MessageToBackend::CheckLauncherPin { pin, channel } => {
+                let unlocked = match &self.config.write().get().launcher_lock {
+                    Some(lock) => crate::launcher_lock::verify(lock, &pin),
+                    None => true,
+                };
+                if unlocked {
+                    self.launcher_unlocked.store(true, Ordering::Relaxed);
+                } else {
+                    tracing::warn!("Incorrect launcher PIN entered");
+                }
+                _ = channel.send(unlocked);
+            },
+            MessageToBackend::LockLauncher => {
+                self.launcher_unlocked.store(false, Ordering::Relaxed);
+            },
+            MessageToBackend::LauncherExiting { channel } => {
+                // Forwards are removed even for games that keep running, nothing renews them after the launcher exits
+                self.remove_all_port_forwards().await;
+                if self.config.write().get().kill_games_on_launcher_exit {
+                    let mut instance_state = self.instance_state.write();
+                    for instance in instance_state.instances.iter_mut() {
+                        if let Some(mut process) = instance.process.take() {
+                            tracing::info!("Killing game process {} because the launcher is exiting", process.pid());
+                            if let Err(error) = process.kill() {
+                                tracing::error!("Failed to kill instance: {error:?}");
+                            }
+                            self.restore_safe_mode_mods(&instance.content_state[ContentFolder::Mods].path);
+                        }
+                    }
+                }
+                self.playtime.write().save();
+                self.shutdown.cancel();
+                _ = channel.send(());
+            },
             MessageToBackend::CreateInstanceShortcut { id, path } => {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     let Ok(current_exe) = std::env::current_exe() else {
@@ -1070,10 +1625,16 @@ impl BackendState {
                     };
 
                     let args = &[
-                        "--run-instance",
+                        "--launch",
                         instance.name.as_str()
                     ];
-                    crate::shortcut::create_shortcut(path, &format!("Launch {}", instance.name), &current_exe, args);
+                    match crate::shortcut::create_shortcut(path, &format!("Launch {}", instance.name), &current_exe, args) {
+                        Ok(path) => self.send.send_success(format!("Created shortcut at {}", path.display())),
+                        Err(error) => {
+                            tracing::error!("Unable to create shortcut: {error}");
+                            self.send.send_error(format!("Unable to create shortcut: {error}"));
+                        },
+                    }
                 }
             },
             MessageToBackend::InstallUpdate { update, modal_action } => {
@@ -1096,7 +1657,7 @@ impl BackendState {
             match secret_storage.read_credentials(selected_account).await {
                 Ok(credentials) => credentials.unwrap_or_default(),
                 Err(error) => {
-                    log::warn!("Unable to read credentials from keychain: {error}");
+                    tracing::warn!("Unable to read credentials from keychain: {error}");
                     self.send.send_warning(
                         "Unable to read credentials from keychain. You will need to log in again",
                     );
@@ -1107,7 +1668,7 @@ impl BackendState {
             AccountCredentials::default()
         };
 
-        let login_tracker = ProgressTracker::new(Arc::from("Logging in"), self.send.clone());
+        let login_tracker = ProgressTracker::new(rust_i18n::t!("progress_logging_in").into(), self.send.clone());
         modal_action.trackers.push(login_tracker.clone());
 
         let login_result = self.login(&mut credentials, &login_tracker, &modal_action).await;
@@ -1154,7 +1715,7 @@ impl BackendState {
         self.update_account_info_with_profile(&profile);
 
         if let Err(error) = secret_storage.write_credentials(profile.id, &credentials).await {
-            log::warn!("Unable to write credentials to keychain: {error}");
+            tracing::warn!("Unable to write credentials to keychain: {error}");
             self.send.send_warning("Unable to write credentials to keychain. You might need to fully log in again next time");
         }
 
@@ -1184,98 +1745,317 @@ impl BackendState {
         self.update_profile_head(&profile);
     }
 
-    pub async fn download_all_metadata(&self) {
-        let Ok(versions) = self.meta.fetch(&MinecraftVersionManifestMetadataItem).await else {
-            panic!("Unable to get Minecraft version manifest");
+    /// Checks the content of the instance for updates, storing the update actions. Returns false when the check failed
+    pub async fn check_content_updates(&self, id: InstanceID, modal_action: &ModalAction) -> bool {
+        let (loader, version) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            let configuration = instance.configuration.get();
+            (configuration.loader, configuration.minecraft_version)
+        } else {
+            self.send.send_error("Can't update instance, unknown id");
+            modal_action.set_error_message("Can't update instance, unknown id".into());
+            return false;
         };
 
-        for link in &versions.versions {
-            let Ok(version_info) = self.meta.fetch(&MinecraftVersionMetadataItem(link)).await else {
-                panic!("Unable to get load version: {:?}", link.id);
+        let mut content = Vec::new();
+        for folder in ContentFolder::iter() {
+            let Some(summaries) = self.clone().load_instance_content(id, folder).await else {
+                return false;
             };
+            content.extend_from_slice(&*summaries);
+        }
 
-            let asset_index = format!("{}", version_info.assets);
+        let modrinth_loader = loader.as_modrinth_loader();
+        if modrinth_loader == ModrinthLoader::Unknown {
+            modal_action.set_error_message("Unable to update instance, unsupported loader".into());
+            return false;
+        }
 
-            let Ok(_) = self.meta.fetch(&AssetsIndexMetadataItem {
-                url: version_info.asset_index.url,
-                cache: self.directories.assets_index_dir.join(format!("{}.json", &asset_index)).into(),
-                hash: version_info.asset_index.sha1,
-            }).await else {
-                panic!("Can't get assets index {:?}", version_info.asset_index.url);
-            };
+        let tracker = ProgressTracker::new(rust_i18n::t!("progress_checking_content").into(), self.send.clone());
+        tracker.set_total(content.len() as u64);
+        modal_action.trackers.push(tracker.clone());
 
-            if let Some(arguments) = &version_info.arguments {
-                for argument in arguments.game.iter() {
-                    let value = match argument {
-                        LaunchArgument::Single(launch_argument_value) => launch_argument_value,
-                        LaunchArgument::Ruled(launch_argument_ruled) => &launch_argument_ruled.value,
+        let semaphore = Semaphore::new(8);
+
+        let mut futures = Vec::new();
+
+        struct UpdateResult {
+            mod_summary: Arc<ContentSummary>,
+            action: ModUpdateAction,
+        }
+
+        { // Scope is needed so await doesn't complain about the non-send RwLockReadGuard
+            let sources = self.mod_metadata_manager.read_content_sources();
+            for summary in content.iter() {
+                let installed_source = sources.get(&summary.content_summary.hash).unwrap_or(ContentSource::Manual);
+                let semaphore = &semaphore;
+                let tracker = &tracker;
+                futures.push(async move {
+                    let Some(source_id) = crate::content_sources::update_source(&installed_source) else {
+                        tracker.add_count(1);
+                        tracker.notify();
+                        return Ok(ModUpdateAction::ManualInstall);
                     };
-                    match value {
-                        LaunchArgumentValue::Single(shared_string) => {
-                            check_argument_expansions(shared_string.as_str());
-                        },
-                        LaunchArgumentValue::Multiple(shared_strings) => {
-                            for shared_string in shared_strings.iter() {
-                                check_argument_expansions(shared_string.as_str());
-                            }
-                        },
-                    }
-                }
-            } else if let Some(legacy_arguments) = &version_info.minecraft_arguments {
-                for argument in legacy_arguments.split_ascii_whitespace() {
-                    check_argument_expansions(argument);
-                }
+                    let source = self.content_source(source_id)?;
+
+                    let permit = semaphore.acquire().await.unwrap();
+                    let result = crate::content_sources::find_update_action(source_id, &*source, &installed_source,
+                        summary.content_summary.hash, &summary.content_summary.extra, loader, version).await;
+                    drop(permit);
+
+                    tracker.add_count(1);
+                    tracker.notify();
+
+                    result
+                }.map_ok(|action| UpdateResult {
+                    mod_summary: summary.content_summary.clone(),
+                    action,
+                }));
             }
         }
 
-        let Ok(runtimes) = self.meta.fetch(&MojangJavaRuntimesMetadataItem).await else {
-            panic!("Unable to get java runtimes manifest");
-        };
+        let results: Result<Vec<UpdateResult>, ContentSourceError> = futures::future::try_join_all(futures).await;
+
+        match results {
+            Ok(updates) => {
+                let mut meta_updates = self.mod_metadata_manager.updates.write();
 
-        for (platform_name, platform) in &runtimes.platforms {
-            for (jre_component, components) in &platform.components {
-                if components.is_empty() {
-                    continue;
+                for update in updates {
+                    update.mod_summary.update_status.store(update.action.to_status(), Ordering::Relaxed);
+                    meta_updates.insert(update.mod_summary.hash, update.action);
                 }
+            },
+            Err(error) => {
+                tracker.set_finished(ProgressTrackerFinishType::Error);
+                modal_action.set_error_message(format!("Error checking for updates: {}", error).into());
+                return false;
+            },
+        }
 
-                let runtime_component_dir = self.directories.runtime_base_dir.join(jre_component).join(platform_name.as_str());
-                let _ = std::fs::create_dir_all(&runtime_component_dir);
-                let Ok(runtime_component_dir) = runtime_component_dir.canonicalize() else {
-                    panic!("Unable to create runtime component dir");
-                };
+        tracker.set_finished(ProgressTrackerFinishType::Normal);
+        true
+    }
 
-                for runtime_component in components {
-                    let Ok(manifest) = self.meta.fetch(&MojangJavaRuntimeComponentMetadataItem {
-                        url: runtime_component.manifest.url,
-                        cache: runtime_component_dir.join("manifest.json").into(),
-                        hash: runtime_component.manifest.sha1,
-                    }).await else {
-                        panic!("Unable to get java runtime component manifest");
-                    };
+    pub async fn update_content(&self, id: InstanceID, mod_id: InstanceContentID, modal_action: &ModalAction) {
+        if self.reject_if_locked(id) {
+            return;
+        }
+        let content_install = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            let configuration = instance.configuration.get();
+            let (loader, minecraft_version) = (configuration.loader, configuration.minecraft_version);
+            let Some((mod_summary, _)) = instance.try_get_content(mod_id) else {
+                self.send.send_error("Can't update mod in instance, unknown mod id");
+                return;
+            };
 
-                    let keys: &[Arc<std::path::Path>] = &[
-                        std::path::Path::new("bin/java").into(),
-                        std::path::Path::new("bin/javaw.exe").into(),
-                        std::path::Path::new("jre.bundle/Contents/Home/bin/java").into(),
-                        std::path::Path::new("MinecraftJava.exe").into(),
-                    ];
+            let Some(update_info) = self.mod_metadata_manager.updates.read().get(&mod_summary.content_summary.hash).cloned() else {
+                self.send.send_error("Can't update mod in instance, missing update action");
+                return;
+            };
 
-                    let mut known_executable_path = false;
-                    for key in keys {
-                        if manifest.files.contains_key(key) {
-                            known_executable_path = true;
-                            break;
-                        }
+            match update_info {
+                ModUpdateAction::ErrorNotFound => {
+                    self.send.send_error("Can't update mod in instance, 404 not found");
+                    return;
+                },
+                ModUpdateAction::ErrorInvalidHash => {
+                    self.send.send_error("Can't update mod in instance, returned invalid hash");
+                    return;
+                },
+                ModUpdateAction::AlreadyUpToDate => {
+                    self.send.send_error("Can't update mod in instance, already up-to-date");
+                    return;
+                },
+                ModUpdateAction::ManualInstall => {
+                    self.send.send_error("Can't update mod in instance, mod was manually installed");
+                    return;
+                },
+                ModUpdateAction::Update { source, file, project_id } => {
+                    let url = match self.content_source(source).and_then(|source| source.download_url(&file)) {
+                        Ok(url) => url,
+                        Err(error) => {
+                            self.send.send_error(format!("Can't update mod in instance: {error}"));
+                            return;
+                        },
+                    };
+                    let mut path = mod_summary.path.with_file_name(&*file.filename);
+                    if !mod_summary.enabled {
+                        path.add_extension("disabled");
                     }
-
-                    if !known_executable_path {
-                        panic!("{}/{} doesn't contain known java executable", jre_component, platform_name);
+                    debug_assert!(path.is_absolute());
+                    ContentInstall {
+                        target: InstallTarget::Instance(id),
+                        loader_hint: loader,
+                        version_hint: Some(minecraft_version.into()),
+                        files: [ContentInstallFile {
+                            replace_old: Some(mod_summary.path.clone()),
+                            path: bridge::install::ContentInstallPath::Raw(path.into()),
+                            download: ContentDownload::Url {
+                                url,
+                                sha1: file.sha1.clone(),
+                                size: file.size,
+                            },
+                            content_source: crate::content_sources::installed_content_source(source, project_id),
+                        }].into(),
                     }
-                }
+                },
+            }
+        } else {
+            self.send.send_error("Can't update mod in instance, unknown instance id");
+            return;
+        };
+
+        // Modpack updates often overwrite configs, so the current ones are kept to roll back to
+        let is_modpack = self.instance_state.write().instances.get_mut(id)
+            .and_then(|instance| instance.try_get_content(mod_id))
+            .is_some_and(|(summary, _)| matches!(summary.content_summary.extra, ContentType::ModrinthModpack { .. }));
+        if is_modpack {
+            self.snapshot_configs(id, ConfigSnapshotReason::ModpackUpdate).await;
+        }
+
+        self.install_content(content_install, modal_action.clone()).await;
+        self.send.send(MessageToFrontend::Refresh);
+    }
+
+    /// Sends an error and returns true if the instance is locked against modification
+    pub(crate) fn reject_if_locked(&self, id: InstanceID) -> bool {
+        let mut instance_state = self.instance_state.write();
+        let Some(instance) = instance_state.instances.get_mut(id) else {
+            return false;
+        };
+        if !instance.configuration.get().locked {
+            return false;
+        }
+        let name = instance.name;
+        drop(instance_state);
+
+        self.send.send_error(rust_i18n::t!("notification_instance_locked", name = name));
+        true
+    }
+
+    /// Deletes the folders of the instances, skipping the ones that are locked, running or launching
+    async fn delete_instances(&self, ids: &[InstanceID]) {
+        let mut root_paths = Vec::with_capacity(ids.len());
+        for &id in ids {
+            if self.reject_if_locked(id) {
+                continue;
+            }
+            let mut instance_state = self.instance_state.write();
+            let Some(instance) = instance_state.instances.get_mut(id) else {
+                continue;
+            };
+            if instance.process.is_some() || instance.launching.is_some() {
+                let name = instance.name;
+                drop(instance_state);
+                self.send.send_warning(format!("Unable to delete {name} while it is running"));
+                continue;
+            }
+            root_paths.push(instance.root_path.clone());
+        }
+
+        // Large instances take a while to delete, so this happens without holding the instance state
+        let results = tokio::task::spawn_blocking(move || {
+            root_paths.iter().map(|root_path| std::fs::remove_dir_all(root_path)).collect::<Vec<_>>()
+        }).await.unwrap_or_default();
+        for result in results {
+            if let Err(err) = result {
+                self.send.send_error(format!("Unable to delete instance folder: {}", err));
+            }
+        }
+    }
+
+    /// Downloads and verifies everything the instances need to launch, only starting Java to check its arguments.
+    /// Instances that are running or launching are skipped
+    async fn prepare_instances(&self, ids: &[InstanceID], modal_action: &ModalAction) {
+        for &id in ids {
+            if modal_action.has_requested_cancel() {
+                return;
+            }
+
+            match self.validate_instance(id, &VerificationReport::default(), modal_action).await {
+                None | Some((_, Ok(()))) => {},
+                Some((_, Err(LaunchError::CancelledByUser))) => return,
+                Some((name, Err(error))) => self.send.send_error(format!("Unable to prepare {name}: {error}")),
             }
         }
+    }
+
+    /// Hashes every file the instance needs instead of trusting the files verified before, repairing those that don't
+    /// match their manifest
+    async fn verify_instance_files(&self, id: InstanceID, modal_action: &ModalAction) {
+        self.launcher.verified_files.clear();
+
+        let report = VerificationReport::default();
+        let Some((name, result)) = self.validate_instance(id, &report, modal_action).await else {
+            modal_action.set_error_message("Can't verify the game files while the instance is running".into());
+            return;
+        };
+
+        match result {
+            Ok(()) if report.repaired() > 0 || report.downloaded() > 0 => {
+                tracing::warn!("Verifying {name} repaired {} files and downloaded {} missing files", report.repaired(), report.downloaded());
+                self.send.send_warning(format!("{} game files of {name} didn't match and were repaired, {} missing files were downloaded",
+                    report.repaired(), report.downloaded()));
+            },
+            Ok(()) => self.send.send_success(format!("All {} game files of {name} are intact", report.verified())),
+            Err(LaunchError::CancelledByUser) => {},
+            Err(error) => modal_action.set_error_message(format!("Unable to verify {name}: {error}").into()),
+        }
+    }
+
+    /// Downloads and verifies everything the instance needs by going through a launch without starting the game. None
+    /// if the instance doesn't exist or is running
+    async fn validate_instance(&self, id: InstanceID, report: &VerificationReport, modal_action: &ModalAction) -> Option<(Ustr, Result<(), LaunchError>)> {
+        let (name, root_path, dot_minecraft, mut configuration) = self.instance_state.write().instances.get_mut(id)
+            .filter(|instance| instance.process.is_none() && instance.launching.is_none())
+            .map(|instance| (instance.name, instance.root_path.clone(), instance.dot_minecraft_path.clone(),
+                instance.configuration.get().clone()))?;
+        self.apply_global_overrides(&mut configuration);
+
+        let login_info = MinecraftLoginInfo {
+            uuid: account::offline_uuid("Player"),
+            username: "Player".into(),
+            access_token: None,
+        };
+
+        let tracker = ProgressTracker::new(rust_i18n::t!("progress_preparing_instance", name = name).into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+
+        let only_selected_language = self.config.write().get().download_only_selected_language;
+        let instance_folder = root_path.file_name().map(|folder| folder.to_string_lossy());
+        let result = self.launcher.launch(&self.redirecting_http_client, dot_minecraft, instance_folder.as_deref(), configuration,
+            None, login_info, Vec::new(), only_selected_language, true, report, &tracker, modal_action).await;
+
+        tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
+        tracker.notify();
+
+        Some((name, result.map(|mut child| {
+            _ = child.kill();
+            _ = child.wait();
+        })))
+    }
+
+    /// Whether launching the instance should be refused because today's playtime is used up
+    fn is_over_playtime_limit(&self, id: InstanceID) -> bool {
+        let config = self.config.write().get().clone();
+        if !config.refuse_launch_over_playtime_limit {
+            return false;
+        }
+
+        let Some((key, instance_limit)) = self.instance_state.write().instances.get_mut(id)
+            .map(|instance| (instance.playtime_key(), instance.configuration.get().daily_playtime_limit))
+        else {
+            return false;
+        };
 
-        println!("Done downloading all metadata");
+        let mut playtime = self.playtime.write();
+        if playtime.is_overridden(&key) {
+            return false;
+        }
+        let limits = PlaytimeLimits {
+            instance: instance_limit,
+            global: config.daily_playtime_limit,
+        };
+        playtime.remaining_seconds(&key, limits).is_some_and(|remaining| remaining <= 0)
     }
 }
 
@@ -1313,21 +2093,56 @@ fn set_mod_child_enabled(child_state_path: &Path, child: &str, enabled: bool) ->
     Ok(())
 }
 
-fn check_argument_expansions(argument: &str) {
-    let mut dollar_last = false;
-    for (i, character) in argument.char_indices() {
-        if character == '$' {
-            dollar_last = true;
-        } else if dollar_last && character == '{' {
-            let remaining = &argument[i..];
-            if let Some(end) = remaining.find('}') {
-                let to_expand = &argument[i+1..i+end];
-                if ArgumentExpansionKey::from_str(to_expand).is_none() {
-                    panic!("Unsupported argument: {:?}", to_expand);
-                }
-            }
-        } else {
-            dollar_last = false;
-        }
+#[cfg(test)]
+mod tests {
+    use bridge::message::BridgeNotificationType;
+    use schema::{instance::InstanceConfiguration, loader::Loader};
+
+    use super::*;
+    use crate::test_harness::{TempDir, TestBackend};
+
+    #[test]
+    fn gated_messages_are_refused_while_locked() {
+        let dir = TempDir::new();
+        let instance_dir = dir.path().join("instances").join("Locked");
+        std::fs::create_dir_all(&instance_dir).unwrap();
+        let configuration = InstanceConfiguration::new("1.21.1".into(), Loader::Vanilla);
+        std::fs::write(instance_dir.join("info_v1.json"), serde_json::to_vec(&configuration).unwrap()).unwrap();
+
+        let mut backend = TestBackend::load(dir.path());
+        backend.state.config.write().modify(|config| config.launcher_lock = Some(crate::launcher_lock::create("1234")));
+        let id = backend.state.instance_state.read().instances.iter().next().unwrap().id;
+        let daily_limit = |backend: &TestBackend| {
+            backend.state.instance_state.write().instances.get_mut(id).unwrap().configuration.get().daily_playtime_limit
+        };
+        backend.notifications();
+
+        let message = || MessageToBackend::SetInstancePlaytimeLimit { id, daily_limit: Some(600) };
+        backend.runtime.block_on(backend.state.handle_message(message()));
+        assert_eq!(daily_limit(&backend), None);
+        assert!(backend.notifications().iter().any(|(notification_type, _)| *notification_type == BridgeNotificationType::Warning));
+
+        backend.state.launcher_unlocked.store(true, Ordering::Relaxed);
+        backend.runtime.block_on(backend.state.handle_message(message()));
+        assert_eq!(daily_limit(&backend), Some(600));
+    }
+
+    #[test]
+    fn locked_instances_are_not_renamed() {
+        let dir = TempDir::new();
+        let instance_dir = dir.path().join("instances").join("Locked");
+        std::fs::create_dir_all(&instance_dir).unwrap();
+        let mut configuration = InstanceConfiguration::new("1.21.1".into(), Loader::Vanilla);
+        configuration.locked = true;
+        std::fs::write(instance_dir.join("info_v1.json"), serde_json::to_vec(&configuration).unwrap()).unwrap();
+
+        let mut backend = TestBackend::load(dir.path());
+        let id = backend.state.instance_state.read().instances.iter().next().unwrap().id;
+        backend.notifications();
+
+        backend.runtime.block_on(backend.state.handle_message(MessageToBackend::RenameInstance { id, name: "Renamed".into() }));
+        assert!(instance_dir.is_dir());
+        assert!(!dir.path().join("instances").join("Renamed").exists());
+        assert!(backend.notifications().iter().any(|(notification_type, _)| *notification_type == BridgeNotificationType::Error));
     }
 }
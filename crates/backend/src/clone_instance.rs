@@ -0,0 +1,270 @@
+use std::{path::Path, sync::Arc};
+
+use bridge::{instance::InstanceID, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}};
+use schema::{content::ContentSource, instance::InstanceConfiguration, modrinth::ModrinthLoader};
+use ustr::Ustr;
+
+use crate::{BackendState, metadata::{items::{ModrinthVersionUpdateMetadataItem, VersionUpdateParameters}, manager::MetaLoadError}};
+
+/// Folders whose content is re-resolved for the new version, along with the loader used to look it up
+const MIGRATED_FOLDERS: &[&str] = &["mods", "resourcepacks"];
+
+#[derive(Default)]
+struct MigrationReport {
+    updated: Vec<String>,
+    kept: usize,
+    /// Files that have no version for the target Minecraft version, they are disabled in the clone
+    disabled: Vec<String>,
+    /// Files that couldn't be looked up or downloaded, they are copied unchanged so that retrying later is possible
+    failed: Vec<String>,
+}
+
+enum Migration {
+    Keep,
+    Replace {
+        url: Arc<str>,
+        sha1: Arc<str>,
+        size: usize,
+        filename: Arc<str>,
+        project_id: Arc<str>,
+    },
+    Disable,
+    /// The lookup itself failed, so it's unknown whether a compatible version exists
+    Failed,
+}
+
+impl BackendState {
+    /// Copies an instance, including its configs, options, servers and worlds, into a new instance for another
+    /// Minecraft version. Mods and resource packs are switched to versions made for the new Minecraft version
+    /// where Modrinth knows about them, anything else is disabled and reported
+    pub async fn clone_instance_to_version(&self, id: InstanceID, name: &str, minecraft_version: Ustr, modal_action: &ModalAction) {
+        let Some((original_name, root_path, loader, original_version)) = self.instance_state.write().instances.get_mut(id)
+            .map(|instance| {
+                let configuration = instance.configuration.get();
+                (instance.name, instance.root_path.clone(), configuration.loader, configuration.minecraft_version)
+            })
+        else {
+            modal_action.set_error_message("Can't clone instance, unknown id".into());
+            return;
+        };
+
+        let name = sanitize_filename::sanitize_with_options(name, sanitize_filename::Options { windows: true, ..Default::default() });
+        let new_instance_dir = self.directories.instances_dir.join(&name);
+        if name.is_empty() || new_instance_dir.exists() || self.instance_state.read().instances.iter().any(|instance| instance.name == name) {
+            modal_action.set_error_message(format!("Can't clone instance, the name '{name}' is already used").into());
+            return;
+        }
+
+        tracing::info!("Cloning instance {original_name} as {name} for Minecraft {minecraft_version}");
+
+        // Work in the temp dir so the instances watcher only sees the finished instance
+        let temp_dir: Arc<Path> = self.directories.temp_dir.join(format!("clone-{:016x}", rand::random::<u64>())).into();
+
+        let copy_tracker = ProgressTracker::new(rust_i18n::t!("progress_copying_instance").into(), self.send.clone());
+        modal_action.trackers.push(copy_tracker.clone());
+        copy_tracker.notify();
+
+        let copy_result = {
+            let temp_dir = temp_dir.clone();
+            let sync_targets = self.config.write().get().sync_targets;
+            let directories = self.directories.clone();
+            tokio::task::spawn_blocking(move || {
+                crate::syncing::copy_instance(&root_path, &temp_dir, sync_targets, &directories)?;
+
+                let info_path = temp_dir.join("info_v1.json");
+                let mut configuration: InstanceConfiguration = crate::read_json(&info_path)
+                    .map_err(|error| std::io::Error::other(format!("{error:?}")))?;
+                configuration.minecraft_version = minecraft_version;
+                configuration.preferred_loader_version = None;
+                configuration.last_played = None;
+                configuration.uuid = None;
+                crate::write_safe(&info_path, &serde_json::to_vec(&configuration)?)
+            }).await.unwrap()
+        };
+
+        copy_tracker.set_finished(ProgressTrackerFinishType::from_err(copy_result.is_err()));
+        copy_tracker.notify();
+
+        if let Err(error) = copy_result {
+            _ = std::fs::remove_dir_all(&temp_dir);
+            modal_action.set_error_message(format!("Unable to copy instance: {error}").into());
+            return;
+        }
+
+        let report = match self.migrate_content(&temp_dir.join(".minecraft"), loader.as_modrinth_loader(), minecraft_version, modal_action).await {
+            Ok(report) => report,
+            Err(error) => {
+                _ = std::fs::remove_dir_all(&temp_dir);
+                modal_action.set_error_message(format!("Unable to migrate content: {error}").into());
+                return;
+            },
+        };
+
+        if modal_action.has_requested_cancel() {
+            _ = std::fs::remove_dir_all(&temp_dir);
+            return;
+        }
+
+        self.migrate_options_txt(&temp_dir.join(".minecraft"), &original_version, &minecraft_version).await;
+
+        if let Err(error) = std::fs::rename(&temp_dir, &new_instance_dir) {
+            _ = std::fs::remove_dir_all(&temp_dir);
+            modal_action.set_error_message(format!("Unable to create instance: {error}").into());
+            return;
+        }
+
+        self.send.send_success(rust_i18n::t!("notification_clone_created", name = name, version = minecraft_version,
+            updated = report.updated.len(), kept = report.kept));
+        if !report.disabled.is_empty() {
+            self.send.send_warning(rust_i18n::t!("notification_clone_disabled", version = minecraft_version, name = name,
+                files = report.disabled.join("\n")));
+        }
+        if !report.failed.is_empty() {
+            self.send.send_warning(rust_i18n::t!("notification_clone_unchecked", version = minecraft_version, name = name,
+                files = report.failed.join("\n")));
+        }
+    }
+
+    async fn migrate_content(&self, dot_minecraft: &Path, mod_loader: ModrinthLoader, minecraft_version: Ustr, modal_action: &ModalAction) -> std::io::Result<MigrationReport> {
+        let mut files = Vec::new();
+        for folder in MIGRATED_FOLDERS {
+            let loader = if *folder == "mods" { mod_loader } else { ModrinthLoader::Minecraft };
+            let Ok(read_dir) = std::fs::read_dir(dot_minecraft.join(folder)) else {
+                continue;
+            };
+            for entry in read_dir.flatten() {
+                if entry.file_type().is_ok_and(|file_type| file_type.is_file()) {
+                    files.push((entry.path(), loader));
+                }
+            }
+        }
+
+        let tracker = ProgressTracker::new(rust_i18n::t!("progress_finding_compatible_versions").into(), self.send.clone());
+        tracker.set_total(files.len() as u64);
+        modal_action.trackers.push(tracker.clone());
+        tracker.notify();
+
+        let semaphore = tokio::sync::Semaphore::new(8);
+        let mut tasks = Vec::new();
+        for (path, loader) in files {
+            let semaphore = &semaphore;
+            let tracker = &tracker;
+            tasks.push(async move {
+                let _permit = semaphore.acquire().await.unwrap();
+                let migration = self.find_migration(&path, loader, minecraft_version).await;
+                tracker.add_count(1);
+                tracker.notify();
+                (path, migration)
+            });
+        }
+        let migrations = futures::future::join_all(tasks).await;
+
+        tracker.set_finished(ProgressTrackerFinishType::Normal);
+        tracker.notify();
+
+        let mut report = MigrationReport::default();
+        let mut sources = Vec::new();
+        for (path, migration) in migrations {
+            let file_name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+            let disabled = file_name.ends_with(".disabled");
+
+            match migration {
+                Migration::Keep => report.kept += 1,
+                Migration::Failed => report.failed.push(file_name),
+                Migration::Disable => {
+                    if !disabled {
+                        std::fs::rename(&path, path.with_added_extension("disabled"))?;
+                    }
+                    report.disabled.push(file_name);
+                },
+                Migration::Replace { url, sha1, size, filename, project_id } => {
+                    let Some(safe_filename) = bridge::safe_path::SafePath::new(&filename) else {
+                        report.disabled.push(file_name);
+                        continue;
+                    };
+                    let download = self.download_file_into_library(modal_action, (&safe_filename).into(), &url, &sha1, size).await;
+                    let (library_path, hash, _) = match download {
+                        Ok(download) => download,
+                        Err(error) => {
+                            tracing::warn!("Unable to download the new version of {file_name}: {error}");
+                            report.failed.push(file_name);
+                            continue;
+                        },
+                    };
+
+                    let mut new_path = path.with_file_name(&*filename);
+                    if disabled {
+                        new_path.add_extension("disabled");
+                    }
+                    std::fs::remove_file(&path)?;
+                    if std::fs::hard_link(&library_path, &new_path).is_err() {
+                        std::fs::copy(&library_path, &new_path)?;
+                    }
+                    sources.push((hash, ContentSource::ModrinthProject { project: project_id }));
+                    report.updated.push(file_name);
+                },
+            }
+        }
+        self.mod_metadata_manager.set_content_sources(sources.into_iter());
+
+        Ok(report)
+    }
+
+    async fn find_migration(&self, path: &Path, loader: ModrinthLoader, minecraft_version: Ustr) -> Migration {
+        if loader == ModrinthLoader::Unknown {
+            return Migration::Disable;
+        }
+
+        let hash = {
+            let path = path.to_path_buf();
+            tokio::task::spawn_blocking(move || {
+                std::fs::read(path).map(|bytes| {
+                    use sha1::{Digest, Sha1};
+                    let mut hasher = Sha1::new();
+                    hasher.update(&bytes);
+                    hex::encode(hasher.finalize())
+                })
+            }).await.unwrap()
+        };
+        let hash = match hash {
+            Ok(hash) => hash,
+            Err(error) => {
+                tracing::warn!("Unable to read {:?}: {error}", path);
+                return Migration::Failed;
+            },
+        };
+
+        let result = self.meta.fetch(&ModrinthVersionUpdateMetadataItem {
+            sha1: hash.as_str().into(),
+            params: VersionUpdateParameters {
+                loaders: [loader].into(),
+                game_versions: [minecraft_version].into(),
+            },
+        }).await;
+
+        let version = match result {
+            Ok(version) => version,
+            // Modrinth answers 404 both for unknown files and for files without a compatible version
+            Err(MetaLoadError::NonOK(404)) => return Migration::Disable,
+            Err(error) => {
+                tracing::warn!("Unable to look up a version of {:?} for {minecraft_version}: {error}", path);
+                return Migration::Failed;
+            },
+        };
+
+        let Some(file) = version.0.files.iter().find(|file| file.primary).or(version.0.files.first()) else {
+            return Migration::Disable;
+        };
+        if file.hashes.sha1.eq_ignore_ascii_case(&hash) {
+            return Migration::Keep;
+        }
+
+        Migration::Replace {
+            url: file.url.clone(),
+            sha1: file.hashes.sha1.clone(),
+            size: file.size,
+            filename: file.filename.clone(),
+            project_id: version.0.project_id.clone(),
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use std::{
+    collections::{BTreeMap, BTreeSet}, path::{Path, PathBuf}, sync::Arc
+};
+
+use bridge::instance::{ConfigFileChange, ConfigFileDiff, DiffLine, DiffLineKind, InstanceID};
+use schema::instance::{ConfigSnapshot, ConfigSnapshotReason};
+use sha1::{Digest, Sha1};
+
+use crate::BackendState;
+
+/// Older snapshots are dropped once there are more than this
+const MAX_SNAPSHOTS: usize = 50;
+/// Larger files in config/ are usually caches rather than configs, they aren't kept
+const MAX_FILE_SIZE: u64 = 4 * 1024 * 1024;
+/// Files where the product of the line counts exceeds this are shown as entirely replaced instead of being diffed line by line
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+fn snapshots_dir(instance_root: &Path) -> PathBuf {
+    instance_root.join("config_snapshots")
+}
+
+fn object_path(instance_root: &Path, hash: &str) -> PathBuf {
+    snapshots_dir(instance_root).join("objects").join(hash)
+}
+
+pub fn load(instance_root: &Path) -> Vec<ConfigSnapshot> {
+    std::fs::read(snapshots_dir(instance_root).join("snapshots.json")).ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save(instance_root: &Path, snapshots: &[ConfigSnapshot]) -> std::io::Result<()> {
+    let json = serde_json::to_vec_pretty(snapshots).map_err(std::io::Error::other)?;
+    crate::write_safe(&snapshots_dir(instance_root).join("snapshots.json"), &json)
+}
+
+/// The config files of the instance, by their path relative to .minecraft
+fn collect(dot_minecraft: &Path) -> std::io::Result<BTreeMap<Arc<str>, PathBuf>> {
+    let mut files = Vec::new();
+    crate::instance_export::collect_files(&dot_minecraft.join("config"), "config", &mut files)?;
+    let options = dot_minecraft.join("options.txt");
+    if options.is_file() {
+        files.push(("options.txt".to_string(), options));
+    }
+
+    Ok(files.into_iter()
+        .filter(|(_, path)| path.metadata().is_ok_and(|metadata| metadata.len() <= MAX_FILE_SIZE))
+        .map(|(name, path)| (Arc::from(name), path))
+        .collect())
+}
+
+/// Snapshots the config files, unless they are unchanged since the last snapshot. Returns the time of the new snapshot
+pub fn take(instance_root: &Path, dot_minecraft: &Path, reason: ConfigSnapshotReason) -> std::io::Result<Option<i64>> {
+    let objects_dir = snapshots_dir(instance_root).join("objects");
+    std::fs::create_dir_all(&objects_dir)?;
+
+    let mut files = BTreeMap::new();
+    for (name, path) in collect(dot_minecraft)? {
+        let contents = std::fs::read(&path)?;
+        let hash: Arc<str> = hex::encode(Sha1::digest(&contents)).into();
+        let object = objects_dir.join(&*hash);
+        if !object.exists() {
+            crate::write_safe(&object, &contents)?;
+        }
+        files.insert(name, hash);
+    }
+
+    let mut snapshots = load(instance_root);
+    if snapshots.last().is_some_and(|last| last.files == files) {
+        return Ok(None);
+    }
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let time = snapshots.last().map_or(now, |last| now.max(last.time + 1));
+    snapshots.push(ConfigSnapshot { time, reason, files });
+    if snapshots.len() > MAX_SNAPSHOTS {
+        snapshots.drain(..snapshots.len() - MAX_SNAPSHOTS);
+    }
+    save(instance_root, &snapshots)?;
+    remove_unreferenced_objects(instance_root, &snapshots);
+
+    Ok(Some(time))
+}
+
+fn remove_unreferenced_objects(instance_root: &Path, snapshots: &[ConfigSnapshot]) {
+    let referenced: BTreeSet<&str> = snapshots.iter().flat_map(|snapshot| snapshot.files.values().map(|hash| &**hash)).collect();
+    let Ok(read_dir) = std::fs::read_dir(snapshots_dir(instance_root).join("objects")) else {
+        return;
+    };
+    for entry in read_dir.flatten() {
+        if !entry.file_name().to_str().is_some_and(|name| referenced.contains(name)) {
+            _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+fn find(instance_root: &Path, time: i64) -> std::io::Result<ConfigSnapshot> {
+    load(instance_root).into_iter()
+        .find(|snapshot| snapshot.time == time)
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "snapshot no longer exists"))
+}
+
+/// Line diff based on the longest common subsequence
+fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let line = |kind, text: &str| DiffLine { kind, text: text.into() };
+
+    if old.len().saturating_mul(new.len()) > MAX_DIFF_CELLS {
+        return old.iter().map(|text| line(DiffLineKind::Removed, text))
+            .chain(new.iter().map(|text| line(DiffLineKind::Added, text)))
+            .collect();
+    }
+
+    // common[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0_u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(line(DiffLineKind::Unchanged, old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(line(DiffLineKind::Removed, old[i]));
+            i += 1;
+        } else {
+            lines.push(line(DiffLineKind::Added, new[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+/// Changes from the snapshot to the config files currently on disk
+pub fn diff(instance_root: &Path, dot_minecraft: &Path, time: i64) -> std::io::Result<Vec<ConfigFileDiff>> {
+    let snapshot = find(instance_root, time)?;
+    let current = collect(dot_minecraft)?;
+
+    let paths: BTreeSet<&Arc<str>> = snapshot.files.keys().chain(current.keys()).collect();
+    let mut diffs = Vec::new();
+    for path in paths {
+        let old = match snapshot.files.get(path) {
+            Some(hash) => Some(std::fs::read(object_path(instance_root, hash))?),
+            None => None,
+        };
+        let new = match current.get(path) {
+            Some(file) => Some(std::fs::read(file)?),
+            None => None,
+        };
+
+        let change = match (&old, &new) {
+            (Some(old), Some(new)) if old == new => continue,
+            (Some(_), Some(_)) => ConfigFileChange::Modified,
+            (Some(_), None) => ConfigFileChange::Removed,
+            (None, _) => ConfigFileChange::Added,
+        };
+
+        let text = |bytes: &Option<Vec<u8>>| bytes.as_deref().map_or(Some(""), |bytes| std::str::from_utf8(bytes).ok());
+        let lines = match (text(&old), text(&new)) {
+            (Some(old), Some(new)) => diff_lines(old, new),
+            _ => Vec::new(),
+        };
+
+        diffs.push(ConfigFileDiff { path: path.clone(), change, lines: lines.into() });
+    }
+    Ok(diffs)
+}
+
+/// Resolves a path from a snapshot inside .minecraft, refusing anything that would escape it, including through
+/// symlinks that were placed in the instance since the snapshot was taken
+fn restore_target(dot_minecraft: &Path, relative: &str) -> std::io::Result<PathBuf> {
+    let relative = Path::new(relative);
+    if !relative.components().all(|component| matches!(component, std::path::Component::Normal(_))) {
+        return Err(std::io::Error::other(format!("Invalid path in snapshot: {}", relative.display())));
+    }
+
+    let mut target = dot_minecraft.to_path_buf();
+    for component in relative.components() {
+        target.push(component);
+        match target.symlink_metadata() {
+            Ok(metadata) if metadata.is_symlink() => {
+                return Err(std::io::Error::other(format!("Refusing to restore through symlink {}", target.display())));
+            },
+            Ok(_) => {},
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => break,
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(dot_minecraft.join(relative))
+}
+
+/// Puts the config files back the way they were in the snapshot, after snapshotting the current files
+pub fn restore(instance_root: &Path, dot_minecraft: &Path, time: i64) -> std::io::Result<()> {
+    // Read before snapshotting, which could prune the snapshot being restored
+    let snapshot = find(instance_root, time)?;
+    let mut files = Vec::with_capacity(snapshot.files.len());
+    for (path, hash) in &snapshot.files {
+        if hash.len() != 40 || !hash.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return Err(std::io::Error::other(format!("Invalid hash in snapshot: {hash}")));
+        }
+        let target = restore_target(dot_minecraft, path)?;
+        files.push((target, std::fs::read(object_path(instance_root, hash))?));
+    }
+    take(instance_root, dot_minecraft, ConfigSnapshotReason::Rollback)?;
+
+    for (path, _) in collect(dot_minecraft)? {
+        if !snapshot.files.contains_key(&path) {
+            std::fs::remove_file(restore_target(dot_minecraft, &path)?)?;
+        }
+    }
+    for (target, contents) in files {
+        crate::write_safe(&target, &contents)?;
+    }
+    Ok(())
+}
+
+impl BackendState {
+    fn instance_config_paths(&self, id: InstanceID) -> Option<(Arc<Path>, Arc<Path>)> {
+        self.instance_state.read().instances.get(id).map(|instance| (instance.root_path.clone(), instance.dot_minecraft_path.clone()))
+    }
+
+    /// Failing to snapshot the configs shouldn't stop what the snapshot was taken for, so errors are only logged
+    pub async fn snapshot_configs(&self, id: InstanceID, reason: ConfigSnapshotReason) {
+        let Some((root, dot_minecraft)) = self.instance_config_paths(id) else {
+            return;
+        };
+        let result = tokio::task::spawn_blocking(move || take(&root, &dot_minecraft, reason)).await.unwrap();
+        if let Err(error) = result {
+            tracing::error!("Unable to snapshot configs: {error}");
+        }
+    }
+
+    pub async fn config_snapshot_diff(&self, id: InstanceID, time: i64) -> Result<Arc<[ConfigFileDiff]>, Arc<str>> {
+        let Some((root, dot_minecraft)) = self.instance_config_paths(id) else {
+            return Err("Unknown instance".into());
+        };
+        tokio::task::spawn_blocking(move || diff(&root, &dot_minecraft, time)).await.unwrap()
+            .map(Arc::from)
+            .map_err(|error| format!("Unable to compare configs: {error}").into())
+    }
+
+    pub async fn restore_config_snapshot(&self, id: InstanceID, time: i64) {
+        let Some((root, dot_minecraft)) = self.instance_config_paths(id) else {
+            return;
+        };
+        if self.instance_state.read().instances.get(id).is_some_and(|instance| instance.process.is_some()) {
+            self.send.send_error(rust_i18n::t!("notification_configs_rollback_running"));
+            return;
+        }
+
+        match tokio::task::spawn_blocking(move || restore(&root, &dot_minecraft, time)).await.unwrap() {
+            Ok(()) => self.send.send_success(rust_i18n::t!("notification_configs_rolled_back")),
+            Err(error) => self.send.send_error(rust_i18n::t!("notification_configs_rollback_failed", error = error)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::TempDir;
+
+    #[test]
+    fn snapshots_are_deduplicated_and_restorable() {
+        let dir = TempDir::new();
+        let root = dir.path().join("instance");
+        let dot_minecraft = root.join(".minecraft");
+        std::fs::create_dir_all(dot_minecraft.join("config")).unwrap();
+        std::fs::write(dot_minecraft.join("options.txt"), "fov:70\n").unwrap();
+        std::fs::write(dot_minecraft.join("config").join("sodium.json"), "{}").unwrap();
+
+        let first = take(&root, &dot_minecraft, ConfigSnapshotReason::Launch).unwrap().unwrap();
+        assert_eq!(take(&root, &dot_minecraft, ConfigSnapshotReason::Launch).unwrap(), None);
+
+        std::fs::write(dot_minecraft.join("options.txt"), "fov:90\n").unwrap();
+        std::fs::write(dot_minecraft.join("config").join("broken.json"), "{").unwrap();
+
+        let diffs = diff(&root, &dot_minecraft, first).unwrap();
+        let changes: Vec<(&str, ConfigFileChange)> = diffs.iter().map(|diff| (&*diff.path, diff.change)).collect();
+        assert_eq!(changes, [("config/broken.json", ConfigFileChange::Added), ("options.txt", ConfigFileChange::Modified)]);
+        assert_eq!(diffs[1].lines.iter().map(|line| line.kind).collect::<Vec<_>>(), [DiffLineKind::Removed, DiffLineKind::Added]);
+
+        restore(&root, &dot_minecraft, first).unwrap();
+        assert_eq!(std::fs::read_to_string(dot_minecraft.join("options.txt")).unwrap(), "fov:70\n");
+        assert!(!dot_minecraft.join("config").join("broken.json").exists());
+        // The rollback itself can be undone
+        assert_eq!(load(&root).last().unwrap().reason, ConfigSnapshotReason::Rollback);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restore_refuses_symlinks() {
+        let dir = TempDir::new();
+        let root = dir.path().join("instance");
+        let dot_minecraft = root.join(".minecraft");
+        let outside = dir.path().join("outside");
+        std::fs::create_dir_all(dot_minecraft.join("config")).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(dot_minecraft.join("config").join("mod.json"), "{}").unwrap();
+        let first = take(&root, &dot_minecraft, ConfigSnapshotReason::Launch).unwrap().unwrap();
+
+        // config/ is replaced by a link to somewhere outside the instance
+        std::fs::remove_dir_all(dot_minecraft.join("config")).unwrap();
+        std::os::unix::fs::symlink(&outside, dot_minecraft.join("config")).unwrap();
+
+        assert!(restore(&root, &dot_minecraft, first).is_err());
+        assert!(!outside.join("mod.json").exists());
+    }
+
+    #[test]
+    fn diffs_lines() {
+        let kinds: Vec<DiffLineKind> = diff_lines("a\nb\nc", "a\nx\nc\nd").iter().map(|line| line.kind).collect();
+        assert_eq!(kinds, [
+            DiffLineKind::Unchanged, DiffLineKind::Removed, DiffLineKind::Added, DiffLineKind::Unchanged, DiffLineKind::Added,
+        ]);
+    }
+}
@@ -0,0 +1,244 @@
+use std::{collections::HashMap, sync::Arc};
+
+use bridge::{content_source::{ContentFile, ContentSearchHit, ContentSearchQuery, ContentSearchResults, ContentVersion}, instance::ContentType};
+use futures::{future::BoxFuture, FutureExt};
+use schema::{
+    curseforge::{
+        CurseForgeFile, CurseForgeFingerprintMatches, CurseForgeFingerprintsRequest, CurseForgeModLoader, CurseForgeResponse, CurseForgeSearchResult,
+        CURSEFORGE_CLASS_MODPACKS, CURSEFORGE_CLASS_MODS, CURSEFORGE_CLASS_RESOURCEPACKS, CURSEFORGE_CLASS_SHADERS,
+        CURSEFORGE_MINECRAFT_GAME_ID,
+    },
+    loader::Loader, modrinth::{ModrinthLoader, ModrinthProjectType}
+};
+use ustr::Ustr;
+
+use super::{ContentSource, ContentSourceError, LocalContentFile};
+
+/// The API allows at most 50 results per page
+const MAX_PAGE_SIZE: usize = 50;
+
+pub struct CurseForgeSource {
+    pub http_client: reqwest::Client,
+    /// `CURSEFORGE_API_URL` outside of tests
+    pub api_url: Arc<str>,
+    pub api_key: Arc<str>,
+}
+
+/// CurseForge identifies files by a 32-bit MurmurHash2 (seed 1) of their contents with whitespace bytes removed
+pub fn fingerprint(data: &[u8]) -> u32 {
+    const M: u32 = 0x5bd1e995;
+
+    let bytes: Vec<u8> = data.iter().copied().filter(|byte| !matches!(byte, b'\t' | b'\n' | b'\r' | b' ')).collect();
+
+    let mut hash = 1 ^ bytes.len() as u32;
+    let mut chunks = bytes.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes(chunk.try_into().unwrap());
+        k = k.wrapping_mul(M);
+        k ^= k >> 24;
+        k = k.wrapping_mul(M);
+        hash = hash.wrapping_mul(M) ^ k;
+    }
+    let remainder = chunks.remainder();
+    if !remainder.is_empty() {
+        for (index, byte) in remainder.iter().enumerate() {
+            hash ^= (*byte as u32) << (8 * index);
+        }
+        hash = hash.wrapping_mul(M);
+    }
+
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(M);
+    hash ^= hash >> 15;
+    hash
+}
+
+fn mod_loader(loader: Loader) -> Option<CurseForgeModLoader> {
+    match loader {
+        Loader::Fabric => Some(CurseForgeModLoader::Fabric),
+        Loader::Forge => Some(CurseForgeModLoader::Forge),
+        Loader::NeoForge => Some(CurseForgeModLoader::NeoForge),
+        Loader::Vanilla | Loader::Unknown => None,
+    }
+}
+
+fn class_id(project_type: ModrinthProjectType) -> Option<u32> {
+    match project_type {
+        ModrinthProjectType::Mod => Some(CURSEFORGE_CLASS_MODS),
+        ModrinthProjectType::Modpack => Some(CURSEFORGE_CLASS_MODPACKS),
+        ModrinthProjectType::Resourcepack => Some(CURSEFORGE_CLASS_RESOURCEPACKS),
+        ModrinthProjectType::Shader => Some(CURSEFORGE_CLASS_SHADERS),
+        ModrinthProjectType::Other => None,
+    }
+}
+
+/// Files without a SHA-1 can't be verified after downloading, so they aren't offered
+fn content_version(file: &CurseForgeFile) -> Option<ContentVersion> {
+    let sha1 = file.sha1()?.clone();
+    Some(ContentVersion {
+        project_id: file.mod_id.to_string().into(),
+        version_id: file.id.to_string().into(),
+        name: file.display_name.clone(),
+        // Loaders are listed among the game versions
+        loaders: file.game_versions.iter()
+            .map(|name| ModrinthLoader::from_name(name))
+            .filter(|loader| *loader != ModrinthLoader::Unknown)
+            .collect(),
+        files: [ContentFile {
+            url: file.download_url.clone(),
+            filename: file.file_name.clone(),
+            sha1,
+            size: file.file_length,
+            primary: true,
+        }].into(),
+    })
+}
+
+impl CurseForgeSource {
+    fn get(&self, path: &str) -> reqwest::RequestBuilder {
+        self.http_client.get(format!("{}{path}", self.api_url)).header("x-api-key", &*self.api_key)
+    }
+}
+
+impl ContentSource for CurseForgeSource {
+    fn search<'a>(&'a self, query: &'a ContentSearchQuery) -> BoxFuture<'a, Result<ContentSearchResults, ContentSourceError>> {
+        async move {
+            let mut params = vec![
+                ("gameId", CURSEFORGE_MINECRAFT_GAME_ID.to_string()),
+                ("index", query.offset.to_string()),
+                ("pageSize", query.limit.min(MAX_PAGE_SIZE).to_string()),
+                // Sorted by popularity, like the relevance order of Modrinth
+                ("sortField", "2".into()),
+                ("sortOrder", "desc".into()),
+            ];
+            if let Some(class_id) = class_id(query.project_type) {
+                params.push(("classId", class_id.to_string()));
+            }
+            if let Some(search) = &query.query {
+                params.push(("searchFilter", search.to_string()));
+            }
+            if let Some(game_version) = &query.game_version {
+                params.push(("gameVersion", game_version.to_string()));
+            }
+            // Categories are ids on CurseForge, so only Modrinth's category names are filtered by
+            let mod_loaders: Vec<String> = query.loaders.iter()
+                .filter_map(|loader| mod_loader(*loader))
+                .map(|mod_loader| (mod_loader as u32).to_string())
+                .collect();
+            if matches!(query.project_type, ModrinthProjectType::Mod | ModrinthProjectType::Modpack) && !mod_loaders.is_empty() {
+                params.push(("modLoaderTypes", format!("[{}]", mod_loaders.join(","))));
+            }
+
+            let result: CurseForgeSearchResult = self.get("/mods/search").query(&params)
+                .send().await?
+                .error_for_status()?
+                .json().await?;
+
+            Ok(ContentSearchResults {
+                hits: result.data.iter().map(|project| ContentSearchHit {
+                    project_id: project.id.to_string().into(),
+                    project_type: query.project_type,
+                    title: project.name.clone(),
+                    description: project.summary.clone(),
+                    author: project.authors.first().map(|author| author.name.clone()).unwrap_or_default(),
+                    icon_url: project.logo.as_ref().and_then(|logo| logo.thumbnail_url.clone().or(logo.url.clone())),
+                    downloads: project.download_count as usize,
+                    client_side: None,
+                    server_side: None,
+                    categories: Default::default(),
+                }).collect(),
+                total_hits: result.pagination.total_count,
+            })
+        }.boxed()
+    }
+
+    fn version<'a>(&'a self, project_id: &'a str, version_id: &'a str) -> BoxFuture<'a, Result<ContentVersion, ContentSourceError>> {
+        async move {
+            let result: CurseForgeResponse<CurseForgeFile> = self.get(&format!("/mods/{project_id}/files/{version_id}"))
+                .send().await?
+                .error_for_status()?
+                .json().await?;
+            content_version(&result.data).ok_or_else(|| ContentSourceError::DownloadUnavailable(result.data.file_name.clone()))
+        }.boxed()
+    }
+
+    fn resolve_version<'a>(&'a self, project_id: &'a str, loader: Loader, game_version: Option<&'a str>)
+        -> BoxFuture<'a, Result<Option<ContentVersion>, ContentSourceError>>
+    {
+        async move {
+            let mut params = vec![("pageSize", MAX_PAGE_SIZE.to_string())];
+            if let Some(game_version) = game_version {
+                params.push(("gameVersion", game_version.to_string()));
+            }
+            if let Some(mod_loader) = mod_loader(loader) {
+                params.push(("modLoaderType", (mod_loader as u32).to_string()));
+            }
+
+            let result: CurseForgeResponse<Vec<CurseForgeFile>> = self.get(&format!("/mods/{project_id}/files")).query(&params)
+                .send().await?
+                .error_for_status()?
+                .json().await?;
+
+            // File ids increase with every upload, so the highest one is the newest
+            Ok(result.data.iter()
+                .filter_map(|file| Some((file.id, content_version(file)?)))
+                .max_by_key(|(id, _)| *id)
+                .map(|(_, version)| version))
+        }.boxed()
+    }
+
+    fn lookup_hashes<'a>(&'a self, files: &'a [LocalContentFile<'a>]) -> BoxFuture<'a, Result<Vec<Option<ContentVersion>>, ContentSourceError>> {
+        async move {
+            if files.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let fingerprints: Vec<u32> = files.iter().map(|file| fingerprint(file.data)).collect();
+            let result: CurseForgeResponse<CurseForgeFingerprintMatches> = self.http_client
+                .post(format!("{}/fingerprints/{CURSEFORGE_MINECRAFT_GAME_ID}", self.api_url))
+                .header("x-api-key", &*self.api_key)
+                .json(&CurseForgeFingerprintsRequest { fingerprints: &fingerprints })
+                .send().await?
+                .error_for_status()?
+                .json().await?;
+
+            let matches: HashMap<u32, &CurseForgeFile> = result.data.exact_matches.iter()
+                .map(|exact_match| (exact_match.file.file_fingerprint, &exact_match.file))
+                .collect();
+            Ok(fingerprints.iter().map(|fingerprint| matches.get(fingerprint).and_then(|file| content_version(file))).collect())
+        }.boxed()
+    }
+
+    /// Fingerprints are MurmurHash2, not SHA-1, so the project the file was installed from is asked for its newest
+    /// compatible file instead
+    fn find_update<'a>(&'a self, _sha1: &'a str, project_id: Option<&'a str>, content_type: &'a ContentType, loader: Loader, game_version: Ustr)
+        -> BoxFuture<'a, Result<Option<ContentVersion>, ContentSourceError>>
+    {
+        async move {
+            let Some(project_id) = project_id else {
+                return Ok(None);
+            };
+            let loader = match content_type {
+                ContentType::Fabric => Loader::Fabric,
+                ContentType::Forge => Loader::Forge,
+                ContentType::NeoForge => Loader::NeoForge,
+                ContentType::JavaModule => loader,
+                ContentType::ResourcePack => Loader::Vanilla,
+                ContentType::ModrinthModpack { .. } => return Ok(None),
+            };
+            self.resolve_version(project_id, loader, Some(game_version.as_str())).await
+        }.boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_ignores_whitespace() {
+        assert_eq!(fingerprint(b""), 0x5bd15e36);
+        assert_eq!(fingerprint(b"fabric.mod.json"), fingerprint(b"fabric .mod\r\n.json\t"));
+        assert_ne!(fingerprint(b"abcd"), fingerprint(b"abce"));
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use bridge::{content_source::{ContentFile, ContentSearchQuery, ContentSearchResults, ContentSourceId, ContentVersion}, instance::ContentType};
+use futures::{future::BoxFuture, FutureExt};
+use schema::{content::ContentSource as InstalledContentSource, curseforge::CURSEFORGE_API_URL, loader::Loader};
+use ustr::Ustr;
+
+use crate::{metadata::manager::MetaLoadError, mod_metadata::ModUpdateAction, BackendState};
+
+pub(crate) mod curseforge;
+mod modrinth;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ContentSourceError {
+    #[error("Failed to load metadata:\n{0}")]
+    MetaLoadError(#[from] MetaLoadError),
+    #[error("Request failed:\n{0}")]
+    Reqwest(#[from] reqwest::Error),
+    #[error("{0} requires an API key, it can be set in the settings")]
+    MissingApiKey(&'static str),
+    #[error("{0} can't be downloaded by launchers, it has to be downloaded from the website")]
+    DownloadUnavailable(Arc<str>),
+}
+
+/// A file on disk that a source is asked to identify
+pub struct LocalContentFile<'a> {
+    pub sha1: &'a str,
+    pub data: &'a [u8],
+}
+
+/// A service that content can be searched for and installed from. Searching, installing, checking for updates and
+/// identifying files when exporting a modpack go through this trait, so a new source only needs an implementation and
+/// a [`ContentSourceId`]
+pub trait ContentSource: Send + Sync {
+    fn search<'a>(&'a self, query: &'a ContentSearchQuery) -> BoxFuture<'a, Result<ContentSearchResults, ContentSourceError>>;
+
+    fn version<'a>(&'a self, project_id: &'a str, version_id: &'a str) -> BoxFuture<'a, Result<ContentVersion, ContentSourceError>>;
+
+    /// Finds the newest version of the project that works with the loader and Minecraft version
+    fn resolve_version<'a>(&'a self, project_id: &'a str, loader: Loader, game_version: Option<&'a str>)
+        -> BoxFuture<'a, Result<Option<ContentVersion>, ContentSourceError>>;
+
+    /// Finds the version each file was downloaded from, in the same order as `files`
+    fn lookup_hashes<'a>(&'a self, files: &'a [LocalContentFile<'a>]) -> BoxFuture<'a, Result<Vec<Option<ContentVersion>>, ContentSourceError>>;
+
+    /// Finds the newest version of the project an installed file was downloaded from that works with the loader and
+    /// Minecraft version, None if the source doesn't know the file. `project_id` is the project the file was recorded
+    /// with, sources that can't look files up by their SHA-1 use it instead
+    fn find_update<'a>(&'a self, sha1: &'a str, project_id: Option<&'a str>, content_type: &'a ContentType, loader: Loader, game_version: Ustr)
+        -> BoxFuture<'a, Result<Option<ContentVersion>, ContentSourceError>>
+    {
+        _ = (sha1, project_id, content_type, loader, game_version);
+        async { Ok(None) }.boxed()
+    }
+
+    /// The URL that `file` is downloaded from
+    fn download_url(&self, file: &ContentFile) -> Result<Arc<str>, ContentSourceError> {
+        file.url.clone().ok_or_else(|| ContentSourceError::DownloadUnavailable(file.filename.clone()))
+    }
+}
+
+/// The source that installed content is recorded with, which is how updates find it again
+pub fn installed_content_source(source: ContentSourceId, project_id: Arc<str>) -> InstalledContentSource {
+    match source {
+        ContentSourceId::Modrinth => InstalledContentSource::ModrinthProject { project: project_id },
+        ContentSourceId::CurseForge => InstalledContentSource::CurseForgeProject { project: project_id },
+    }
+}
+
+/// The source to check for updates of content that was recorded with `source`
+pub fn update_source(source: &InstalledContentSource) -> Option<ContentSourceId> {
+    match source {
+        InstalledContentSource::Manual => None,
+        InstalledContentSource::ModrinthUnknown | InstalledContentSource::ModrinthProject { .. } => Some(ContentSourceId::Modrinth),
+        InstalledContentSource::CurseForgeProject { .. } => Some(ContentSourceId::CurseForge),
+    }
+}
+
+/// The project that content was recorded with, if the source knows it
+pub fn installed_project(source: &InstalledContentSource) -> Option<&Arc<str>> {
+    match source {
+        InstalledContentSource::Manual | InstalledContentSource::ModrinthUnknown => None,
+        InstalledContentSource::ModrinthProject { project } | InstalledContentSource::CurseForgeProject { project } => Some(project),
+    }
+}
+
+/// Asks `source` for the newest version of installed content and decides what updating it would do
+pub async fn find_update_action(source_id: ContentSourceId, source: &dyn ContentSource, installed_source: &InstalledContentSource,
+    hash: [u8; 20], content_type: &ContentType, loader: Loader, game_version: Ustr) -> Result<ModUpdateAction, ContentSourceError>
+{
+    let sha1 = hex::encode(hash);
+    let installed_project = installed_project(installed_source);
+    let Some(latest) = source.find_update(&sha1, installed_project.map(|project| &**project), content_type, loader, game_version).await? else {
+        return Ok(ModUpdateAction::ErrorNotFound);
+    };
+
+    if let Some(project) = installed_project {
+        if &latest.project_id != project {
+            tracing::error!("Refusing to update {:?}, mismatched project ids: expected {}, got {}",
+                hash, &project, &latest.project_id);
+            return Ok(ModUpdateAction::ErrorNotFound);
+        }
+    }
+
+    let Some(install_file) = latest.primary_file() else {
+        return Ok(ModUpdateAction::ErrorNotFound);
+    };
+
+    let mut latest_hash = [0u8; 20];
+    let Ok(_) = hex::decode_to_slice(&*install_file.sha1, &mut latest_hash) else {
+        return Ok(ModUpdateAction::ErrorInvalidHash);
+    };
+
+    if latest_hash == hash {
+        Ok(ModUpdateAction::AlreadyUpToDate)
+    } else {
+        Ok(ModUpdateAction::Update {
+            source: source_id,
+            file: install_file.clone(),
+            project_id: latest.project_id.clone(),
+        })
+    }
+}
+
+impl BackendState {
+    pub fn content_source(&self, id: ContentSourceId) -> Result<Box<dyn ContentSource>, ContentSourceError> {
+        match id {
+            ContentSourceId::Modrinth => Ok(Box::new(modrinth::ModrinthSource {
+                meta: self.meta.clone(),
+                http_client: self.http_client.clone(),
+            })),
+            ContentSourceId::CurseForge => {
+                let Some(api_key) = self.config.write().get().curseforge_api_key.clone() else {
+                    return Err(ContentSourceError::MissingApiKey(id.name()));
+                };
+                Ok(Box::new(curseforge::CurseForgeSource {
+                    http_client: self.http_client.clone(),
+                    api_url: CURSEFORGE_API_URL.into(),
+                    api_key,
+                }))
+            },
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::{collections::HashMap, sync::Arc};
+
+use bridge::{content_source::{ContentFile, ContentSearchHit, ContentSearchQuery, ContentSearchResults, ContentVersion}, instance::ContentType};
+use futures::{future::BoxFuture, FutureExt};
+use schema::{
+    loader::Loader, modrinth::{ModrinthLoader, ModrinthProjectType, ModrinthProjectVersion, ModrinthProjectVersionsRequest, ModrinthSearchIndex, ModrinthSearchRequest}
+};
+use serde::Serialize;
+use ustr::Ustr;
+
+use crate::metadata::{
+    items::{
+        ModrinthProjectVersionsMetadataItem, ModrinthSearchMetadataItem, ModrinthV3VersionUpdateMetadataItem, ModrinthVersionMetadataItem,
+        ModrinthVersionUpdateMetadataItem, VersionUpdateParameters, VersionV3LoaderFields, VersionV3UpdateParameters
+    },
+    manager::{MetaLoadError, MetadataManager}
+};
+
+use super::{ContentSource, ContentSourceError, LocalContentFile};
+
+pub struct ModrinthSource {
+    pub meta: Arc<MetadataManager>,
+    pub http_client: reqwest::Client,
+}
+
+#[derive(Serialize)]
+struct VersionFilesRequest<'a> {
+    hashes: &'a [&'a str],
+    algorithm: &'static str,
+}
+
+fn content_version(version: &ModrinthProjectVersion) -> ContentVersion {
+    ContentVersion {
+        project_id: version.project_id.clone(),
+        version_id: version.id.clone(),
+        name: version.name.clone(),
+        loaders: version.loaders.clone().unwrap_or_default(),
+        files: version.files.iter().map(|file| ContentFile {
+            url: Some(file.url.clone()),
+            filename: file.filename.clone(),
+            sha1: file.hashes.sha1.clone(),
+            size: file.size,
+            primary: file.primary,
+        }).collect(),
+    }
+}
+
+/// Facets in the same group match any, separate groups all have to match
+fn search_facets(query: &ContentSearchQuery) -> String {
+    let mut facets = vec![format!("[\"project_type:{}\"]", query.project_type.as_str())];
+    let loaders: Vec<String> = query.loaders.iter()
+        .map(|loader| loader.as_modrinth_loader())
+        .filter(|loader| *loader != ModrinthLoader::Unknown)
+        .map(|loader| format!("\"categories:{}\"", loader.id()))
+        .collect();
+    if matches!(query.project_type, ModrinthProjectType::Mod | ModrinthProjectType::Modpack) && !loaders.is_empty() {
+        facets.push(format!("[{}]", loaders.join(",")));
+    }
+    for category in query.categories.iter() {
+        facets.push(format!("[\"categories:{category}\"]"));
+    }
+    if let Some(game_version) = &query.game_version {
+        facets.push(format!("[\"versions:{game_version}\"]"));
+    }
+    format!("[{}]", facets.join(","))
+}
+
+impl ContentSource for ModrinthSource {
+    fn search<'a>(&'a self, query: &'a ContentSearchQuery) -> BoxFuture<'a, Result<ContentSearchResults, ContentSourceError>> {
+        async move {
+            let request = ModrinthSearchRequest {
+                query: query.query.clone(),
+                facets: Some(search_facets(query).into()),
+                index: ModrinthSearchIndex::Relevance,
+                offset: query.offset,
+                limit: query.limit,
+            };
+            let result = self.meta.fetch(&ModrinthSearchMetadataItem(&request)).await?;
+
+            Ok(ContentSearchResults {
+                hits: result.hits.iter().map(|hit| ContentSearchHit {
+                    project_id: hit.project_id.clone(),
+                    project_type: hit.project_type,
+                    title: hit.title.clone().unwrap_or_else(|| hit.project_id.clone()),
+                    description: hit.description.clone(),
+                    author: hit.author.clone(),
+                    icon_url: hit.icon_url.clone(),
+                    downloads: hit.downloads,
+                    client_side: hit.client_side,
+                    server_side: hit.server_side,
+                    categories: hit.display_categories.clone().unwrap_or_default(),
+                }).collect(),
+                total_hits: result.total_hits,
+            })
+        }.boxed()
+    }
+
+    fn version<'a>(&'a self, _project_id: &'a str, version_id: &'a str) -> BoxFuture<'a, Result<ContentVersion, ContentSourceError>> {
+        async move {
+            let version = self.meta.fetch(&ModrinthVersionMetadataItem(version_id.into())).await?;
+            Ok(content_version(&version))
+        }.boxed()
+    }
+
+    fn resolve_version<'a>(&'a self, project_id: &'a str, loader: Loader, game_version: Option<&'a str>)
+        -> BoxFuture<'a, Result<Option<ContentVersion>, ContentSourceError>>
+    {
+        async move {
+            let versions = self.meta.fetch(&ModrinthProjectVersionsMetadataItem(&ModrinthProjectVersionsRequest {
+                project_id: project_id.into(),
+                game_versions: game_version.map(|version| [version.into()].into()),
+                loaders: None,
+            })).await?;
+
+            // Versions without a matching loader are still used, e.g. resource packs and datapacks don't have one
+            let modrinth_loader = loader.as_modrinth_loader();
+            let version = if modrinth_loader != ModrinthLoader::Unknown {
+                versions.0.iter()
+                    .find(|version| version.loaders.as_ref().is_some_and(|loaders| loaders.contains(&modrinth_loader)))
+                    .or(versions.0.first())
+            } else {
+                versions.0.first()
+            };
+
+            Ok(version.map(content_version))
+        }.boxed()
+    }
+
+    fn lookup_hashes<'a>(&'a self, files: &'a [LocalContentFile<'a>]) -> BoxFuture<'a, Result<Vec<Option<ContentVersion>>, ContentSourceError>> {
+        async move {
+            if files.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let hashes: Vec<&str> = files.iter().map(|file| file.sha1).collect();
+            let versions: HashMap<String, ModrinthProjectVersion> = self.http_client.post("https://api.modrinth.com/v2/version_files")
+                .json(&VersionFilesRequest { hashes: &hashes, algorithm: "sha1" })
+                .send().await?
+                .error_for_status()?
+                .json().await?;
+
+            Ok(files.iter().map(|file| versions.get(file.sha1).map(content_version)).collect())
+        }.boxed()
+    }
+
+    fn find_update<'a>(&'a self, sha1: &'a str, _project_id: Option<&'a str>, content_type: &'a ContentType, loader: Loader, game_version: Ustr)
+        -> BoxFuture<'a, Result<Option<ContentVersion>, ContentSourceError>>
+    {
+        async move {
+            let modrinth_loader = loader.as_modrinth_loader();
+            let params = |loader: ModrinthLoader| VersionUpdateParameters {
+                loaders: [loader].into(),
+                game_versions: [game_version].into(),
+            };
+
+            let result = match content_type {
+                ContentType::Fabric => self.meta.fetch(&ModrinthVersionUpdateMetadataItem { sha1: sha1.into(), params: params(ModrinthLoader::Fabric) }).await,
+                ContentType::Forge => self.meta.fetch(&ModrinthVersionUpdateMetadataItem { sha1: sha1.into(), params: params(ModrinthLoader::Forge) }).await,
+                ContentType::NeoForge => self.meta.fetch(&ModrinthVersionUpdateMetadataItem { sha1: sha1.into(), params: params(ModrinthLoader::NeoForge) }).await,
+                ContentType::JavaModule => self.meta.fetch(&ModrinthVersionUpdateMetadataItem { sha1: sha1.into(), params: params(modrinth_loader) }).await,
+                ContentType::ResourcePack => self.meta.fetch(&ModrinthVersionUpdateMetadataItem { sha1: sha1.into(), params: params(ModrinthLoader::Minecraft) }).await,
+                ContentType::ModrinthModpack { .. } => {
+                    self.meta.fetch(&ModrinthV3VersionUpdateMetadataItem {
+                        sha1: sha1.into(),
+                        params: VersionV3UpdateParameters {
+                            loaders: ["mrpack".into()].into(),
+                            loader_fields: VersionV3LoaderFields {
+                                mrpack_loaders: [modrinth_loader].into(),
+                                game_versions: [game_version].into(),
+                            },
+                        },
+                    }).await
+                },
+            };
+
+            match result {
+                Ok(result) => Ok(Some(content_version(&result.0))),
+                Err(MetaLoadError::NonOK(404)) => Ok(None),
+                Err(error) => Err(error.into()),
+            }
+        }.boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn facets_match_any_loader_and_every_category() {
+        let query = ContentSearchQuery {
+            query: None,
+            project_type: ModrinthProjectType::Mod,
+            loaders: [Loader::Fabric, Loader::Vanilla].into(),
+            categories: ["optimization".into(), "utility".into()].into(),
+            game_version: Some("1.21.1".into()),
+            offset: 0,
+            limit: 20,
+        };
+        assert_eq!(search_facets(&query), concat!(
+            r#"[["project_type:mod"],["categories:fabric"],["categories:optimization"],["categories:utility"],"#,
+            r#"["versions:1.21.1"]]"#,
+        ));
+    }
+}
@@ -0,0 +1,108 @@
+use std::{io::{BufRead, BufReader}, path::{Path, PathBuf}, sync::Arc, time::SystemTime};
+
+const MAX_EXCERPT_LINES: usize = 40;
+
+pub struct CrashReport {
+    pub path: PathBuf,
+    pub excerpt: Arc<str>,
+}
+
+/// Finds the newest crash report or JVM error log written since the game was started
+pub fn find_crash_report(dot_minecraft: &Path, since: SystemTime) -> Option<CrashReport> {
+    let mut newest: Option<(SystemTime, PathBuf)> = None;
+
+    let mut consider = |path: PathBuf| {
+        let Ok(modified) = path.metadata().and_then(|metadata| metadata.modified()) else {
+            return;
+        };
+        if modified < since {
+            return;
+        }
+        if newest.as_ref().is_none_or(|(newest_modified, _)| modified > *newest_modified) {
+            newest = Some((modified, path));
+        }
+    };
+
+    if let Ok(read_dir) = std::fs::read_dir(dot_minecraft.join("crash-reports")) {
+        for entry in read_dir.flatten() {
+            let path = entry.path();
+            let is_bundle = entry.file_name().to_string_lossy().starts_with("pandora-");
+            if path.extension().is_some_and(|extension| extension == "txt") && !is_bundle {
+                consider(path);
+            }
+        }
+    }
+
+    if let Ok(read_dir) = std::fs::read_dir(dot_minecraft) {
+        for entry in read_dir.flatten() {
+            if entry.file_name().to_string_lossy().starts_with("hs_err_pid") {
+                consider(entry.path());
+            }
+        }
+    }
+
+    let (_, path) = newest?;
+    let excerpt = read_excerpt(&path).unwrap_or_default();
+
+    Some(CrashReport {
+        path,
+        excerpt: excerpt.into(),
+    })
+}
+
+/// Writes a single file with the system info, including GPU and driver, followed by the full crash report
+pub fn write_bundle(dot_minecraft: &Path, exit_code: Option<i32>, crash_report: Option<&CrashReport>, system_info: &str) -> std::io::Result<PathBuf> {
+    let timestamp = chrono::Local::now().format("%Y-%m-%d_%H.%M.%S");
+    let dir = dot_minecraft.join("crash-reports");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("pandora-{timestamp}.txt"));
+
+    let mut bundle = String::new();
+    bundle.push_str("---- Pandora Crash Bundle ----\n");
+    match exit_code {
+        Some(code) => bundle.push_str(&format!("Exit code: {code}\n")),
+        None => bundle.push_str("Exit code: unknown\n"),
+    }
+    bundle.push_str(system_info);
+
+    if let Some(crash_report) = crash_report {
+        bundle.push_str(&format!("\n---- {} ----\n", crash_report.path.display()));
+        match std::fs::read_to_string(&crash_report.path) {
+            Ok(contents) => bundle.push_str(&contents),
+            Err(error) => bundle.push_str(&format!("Unable to read crash report: {error}\n")),
+        }
+    }
+
+    crate::write_safe(&path, bundle.as_bytes())?;
+    Ok(path)
+}
+
+fn read_excerpt(path: &Path) -> std::io::Result<String> {
+    let reader = BufReader::new(std::fs::File::open(path)?);
+
+    let mut excerpt = String::new();
+    let mut started = false;
+    let mut lines = 0;
+
+    for line in reader.lines() {
+        let line = line?;
+
+        // Skip the joke header of Minecraft crash reports, the interesting part starts at the description
+        if !started {
+            if line.starts_with("---- Minecraft Crash Report") || line.starts_with("// ") || line.trim().is_empty() {
+                continue;
+            }
+            started = true;
+        }
+
+        excerpt.push_str(&line);
+        excerpt.push('\n');
+
+        lines += 1;
+        if lines >= MAX_EXCERPT_LINES {
+            break;
+        }
+    }
+
+    Ok(excerpt)
+}
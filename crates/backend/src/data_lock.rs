@@ -0,0 +1,73 @@
+//! Advisory locks that stop two launcher processes from writing to the same launcher directory or instance at once
+
+use std::{path::Path, sync::{Arc, OnceLock}};
+
+use crate::lockfile::Lockfile;
+
+/// Held for as long as the process runs
+static LAUNCHER_DIR_LOCK: OnceLock<Lockfile> = OnceLock::new();
+
+/// Set when another process holds the launcher directory, nothing inside it is written to
+static READ_ONLY_DIR: OnceLock<Arc<Path>> = OnceLock::new();
+
+/// Locks the launcher directory for this process. Returns false if another process is already using it
+pub fn lock_launcher_dir(launcher_dir: &Path) -> std::io::Result<bool> {
+    if LAUNCHER_DIR_LOCK.get().is_some() {
+        return Ok(true);
+    }
+    std::fs::create_dir_all(launcher_dir)?;
+    match Lockfile::try_create(launcher_dir.join("launcher.lock").into())? {
+        Some(lock) => {
+            _ = LAUNCHER_DIR_LOCK.set(lock);
+            Ok(true)
+        },
+        None => Ok(false),
+    }
+}
+
+/// Opens the launcher directory without writing to it, for when another process holds its lock
+pub fn set_read_only(launcher_dir: &Path) {
+    _ = READ_ONLY_DIR.set(launcher_dir.into());
+}
+
+pub fn is_read_only() -> bool {
+    READ_ONLY_DIR.get().is_some()
+}
+
+/// Refuses writes into the launcher directory while it's open read-only. Writes elsewhere, like exports chosen by
+/// the user, are still allowed
+pub fn check_writable(path: &Path) -> std::io::Result<()> {
+    match READ_ONLY_DIR.get() {
+        Some(launcher_dir) if path.starts_with(launcher_dir) => Err(std::io::Error::new(
+            std::io::ErrorKind::ReadOnlyFilesystem,
+            "the launcher directory is open read-only because another launcher is using it",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Copies of an instance skip this file, on Windows it can't even be read while locked
+pub(crate) const INSTANCE_LOCK_FILENAME: &str = ".instance.lock";
+
+/// Locks an instance while it's being launched or played. Returns None if it's in use by another launcher
+pub(crate) fn lock_instance(root_path: &Path) -> std::io::Result<Option<Lockfile>> {
+    Lockfile::try_create(root_path.join(INSTANCE_LOCK_FILENAME).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::TempDir;
+
+    #[test]
+    fn instance_lock_is_exclusive_until_dropped() {
+        let dir = TempDir::new();
+
+        let lock = lock_instance(dir.path()).unwrap();
+        assert!(lock.is_some());
+        assert!(lock_instance(dir.path()).unwrap().is_none());
+
+        drop(lock);
+        assert!(lock_instance(dir.path()).unwrap().is_some());
+    }
+}
@@ -19,12 +19,18 @@ pub struct LauncherDirectories {
     pub content_library_dir: Arc<Path>,
     pub content_meta_dir: Arc<Path>,
 
+    pub skins_dir: Arc<Path>,
+    pub instance_icons_dir: Arc<Path>,
+
     pub temp_dir: Arc<Path>,
     pub temp_natives_base_dir: Arc<Path>,
 
     pub root_launcher_dir: Arc<Path>,
     pub config_json: Arc<Path>,
     pub accounts_json: Arc<Path>,
+    pub playtime_json: Arc<Path>,
+    pub verified_files_json: Arc<Path>,
+    pub library_references_json: Arc<Path>,
 }
 
 impl LauncherDirectories {
@@ -49,11 +55,17 @@ impl LauncherDirectories {
         let content_library_dir = launcher_dir.join("contentlibrary");
         let content_meta_dir = launcher_dir.join("contentmeta");
 
+        let skins_dir = launcher_dir.join("skins");
+        let instance_icons_dir = launcher_dir.join("instanceicons");
+
         let temp_dir = launcher_dir.join("temp");
         let temp_natives_base_dir = temp_dir.join("natives");
 
         let config_json = launcher_dir.join("config.json");
         let accounts_json = launcher_dir.join("accounts.json");
+        let playtime_json = launcher_dir.join("playtime.json");
+        let verified_files_json = launcher_dir.join("verified_files.json");
+        let library_references_json = launcher_dir.join("library_references.json");
 
         Self {
             instances_dir: instances_dir.into(),
@@ -74,12 +86,18 @@ impl LauncherDirectories {
             content_library_dir: content_library_dir.into(),
             content_meta_dir: content_meta_dir.into(),
 
+            skins_dir: skins_dir.into(),
+            instance_icons_dir: instance_icons_dir.into(),
+
             temp_dir: temp_dir.into(),
             temp_natives_base_dir: temp_natives_base_dir.into(),
 
             root_launcher_dir: launcher_dir.into(),
             config_json: config_json.into(),
             accounts_json: accounts_json.into(),
+            playtime_json: playtime_json.into(),
+            verified_files_json: verified_files_json.into(),
+            library_references_json: library_references_json.into(),
         }
     }
 }
@@ -0,0 +1,150 @@
+use std::path::{Path, PathBuf};
+
+use bridge::message::DownloadConditions;
+
+use crate::file_verification::FileState;
+
+/// Kept free on top of what a download needs, a completely full disk breaks the game and other programs
+const SPACE_MARGIN: u64 = 256 * 1024 * 1024;
+
+#[derive(thiserror::Error, Debug)]
+#[error("Not enough disk space in {}: {} is needed, but only {} is available", path.display(), format_bytes(*required), format_bytes(*available))]
+pub struct InsufficientSpace {
+    pub path: PathBuf,
+    pub required: u64,
+    pub available: u64,
+}
+
+pub fn query(launcher_dir: &Path) -> DownloadConditions {
+    DownloadConditions {
+        available_space: available_space(launcher_dir),
+        metered: is_metered_connection().unwrap_or(false),
+    }
+}
+
+/// Fails before anything is downloaded when the filesystem that `path` is on can't fit `required` more bytes, instead of
+/// running into an I/O error halfway through. Passes when the available space can't be determined
+pub fn ensure_space(path: &Path, required: u64) -> Result<(), InsufficientSpace> {
+    if required == 0 {
+        return Ok(());
+    }
+    // The target directory is usually only created by the download itself
+    let available = path.ancestors().find(|path| path.exists()).and_then(available_space);
+    check_space(path, required, available)
+}
+
+fn check_space(path: &Path, required: u64, available: Option<u64>) -> Result<(), InsufficientSpace> {
+    let Some(available) = available else {
+        return Ok(());
+    };
+    if available < required.saturating_add(SPACE_MARGIN) {
+        return Err(InsufficientSpace { path: path.to_path_buf(), required, available });
+    }
+    Ok(())
+}
+
+/// Bytes still to be written for the files that verification found missing or invalid
+pub fn missing_size(sizes: &[u64], states: &[FileState]) -> u64 {
+    sizes.iter().zip(states).filter(|(_, state)| **state != FileState::Valid).map(|(size, _)| size).sum()
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+    let mib = bytes as f64 / MIB;
+    if mib >= 1024.0 {
+        format!("{:.1} GiB", mib / 1024.0)
+    } else {
+        format!("{mib:.1} MiB")
+    }
+}
+
+/// Returns the number of bytes available to the current user on the filesystem containing `path`
+#[cfg(unix)]
+pub fn available_space(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(windows)]
+pub fn available_space(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut available = 0u64;
+    if unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, std::ptr::null_mut(), std::ptr::null_mut()) } == 0 {
+        return None;
+    }
+    Some(available)
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn available_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Asks NetworkManager whether the primary connection is metered. Returns None if the OS doesn't expose it
+#[cfg(target_os = "linux")]
+fn is_metered_connection() -> Option<bool> {
+    let output = std::process::Command::new("busctl")
+        .args([
+            // Reading the property needs no privileges, this keeps polkit from ever prompting for a password
+            "--allow-interactive-authorization=no",
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ])
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    // Output looks like "u 1", see NMMetered: 1 = yes, 3 = guess yes
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let value: u32 = stdout.trim().strip_prefix("u ")?.parse().ok()?;
+    Some(value == 1 || value == 3)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_metered_connection() -> Option<bool> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn space_is_checked_for_missing_files_only() {
+        let sizes = [100, 200, 300];
+        let states = [FileState::Valid, FileState::Missing, FileState::Invalid];
+        assert_eq!(missing_size(&sizes, &states), 500);
+
+        let dir = crate::test_harness::TempDir::new();
+        let target = dir.path().join("not").join("created").join("yet");
+        assert!(ensure_space(&target, 0).is_ok());
+
+        let available = 1024 * 1024 * 1024;
+        assert!(check_space(&target, 1024, Some(available)).is_ok());
+        assert!(check_space(&target, u64::MAX, None).is_ok());
+        // The margin has to stay free as well
+        assert!(check_space(&target, available - SPACE_MARGIN / 2, Some(available)).is_err());
+
+        let error = check_space(&target, u64::MAX / 2, Some(available)).unwrap_err();
+        assert_eq!(error.path, target);
+        assert_eq!(error.available, available);
+    }
+}
@@ -0,0 +1,360 @@
+use std::{path::{Path, PathBuf}, sync::{atomic::{AtomicU32, AtomicUsize, Ordering}, Arc}, time::{Duration, Instant}};
+
+use bridge::modal_action::ProgressTracker;
+use parking_lot::Mutex;
+use reqwest::StatusCode;
+use schema::backend_config::DownloadLimits;
+use tokio::{io::AsyncWriteExt, sync::{Semaphore, SemaphorePermit}};
+
+/// Limit max concurrent connections to 8 by default to avoid ratelimiting issues
+pub const DEFAULT_MAX_CONCURRENT_DOWNLOADS: usize = 8;
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+#[derive(thiserror::Error, Debug)]
+pub enum DownloadError {
+    #[error("Failed to load remote content")]
+    Reqwest(#[from] reqwest::Error),
+    #[error("Server responded with {0}")]
+    Status(StatusCode),
+    #[error("Failed to write downloaded data: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl DownloadError {
+    fn is_transient(&self) -> bool {
+        match self {
+            DownloadError::Reqwest(error) => error.is_timeout() || error.is_connect() || error.is_body() || error.is_request(),
+            DownloadError::Status(status) => *status == StatusCode::TOO_MANY_REQUESTS
+                || *status == StatusCode::RANGE_NOT_SATISFIABLE
+                || status.is_server_error(),
+            DownloadError::Io(_) => false,
+        }
+    }
+}
+
+/// Queue that every file download goes through, so that the concurrency and bandwidth limits apply across
+/// assets, libraries, Java runtimes and content at the same time
+#[derive(Clone)]
+pub struct DownloadManager {
+    inner: Arc<DownloadManagerInner>,
+}
+
+struct DownloadManagerInner {
+    slots: Semaphore,
+    max_concurrent: Mutex<usize>,
+    /// Permits to discard once they're released, when the limit was lowered while downloads were running
+    excess_permits: AtomicUsize,
+    max_retries: AtomicU32,
+    throttle: Mutex<Throttle>,
+}
+
+/// The file that received bytes are written to as they arrive. It's kept between attempts so that a retry can ask
+/// the server for only the rest
+struct Destination {
+    file: tokio::fs::File,
+    len: u64,
+}
+
+impl Destination {
+    fn len(&self) -> u64 {
+        self.len
+    }
+
+    async fn append(&mut self, chunk: &[u8]) -> std::io::Result<()> {
+        self.file.write_all(chunk).await?;
+        self.len += chunk.len() as u64;
+        Ok(())
+    }
+
+    async fn clear(&mut self) -> std::io::Result<()> {
+        self.file.flush().await?;
+        self.file.set_len(0).await?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+struct Throttle {
+    bytes_per_second: Option<f64>,
+    /// Bytes that can be received without waiting, negative when over budget
+    budget: f64,
+    updated: Instant,
+}
+
+impl Throttle {
+    /// Takes `bytes` out of the budget, returning how long to wait until it has been paid back
+    fn take(&mut self, bytes: usize) -> Duration {
+        let Some(rate) = self.bytes_per_second else {
+            return Duration::ZERO;
+        };
+
+        let now = Instant::now();
+        self.budget = (self.budget + now.duration_since(self.updated).as_secs_f64() * rate).min(rate);
+        self.updated = now;
+        self.budget -= bytes as f64;
+
+        if self.budget < 0.0 {
+            Duration::from_secs_f64(-self.budget / rate)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+struct Slot<'a> {
+    permit: Option<SemaphorePermit<'a>>,
+    excess_permits: &'a AtomicUsize,
+}
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        let discard = self.excess_permits.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |excess| excess.checked_sub(1)).is_ok();
+        if discard && let Some(permit) = self.permit.take() {
+            permit.forget();
+        }
+    }
+}
+
+fn max_concurrent(limits: DownloadLimits) -> usize {
+    limits.max_concurrent.map(|max| max.max(1) as usize).unwrap_or(DEFAULT_MAX_CONCURRENT_DOWNLOADS)
+}
+
+fn max_retries(limits: DownloadLimits) -> u32 {
+    limits.max_retries.unwrap_or(DEFAULT_MAX_RETRIES)
+}
+
+/// Path that a file is downloaded to before it's complete
+pub fn part_path(path: &Path) -> PathBuf {
+    let mut part_path = path.to_path_buf().into_os_string();
+    part_path.push(".part");
+    PathBuf::from(part_path)
+}
+
+/// Removes `counted` bytes from the progress again, when the data they were counted for was thrown away
+fn set_back(progress: Option<&ProgressTracker>, counted: &mut usize) {
+    if let Some(progress) = progress && *counted > 0 {
+        progress.sub_count(*counted as u64);
+        progress.notify();
+    }
+    *counted = 0;
+}
+
+fn bytes_per_second(limits: DownloadLimits) -> Option<f64> {
+    limits.max_kib_per_second.filter(|kib| *kib > 0).map(|kib| kib as f64 * 1024.0)
+}
+
+impl DownloadManager {
+    pub fn new(limits: DownloadLimits) -> Self {
+        let max_concurrent = max_concurrent(limits);
+        Self {
+            inner: Arc::new(DownloadManagerInner {
+                slots: Semaphore::new(max_concurrent),
+                max_concurrent: Mutex::new(max_concurrent),
+                excess_permits: AtomicUsize::new(0),
+                max_retries: AtomicU32::new(max_retries(limits)),
+                throttle: Mutex::new(Throttle {
+                    bytes_per_second: bytes_per_second(limits),
+                    budget: 0.0,
+                    updated: Instant::now(),
+                }),
+            }),
+        }
+    }
+
+    /// Applies new limits, downloads that are already running keep going until they finish
+    pub fn set_limits(&self, limits: DownloadLimits) {
+        let new_max = max_concurrent(limits);
+        let mut current_max = self.inner.max_concurrent.lock();
+
+        if new_max > *current_max {
+            let mut added = new_max - *current_max;
+            let cancelled = self.inner.excess_permits.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |excess| {
+                Some(excess.saturating_sub(added))
+            }).unwrap();
+            added -= cancelled.min(added);
+            self.inner.slots.add_permits(added);
+        } else if new_max < *current_max {
+            let removed = *current_max - new_max;
+            let forgotten = self.inner.slots.forget_permits(removed);
+            self.inner.excess_permits.fetch_add(removed - forgotten, Ordering::SeqCst);
+        }
+        *current_max = new_max;
+
+        self.inner.max_retries.store(max_retries(limits), Ordering::Relaxed);
+        self.inner.throttle.lock().bytes_per_second = bytes_per_second(limits);
+    }
+
+    async fn acquire_slot(&self) -> Slot<'_> {
+        Slot {
+            permit: Some(self.inner.slots.acquire().await.unwrap()),
+            excess_permits: &self.inner.excess_permits,
+        }
+    }
+
+    /// Downloads `url` to `path` through a `.part` file that is only renamed once complete, retrying transient failures
+    /// with exponential backoff. A `.part` file left behind by an earlier failed download is resumed instead of
+    /// starting over. Mojang urls are rewritten to the configured mirror. When given, `progress` is advanced as bytes
+    /// arrive and set back when the download fails
+    pub async fn download_file(&self, client: &reqwest::Client, url: &str, path: &Path, progress: Option<&ProgressTracker>) -> Result<(), DownloadError> {
+        let part_path = part_path(path);
+        let file = tokio::fs::OpenOptions::new().create(true).append(true).open(&part_path).await?;
+        let len = file.metadata().await?.len();
+
+        let mut destination = Destination { file, len };
+        let result = self.download(client, url, &mut destination, progress).await;
+        destination.file.flush().await?;
+        drop(destination);
+
+        match result {
+            Ok(()) => {
+                tokio::fs::rename(&part_path, path).await?;
+                Ok(())
+            },
+            Err(error) => {
+                // Keep the partial data only when it can be continued later
+                if !error.is_transient() {
+                    _ = tokio::fs::remove_file(&part_path).await;
+                }
+                Err(error)
+            },
+        }
+    }
+
+    /// Goes through the configured mirror first, if there is one, and falls back to Mojang's servers when it fails
+    async fn download(&self, client: &reqwest::Client, url: &str, destination: &mut Destination, progress: Option<&ProgressTracker>) -> Result<(), DownloadError> {
+        if let Some(progress) = progress {
+            progress.set_counts_bytes();
+        }
+
+        let mut candidates = crate::endpoints::candidates(url).peekable();
+        loop {
+            let candidate = candidates.next().unwrap();
+            match self.download_from(client, &candidate, destination, progress).await {
+                Err(error) if !matches!(error, DownloadError::Io(_)) && candidates.peek().is_some() => {
+                    tracing::warn!("Download from mirror {candidate} failed, falling back to {url}: {error}");
+                    // The mirror may have served something other than the official file
+                    destination.clear().await?;
+                },
+                result => return result,
+            }
+        }
+    }
+
+    async fn download_from(&self, client: &reqwest::Client, url: &str, destination: &mut Destination, progress: Option<&ProgressTracker>) -> Result<(), DownloadError> {
+        // Bytes resumed from an earlier download count as progress too
+        let mut counted = destination.len() as usize;
+        if let Some(progress) = progress && counted > 0 {
+            progress.add_count(counted as u64);
+            progress.notify();
+        }
+
+        let max_attempts = self.inner.max_retries.load(Ordering::Relaxed).saturating_add(1);
+        let mut attempt = 0;
+        let result = loop {
+            let error = match self.download_attempt(client, url, destination, progress, &mut counted).await {
+                Ok(()) => break Ok(()),
+                Err(error) => error,
+            };
+
+            if matches!(error, DownloadError::Status(StatusCode::RANGE_NOT_SATISFIABLE)) {
+                // The partial data doesn't fit the file on the server, it probably changed since
+                set_back(progress, &mut counted);
+                if let Err(error) = destination.clear().await {
+                    break Err(error.into());
+                }
+            }
+
+            attempt += 1;
+            if attempt >= max_attempts || !error.is_transient() {
+                break Err(error);
+            }
+
+            let backoff = INITIAL_BACKOFF * 2u32.pow((attempt - 1).min(6));
+            tracing::warn!("Download of {url} failed at {} bytes, retrying in {backoff:?}: {error}", destination.len());
+            tokio::time::sleep(backoff).await;
+        };
+
+        if result.is_err() {
+            set_back(progress, &mut counted);
+        }
+        result
+    }
+
+    async fn download_attempt(&self, client: &reqwest::Client, url: &str, destination: &mut Destination, progress: Option<&ProgressTracker>, counted: &mut usize) -> Result<(), DownloadError> {
+        let _slot = self.acquire_slot().await;
+
+        let offset = destination.len();
+        let mut request = client.get(url);
+        if offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={offset}-"));
+        }
+
+        let mut response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(DownloadError::Status(response.status()));
+        }
+
+        if offset > 0 && response.status() != StatusCode::PARTIAL_CONTENT {
+            // The server doesn't support ranges and sent the whole file again
+            set_back(progress, counted);
+            destination.clear().await?;
+        }
+
+        while let Some(chunk) = response.chunk().await? {
+            destination.append(&chunk).await?;
+            *counted += chunk.len();
+
+            if let Some(progress) = progress {
+                progress.add_count(chunk.len() as u64);
+                progress.notify();
+            }
+
+            let wait = self.inner.throttle.lock().take(chunk.len());
+            if !wait.is_zero() {
+                tokio::time::sleep(wait).await;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn throttle_waits_once_budget_is_spent() {
+        let mut throttle = Throttle {
+            bytes_per_second: Some(1000.0),
+            budget: 1000.0,
+            updated: Instant::now(),
+        };
+        assert_eq!(throttle.take(500), Duration::ZERO);
+        let wait = throttle.take(1500);
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+
+        throttle.bytes_per_second = None;
+        assert_eq!(throttle.take(1_000_000), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn cleared_file_destination_starts_over() {
+        let dir = crate::test_harness::TempDir::new();
+        let path = dir.path().join("file.jar");
+        let part_path = part_path(&path);
+        assert_eq!(part_path.file_name().unwrap(), "file.jar.part");
+
+        let file = tokio::fs::OpenOptions::new().create(true).append(true).open(&part_path).await.unwrap();
+        let mut destination = Destination { file, len: 0 };
+        destination.append(b"partial").await.unwrap();
+        assert_eq!(destination.len(), 7);
+
+        destination.clear().await.unwrap();
+        destination.append(b"whole").await.unwrap();
+        destination.file.flush().await.unwrap();
+        assert_eq!(destination.len(), 5);
+        assert_eq!(std::fs::read(&part_path).unwrap(), b"whole");
+    }
+}
@@ -0,0 +1,79 @@
+use std::{borrow::Cow, sync::LazyLock};
+
+use parking_lot::RwLock;
+use schema::backend_config::EndpointOverrides;
+
+static OVERRIDES: LazyLock<RwLock<EndpointOverrides>> = LazyLock::new(Default::default);
+
+const META_HOSTS: &[&str] = &[
+    "https://piston-meta.mojang.com",
+    "https://launchermeta.mojang.com",
+    "https://piston-data.mojang.com",
+    "https://launcher.mojang.com",
+];
+const ASSETS_HOSTS: &[&str] = &["https://resources.download.minecraft.net"];
+const LIBRARIES_HOSTS: &[&str] = &["https://libraries.minecraft.net"];
+
+pub fn set_overrides(overrides: EndpointOverrides) {
+    *OVERRIDES.write() = overrides;
+}
+
+/// Rewrites a Mojang url to point at the configured proxy or mirror, if there is one
+pub fn resolve(url: &str) -> Cow<'_, str> {
+    resolve_with(&OVERRIDES.read(), url)
+}
+
+/// Every url a Mojang file can be downloaded from, in the order they should be tried. The mirror comes first, with
+/// the official url as the fallback
+pub fn candidates(url: &str) -> impl Iterator<Item = Cow<'_, str>> {
+    let resolved = resolve(url);
+    let fallback = matches!(resolved, Cow::Owned(_)).then_some(Cow::Borrowed(url));
+    std::iter::once(resolved).chain(fallback)
+}
+
+fn resolve_with<'a>(overrides: &EndpointOverrides, url: &'a str) -> Cow<'a, str> {
+    let groups = [
+        (META_HOSTS, &overrides.meta),
+        (ASSETS_HOSTS, &overrides.assets),
+        (LIBRARIES_HOSTS, &overrides.libraries),
+    ];
+
+    for (hosts, replacement) in groups {
+        let Some(replacement) = replacement else {
+            continue;
+        };
+        for host in hosts {
+            if let Some(path) = url.strip_prefix(host) && (path.is_empty() || path.starts_with('/')) {
+                return Cow::Owned(format!("{}{}", replacement.trim_end_matches('/'), path));
+            }
+        }
+    }
+
+    Cow::Borrowed(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mirror_replaces_only_mojang_hosts() {
+        let bmclapi = EndpointOverrides::bmclapi();
+
+        assert_eq!(
+            resolve_with(&bmclapi, "https://libraries.minecraft.net/com/mojang/brigadier/1.0.18/brigadier-1.0.18.jar"),
+            "https://bmclapi2.bangbang93.com/maven/com/mojang/brigadier/1.0.18/brigadier-1.0.18.jar"
+        );
+        assert_eq!(
+            resolve_with(&bmclapi, "https://resources.download.minecraft.net/ab/abcdef"),
+            "https://bmclapi2.bangbang93.com/assets/ab/abcdef"
+        );
+        assert_eq!(
+            resolve_with(&bmclapi, "https://piston-data.mojang.com/v1/objects/abc/client.jar"),
+            "https://bmclapi2.bangbang93.com/v1/objects/abc/client.jar"
+        );
+        assert!(matches!(resolve_with(&bmclapi, "https://libraries.minecraft.net.example.com/a.jar"), Cow::Borrowed(_)));
+        assert!(matches!(resolve_with(&bmclapi, "https://maven.fabricmc.net/a.jar"), Cow::Borrowed(_)));
+        assert!(matches!(resolve_with(&EndpointOverrides::default(), "https://libraries.minecraft.net/a.jar"), Cow::Borrowed(_)));
+    }
+}
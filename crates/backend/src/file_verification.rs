@@ -0,0 +1,208 @@
+use std::{collections::HashMap, fs::Metadata, path::{Path, PathBuf}, sync::{atomic::{AtomicBool, Ordering}, Arc}, time::UNIX_EPOCH};
+
+use bridge::modal_action::ProgressTracker;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+
+/// Verification is bound by disk reads, so more threads than this only add contention
+const MAX_HASH_THREADS: usize = 8;
+
+static HASH_POOL: Lazy<rayon::ThreadPool> = Lazy::new(|| {
+    let threads = std::thread::available_parallelism().map(usize::from).unwrap_or(4).min(MAX_HASH_THREADS);
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|index| format!("hash-verify-{index}"))
+        .build()
+        .unwrap()
+});
+
+pub struct FileToVerify {
+    pub path: PathBuf,
+    /// Without a hash, any existing file of the right size is assumed to be fine
+    pub sha1: Option<[u8; 20]>,
+    pub size: Option<u64>,
+    /// Added to the progress tracker when the file is valid
+    pub progress: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileState {
+    Valid,
+    Missing,
+    /// The file exists but has the wrong size or hash
+    Invalid,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+struct VerifiedFile {
+    size: u64,
+    /// Modification time in nanoseconds since the unix epoch
+    modified: u64,
+    sha1: String,
+}
+
+impl VerifiedFile {
+    fn new(metadata: &Metadata, sha1: [u8; 20]) -> Option<Self> {
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_nanos() as u64;
+        Some(Self { size: metadata.len(), modified, sha1: hex::encode(sha1) })
+    }
+}
+
+/// Files that were hashed before, with the size and modification time they had. A file that still matches
+/// doesn't need to be hashed again, so repeat launches only read files that changed
+pub struct VerifiedFileCache {
+    path: Arc<Path>,
+    files: Mutex<HashMap<PathBuf, VerifiedFile>>,
+    changed: AtomicBool,
+}
+
+impl VerifiedFileCache {
+    pub fn load(path: Arc<Path>) -> Self {
+        let files = crate::read_json(&path).unwrap_or_default();
+        Self {
+            path,
+            files: Mutex::new(files),
+            changed: AtomicBool::new(false),
+        }
+    }
+
+    fn is_verified(&self, path: &Path, metadata: &Metadata, sha1: [u8; 20]) -> bool {
+        let Some(current) = VerifiedFile::new(metadata, sha1) else {
+            return false;
+        };
+        self.files.lock().get(path) == Some(&current)
+    }
+
+    fn record(&self, path: &Path, metadata: &Metadata, sha1: [u8; 20]) {
+        if let Some(verified) = VerifiedFile::new(metadata, sha1) {
+            self.files.lock().insert(path.to_path_buf(), verified);
+            self.changed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Hex encoded hash the file had when it was last verified, as long as it hasn't changed since
+    pub fn recorded_sha1(&self, path: &Path) -> Option<String> {
+        let metadata = std::fs::metadata(path).ok()?;
+        let current = VerifiedFile::new(&metadata, [0; 20])?;
+        let files = self.files.lock();
+        let recorded = files.get(path)?;
+        (recorded.size == current.size && recorded.modified == current.modified).then(|| recorded.sha1.clone())
+    }
+
+    fn save(&self) {
+        if !self.changed.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        let result = serde_json::to_vec(&*self.files.lock())
+            .map_err(std::io::Error::from)
+            .and_then(|bytes| crate::write_safe(&self.path, &bytes));
+        if let Err(error) = result {
+            tracing::error!("Unable to save verified files: {error}");
+        }
+    }
+
+    /// Forgets every file, so that the next launch hashes all of them again
+    pub fn clear(&self) {
+        self.files.lock().clear();
+        self.changed.store(false, Ordering::Relaxed);
+        _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn verify(file: &FileToVerify, cache: Option<&VerifiedFileCache>) -> FileState {
+    let Ok(metadata) = std::fs::metadata(&file.path) else {
+        return FileState::Missing;
+    };
+    if !metadata.is_file() {
+        return FileState::Invalid;
+    }
+    if let Some(size) = file.size && metadata.len() != size {
+        return FileState::Invalid;
+    }
+    let Some(expected_hash) = file.sha1 else {
+        return FileState::Valid;
+    };
+    if cache.is_some_and(|cache| cache.is_verified(&file.path, &metadata, expected_hash)) {
+        return FileState::Valid;
+    }
+    if !crate::check_sha1_hash(&file.path, expected_hash).unwrap_or(false) {
+        return FileState::Invalid;
+    }
+    if let Some(cache) = cache {
+        cache.record(&file.path, &metadata, expected_hash);
+    }
+    FileState::Valid
+}
+
+/// Checks every file against its expected size and hash on a bounded pool of hashing threads, returning the
+/// states in the same order. Sizes are compared first so that files which obviously changed are never read,
+/// and files recorded in `cache` as unchanged since they were last hashed aren't read either
+pub async fn verify_files(files: Vec<FileToVerify>, cache: Option<Arc<VerifiedFileCache>>, tracker: Option<ProgressTracker>) -> Vec<FileState> {
+    tokio::task::spawn_blocking(move || {
+        let states = HASH_POOL.install(|| {
+            files.into_par_iter().map(|file| {
+                let state = verify(&file, cache.as_deref());
+                if state == FileState::Valid && let Some(tracker) = &tracker {
+                    tracker.add_count(file.progress);
+                    tracker.notify();
+                }
+                state
+            }).collect()
+        });
+        if let Some(cache) = &cache {
+            cache.save();
+        }
+        states
+    }).await.unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use sha1::{Digest, Sha1};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn sizes_are_checked_before_hashes() {
+        let dir = crate::test_harness::TempDir::new();
+        let contents = b"asset contents";
+        let sha1: [u8; 20] = Sha1::digest(contents).into();
+        std::fs::write(dir.path().join("valid"), contents).unwrap();
+        std::fs::write(dir.path().join("corrupt"), b"asset c0ntents").unwrap();
+        std::fs::write(dir.path().join("truncated"), b"asset").unwrap();
+
+        let file = |name: &str| FileToVerify {
+            path: dir.path().join(name),
+            sha1: Some(sha1),
+            size: Some(contents.len() as u64),
+            progress: 1,
+        };
+        let states = verify_files(vec![file("valid"), file("corrupt"), file("truncated"), file("missing")], None, None).await;
+        assert_eq!(states, [FileState::Valid, FileState::Invalid, FileState::Invalid, FileState::Missing]);
+    }
+
+    #[tokio::test]
+    async fn unchanged_files_are_not_hashed_again() {
+        let dir = crate::test_harness::TempDir::new();
+        let cache_path: Arc<Path> = dir.path().join("verified_files.json").into();
+        let path = dir.path().join("asset");
+        let sha1: [u8; 20] = Sha1::digest(b"asset").into();
+        std::fs::write(&path, b"asset").unwrap();
+
+        let file = || FileToVerify { path: path.clone(), sha1: Some(sha1), size: Some(5), progress: 1 };
+        let cache = Arc::new(VerifiedFileCache::load(cache_path.clone()));
+        verify_files(vec![file()], Some(cache.clone()), None).await;
+
+        // The file is corrupted without changing its size or modification time, which only a full check notices
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        std::fs::write(&path, b"ASSET").unwrap();
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+
+        let cache = Arc::new(VerifiedFileCache::load(cache_path));
+        assert_eq!(verify_files(vec![file()], Some(cache.clone()), None).await, [FileState::Valid]);
+        cache.clear();
+        assert_eq!(verify_files(vec![file()], Some(cache), None).await, [FileState::Invalid]);
+    }
+}
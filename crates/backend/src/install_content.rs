@@ -1,14 +1,14 @@
-use std::{ffi::{OsStr, OsString}, io::Write, path::{Path, PathBuf}, sync::Arc};
+use std::{ffi::{OsStr, OsString}, path::{Path, PathBuf}, sync::Arc};
 
 use bridge::{
-    install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath}, instance::{ContentType, ContentSummary}, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath
+    content_source::ContentSourceId, install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath}, instance::{ContentType, ContentSummary}, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath
 };
-use reqwest::StatusCode;
-use schema::{content::ContentSource, loader::Loader, modrinth::{ModrinthLoader, ModrinthProjectVersionsRequest}};
+use schema::{content::ContentSource, loader::Loader, modrinth::ModrinthProjectType};
 use sha1::{Digest, Sha1};
-use tokio::io::AsyncWriteExt;
 
-use crate::{lockfile::Lockfile, metadata::{items::{MinecraftVersionManifestMetadataItem, ModrinthProjectVersionsMetadataItem, ModrinthVersionMetadataItem}, manager::MetaLoadError}, BackendState};
+use crate::{
+    content_sources::ContentSourceError, download_manager::DownloadError, lockfile::Lockfile, metadata::{items::MinecraftVersionManifestMetadataItem, manager::MetaLoadError}, BackendState
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum ContentInstallError {
@@ -20,10 +20,12 @@ pub enum ContentInstallError {
     InvalidFilename(Arc<str>),
     #[error("Failed to download remote content")]
     Reqwest(#[from] reqwest::Error),
-    #[error("Remote server returned non-200 status code: {0}")]
-    NotOK(StatusCode),
+    #[error("Failed to download remote content:\n{0}")]
+    Download(#[from] DownloadError),
     #[error("Downloaded file had the wrong size")]
     WrongFilesize,
+    #[error("The server didn't report the size of {0}")]
+    UnknownFilesize(Arc<str>),
     #[error("Downloaded file had the wrong hash")]
     WrongHash,
     #[error("Hash isn't a valid sha1 hash:\n{0}")]
@@ -32,6 +34,8 @@ pub enum ContentInstallError {
     IoError(#[from] std::io::Error),
     #[error("Failed to load metadata:\n{0}")]
     MetaLoadError(#[from] MetaLoadError),
+    #[error("{0}")]
+    ContentSource(#[from] ContentSourceError),
     #[error("Mismatched project id for version {0}, expected {1} got {2}")]
     MismatchedProjectIdForVersion(Arc<str>, Arc<str>, Arc<str>),
 }
@@ -46,7 +50,7 @@ struct InstallFromContentLibrary {
 }
 
 #[derive(Clone)]
-struct FilenameAndExtension {
+pub(crate) struct FilenameAndExtension {
     filename: Option<OsString>,
     extension: Option<OsString>,
 }
@@ -69,113 +73,29 @@ impl From<&Path> for FilenameAndExtension {
     }
 }
 
+fn project_type_directory(project_type: ModrinthProjectType) -> Option<&'static str> {
+    match project_type {
+        ModrinthProjectType::Mod => Some("mods"),
+        ModrinthProjectType::Resourcepack => Some("resourcepacks"),
+        ModrinthProjectType::Shader => Some("shaderpacks"),
+        ModrinthProjectType::Modpack | ModrinthProjectType::Other => None,
+    }
+}
+
 impl BackendState {
     pub async fn install_content(&self, content: ContentInstall, modal_action: ModalAction) {
-        let semaphore = tokio::sync::Semaphore::new(8);
-
         let mut tasks = Vec::new();
 
         for content_file in content.files.iter() {
             tasks.push(async {
                 match content_file.download {
                     bridge::install::ContentDownload::Modrinth { ref project_id, ref version_id } => {
-                        let version = if let Some(version_id) = version_id {
-                            let version = self.meta.fetch(&ModrinthVersionMetadataItem(version_id.clone())).await?;
-                            Some(version)
-                        } else {
-                            let versions = self.meta.fetch(&ModrinthProjectVersionsMetadataItem(&ModrinthProjectVersionsRequest {
-                                project_id: project_id.clone(),
-                                game_versions: content.version_hint.clone().map(|v| [v].into()),
-                                loaders: None,
-                            })).await?;
-
-                            let modrinth_loader = content.loader_hint.as_modrinth_loader();
-                            let version = if modrinth_loader != ModrinthLoader::Unknown {
-                                versions.0.iter()
-                                    .find(|version| if let Some(loaders) = &version.loaders {
-                                        loaders.contains(&modrinth_loader)
-                                    } else {
-                                        false
-                                    })
-                                    .or(versions.0.first())
-                            } else {
-                                versions.0.first()
-                            };
-
-                            version.map(|v| Arc::new(v.clone()))
-                        };
-
-                        if let Some(version) = version {
-                            if &version.project_id != project_id {
-                                return Err(ContentInstallError::MismatchedProjectIdForVersion(
-                                    version.id.clone(),
-                                    project_id.clone(),
-                                    version.project_id.clone()
-                                ));
-                            }
-
-                            let install_file = version
-                                .files
-                                .iter()
-                                .find(|file| file.primary)
-                                .unwrap_or(version.files.first().unwrap());
-
-                            let url = &install_file.url;
-                            let sha1 = &install_file.hashes.sha1;
-                            let size = install_file.size;
-
-                            let Some(safe_filename) = SafePath::new(&install_file.filename) else {
-                                return Err(ContentInstallError::InvalidFilename(install_file.filename.clone()));
-                            };
-
-                            let (path, hash, mod_summary) = self.download_file_into_library(&modal_action,
-                                (&safe_filename).into(), url, sha1, size, &semaphore).await?;
-
-                            let install_path = match &content_file.path {
-                                ContentInstallPath::Raw(path) => path.clone(),
-                                ContentInstallPath::Safe(safe_path) => safe_path.to_path(Path::new("")).into(),
-                                ContentInstallPath::Automatic => {
-                                    let base = if let Some(mod_summary) = &mod_summary {
-                                        match mod_summary.extra {
-                                            ContentType::Fabric | ContentType::Forge | ContentType::NeoForge | ContentType::JavaModule | ContentType::ModrinthModpack { .. } => {
-                                                Path::new("mods")
-                                            },
-                                            ContentType::ResourcePack => {
-                                                Path::new("resourcepacks")
-                                            }
-                                        }
-                                    } else if let Some(loaders) = &version.loaders {
-                                        let mut base = None;
-                                        for loader in loaders.iter() {
-                                            base = loader.install_directory();
-                                            if base.is_some() {
-                                                break;
-                                            }
-                                        }
-                                        if let Some(base) = base {
-                                            Path::new(base)
-                                        } else {
-                                            return Err(ContentInstallError::UnableToDetermineContentType(install_file.filename.clone()))
-                                        }
-                                    } else {
-                                        return Err(ContentInstallError::UnableToDetermineContentType(install_file.filename.clone()))
-                                    };
-
-                                    safe_filename.to_path(base).into()
-                                },
-                            };
-
-                            Ok(InstallFromContentLibrary {
-                                from: path,
-                                replace: content_file.replace_old.clone(),
-                                hash,
-                                install_path,
-                                content_file: content_file.clone(),
-                                mod_summary
-                            })
-                        } else {
-                            Err(ContentInstallError::UnableToFindDependencyVersion)
-                        }
+                        let source = self.content_source(ContentSourceId::Modrinth)?;
+                        self.download_from_source(&*source, &content, content_file, project_id, version_id.as_deref(), None, &modal_action).await
+                    },
+                    bridge::install::ContentDownload::CurseForge { ref project_id, ref version_id, project_type } => {
+                        let source = self.content_source(ContentSourceId::CurseForge)?;
+                        self.download_from_source(&*source, &content, content_file, project_id, version_id.as_deref(), Some(project_type), &modal_action).await
                     },
                     bridge::install::ContentDownload::Url { ref url, ref sha1, size } => {
                         let name = match &content_file.path {
@@ -185,7 +105,7 @@ impl BackendState {
                         };
 
                         let (path, hash, mod_summary) = self.download_file_into_library(&modal_action,
-                            name, url, sha1, size, &semaphore).await?;
+                            name, url, sha1, size).await?;
 
                         let install_path = match &content_file.path {
                             ContentInstallPath::Raw(path) => path.clone(),
@@ -203,7 +123,7 @@ impl BackendState {
                         });
                     },
                     bridge::install::ContentDownload::File { path: ref copy_path } => {
-                        let title = format!("Copying {}", copy_path.file_name().unwrap().to_string_lossy());
+                        let title = rust_i18n::t!("progress_copying_file", name = copy_path.file_name().unwrap().to_string_lossy());
                         let tracker = ProgressTracker::new(title.into(), self.send.clone());
                         modal_action.trackers.push(tracker.clone());
 
@@ -364,8 +284,98 @@ impl BackendState {
         }
     }
 
-    async fn download_file_into_library(&self, modal_action: &ModalAction, name: FilenameAndExtension, url: &Arc<str>, sha1: &Arc<str>, size: usize, semaphore: &tokio::sync::Semaphore) -> Result<(PathBuf, [u8; 20], Option<Arc<ContentSummary>>), ContentInstallError> {
-        let mut result = self.download_file_into_library_inner(modal_action, name, url, sha1, size, semaphore).await?;
+    /// Fills in the size of URL downloads whose source doesn't provide one from the server's Content-Length, so the
+    /// downloaded file can still be checked against it
+    pub(crate) async fn resolve_download_sizes(&self, files: &mut [ContentInstallFile]) -> Result<(), ContentInstallError> {
+        let tasks = files.iter_mut().filter_map(|file| match &mut file.download {
+            ContentDownload::Url { url, size, .. } if *size == 0 => Some(async move {
+                let response = self.redirecting_http_client.head(&**url).send().await?.error_for_status()?;
+                let length = response.content_length().ok_or_else(|| ContentInstallError::UnknownFilesize(url.clone()))?;
+                *size = length as usize;
+                Ok::<_, ContentInstallError>(())
+            }),
+            _ => None,
+        });
+        futures::future::try_join_all(tasks).await?;
+        Ok(())
+    }
+
+    /// Downloads a version of a project from a source into the content library. `project_type` is where the file is
+    /// installed when the path is automatic and neither the file nor the version's loaders tell
+    async fn download_from_source(&self, source: &dyn crate::content_sources::ContentSource, content: &ContentInstall,
+        content_file: &ContentInstallFile, project_id: &Arc<str>, version_id: Option<&str>, project_type: Option<ModrinthProjectType>,
+        modal_action: &ModalAction) -> Result<InstallFromContentLibrary, ContentInstallError>
+    {
+        let version = if let Some(version_id) = version_id {
+            Some(source.version(project_id, version_id).await?)
+        } else {
+            source.resolve_version(project_id, content.loader_hint, content.version_hint.as_deref()).await?
+        };
+
+        if let Some(version) = version {
+            if &version.project_id != project_id {
+                return Err(ContentInstallError::MismatchedProjectIdForVersion(
+                    version.version_id.clone(),
+                    project_id.clone(),
+                    version.project_id.clone()
+                ));
+            }
+
+            let Some(install_file) = version.primary_file() else {
+                return Err(ContentInstallError::UnableToFindDependencyVersion);
+            };
+
+            let url = &source.download_url(install_file)?;
+            let sha1 = &install_file.sha1;
+            let size = install_file.size;
+
+            let Some(safe_filename) = SafePath::new(&install_file.filename) else {
+                return Err(ContentInstallError::InvalidFilename(install_file.filename.clone()));
+            };
+
+            let (path, hash, mod_summary) = self.download_file_into_library(modal_action,
+                (&safe_filename).into(), url, sha1, size).await?;
+
+            let install_path = match &content_file.path {
+                ContentInstallPath::Raw(path) => path.clone(),
+                ContentInstallPath::Safe(safe_path) => safe_path.to_path(Path::new("")).into(),
+                ContentInstallPath::Automatic => {
+                    let base = if let Some(mod_summary) = &mod_summary {
+                        match mod_summary.extra {
+                            ContentType::Fabric | ContentType::Forge | ContentType::NeoForge | ContentType::JavaModule | ContentType::ModrinthModpack { .. } => {
+                                Path::new("mods")
+                            },
+                            ContentType::ResourcePack => {
+                                Path::new("resourcepacks")
+                            }
+                        }
+                    } else if let Some(base) = project_type.and_then(project_type_directory)
+                        .or_else(|| version.loaders.iter().find_map(|loader| loader.install_directory()))
+                    {
+                        Path::new(base)
+                    } else {
+                        return Err(ContentInstallError::UnableToDetermineContentType(install_file.filename.clone()))
+                    };
+
+                    safe_filename.to_path(base).into()
+                },
+            };
+
+            Ok(InstallFromContentLibrary {
+                from: path,
+                replace: content_file.replace_old.clone(),
+                hash,
+                install_path,
+                content_file: content_file.clone(),
+                mod_summary
+            })
+        } else {
+            Err(ContentInstallError::UnableToFindDependencyVersion)
+        }
+    }
+
+    pub(crate) async fn download_file_into_library(&self, modal_action: &ModalAction, name: FilenameAndExtension, url: &Arc<str>, sha1: &Arc<str>, size: usize) -> Result<(PathBuf, [u8; 20], Option<Arc<ContentSummary>>), ContentInstallError> {
+        let mut result = self.download_file_into_library_inner(modal_action, name, url, sha1, size).await?;
 
         if let Some(summary) = &result.2 {
             if let ContentType::ModrinthModpack { downloads, .. } = &summary.extra {
@@ -382,7 +392,7 @@ impl BackendState {
                     };
 
                     tasks.push(self.download_file_into_library_inner(modal_action, name,
-                        &download.downloads[0], &download.hashes.sha1, download.file_size, semaphore));
+                        &download.downloads[0], &download.hashes.sha1, download.file_size));
                 }
 
                 _ = futures::future::try_join_all(tasks).await;
@@ -393,10 +403,10 @@ impl BackendState {
         Ok(result)
     }
 
-    async fn download_file_into_library_inner(&self, modal_action: &ModalAction, name: FilenameAndExtension, url: &Arc<str>, sha1: &Arc<str>, size: usize, semaphore: &tokio::sync::Semaphore) -> Result<(PathBuf, [u8; 20], Option<Arc<ContentSummary>>), ContentInstallError> {
+    async fn download_file_into_library_inner(&self, modal_action: &ModalAction, name: FilenameAndExtension, url: &Arc<str>, sha1: &Arc<str>, size: usize) -> Result<(PathBuf, [u8; 20], Option<Arc<ContentSummary>>), ContentInstallError> {
         let mut expected_hash = [0u8; 20];
         let Ok(_) = hex::decode_to_slice(&**sha1, &mut expected_hash) else {
-            log::warn!("Content install has invalid sha1: {}", sha1);
+            tracing::warn!("Content install has invalid sha1: {}", sha1);
             return Err(ContentInstallError::InvalidHash(sha1.clone()));
         };
 
@@ -413,15 +423,13 @@ impl BackendState {
 
         let lockfile = Lockfile::create(path.with_added_extension("lock").into()).await;
 
-        let _permit = semaphore.acquire().await.unwrap();
-
         let file_name = name.filename.clone();
 
-        let title = format!("Downloading {}", file_name.as_deref().map(|s| s.to_string_lossy()).unwrap_or(std::borrow::Cow::Borrowed("???")));
+        let title = rust_i18n::t!("progress_downloading_file", name = file_name.as_deref().map(|s| s.to_string_lossy()).unwrap_or(std::borrow::Cow::Borrowed("???")));
         let tracker = ProgressTracker::new(title.into(), self.send.clone());
         modal_action.trackers.push(tracker.clone());
 
-        tracker.set_total(size);
+        tracker.set_total(size as u64);
         tracker.notify();
 
         let valid_hash_on_disk = {
@@ -432,61 +440,28 @@ impl BackendState {
         };
 
         if valid_hash_on_disk {
-            tracker.set_count(size);
+            tracker.set_count(size as u64);
             tracker.set_finished(ProgressTrackerFinishType::Fast);
             tracker.notify();
             let summary = self.mod_metadata_manager.get_path(&path);
             return Ok((path, expected_hash, summary));
         }
 
-        let response = self.redirecting_http_client.get(&**url).send().await?;
-
-        if response.status() != StatusCode::OK {
-            return Err(ContentInstallError::NotOK(response.status()));
-        }
-
-        // Tokio doesn't have lock, so we use std temporarily to lock it
-        let file = std::fs::File::create(&path)?;
-        _ = file.lock();
-
-        let mut file = tokio::fs::File::from_std(file);
-
-        use futures::StreamExt;
-        let mut stream = response.bytes_stream();
-
-        let mut total_bytes = 0;
-
-        let mut hasher = Sha1::new();
-        while let Some(item) = stream.next().await {
-            let item = item?;
-
-            total_bytes += item.len();
-            tracker.add_count(item.len());
-            tracker.notify();
-
-            hasher.write_all(&item)?;
-            file.write_all(&item).await?;
-        }
-
+        // The file only appears under its real name once complete, an interrupted download is resumed next time
+        self.download_manager.download_file(&self.redirecting_http_client, url, &path, Some(&tracker)).await?;
         tracker.set_finished(ProgressTrackerFinishType::Fast);
 
-        let actual_hash = hasher.finalize();
-
-        let wrong_hash = *actual_hash != expected_hash;
-        let wrong_size = total_bytes != size;
-
-        if wrong_hash || wrong_size {
-            let _ = file.set_len(0).await;
-            drop(file);
-            let _ = tokio::fs::remove_file(&path).await;
+        let correct_hash = {
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || {
+                crate::check_sha1_hash(&path, expected_hash)
+            }).await.unwrap()?
+        };
+        let wrong_size = std::fs::metadata(&path)?.len() as usize != size;
 
-            if wrong_hash {
-                return Err(ContentInstallError::WrongHash);
-            } else if wrong_size {
-                return Err(ContentInstallError::WrongFilesize);
-            } else {
-                unreachable!();
-            }
+        if !correct_hash || wrong_size {
+            _ = std::fs::remove_file(&path);
+            return Err(if correct_hash { ContentInstallError::WrongFilesize } else { ContentInstallError::WrongHash });
         }
 
         drop(lockfile);
@@ -495,3 +470,98 @@ impl BackendState {
         Ok((path, expected_hash, summary))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bridge::{install::InstallTarget, instance::ContentUpdateStatus};
+
+    use super::*;
+    use crate::{
+        content_sources::{curseforge::CurseForgeSource, find_update_action}, mod_metadata::ModUpdateAction, test_harness::{self, MockServer, TempDir, TestBackend}
+    };
+
+    fn curseforge_file(id: u32, body: &[u8], url: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "modId": 123,
+            "displayName": "Shader",
+            "fileName": "shader.zip",
+            "downloadUrl": url,
+            "fileLength": body.len(),
+            "fileFingerprint": 0,
+            "hashes": [{ "value": test_harness::sha1_hex(body), "algo": 1 }],
+            "gameVersions": ["1.21.1"],
+        })
+    }
+
+    #[test]
+    fn installs_and_updates_from_curseforge() {
+        let dir = TempDir::new();
+        let backend = TestBackend::load(dir.path());
+        let old_body = b"old shader".to_vec();
+        let new_body = b"new shader".to_vec();
+
+        backend.runtime.block_on(async {
+            let files = MockServer::start(HashMap::from([("/shader.zip".to_string(), new_body.clone())])).await;
+            let files_json = serde_json::json!({
+                "data": [
+                    curseforge_file(1000, &old_body, &files.url("/old.zip")),
+                    curseforge_file(1001, &new_body, &files.url("/shader.zip")),
+                ],
+            });
+            let api = MockServer::start(HashMap::from([("/mods/123/files".to_string(), serde_json::to_vec(&files_json).unwrap())])).await;
+            let source = CurseForgeSource {
+                http_client: test_harness::http_client(),
+                api_url: api.url("").into(),
+                api_key: "key".into(),
+            };
+
+            let content_file = ContentInstallFile {
+                replace_old: None,
+                path: ContentInstallPath::Automatic,
+                download: ContentDownload::CurseForge { project_id: "123".into(), version_id: None, project_type: ModrinthProjectType::Shader },
+                content_source: crate::content_sources::installed_content_source(ContentSourceId::CurseForge, "123".into()),
+            };
+            let content = ContentInstall {
+                target: InstallTarget::Library,
+                loader_hint: Loader::Fabric,
+                version_hint: Some("1.21.1".into()),
+                files: [content_file.clone()].into(),
+            };
+
+            // The newest file is installed, into the folder of the project's class since it isn't a mod
+            let installed = backend.state.download_from_source(&source, &content, &content_file, &"123".into(), None,
+                Some(ModrinthProjectType::Shader), &ModalAction::default()).await.unwrap();
+            assert_eq!(std::fs::read(&installed.from).unwrap(), new_body);
+            assert_eq!(&*installed.install_path, Path::new("shaderpacks/shader.zip"));
+            assert_eq!(installed.content_file.content_source, ContentSource::CurseForgeProject { project: "123".into() });
+            assert!(files.requests().iter().all(|path| path == "/shader.zip"));
+
+            let installed_source = installed.content_file.content_source;
+            let content_type = ContentType::ResourcePack;
+            let action = find_update_action(ContentSourceId::CurseForge, &source, &installed_source, installed.hash,
+                &content_type, Loader::Fabric, "1.21.1".into()).await.unwrap();
+            assert!(matches!(action, ModUpdateAction::AlreadyUpToDate));
+
+            let mut old_hash = [0u8; 20];
+            hex::decode_to_slice(test_harness::sha1_hex(&old_body), &mut old_hash).unwrap();
+            let action = find_update_action(ContentSourceId::CurseForge, &source, &installed_source, old_hash,
+                &content_type, Loader::Fabric, "1.21.1".into()).await.unwrap();
+            assert!(action.to_status() == ContentUpdateStatus::CurseForge);
+            let ModUpdateAction::Update { source: update_source, file, project_id } = action else {
+                panic!("expected an update");
+            };
+            assert_eq!(update_source, ContentSourceId::CurseForge);
+            assert_eq!(&*file.filename, "shader.zip");
+            assert_eq!(&*project_id, "123");
+
+            // Files installed from a different project are never replaced
+            let other_project = ContentSource::CurseForgeProject { project: "456".into() };
+            let action = find_update_action(ContentSourceId::CurseForge, &source, &other_project, old_hash,
+                &content_type, Loader::Fabric, "1.21.1".into()).await.unwrap();
+            assert!(matches!(action, ModUpdateAction::ErrorNotFound));
+        });
+    }
+}
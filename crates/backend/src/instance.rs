@@ -1,5 +1,5 @@
 use std::{
-    collections::{BTreeMap, HashSet}, ffi::OsStr, hash::{DefaultHasher, Hash, Hasher}, io::Read, path::{Path, PathBuf}, process::Child, sync::{
+    collections::{BTreeMap, HashSet}, ffi::OsStr, hash::{DefaultHasher, Hash, Hasher}, io::Read, path::{Path, PathBuf}, sync::{
         Arc, atomic::Ordering
     }
 };
@@ -9,7 +9,7 @@ use base64::Engine;
 use bridge::{
     instance::{
         ContentSummary, InstanceContentID, InstanceContentSummary, InstanceID, InstanceServerSummary, InstanceStatus, InstanceWorldSummary
-    }, message::{AtomicBridgeDataLoadState, BridgeDataLoadState, MessageToFrontend}, notify_signal::{KeepAliveNotifySignal, KeepAliveNotifySignalHandle}
+    }, message::{AtomicBridgeDataLoadState, BridgeDataLoadState, LaunchMods, MessageToFrontend}, modal_action::ModalAction, notify_signal::{KeepAliveNotifySignal, KeepAliveNotifySignalHandle}
 };
 use parking_lot::RwLock;
 use relative_path::RelativePath;
@@ -19,7 +19,7 @@ use thiserror::Error;
 
 use ustr::Ustr;
 
-use crate::{id_slab::{GetId, Id}, mod_metadata::ModMetadataManager, persistent::Persistent, BackendStateInstances, IoOrSerializationError};
+use crate::{id_slab::{GetId, Id}, mod_metadata::ModMetadataManager, persistent::Persistent, playtime::PlaytimeTracker, process::GameProcess, BackendStateInstances, IoOrSerializationError};
 
 #[derive(Debug)]
 pub struct Instance {
@@ -30,12 +30,21 @@ pub struct Instance {
     pub saves_path: Arc<Path>,
     pub name: Ustr,
     pub configuration: Persistent<InstanceConfiguration>,
+    /// Seconds played in total, mirrored from the playtime ledger
+    pub total_playtime: u64,
 
-    pub child: Option<Child>,
+    pub process: Option<GameProcess>,
+    pub crashed: bool,
+    /// Action of the launch in progress, kept so the frontend can reattach to its progress
+    pub launching: Option<ModalAction>,
+    /// Mods the running process was launched with
+    pub launched_mods: LaunchMods,
 
     pub watching_dot_minecraft: bool,
     pub watching_server_dat: bool,
     pub watching_saves_dir: bool,
+    /// Screenshots already shown to the user since the last launch
+    pub notified_screenshots: HashSet<Arc<Path>>,
 
     pub worlds_state: Arc<AtomicBridgeDataLoadState>,
     dirty_worlds: HashSet<Arc<Path>>,
@@ -153,6 +162,25 @@ impl GetId for Instance {
     }
 }
 
+/// The folder the game runs in, the instance's own .minecraft unless it reuses a game directory elsewhere
+fn dot_minecraft_path(root: &Path, configuration: &InstanceConfiguration) -> PathBuf {
+    match &configuration.game_directory {
+        Some(game_directory) => game_directory.to_path_buf(),
+        None => root.join(".minecraft"),
+    }
+}
+
+/// The folder the game of the instance in `root` runs in, for instances that aren't loaded
+pub(crate) fn read_dot_minecraft_path(root: &Path) -> PathBuf {
+    #[derive(serde::Deserialize)]
+    struct GameDirectory {
+        game_directory: Option<PathBuf>,
+    }
+
+    let game_directory = crate::read_json::<GameDirectory>(&root.join("info_v1.json")).ok().and_then(|info| info.game_directory);
+    game_directory.unwrap_or_else(|| root.join(".minecraft"))
+}
+
 #[derive(Error, Debug)]
 pub enum InstanceLoadError {
     #[error("Not a directory")]
@@ -174,14 +202,13 @@ impl From<IoOrSerializationError> for InstanceLoadError {
 
 impl Instance {
     pub fn on_root_renamed(&mut self, path: &Path) {
-        log::info!("Instance {:?} has been moved to {:?}", self.root_path, path);
+        tracing::info!("Instance {:?} has been moved to {:?}", self.root_path, path);
 
         self.name = path.file_name().unwrap().to_string_lossy().into_owned().into();
         self.root_path = path.into();
         self.configuration = Persistent::load_or(path.join("info_v1.json").into(), self.configuration.get().clone());
 
-        let mut dot_minecraft_path = path.to_owned();
-        dot_minecraft_path.push(".minecraft");
+        let dot_minecraft_path = dot_minecraft_path(path, self.configuration.get());
 
         for content_folder in ContentFolder::iter() {
             self.content_state[content_folder].path = content_folder.path().to_path(&dot_minecraft_path).into();
@@ -270,7 +297,7 @@ impl Instance {
     }
 
     fn load_worlds_all(saves_path: &Path) -> Arc<[InstanceWorldSummary]> {
-        log::info!("Loading all worlds in {:?}", saves_path);
+        tracing::info!("Loading all worlds in {:?}", saves_path);
 
         let Ok(directory) = std::fs::read_dir(&saves_path) else {
             return [].into();
@@ -285,7 +312,7 @@ impl Instance {
             }
 
             let Ok(entry) = entry else {
-                log::error!("Error reading directory in saves folder: {:?}", entry.unwrap_err());
+                tracing::error!("Error reading directory in saves folder: {:?}", entry.unwrap_err());
                 continue;
             };
             let path = entry.path();
@@ -300,7 +327,7 @@ impl Instance {
                     summaries.push(summary);
                 },
                 Err(err) => {
-                    log::error!("Error loading world summary: {:?}", err);
+                    tracing::error!("Error loading world summary: {:?}", err);
                 },
             }
         }
@@ -311,8 +338,8 @@ impl Instance {
     }
 
     fn load_worlds_dirty(dirty: HashSet<Arc<Path>>, last: Arc<[InstanceWorldSummary]>) -> Arc<[InstanceWorldSummary]> {
-        log::debug!("Loading changed worlds");
-        log::trace!("Changed worlds: {:?}", dirty);
+        tracing::debug!("Loading changed worlds");
+        tracing::trace!("Changed worlds: {:?}", dirty);
 
         let mut summaries = Vec::with_capacity(64);
 
@@ -334,7 +361,7 @@ impl Instance {
                     summaries.push(summary);
                 },
                 Err(err) => {
-                    log::error!("Error loading world summary: {:?}", err);
+                    tracing::error!("Error loading world summary: {:?}", err);
                 },
             }
         }
@@ -412,7 +439,7 @@ impl Instance {
     }
 
     fn load_servers_all(server_dat_path: &Path) -> Arc<[InstanceServerSummary]> {
-        log::info!("Loading servers from {:?}", server_dat_path);
+        tracing::info!("Loading servers from {:?}", server_dat_path);
 
         if !server_dat_path.is_file() {
             return Arc::from([]);
@@ -421,7 +448,7 @@ impl Instance {
         let result = match load_servers_summary(&server_dat_path) {
             Ok(summaries) => summaries.into(),
             Err(err) => {
-                log::error!("Error loading servers: {:?}", err);
+                tracing::error!("Error loading servers: {:?}", err);
                 Arc::from([])
             },
         };
@@ -509,7 +536,7 @@ impl Instance {
     }
 
     fn load_content_all(path: &Path, mod_metadata_manager: Arc<ModMetadataManager>) -> Vec<InstanceContentSummary> {
-        log::info!("Loading all content from {:?}", path);
+        tracing::info!("Loading all content from {:?}", path);
 
         let Ok(directory) = std::fs::read_dir(&path) else {
             return Vec::new();
@@ -521,7 +548,7 @@ impl Instance {
 
         for entry in directory {
             let Ok(entry) = entry else {
-                log::error!("Error reading file in content folder: {:?}", entry.unwrap_err());
+                tracing::error!("Error reading file in content folder: {:?}", entry.unwrap_err());
                 continue;
             };
 
@@ -543,8 +570,8 @@ impl Instance {
         mod_metadata_manager: Arc<ModMetadataManager>,
         last: Arc<[InstanceContentSummary]>,
     ) -> Vec<InstanceContentSummary> {
-        log::debug!("Loading changed content");
-        log::trace!("Changed content: {:?}", dirty);
+        tracing::debug!("Loading changed content");
+        tracing::trace!("Changed content: {:?}", dirty);
 
         let mut summaries = Vec::with_capacity(last.len() + 8);
 
@@ -632,7 +659,7 @@ impl Instance {
 
     pub fn load_from_folder(path: impl AsRef<Path>) -> Result<Self, InstanceLoadError> {
         let path = path.as_ref();
-        log::info!("Loading instance from {:?}", path);
+        tracing::info!("Loading instance from {:?}", path);
 
         if !path.is_dir() {
             return Err(InstanceLoadError::NotADirectory);
@@ -642,8 +669,7 @@ impl Instance {
 
         let instance_info: Persistent<InstanceConfiguration> = Persistent::try_load(info_path.clone())?;
 
-        let mut dot_minecraft_path = path.to_owned();
-        dot_minecraft_path.push(".minecraft");
+        let dot_minecraft_path = dot_minecraft_path(path, instance_info.get());
 
         let saves_path = dot_minecraft_path.join("saves");
         let server_dat_path = dot_minecraft_path.join("servers.dat");
@@ -660,12 +686,17 @@ impl Instance {
             saves_path: saves_path.into(),
             name: path.file_name().unwrap().to_string_lossy().into_owned().into(),
             configuration: instance_info,
+            total_playtime: 0,
 
-            child: None,
+            process: None,
+            crashed: false,
+            launching: None,
+            launched_mods: LaunchMods::All,
 
             watching_dot_minecraft: false,
             watching_server_dat: false,
             watching_saves_dir: false,
+            notified_screenshots: HashSet::new(),
 
             worlds_state: Arc::new(AtomicBridgeDataLoadState::new(BridgeDataLoadState::Unloaded)),
             dirty_worlds: HashSet::new(),
@@ -726,13 +757,27 @@ impl Instance {
     }
 
     pub fn status(&self) -> InstanceStatus {
-        if self.child.is_some() {
+        if self.process.is_some() {
             InstanceStatus::Running
+        } else if self.launching.is_some() {
+            InstanceStatus::Launching
+        } else if self.crashed {
+            InstanceStatus::Crashed
         } else {
             InstanceStatus::NotRunning
         }
     }
 
+    /// Key of the instance in the playtime ledger
+    pub fn playtime_key(&mut self) -> Arc<str> {
+        crate::playtime::instance_key(self.configuration.get().uuid.unwrap_or_default())
+    }
+
+    /// Updates the total playtime sent to the frontend from the ledger
+    pub fn sync_playtime(&mut self, playtime: &mut PlaytimeTracker) {
+        self.total_playtime = playtime.total_played_seconds(&self.playtime_key());
+    }
+
     pub fn create_modify_message(&mut self) -> MessageToFrontend {
         self.create_modify_message_with_status(self.status())
     }
@@ -743,11 +788,24 @@ impl Instance {
             name: self.name,
             dot_minecraft_folder: self.dot_minecraft_path.clone(),
             configuration: self.configuration.get().clone(),
+            total_playtime: self.total_playtime,
             status,
+            process: self.process.as_ref().map(GameProcess::info),
         }
     }
 }
 
+/// Number and total size of the enabled mod jars in `mods_dir`, used to judge how heavily modded an instance is
+pub fn mod_footprint(mods_dir: &Path) -> (usize, u64) {
+    let Ok(read_dir) = std::fs::read_dir(mods_dir) else {
+        return (0, 0);
+    };
+    read_dir.filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().is_some_and(|extension| extension == "jar"))
+        .filter_map(|entry| entry.metadata().ok())
+        .fold((0, 0), |(count, bytes), metadata| (count + 1, bytes + metadata.len()))
+}
+
 fn create_instance_content_summary(path: &Path, mod_metadata_manager: &Arc<ModMetadataManager>) -> Option<InstanceContentSummary> {
     if !path.is_file() {
         return None;
@@ -763,7 +821,7 @@ fn create_instance_content_summary(path: &Path, mod_metadata_manager: &Arc<ModMe
     } else if filename.ends_with(".jar") || filename.ends_with(".mrpack") || filename.ends_with(".zip") {
         true
     } else {
-        log::trace!("Skipping content file {}, unknown extension", filename);
+        tracing::trace!("Skipping content file {}, unknown extension", filename);
         return None;
     };
     let Ok(mut file) = std::fs::File::open(&path) else {
@@ -843,15 +901,12 @@ fn load_world_summary(path: &Path) -> anyhow::Result<InstanceWorldSummary> {
     let data = root.find_compound("Data").context("Unable to get Data")?;
     let last_played: i64 = data.find_numeric("LastPlayed").context("Unable to get LastPlayed")?;
     let level_name = data.find_string("LevelName").cloned().unwrap_or_default();
+    let version = data.find_compound("Version").and_then(|version| version.find_string("Name").map(|name| Arc::from(name.as_str())));
 
     let folder = path.file_name().context("Unable to get filename")?.to_string_lossy();
 
-    let subtitle = if let Some(date_time) = chrono::DateTime::from_timestamp_millis(last_played) && last_played > 0 {
-        let date_time = date_time.with_timezone(&chrono::Local);
-        format!("{} ({})", folder, date_time.format("%d/%m/%Y %H:%M")).into()
-    } else {
-        format!("{}", folder).into()
-    };
+    // The last played time is formatted by the frontend, in the user's locale
+    let subtitle = folder.as_ref().into();
 
     let title = if level_name.is_empty() {
         folder.into_owned().into()
@@ -871,6 +926,7 @@ fn load_world_summary(path: &Path) -> anyhow::Result<InstanceWorldSummary> {
         subtitle,
         level_path: path.into(),
         last_played,
+        version,
         png_icon: icon,
     })
 }
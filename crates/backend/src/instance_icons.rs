@@ -0,0 +1,52 @@
+use std::{io::Cursor, path::Path, sync::Arc};
+
+use image::imageops::FilterType;
+use sha1::{Digest, Sha1};
+
+/// Icons are shown small in the instance list, so larger images are scaled down to keep the folder small
+const ICON_SIZE: u32 = 128;
+
+#[derive(thiserror::Error, Debug)]
+pub enum InstanceIconError {
+    #[error("Invalid image: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Copies the image at `path` into `icons_dir` as a square PNG, returning where it was written. The file is named by
+/// its SHA-1 so that picking another image never reuses a path the frontend may have cached
+pub fn import(icons_dir: &Path, path: &Path) -> Result<Arc<Path>, InstanceIconError> {
+    let image = image::open(path)?;
+    let image = if image.width() > ICON_SIZE || image.height() > ICON_SIZE || image.width() != image.height() {
+        image.resize_to_fill(ICON_SIZE, ICON_SIZE, FilterType::Lanczos3)
+    } else {
+        image
+    };
+
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)?;
+
+    let icon_path = icons_dir.join(format!("{}.png", hex::encode(Sha1::digest(&png))));
+    if !icon_path.exists() {
+        crate::write_safe(&icon_path, &png)?;
+    }
+    Ok(icon_path.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::TempDir;
+
+    #[test]
+    fn large_images_are_cropped_to_a_square() {
+        let dir = TempDir::new();
+        let source = dir.path().join("banner.png");
+        image::RgbaImage::new(400, 200).save(&source).unwrap();
+
+        let icon = import(&dir.path().join("icons"), &source).unwrap();
+        let icon = image::open(&icon).unwrap();
+        assert_eq!((icon.width(), icon.height()), (ICON_SIZE, ICON_SIZE));
+    }
+}
@@ -0,0 +1,182 @@
+//! Lets a second invocation of the launcher hand its command to the one that's already running, so there is only
+//! ever one process working on the launcher directory
+
+use std::{io::{BufRead, BufReader, Write}, path::Path, sync::atomic::{AtomicBool, Ordering}};
+
+use bridge::message::MessageToFrontend;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt};
+
+use crate::BackendState;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IpcRequest {
+    /// Brings the existing window to the front
+    Focus,
+    /// Launches the instance with this name in the running launcher
+    Launch {
+        name: String,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IpcResponse {
+    Ok,
+    Error {
+        message: String,
+    },
+}
+
+static SERVE: AtomicBool = AtomicBool::new(false);
+
+/// Only a launcher with a window can act on requests, headless commands leave them to the next window that opens
+pub fn enable_server() {
+    SERVE.store(true, Ordering::Relaxed);
+}
+
+pub fn is_server_enabled() -> bool {
+    SERVE.load(Ordering::Relaxed)
+}
+
+#[cfg(unix)]
+fn socket_path(launcher_dir: &Path) -> std::path::PathBuf {
+    launcher_dir.join("launcher.sock")
+}
+
+/// Pipe names are global, so the launcher directory is part of the name to keep portable installs apart
+#[cfg(windows)]
+fn pipe_name(launcher_dir: &Path) -> String {
+    use sha1::{Digest, Sha1};
+    let hash = Sha1::digest(launcher_dir.as_os_str().as_encoded_bytes());
+    format!(r"\\.\pipe\PandoraLauncher-{}", hex::encode(&hash[..8]))
+}
+
+/// Sends the request to a launcher that's already running in `launcher_dir`. Returns None if there is none, in which
+/// case this process should handle the request itself
+pub fn forward(launcher_dir: &Path, request: &IpcRequest) -> Option<IpcResponse> {
+    #[cfg(unix)]
+    let stream = std::os::unix::net::UnixStream::connect(socket_path(launcher_dir)).ok()?;
+    #[cfg(windows)]
+    let stream = std::fs::OpenOptions::new().read(true).write(true).open(pipe_name(launcher_dir)).ok()?;
+
+    let mut line = serde_json::to_string(request).ok()?;
+    line.push('\n');
+    (&stream).write_all(line.as_bytes()).ok()?;
+
+    let mut response = String::new();
+    BufReader::new(&stream).read_line(&mut response).ok()?;
+    serde_json::from_str(&response).ok()
+}
+
+impl BackendState {
+    /// Accepts requests from other invocations of the launcher until the backend shuts down
+    pub async fn serve_ipc(self) {
+        #[cfg(unix)]
+        {
+            let path = socket_path(&self.directories.root_launcher_dir);
+            if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+                tracing::warn!("Another launcher is already listening on {}", path.display());
+                return;
+            }
+            // A socket left behind by a launcher that crashed can't be bound over
+            _ = std::fs::remove_file(&path);
+            let listener = match tokio::net::UnixListener::bind(&path) {
+                Ok(listener) => listener,
+                Err(error) => {
+                    tracing::error!("Unable to listen for other launcher invocations: {error}");
+                    return;
+                },
+            };
+            loop {
+                tokio::select! {
+                    accepted = listener.accept() => match accepted {
+                        Ok((stream, _)) => {
+                            tokio::task::spawn(self.clone().handle_ipc_connection(stream));
+                        },
+                        Err(error) => tracing::warn!("Unable to accept launcher invocation: {error}"),
+                    },
+                    _ = self.shutdown.cancelled() => break,
+                }
+            }
+            _ = std::fs::remove_file(&path);
+        }
+
+        #[cfg(windows)]
+        {
+            use tokio::net::windows::named_pipe::ServerOptions;
+
+            let name = pipe_name(&self.directories.root_launcher_dir);
+            let mut server = match ServerOptions::new().first_pipe_instance(true).create(&name) {
+                Ok(server) => server,
+                Err(error) => {
+                    tracing::error!("Unable to listen for other launcher invocations: {error}");
+                    return;
+                },
+            };
+            loop {
+                tokio::select! {
+                    connected = server.connect() => {
+                        if let Err(error) = connected {
+                            tracing::warn!("Unable to accept launcher invocation: {error}");
+                            continue;
+                        }
+                        let next = match ServerOptions::new().create(&name) {
+                            Ok(next) => next,
+                            Err(error) => {
+                                tracing::error!("Unable to keep listening for other launcher invocations: {error}");
+                                return;
+                            },
+                        };
+                        let connected = std::mem::replace(&mut server, next);
+                        tokio::task::spawn(self.clone().handle_ipc_connection(connected));
+                    },
+                    _ = self.shutdown.cancelled() => break,
+                }
+            }
+        }
+    }
+
+    async fn handle_ipc_connection(self, stream: impl AsyncRead + AsyncWrite + Unpin) {
+        let (reader, mut writer) = tokio::io::split(stream);
+        let mut line = String::new();
+        if tokio::io::BufReader::new(reader).read_line(&mut line).await.is_err() {
+            return;
+        }
+
+        let response = match serde_json::from_str::<IpcRequest>(&line) {
+            Ok(request) => {
+                tracing::info!("Received {request:?} from another launcher invocation");
+                self.handle_ipc_request(request)
+            },
+            Err(error) => IpcResponse::Error { message: format!("Invalid request: {error}") },
+        };
+
+        if let Ok(mut response) = serde_json::to_string(&response) {
+            response.push('\n');
+            _ = writer.write_all(response.as_bytes()).await;
+        }
+    }
+
+    fn handle_ipc_request(&self, request: IpcRequest) -> IpcResponse {
+        match request {
+            IpcRequest::Focus => {
+                self.send.send(MessageToFrontend::FocusWindow);
+                IpcResponse::Ok
+            },
+            IpcRequest::Launch { name } => {
+                let instance = self.instance_state.read().instances.iter()
+                    .find(|instance| instance.name.as_str() == name)
+                    .map(|instance| (instance.id, instance.name));
+                match instance {
+                    Some((id, name)) => {
+                        self.send.send(MessageToFrontend::LaunchRequested { id, name });
+                        IpcResponse::Ok
+                    },
+                    None => IpcResponse::Error { message: format!("Unable to find instance {name}") },
+                }
+            },
+        }
+    }
+}
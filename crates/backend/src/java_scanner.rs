@@ -0,0 +1,130 @@
+use std::{path::{Path, PathBuf}, process::Stdio, sync::Arc};
+
+use bridge::message::JavaInstallation;
+use rustc_hash::FxHashSet;
+
+use crate::{directories::LauncherDirectories, launch::Launcher};
+
+/// Looks for installed JVMs in the usual places and asks each one for its version, vendor and
+/// architecture. Spawns a process per candidate, so this should be run on a blocking thread
+pub fn scan(directories: &LauncherDirectories) -> Vec<JavaInstallation> {
+    let mut binaries = Vec::new();
+    let mut seen = FxHashSet::default();
+
+    for home in candidate_homes(directories) {
+        let Some(binary) = Launcher::search_for_java_binary(&home) else {
+            continue;
+        };
+        // Distros commonly symlink the same JVM under several names
+        let canonical = binary.canonicalize().unwrap_or_else(|_| binary.clone());
+        if seen.insert(canonical) {
+            binaries.push(binary);
+        }
+    }
+
+    let mut installations: Vec<JavaInstallation> = binaries.into_iter().filter_map(|binary| probe(&binary)).collect();
+    installations.sort_by(|a, b| b.major_version.cmp(&a.major_version).then_with(|| a.path.cmp(&b.path)));
+    installations
+}
+
+fn candidate_homes(directories: &LauncherDirectories) -> Vec<PathBuf> {
+    let mut homes = Vec::new();
+
+    if let Some(java_home) = std::env::var_os("JAVA_HOME") {
+        homes.push(PathBuf::from(java_home));
+    }
+
+    let java_name = if cfg!(windows) { "javaw.exe" } else { "java" };
+    if let Some(path) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&path) {
+            let binary = dir.join(java_name);
+            if binary.is_file() {
+                homes.push(binary);
+            }
+        }
+    }
+
+    let user_home = std::env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).map(PathBuf::from);
+
+    let mut parents: Vec<PathBuf> = Vec::new();
+    if let Some(user_home) = &user_home {
+        parents.push(user_home.join(".sdkman").join("candidates").join("java"));
+        parents.push(user_home.join(".jdks"));
+    }
+
+    #[cfg(target_os = "linux")]
+    parents.extend(["/usr/lib/jvm", "/usr/lib64/jvm", "/usr/java", "/opt/java", "/opt"].map(PathBuf::from));
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut mac_parents = vec![PathBuf::from("/Library/Java/JavaVirtualMachines")];
+        if let Some(user_home) = &user_home {
+            mac_parents.push(user_home.join("Library").join("Java").join("JavaVirtualMachines"));
+        }
+        for parent in mac_parents {
+            homes.extend(child_dirs(&parent).into_iter().map(|jvm| jvm.join("Contents").join("Home")));
+        }
+    }
+
+    #[cfg(windows)]
+    for program_files in ["ProgramFiles", "ProgramFiles(x86)"] {
+        if let Some(program_files) = std::env::var_os(program_files) {
+            let program_files = PathBuf::from(program_files);
+            for vendor in ["Java", "Eclipse Adoptium", "Eclipse Foundation", "Microsoft", "Zulu", "BellSoft", "Amazon Corretto", "Semeru"] {
+                parents.push(program_files.join(vendor));
+            }
+        }
+    }
+
+    for parent in parents {
+        homes.extend(child_dirs(&parent));
+    }
+
+    // Runtimes downloaded by the launcher itself
+    for component in child_dirs(&directories.runtime_base_dir) {
+        homes.extend(child_dirs(&component));
+    }
+
+    homes
+}
+
+fn child_dirs(path: &Path) -> Vec<PathBuf> {
+    let Ok(read_dir) = std::fs::read_dir(path) else {
+        return Vec::new();
+    };
+    read_dir.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()).collect()
+}
+
+/// Runs the JVM with `-XshowSettings:properties` and reads the interesting properties back
+fn probe(binary: &Path) -> Option<JavaInstallation> {
+    let mut command = std::process::Command::new(binary);
+    command.args(["-XshowSettings:properties", "-version"]);
+    command.stdin(Stdio::null());
+    command.stdout(Stdio::null());
+    command.stderr(Stdio::piped());
+    crate::process::configure_command(&mut command);
+
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    let property = |name: &str| -> Option<Arc<str>> {
+        stderr.lines().find_map(|line| {
+            let (key, value) = line.trim().split_once(" = ")?;
+            (key == name).then(|| Arc::from(value.trim()))
+        })
+    };
+
+    let specification_version = property("java.specification.version")?;
+    let major_version = specification_version.strip_prefix("1.").unwrap_or(&specification_version).parse().ok()?;
+
+    Some(JavaInstallation {
+        path: binary.into(),
+        major_version,
+        version: property("java.version"),
+        vendor: property("java.vendor"),
+        arch: property("os.arch"),
+    })
+}
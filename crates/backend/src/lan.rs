@@ -0,0 +1,379 @@
+use std::{
+    io::{BufRead, BufReader, Read}, net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket}, sync::{atomic::{AtomicBool, Ordering}, Arc}, time::Duration
+};
+
+use bridge::instance::{InstanceID, LanGameInfo};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use regex::Regex;
+
+use crate::{startup_watch::StartupWatch, BackendState};
+
+const SSDP_ADDRESS: &str = "239.255.255.250:1900";
+const SSDP_SEARCH: &str = "M-SEARCH * HTTP/1.1\r\nHOST: 239.255.255.250:1900\r\nST: urn:schemas-upnp-org:device:InternetGatewayDevice:1\r\nMAN: \"ssdp:discover\"\r\nMX: 2\r\n\r\n";
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(3);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// Mappings expire on their own if the launcher stops without removing them, so they're renewed while the game runs
+const LEASE_DURATION: Duration = Duration::from_secs(60 * 60);
+const LEASE_RENEWAL_INTERVAL: Duration = Duration::from_secs(30 * 60);
+/// UPnP error code of gateways that only support mappings without a lease duration
+const ONLY_PERMANENT_LEASES_SUPPORTED: &str = "725";
+/// Services of an internet gateway that can forward ports, in order of preference
+const FORWARDING_SERVICES: &[&str] = &[
+    "urn:schemas-upnp-org:service:WANIPConnection:2",
+    "urn:schemas-upnp-org:service:WANIPConnection:1",
+    "urn:schemas-upnp-org:service:WANPPPConnection:1",
+];
+
+/// The integrated server logs "Started serving on", the chat message is "Local game hosted on port", and some
+/// mods and older versions print "Opened to LAN on port"
+static LAN_PORT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:Started serving on|Local game hosted on port|Opened to LAN on port) \[?(\d{1,5})").unwrap()
+});
+
+pub fn detect_lan_port(line: &str) -> Option<u16> {
+    LAN_PORT.captures(line)?.get(1)?.as_str().parse().ok()
+}
+
+/// Address of this machine on the local network, found by asking the OS which interface it would route through
+pub fn local_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    // Connecting a UDP socket doesn't send anything
+    socket.connect("8.8.8.8:80").ok()?;
+    Some(socket.local_addr().ok()?.ip())
+}
+
+/// Notices when the game running an instance is opened to LAN
+#[derive(Default, Debug)]
+pub struct LanWatch {
+    address: Mutex<Option<SocketAddr>>,
+    changed: AtomicBool,
+}
+
+impl LanWatch {
+    pub fn observe(&self, line: &str) {
+        let Some(port) = detect_lan_port(line) else {
+            return;
+        };
+        let ip = local_ip().unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        *self.address.lock() = Some(SocketAddr::new(ip, port));
+        self.changed.store(true, Ordering::Release);
+    }
+
+    pub fn address(&self) -> Option<SocketAddr> {
+        *self.address.lock()
+    }
+
+    /// Returns whether a port was detected since the last call
+    pub fn take_changed(&self) -> bool {
+        self.changed.swap(false, Ordering::AcqRel)
+    }
+}
+
+/// Reads all game output when it isn't shown to the user, so that the game being opened to LAN is still noticed
+pub fn watch_output(output: impl Read + Send + 'static, startup_watch: Arc<StartupWatch>, lan_watch: Arc<LanWatch>) {
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(output);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match reader.read_line(&mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    startup_watch.observe(&line);
+                    lan_watch.observe(&line);
+                },
+            }
+        }
+    });
+}
+
+pub fn lan_game_info(lan_watch: &LanWatch, port_forward: Option<&PortForward>) -> Option<LanGameInfo> {
+    let local_address = lan_watch.address()?;
+    let port_forward = port_forward.filter(|forward| forward.port == local_address.port());
+    let public_address = port_forward
+        .and_then(|forward| forward.external_ip)
+        .map(|ip| SocketAddrV4::new(ip, local_address.port()));
+    Some(LanGameInfo { local_address, port_forwarded: port_forward.is_some(), public_address })
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum PortForwardError {
+    #[error("No UPnP gateway was found on the network")]
+    NoGateway,
+    #[error("The gateway doesn't support port forwarding")]
+    Unsupported,
+    #[error("Network error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Unable to reach the gateway: {0}")]
+    Reqwest(#[from] reqwest::Error),
+    #[error("The gateway refused the request: {0}")]
+    Refused(String),
+    #[error("The gateway only supports permanent port forwards")]
+    OnlyPermanentLeases,
+}
+
+#[derive(Debug, Clone)]
+struct Gateway {
+    control_url: reqwest::Url,
+    service_type: &'static str,
+    local_ip: Ipv4Addr,
+}
+
+/// A port mapping added to the gateway, which has to be removed again when the game stops. The lease is renewed
+/// until then, and runs out by itself if the launcher stops without removing it
+#[derive(Debug)]
+pub struct PortForward {
+    gateway: Gateway,
+    pub port: u16,
+    pub external_ip: Option<Ipv4Addr>,
+    renewal: Option<tokio::task::AbortHandle>,
+}
+
+impl Drop for PortForward {
+    fn drop(&mut self) {
+        if let Some(renewal) = &self.renewal {
+            renewal.abort();
+        }
+    }
+}
+
+/// Returns the text inside the first `<tag>...</tag>` in `xml`
+fn tag_text<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{tag}>"))? + tag.len() + 2;
+    let length = xml[start..].find(&format!("</{tag}>"))?;
+    Some(xml[start..start + length].trim())
+}
+
+/// Finds the control url of a service that can forward ports in a gateway's device description
+fn find_forwarding_service(description: &str) -> Option<(&'static str, &str)> {
+    let services: Vec<&str> = description.split("<service>").skip(1).collect();
+    FORWARDING_SERVICES.iter().find_map(|service_type| {
+        services.iter().find_map(|service| {
+            (tag_text(service, "serviceType")? == *service_type).then_some((*service_type, tag_text(service, "controlURL")?))
+        })
+    })
+}
+
+fn ssdp_location(response: &str) -> Option<&str> {
+    response.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim().eq_ignore_ascii_case("location").then(|| value.trim())
+    })
+}
+
+async fn discover_gateway(client: &reqwest::Client) -> Result<Gateway, PortForwardError> {
+    let socket = tokio::net::UdpSocket::bind("0.0.0.0:0").await?;
+    socket.send_to(SSDP_SEARCH.as_bytes(), SSDP_ADDRESS).await?;
+
+    let mut buffer = [0u8; 2048];
+    let (location, gateway_address) = tokio::time::timeout(DISCOVERY_TIMEOUT, async {
+        loop {
+            let (length, from) = socket.recv_from(&mut buffer).await?;
+            let response = String::from_utf8_lossy(&buffer[..length]);
+            if let Some(location) = ssdp_location(&response) {
+                return Ok::<_, std::io::Error>((location.to_string(), from));
+            }
+        }
+    }).await.map_err(|_| PortForwardError::NoGateway)??;
+
+    let location = reqwest::Url::parse(&location).map_err(|_| PortForwardError::NoGateway)?;
+    let description = client.get(location.clone()).timeout(REQUEST_TIMEOUT).send().await?.error_for_status()?.text().await?;
+    let (service_type, control_url) = find_forwarding_service(&description).ok_or(PortForwardError::Unsupported)?;
+    let control_url = location.join(control_url).map_err(|_| PortForwardError::Unsupported)?;
+
+    // The address the gateway is reached through is the one that mapped ports should point to
+    let route = UdpSocket::bind("0.0.0.0:0")?;
+    route.connect(gateway_address)?;
+    let IpAddr::V4(local_ip) = route.local_addr()?.ip() else {
+        return Err(PortForwardError::Unsupported);
+    };
+
+    Ok(Gateway { control_url, service_type, local_ip })
+}
+
+async fn soap_request(client: &reqwest::Client, gateway: &Gateway, action: &str, arguments: &[(&str, String)]) -> Result<String, PortForwardError> {
+    let arguments: String = arguments.iter().map(|(name, value)| format!("<{name}>{value}</{name}>")).collect();
+    let body = format!(concat!(
+        r#"<?xml version="1.0"?>"#,
+        r#"<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" s:encodingStyle="http://schemas.xmlsoap.org/soap/encoding/">"#,
+        r#"<s:Body><u:{action} xmlns:u="{service}">{arguments}</u:{action}></s:Body></s:Envelope>"#,
+    ), action = action, service = gateway.service_type, arguments = arguments);
+
+    let response = client.post(gateway.control_url.clone())
+        .timeout(REQUEST_TIMEOUT)
+        .header(reqwest::header::CONTENT_TYPE, r#"text/xml; charset="utf-8""#)
+        .header("SOAPAction", format!(r#""{}#{action}""#, gateway.service_type))
+        .body(body)
+        .send().await?;
+
+    let status = response.status();
+    let text = response.text().await?;
+    if !status.is_success() {
+        if tag_text(&text, "errorCode") == Some(ONLY_PERMANENT_LEASES_SUPPORTED) {
+            return Err(PortForwardError::OnlyPermanentLeases);
+        }
+        let description = tag_text(&text, "errorDescription").map(str::to_string).unwrap_or_else(|| status.to_string());
+        return Err(PortForwardError::Refused(description));
+    }
+    Ok(text)
+}
+
+async fn add_port_mapping(client: &reqwest::Client, gateway: &Gateway, port: u16, lease: Duration) -> Result<(), PortForwardError> {
+    soap_request(client, gateway, "AddPortMapping", &[
+        ("NewRemoteHost", String::new()),
+        ("NewExternalPort", port.to_string()),
+        ("NewProtocol", "TCP".to_string()),
+        ("NewInternalPort", port.to_string()),
+        ("NewInternalClient", gateway.local_ip.to_string()),
+        ("NewEnabled", "1".to_string()),
+        ("NewPortMappingDescription", "Pandora Launcher LAN game".to_string()),
+        ("NewLeaseDuration", lease.as_secs().to_string()),
+    ]).await?;
+    Ok(())
+}
+
+pub async fn forward_port(client: &reqwest::Client, port: u16) -> Result<PortForward, PortForwardError> {
+    let gateway = discover_gateway(client).await?;
+
+    let renewal = match add_port_mapping(client, &gateway, port, LEASE_DURATION).await {
+        Ok(()) => {
+            let client = client.clone();
+            let gateway = gateway.clone();
+            let renewal = tokio::task::spawn(async move {
+                loop {
+                    tokio::time::sleep(LEASE_RENEWAL_INTERVAL).await;
+                    if let Err(error) = add_port_mapping(&client, &gateway, port, LEASE_DURATION).await {
+                        tracing::warn!("Unable to renew port forward: {error}");
+                    }
+                }
+            });
+            Some(renewal.abort_handle())
+        },
+        // Such mappings stay until they're removed, which happens when the game stops or the launcher exits
+        Err(PortForwardError::OnlyPermanentLeases) => {
+            add_port_mapping(client, &gateway, port, Duration::ZERO).await?;
+            None
+        },
+        Err(error) => return Err(error),
+    };
+
+    let external_ip = match soap_request(client, &gateway, "GetExternalIPAddress", &[]).await {
+        Ok(response) => tag_text(&response, "NewExternalIPAddress").and_then(|ip| ip.parse().ok()),
+        Err(error) => {
+            tracing::warn!("Unable to query external IP address: {error}");
+            None
+        },
+    };
+
+    Ok(PortForward { gateway, port, external_ip, renewal })
+}
+
+impl PortForward {
+    pub async fn remove(self, client: &reqwest::Client) -> Result<(), PortForwardError> {
+        if let Some(renewal) = &self.renewal {
+            renewal.abort();
+        }
+        soap_request(client, &self.gateway, "DeletePortMapping", &[
+            ("NewRemoteHost", String::new()),
+            ("NewExternalPort", self.port.to_string()),
+            ("NewProtocol", "TCP".to_string()),
+        ]).await?;
+        Ok(())
+    }
+}
+
+impl BackendState {
+    /// Forwards the port of the instance's LAN game through UPnP, or removes the forward again
+    pub async fn set_lan_port_forwarding(&self, id: InstanceID, enabled: bool) {
+        let (port, previous) = {
+            let mut instance_state = self.instance_state.write();
+            let Some(process) = instance_state.instances.get_mut(id).and_then(|instance| instance.process.as_mut()) else {
+                return;
+            };
+            (process.lan_watch.address().map(|address| address.port()), process.port_forward.take())
+        };
+
+        if let Some(previous) = previous
+            && let Err(error) = previous.remove(&self.http_client).await
+        {
+            tracing::warn!("Unable to remove port forward: {error}");
+        }
+
+        let forward = match port {
+            Some(port) if enabled => match forward_port(&self.http_client, port).await {
+                Ok(forward) => Some(forward),
+                Err(error) => {
+                    self.send.send_error(rust_i18n::t!("notification_port_forward_failed", port = port, error = error));
+                    None
+                },
+            },
+            _ => None,
+        };
+
+        let mut instance_state = self.instance_state.write();
+        let process = instance_state.instances.get_mut(id).and_then(|instance| instance.process.as_mut());
+        match (process, forward) {
+            (Some(process), Some(forward)) => {
+                match forward.external_ip {
+                    Some(ip) => self.send.send_success(rust_i18n::t!("notification_port_forwarded_at", port = forward.port, ip = ip)),
+                    None => self.send.send_success(rust_i18n::t!("notification_port_forwarded", port = forward.port)),
+                }
+                process.port_forward = Some(forward);
+            },
+            (None, Some(forward)) => {
+                // The game stopped while the port was being forwarded
+                self.remove_port_forward(forward);
+            },
+            _ => {},
+        }
+
+        if let Some(instance) = instance_state.instances.get(id) {
+            self.send.send(instance.create_modify_message());
+        }
+    }
+
+    /// Removes the port forwards of all games, for when the launcher exits
+    pub async fn remove_all_port_forwards(&self) {
+        let forwards: Vec<PortForward> = self.instance_state.write().instances.iter_mut()
+            .filter_map(|instance| instance.process.as_mut()?.port_forward.take())
+            .collect();
+
+        let removals = forwards.into_iter().map(|forward| forward.remove(&self.http_client));
+        for result in futures::future::join_all(removals).await {
+            if let Err(error) = result {
+                tracing::warn!("Unable to remove port forward: {error}");
+            }
+        }
+    }
+
+    pub fn remove_port_forward(&self, forward: PortForward) {
+        let http_client = self.http_client.clone();
+        tokio::task::spawn(async move {
+            if let Err(error) = forward.remove(&http_client).await {
+                tracing::warn!("Unable to remove port forward: {error}");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_lan_port_and_gateway_service() {
+        assert_eq!(detect_lan_port("[Server thread/INFO]: Started serving on 51234"), Some(51234));
+        assert_eq!(detect_lan_port("[CHAT] Local game hosted on port [25565]"), Some(25565));
+        assert_eq!(detect_lan_port("Opened to LAN on port 40000"), Some(40000));
+        assert_eq!(detect_lan_port("Started serving on 99999"), None);
+        assert_eq!(detect_lan_port("Loading 42 recipes"), None);
+
+        let description = "<root><device><serviceList>\
+            <service><serviceType>urn:schemas-upnp-org:service:Layer3Forwarding:1</serviceType><controlURL>/l3f</controlURL></service>\
+            <service><serviceType>urn:schemas-upnp-org:service:WANIPConnection:1</serviceType><controlURL>/ipc</controlURL></service>\
+            </serviceList></device></root>";
+        assert_eq!(find_forwarding_service(description), Some(("urn:schemas-upnp-org:service:WANIPConnection:1", "/ipc")));
+        assert_eq!(ssdp_location("HTTP/1.1 200 OK\r\nLocation: http://192.168.1.1:5000/desc.xml\r\n\r\n"), Some("http://192.168.1.1:5000/desc.xml"));
+    }
+}
@@ -0,0 +1,334 @@
+use std::{
+    io::{BufRead, BufReader, Write}, net::{Ipv4Addr, SocketAddr, SocketAddrV4, TcpListener, TcpStream, UdpSocket}, path::Path, sync::Arc, time::{Duration, Instant}
+};
+
+use bridge::{
+    instance::{InstanceID, LanShare}, message::InstanceExportFolder, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}
+};
+use enumset::EnumSet;
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use crate::BackendState;
+
+// Shares are registered over mDNS and also announced over multicast the same way Minecraft announces LAN games,
+// which still reaches launchers on networks that filter mDNS
+const MDNS_SERVICE_TYPE: &str = "_pandora-share._tcp.local.";
+const SHARE_GROUP: Ipv4Addr = Ipv4Addr::new(239, 255, 77, 78);
+const SHARE_PORT: u16 = 4447;
+const ANNOUNCEMENT_VERSION: u32 = 1;
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(1);
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(3);
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Saves aren't shared, everyone at a LAN party plays on the host's world
+const SHARED_FOLDERS: EnumSet<InstanceExportFolder> = enumset::enum_set!(
+    InstanceExportFolder::Mods | InstanceExportFolder::Config | InstanceExportFolder::ResourcePacks | InstanceExportFolder::ShaderPacks
+);
+
+#[derive(Serialize, Deserialize)]
+struct Announcement {
+    version: u32,
+    name: Arc<str>,
+    port: u16,
+    /// Random part of the download path, so only launchers that received the announcement can download the share
+    token: Arc<str>,
+}
+
+/// Registers the share over mDNS, the daemon answers queries from other launchers until it's shut down
+fn register_mdns(name: &str, port: u16, token: &str) -> mdns_sd::Result<mdns_sd::ServiceDaemon> {
+    let daemon = mdns_sd::ServiceDaemon::new()?;
+    let version = ANNOUNCEMENT_VERSION.to_string();
+    let properties = [("version", version.as_str()), ("name", name), ("token", token)];
+    let service = mdns_sd::ServiceInfo::new(MDNS_SERVICE_TYPE, token, &format!("pandora-{token}.local."), "", port, &properties[..])?
+        .enable_addr_auto();
+    daemon.register(service)?;
+    Ok(daemon)
+}
+
+/// Serves the archive over HTTP and announces it on the local network until cancelled
+fn serve_share(archive: &Path, name: &str, cancel: &CancellationToken) -> std::io::Result<()> {
+    let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    listener.set_nonblocking(true)?;
+
+    let token: Arc<str> = format!("{:016x}", rand::random::<u64>()).into();
+    let request_path: Arc<str> = format!("/{token}.zip").into();
+    let port = listener.local_addr()?.port();
+    let mdns = match register_mdns(name, port, &token) {
+        Ok(daemon) => Some(daemon),
+        Err(error) => {
+            tracing::warn!("Unable to register LAN share over mDNS, only announcing it over multicast: {error}");
+            None
+        },
+    };
+    let announcement = serde_json::to_vec(&Announcement {
+        version: ANNOUNCEMENT_VERSION,
+        name: name.into(),
+        port,
+        token,
+    })?;
+
+    let announcer = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    announcer.set_multicast_ttl_v4(1)?;
+    let mut last_announced: Option<Instant> = None;
+
+    while !cancel.is_cancelled() {
+        if last_announced.is_none_or(|at| at.elapsed() >= ANNOUNCE_INTERVAL) {
+            // Failures like not being connected to a network yet are retried with the next announcement
+            if let Err(error) = announcer.send_to(&announcement, SocketAddrV4::new(SHARE_GROUP, SHARE_PORT)) {
+                tracing::debug!("Unable to announce LAN share: {error}");
+            }
+            last_announced = Some(Instant::now());
+        }
+
+        match listener.accept() {
+            Ok((stream, address)) => {
+                tracing::info!("Sending shared instance to {address}");
+                let archive = archive.to_path_buf();
+                let request_path = request_path.clone();
+                std::thread::spawn(move || {
+                    if let Err(error) = respond(stream, &archive, &request_path) {
+                        tracing::warn!("Unable to send shared instance to {address}: {error}");
+                    }
+                });
+            },
+            Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => std::thread::sleep(ACCEPT_INTERVAL),
+            Err(error) => {
+                if let Some(mdns) = mdns {
+                    _ = mdns.shutdown();
+                }
+                return Err(error);
+            },
+        }
+    }
+
+    // Shutting down sends a goodbye, so other launchers forget the share right away
+    if let Some(mdns) = mdns {
+        _ = mdns.shutdown();
+    }
+    Ok(())
+}
+
+/// Answers a single HTTP request, sending the archive when `request_path` was requested
+fn respond(stream: TcpStream, archive: &Path, request_path: &str) -> std::io::Result<()> {
+    // Accepted streams inherit the listener being non-blocking on some platforms
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_ascii_whitespace();
+    let found = parts.next() == Some("GET") && parts.next() == Some(request_path);
+
+    let mut writer = &stream;
+    if !found {
+        writer.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")?;
+        return Ok(());
+    }
+
+    let mut file = std::fs::File::open(archive)?;
+    let length = file.metadata()?.len();
+    write!(writer, "HTTP/1.1 200 OK\r\nContent-Type: application/zip\r\nContent-Length: {length}\r\nConnection: close\r\n\r\n")?;
+    std::io::copy(&mut file, &mut writer)?;
+    writer.flush()
+}
+
+/// Binds the announcement port shared with other launchers on this machine, which may be discovering at the same time
+fn bind_announcement_socket() -> std::io::Result<tokio::net::UdpSocket> {
+    let socket = socket2::Socket::new(socket2::Domain::IPV4, socket2::Type::DGRAM, Some(socket2::Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, SHARE_PORT)).into())?;
+    socket.set_nonblocking(true)?;
+    let socket = tokio::net::UdpSocket::from_std(socket.into())?;
+    socket.join_multicast_v4(SHARE_GROUP, Ipv4Addr::UNSPECIFIED)?;
+    Ok(socket)
+}
+
+/// Browses for shares registered over mDNS until the deadline
+fn discover_mdns(deadline: Instant) -> mdns_sd::Result<Vec<LanShare>> {
+    let daemon = mdns_sd::ServiceDaemon::new()?;
+    let events = daemon.browse(MDNS_SERVICE_TYPE)?;
+
+    let mut shares = Vec::new();
+    while let Ok(event) = events.recv_deadline(deadline) {
+        let mdns_sd::ServiceEvent::ServiceResolved(service) = event else {
+            continue;
+        };
+        if service.get_property_val_str("version") != Some(&*ANNOUNCEMENT_VERSION.to_string()) {
+            continue;
+        }
+        let (Some(name), Some(token), Some(ip)) = (
+            service.get_property_val_str("name"),
+            service.get_property_val_str("token"),
+            service.get_addresses_v4().into_iter().next(),
+        ) else {
+            continue;
+        };
+        let address = SocketAddr::new((*ip).into(), service.get_port());
+        shares.push(LanShare {
+            name: name.into(),
+            address,
+            url: format!("http://{address}/{token}.zip").into(),
+        });
+    }
+
+    _ = daemon.shutdown();
+    Ok(shares)
+}
+
+/// Listens for announcements of other launchers for a few seconds, both over mDNS and multicast
+pub async fn discover() -> std::io::Result<Vec<LanShare>> {
+    let mdns = tokio::task::spawn_blocking(|| discover_mdns(Instant::now() + DISCOVERY_TIMEOUT));
+
+    let socket = bind_announcement_socket()?;
+    let deadline = tokio::time::Instant::now() + DISCOVERY_TIMEOUT;
+    let mut shares: Vec<LanShare> = Vec::new();
+    let mut buffer = [0_u8; 2048];
+    loop {
+        let (length, from) = tokio::select! {
+            received = socket.recv_from(&mut buffer) => received?,
+            _ = tokio::time::sleep_until(deadline) => break,
+        };
+        let Ok(announcement) = serde_json::from_slice::<Announcement>(&buffer[..length]) else {
+            continue;
+        };
+        if announcement.version != ANNOUNCEMENT_VERSION {
+            continue;
+        }
+
+        let address = SocketAddr::new(from.ip(), announcement.port);
+        if shares.iter().any(|share| share.address == address) {
+            continue;
+        }
+        shares.push(LanShare {
+            name: announcement.name,
+            address,
+            url: format!("http://{address}/{}.zip", announcement.token).into(),
+        });
+    }
+
+    match mdns.await.unwrap() {
+        Ok(mdns_shares) => {
+            for share in mdns_shares {
+                if !shares.iter().any(|existing| existing.address == share.address) {
+                    shares.push(share);
+                }
+            }
+        },
+        Err(error) => tracing::warn!("Unable to browse for LAN shares over mDNS: {error}"),
+    }
+
+    Ok(shares)
+}
+
+impl BackendState {
+    /// Lets other launchers on the local network import the instance, until the modal action is cancelled
+    pub async fn share_instance_on_lan(&self, id: InstanceID, modal_action: &ModalAction) {
+        if let Err(error) = std::fs::create_dir_all(&self.directories.temp_dir) {
+            modal_action.set_error_message(format!("Unable to share instance: {error}").into());
+            return;
+        }
+        let archive: Arc<Path> = self.directories.temp_dir.join(format!("lan-share-{:016x}.zip", rand::random::<u64>())).into();
+
+        let name = match self.write_instance_archive(id, &archive, SHARED_FOLDERS, modal_action).await {
+            Ok(Some(name)) => name,
+            Ok(None) => return,
+            Err(error) => {
+                modal_action.set_error_message(error);
+                return;
+            },
+        };
+
+        let tracker = ProgressTracker::new(rust_i18n::t!("progress_sharing_lan", name = name).into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.notify();
+        self.send.send_info(rust_i18n::t!("notification_lan_sharing", name = name));
+
+        let result = {
+            let archive = archive.clone();
+            let cancel = modal_action.request_cancel.clone();
+            tokio::task::spawn_blocking(move || serve_share(&archive, &name, &cancel)).await.unwrap()
+        };
+        _ = std::fs::remove_file(&archive);
+
+        tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
+        tracker.notify();
+
+        if let Err(error) = result {
+            modal_action.set_error_message(format!("Unable to share instance: {error}").into());
+        }
+    }
+
+    pub async fn import_lan_share(&self, share: LanShare, modal_action: &ModalAction) {
+        let download_path = self.directories.temp_dir.join(format!("lan-import-{:016x}.zip", rand::random::<u64>()));
+
+        let tracker = ProgressTracker::new(rust_i18n::t!("progress_downloading_lan_share", name = share.name, address = share.address.ip()).into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.notify();
+
+        let result = tokio::select! {
+            result = self.download_manager.download_file(&self.http_client, &share.url, &download_path, Some(&tracker)) => Some(result),
+            _ = modal_action.request_cancel.cancelled() => None,
+        };
+
+        tracker.set_finished(ProgressTrackerFinishType::from_err(!matches!(result, Some(Ok(())))));
+        tracker.notify();
+
+        match result {
+            Some(Ok(())) => self.import_instance_archive(&download_path, &share.name, modal_action).await,
+            Some(Err(error)) => modal_action.set_error_message(format!("Unable to download shared instance: {error}").into()),
+            None => {},
+        }
+
+        // A partial download can't be continued, the share is served under a new token next time
+        _ = std::fs::remove_file(crate::download_manager::part_path(&download_path));
+        _ = std::fs::remove_file(&download_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Read, path::PathBuf};
+
+    use super::*;
+    use crate::test_harness::TempDir;
+
+    fn request(archive: &Path, path: &str) -> String {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let address = listener.local_addr().unwrap();
+        let archive = archive.to_path_buf();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            respond(stream, &archive, "/token.zip").unwrap();
+        });
+
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: {address}\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        server.join().unwrap();
+        response
+    }
+
+    #[test]
+    fn serves_archive_only_at_its_path() {
+        let dir = TempDir::new();
+        let archive: PathBuf = dir.path().join("share.zip");
+        std::fs::write(&archive, b"archive").unwrap();
+
+        let response = request(&archive, "/token.zip");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Length: 7\r\n"));
+        assert!(response.ends_with("\r\n\r\narchive"));
+
+        let response = request(&archive, "/other.zip");
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+}
@@ -1,18 +1,19 @@
 use std::{
-    borrow::Cow, cmp::Ordering, collections::{BTreeSet, HashMap, HashSet}, ffi::{OsStr, OsString}, fs::File, io::{BufRead, BufReader, Read, Write}, path::{Path, PathBuf}, process::{Child, Stdio}, sync::{Arc, OnceLock, atomic::AtomicBool}
+    borrow::Cow, cmp::Ordering, collections::{BTreeSet, HashMap, HashSet}, ffi::{OsStr, OsString}, fs::File, io::{BufRead, BufReader, Read, Write}, path::{Path, PathBuf}, process::{Child, Stdio}, sync::{Arc, OnceLock, atomic::AtomicBool}, time::Instant
 };
 
 use bridge::{
     handle::FrontendHandle, message::{MessageToFrontend, QuickPlayLaunch}, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType, ProgressTrackers}, safe_path::SafePath
 };
 use futures::{FutureExt, TryFutureExt};
+use indexmap::IndexMap;
 use rand::seq::SliceRandom;
 use rc_zip_sync::{ArchiveHandle, ReadZip};
 use regex::Regex;
 use rustc_hash::FxHashMap;
 use schema::{
-    assets_index::AssetsIndex, fabric_launch::FabricLaunch, forge::{ForgeInstallProfile, ForgeInstallProfileLegacy, ForgeSide, VersionFragment}, instance::InstanceConfiguration, java_runtime_component::{JavaRuntimeComponentFile, JavaRuntimeComponentManifest}, loader::Loader, maven::{MavenCoordinate, MavenMetadataXml}, version::{
-        GameLibrary, GameLibraryArtifact, GameLibraryDownloads, GameLibraryExtractOptions, GameLogging, LaunchArgument, LaunchArgumentValue, MinecraftVersion, OsArch, OsName, PartialMinecraftVersion, Rule, RuleAction
+    assets_index::AssetsIndex, fabric_launch::FabricLaunch, forge::{ForgeInstallProfile, ForgeInstallProfileLegacy, ForgeSide, VersionFragment}, instance::{InstanceBrandingConfiguration, InstanceConfiguration}, java_runtime_component::{JavaRuntimeComponentFile, JavaRuntimeComponentManifest}, loader::Loader, maven::{MavenCoordinate, MavenMetadataXml}, version::{
+        GameLibrary, GameLibraryArtifact, GameLibraryDownloads, GameLibraryExtractOptions, GameLogging, LaunchArgument, LaunchArgumentValue, MinecraftVersion, OsArch, OsName, PartialMinecraftVersion, Rule, RuleAction, VersionDownloadLink
     }, version_manifest::MinecraftVersionManifest
 };
 use serde::Deserialize;
@@ -20,9 +21,9 @@ use sha1::{Digest, Sha1};
 use ustr::Ustr;
 
 use crate::{
-    account::MinecraftLoginInfo, directories::LauncherDirectories, launch_wrapper, metadata::{items::{AssetsIndexMetadataItem, FabricLaunchMetadataItem, FabricLoaderManifestMetadataItem, ForgeInstallerMavenMetadataItem, MinecraftVersionManifestMetadataItem, MinecraftVersionMetadataItem, MojangJavaRuntimeComponentMetadataItem, MojangJavaRuntimesMetadataItem, NeoforgeInstallerMavenMetadataItem}, manager::{
+    account::MinecraftLoginInfo, asset_languages, directories::LauncherDirectories, download_conditions::{self, InsufficientSpace}, download_manager::{DownloadError, DownloadManager}, file_verification::{self, FileState, FileToVerify, VerifiedFileCache}, launch_wrapper, library_store::{self, LibraryReferences, UsedLibraries}, metadata::{items::{AssetsIndexMetadataItem, FabricLaunchMetadataItem, FabricLoaderManifestMetadataItem, ForgeInstallerMavenMetadataItem, MinecraftVersionManifestMetadataItem, MinecraftVersionMetadataItem, MojangJavaRuntimeComponentMetadataItem, MojangJavaRuntimesMetadataItem, NeoforgeInstallerMavenMetadataItem}, manager::{
         MetaLoadError, MetadataManager,
-    }}
+    }}, verification_report::VerificationReport
 };
 
 #[derive(Clone)]
@@ -31,6 +32,9 @@ pub struct Launcher {
     directories: Arc<LauncherDirectories>,
     launch_wrapper: Arc<Path>,
     sender: FrontendHandle,
+    download_manager: DownloadManager,
+    pub verified_files: Arc<VerifiedFileCache>,
+    library_references: LibraryReferences,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -61,6 +65,18 @@ pub enum LaunchError {
     CancelledByUser,
     #[error("Loader supports the wrong version of Minecraft: {0}")]
     MismatchedLoaderVersions(Arc<str>),
+    #[error("No loader versions are available")]
+    NoLoaderVersions,
+    #[error("Unable to launch instance with an unknown loader")]
+    UnknownLoader,
+    #[error("Unsupported launch argument: {0}")]
+    UnsupportedArgument(Arc<str>),
+    #[error("Library path contains an invalid character")]
+    InvalidClasspath,
+    #[error("Missing library: {0}")]
+    MissingLibrary(Arc<str>),
+    #[error("Unable to find wrapper command: {0}")]
+    WrapperNotFound(Arc<str>),
 }
 
 #[derive(PartialEq, Eq)]
@@ -70,13 +86,18 @@ pub enum AddVanillaJar {
 }
 
 impl Launcher {
-    pub fn new(meta: Arc<MetadataManager>, directories: Arc<LauncherDirectories>, sender: FrontendHandle) -> Self {
+    pub fn new(meta: Arc<MetadataManager>, directories: Arc<LauncherDirectories>, sender: FrontendHandle, download_manager: DownloadManager) -> Self {
         let launch_wrapper = launch_wrapper::create_wrapper(&directories.temp_dir).into();
+        let verified_files = Arc::new(VerifiedFileCache::load(directories.verified_files_json.clone()));
+        let library_references = LibraryReferences::load(directories.library_references_json.clone(), directories.libraries_dir.clone());
         Self {
             meta,
             directories,
             launch_wrapper,
             sender,
+            download_manager,
+            verified_files,
+            library_references,
         }
     }
 
@@ -84,21 +105,30 @@ impl Launcher {
         &self,
         http_client: &reqwest::Client,
         dot_minecraft_path: Arc<Path>,
+        // Name of the instance's folder, used to remember which libraries it needs. None for launches that don't
+        // belong to an instance
+        instance_folder: Option<&str>,
         instance_info: InstanceConfiguration,
         quick_play: Option<QuickPlayLaunch>,
         login_info: MinecraftLoginInfo,
         add_mods: Vec<PathBuf>,
+        only_selected_language: bool,
+        validate_only: bool,
+        report: &VerificationReport,
         launch_tracker: &ProgressTracker,
         modal_action: &ModalAction,
     ) -> Result<Child, LaunchError> {
-        log::info!("Launching {:?}", dot_minecraft_path);
+        tracing::info!("Launching {:?}", dot_minecraft_path);
 
         launch_tracker.set_total(6);
 
-        log::debug!("Creating launch version");
+        let preparation_started = Instant::now();
+        let used_libraries = UsedLibraries::default();
+
+        tracing::debug!("Creating launch version");
 
         let (version_info, add_vanilla_jar) = tokio::select! {
-            result = self.create_launch_version(http_client, &modal_action.trackers, launch_tracker, &instance_info) => result?,
+            result = self.create_launch_version(http_client, &modal_action.trackers, launch_tracker, &instance_info, &used_libraries) => result?,
             _ = modal_action.request_cancel.cancelled() => {
                 self.sender.send(MessageToFrontend::CloseModal);
                 return Err(LaunchError::CancelledByUser);
@@ -112,7 +142,7 @@ impl Launcher {
 
         let launch_rule_context = LaunchRuleContext {
             is_demo_user: false,
-            custom_resolution: None,
+            custom_resolution: instance_info.resolution.map(|resolution| (resolution.width, resolution.height)),
             quick_play,
         };
 
@@ -124,15 +154,12 @@ impl Launcher {
         let natives_dir = self.directories.temp_natives_base_dir.join(calculate_natives_dirname(&artifacts));
         let _ = std::fs::create_dir_all(&natives_dir);
 
-        if add_vanilla_jar == AddVanillaJar::Yes {
-            let client_download = &version_info.downloads.client;
-            artifacts.push(GameLibraryArtifact {
-                path: format!("net/minecraft/{0}/minecraft-client-{0}.jar", instance_info.minecraft_version).into(),
-                sha1: Some(client_download.sha1),
-                size: Some(client_download.size),
-                url: client_download.url,
-            });
-        }
+        let client_download = if add_vanilla_jar == AddVanillaJar::Yes {
+            launch_tracker.add_total(1);
+            Some(&version_info.downloads.client)
+        } else {
+            None
+        };
 
         let mojang_java_binary_future = self.load_mojang_java_binary(
             &self.meta,
@@ -143,21 +170,24 @@ impl Launcher {
             launch_tracker,
         );
         let load_assets_future =
-            self.load_assets(&self.meta, http_client, &dot_minecraft_path, &version_info, &modal_action.trackers, launch_tracker);
+            self.load_assets(&self.meta, http_client, &dot_minecraft_path, &version_info, only_selected_language, report, &modal_action.trackers, launch_tracker);
         let load_libraries_future =
-            self.load_libraries(http_client, &artifacts, &modal_action.trackers, launch_tracker);
+            self.load_libraries(http_client, &artifacts, report, &modal_action.trackers, launch_tracker, &used_libraries);
+        let load_client_jar_future =
+            self.load_client_jar(http_client, instance_info.minecraft_version, client_download, report, &modal_action.trackers, launch_tracker, &used_libraries);
         let load_log_configuration = self.load_log_configuration(http_client, version_info.logging.as_ref());
 
-        log::debug!("Loading java, assets, libraries and log configuration");
+        tracing::debug!("Loading java, assets, libraries, client jar and log configuration");
 
-        let joined = futures::future::try_join4(
+        let joined = futures::future::try_join5(
             mojang_java_binary_future.map_err(LaunchError::from),
             load_assets_future.map_err(LaunchError::from),
             load_libraries_future.map_err(LaunchError::from),
+            load_client_jar_future.map_err(LaunchError::from),
             load_log_configuration.map(Ok),
         );
 
-        let (java_path, assets_index_name, library_paths, log_configuration) = tokio::select! {
+        let (java_path, (assets_index_name, game_assets_dir), library_paths, client_jar_path, log_configuration) = tokio::select! {
             result = joined => result?,
             _ = modal_action.request_cancel.cancelled() => {
                 self.sender.send(MessageToFrontend::CloseModal);
@@ -168,52 +198,39 @@ impl Launcher {
         launch_tracker.add_count(1);
         launch_tracker.notify();
 
+        self.sender.send_info(report.summary(preparation_started.elapsed()));
+
+        // The natives dir is keyed by the libraries it was built from, so it only needs to be extracted once
+        let natives_marker = natives_dir.join(NATIVES_EXTRACTED_MARKER);
+        let mut extract_natives = !natives_marker.exists();
+
         let mut classpath = Vec::new();
         for (raw_path, library_path) in library_paths {
             if let Some(extract_options) = natives_to_extract.get(&raw_path) {
-                let Ok(file) = std::fs::File::open(library_path) else {
-                    continue;
-                };
-                let Ok(archive) = file.read_zip() else {
-                    continue;
-                };
-                for file in archive.entries() {
-                    let Some(path) = SafePath::new(&file.name) else {
-                        continue;
-                    };
-
-                    if let Some(exclude) = &extract_options.exclude {
-                        let mut skip = false;
-                        for to_exclude in exclude.iter() {
-                            if path.starts_with(to_exclude) {
-                                skip = true;
-                                break;
-                            }
-                        }
-                        if skip {
-                            continue;
-                        }
-                    }
-
-                    let output_path = path.to_path(&natives_dir);
-                    match file.kind() {
-                        rc_zip_sync::rc_zip::EntryKind::Directory => {
-                            let _ = std::fs::create_dir(output_path);
-                        },
-                        rc_zip_sync::rc_zip::EntryKind::File => {
-                            let Ok(mut outfile) = std::fs::File::create(&output_path) else {
-                                continue;
-                            };
-                            let _ = std::io::copy(&mut file.reader(), &mut outfile);
-                        },
-                        rc_zip_sync::rc_zip::EntryKind::Symlink => {},
-                    }
+                if extract_natives && let Err(error) = extract_natives_jar(&library_path, &natives_dir, extract_options) {
+                    tracing::error!("Unable to extract natives from {:?}: {error}", library_path);
+                    extract_natives = false;
+                    let _ = std::fs::remove_file(&natives_marker);
                 }
             } else {
                 classpath.push(library_path.into_os_string());
             }
         }
 
+        // Written on every launch so that the modification time tracks when the dir was last used
+        if extract_natives || natives_marker.exists() {
+            let _ = std::fs::write(&natives_marker, []);
+        }
+        remove_stale_natives_dirs(&self.directories.temp_natives_base_dir, &natives_dir);
+
+        if let Some(client_jar_path) = client_jar_path {
+            classpath.push(client_jar_path.into_os_string());
+        }
+
+        if let Some(instance_folder) = instance_folder {
+            self.library_references.record(instance_folder, used_libraries);
+        }
+
         let launch_context = LaunchContext {
             launch_wrapper_path: self.launch_wrapper.clone(),
             java_path,
@@ -222,13 +239,15 @@ impl Launcher {
             game_dir: dot_minecraft_path,
             configuration: instance_info,
             assets_root: self.directories.assets_root_dir.clone(),
+            game_assets_dir,
             temp_dir: self.directories.temp_dir.clone(),
             assets_index_name,
             classpath,
             log_configuration,
             rule_context: launch_rule_context,
             login_info,
-            add_mods
+            add_mods,
+            validate_only,
         };
 
         if modal_action.has_requested_cancel() {
@@ -236,7 +255,7 @@ impl Launcher {
             return Err(LaunchError::CancelledByUser);
         }
 
-        log::info!("Launching game process");
+        tracing::info!("Launching game process");
         let child = launch_context.launch(&version_info)?;
 
         launch_tracker.add_count(1);
@@ -250,6 +269,7 @@ impl Launcher {
         progress_trackers: &ProgressTrackers,
         launch_tracker: &ProgressTracker,
         instance_info: &InstanceConfiguration,
+        used_libraries: &UsedLibraries,
     ) -> Result<(Arc<MinecraftVersion>, AddVanillaJar), LaunchError> {
         match instance_info.loader {
             Loader::Vanilla => {
@@ -270,19 +290,7 @@ impl Launcher {
             Loader::Fabric => {
                 let versions = self.meta.fetch(&MinecraftVersionManifestMetadataItem).map_err(LaunchError::from);
 
-                let fabric_loader_version = async move {
-                    if let Some(preferred_version) = instance_info.preferred_loader_version {
-                        Ok(preferred_version)
-                    } else {
-                        let manifest = self.meta.fetch(&FabricLoaderManifestMetadataItem).map_err(LaunchError::from).await?;
-
-                        let mut latest_loader_version = manifest.0.iter().find(|v| v.stable);
-                        if latest_loader_version.is_none() {
-                            latest_loader_version = manifest.0.first();
-                        }
-                        Ok(latest_loader_version.unwrap().version)
-                    }
-                };
+                let fabric_loader_version = self.resolve_fabric_loader_version(instance_info);
 
                 launch_tracker.add_total(4);
                 launch_tracker.notify();
@@ -328,67 +336,7 @@ impl Launcher {
                     futures::future::try_join(version, fabric_launch).await?;
 
                 let mut version: MinecraftVersion = (*version).clone();
-
-                if let Some(loader) = &fabric_launch.loader {
-                    let loader_coordinate = MavenCoordinate::create(&loader.maven);
-                    let artifact_path = loader_coordinate.artifact_path();
-                    version.libraries.push(GameLibrary {
-                        downloads: GameLibraryDownloads {
-                            artifact: Some(GameLibraryArtifact {
-                                url: format!("https://maven.fabricmc.net/{}", &artifact_path).into(),
-                                path: artifact_path.into(),
-                                sha1: None,
-                                size: None,
-                            }),
-                            classifiers: None,
-                        },
-                        name: loader.maven,
-                        rules: None,
-                        natives: None,
-                        extract: None,
-                    });
-                }
-
-                if let Some(intermediary) = &fabric_launch.intermediary {
-                    let intermediary_coordinate = MavenCoordinate::create(&intermediary.maven);
-                    let artifact_path = intermediary_coordinate.artifact_path();
-                    version.libraries.push(GameLibrary {
-                        downloads: GameLibraryDownloads {
-                            artifact: Some(GameLibraryArtifact {
-                                url: format!("https://maven.fabricmc.net/{}", &artifact_path).into(),
-                                path: artifact_path.into(),
-                                sha1: None,
-                                size: None,
-                            }),
-                            classifiers: None,
-                        },
-                        name: intermediary.maven,
-                        rules: None,
-                        natives: None,
-                        extract: None,
-                    });
-                }
-
-                let libraries = &fabric_launch.launcher_meta.libraries;
-                for library in libraries.common.iter().chain(libraries.client.iter()) {
-                    let library_coordinate = MavenCoordinate::create(&library.name);
-                    let artifact_path = library_coordinate.artifact_path();
-                    version.libraries.push(GameLibrary {
-                        downloads: GameLibraryDownloads {
-                            artifact: Some(GameLibraryArtifact {
-                                url: format!("{}{}", &library.url, &artifact_path).into(),
-                                path: artifact_path.into(),
-                                sha1: Some(library.sha1),
-                                size: Some(library.size),
-                            }),
-                            classifiers: None,
-                        },
-                        name: library.name,
-                        rules: None,
-                        natives: None,
-                        extract: None,
-                    });
-                }
+                version.libraries.extend(fabric_libraries(&fabric_launch));
 
                 version.main_class = fabric_launch.launcher_meta.main_class.client;
 
@@ -411,7 +359,8 @@ impl Launcher {
                     "net/minecraftforge/forge/{0}/forge-{0}-installer.jar",
                     "https://maven.minecraftforge.net/net/minecraftforge/forge/{0}/forge-{0}-installer.jar",
                     true,
-                    false
+                    false,
+                    used_libraries,
                 ).await
             },
             Loader::NeoForge => {
@@ -431,13 +380,89 @@ impl Launcher {
                     "net/neoforged/neoforge/{0}/neoforge-{0}-installer.jar",
                     "https://maven.neoforged.net/releases/net/neoforged/neoforge/{0}/neoforge-{0}-installer.jar",
                     false,
-                    true
+                    true,
+                    used_libraries,
                 ).await
             },
-            Loader::Unknown => todo!(),
+            Loader::Unknown => Err(LaunchError::UnknownLoader),
         }
     }
 
+    pub async fn resolve_fabric_loader_version(&self, instance_info: &InstanceConfiguration) -> Result<Ustr, LaunchError> {
+        if let Some(preferred_version) = instance_info.preferred_loader_version {
+            return Ok(preferred_version);
+        }
+
+        let manifest = self.meta.fetch(&FabricLoaderManifestMetadataItem).await?;
+
+        let mut latest_loader_version = manifest.0.iter().find(|v| v.stable);
+        if latest_loader_version.is_none() {
+            latest_loader_version = manifest.0.first();
+        }
+        let Some(latest_loader_version) = latest_loader_version else {
+            return Err(LaunchError::NoLoaderVersions);
+        };
+        Ok(latest_loader_version.version)
+    }
+
+    /// Downloads the loader's core libraries and intermediary mappings into the shared libraries
+    /// directory ahead of time, so the first launch of a new modded instance doesn't have to wait on them
+    pub async fn prefetch_loader_libraries(
+        &self,
+        http_client: &reqwest::Client,
+        instance_info: &InstanceConfiguration,
+    ) -> Result<(), LaunchError> {
+        let libraries = match instance_info.loader {
+            Loader::Fabric => {
+                let loader_version = self.resolve_fabric_loader_version(instance_info).await?;
+                let fabric_launch = self.meta.fetch(&FabricLaunchMetadataItem {
+                    minecraft_version: instance_info.minecraft_version,
+                    loader_version,
+                }).await?;
+                fabric_libraries(&fabric_launch)
+            },
+            Loader::Vanilla | Loader::Forge | Loader::NeoForge | Loader::Unknown => return Ok(()),
+        };
+
+        let artifacts: Vec<GameLibraryArtifact> = libraries.into_iter()
+            .filter_map(|library| library.downloads.artifact)
+            .collect();
+
+        let modal_action = self.sender.start_background_task(rust_i18n::t!("progress_caching_loader_libraries"));
+        let tracker = ProgressTracker::new(rust_i18n::t!("progress_caching_loader_libraries").into(), self.sender.clone());
+        modal_action.trackers.push(tracker.clone());
+        // Cancelling from the task center stops the downloads, the libraries are fetched on launch instead
+        let result = tokio::select! {
+            result = do_libraries_load(http_client, &self.download_manager, &self.verified_files, &artifacts, self.directories.libraries_dir.clone(), &VerificationReport::default(), &tracker) => result,
+            _ = modal_action.request_cancel.cancelled() => {
+                tracker.set_finished(ProgressTrackerFinishType::Error);
+                return Err(LaunchError::CancelledByUser);
+            }
+        };
+        tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
+
+        result?;
+        Ok(())
+    }
+
+    /// Loads the version JSON and asset index into the metadata cache, so a launch that follows soon
+    /// after can start downloading right away instead of waiting on these requests one after the other
+    pub async fn prewarm_metadata(&self, minecraft_version: Ustr) -> Result<(), LaunchError> {
+        let versions = self.meta.fetch(&MinecraftVersionManifestMetadataItem).await?;
+        let Some(version) = versions.versions.iter().find(|v| v.id == minecraft_version) else {
+            return Err(LaunchError::CantFindVersion(minecraft_version.as_str()));
+        };
+        let version_info = self.meta.fetch(&MinecraftVersionMetadataItem(version)).await?;
+
+        self.meta.fetch(&AssetsIndexMetadataItem {
+            url: version_info.asset_index.url,
+            cache: self.directories.assets_index_dir.join(format!("{}.json", &version_info.assets)).into(),
+            hash: version_info.asset_index.sha1,
+        }).await?;
+
+        Ok(())
+    }
+
     async fn create_forgelike_launch_version(
         &self,
         http_client: &reqwest::Client,
@@ -451,6 +476,7 @@ impl Launcher {
         installer_url: &'static str,
         check_mirrors: bool,
         neoforge_versioning: bool,
+        used_libraries: &UsedLibraries,
     ) -> Result<(Arc<MinecraftVersion>, AddVanillaJar), LaunchError> {
         launch_tracker.add_count(1);
         launch_tracker.notify();
@@ -534,7 +560,7 @@ impl Launcher {
             progress_trackers,
             launch_tracker,
         );
-        let load_installer_library_future = self.load_libraries(http_client, artifacts, progress_trackers, launch_tracker);
+        let load_installer_library_future = self.load_libraries(http_client, artifacts, &VerificationReport::default(), progress_trackers, launch_tracker, used_libraries);
 
         let (artifact_load_result, java_load_result) = futures::future::try_join(
             load_installer_library_future.map_err(LaunchError::from),
@@ -560,14 +586,14 @@ impl Launcher {
             if let Ok(install_profile_legacy) = serde_json::from_slice(&install_profile_bytes) {
                 launch_tracker.add_count(1);
                 let ret = self.create_forgelike_install_version_legacy(install_profile_legacy, installer_zip,
-                    base_version, http_client, progress_trackers, launch_tracker, instance_info, check_mirrors).await;
+                    base_version, http_client, progress_trackers, launch_tracker, instance_info, check_mirrors, used_libraries).await;
                 return ret;
             }
         }
 
         self.create_forgelike_install_version_modern(install_profile?, installer_zip,
             installer_path, minecraft_jar_path, &java_load_result, base_version, http_client,
-            progress_trackers, launch_tracker, instance_info, check_mirrors).await
+            progress_trackers, launch_tracker, instance_info, check_mirrors, used_libraries).await
     }
 
     async fn create_forgelike_install_version_modern(
@@ -583,6 +609,7 @@ impl Launcher {
         launch_tracker: &ProgressTracker,
         instance_info: &InstanceConfiguration,
         check_mirrors: bool,
+        used_libraries: &UsedLibraries,
     ) -> Result<(Arc<MinecraftVersion>, AddVanillaJar), LaunchError> {
         if &*install_profile.minecraft != instance_info.minecraft_version.as_str() {
             return Err(LaunchError::MismatchedLoaderVersions(install_profile.minecraft.clone()));
@@ -619,7 +646,7 @@ impl Launcher {
             Some(artifact)
         }).collect::<Vec<_>>();
 
-        self.load_libraries(http_client, &libraries, progress_trackers, launch_tracker).await?;
+        self.load_libraries(http_client, &libraries, &VerificationReport::default(), progress_trackers, launch_tracker, used_libraries).await?;
 
         let forge_temp = self.directories.temp_dir.join("forge_installer");
 
@@ -636,9 +663,11 @@ impl Launcher {
                 let artifact_path = artifact.artifact_path();
                 if let Some(target) = SafePath::new(&artifact_path) {
                     let target = target.to_path(&self.directories.libraries_dir);
+                    // Processor outputs are only found through the data, they need to be kept for the next launch
+                    used_libraries.add(target.clone());
                     data.insert(key, target.into_os_string());
                 } else {
-                    log::error!("Artifact generated invalid path: {}", artifact_path);
+                    tracing::error!("Artifact generated invalid path: {}", artifact_path);
                 }
             } else if value.starts_with('\'') && value.ends_with('\'') {
                 data.insert(key, OsString::from(&value[1..value.len()-1]));
@@ -655,7 +684,7 @@ impl Launcher {
                     crate::write_safe(&target, &file.bytes()?)?;
                     data.insert(key, target.into_os_string());
                 } else {
-                    log::error!("Unable to extract {}", file_name);
+                    tracing::error!("Unable to extract {}", file_name);
                 }
             }
         }
@@ -669,10 +698,10 @@ impl Launcher {
         data.insert("INSTALLER".into(), installer_path.as_os_str().to_os_string());
         data.insert("LIBRARY_DIR".into(), self.directories.libraries_dir.as_os_str().to_os_string());
 
-        let processor_tracker = ProgressTracker::new("Forge Post Processors".into(), self.sender.clone());
+        let processor_tracker = ProgressTracker::new(rust_i18n::t!("progress_forge_processors").into(), self.sender.clone());
         progress_trackers.push(processor_tracker.clone());
 
-        processor_tracker.set_total(install_profile.processors.len());
+        processor_tracker.set_total(install_profile.processors.len() as u64);
         processor_tracker.notify();
 
         for processor in install_profile.processors.iter() {
@@ -698,7 +727,7 @@ impl Launcher {
             let relative_jar_path = jar.artifact_path();
 
             let Some(safe_jar_path) = SafePath::new(&relative_jar_path) else {
-                log::error!("Unable to run processor, invalid path: {}", relative_jar_path);
+                tracing::error!("Unable to run processor, invalid path: {}", relative_jar_path);
                 processor_tracker.add_count(1);
                 processor_tracker.notify();
                 continue;
@@ -718,7 +747,7 @@ impl Launcher {
             drop(jar_file);
 
             let Ok(manifest_str) = str::from_utf8(&manifest_bytes) else {
-                log::error!("Unable to run processor, MANIFEST.MF is not utf8 encoded");
+                tracing::error!("Unable to run processor, MANIFEST.MF is not utf8 encoded");
                 processor_tracker.add_count(1);
                 processor_tracker.notify();
                 continue;
@@ -727,7 +756,7 @@ impl Launcher {
             let manifest_map = crate::java_manifest::parse_java_manifest(manifest_str);
 
             let Some(main_class) = manifest_map.get("Main-Class") else {
-                log::error!("Unable to run processor, can't find Main-Class in MANIFEST.MF");
+                tracing::error!("Unable to run processor, can't find Main-Class in MANIFEST.MF");
                 processor_tracker.add_count(1);
                 processor_tracker.notify();
                 continue;
@@ -744,7 +773,7 @@ impl Launcher {
             command.arg(std::env::join_paths(processor.classpath.iter().map(|f| {
                 let artifact = MavenCoordinate::create(&**f);
                 self.directories.libraries_dir.join(artifact.artifact_path()).into_os_string()
-            }).chain(std::iter::once(jar_path.into_os_string()))).unwrap());
+            }).chain(std::iter::once(jar_path.into_os_string()))).map_err(|_| LaunchError::InvalidClasspath)?);
 
             command.arg(main_class);
 
@@ -756,13 +785,13 @@ impl Launcher {
                         let target = target.to_path(&self.directories.libraries_dir);
                         Cow::Owned(target.into_os_string())
                     } else {
-                        log::error!("Artifact generated invalid path: {}", artifact_path);
+                        tracing::error!("Artifact generated invalid path: {}", artifact_path);
                         continue;
                     }
                 } else if &**arg == "{ROOT}/libraries/" {
                     Cow::Borrowed(self.directories.libraries_dir.as_os_str())
                 } else {
-                    expand_forge_argument(&arg, &data)
+                    expand_forge_argument(&arg, &data)?
                 };
                 command.arg(expanded);
             }
@@ -796,6 +825,7 @@ impl Launcher {
         launch_tracker: &ProgressTracker,
         instance_info: &InstanceConfiguration,
         check_mirrors: bool,
+        used_libraries: &UsedLibraries,
     ) -> Result<(Arc<MinecraftVersion>, AddVanillaJar), LaunchError> {
         if &*install_profile.install.minecraft != instance_info.minecraft_version.as_str() {
             return Err(LaunchError::MismatchedLoaderVersions(install_profile.install.minecraft.clone()));
@@ -811,6 +841,7 @@ impl Launcher {
         }
         let forge_artifact_path = self.directories.libraries_dir.join(forge_path.as_str());
         crate::write_safe(&forge_artifact_path, &file.bytes()?)?;
+        used_libraries.add(forge_artifact_path);
 
         // Read partial minecraft version
         let version: PartialMinecraftVersion = install_profile.version_info.into_partial_version(ForgeSide::Client);
@@ -837,7 +868,7 @@ impl Launcher {
                 Some(artifact)
             }).collect::<Vec<_>>();
 
-            self.load_libraries(http_client, &libraries, progress_trackers, launch_tracker).await?;
+            self.load_libraries(http_client, &libraries, &VerificationReport::default(), progress_trackers, launch_tracker, used_libraries).await?;
         }
 
         Ok((Arc::new(version.apply_to(&base_version)), AddVanillaJar::Yes))
@@ -888,41 +919,122 @@ impl Launcher {
         if let Some(jvm_binary) = &configuration.jvm_binary {
             if jvm_binary.enabled && let Some(path) = &jvm_binary.path {
                 if let Some(binary) = Self::search_for_java_binary(&path) {
+                    let needed_version = version_info.java_version.as_ref().map_or(8, |java_version| java_version.major_version);
+                    let major_version = jvm_binary.major_version.or_else(|| detect_java_major_version(&binary));
+                    if let Some(major_version) = major_version && major_version != needed_version {
+                        tracing::warn!("Overridden Java binary {:?} is Java {}, but Java {} is expected", binary, major_version, needed_version);
+                        self.sender.send_warning(format!("Launching with Java {major_version}, but this version of Minecraft expects Java {needed_version}"));
+                    }
                     return Ok(binary);
                 }
             }
         }
 
-        if let Some(force_external_java) = std::env::var_os("FORCE_EXTERNAL_JAVA") {
-            let paths = std::env::split_paths(&force_external_java);
+        if let Some(external_java) = self.find_external_java(version_info) {
+            return external_java;
+        }
 
-            let mut found_versions = BTreeSet::new();
+        let (runtime_component_dir, runtime, fresh_install) = self.mojang_java_runtime(meta, version_info).await?;
 
-            let needed_version = if let Some(java_version) = &version_info.java_version {
-                java_version.major_version
-            } else {
-                8
-            };
+        let initial_title = if fresh_install {
+            rust_i18n::t!("progress_downloading_java")
+        } else {
+            rust_i18n::t!("progress_verifying_java")
+        };
 
-            for path in paths {
-                let Some(binary) = Self::search_for_java_binary(&path) else {
-                    continue;
-                };
+        let java_runtime_tracker = ProgressTracker::new(initial_title.into(), self.sender.clone());
+        progress_trackers.push(java_runtime_tracker.clone());
+        java_runtime_tracker.notify();
 
-                let Some(major_version) = self.get_major_java_version(&binary) else {
-                    continue;
-                };
+        let result = do_java_runtime_load(http_client, &self.download_manager, &self.verified_files, runtime_component_dir, fresh_install, runtime, &java_runtime_tracker).await;
 
-                if major_version == needed_version {
-                    return Ok(binary);
-                } else {
-                    found_versions.insert(major_version);
-                }
+        java_runtime_tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
+        java_runtime_tracker.notify();
+
+        launch_tracker.add_count(1);
+        launch_tracker.notify();
+
+        result
+    }
+
+    /// Looks for a Java binary of the version Minecraft expects in the paths listed by `FORCE_EXTERNAL_JAVA`,
+    /// returning None when the variable isn't set
+    pub fn find_external_java(&self, version_info: &MinecraftVersion) -> Option<Result<PathBuf, LoadJavaRuntimeError>> {
+        let force_external_java = std::env::var_os("FORCE_EXTERNAL_JAVA")?;
+        let paths = std::env::split_paths(&force_external_java);
+
+        let mut found_versions = BTreeSet::new();
+
+        let needed_version = if let Some(java_version) = &version_info.java_version {
+            java_version.major_version
+        } else {
+            8
+        };
+
+        for path in paths {
+            let Some(binary) = Self::search_for_java_binary(&path) else {
+                continue;
+            };
+
+            let Some(major_version) = self.get_major_java_version(&binary) else {
+                continue;
+            };
+
+            if major_version == needed_version {
+                return Some(Ok(binary));
+            } else {
+                found_versions.insert(major_version);
             }
+        }
+
+        Some(Err(LoadJavaRuntimeError::UnableToFindExternalBinary(needed_version, found_versions.into_iter().collect())))
+    }
+
+    /// Picks the Mojang Java runtime for this version of Minecraft, returning the directory it's installed into, its
+    /// manifest and whether that directory had to be created, i.e. the runtime wasn't installed yet. Nothing is
+    /// downloaded besides the manifest
+    pub async fn mojang_java_runtime(
+        &self,
+        meta: &MetadataManager,
+        version_info: &MinecraftVersion,
+    ) -> Result<(PathBuf, Arc<JavaRuntimeComponentManifest>, bool), LoadJavaRuntimeError> {
+        let (runtime_component_dir, mut manifest_item) = self.locate_mojang_java_runtime(meta, version_info).await?;
+
+        // Checked before the folder is created below, which would otherwise always make it look installed
+        let fresh_install = !runtime_component_dir.exists();
+        let _ = std::fs::create_dir_all(&runtime_component_dir);
+        let Ok(runtime_component_dir) = runtime_component_dir.canonicalize() else {
+            return Err(LoadJavaRuntimeError::InvalidComponentPath);
+        };
+
+        manifest_item.cache = Some(runtime_component_dir.join("manifest.json").into());
+        let runtime = meta.fetch(&manifest_item).await?;
+
+        Ok((runtime_component_dir, runtime, fresh_install))
+    }
 
-            return Err(LoadJavaRuntimeError::UnableToFindExternalBinary(needed_version, found_versions.into_iter().collect()));
+    /// Like [`Self::mojang_java_runtime`], but doesn't create the runtime's directory, so looking ahead at a launch
+    /// doesn't make the runtime look installed. The manifest is only cached on disk if the directory already exists
+    pub async fn peek_mojang_java_runtime(
+        &self,
+        meta: &MetadataManager,
+        version_info: &MinecraftVersion,
+    ) -> Result<(PathBuf, Arc<JavaRuntimeComponentManifest>), LoadJavaRuntimeError> {
+        let (runtime_component_dir, mut manifest_item) = self.locate_mojang_java_runtime(meta, version_info).await?;
+        if runtime_component_dir.is_dir() {
+            manifest_item.cache = Some(runtime_component_dir.join("manifest.json").into());
         }
+        let runtime = meta.fetch(&manifest_item).await?;
+        Ok((runtime_component_dir, runtime))
+    }
 
+    /// The directory the runtime component for this version of Minecraft belongs in and where its manifest is
+    /// downloaded from, without a cache file for the manifest
+    async fn locate_mojang_java_runtime(
+        &self,
+        meta: &MetadataManager,
+        version_info: &MinecraftVersion,
+    ) -> Result<(PathBuf, MojangJavaRuntimeComponentMetadataItem), LoadJavaRuntimeError> {
         let mut platform: Ustr = match (std::env::consts::OS, std::env::consts::ARCH) {
             ("linux", "x86_64") => "linux".into(),
             ("linux", "x86") => "linux-i386".into(),
@@ -967,38 +1079,13 @@ impl Launcher {
         }
 
         let runtime_component_dir = self.directories.runtime_base_dir.join(jre_component).join(platform);
-        let _ = std::fs::create_dir_all(&runtime_component_dir);
-        let Ok(runtime_component_dir) = runtime_component_dir.canonicalize() else {
-            return Err(LoadJavaRuntimeError::InvalidComponentPath);
-        };
-
-        let fresh_install = !runtime_component_dir.exists();
-
-        let runtime = meta.fetch(&MojangJavaRuntimeComponentMetadataItem {
+        let manifest_item = MojangJavaRuntimeComponentMetadataItem {
             url: runtime_component.manifest.url,
-            cache: runtime_component_dir.join("manifest.json").into(),
+            cache: None,
             hash: runtime_component.manifest.sha1,
-        }).await?;
-
-        let initial_title = if fresh_install {
-            "Downloading Java Runtime"
-        } else {
-            "Verifying integrity of Java Runtime"
         };
 
-        let java_runtime_tracker = ProgressTracker::new(initial_title.into(), self.sender.clone());
-        progress_trackers.push(java_runtime_tracker.clone());
-        java_runtime_tracker.notify();
-
-        let result = do_java_runtime_load(http_client, runtime_component_dir, fresh_install, runtime, &java_runtime_tracker).await;
-
-        java_runtime_tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
-        java_runtime_tracker.notify();
-
-        launch_tracker.add_count(1);
-        launch_tracker.notify();
-
-        result
+        Ok((runtime_component_dir, manifest_item))
     }
 
     async fn load_assets(
@@ -1007,9 +1094,11 @@ impl Launcher {
         http_client: &reqwest::Client,
         game_dir: &Arc<Path>,
         version_info: &MinecraftVersion,
+        only_selected_language: bool,
+        report: &VerificationReport,
         progress_trackers: &ProgressTrackers,
         launch_tracker: &ProgressTracker,
-    ) -> Result<String, LoadAssetObjectsError> {
+    ) -> Result<(String, Arc<Path>), LoadAssetObjectsError> {
         let asset_index = format!("{}", version_info.assets);
 
         let assets_index = meta.fetch(&AssetsIndexMetadataItem {
@@ -1017,21 +1106,39 @@ impl Launcher {
             cache: self.directories.assets_index_dir.join(format!("{}.json", &asset_index)).into(),
             hash: version_info.asset_index.sha1,
         }).await?;
+        let assets_index = if only_selected_language {
+            let selected_language = asset_languages::selected_language(game_dir);
+            Arc::new(asset_languages::without_unused_languages(&assets_index, selected_language.as_deref()))
+        } else {
+            assets_index
+        };
 
-        let initial_title = Arc::from("Verifying integrity of game assets");
+        let initial_title = rust_i18n::t!("progress_verifying_assets").into();
         let assets_tracker = ProgressTracker::new(initial_title, self.sender.clone());
         progress_trackers.push(assets_tracker.clone());
         assets_tracker.notify();
 
-        let assets_dir = if assets_index.map_to_resources == Some(true) {
+        // Old versions read assets by name, so they are copied out of the object store into the layout they expect
+        let game_assets_dir: Arc<Path> = if assets_index.map_to_resources == Some(true) {
             game_dir.join("resources").into()
         } else if assets_index.r#virtual == Some(true) {
-            self.directories.assets_root_dir.join("virtual").join("legacy").into()
+            self.directories.assets_root_dir.join("virtual").join(&asset_index).into()
         } else {
-            self.directories.assets_objects_dir.clone()
+            self.directories.assets_root_dir.clone()
         };
 
-        let result = do_asset_objects_load(http_client, assets_index, assets_dir, &assets_tracker).await;
+        let mut result = do_asset_objects_load(http_client, &self.download_manager, &self.verified_files, assets_index.clone(), self.directories.assets_objects_dir.clone(), report, &assets_tracker).await;
+
+        if result.is_ok() && (assets_index.map_to_resources == Some(true) || assets_index.r#virtual == Some(true)) {
+            assets_tracker.set_title(rust_i18n::t!("progress_copying_legacy_assets").into());
+            assets_tracker.notify();
+
+            let objects_dir = self.directories.assets_objects_dir.clone();
+            let target_dir = game_assets_dir.clone();
+            result = tokio::task::spawn_blocking(move || {
+                build_named_asset_layout(&assets_index, &objects_dir, &target_dir)
+            }).await.unwrap();
+        }
 
         assets_tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
         assets_tracker.notify();
@@ -1041,23 +1148,25 @@ impl Launcher {
 
         result?;
 
-        Ok(asset_index)
+        Ok((asset_index, game_assets_dir))
     }
 
     async fn load_libraries(
         &self,
         http_client: &reqwest::Client,
         artifacts: &[GameLibraryArtifact],
+        report: &VerificationReport,
         progress_trackers: &ProgressTrackers,
         launch_tracker: &ProgressTracker,
+        used_libraries: &UsedLibraries,
     ) -> Result<Vec<(Ustr, PathBuf)>, LoadLibrariesError> {
-        let initial_title = Arc::from("Verifying integrity of game libraries");
+        let initial_title = rust_i18n::t!("progress_verifying_libraries").into();
         let libraries_tracker = ProgressTracker::new(initial_title, self.sender.clone());
         progress_trackers.push(libraries_tracker.clone());
         libraries_tracker.notify();
 
         let result =
-            do_libraries_load(http_client, artifacts, self.directories.libraries_dir.clone(), &libraries_tracker).await;
+            do_libraries_load(http_client, &self.download_manager, &self.verified_files, artifacts, self.directories.libraries_dir.clone(), report, &libraries_tracker).await;
 
         libraries_tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
         libraries_tracker.notify();
@@ -1065,9 +1174,53 @@ impl Launcher {
         launch_tracker.add_count(1);
         launch_tracker.notify();
 
+        if let Ok(paths) = &result {
+            used_libraries.extend(paths.iter().map(|(_, path)| path.clone()));
+        }
         result
     }
 
+    /// Downloads the vanilla client jar into the shared libraries store and verifies its size and sha1
+    async fn load_client_jar(
+        &self,
+        http_client: &reqwest::Client,
+        minecraft_version: Ustr,
+        client_download: Option<&VersionDownloadLink>,
+        report: &VerificationReport,
+        progress_trackers: &ProgressTrackers,
+        launch_tracker: &ProgressTracker,
+        used_libraries: &UsedLibraries,
+    ) -> Result<Option<PathBuf>, LoadLibrariesError> {
+        let Some(client_download) = client_download else {
+            return Ok(None);
+        };
+
+        let artifact = GameLibraryArtifact {
+            path: format!("net/minecraft/{0}/minecraft-client-{0}.jar", minecraft_version).into(),
+            sha1: Some(client_download.sha1),
+            size: Some(client_download.size),
+            url: client_download.url,
+        };
+
+        let initial_title = rust_i18n::t!("progress_verifying_client_jar").into();
+        let client_jar_tracker = ProgressTracker::new(initial_title, self.sender.clone());
+        progress_trackers.push(client_jar_tracker.clone());
+        client_jar_tracker.notify();
+
+        let result =
+            do_libraries_load(http_client, &self.download_manager, &self.verified_files, std::slice::from_ref(&artifact), self.directories.libraries_dir.clone(), report, &client_jar_tracker).await;
+
+        client_jar_tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
+        client_jar_tracker.notify();
+
+        launch_tracker.add_count(1);
+        launch_tracker.notify();
+
+        let path = result?.pop().map(|(_, path)| path);
+        used_libraries.extend(path.clone());
+        Ok(path)
+    }
+
     async fn load_log_configuration(
         &self,
         http_client: &reqwest::Client,
@@ -1081,19 +1234,19 @@ impl Launcher {
         };
         let id = client.file.id.as_str();
         if !path_is_normal(id) {
-            log::error!("Log configuration has invalid path: {}", id);
+            tracing::error!("Log configuration has invalid path: {}", id);
             return None;
         }
-        let path = self.directories.log_configs_dir.join(id);
-
-        let _ = std::fs::create_dir(&self.directories.log_configs_dir);
 
         let mut expected_hash = [0u8; 20];
         let Ok(_) = hex::decode_to_slice(client.file.sha1.as_str(), &mut expected_hash) else {
-            log::error!("Log configuration has invalid sha1: {}", client.file.sha1.as_str());
+            tracing::error!("Log configuration has invalid sha1: {}", client.file.sha1.as_str());
             return None;
         };
 
+        // Mojang has updated configs in place under the same id (e.g. for log4shell), so key by hash as well
+        let path = self.directories.log_configs_dir.join(hex::encode(expected_hash)).join(id);
+
         let valid_hash_on_disk = {
             let path = path.clone();
             tokio::task::spawn_blocking(move || {
@@ -1102,21 +1255,21 @@ impl Launcher {
         };
 
         if valid_hash_on_disk {
-            return Some(expand_logging_argument(client.argument.as_str(), &path));
+            return expand_logging_argument(client.argument.as_str(), &path);
         }
 
-        let Ok(response) = http_client.get(client.file.url.as_str()).send().await else {
-            log::error!("Failed to make request to download log configuration");
+        let Ok(response) = http_client.get(&*crate::endpoints::resolve(client.file.url.as_str())).send().await else {
+            tracing::error!("Failed to make request to download log configuration");
             return None;
         };
         let Ok(bytes) = response.bytes().await else {
-            log::error!("Failed to download log configuration");
+            tracing::error!("Failed to download log configuration");
             return None;
         };
         let bytes = Arc::new(bytes);
 
         if bytes.len() != client.file.size as usize {
-            log::error!("Rejecting log configuration because invalid size");
+            tracing::error!("Rejecting log configuration because invalid size");
             return None;
         }
 
@@ -1133,16 +1286,20 @@ impl Launcher {
         };
 
         if !correct_hash {
-            log::error!("Log configuration has incorrect hash");
+            tracing::error!("Log configuration has incorrect hash");
             return None;
         }
 
-        let Ok(_) = tokio::fs::write(path.clone(), &*bytes).await else {
-            log::error!("Failed to write log configuration to disk");
+        let write_result = {
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || crate::write_safe(&path, &bytes)).await.unwrap()
+        };
+        let Ok(_) = write_result else {
+            tracing::error!("Failed to write log configuration to disk");
             return None;
         };
 
-        Some(expand_logging_argument(client.argument.as_str(), &path))
+        expand_logging_argument(client.argument.as_str(), &path)
     }
 
     fn can_skip_forge_processor(&self, jar: &MavenCoordinate<'_>, processor: &schema::forge::ForgeInstallProcessor, data: &FxHashMap<String, OsString>) -> bool {
@@ -1151,8 +1308,9 @@ impl Launcher {
                 return false;
             }
             for (key, value) in outputs {
-                let key = expand_forge_argument(key, data);
-                let value = expand_forge_argument(value, data);
+                let (Ok(key), Ok(value)) = (expand_forge_argument(key, data), expand_forge_argument(value, data)) else {
+                    return false;
+                };
 
                 let Some(value) = value.to_str() else {
                     return false;
@@ -1260,7 +1418,7 @@ impl Launcher {
         }
     }
 
-    fn search_for_java_binary(path: &Path) -> Option<PathBuf> {
+    pub fn search_for_java_binary(path: &Path) -> Option<PathBuf> {
         if path.is_file() {
             return Some(path.to_path_buf());
         }
@@ -1304,7 +1462,7 @@ impl Launcher {
 
         let mut process = command.spawn().ok()?;
 
-        let mut stdin = process.stdin.take().unwrap();
+        let mut stdin = process.stdin.take()?;
         stdin.write_all(b"printproperty\njava.specification.version\nexit\n").ok()?;
         stdin.flush().ok()?;
 
@@ -1323,7 +1481,34 @@ impl Launcher {
     }
 }
 
-fn expand_logging_argument(argument: &str, path: &Path) -> OsString {
+/// Runs `java -version` to check that the binary works, returning its major version
+pub fn detect_java_major_version(binary: &Path) -> Option<u32> {
+    let mut command = std::process::Command::new(binary);
+    command.arg("-version");
+    command.stdin(Stdio::null());
+    command.stdout(Stdio::null());
+    command.stderr(Stdio::piped());
+    crate::process::configure_command(&mut command);
+
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_java_version_output(&String::from_utf8_lossy(&output.stderr))
+}
+
+/// Extracts the major version from `java -version` output, e.g. `openjdk version "17.0.9" 2023-10-17`
+/// or `java version "1.8.0_392"`
+fn parse_java_version_output(output: &str) -> Option<u32> {
+    let first_line = output.lines().next()?;
+    let (_, rest) = first_line.split_once('"')?;
+    let (version, _) = rest.split_once('"')?;
+    let version = version.strip_prefix("1.").unwrap_or(version);
+    let end = version.find(|c: char| !c.is_ascii_digit()).unwrap_or(version.len());
+    version[..end].parse().ok()
+}
+
+fn expand_logging_argument(argument: &str, path: &Path) -> Option<OsString> {
     let mut dollar_last = false;
     let mut builder = OsString::new();
     let mut copied_to_builder = 0;
@@ -1339,7 +1524,8 @@ fn expand_logging_argument(argument: &str, path: &Path) -> OsString {
                     builder.push(path.as_os_str());
                     copied_to_builder = i+end+1;
                 } else {
-                    panic!("Unsupported argument: {:?}", to_expand);
+                    tracing::error!("Unsupported logging argument: {:?}", to_expand);
+                    return None;
                 }
             }
         } else {
@@ -1347,7 +1533,124 @@ fn expand_logging_argument(argument: &str, path: &Path) -> OsString {
         }
     }
     builder.push(&argument[copied_to_builder..]);
-    builder
+    Some(builder)
+}
+
+pub fn fabric_libraries(fabric_launch: &FabricLaunch) -> Vec<GameLibrary> {
+    let mut libraries = Vec::new();
+
+    let maven_libraries = fabric_launch.loader.iter().map(|loader| loader.maven)
+        .chain(fabric_launch.intermediary.iter().map(|intermediary| intermediary.maven));
+    for name in maven_libraries {
+        let coordinate = MavenCoordinate::create(&name);
+        let artifact_path = coordinate.artifact_path();
+        libraries.push(GameLibrary {
+            downloads: GameLibraryDownloads {
+                artifact: Some(GameLibraryArtifact {
+                    url: format!("https://maven.fabricmc.net/{}", &artifact_path).into(),
+                    path: artifact_path.into(),
+                    sha1: None,
+                    size: None,
+                }),
+                classifiers: None,
+            },
+            name,
+            rules: None,
+            natives: None,
+            extract: None,
+        });
+    }
+
+    let launcher_libraries = &fabric_launch.launcher_meta.libraries;
+    for library in launcher_libraries.common.iter().chain(launcher_libraries.client.iter()) {
+        let library_coordinate = MavenCoordinate::create(&library.name);
+        let artifact_path = library_coordinate.artifact_path();
+        libraries.push(GameLibrary {
+            downloads: GameLibraryDownloads {
+                artifact: Some(GameLibraryArtifact {
+                    url: format!("{}{}", &library.url, &artifact_path).into(),
+                    path: artifact_path.into(),
+                    sha1: Some(library.sha1),
+                    size: Some(library.size),
+                }),
+                classifiers: None,
+            },
+            name: library.name,
+            rules: None,
+            natives: None,
+            extract: None,
+        });
+    }
+
+    libraries
+}
+
+const NATIVES_EXTRACTED_MARKER: &str = ".extracted";
+
+/// Natives dirs that haven't been used for this long are removed when launching
+const STALE_NATIVES_AGE: std::time::Duration = std::time::Duration::from_secs(14 * 24 * 60 * 60);
+
+fn extract_natives_jar(jar: &Path, natives_dir: &Path, extract_options: &GameLibraryExtractOptions) -> std::io::Result<()> {
+    let archive = File::open(jar)?.read_zip().map_err(std::io::Error::other)?;
+    for file in archive.entries() {
+        let Some(path) = SafePath::new(&file.name) else {
+            continue;
+        };
+
+        if let Some(exclude) = &extract_options.exclude
+            && exclude.iter().any(|to_exclude| path.starts_with(to_exclude))
+        {
+            continue;
+        }
+
+        let output_path = path.to_path(natives_dir);
+        match file.kind() {
+            rc_zip_sync::rc_zip::EntryKind::Directory => {
+                std::fs::create_dir_all(output_path)?;
+            },
+            rc_zip_sync::rc_zip::EntryKind::File => {
+                if let Some(parent) = output_path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                let mut outfile = File::create(&output_path)?;
+                std::io::copy(&mut file.reader(), &mut outfile)?;
+            },
+            rc_zip_sync::rc_zip::EntryKind::Symlink => {},
+        }
+    }
+    Ok(())
+}
+
+fn remove_stale_natives_dirs(natives_base_dir: &Path, current: &Path) {
+    let Ok(read_dir) = std::fs::read_dir(natives_base_dir) else {
+        return;
+    };
+    for entry in read_dir.flatten() {
+        let path = entry.path();
+        if path == current || !entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            continue;
+        }
+
+        let last_used = std::fs::metadata(path.join(NATIVES_EXTRACTED_MARKER))
+            .or_else(|_| entry.metadata())
+            .and_then(|metadata| metadata.modified());
+        let Ok(last_used) = last_used else {
+            continue;
+        };
+        if last_used.elapsed().is_ok_and(|elapsed| elapsed > STALE_NATIVES_AGE) {
+            tracing::debug!("Removing stale natives dir {:?}", path);
+            let _ = std::fs::remove_dir_all(&path);
+        }
+    }
+}
+
+/// Value for `${arch}` in legacy natives classifiers, e.g. `natives-windows-${arch}`
+fn natives_arch() -> &'static str {
+    if cfg!(target_pointer_width = "64") {
+        "64"
+    } else {
+        "32"
+    }
 }
 
 fn calculate_natives_dirname(artifacts: &[GameLibraryArtifact]) -> String {
@@ -1376,6 +1679,8 @@ fn calculate_natives_dirname(artifacts: &[GameLibraryArtifact]) -> String {
 pub enum LoadJavaRuntimeError {
     #[error("Failed to load remote content:\n{0}")]
     Reqwest(#[from] reqwest::Error),
+    #[error("Failed to download file:\n{0}")]
+    Download(#[from] DownloadError),
     #[error("Failed to perform I/O operation:\n{0}")]
     IoError(#[from] std::io::Error),
     #[error("Failed to load metadata:\n{0}")]
@@ -1400,10 +1705,14 @@ pub enum LoadJavaRuntimeError {
     UnableToFindBinary,
     #[error("Unable to find external binary, needed Java {0}, got Java {1:?}")]
     UnableToFindExternalBinary(u32, Vec<u32>),
+    #[error(transparent)]
+    InsufficientSpace(#[from] InsufficientSpace),
 }
 
 async fn do_java_runtime_load(
     http_client: &reqwest::Client,
+    download_manager: &DownloadManager,
+    verified_files: &Arc<VerifiedFileCache>,
     runtime_component_dir: PathBuf,
     fresh_install: bool,
     runtime: Arc<JavaRuntimeComponentManifest>,
@@ -1411,10 +1720,9 @@ async fn do_java_runtime_load(
 ) -> Result<PathBuf, LoadJavaRuntimeError> {
     let mut links = HashMap::new();
 
-    // Limit max concurrent connections to 8 to avoid ratelimiting issues
-    let download_semaphore = tokio::sync::Semaphore::new(8);
-    let disk_semaphore = tokio::sync::Semaphore::new(32);
     let started_downloading = AtomicBool::new(fresh_install);
+    let mut files = Vec::new();
+    let verified: OnceLock<Vec<FileState>> = OnceLock::new();
 
     let mut tasks = Vec::new();
 
@@ -1437,32 +1745,31 @@ async fn do_java_runtime_load(
                     return Err(LoadJavaRuntimeError::InvalidHash(downloads.raw.sha1));
                 };
 
-                total_size += downloads.raw.size;
+                // Progress is counted in downloaded bytes, which are compressed when lzma is available
+                let tracker_size = downloads.lzma.as_ref().unwrap_or(&downloads.raw).size as u64;
+                total_size += tracker_size;
+
+                let index = files.len();
+                files.push(FileToVerify {
+                    path: path.clone(),
+                    sha1: Some(expected_hash),
+                    size: Some(downloads.raw.size as u64),
+                    progress: tracker_size,
+                });
 
                 let started_downloading = &started_downloading;
-                let download_semaphore = &download_semaphore;
-                let disk_semaphore = &disk_semaphore;
+                let verified = &verified;
 
                 let task = async move {
-                    let valid_hash_on_disk = {
-                        let path = path.clone();
-                        let permit = disk_semaphore.acquire().await.unwrap();
-                        let result = tokio::task::spawn_blocking(move || {
-                            crate::check_sha1_hash(&path, expected_hash).unwrap_or(false)
-                        }).await.unwrap();
-                        drop(permit);
-                        result
-                    };
-
-                    if valid_hash_on_disk {
-                        java_runtime_tracker.add_count(downloads.raw.size as usize);
-                        java_runtime_tracker.notify();
+                    // Tasks only run once every file has been verified, valid files were already counted as progress
+                    let state = verified.get().unwrap()[index];
+                    if state == FileState::Valid {
                         return Ok(());
                     }
 
                     let was_downloading = started_downloading.swap(true, std::sync::atomic::Ordering::Relaxed);
                     if !was_downloading {
-                        java_runtime_tracker.set_title(Arc::from("Downloading Java Runtime"));
+                        java_runtime_tracker.set_title(rust_i18n::t!("progress_downloading_java").into());
                     }
 
                     let (lzma, size, download) = if let Some(lzma) = &downloads.lzma {
@@ -1471,76 +1778,59 @@ async fn do_java_runtime_load(
                         (false, downloads.raw.size as usize, &downloads.raw)
                     };
 
-                    let permit = download_semaphore.acquire().await.unwrap();
-                    let response = http_client.get(download.url.as_str()).send().await?;
-                    let bytes = response.bytes().await?;
-                    drop(permit);
-
-                    if bytes.len() != size {
-                        return Err(LoadJavaRuntimeError::WrongResponseSize(size, bytes.len()));
+                    if let Some(parent) = path.parent() {
+                        _ = std::fs::create_dir_all(parent);
                     }
 
-                    let decompressed_or_raw = if lzma {
-                        let result = tokio::task::spawn_blocking(move || {
-                            let mut output = Vec::new();
-                            lzma_rs::lzma_decompress(&mut std::io::Cursor::new(bytes), &mut output)?;
-                            Ok(output)
-                        }).await.unwrap();
-
-                        match result {
-                            Ok(decompressed) => Ok(decompressed),
-                            Err(lzma_error) => {
-                                return Err(LoadJavaRuntimeError::Lzma(lzma_error));
-                            },
-                        }
-                    } else {
-                        Err(bytes)
-                    };
+                    // Compressed files are downloaded next to where they go and decompressed into place
+                    let mut download_path = path.clone();
+                    if lzma {
+                        download_path.add_extension("lzma");
+                    }
+                    download_manager.download_file(http_client, download.url.as_str(), &download_path, Some(java_runtime_tracker)).await?;
 
-                    let decompressed_or_raw = Arc::new(decompressed_or_raw);
+                    let downloaded_size = tokio::fs::metadata(&download_path).await?.len() as usize;
+                    if downloaded_size != size {
+                        _ = tokio::fs::remove_file(&download_path).await;
+                        return Err(LoadJavaRuntimeError::WrongResponseSize(size, downloaded_size));
+                    }
 
-                    let bytes = match &*decompressed_or_raw {
-                        Ok(vec) => vec.as_slice(),
-                        Err(bytes) => bytes,
-                    };
+                    if lzma {
+                        let result = {
+                            let download_path = download_path.clone();
+                            let path = path.clone();
+                            tokio::task::spawn_blocking(move || {
+                                let mut input = BufReader::new(File::open(&download_path)?);
+                                let mut output = std::io::BufWriter::new(File::create(&path)?);
+                                lzma_rs::lzma_decompress(&mut input, &mut output)?;
+                                output.flush()?;
+                                Ok::<_, LoadJavaRuntimeError>(())
+                            }).await.unwrap()
+                        };
+                        _ = tokio::fs::remove_file(&download_path).await;
+                        result?;
+                    }
 
-                    if bytes.len() != downloads.raw.size as usize {
+                    if tokio::fs::metadata(&path).await?.len() != downloads.raw.size as u64 {
+                        _ = tokio::fs::remove_file(&path).await;
                         return Err(LoadJavaRuntimeError::WrongRawSize);
                     }
 
                     let valid_hash = {
-                        let decompressed_or_raw = Arc::clone(&decompressed_or_raw);
-                        tokio::task::spawn_blocking(move || {
-                            let bytes = match &*decompressed_or_raw {
-                                Ok(vec) => vec.as_slice(),
-                                Err(bytes) => bytes,
-                            };
-
-                            let mut hasher = Sha1::new();
-                            hasher.update(bytes);
-                            let actual_hash = hasher.finalize();
-
-                            expected_hash == *actual_hash
-                        }).await.unwrap()
+                        let path = path.clone();
+                        tokio::task::spawn_blocking(move || crate::check_sha1_hash(&path, expected_hash)).await.unwrap()?
                     };
-
                     if !valid_hash {
+                        _ = tokio::fs::remove_file(&path).await;
                         return Err(LoadJavaRuntimeError::WrongHash);
                     }
 
-                    if let Some(parent) = path.parent() {
-                        _ = std::fs::create_dir_all(parent);
-                    }
-                    tokio::fs::write(&path, bytes).await?;
-
                     #[cfg(unix)]
                     if *executable {
                         use std::os::unix::fs::PermissionsExt;
                         let _ = tokio::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).await;
                     }
 
-                    java_runtime_tracker.add_count(downloads.raw.size as usize);
-                    java_runtime_tracker.notify();
                     Ok(())
                 };
                 tasks.push(task);
@@ -1550,9 +1840,13 @@ async fn do_java_runtime_load(
             },
         }
     }
-    java_runtime_tracker.set_total(total_size as usize);
+    java_runtime_tracker.set_total(total_size);
     java_runtime_tracker.notify();
 
+    let sizes: Vec<u64> = files.iter().map(|file| file.size.unwrap_or_default()).collect();
+    let states = file_verification::verify_files(files, Some(verified_files.clone()), Some(java_runtime_tracker.clone())).await;
+    download_conditions::ensure_space(&runtime_component_dir, download_conditions::missing_size(&sizes, &states))?;
+    _ = verified.set(states);
     futures::future::try_join_all(tasks).await?;
 
     for (path, target) in links {
@@ -1599,6 +1893,8 @@ async fn do_java_runtime_load(
 pub enum LoadAssetObjectsError {
     #[error("Failed to load remote content")]
     Reqwest(#[from] reqwest::Error),
+    #[error("Failed to download file:\n{0}")]
+    Download(#[from] DownloadError),
     #[error("Failed to perform I/O operation")]
     IoError(#[from] std::io::Error),
     #[error("Hash isn't a valid sha1 hash\n{0}")]
@@ -1609,18 +1905,22 @@ pub enum LoadAssetObjectsError {
     WrongHash,
     #[error("Failed to load metadata:\n{0}")]
     MetaLoadError(#[from] MetaLoadError),
+    #[error(transparent)]
+    InsufficientSpace(#[from] InsufficientSpace),
 }
 
 async fn do_asset_objects_load(
     http_client: &reqwest::Client,
+    download_manager: &DownloadManager,
+    verified_files: &Arc<VerifiedFileCache>,
     assets_index: Arc<AssetsIndex>,
     assets_objects_dir: Arc<Path>,
+    report: &VerificationReport,
     assets_tracker: &ProgressTracker,
 ) -> Result<(), LoadAssetObjectsError> {
-    // Limit max concurrent connections to 8 to avoid ratelimiting issues
-    let download_semaphore = tokio::sync::Semaphore::new(8);
-    let disk_semaphore = tokio::sync::Semaphore::new(32);
     let started_downloading = AtomicBool::new(false);
+    let mut files = Vec::new();
+    let verified: OnceLock<Vec<FileState>> = OnceLock::new();
 
     let mut total_size = 0;
 
@@ -1638,81 +1938,98 @@ async fn do_asset_objects_load(
         let _ = std::fs::create_dir(&path);
         path.push(asset.hash.as_str());
 
-        total_size += asset.size;
+        total_size += asset.size as u64;
+
+        let index = files.len();
+        files.push(FileToVerify {
+            path: path.clone(),
+            sha1: Some(expected_hash),
+            size: Some(asset.size as u64),
+            progress: asset.size as u64,
+        });
 
         let started_downloading = &started_downloading;
-        let download_semaphore = &download_semaphore;
-        let disk_semaphore = &disk_semaphore;
+        let verified = &verified;
 
         let url = format!("https://resources.download.minecraft.net/{}/{}", &asset.hash[..2], &asset.hash);
 
         let task = async move {
-            let valid_hash_on_disk = {
-                let path = path.clone();
-                let permit = disk_semaphore.acquire().await.unwrap();
-                let result = tokio::task::spawn_blocking(move || {
-                    crate::check_sha1_hash(&path, expected_hash).unwrap_or(false)
-                }).await.unwrap();
-                drop(permit);
-                result
-            };
-
-            if valid_hash_on_disk {
-                assets_tracker.add_count(asset.size as usize);
-                assets_tracker.notify();
+            // Tasks only run once every file has been verified, valid files were already counted as progress
+            let state = verified.get().unwrap()[index];
+            if state == FileState::Valid {
+                report.record_verified();
                 return Ok(());
             }
 
             let was_downloading = started_downloading.swap(true, std::sync::atomic::Ordering::Relaxed);
             if !was_downloading {
-                assets_tracker.set_title(Arc::from("Downloading game assets"));
+                assets_tracker.set_title(rust_i18n::t!("progress_downloading_assets").into());
             }
 
-            let permit = download_semaphore.acquire().await.unwrap();
-            let response = http_client.get(&url).send().await?;
-            let bytes = Arc::new(response.bytes().await?);
-            drop(permit);
+            download_manager.download_file(http_client, &url, &path, Some(assets_tracker)).await?;
 
-            if bytes.len() != asset.size as usize {
-                return Err(LoadAssetObjectsError::WrongResponseSize(asset.size as usize, bytes.len()));
+            let size = tokio::fs::metadata(&path).await?.len() as usize;
+            if size != asset.size as usize {
+                _ = tokio::fs::remove_file(&path).await;
+                return Err(LoadAssetObjectsError::WrongResponseSize(asset.size as usize, size));
             }
 
             let correct_hash = {
-                let bytes = Arc::clone(&bytes);
-
-                tokio::task::spawn_blocking(move || {
-                    let mut hasher = Sha1::new();
-                    hasher.update(&*bytes);
-                    let actual_hash = hasher.finalize();
-
-                    expected_hash == *actual_hash
-                }).await.unwrap()
+                let path = path.clone();
+                tokio::task::spawn_blocking(move || crate::check_sha1_hash(&path, expected_hash)).await.unwrap()?
             };
-
             if !correct_hash {
+                _ = tokio::fs::remove_file(&path).await;
                 return Err(LoadAssetObjectsError::WrongHash);
             }
 
-            tokio::fs::write(path.clone(), &*bytes).await?;
-            assets_tracker.add_count(asset.size as usize);
-            assets_tracker.notify();
+            report.record_downloaded(size, state == FileState::Invalid);
             Ok(())
         };
         tasks.push(task);
     }
 
-    assets_tracker.set_total(total_size as usize);
+    assets_tracker.set_total(total_size);
     assets_tracker.notify();
 
+    let sizes: Vec<u64> = files.iter().map(|file| file.size.unwrap_or_default()).collect();
+    let states = file_verification::verify_files(files, Some(verified_files.clone()), Some(assets_tracker.clone())).await;
+    download_conditions::ensure_space(&assets_objects_dir, download_conditions::missing_size(&sizes, &states))?;
+    _ = verified.set(states);
     futures::future::try_join_all(tasks).await?;
 
     Ok(())
 }
 
+/// Copies every object in the index from the hash-named object store to its name under `target_dir`,
+/// as expected by versions using the `virtual` or `map_to_resources` layouts
+fn build_named_asset_layout(assets_index: &AssetsIndex, objects_dir: &Path, target_dir: &Path) -> Result<(), LoadAssetObjectsError> {
+    for (name, asset) in &assets_index.objects {
+        let Some(relative) = SafePath::new(name) else {
+            tracing::warn!("Skipping asset with illegal name {name}");
+            continue;
+        };
+        let target = relative.to_path(target_dir);
+
+        if std::fs::metadata(&target).is_ok_and(|metadata| metadata.len() == asset.size as u64) {
+            continue;
+        }
+
+        let source = objects_dir.join(&asset.hash[..2]).join(asset.hash.as_str());
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(&source, &target)?;
+    }
+    Ok(())
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum LoadLibrariesError {
     #[error("Failed to load remote content")]
     Reqwest(#[from] reqwest::Error),
+    #[error("Failed to download file:\n{0}")]
+    Download(#[from] DownloadError),
     #[error("Failed to perform I/O operation")]
     IoError(#[from] std::io::Error),
     #[error("Hash isn't a valid sha1 hash\n{0}")]
@@ -1723,18 +2040,22 @@ pub enum LoadLibrariesError {
     WrongHash,
     #[error("Illegal library path {0}, directory traversal?")]
     IllegalLibraryPath(Ustr),
+    #[error(transparent)]
+    InsufficientSpace(#[from] InsufficientSpace),
 }
 
 async fn do_libraries_load(
     http_client: &reqwest::Client,
+    download_manager: &DownloadManager,
+    verified_files: &Arc<VerifiedFileCache>,
     artifacts: &[GameLibraryArtifact],
     libraries_dir: Arc<Path>,
+    report: &VerificationReport,
     libraries_tracker: &ProgressTracker,
 ) -> Result<Vec<(Ustr, PathBuf)>, LoadLibrariesError> {
-    // Limit max concurrent connections to 8 to avoid ratelimiting issues
-    let download_semaphore = tokio::sync::Semaphore::new(8);
-    let disk_semaphore = tokio::sync::Semaphore::new(32);
     let started_downloading = AtomicBool::new(false);
+    let mut files = Vec::new();
+    let verified: OnceLock<Vec<FileState>> = OnceLock::new();
 
     let mut total_size = 0;
 
@@ -1757,83 +2078,80 @@ async fn do_libraries_load(
             return Err(LoadLibrariesError::IllegalLibraryPath(artifact.path));
         }
 
-        let artifact_path = libraries_dir.join(artifact.path.as_str());
+        let artifact_path = library_store::store_path(&libraries_dir, artifact.path.as_str(), artifact.sha1.as_deref(), verified_files);
         let Some(artifact_path_parent) = artifact_path.parent() else {
             return Err(LoadLibrariesError::IllegalLibraryPath(artifact.path));
         };
         let _ = std::fs::create_dir_all(artifact_path_parent);
 
-        let tracker_size = artifact.size.unwrap_or(1000000);
+        let tracker_size = artifact.size.unwrap_or(1000000) as u64;
         total_size += tracker_size;
 
+        let index = files.len();
+        files.push(FileToVerify {
+            path: artifact_path.clone(),
+            sha1: expected_hash,
+            size: artifact.size.map(|size| size as u64),
+            progress: tracker_size,
+        });
+
         let started_downloading = &started_downloading;
-        let download_semaphore = &download_semaphore;
-        let disk_semaphore = &disk_semaphore;
+        let verified = &verified;
 
         let task = async move {
-            let valid_hash_on_disk = if let Some(expected_hash) = expected_hash {
-                let artifact_path = artifact_path.clone();
-                let permit = disk_semaphore.acquire().await.unwrap();
-                let result = tokio::task::spawn_blocking(move || {
-                    crate::check_sha1_hash(&artifact_path, expected_hash).unwrap_or(false)
-                }).await.unwrap();
-                drop(permit);
-                result
-            } else {
-                artifact_path.exists()
-            };
-
-            if valid_hash_on_disk {
-                libraries_tracker.add_count(tracker_size as usize);
-                libraries_tracker.notify();
+            // Tasks only run once every file has been verified, valid files were already counted as progress
+            let state = verified.get().unwrap()[index];
+            if state == FileState::Valid {
+                report.record_verified();
                 return Ok((artifact.path, artifact_path));
             }
 
             let was_downloading = started_downloading.swap(true, std::sync::atomic::Ordering::Relaxed);
             if !was_downloading {
-                libraries_tracker.set_title(Arc::from("Downloading game libraries"));
+                libraries_tracker.set_title(rust_i18n::t!("progress_downloading_libraries").into());
             }
 
-            let permit = download_semaphore.acquire().await.unwrap();
-            let response = http_client.get(artifact.url.as_str()).send().await?;
-            let bytes = Arc::new(response.bytes().await?);
-            drop(permit);
+            // Without a known size the progress is estimated, so it's only advanced once the download is done
+            let progress = artifact.size.is_some().then_some(libraries_tracker);
+            // Large libraries such as the client jar go through a .part file, so an interrupted download is resumed
+            download_manager.download_file(http_client, artifact.url.as_str(), &artifact_path, progress).await?;
 
-            if let Some(artifact_size) = artifact.size && bytes.len() != artifact_size as usize {
-                return Err(LoadLibrariesError::WrongResponseSize(artifact_size as usize, bytes.len()));
+            let size = tokio::fs::metadata(&artifact_path).await?.len() as usize;
+            if let Some(artifact_size) = artifact.size && size != artifact_size as usize {
+                _ = tokio::fs::remove_file(&artifact_path).await;
+                return Err(LoadLibrariesError::WrongResponseSize(artifact_size as usize, size));
             }
 
-            let correct_hash = {
-                if let Some(expected_hash) = expected_hash {
-                    let bytes = Arc::clone(&bytes);
-
-                    tokio::task::spawn_blocking(move || {
-                        let mut hasher = Sha1::new();
-                        hasher.update(&*bytes);
-                        let actual_hash = hasher.finalize();
+            if let Some(expected_hash) = expected_hash {
+                let artifact_path = artifact_path.clone();
+                let correct_hash = tokio::task::spawn_blocking(move || {
+                    crate::check_sha1_hash(&artifact_path, expected_hash)
+                }).await.unwrap()?;
 
-                        expected_hash == *actual_hash
-                    }).await.unwrap()
-                } else {
-                    true
+                if !correct_hash {
+                    _ = tokio::fs::remove_file(&artifact_path).await;
+                    return Err(LoadLibrariesError::WrongHash);
                 }
-            };
-
-            if !correct_hash {
-                return Err(LoadLibrariesError::WrongHash);
             }
 
-            tokio::fs::write(artifact_path.clone(), &*bytes).await?;
-            libraries_tracker.add_count(tracker_size as usize);
-            libraries_tracker.notify();
+            report.record_downloaded(size, state == FileState::Invalid);
+            if progress.is_none() {
+                libraries_tracker.add_count(tracker_size);
+                libraries_tracker.notify();
+            }
             Ok((artifact.path, artifact_path))
         };
         tasks.push(task);
     }
 
-    libraries_tracker.set_total(total_size as usize);
+    libraries_tracker.set_total(total_size);
     libraries_tracker.notify();
 
+    // Libraries without a known size are estimated the same way as for the progress
+    let sizes: Vec<u64> = files.iter().map(|file| file.size.unwrap_or(file.progress)).collect();
+    let states = file_verification::verify_files(files, Some(verified_files.clone()), Some(libraries_tracker.clone())).await;
+    download_conditions::ensure_space(&libraries_dir, download_conditions::missing_size(&sizes, &states))?;
+    _ = verified.set(states);
     futures::future::try_join_all(tasks).await
 }
 
@@ -1848,6 +2166,7 @@ pub enum ArgumentExpansionKey {
     VersionName,
     GameDirectory,
     AssetsRoot,
+    GameAssets,
     AssetsIndexName,
     AuthUuid,
     AuthAccessToken,
@@ -1876,7 +2195,8 @@ impl ArgumentExpansionKey {
             "auth_player_name" => Some(Self::AuthPlayerName),
             "version_name" => Some(Self::VersionName),
             "game_directory" => Some(Self::GameDirectory),
-            "assets_root" | "game_assets" => Some(Self::AssetsRoot),
+            "assets_root" => Some(Self::AssetsRoot),
+            "game_assets" => Some(Self::GameAssets),
             "assets_index_name" => Some(Self::AssetsIndexName),
             "auth_uuid" => Some(Self::AuthUuid),
             "auth_access_token" | "auth_session" => Some(Self::AuthAccessToken),
@@ -1916,8 +2236,9 @@ impl LaunchRuleContext {
             _ => None,
         };
 
-        // Remove duplicate libraries
-        let mut deduplicated_libraries: HashMap<String, (GameLibrary, Vec<isize>)> = HashMap::new();
+        // Remove duplicate libraries, keeping the highest version of each group:artifact. The map
+        // is ordered so that the classpath keeps the order in which libraries were first declared
+        let mut deduplicated_libraries: IndexMap<String, (GameLibrary, Vec<isize>)> = IndexMap::new();
         for library in libraries {
             if let Some(rules) = &library.rules && !self.check_rules(rules) {
                 continue;
@@ -1932,7 +2253,7 @@ impl LaunchRuleContext {
             };
 
             let version_id = coordinate.version_id();
-            if let Some((_, existing_library_version)) = deduplicated_libraries.get(&coordinate_id) {
+            if let Some((existing_library, existing_library_version)) = deduplicated_libraries.get(&coordinate_id) {
                 let mut ordering = Ordering::Equal;
                 for (left, right) in version_id.iter().zip(existing_library_version.iter()) {
                     let cmp = left.cmp(right);
@@ -1945,8 +2266,12 @@ impl LaunchRuleContext {
                     ordering = version_id.len().cmp(&existing_library_version.len());
                 }
                 if ordering == Ordering::Less {
+                    tracing::info!("Dropping duplicate library {} in favour of {}", library.name, existing_library.name);
                     continue;
                 }
+                if existing_library.name != library.name {
+                    tracing::info!("Dropping duplicate library {} in favour of {}", existing_library.name, library.name);
+                }
             }
 
             deduplicated_libraries.insert(coordinate_id, (library.clone(), version_id));
@@ -1968,12 +2293,11 @@ impl LaunchRuleContext {
                 && let Some(classifiers) = &library.downloads.classifiers
                 && let Some(os_name) = os_name
                 && let Some(natives_id) = platform_natives.get(&os_name)
-                && let Some(natives) = classifiers.get(natives_id)
+                && let Some(natives) = classifiers.get(&Ustr::from(&natives_id.replace("${arch}", natives_arch())))
             {
+                // Natives jars are always extracted rather than added to the classpath, `extract` only adds exclusions
                 artifacts.push(natives.clone());
-                if let Some(extract) = &library.extract {
-                    natives_to_extract.insert(natives.path, extract.clone());
-                }
+                natives_to_extract.insert(natives.path, library.extract.clone().unwrap_or_default());
             }
         }
     }
@@ -2063,50 +2387,42 @@ pub struct LaunchContext {
     pub assets_root: Arc<Path>,
     pub temp_dir: Arc<Path>,
     pub assets_index_name: String,
+    /// Same as `assets_root` unless the version uses the `virtual` or `map_to_resources` asset layout
+    pub game_assets_dir: Arc<Path>,
     pub classpath: Vec<OsString>,
     pub log_configuration: Option<OsString>,
     pub rule_context: LaunchRuleContext,
     pub login_info: MinecraftLoginInfo,
     pub add_mods: Vec<PathBuf>,
+    /// Starts the JVM with `-version` instead of the game, which checks the JVM arguments and exits
+    pub validate_only: bool,
 }
 
 impl LaunchContext {
-    pub fn launch(mut self, version_info: &MinecraftVersion) -> std::io::Result<std::process::Child> {
+    pub fn launch(mut self, version_info: &MinecraftVersion) -> Result<std::process::Child, LaunchError> {
         #[cfg(target_os = "linux")]
-        let use_mangohud = self.configuration.linux_wrapper.map(|w| w.use_mangohud).unwrap_or(false);
-        #[cfg(target_os = "linux")]
-        let use_gamemode = self.configuration.linux_wrapper.map(|w| w.use_gamemode).unwrap_or(false);
+        let wrapper = self.configuration.linux_wrapper.as_ref().map(|w| w.wrapper_command()).unwrap_or_default();
+        #[cfg(not(target_os = "linux"))]
+        let wrapper: Vec<String> = Vec::new();
 
-        #[cfg(target_os = "linux")]
-        let mut command = match (use_mangohud, use_gamemode) {
-            (true, true) => {
-                let mut cmd = std::process::Command::new("mangohud");
-                cmd.arg("gamemoderun");
-                cmd.arg(&*self.java_path);
-                cmd
-            }
-            (true, false) => {
-                let mut cmd = std::process::Command::new("mangohud");
-                cmd.arg(&*self.java_path);
-                cmd
-            }
-            (false, true) => {
-                let mut cmd = std::process::Command::new("gamemoderun");
-                cmd.arg(&*self.java_path);
-                cmd
-            }
-            (false, false) => {
-                std::process::Command::new(&*self.java_path)
-            }
-        };
+        let mut command = wrapper_command(&wrapper, &self.java_path);
 
         #[cfg(target_os = "linux")]
-        if self.configuration.linux_wrapper.map(|w| w.use_discrete_gpu).unwrap_or(true) {
+        if self.configuration.linux_wrapper.as_ref().map(|w| w.use_discrete_gpu).unwrap_or(true) {
             command.env("DRI_PRIME", "1");
         }
 
-        #[cfg(not(target_os = "linux"))]
-        let mut command = std::process::Command::new(&*self.java_path);
+        // Applied last, so a variable set by the user also replaces one the launcher sets such as DRI_PRIME
+        if let Some(environment) = &self.configuration.environment && environment.enabled {
+            for variable in environment.variables() {
+                match variable {
+                    Ok((key, value)) => {
+                        command.env(key, value);
+                    },
+                    Err(error) => tracing::warn!("Ignoring environment variable, {error}"),
+                }
+            }
+        }
 
         command.current_dir(&self.game_dir);
         command.stdin(Stdio::piped());
@@ -2118,7 +2434,7 @@ impl LaunchContext {
         if let Some(arguments) = &version_info.arguments {
             self.process_arguments(&arguments.jvm, &mut |arg| {
                 command.arg(arg);
-            });
+            })?;
         } else {
             let mut java_library_path = OsString::new();
             java_library_path.push("-Djava.library.path=");
@@ -2126,7 +2442,7 @@ impl LaunchContext {
 
             command.arg(java_library_path);
             command.arg("-cp");
-            command.arg(std::env::join_paths(&self.classpath).unwrap());
+            command.arg(std::env::join_paths(&self.classpath).map_err(|_| LaunchError::InvalidClasspath)?);
         }
 
         if let Some(log_configuration) = &self.log_configuration {
@@ -2138,18 +2454,44 @@ impl LaunchContext {
             command.arg(format!("-Xmx{}m", memory.max.max(memory.min).max(128)));
         }
         if let Some(jvm_flags) = &self.configuration.jvm_flags && jvm_flags.enabled {
-            if let Ok(split) = shell_words::split(&jvm_flags.flags) {
-                command.args(split);
-            } else {
-                command.args(jvm_flags.flags.split_whitespace());
+            let flags = jvm_flags.split();
+            let memory_enabled = self.configuration.memory.is_some_and(|memory| memory.enabled);
+            for warning in schema::instance::jvm_flag_warnings(&flags, memory_enabled) {
+                tracing::warn!("{warning}");
             }
+            command.args(flags);
+        }
+        if let Some(branding) = self.branding() {
+            for property in branding.system_properties() {
+                match property {
+                    Ok((key, value)) => {
+                        command.arg(format!("-D{key}={value}"));
+                    },
+                    Err(error) => tracing::warn!("Ignoring system property, {error}"),
+                }
+            }
+        }
+
+        if self.validate_only {
+            for entry in &self.classpath {
+                if !Path::new(entry).exists() {
+                    return Err(LaunchError::MissingLibrary(entry.to_string_lossy().into()));
+                }
+            }
+            command.arg("-version");
+            crate::process::configure_command(&mut command);
+            return spawn_wrapped(&mut command, &wrapper);
         }
 
         command.arg("com.moulberry.pandora.LaunchWrapper");
 
-        let mut child = command.spawn()?;
+        crate::process::configure_command(&mut command);
 
-        let mut stdin = child.stdin.take().expect("stdin present");
+        let mut child = spawn_wrapped(&mut command, &wrapper)?;
+
+        let Some(mut stdin) = child.stdin.take() else {
+            return Err(std::io::Error::other("Game process has no stdin").into());
+        };
 
         let mut stdin_arguments = String::new();
 
@@ -2158,22 +2500,35 @@ impl LaunchContext {
                 stdin_arguments.push_str("arg\n");
                 stdin_arguments.push_str(arg.to_string_lossy().as_ref());
                 stdin_arguments.push('\n');
-            });
+            })?;
         }
         if let Some(legacy_arguments) = &version_info.minecraft_arguments {
             for argument in legacy_arguments.split_ascii_whitespace() {
                 stdin_arguments.push_str("arg\n");
-                stdin_arguments.push_str(self.expand_argument(argument).to_string_lossy().as_ref());
+                stdin_arguments.push_str(self.expand_argument(argument)?.to_string_lossy().as_ref());
                 stdin_arguments.push('\n');
             }
         }
 
+        // Versions before 23w14a don't have quick play, but they do support joining a server directly
+        if !supports_quick_play(version_info) {
+            match &self.rule_context.quick_play {
+                Some(QuickPlayLaunch::Multiplayer(target)) => {
+                    let (host, port) = split_server_address(&target.to_string_lossy());
+                    stdin_arguments.push_str(&format!("arg\n--server\narg\n{host}\narg\n--port\narg\n{port}\n"));
+                },
+                Some(quick_play) => {
+                    tracing::warn!("Ignoring {quick_play:?}, this version doesn't support quick play");
+                },
+                None => {},
+            }
+        }
 
         if !self.add_mods.is_empty() {
             match self.configuration.loader {
                 Loader::Vanilla => {},
                 Loader::Fabric => {
-                    let mods = std::env::join_paths(self.add_mods).unwrap();
+                    let mods = std::env::join_paths(self.add_mods).map_err(|_| LaunchError::InvalidClasspath)?;
 
                     stdin_arguments.push_str("property\n");
                     stdin_arguments.push_str("fabric.addMods\n");
@@ -2181,9 +2536,7 @@ impl LaunchContext {
                     stdin_arguments.push('\n');
                 },
                 _ => {
-                    if cfg!(debug_assertions) {
-                        panic!("addMods was used for unsupported loader: {:?}", self.configuration.loader);
-                    }
+                    tracing::error!("addMods was used for unsupported loader: {:?}", self.configuration.loader);
                 }
             }
         }
@@ -2198,35 +2551,37 @@ impl LaunchContext {
         Ok(child)
     }
 
-    fn process_arguments(&self, arguments: &[LaunchArgument], handler: &mut impl FnMut(&OsStr)) {
+    fn process_arguments(&self, arguments: &[LaunchArgument], handler: &mut impl FnMut(&OsStr)) -> Result<(), LaunchError> {
         for argument in arguments {
             match argument {
                 LaunchArgument::Single(value) => {
-                    self.process_argument(value, handler);
+                    self.process_argument(value, handler)?;
                 },
                 LaunchArgument::Ruled(ruled) => {
                     if self.rule_context.check_rules(&ruled.rules) {
-                        self.process_argument(&ruled.value, handler);
+                        self.process_argument(&ruled.value, handler)?;
                     }
                 },
             }
         }
+        Ok(())
     }
 
-    fn process_argument(&self, value: &LaunchArgumentValue, handler: &mut impl FnMut(&OsStr)) {
+    fn process_argument(&self, value: &LaunchArgumentValue, handler: &mut impl FnMut(&OsStr)) -> Result<(), LaunchError> {
         match value {
             LaunchArgumentValue::Single(string) => {
-                (handler)(&self.expand_argument(string));
+                (handler)(&self.expand_argument(string)?);
             },
             LaunchArgumentValue::Multiple(strings) => {
                 for string in strings.iter() {
-                    (handler)(&self.expand_argument(string));
+                    (handler)(&self.expand_argument(string)?);
                 }
             },
         }
+        Ok(())
     }
 
-    fn expand_argument<'a>(&self, argument: &'a str) -> Cow<'a, OsStr> {
+    fn expand_argument<'a>(&self, argument: &'a str) -> Result<Cow<'a, OsStr>, LaunchError> {
         let mut dollar_last = false;
         let mut builder = OsString::new();
         let mut copied_to_builder = 0;
@@ -2238,12 +2593,12 @@ impl LaunchContext {
                 if let Some(end) = remaining.find('}') {
                     let to_expand = &argument[i+1..i+end];
                     if let Some(to_expand) = ArgumentExpansionKey::from_str(to_expand) {
-                        let expanded = self.resolve_expansion(to_expand);
+                        let expanded = self.resolve_expansion(to_expand)?;
                         builder.push(&argument[copied_to_builder..i-1]);
                         builder.push(expanded);
                         copied_to_builder = i+end+1;
                     } else {
-                        panic!("Unsupported argument: {:?}", to_expand);
+                        return Err(LaunchError::UnsupportedArgument(to_expand.into()));
                     }
                 }
             } else {
@@ -2252,29 +2607,38 @@ impl LaunchContext {
         }
         if !builder.is_empty() {
             builder.push(&argument[copied_to_builder..]);
-            return Cow::Owned(builder);
+            return Ok(Cow::Owned(builder));
         }
-        Cow::Borrowed(OsStr::new(argument))
+        Ok(Cow::Borrowed(OsStr::new(argument)))
+    }
+
+    fn branding(&self) -> Option<&InstanceBrandingConfiguration> {
+        self.configuration.branding.as_ref().filter(|branding| branding.enabled)
     }
 
-    fn resolve_expansion(&self, key: ArgumentExpansionKey) -> Cow<'_, OsStr> {
-        match key {
+    fn resolve_expansion(&self, key: ArgumentExpansionKey) -> Result<Cow<'_, OsStr>, LaunchError> {
+        Ok(match key {
             ArgumentExpansionKey::NativesDirectory => self.natives_dir.as_os_str().into(),
             ArgumentExpansionKey::LibrariesDirectory => self.libraries_dir.as_os_str().into(),
-            ArgumentExpansionKey::ClasspathSeparator => if cfg!(unix) {
-                OsStr::new(":").into()
-            } else if cfg!(windows) {
+            ArgumentExpansionKey::ClasspathSeparator => if cfg!(windows) {
                 OsStr::new(";").into()
             } else {
-                panic!("Unsupported platform")
+                OsStr::new(":").into()
+            },
+            ArgumentExpansionKey::LauncherName => {
+                let name = self.branding().map(|branding| &*branding.launcher_name).filter(|name| !name.is_empty());
+                OsStr::new(name.unwrap_or("PandoraLauncher")).into()
+            },
+            ArgumentExpansionKey::LauncherVersion => {
+                let version = self.branding().map(|branding| &*branding.launcher_version).filter(|version| !version.is_empty());
+                OsStr::new(version.unwrap_or("1.0.0")).into()
             },
-            ArgumentExpansionKey::LauncherName => OsStr::new("PandoraLauncher").into(),
-            ArgumentExpansionKey::LauncherVersion => OsStr::new("1.0.0").into(),
-            ArgumentExpansionKey::Classpath => std::env::join_paths(&self.classpath).unwrap().into(),
+            ArgumentExpansionKey::Classpath => std::env::join_paths(&self.classpath).map_err(|_| LaunchError::InvalidClasspath)?.into(),
             ArgumentExpansionKey::AuthPlayerName => OsStr::new(&*self.login_info.username).into(),
             ArgumentExpansionKey::VersionName => OsStr::new("1.21.10").into(),
             ArgumentExpansionKey::GameDirectory => self.game_dir.as_os_str().into(),
             ArgumentExpansionKey::AssetsRoot => self.assets_root.as_os_str().into(),
+            ArgumentExpansionKey::GameAssets => self.game_assets_dir.as_os_str().into(),
             ArgumentExpansionKey::AssetsIndexName => OsStr::new(&self.assets_index_name).into(),
             ArgumentExpansionKey::AuthUuid => OsString::from(self.login_info.uuid.as_hyphenated().to_string()).into(),
             ArgumentExpansionKey::AuthAccessToken => OsStr::new(if let Some(access_token) = &self.login_info.access_token {
@@ -2288,8 +2652,8 @@ impl LaunchContext {
             ArgumentExpansionKey::QuickPlayPath => OsStr::new("quickPlay/log.json").into(),
             ArgumentExpansionKey::UserProperties => OsStr::new("{}").into(),
             ArgumentExpansionKey::UserType => OsStr::new("msa").into(),
-            ArgumentExpansionKey::ResolutionWidth => OsString::from(format!("{}", self.rule_context.custom_resolution.unwrap().0)).into(),
-            ArgumentExpansionKey::ResolutionHeight => OsString::from(format!("{}", self.rule_context.custom_resolution.unwrap().1)).into(),
+            ArgumentExpansionKey::ResolutionWidth => OsString::from(format!("{}", self.rule_context.custom_resolution.map(|r| r.0).unwrap_or(854))).into(),
+            ArgumentExpansionKey::ResolutionHeight => OsString::from(format!("{}", self.rule_context.custom_resolution.map(|r| r.1).unwrap_or(480))).into(),
             ArgumentExpansionKey::QuickPlaySingleplayer => {
                 if let Some(QuickPlayLaunch::Singleplayer(target)) = &self.rule_context.quick_play {
                     target.into()
@@ -2311,8 +2675,56 @@ impl LaunchContext {
                     OsStr::new("").into()
                 }
             },
+        })
+    }
+}
+
+fn supports_quick_play(version_info: &MinecraftVersion) -> bool {
+    let Some(arguments) = &version_info.arguments else {
+        return false;
+    };
+    arguments.game.iter().any(|argument| {
+        let value = match argument {
+            LaunchArgument::Single(value) => value,
+            LaunchArgument::Ruled(ruled) => &ruled.value,
+        };
+        match value {
+            LaunchArgumentValue::Single(string) => string.contains("${quickPlayMultiplayer}"),
+            LaunchArgumentValue::Multiple(strings) => strings.iter().any(|string| string.contains("${quickPlayMultiplayer}")),
         }
+    })
+}
+
+/// Command running Java through `wrapper`, whose first element is the program and the rest its arguments
+fn wrapper_command(wrapper: &[String], java_path: &Path) -> std::process::Command {
+    let Some((program, args)) = wrapper.split_first() else {
+        return std::process::Command::new(java_path);
+    };
+    let mut command = std::process::Command::new(program);
+    command.args(args);
+    command.arg(java_path);
+    command
+}
+
+/// When there is a wrapper, it's the program being started, so not finding it means the wrapper is missing
+fn spawn_wrapped(command: &mut std::process::Command, wrapper: &[String]) -> Result<Child, LaunchError> {
+    command.spawn().map_err(|error| match wrapper.first() {
+        Some(program) if error.kind() == std::io::ErrorKind::NotFound => LaunchError::WrapperNotFound(program.as_str().into()),
+        _ => error.into(),
+    })
+}
+
+/// Splits a server list address into host and port, defaulting to 25565
+fn split_server_address(address: &str) -> (&str, u16) {
+    let address = address.trim();
+    if let Some(bracketed) = address.strip_prefix('[') && let Some((host, rest)) = bracketed.split_once(']') {
+        let port = rest.strip_prefix(':').and_then(|port| port.parse().ok()).unwrap_or(25565);
+        return (host, port);
+    }
+    if let Some((host, port)) = address.split_once(':') && !port.contains(':') && let Ok(port) = port.parse() {
+        return (host, port);
     }
+    (address, 25565)
 }
 
 fn path_is_normal(path: impl AsRef<Path>) -> bool {
@@ -2331,7 +2743,7 @@ fn path_is_normal(path: impl AsRef<Path>) -> bool {
     true
 }
 
-fn expand_forge_argument<'a>(argument: &'a str, map: &FxHashMap<String, OsString>) -> Cow<'a, OsStr> {
+fn expand_forge_argument<'a>(argument: &'a str, map: &FxHashMap<String, OsString>) -> Result<Cow<'a, OsStr>, LaunchError> {
     let mut builder = OsString::new();
     let mut copied_to_builder = 0;
     for (i, character) in argument.char_indices() {
@@ -2344,14 +2756,396 @@ fn expand_forge_argument<'a>(argument: &'a str, map: &FxHashMap<String, OsString
                     builder.push(expanded);
                     copied_to_builder = i+end+1;
                 } else {
-                    panic!("Unsupported argument: {:?}", to_expand);
+                    return Err(LaunchError::UnsupportedArgument(to_expand.into()));
                 }
             }
         }
     }
     if !builder.is_empty() {
         builder.push(&argument[copied_to_builder..]);
-        return Cow::Owned(builder);
+        return Ok(Cow::Owned(builder));
+    }
+    Ok(Cow::Borrowed(OsStr::new(argument)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::{self, MockServer, TempDir};
+
+    fn current_os() -> &'static str {
+        match std::env::consts::OS {
+            "macos" => "osx",
+            other => other,
+        }
+    }
+
+    fn rules(json: serde_json::Value) -> Vec<Rule> {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn library(name: &str, size: u32) -> GameLibrary {
+        let coordinate = MavenCoordinate::create(name);
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "downloads": {
+                "artifact": {
+                    "path": coordinate.artifact_path(),
+                    "sha1": null,
+                    "size": size,
+                    "url": format!("https://libraries.minecraft.net/{}", coordinate.artifact_path()),
+                }
+            }
+        })).unwrap()
+    }
+
+    fn rule_context() -> LaunchRuleContext {
+        LaunchRuleContext {
+            is_demo_user: false,
+            custom_resolution: None,
+            quick_play: None,
+        }
+    }
+
+    #[test]
+    fn wrappers_are_put_in_front_of_java() {
+        let configuration = schema::instance::InstanceLinuxWrapperConfiguration {
+            use_gamemode: true,
+            use_prime_run: true,
+            custom_command: "obs-gamecapture --name 'My Game'".into(),
+            ..Default::default()
+        };
+        let command = wrapper_command(&configuration.wrapper_command(), Path::new("java"));
+        assert_eq!(command.get_program(), "gamemoderun");
+        let args: Vec<&OsStr> = command.get_args().collect();
+        assert_eq!(args, ["prime-run", "obs-gamecapture", "--name", "My Game", "java"]);
+
+        let command = wrapper_command(&[], Path::new("java"));
+        assert_eq!(command.get_program(), "java");
+        assert_eq!(command.get_args().count(), 0);
+    }
+
+    #[test]
+    fn server_addresses_are_split_into_host_and_port() {
+        assert_eq!(split_server_address("play.example.com"), ("play.example.com", 25565));
+        assert_eq!(split_server_address("play.example.com:25570"), ("play.example.com", 25570));
+        assert_eq!(split_server_address("[::1]:25570"), ("::1", 25570));
+        assert_eq!(split_server_address("::1"), ("::1", 25565));
+    }
+
+    #[test]
+    fn rules_allow_unconditionally() {
+        assert!(rule_context().check_rules(&rules(serde_json::json!([{ "action": "allow" }]))));
+    }
+
+    #[test]
+    fn rules_later_disallow_for_current_os_wins() {
+        let rules = rules(serde_json::json!([
+            { "action": "allow" },
+            { "action": "disallow", "os": { "name": current_os() } },
+        ]));
+        assert!(!rule_context().check_rules(&rules));
+    }
+
+    #[test]
+    fn rules_features_require_matching_context() {
+        let rules = rules(serde_json::json!([
+            { "action": "allow", "features": { "has_custom_resolution": true } },
+        ]));
+        assert!(!rule_context().check_rules(&rules));
+
+        let mut context = rule_context();
+        context.custom_resolution = Some((1280, 720));
+        assert!(context.check_rules(&rules));
+    }
+
+    #[test]
+    fn duplicate_libraries_keep_highest_version_in_declaration_order() {
+        let libraries = [
+            library("org.ow2.asm:asm:9.3", 1000),
+            library("org.lwjgl:lwjgl:3.3.3", 1000),
+            library("org.ow2.asm:asm:9.6", 1000),
+            library("org.lwjgl:lwjgl:3.2.2", 1000),
+        ];
+
+        let mut artifacts = Vec::new();
+        let mut natives = HashMap::new();
+        rule_context().collect_libraries(&libraries, &mut artifacts, &mut natives);
+
+        let paths: Vec<&str> = artifacts.iter().map(|artifact| artifact.path.as_str()).collect();
+        assert_eq!(paths, [
+            "org/ow2/asm/asm/9.6/asm-9.6.jar",
+            "org/lwjgl/lwjgl/3.3.3/lwjgl-3.3.3.jar",
+        ]);
+    }
+
+    #[test]
+    fn legacy_natives_use_the_platform_classifier() {
+        let os = current_os();
+        let classifier = format!("natives-{os}-{}", natives_arch());
+        let library: GameLibrary = serde_json::from_value(serde_json::json!({
+            "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.0",
+            "downloads": {
+                "classifiers": {
+                    (classifier.clone()): {
+                        "path": format!("org/lwjgl/lwjgl/lwjgl-platform/2.9.0/lwjgl-platform-2.9.0-{classifier}.jar"),
+                        "sha1": null,
+                        "size": 1000,
+                        "url": "https://libraries.minecraft.net/lwjgl-platform.jar",
+                    }
+                }
+            },
+            "natives": { (os): format!("natives-{os}-${{arch}}") },
+            "extract": { "exclude": ["META-INF/"] },
+        })).unwrap();
+
+        let mut artifacts = Vec::new();
+        let mut natives = HashMap::new();
+        rule_context().collect_libraries(&[library], &mut artifacts, &mut natives);
+
+        assert_eq!(artifacts.len(), 1);
+        let extract = natives.get(&artifacts[0].path).unwrap();
+        assert_eq!(extract.exclude.as_ref().map(|exclude| exclude.len()), Some(1));
+    }
+
+    #[test]
+    fn expand_logging_argument_substitutes_path() {
+        let expanded = expand_logging_argument("-Dlog4j.configurationFile=${path}", Path::new("/tmp/client.xml"));
+        assert_eq!(expanded, Some(OsString::from("-Dlog4j.configurationFile=/tmp/client.xml")));
+        assert_eq!(expand_logging_argument("-Dfoo=${unknown}", Path::new("/tmp/client.xml")), None);
+    }
+
+    #[test]
+    fn expand_forge_argument_rejects_unknown_keys() {
+        let mut data = FxHashMap::default();
+        data.insert("SIDE".to_string(), OsString::from("client"));
+
+        assert_eq!(expand_forge_argument("--side={SIDE}", &data).unwrap(), OsStr::new("--side=client"));
+        assert_eq!(expand_forge_argument("plain", &data).unwrap(), OsStr::new("plain"));
+        assert!(matches!(expand_forge_argument("{MISSING}", &data), Err(LaunchError::UnsupportedArgument(_))));
+    }
+
+    #[test]
+    fn java_version_output_is_parsed() {
+        assert_eq!(parse_java_version_output("openjdk version \"17.0.9\" 2023-10-17\nOpenJDK Runtime Environment"), Some(17));
+        assert_eq!(parse_java_version_output("java version \"1.8.0_392\""), Some(8));
+        assert_eq!(parse_java_version_output("openjdk version \"21-ea\" 2023-09-19"), Some(21));
+        assert_eq!(parse_java_version_output("Error: could not find libjava.so"), None);
+    }
+
+    #[test]
+    fn argument_expansion_keys_are_recognized() {
+        assert!(ArgumentExpansionKey::from_str("classpath").is_some());
+        assert!(ArgumentExpansionKey::from_str("auth_player_name").is_some());
+        assert!(ArgumentExpansionKey::from_str("not_a_real_key").is_none());
+        assert!(matches!(ArgumentExpansionKey::from_str("game_assets"), Some(ArgumentExpansionKey::GameAssets)));
+    }
+
+    #[test]
+    fn legacy_assets_are_copied_by_name() {
+        let temp = TempDir::new();
+        let objects_dir = temp.path().join("objects");
+        let target_dir = temp.path().join("virtual/legacy");
+
+        let body = b"sound";
+        let hash = test_harness::sha1_hex(body);
+        std::fs::create_dir_all(objects_dir.join(&hash[..2])).unwrap();
+        std::fs::write(objects_dir.join(&hash[..2]).join(&hash), body).unwrap();
+
+        let assets_index: AssetsIndex = serde_json::from_value(serde_json::json!({
+            "objects": {
+                "sounds/random/click.ogg": { "hash": hash, "size": body.len() },
+                "../escape.ogg": { "hash": hash, "size": body.len() },
+            },
+            "virtual": true,
+        })).unwrap();
+
+        build_named_asset_layout(&assets_index, &objects_dir, &target_dir).unwrap();
+
+        assert_eq!(std::fs::read(target_dir.join("sounds/random/click.ogg")).unwrap(), body);
+        assert!(!temp.path().join("virtual/escape.ogg").exists());
+    }
+
+    #[tokio::test]
+    async fn libraries_are_downloaded_and_verified() {
+        let body = vec![7u8; 4096];
+        let server = MockServer::start(HashMap::from([
+            ("/good.jar".to_string(), body.clone()),
+            ("/bad.jar".to_string(), vec![0u8; 4096]),
+        ])).await;
+        let dir = TempDir::new();
+        let libraries_dir: Arc<Path> = dir.path().into();
+        let tracker = ProgressTracker::new("Test".into(), test_harness::frontend_handle());
+        let http_client = test_harness::http_client();
+        let download_manager = DownloadManager::new(Default::default());
+        let verified_files = Arc::new(VerifiedFileCache::load(dir.path().join("verified_files.json").into()));
+
+        let good = GameLibraryArtifact {
+            path: "good/good.jar".into(),
+            sha1: Some(test_harness::sha1_hex(&body).as_str().into()),
+            size: Some(body.len() as u32),
+            url: server.url("/good.jar").as_str().into(),
+        };
+        let report = VerificationReport::default();
+        let result = do_libraries_load(&http_client, &download_manager, &verified_files, std::slice::from_ref(&good), libraries_dir.clone(), &report, &tracker).await.unwrap();
+        assert_eq!(std::fs::read(&result[0].1).unwrap(), body);
+        assert_eq!(report.downloaded(), 1);
+
+        // Already present with the correct hash, so it shouldn't be downloaded again
+        do_libraries_load(&http_client, &download_manager, &verified_files, std::slice::from_ref(&good), libraries_dir.clone(), &report, &tracker).await.unwrap();
+        assert_eq!(server.requests(), ["/good.jar"]);
+        assert_eq!(report.verified(), 1);
+
+        // Corrupted on disk, so it is downloaded again and counted as a repair
+        std::fs::write(&result[0].1, b"corrupt").unwrap();
+        do_libraries_load(&http_client, &download_manager, &verified_files, std::slice::from_ref(&good), libraries_dir.clone(), &report, &tracker).await.unwrap();
+        assert_eq!(report.repaired(), 1);
+
+        let wrong_hash = GameLibraryArtifact {
+            path: "bad/bad.jar".into(),
+            url: server.url("/bad.jar").as_str().into(),
+            ..good
+        };
+        let result = do_libraries_load(&http_client, &download_manager, &verified_files, &[wrong_hash], libraries_dir.clone(), &report, &tracker).await;
+        assert!(matches!(result, Err(LoadLibrariesError::WrongHash)));
+
+        let wrong_size = GameLibraryArtifact {
+            size: Some(1),
+            ..good
+        };
+        let _ = std::fs::remove_file(libraries_dir.join("good/good.jar"));
+        let result = do_libraries_load(&http_client, &download_manager, &verified_files, &[wrong_size], libraries_dir, &report, &tracker).await;
+        assert!(matches!(result, Err(LoadLibrariesError::WrongResponseSize(1, 4096))));
+    }
+
+    // Runs the whole launch against a mirror serving a small legacy version, with a stand-in for Java that records
+    // how it was started
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn vanilla_launch_downloads_everything_and_starts_java() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let asset = b"click".to_vec();
+        let asset_hash = test_harness::sha1_hex(&asset);
+        let library = vec![1u8; 2048];
+        let client = vec![2u8; 4096];
+        let library_path = "com/example/library/1.0/library-1.0.jar";
+
+        let assets_index = serde_json::to_vec(&serde_json::json!({
+            "objects": { "sounds/random/click.ogg": { "hash": asset_hash, "size": asset.len() } },
+        })).unwrap();
+        let assets_index_hash = test_harness::sha1_hex(&assets_index);
+        let client_hash = test_harness::sha1_hex(&client);
+
+        let version = serde_json::to_vec(&serde_json::json!({
+            "assetIndex": {
+                "id": "1.12",
+                "sha1": assets_index_hash,
+                "size": assets_index.len(),
+                "totalSize": asset.len(),
+                "url": format!("https://piston-meta.mojang.com/v1/packages/{assets_index_hash}/1.12.json"),
+            },
+            "assets": "1.12",
+            "downloads": {
+                "client": {
+                    "sha1": client_hash,
+                    "size": client.len(),
+                    "url": format!("https://piston-data.mojang.com/v1/objects/{client_hash}/client.jar"),
+                },
+            },
+            "id": "1.12.2",
+            "javaVersion": { "component": "jre-legacy", "majorVersion": 8 },
+            "libraries": [{
+                "name": "com.example:library:1.0",
+                "downloads": {
+                    "artifact": {
+                        "path": library_path,
+                        "sha1": test_harness::sha1_hex(&library),
+                        "size": library.len(),
+                        "url": format!("https://libraries.minecraft.net/{library_path}"),
+                    },
+                },
+            }],
+            "mainClass": "net.minecraft.client.main.Main",
+            "minecraftArguments": "--username ${auth_player_name} --version ${version_name} --assetIndex ${assets_index_name}",
+            "minimumLauncherVersion": 18,
+            "releaseTime": "2017-09-18T08:39:46+00:00",
+            "time": "2017-09-18T08:39:46+00:00",
+            "type": "release",
+        })).unwrap();
+        let version_hash = test_harness::sha1_hex(&version);
+
+        let manifest = serde_json::to_vec(&serde_json::json!({
+            "latest": { "release": "1.12.2", "snapshot": "1.12.2" },
+            "versions": [{
+                "id": "1.12.2",
+                "type": "release",
+                "url": format!("https://piston-meta.mojang.com/v1/packages/{version_hash}/1.12.2.json"),
+                "time": "2017-09-18T08:39:46+00:00",
+                "releaseTime": "2017-09-18T08:39:46+00:00",
+                "sha1": version_hash,
+                "complianceLevel": 0,
+            }],
+        })).unwrap();
+
+        let server = MockServer::start(HashMap::from([
+            ("/mc/game/version_manifest_v2.json".to_string(), manifest),
+            (format!("/v1/packages/{version_hash}/1.12.2.json"), version),
+            (format!("/v1/packages/{assets_index_hash}/1.12.json"), assets_index),
+            (format!("/v1/objects/{client_hash}/client.jar"), client.clone()),
+            (format!("/assets/{}/{asset_hash}", &asset_hash[..2]), asset.clone()),
+            (format!("/maven/{library_path}"), library.clone()),
+        ])).await;
+        let _endpoints = test_harness::override_endpoints(schema::backend_config::EndpointOverrides {
+            meta: Some(server.url("").into()),
+            assets: Some(server.url("/assets").into()),
+            libraries: Some(server.url("/maven").into()),
+        });
+
+        let dir = TempDir::new();
+        let directories = Arc::new(LauncherDirectories::new(dir.path().to_path_buf()));
+        let http_client = test_harness::http_client();
+        let meta = Arc::new(MetadataManager::new(http_client.clone(), directories.metadata_dir.clone()));
+        let launcher = Launcher::new(meta, directories.clone(), test_harness::frontend_handle(), DownloadManager::new(Default::default()));
+
+        let java = dir.path().join("java");
+        std::fs::write(&java, "#!/bin/sh\nprintf '%s\\n' \"$@\" > java-args.txt\ncat > java-stdin.txt\n").unwrap();
+        std::fs::set_permissions(&java, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut configuration = InstanceConfiguration::new("1.12.2".into(), Loader::Vanilla);
+        configuration.jvm_binary = Some(schema::instance::InstanceJvmBinaryConfiguration {
+            enabled: true,
+            path: Some(java.into()),
+            major_version: Some(8),
+        });
+
+        let game_dir: Arc<Path> = dir.path().join("instance/.minecraft").into();
+        let login_info = MinecraftLoginInfo {
+            uuid: uuid::Uuid::nil(),
+            username: "Steve".into(),
+            access_token: None,
+        };
+        let report = VerificationReport::default();
+        let tracker = ProgressTracker::new("Launch".into(), test_harness::frontend_handle());
+        let mut child = launcher.launch(&http_client, game_dir.clone(), None, configuration, None, login_info, Vec::new(),
+            false, false, &report, &tracker, &ModalAction::default()).await.unwrap();
+        assert!(child.wait().unwrap().success());
+
+        let library_file = directories.libraries_dir.join(library_path);
+        let client_file = directories.libraries_dir.join("net/minecraft/1.12.2/minecraft-client-1.12.2.jar");
+        assert_eq!(std::fs::read(&library_file).unwrap(), library);
+        assert_eq!(std::fs::read(&client_file).unwrap(), client);
+        assert_eq!(std::fs::read(directories.assets_objects_dir.join(&asset_hash[..2]).join(&asset_hash)).unwrap(), asset);
+
+        let args = std::fs::read_to_string(game_dir.join("java-args.txt")).unwrap();
+        let args: Vec<&str> = args.lines().collect();
+        let classpath_index = args.iter().position(|arg| *arg == "-cp").unwrap();
+        let classpath: Vec<PathBuf> = std::env::split_paths(args[classpath_index + 1]).collect();
+        assert_eq!(classpath, [library_file, client_file, launcher.launch_wrapper.to_path_buf()]);
+        assert_eq!(args.last(), Some(&"com.moulberry.pandora.LaunchWrapper"));
+
+        let stdin = std::fs::read_to_string(game_dir.join("java-stdin.txt")).unwrap();
+        assert!(stdin.starts_with("arg\n--username\narg\nSteve\narg\n--version\narg\n1.12.2\narg\n--assetIndex\narg\n1.12\n"), "{stdin}");
+        assert!(stdin.ends_with("launch\nnet.minecraft.client.main.Main\n"), "{stdin}");
     }
-    Cow::Borrowed(OsStr::new(argument))
 }
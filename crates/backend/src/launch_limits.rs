@@ -0,0 +1,121 @@
+use schema::{backend_config::LaunchLimits, instance::{jvm_flag_option, InstanceConfiguration}};
+
+use crate::BackendState;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum LaunchLimitError {
+    #[error("{running} instances are already running, at most {limit} can run at the same time")]
+    TooManyRunning { running: usize, limit: u32 },
+    #[error("Running instances would use up to {committed} MiB of memory, more than the budget of {budget} MiB ({percent}% of system memory)")]
+    OverMemoryBudget { committed: u64, budget: u64, percent: u32 },
+}
+
+/// Parses a JVM memory size like `4G`, `512m` or `1048576` into MiB
+pub(crate) fn parse_size_mib(value: &str) -> Option<u64> {
+    let (number, unit) = match value.char_indices().last()? {
+        (index, unit) if unit.is_ascii_alphabetic() => (&value[..index], unit.to_ascii_lowercase()),
+        _ => (value, 'b'),
+    };
+    let number: u64 = number.parse().ok()?;
+    match unit {
+        'b' => Some(number / 1024 / 1024),
+        'k' => Some(number / 1024),
+        'm' => Some(number),
+        'g' => Some(number * 1024),
+        't' => Some(number * 1024 * 1024),
+        _ => None,
+    }
+}
+
+/// Max heap the game is started with, None when the JVM picks its default. An -Xmx in the JVM flags comes after
+/// the memory setting on the command line, so the last one wins
+pub fn max_heap_mib(configuration: &InstanceConfiguration) -> Option<u64> {
+    let from_flags = configuration.jvm_flags.as_ref()
+        .filter(|jvm_flags| jvm_flags.enabled)
+        .and_then(|jvm_flags| {
+            jvm_flags.split().iter()
+                .rfind(|flag| jvm_flag_option(flag) == "-Xmx")
+                .and_then(|flag| parse_size_mib(&flag["-Xmx".len()..]))
+        });
+
+    from_flags.or_else(|| {
+        configuration.memory
+            .filter(|memory| memory.enabled)
+            .map(|memory| memory.max.max(memory.min).max(128) as u64)
+    })
+}
+
+/// The JVM defaults to a quarter of physical memory for the max heap
+pub fn effective_heap_mib(max_heap_mib: Option<u64>, total_memory_mib: Option<u64>) -> u64 {
+    max_heap_mib.unwrap_or_else(|| total_memory_mib.map(|total| total / 4).unwrap_or(0))
+}
+
+/// Checks whether another instance with `requested_heap_mib` can be launched next to the running ones
+pub fn check(limits: &LaunchLimits, running_heaps_mib: &[u64], requested_heap_mib: u64, total_memory_mib: Option<u64>)
+    -> Result<(), LaunchLimitError>
+{
+    if let Some(limit) = limits.max_running && running_heaps_mib.len() >= limit as usize {
+        return Err(LaunchLimitError::TooManyRunning { running: running_heaps_mib.len(), limit });
+    }
+
+    if let Some(percent) = limits.memory_budget_percent && let Some(total) = total_memory_mib {
+        let budget = total * percent as u64 / 100;
+        let committed = running_heaps_mib.iter().sum::<u64>() + requested_heap_mib;
+        if committed > budget {
+            return Err(LaunchLimitError::OverMemoryBudget { committed, budget, percent });
+        }
+    }
+
+    Ok(())
+}
+
+impl BackendState {
+    /// Checks the launch limits before launching the instance. Going over the memory budget only warns, unless
+    /// launching is refused in the settings. Returns the error when launching should be refused
+    pub fn check_launch_limits(&self, configuration: &InstanceConfiguration) -> Result<(), LaunchLimitError> {
+        let limits = self.config.write().get().launch_limits;
+        if limits == LaunchLimits::default() {
+            return Ok(());
+        }
+
+        let total_memory_mib = crate::system_info::total_memory_mib();
+        let running: Vec<u64> = self.instance_state.read().instances.iter()
+            .filter_map(|instance| instance.process.as_ref().map(|process| process.max_heap_mib))
+            .collect();
+        let requested = effective_heap_mib(max_heap_mib(configuration), total_memory_mib);
+
+        match check(&limits, &running, requested, total_memory_mib) {
+            Err(error @ LaunchLimitError::OverMemoryBudget { .. }) if !limits.refuse_over_memory_budget => {
+                tracing::warn!("{error}");
+                self.send.send_warning(error.to_string());
+                Ok(())
+            },
+            result => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_heap_sizes() {
+        assert_eq!(parse_size_mib("4G"), Some(4096));
+        assert_eq!(parse_size_mib("512m"), Some(512));
+        assert_eq!(parse_size_mib("2097152k"), Some(2048));
+        assert_eq!(parse_size_mib("1073741824"), Some(1024));
+        assert_eq!(parse_size_mib("lots"), None);
+    }
+
+    #[test]
+    fn enforces_limits() {
+        let limits = LaunchLimits { max_running: Some(2), memory_budget_percent: Some(50), refuse_over_memory_budget: true };
+        assert_eq!(check(&limits, &[2048], 4096, Some(16384)), Ok(()));
+        assert_eq!(check(&limits, &[2048, 2048], 1024, Some(16384)), Err(LaunchLimitError::TooManyRunning { running: 2, limit: 2 }));
+        assert_eq!(check(&limits, &[6144], 4096, Some(16384)),
+            Err(LaunchLimitError::OverMemoryBudget { committed: 10240, budget: 8192, percent: 50 }));
+        // Without knowing the system memory, the budget can't be checked
+        assert_eq!(check(&limits, &[6144], 4096, None), Ok(()));
+    }
+}
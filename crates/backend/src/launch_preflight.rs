@@ -0,0 +1,252 @@
+use std::{collections::HashMap, path::{Path, PathBuf}, sync::Arc};
+
+use auth::{credentials::AuthStageWithData, secret::PlatformSecretStorage};
+use bridge::{instance::InstanceID, message::{LaunchPreflight, PreflightAccount, PreflightJava}};
+use schema::{
+    instance::InstanceConfiguration, java_runtime_component::JavaRuntimeComponentFile, loader::Loader, version::{GameLibraryArtifact, MinecraftVersion}
+};
+use uuid::Uuid;
+
+use crate::{
+    BackendState, asset_languages, launch::{LaunchError, LaunchRuleContext, Launcher}, metadata::items::{
+        AssetsIndexMetadataItem, FabricLaunchMetadataItem, MinecraftVersionManifestMetadataItem, MinecraftVersionMetadataItem
+    }
+};
+
+/// A file the launch needs, with the size it has on disk and the number of bytes downloaded to create it
+struct PlannedFile {
+    path: PathBuf,
+    size: u64,
+    download_size: u64,
+}
+
+#[derive(Default)]
+struct PlannedFiles {
+    cached: usize,
+    missing: usize,
+    download_size: u64,
+    install_size: u64,
+}
+
+/// Compares sizes only, reading every file to hash it would take as long as the launch itself
+fn check_planned_files(files: &[PlannedFile]) -> PlannedFiles {
+    let mut planned = PlannedFiles::default();
+    for file in files {
+        let cached = std::fs::metadata(&file.path).is_ok_and(|metadata| metadata.is_file() && metadata.len() == file.size);
+        if cached {
+            planned.cached += 1;
+        } else {
+            planned.missing += 1;
+            planned.download_size += file.download_size;
+            planned.install_size += file.size;
+        }
+    }
+    planned
+}
+
+impl BackendState {
+    pub async fn launch_preflight(&self, id: InstanceID) -> Result<LaunchPreflight, Arc<str>> {
+        let Some((dot_minecraft, mut configuration)) = self.instance_state.write().instances.get_mut(id)
+            .map(|instance| (instance.dot_minecraft_path.clone(), instance.configuration.get().clone()))
+        else {
+            return Err("Unknown instance".into());
+        };
+        self.apply_global_overrides(&mut configuration);
+
+        let account = self.preflight_account(configuration.account).await;
+
+        let (version_info, runs_loader_installer) = self.plan_launch_version(&configuration).await
+            .map_err(|error| Arc::from(format!("Unable to load version information:\n{error}")))?;
+
+        let mut files = Vec::new();
+
+        let launch_rule_context = LaunchRuleContext {
+            is_demo_user: false,
+            custom_resolution: configuration.resolution.map(|resolution| (resolution.width, resolution.height)),
+            quick_play: None,
+        };
+        let mut artifacts = Vec::new();
+        launch_rule_context.collect_libraries(&version_info.libraries, &mut artifacts, &mut HashMap::new());
+        artifacts.push(GameLibraryArtifact {
+            path: format!("net/minecraft/{0}/minecraft-client-{0}.jar", configuration.minecraft_version).into(),
+            sha1: Some(version_info.downloads.client.sha1),
+            size: Some(version_info.downloads.client.size),
+            url: version_info.downloads.client.url,
+        });
+        for artifact in &artifacts {
+            // Libraries without a size are rare and small, they're counted as missing until they exist
+            let size = artifact.size.map(u64::from).unwrap_or(0);
+            let path = crate::library_store::store_path(&self.directories.libraries_dir, artifact.path.as_str(), artifact.sha1.as_deref(), &self.launcher.verified_files);
+            if artifact.size.is_none() && path.is_file() {
+                continue;
+            }
+            files.push(PlannedFile { path, size, download_size: size });
+        }
+
+        let assets_index = self.meta.fetch(&AssetsIndexMetadataItem {
+            url: version_info.asset_index.url,
+            cache: self.directories.assets_index_dir.join(format!("{}.json", &version_info.assets)).into(),
+            hash: version_info.asset_index.sha1,
+        }).await.map_err(|error| Arc::from(format!("Unable to load the asset index:\n{error}")))?;
+        let assets_index = if self.config.write().get().download_only_selected_language {
+            let selected_language = asset_languages::selected_language(&dot_minecraft);
+            Arc::new(asset_languages::without_unused_languages(&assets_index, selected_language.as_deref()))
+        } else {
+            assets_index
+        };
+        for asset in assets_index.objects.values() {
+            files.push(PlannedFile {
+                path: self.directories.assets_objects_dir.join(&asset.hash[..2]).join(asset.hash.as_str()),
+                size: asset.size as u64,
+                download_size: asset.size as u64,
+            });
+        }
+
+        let (java, java_files) = self.preflight_java(&configuration, &version_info).await;
+        let java_file_count = java_files.len();
+        files.extend(java_files);
+
+        let launcher_dir = self.directories.root_launcher_dir.clone();
+        let (planned, missing_java_files, available_space) = tokio::task::spawn_blocking(move || {
+            let planned = check_planned_files(&files);
+            let missing_java_files = check_planned_files(&files[files.len() - java_file_count..]).missing;
+            (planned, missing_java_files, crate::download_conditions::available_space(&launcher_dir))
+        }).await.unwrap();
+
+        let java = match java {
+            PreflightJava::Mojang { component, .. } => PreflightJava::Mojang { component, missing_files: missing_java_files },
+            java => java,
+        };
+
+        Ok(LaunchPreflight {
+            account,
+            java,
+            cached_files: planned.cached,
+            files_to_download: planned.missing,
+            download_size: planned.download_size,
+            install_size: planned.install_size,
+            available_space,
+            runs_loader_installer,
+        })
+    }
+
+    /// Checks the saved session of the account that would be used, without refreshing it or asking the user to log in
+    async fn preflight_account(&self, account_override: Option<Uuid>) -> PreflightAccount {
+        let (uuid, username) = {
+            let mut account_info = self.account_info.write();
+            let account_info = account_info.get();
+
+            let selected = account_override
+                .filter(|uuid| account_info.accounts.contains_key(uuid))
+                .or(account_info.selected_account);
+            let Some(uuid) = selected else {
+                return PreflightAccount::LoginRequired;
+            };
+            let Some(account) = account_info.accounts.get(&uuid) else {
+                return PreflightAccount::LoginRequired;
+            };
+            if account.offline {
+                return PreflightAccount::Offline { username: account.username.clone() };
+            }
+            (uuid, account.username.clone())
+        };
+
+        let Ok(secret_storage) = self.secret_storage.get_or_init(PlatformSecretStorage::new).await else {
+            return PreflightAccount::LoginRequired;
+        };
+        let Ok(Some(mut credentials)) = secret_storage.read_credentials(uuid).await else {
+            return PreflightAccount::LoginRequired;
+        };
+        match credentials.stage() {
+            AuthStageWithData::AccessToken(_) => PreflightAccount::Ready { username },
+            AuthStageWithData::Initial => PreflightAccount::LoginRequired,
+            _ => PreflightAccount::Refresh { username },
+        }
+    }
+
+    /// Loads the version that would be launched. Forge and NeoForge only know their version once their installer
+    /// has run, so the vanilla version is used for them instead
+    async fn plan_launch_version(&self, configuration: &InstanceConfiguration) -> Result<(Arc<MinecraftVersion>, bool), LaunchError> {
+        let versions = self.meta.fetch(&MinecraftVersionManifestMetadataItem).await?;
+        let Some(version) = versions.versions.iter().find(|v| v.id == configuration.minecraft_version) else {
+            return Err(LaunchError::CantFindVersion(configuration.minecraft_version.as_str()));
+        };
+        let version_info = self.meta.fetch(&MinecraftVersionMetadataItem(version)).await?;
+
+        match configuration.loader {
+            Loader::Vanilla => Ok((version_info, false)),
+            Loader::Fabric => {
+                let loader_version = self.launcher.resolve_fabric_loader_version(configuration).await?;
+                let fabric_launch = self.meta.fetch(&FabricLaunchMetadataItem {
+                    minecraft_version: configuration.minecraft_version,
+                    loader_version,
+                }).await?;
+
+                let mut version_info = (*version_info).clone();
+                version_info.libraries.extend(crate::launch::fabric_libraries(&fabric_launch));
+                Ok((Arc::new(version_info), false))
+            },
+            Loader::Forge | Loader::NeoForge => Ok((version_info, true)),
+            Loader::Unknown => Err(LaunchError::UnknownLoader),
+        }
+    }
+
+    /// Finds the Java binary that would be used, along with the files of the Mojang runtime when that's the one
+    async fn preflight_java(&self, configuration: &InstanceConfiguration, version_info: &MinecraftVersion) -> (PreflightJava, Vec<PlannedFile>) {
+        if let Some(jvm_binary) = &configuration.jvm_binary
+            && jvm_binary.enabled
+            && let Some(path) = &jvm_binary.path
+            && let Some(binary) = Launcher::search_for_java_binary(path)
+        {
+            return (PreflightJava::External { path: binary.into() }, Vec::new());
+        }
+
+        if let Some(external_java) = self.launcher.find_external_java(version_info) {
+            let java = match external_java {
+                Ok(binary) => PreflightJava::External { path: binary.into() },
+                Err(error) => PreflightJava::Unavailable { error: error.to_string().into() },
+            };
+            return (java, Vec::new());
+        }
+
+        let (runtime_dir, runtime) = match self.launcher.peek_mojang_java_runtime(&self.meta, version_info).await {
+            Ok(runtime) => runtime,
+            Err(error) => return (PreflightJava::Unavailable { error: error.to_string().into() }, Vec::new()),
+        };
+
+        let files = runtime.files.iter().filter_map(|(filename, contents)| {
+            let JavaRuntimeComponentFile::File { downloads, .. } = contents else {
+                return None;
+            };
+            Some(PlannedFile {
+                path: runtime_dir.join(filename),
+                size: downloads.raw.size as u64,
+                download_size: downloads.lzma.as_ref().unwrap_or(&downloads.raw).size as u64,
+            })
+        }).collect();
+
+        let component = runtime_dir.parent()
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into())
+            .unwrap_or_else(|| "java-runtime".into());
+
+        (PreflightJava::Mojang { component, missing_files: 0 }, files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_with_the_wrong_size_are_planned_for_download() {
+        let dir = crate::test_harness::TempDir::new();
+        std::fs::write(dir.path().join("cached"), b"12345").unwrap();
+        std::fs::write(dir.path().join("truncated"), b"12").unwrap();
+
+        let file = |name: &str| PlannedFile { path: dir.path().join(name), size: 5, download_size: 3 };
+        let planned = check_planned_files(&[file("cached"), file("truncated"), file("missing")]);
+        assert_eq!((planned.cached, planned.missing), (1, 2));
+        assert_eq!((planned.download_size, planned.install_size), (6, 10));
+    }
+}
@@ -0,0 +1,456 @@
+use std::{collections::HashMap, path::{Path, PathBuf}, sync::Arc};
+
+use bridge::{
+    message::{OfficialLauncherProfile, OfficialLauncherProfiles}, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}
+};
+use schema::{
+    instance::{InstanceConfiguration, InstanceJvmFlagsConfiguration, InstanceMemoryConfiguration, InstanceResolutionConfiguration},
+    launcher_profiles::{LauncherProfile, LauncherProfilesJson, LauncherVersionJson}, loader::Loader, multimc::MmcPack
+};
+use ustr::Ustr;
+
+use crate::{BackendState, metadata::items::MinecraftVersionManifestMetadataItem};
+
+/// Files of the Minecraft Launcher itself in its game folder, left out when copying the folder into an instance
+const OFFICIAL_LAUNCHER_FILES: &[&str] = &[
+    "assets", "bin", "libraries", "runtime", "versions", "webcache2",
+    "launcher_accounts.json", "launcher_accounts_microsoft_store.json", "launcher_cef_log.txt", "launcher_entitlements.json",
+    "launcher_gamer_pics.json", "launcher_log.txt", "launcher_msa_credentials.bin", "launcher_product_state.json",
+    "launcher_profiles.json", "launcher_settings.json", "launcher_skins.json", "launcher_ui_state.json", "treatment_tags.json",
+];
+
+/// The files of the Minecraft Launcher in `game_dir`, if it's the launcher's folder
+pub(crate) fn official_launcher_files(game_dir: &Path) -> Vec<PathBuf> {
+    if game_dir.join("launcher_profiles.json").is_file() {
+        OFFICIAL_LAUNCHER_FILES.iter().map(|file| game_dir.join(file)).collect()
+    } else {
+        Vec::new()
+    }
+}
+
+/// An instance from another launcher, converted to our version and loader model
+#[derive(Debug)]
+pub struct ForeignInstance {
+    pub name: String,
+    pub minecraft_version: Ustr,
+    pub loader: Loader,
+    pub loader_version: Option<Ustr>,
+    /// The folder the other launcher runs the game in, copied into the new instance's .minecraft
+    pub game_dir: PathBuf,
+    pub memory: Option<InstanceMemoryConfiguration>,
+    pub jvm_flags: Option<InstanceJvmFlagsConfiguration>,
+    pub resolution: Option<InstanceResolutionConfiguration>,
+}
+
+impl ForeignInstance {
+    fn configuration(&self) -> InstanceConfiguration {
+        let mut configuration = InstanceConfiguration::new(self.minecraft_version, self.loader);
+        configuration.preferred_loader_version = self.loader_version
+            .map(|version| self.loader.maven_version(&self.minecraft_version, &version).into());
+        configuration.memory = self.memory;
+        configuration.jvm_flags = self.jvm_flags.clone();
+        configuration.resolution = self.resolution;
+        configuration
+    }
+}
+
+/// Parses the `key=value` lines of a MultiMC/Prism `instance.cfg`, ignoring section headers
+fn parse_instance_cfg(text: &str) -> HashMap<&str, &str> {
+    text.lines()
+        .filter(|line| !line.starts_with('['))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect()
+}
+
+fn read_multimc_instance(dir: &Path) -> Result<ForeignInstance, String> {
+    let cfg = std::fs::read_to_string(dir.join("instance.cfg")).map_err(|error| format!("Unable to read instance.cfg: {error}"))?;
+    let cfg = parse_instance_cfg(&cfg);
+
+    let pack = std::fs::read(dir.join("mmc-pack.json")).map_err(|error| format!("Unable to read mmc-pack.json: {error}"))?;
+    let pack: MmcPack = serde_json::from_slice(&pack).map_err(|error| format!("Unable to parse mmc-pack.json: {error}"))?;
+    multimc_instance_from(dir, &cfg, &pack)
+}
+
+fn multimc_instance_from(dir: &Path, cfg: &HashMap<&str, &str>, pack: &MmcPack) -> Result<ForeignInstance, String> {
+    let folder_name = dir.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let name = cfg.get("name").filter(|name| !name.is_empty()).map(|name| name.to_string()).unwrap_or(folder_name);
+
+    let minecraft_version = pack.minecraft_version().ok_or_else(|| format!("{name} doesn't have a Minecraft version"))?;
+    let (loader, loader_version) = pack.loader();
+    if loader == Loader::Unknown {
+        return Err(format!("{name} uses a loader that isn't supported"));
+    }
+
+    let game_dir = [".minecraft", "minecraft"].iter()
+        .map(|folder| dir.join(folder))
+        .find(|path| path.is_dir())
+        .unwrap_or_else(|| dir.join(".minecraft"));
+
+    let is_true = |key: &str| cfg.get(key).is_some_and(|value| value.eq_ignore_ascii_case("true"));
+
+    let memory = if is_true("OverrideMemory") {
+        let min = cfg.get("MinMemAlloc").and_then(|value| value.parse().ok()).unwrap_or(InstanceMemoryConfiguration::DEFAULT_MIN);
+        let max = cfg.get("MaxMemAlloc").and_then(|value| value.parse().ok()).unwrap_or(InstanceMemoryConfiguration::DEFAULT_MAX);
+        Some(InstanceMemoryConfiguration { enabled: true, min: min.min(max), max })
+    } else {
+        None
+    };
+
+    let jvm_flags = cfg.get("JvmArgs")
+        .filter(|flags| is_true("OverrideJavaArgs") && !flags.is_empty())
+        .map(|flags| InstanceJvmFlagsConfiguration { enabled: true, flags: (*flags).into() });
+
+    Ok(ForeignInstance {
+        name,
+        minecraft_version: minecraft_version.into(),
+        loader,
+        loader_version: loader_version.map(Ustr::from),
+        game_dir,
+        memory,
+        jvm_flags,
+        resolution: None,
+    })
+}
+
+/// Finds MultiMC/Prism instances in `path`, which is either a single instance folder or a folder containing them
+pub fn find_multimc_instances(path: &Path) -> Vec<PathBuf> {
+    if path.join("instance.cfg").is_file() {
+        return vec![path.to_path_buf()];
+    }
+    // The launcher's own folder keeps its instances in a subfolder
+    let instances = if path.join("instances").is_dir() { path.join("instances") } else { path.to_path_buf() };
+
+    let Ok(read_dir) = std::fs::read_dir(&instances) else {
+        return Vec::new();
+    };
+    let mut found: Vec<PathBuf> = read_dir.flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join("instance.cfg").is_file() && path.join("mmc-pack.json").is_file())
+        .collect();
+    found.sort();
+    found
+}
+
+/// Where the official launcher keeps its files unless it was moved
+pub fn default_official_minecraft_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        std::env::var_os("APPDATA").map(|appdata| PathBuf::from(appdata).join(".minecraft"))
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library").join("Application Support").join("minecraft"))
+    } else {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".minecraft"))
+    }
+}
+
+fn read_launcher_profiles(minecraft_dir: &Path) -> Result<LauncherProfilesJson, String> {
+    let bytes = std::fs::read(minecraft_dir.join("launcher_profiles.json"))
+        .map_err(|error| format!("Unable to read launcher_profiles.json in {}: {error}", minecraft_dir.display()))?;
+    serde_json::from_slice(&bytes).map_err(|error| format!("Unable to parse launcher_profiles.json: {error}"))
+}
+
+fn official_profile_name(profile: &LauncherProfile) -> Arc<str> {
+    if let Some(name) = profile.name.as_ref().filter(|name| !name.is_empty()) {
+        return name.clone();
+    }
+    match profile.profile_type.as_deref() {
+        Some("latest-release") => "Latest release".into(),
+        Some("latest-snapshot") => "Latest snapshot".into(),
+        _ => profile.last_version_id.clone().unwrap_or_else(|| "Unnamed profile".into()),
+    }
+}
+
+fn official_profile_game_dir(minecraft_dir: &Path, profile: &LauncherProfile) -> PathBuf {
+    profile.game_dir.as_deref().map(Path::to_path_buf).unwrap_or_else(|| minecraft_dir.to_path_buf())
+}
+
+/// Lists the profiles in the official launcher's `launcher_profiles.json`, looking in the default location if no
+/// folder is given
+pub fn list_official_launcher_profiles(minecraft_dir: Option<PathBuf>) -> Result<OfficialLauncherProfiles, String> {
+    let minecraft_dir = minecraft_dir.or_else(default_official_minecraft_dir)
+        .ok_or("Unable to find the Minecraft Launcher's folder")?;
+    let profiles = read_launcher_profiles(&minecraft_dir)?;
+
+    let mut profiles: Vec<OfficialLauncherProfile> = profiles.profiles.iter()
+        .map(|(id, profile)| OfficialLauncherProfile {
+            id: id.clone(),
+            name: official_profile_name(profile),
+            version: profile.last_version_id.clone().or_else(|| profile.profile_type.clone()).unwrap_or_else(|| "latest-release".into()),
+            game_dir: official_profile_game_dir(&minecraft_dir, profile).into(),
+        })
+        .collect();
+    profiles.sort_by(|a, b| lexical_sort::natural_lexical_cmp(&a.name, &b.name));
+
+    Ok(OfficialLauncherProfiles {
+        minecraft_dir: minecraft_dir.into(),
+        profiles: profiles.into(),
+    })
+}
+
+/// Splits a version folder name of the official launcher into the loader, its version and the Minecraft version if
+/// the name contains it, e.g. "fabric-loader-0.16.5-1.21.1" or "1.20.1-forge-47.2.0"
+fn parse_official_version_id(id: &str) -> (Loader, Option<&str>, Option<&str>) {
+    if let Some(rest) = id.strip_prefix("fabric-loader-") {
+        return match rest.split_once('-') {
+            Some((loader_version, minecraft_version)) => (Loader::Fabric, Some(loader_version), Some(minecraft_version)),
+            None => (Loader::Fabric, Some(rest), None),
+        };
+    }
+    if id.starts_with("quilt-loader-") {
+        return (Loader::Unknown, None, None);
+    }
+    if let Some(loader_version) = id.strip_prefix("neoforge-") {
+        return (Loader::NeoForge, Some(loader_version), None);
+    }
+    if let Some((minecraft_version, rest)) = id.split_once("-forge") {
+        // Older installers repeat the Minecraft version, e.g. "1.12.2-forge1.12.2-14.23.5.2859"
+        let loader_version = rest.trim_start_matches('-');
+        let loader_version = loader_version.strip_prefix(minecraft_version)
+            .map(|version| version.trim_start_matches('-'))
+            .unwrap_or(loader_version);
+        return (Loader::Forge, Some(loader_version), Some(minecraft_version));
+    }
+    (Loader::Vanilla, None, Some(id))
+}
+
+/// Splits the official launcher's JVM arguments into heap sizes and the remaining flags
+fn convert_java_args(java_args: &str) -> (Option<InstanceMemoryConfiguration>, Option<InstanceJvmFlagsConfiguration>) {
+    let args = shell_words::split(java_args)
+        .unwrap_or_else(|_| java_args.split_whitespace().map(str::to_string).collect());
+
+    let mut min = None;
+    let mut max = None;
+    let mut flags = Vec::new();
+    for arg in args {
+        if let Some(size) = arg.strip_prefix("-Xmx") {
+            max = crate::launch_limits::parse_size_mib(size).and_then(|mib| mib.try_into().ok());
+        } else if let Some(size) = arg.strip_prefix("-Xms") {
+            min = crate::launch_limits::parse_size_mib(size).and_then(|mib| mib.try_into().ok());
+        } else {
+            flags.push(arg);
+        }
+    }
+
+    let memory = max.map(|max| InstanceMemoryConfiguration {
+        enabled: true,
+        min: min.unwrap_or(InstanceMemoryConfiguration::DEFAULT_MIN).min(max),
+        max,
+    });
+    let jvm_flags = (!flags.is_empty()).then(|| InstanceJvmFlagsConfiguration {
+        enabled: true,
+        flags: shell_words::join(&flags).into(),
+    });
+    (memory, jvm_flags)
+}
+
+impl BackendState {
+    /// Imports every MultiMC/Prism Launcher instance found in `path`, copying its game folder
+    pub async fn import_multimc_instances(&self, path: PathBuf, modal_action: &ModalAction) {
+        let found = {
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || find_multimc_instances(&path)).await.unwrap()
+        };
+        if found.is_empty() {
+            modal_action.set_error_message(format!("No MultiMC or Prism Launcher instances were found in {}", path.display()).into());
+            return;
+        }
+
+        let mut instances = Vec::new();
+        for dir in found {
+            match read_multimc_instance(&dir) {
+                Ok(instance) => instances.push(instance),
+                Err(error) => self.send.send_warning(rust_i18n::t!("notification_import_skipped", path = dir.display(), error = error)),
+            }
+        }
+
+        let mut imported = Vec::new();
+        for instance in instances {
+            if modal_action.has_requested_cancel() {
+                break;
+            }
+            if let Some(name) = self.import_foreign_instance(instance, false, modal_action).await {
+                imported.push(name);
+            }
+        }
+
+        if !imported.is_empty() {
+            self.send.send_success(rust_i18n::t!("notification_imported", names = imported.join(", ")));
+        }
+    }
+
+    /// Creates instances from the official launcher's profiles with the given ids
+    pub async fn import_official_launcher_profiles(&self, minecraft_dir: Arc<Path>, profile_ids: Vec<Arc<str>>, reuse_game_dir: bool,
+        modal_action: &ModalAction
+    ) {
+        let profiles = match read_launcher_profiles(&minecraft_dir) {
+            Ok(profiles) => profiles,
+            Err(error) => {
+                modal_action.set_error_message(error.into());
+                return;
+            },
+        };
+
+        let mut imported = Vec::new();
+        for id in profile_ids {
+            if modal_action.has_requested_cancel() {
+                break;
+            }
+            let Some(profile) = profiles.profiles.get(&id) else {
+                continue;
+            };
+            match self.convert_official_profile(&minecraft_dir, profile).await {
+                Ok(instance) => {
+                    if let Some(name) = self.import_foreign_instance(instance, reuse_game_dir, modal_action).await {
+                        imported.push(name);
+                    }
+                },
+                Err(error) => self.send.send_warning(error),
+            }
+        }
+
+        if !imported.is_empty() {
+            self.send.send_success(rust_i18n::t!("notification_imported", names = imported.join(", ")));
+        }
+    }
+
+    async fn convert_official_profile(&self, minecraft_dir: &Path, profile: &LauncherProfile) -> Result<ForeignInstance, String> {
+        let name = official_profile_name(profile);
+
+        let (loader, loader_version, minecraft_version) = match profile.last_version_id.as_deref() {
+            None | Some("latest-release") | Some("latest-snapshot") => {
+                let manifest = self.meta.fetch(&MinecraftVersionManifestMetadataItem).await
+                    .map_err(|error| format!("Skipped {name}, unable to find the latest version: {error}"))?;
+                let snapshot = profile.profile_type.as_deref() == Some("latest-snapshot")
+                    || profile.last_version_id.as_deref() == Some("latest-snapshot");
+                (Loader::Vanilla, None, if snapshot { manifest.latest.snapshot } else { manifest.latest.release })
+            },
+            Some(version_id) => {
+                let (loader, loader_version, minecraft_version) = parse_official_version_id(version_id);
+                // Modded versions name the Minecraft version they're based on in their version json
+                let inherits_from = std::fs::read(minecraft_dir.join("versions").join(version_id).join(format!("{version_id}.json"))).ok()
+                    .and_then(|bytes| serde_json::from_slice::<LauncherVersionJson>(&bytes).ok())
+                    .and_then(|version| version.inherits_from);
+                let Some(minecraft_version) = inherits_from.as_deref().or(minecraft_version) else {
+                    return Err(format!("Skipped {name}, unable to tell which Minecraft version {version_id} is based on"));
+                };
+                (loader, loader_version.map(Ustr::from), Ustr::from(minecraft_version))
+            },
+        };
+        if loader == Loader::Unknown {
+            return Err(format!("Skipped {name}, it uses a loader that isn't supported"));
+        }
+
+        let (memory, jvm_flags) = profile.java_args.as_deref().map(convert_java_args).unwrap_or_default();
+
+        Ok(ForeignInstance {
+            name: name.to_string(),
+            minecraft_version,
+            loader,
+            loader_version,
+            game_dir: official_profile_game_dir(minecraft_dir, profile),
+            memory,
+            jvm_flags,
+            resolution: profile.resolution.map(|resolution| InstanceResolutionConfiguration {
+                width: resolution.width,
+                height: resolution.height,
+            }),
+        })
+    }
+
+    /// Creates an instance from one found in another launcher, returning the name it was given. With `reuse_game_dir`
+    /// the instance runs in the other launcher's game directory, otherwise that directory is copied
+    pub async fn import_foreign_instance(&self, instance: ForeignInstance, reuse_game_dir: bool, modal_action: &ModalAction) -> Option<String> {
+        let Some(name) = self.unused_instance_name(&instance.name) else {
+            self.send.send_warning(rust_i18n::t!("notification_import_name_used", name = instance.name));
+            return None;
+        };
+
+        tracing::info!("Importing {} from {:?} as {name}", instance.name, instance.game_dir);
+
+        let title = if reuse_game_dir {
+            rust_i18n::t!("progress_importing", name = instance.name)
+        } else {
+            rust_i18n::t!("progress_copying_file", name = instance.name)
+        };
+        let tracker = ProgressTracker::new(title.into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.notify();
+
+        // Work in the temp dir so the instances watcher only sees the finished instance
+        let temp_dir: Arc<Path> = self.directories.temp_dir.join(format!("import-{:016x}", rand::random::<u64>())).into();
+        let mut configuration = instance.configuration();
+        if reuse_game_dir {
+            configuration.game_directory = Some(instance.game_dir.clone().into());
+        }
+        let result = {
+            let temp_dir = temp_dir.clone();
+            tokio::task::spawn_blocking(move || {
+                crate::write_safe(&temp_dir.join("info_v1.json"), &serde_json::to_vec(&configuration)?)?;
+                let dot_minecraft = temp_dir.join(".minecraft");
+                if reuse_game_dir {
+                    Ok(())
+                } else if instance.game_dir.is_dir() {
+                    crate::syncing::copy_dir_skipping(&instance.game_dir, &dot_minecraft, &official_launcher_files(&instance.game_dir))
+                } else {
+                    std::fs::create_dir_all(&dot_minecraft)
+                }
+            }).await.unwrap()
+        };
+
+        let result = result.and_then(|_| {
+            self.file_watching.write().watch_filesystem(self.directories.instances_dir.clone(), crate::backend::WatchTarget::InstancesDir);
+            std::fs::rename(&temp_dir, self.directories.instances_dir.join(&name))
+        });
+
+        tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
+        tracker.notify();
+
+        if let Err(error) = result {
+            _ = std::fs::remove_dir_all(&temp_dir);
+            self.send.send_error(rust_i18n::t!("notification_import_failed", name = name, error = error));
+            return None;
+        }
+
+        Some(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prism_instances_are_converted() {
+        let cfg = parse_instance_cfg("[General]\nname=Fabulously Optimized\nOverrideMemory=true\nMinMemAlloc=1024\nMaxMemAlloc=6144\n\
+            OverrideJavaArgs=false\nJvmArgs=-XX:+UseZGC\n");
+        let pack: MmcPack = serde_json::from_str(r#"{"components": [
+            {"uid": "org.lwjgl3", "version": "3.3.3"},
+            {"uid": "net.minecraft", "version": "1.21.1"},
+            {"uid": "net.fabricmc.intermediary", "version": "1.21.1"},
+            {"uid": "net.fabricmc.fabric-loader", "cachedVersion": "0.16.5"}
+        ], "formatVersion": 1}"#).unwrap();
+
+        let instance = multimc_instance_from(Path::new("/prism/instances/FO"), &cfg, &pack).unwrap();
+        assert_eq!(instance.name, "Fabulously Optimized");
+        assert_eq!(instance.minecraft_version.as_str(), "1.21.1");
+        assert_eq!(instance.loader, Loader::Fabric);
+        assert_eq!(instance.loader_version.map(|version| version.as_str()), Some("0.16.5"));
+        let memory = instance.memory.unwrap();
+        assert_eq!((memory.enabled, memory.min, memory.max), (true, 1024, 6144));
+        assert!(instance.jvm_flags.is_none());
+    }
+
+    #[test]
+    fn official_launcher_versions_and_args_are_converted() {
+        assert_eq!(parse_official_version_id("1.21.1"), (Loader::Vanilla, None, Some("1.21.1")));
+        assert_eq!(parse_official_version_id("fabric-loader-0.16.5-1.21.1"), (Loader::Fabric, Some("0.16.5"), Some("1.21.1")));
+        assert_eq!(parse_official_version_id("1.20.1-forge-47.2.0"), (Loader::Forge, Some("47.2.0"), Some("1.20.1")));
+        assert_eq!(parse_official_version_id("1.12.2-forge1.12.2-14.23.5.2859"), (Loader::Forge, Some("14.23.5.2859"), Some("1.12.2")));
+        assert_eq!(parse_official_version_id("neoforge-21.1.65"), (Loader::NeoForge, Some("21.1.65"), None));
+
+        let (memory, jvm_flags) = convert_java_args("-Xmx4G -XX:+UseG1GC -Xms1024m -Dfoo=\"a b\"");
+        let memory = memory.unwrap();
+        assert_eq!((memory.min, memory.max), (1024, 4096));
+        assert_eq!(&*jvm_flags.unwrap().flags, "-XX:+UseG1GC '-Dfoo=a b'");
+    }
+}
@@ -0,0 +1,35 @@
+use schema::backend_config::LauncherLock;
+use sha2::{Digest, Sha256};
+
+pub fn create(pin: &str) -> LauncherLock {
+    let salt = hex::encode(rand::random::<[u8; 16]>());
+    let pin_sha256 = hash_pin(&salt, pin);
+    LauncherLock {
+        salt: salt.into(),
+        pin_sha256: pin_sha256.into(),
+    }
+}
+
+pub fn verify(lock: &LauncherLock, pin: &str) -> bool {
+    hash_pin(&lock.salt, pin) == *lock.pin_sha256
+}
+
+fn hash_pin(salt: &str, pin: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(pin.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pin_is_verified_against_salted_hash() {
+        let lock = create("1234");
+        assert!(verify(&lock, "1234"));
+        assert!(!verify(&lock, "4321"));
+        assert_ne!(create("1234").pin_sha256, lock.pin_sha256);
+    }
+}
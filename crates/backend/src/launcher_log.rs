@@ -0,0 +1,87 @@
+use std::{io::{Read, Seek, SeekFrom}, path::{Path, PathBuf}, sync::Arc};
+
+/// Name of the log file the launcher writes to, in the launcher directory
+pub const FILE_NAME: &str = "launcher.log";
+
+/// Logs of previous sessions that are kept around as `launcher.log.1` (the most recent) to `launcher.log.4`
+const KEPT_OLD_LOGS: usize = 4;
+
+/// How much of the end of the log is sent to the log viewer
+pub const VIEWER_TAIL_BYTES: u64 = 256 * 1024;
+
+/// Moves the logs of previous sessions up by one, so a new `launcher.log` can be started. The oldest is removed
+pub fn rotate(launcher_dir: &Path) {
+    // Written by earlier versions, which only kept a single old log
+    _ = std::fs::remove_file(launcher_dir.join(format!("{FILE_NAME}.old")));
+
+    for index in (1..KEPT_OLD_LOGS).rev() {
+        let from = old_log_path(launcher_dir, index);
+        if from.exists() {
+            _ = std::fs::rename(from, old_log_path(launcher_dir, index + 1));
+        }
+    }
+
+    let current = launcher_dir.join(FILE_NAME);
+    if current.exists() {
+        _ = std::fs::rename(current, old_log_path(launcher_dir, 1));
+    }
+}
+
+/// Log files that exist in the launcher directory, newest first
+pub fn log_files(launcher_dir: &Path) -> Vec<PathBuf> {
+    std::iter::once(launcher_dir.join(FILE_NAME))
+        .chain((1..=KEPT_OLD_LOGS).map(|index| old_log_path(launcher_dir, index)))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// The last `max_bytes` of the current log, starting at a full line
+pub fn tail(launcher_dir: &Path, max_bytes: u64) -> std::io::Result<Arc<str>> {
+    let mut file = std::fs::File::open(launcher_dir.join(FILE_NAME))?;
+    let length = file.metadata()?.len();
+    let start = length.saturating_sub(max_bytes);
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+
+    let mut text = String::from_utf8_lossy(&bytes);
+    if start > 0 && let Some(newline) = text.find('\n') {
+        text = text[newline + 1..].to_owned().into();
+    }
+    Ok(text.into())
+}
+
+fn old_log_path(launcher_dir: &Path, index: usize) -> PathBuf {
+    launcher_dir.join(format!("{FILE_NAME}.{index}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation_keeps_the_newest_logs() {
+        let dir = crate::test_harness::TempDir::new();
+        let launcher_dir = dir.path();
+
+        for session in 0..6 {
+            rotate(launcher_dir);
+            std::fs::write(launcher_dir.join(FILE_NAME), format!("session {session}")).unwrap();
+        }
+
+        let logs: Vec<String> = log_files(launcher_dir).iter()
+            .map(|path| std::fs::read_to_string(path).unwrap())
+            .collect();
+        assert_eq!(logs, vec!["session 5", "session 4", "session 3", "session 2", "session 1"]);
+    }
+
+    #[test]
+    fn tail_starts_at_a_full_line() {
+        let dir = crate::test_harness::TempDir::new();
+        std::fs::write(dir.path().join(FILE_NAME), "first line\nsecond line\nthird line\n").unwrap();
+
+        assert_eq!(&*tail(dir.path(), 15).unwrap(), "third line\n");
+        assert_eq!(&*tail(dir.path(), 1000).unwrap(), "first line\nsecond line\nthird line\n");
+    }
+}
@@ -9,26 +9,80 @@ use rand::RngCore;
 use serde::Deserialize;
 use sha1::{Digest, Sha1};
 
+rust_i18n::i18n!("locales", fallback = "en");
+
 mod backend_filesystem;
 mod backend_handler;
 
 mod account;
 mod arcfactory;
+mod asset_languages;
+mod clone_instance;
+mod config_snapshots;
+mod content_sources;
+mod crash_report;
+mod diagnostics;
+pub mod data_lock;
 mod directories;
+mod download_conditions;
+mod download_manager;
+mod endpoints;
+mod file_verification;
 mod install_content;
 mod instance;
+mod instance_icons;
+mod instance_export;
+pub mod ipc;
 mod java_manifest;
+mod java_scanner;
+mod lan;
+mod lan_share;
 mod launch;
+mod launch_limits;
+mod launch_preflight;
+mod launcher_import;
+pub mod launcher_log;
+mod launcher_lock;
 mod launch_wrapper;
+mod library_store;
 mod lockfile;
 mod log_reader;
 mod metadata;
+mod metadata_refresh;
+mod metadata_validation;
+mod mod_bisection;
+mod mod_list;
 mod mod_metadata;
+mod modpack;
+mod mods_overview;
+mod network;
+mod offline;
+mod official_files;
+mod options_migration;
+mod pack_watch;
 mod id_slab;
 mod persistent;
+mod playtime;
+mod process;
+mod safe_mode;
+mod screenshots;
 mod shortcut;
+mod skins;
+mod smoke_test;
+mod speedrun_preset;
+mod startup_watch;
+mod storage;
+mod strict_schema;
 mod syncing;
+mod system_info;
+#[cfg(test)]
+mod test_harness;
+mod timeline;
 mod update;
+mod verification_report;
+mod world_preview;
+mod worlds;
+mod zip_writer;
 
 pub(crate) fn is_single_component_path(path: &str) -> bool {
     let path = std::path::Path::new(path);
@@ -65,6 +119,8 @@ pub(crate) fn read_json<T: for <'de> Deserialize<'de>>(path: &Path) -> Result<T,
 }
 
 pub(crate) fn write_safe(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    crate::data_lock::check_writable(path)?;
+
     if let Some(parent) = path.parent() {
         let _ = std::fs::create_dir_all(parent);
     }
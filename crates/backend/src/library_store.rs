@@ -0,0 +1,190 @@
+use std::{collections::BTreeMap, path::{Path, PathBuf}, sync::Arc};
+
+use parking_lot::Mutex;
+use rustc_hash::FxHashSet;
+
+use crate::file_verification::VerifiedFileCache;
+
+/// Where a library is kept in the shared store. Libraries live at their maven path, unless that path already holds a
+/// verified copy with another hash, which happens when versions or loaders ship different builds under the same
+/// coordinates. Those builds are kept next to it in a directory named by their hash, so launching one instance doesn't
+/// replace the jar another instance needs
+pub fn store_path(libraries_dir: &Path, artifact_path: &str, sha1: Option<&str>, verified_files: &VerifiedFileCache) -> PathBuf {
+    let canonical = libraries_dir.join(artifact_path);
+    let Some(sha1) = sha1 else {
+        return canonical;
+    };
+
+    let variant = variant_path(&canonical, sha1);
+    if variant.is_file() {
+        return variant;
+    }
+    match verified_files.recorded_sha1(&canonical) {
+        Some(recorded) if !recorded.eq_ignore_ascii_case(sha1) => variant,
+        _ => canonical,
+    }
+}
+
+fn variant_path(canonical: &Path, sha1: &str) -> PathBuf {
+    let file_name = canonical.file_name().unwrap_or_default();
+    canonical.with_file_name(sha1.to_ascii_lowercase()).join(file_name)
+}
+
+/// Library files used while preparing one launch, including the ones only needed by loader installers
+#[derive(Default)]
+pub struct UsedLibraries(Mutex<Vec<PathBuf>>);
+
+impl UsedLibraries {
+    pub fn add(&self, path: impl Into<PathBuf>) {
+        self.0.lock().push(path.into());
+    }
+
+    pub fn extend(&self, paths: impl IntoIterator<Item = PathBuf>) {
+        self.0.lock().extend(paths);
+    }
+}
+
+/// Library files each instance used on its last launch, by the name of the instance's folder. Libraries that no
+/// installed instance uses are what the cleanup removes
+pub struct LibraryReferences {
+    path: Arc<Path>,
+    libraries_dir: Arc<Path>,
+    instances: Mutex<BTreeMap<String, Vec<PathBuf>>>,
+}
+
+impl LibraryReferences {
+    pub fn load(path: Arc<Path>, libraries_dir: Arc<Path>) -> Self {
+        let instances = crate::read_json(&path).unwrap_or_default();
+        Self {
+            path,
+            libraries_dir,
+            instances: Mutex::new(instances),
+        }
+    }
+
+    pub fn record(&self, instance: &str, used: UsedLibraries) {
+        let mut paths: Vec<PathBuf> = used.0.into_inner().into_iter()
+            .filter_map(|path| path.strip_prefix(&self.libraries_dir).ok().map(Path::to_path_buf))
+            .collect();
+        paths.sort();
+        paths.dedup();
+
+        let mut instances = self.instances.lock();
+        if instances.get(instance) == Some(&paths) {
+            return;
+        }
+        instances.insert(instance.to_string(), paths);
+        let result = serde_json::to_vec(&*instances)
+            .map_err(std::io::Error::from)
+            .and_then(|bytes| crate::write_safe(&self.path, &bytes));
+        if let Err(error) = result {
+            tracing::error!("Unable to save library references: {error}");
+        }
+    }
+}
+
+/// Files in the store that none of `instances` used on their last launch, with their sizes. Fails with the name of
+/// an instance that hasn't been launched since references were recorded, since its libraries aren't known
+pub fn unused_libraries(libraries_dir: &Path, references_path: &Path, instances: &[Arc<str>]) -> Result<Vec<(PathBuf, u64)>, Arc<str>> {
+    let references: BTreeMap<String, Vec<PathBuf>> = crate::read_json(references_path).unwrap_or_default();
+
+    let mut used = FxHashSet::default();
+    for instance in instances {
+        let Some(paths) = references.get(&**instance) else {
+            return Err(instance.clone());
+        };
+        used.extend(paths.iter().map(|path| libraries_dir.join(path)));
+    }
+
+    let mut unused = Vec::new();
+    collect_unused(libraries_dir, &used, &mut unused);
+    Ok(unused)
+}
+
+fn collect_unused(dir: &Path, used: &FxHashSet<PathBuf>, unused: &mut Vec<(PathBuf, u64)>) {
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let path = entry.path();
+        if metadata.is_dir() {
+            collect_unused(&path, used, unused);
+        } else if !used.contains(&path) {
+            unused.push((path, metadata.len()));
+        }
+    }
+}
+
+/// Removes the directories under `dir` that are left empty, but not `dir` itself
+pub fn remove_empty_dirs(dir: &Path) {
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            let path = entry.path();
+            remove_empty_dirs(&path);
+            // Only succeeds when the directory is empty
+            _ = std::fs::remove_dir(&path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sha1::{Digest, Sha1};
+
+    use crate::file_verification::{verify_files, FileToVerify};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn other_builds_under_the_same_coordinates_are_kept_apart() {
+        let dir = crate::test_harness::TempDir::new();
+        let libraries_dir = dir.path().join("libraries");
+        let cache = Arc::new(VerifiedFileCache::load(dir.path().join("verified_files.json").into()));
+
+        let first = hex::encode(Sha1::digest(b"first build"));
+        let second = hex::encode(Sha1::digest(b"second build"));
+        let canonical = libraries_dir.join("com/example/lib/1.0/lib-1.0.jar");
+
+        assert_eq!(store_path(&libraries_dir, "com/example/lib/1.0/lib-1.0.jar", Some(&first), &cache), canonical);
+        std::fs::create_dir_all(canonical.parent().unwrap()).unwrap();
+        std::fs::write(&canonical, b"first build").unwrap();
+
+        // Until the jar is verified it may just be corrupt, so the other build repairs it in place
+        assert_eq!(store_path(&libraries_dir, "com/example/lib/1.0/lib-1.0.jar", Some(&second), &cache), canonical);
+
+        let mut hash = [0; 20];
+        hex::decode_to_slice(&first, &mut hash).unwrap();
+        let file = FileToVerify { path: canonical.clone(), sha1: Some(hash), size: None, progress: 0 };
+        verify_files(vec![file], Some(cache.clone()), None).await;
+
+        let variant = store_path(&libraries_dir, "com/example/lib/1.0/lib-1.0.jar", Some(&second), &cache);
+        assert_eq!(variant, libraries_dir.join(format!("com/example/lib/1.0/{second}/lib-1.0.jar")));
+        assert_eq!(store_path(&libraries_dir, "com/example/lib/1.0/lib-1.0.jar", Some(&first), &cache), canonical);
+        assert_eq!(store_path(&libraries_dir, "com/example/lib/1.0/lib-1.0.jar", None, &cache), canonical);
+    }
+
+    #[test]
+    fn only_libraries_of_installed_instances_are_kept() {
+        let dir = crate::test_harness::TempDir::new();
+        let libraries_dir: Arc<Path> = dir.path().join("libraries").into();
+        let references_path: Arc<Path> = dir.path().join("library_references.json").into();
+        for name in ["shared.jar", "a.jar", "deleted.jar"] {
+            std::fs::create_dir_all(&libraries_dir).unwrap();
+            std::fs::write(libraries_dir.join(name), name).unwrap();
+        }
+
+        let references = LibraryReferences::load(references_path.clone(), libraries_dir.clone());
+        let used = UsedLibraries::default();
+        used.extend([libraries_dir.join("shared.jar"), libraries_dir.join("a.jar")]);
+        references.record("A", used);
+        let used = UsedLibraries::default();
+        used.extend([libraries_dir.join("shared.jar"), libraries_dir.join("deleted.jar")]);
+        references.record("Deleted", used);
+
+        let unused = unused_libraries(&libraries_dir, &references_path, &[Arc::from("A")]).unwrap();
+        assert_eq!(unused, vec![(libraries_dir.join("deleted.jar"), "deleted.jar".len() as u64)]);
+
+        let error = unused_libraries(&libraries_dir, &references_path, &[Arc::from("A"), Arc::from("Never launched")]);
+        assert_eq!(error, Err(Arc::from("Never launched")));
+    }
+}
@@ -14,13 +14,18 @@ use once_cell::sync::Lazy;
 use regex::Regex;
 use thiserror::Error;
 
+use crate::{lan::LanWatch, startup_watch::StartupWatch};
+
 static GAME_OUTPUT_ID: AtomicUsize = AtomicUsize::new(0);
-static REPLACEMENTS: Lazy<[(Regex, &'static str); 7]> = Lazy::new(|| {
+static REPLACEMENTS: Lazy<[(Regex, &'static str); 9]> = Lazy::new(|| {
     [
         // Access token replacements
         (regex::Regex::new(r#"SignedJWT: [^\s]+"#).unwrap(), "SignedJWT: *****"),
         (regex::Regex::new(r#"Session ID is [^\s)]+"#).unwrap(), "Session ID is *****"),
         (regex::Regex::new(r#"--accessToken, [^\s,]+"#).unwrap(), "--accessToken, *****"),
+        (regex::Regex::new(r#"eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+"#).unwrap(), "*****"),
+        // Credentials in URLs, e.g. of a proxy
+        (regex::Regex::new(r#"://[^/\s:@]+:[^/\s@]+@"#).unwrap(), "://*****:*****@"),
         // Computer username replacements
         (regex::Regex::new(r#"\/home\/[^/]+\/"#).unwrap(), "/home/*****/"),
         (regex::Regex::new(r#"\/Users\/[^/]+\/"#).unwrap(), "/Users/*****/"),
@@ -39,7 +44,7 @@ pub fn replace(string: &str) -> Cow<'_, str> {
     replaced
 }
 
-pub fn start_game_output(stdout: ChildStdout, stderr: Option<ChildStderr>, sender: FrontendHandle) {
+pub fn start_game_output(stdout: ChildStdout, stderr: Option<ChildStderr>, sender: FrontendHandle, startup_watch: Arc<StartupWatch>, lan_watch: Arc<LanWatch>) {
     let id = GAME_OUTPUT_ID.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
     let keep_alive = KeepAlive::new();
     let keep_alive_handle = keep_alive.create_handle();
@@ -48,6 +53,8 @@ pub fn start_game_output(stdout: ChildStdout, stderr: Option<ChildStderr>, sende
     if let Some(stderr) = stderr {
         let sender = sender.clone();
         let keep_alive_handle = keep_alive_handle.clone();
+        let startup_watch = startup_watch.clone();
+        let lan_watch = lan_watch.clone();
         std::thread::spawn(move || {
             let mut raw_text = String::new();
             let mut reader = BufReader::new(stderr);
@@ -59,6 +66,8 @@ pub fn start_game_output(stdout: ChildStdout, stderr: Option<ChildStderr>, sende
                         break; // EOF
                     },
                     Ok(_) => {
+                        startup_watch.observe(&raw_text);
+                        lan_watch.observe(&raw_text);
                         let replaced = replace(&*raw_text);
 
                         sender.send(MessageToFrontend::AddGameOutput {
@@ -80,7 +89,9 @@ pub fn start_game_output(stdout: ChildStdout, stderr: Option<ChildStderr>, sende
             stack: Vec::new(),
             id,
             sender: sender.clone(),
-            empty_message: "<empty>".into()
+            empty_message: "<empty>".into(),
+            startup_watch,
+            lan_watch,
         };
         let mut log_input = LogInput {
             buffer: Vec::new(),
@@ -148,6 +159,8 @@ struct LogReader {
     id: usize,
     sender: FrontendHandle,
     empty_message: Arc<str>,
+    startup_watch: Arc<StartupWatch>,
+    lan_watch: Arc<LanWatch>,
 }
 
 struct LogInput {
@@ -904,6 +917,10 @@ impl LogReader {
                 } else {
                     Arc::new([self.empty_message.clone()])
                 };
+                for line in final_lines.iter() {
+                    self.startup_watch.observe(line);
+                    self.lan_watch.observe(line);
+                }
                 self.sender.send(MessageToFrontend::AddGameOutput {
                     id: self.id,
                     time: timestamp.unwrap_or(Utc::now().timestamp_millis()),
@@ -1053,6 +1070,9 @@ impl LogReader {
             return Ok(());
         }
 
+        self.startup_watch.observe(line);
+        self.lan_watch.observe(line);
+
         self.sender.send(MessageToFrontend::AddGameOutput {
             id: self.id,
             time: Utc::now().timestamp_millis(),
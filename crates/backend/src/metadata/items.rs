@@ -4,7 +4,7 @@ use std::{
 
 use reqwest::RequestBuilder;
 use schema::{
-    assets_index::AssetsIndex, fabric_launch::FabricLaunch, fabric_loader_manifest::{FABRIC_LOADER_MANIFEST_URL, FabricLoaderManifest}, forge::{ForgeMavenManifest, NeoforgeMavenManifest, VersionFragment}, java_runtime_component::JavaRuntimeComponentManifest, java_runtimes::{JAVA_RUNTIMES_URL, JavaRuntimes}, maven::MavenMetadataXml, modrinth::{MODRINTH_SEARCH_URL, ModrinthLoader, ModrinthProjectVersion, ModrinthProjectVersionsRequest, ModrinthProjectVersionsResult, ModrinthSearchRequest, ModrinthSearchResult, ModrinthVersionFileUpdateResult}, version::MinecraftVersion, version_manifest::{MOJANG_VERSION_MANIFEST_URL, MinecraftVersionLink, MinecraftVersionManifest}
+    assets_index::AssetsIndex, fabric_launch::FabricLaunch, fabric_loader_manifest::{FABRIC_LOADER_MANIFEST_URL, FabricLoaderManifest}, forge::{ForgeMavenManifest, NeoforgeMavenManifest, VersionFragment}, java_runtime_component::JavaRuntimeComponentManifest, java_runtimes::{JAVA_RUNTIMES_URL, JavaRuntimes}, maven::MavenMetadataXml, modrinth::{MODRINTH_SEARCH_URL, ModrinthLoader, ModrinthProjectVersion, ModrinthProjectVersionsRequest, ModrinthProjectVersionsResult, ModrinthSearchRequest, ModrinthSearchResult, ModrinthVersionFileUpdateResult}, speedrun_mods::{SPEEDRUN_MODS_URL, SpeedrunModsManifest}, version::MinecraftVersion, version_manifest::{MOJANG_VERSION_MANIFEST_URL, MinecraftVersionLink, MinecraftVersionManifest}
 };
 use serde::Serialize;
 use ustr::Ustr;
@@ -20,7 +20,8 @@ pub trait MetadataItem: Debug {
     fn post_process_download(bytes: &[u8]) -> Result<Cow<'_, [u8]>, MetaLoadError> {
         Ok(Cow::Borrowed(bytes))
     }
-    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError>;
+    /// `strict` rejects fields the schema doesn't know about, for the schemas that are checked for them
+    fn deserialize(bytes: &[u8], strict: bool) -> Result<Self::T, MetaLoadError>;
     fn cache_file(&self, _metadata_manager: &MetadataManager) -> Option<impl AsRef<Path> + Send + Sync + 'static> {
         None::<PathBuf>
     }
@@ -51,8 +52,8 @@ impl MetadataItem for MinecraftVersionManifestMetadataItem {
         states.minecraft_version_manifest.clone()
     }
 
-    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
-        Ok(serde_json::from_slice(bytes)?)
+    fn deserialize(bytes: &[u8], strict: bool) -> Result<Self::T, MetaLoadError> {
+        Ok(crate::strict_schema::from_slice(bytes, strict)?)
     }
 }
 
@@ -78,8 +79,8 @@ impl MetadataItem for MojangJavaRuntimesMetadataItem {
         states.mojang_java_runtimes.clone()
     }
 
-    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
-        Ok(serde_json::from_slice(bytes)?)
+    fn deserialize(bytes: &[u8], strict: bool) -> Result<Self::T, MetaLoadError> {
+        Ok(crate::strict_schema::from_slice(bytes, strict)?)
     }
 }
 
@@ -105,7 +106,7 @@ impl<'v> MetadataItem for MinecraftVersionMetadataItem<'v> {
         if !crate::is_single_component_path(&self.0.sha1) {
             panic!("Invalid sha1 {}, possible directory traversal attack?", self.0.sha1);
         }
-        let mut path = metadata_manager.metadata_cache.join("version_info");
+        let mut path = metadata_manager.version_info_cache_dir();
         path.push(self.0.sha1.as_str());
         Some(path)
     }
@@ -114,8 +115,8 @@ impl<'v> MetadataItem for MinecraftVersionMetadataItem<'v> {
         states.version_info.entry(self.0.url).or_default().clone()
     }
 
-    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
-        Ok(serde_json::from_slice(bytes)?)
+    fn deserialize(bytes: &[u8], strict: bool) -> Result<Self::T, MetaLoadError> {
+        Ok(crate::strict_schema::from_slice(bytes, strict)?)
     }
 }
 
@@ -149,15 +150,16 @@ impl MetadataItem for AssetsIndexMetadataItem {
         Some(self.hash)
     }
 
-    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
-        Ok(serde_json::from_slice(bytes)?)
+    fn deserialize(bytes: &[u8], strict: bool) -> Result<Self::T, MetaLoadError> {
+        Ok(crate::strict_schema::from_slice(bytes, strict)?)
     }
 }
 
 #[derive(Debug)]
 pub struct MojangJavaRuntimeComponentMetadataItem {
     pub url: Ustr,
-    pub cache: Arc<Path>,
+    /// None keeps the manifest in memory only, for when the runtime's folder shouldn't be created
+    pub cache: Option<Arc<Path>>,
     pub hash: Ustr,
 }
 
@@ -177,15 +179,15 @@ impl MetadataItem for MojangJavaRuntimeComponentMetadataItem {
     }
 
     fn cache_file(&self, _: &MetadataManager) -> Option<impl AsRef<Path> + Send + Sync + 'static> {
-        Some(Arc::clone(&self.cache))
+        self.cache.clone()
     }
 
     fn data_hash(&self) -> Option<Ustr> {
         Some(self.hash)
     }
 
-    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
-        Ok(serde_json::from_slice(bytes)?)
+    fn deserialize(bytes: &[u8], strict: bool) -> Result<Self::T, MetaLoadError> {
+        Ok(crate::strict_schema::from_slice(bytes, strict)?)
     }
 }
 
@@ -211,7 +213,34 @@ impl MetadataItem for FabricLoaderManifestMetadataItem {
         states.fabric_loader_manifest.clone()
     }
 
-    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
+    fn deserialize(bytes: &[u8], strict: bool) -> Result<Self::T, MetaLoadError> {
+        Ok(crate::strict_schema::from_slice(bytes, strict)?)
+    }
+}
+
+#[derive(Debug)]
+pub struct SpeedrunModsMetadataItem;
+
+impl MetadataItem for SpeedrunModsMetadataItem {
+    type T = SpeedrunModsManifest;
+
+    fn request(&self, client: &reqwest::Client) -> RequestBuilder {
+        client.get(SPEEDRUN_MODS_URL)
+    }
+
+    fn expires(&self) -> bool {
+        true
+    }
+
+    fn cache_file(&self, metadata_manager: &MetadataManager) -> Option<impl AsRef<Path> + Send + Sync + 'static> {
+        Some(Arc::clone(&metadata_manager.speedrun_mods_cache))
+    }
+
+    fn state(&self, states: &mut MetadataManagerStates) -> MetaLoadStateWrapper<Self::T> {
+        states.speedrun_mods.clone()
+    }
+
+    fn deserialize(bytes: &[u8], _strict: bool) -> Result<Self::T, MetaLoadError> {
         Ok(serde_json::from_slice(bytes)?)
     }
 }
@@ -245,8 +274,8 @@ impl MetadataItem for FabricLaunchMetadataItem {
         states.fabric_launch.entry(key).or_default().clone()
     }
 
-    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
-        Ok(serde_json::from_slice(bytes)?)
+    fn deserialize(bytes: &[u8], strict: bool) -> Result<Self::T, MetaLoadError> {
+        Ok(crate::strict_schema::from_slice(bytes, strict)?)
     }
 }
 
@@ -268,7 +297,7 @@ impl<'a> MetadataItem for ModrinthSearchMetadataItem<'a> {
         states.modrinth_search.entry(self.0.clone()).or_default().clone()
     }
 
-    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
+    fn deserialize(bytes: &[u8], _strict: bool) -> Result<Self::T, MetaLoadError> {
         Ok(serde_json::from_slice(bytes)?)
     }
 }
@@ -299,7 +328,7 @@ impl<'a> MetadataItem for ModrinthProjectVersionsMetadataItem<'a> {
         states.modrinth_project_versions.entry(self.0.clone()).or_default().clone()
     }
 
-    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
+    fn deserialize(bytes: &[u8], _strict: bool) -> Result<Self::T, MetaLoadError> {
         Ok(serde_json::from_slice(bytes)?)
     }
 }
@@ -323,7 +352,7 @@ impl MetadataItem for ModrinthVersionMetadataItem {
         states.modrinth_versions.entry(self.0.clone()).or_default().clone()
     }
 
-    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
+    fn deserialize(bytes: &[u8], _strict: bool) -> Result<Self::T, MetaLoadError> {
         Ok(serde_json::from_slice(bytes)?)
     }
 }
@@ -356,7 +385,7 @@ impl MetadataItem for ModrinthVersionUpdateMetadataItem {
         states.modrinth_version_updates.entry(self.sha1.clone()).or_default().clone()
     }
 
-    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
+    fn deserialize(bytes: &[u8], _strict: bool) -> Result<Self::T, MetaLoadError> {
         Ok(serde_json::from_slice(bytes)?)
     }
 }
@@ -395,7 +424,7 @@ impl MetadataItem for ModrinthV3VersionUpdateMetadataItem {
         states.modrinth_version_updates.entry(self.sha1.clone()).or_default().clone()
     }
 
-    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
+    fn deserialize(bytes: &[u8], _strict: bool) -> Result<Self::T, MetaLoadError> {
         Ok(serde_json::from_slice(bytes)?)
     }
 }
@@ -422,7 +451,7 @@ impl MetadataItem for NeoforgeInstallerMavenMetadataItem {
         states.neoforge_installer_maven_manifest.clone()
     }
 
-    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
+    fn deserialize(bytes: &[u8], _strict: bool) -> Result<Self::T, MetaLoadError> {
         let maven = serde_xml_rs::from_reader::<MavenMetadataXml, _>(bytes)?;
 
         let mut versions: Vec<Ustr> = maven.versioning.versions.version.iter().cloned().collect();
@@ -457,7 +486,7 @@ impl MetadataItem for ForgeInstallerMavenMetadataItem {
         states.forge_installer_maven_manifest.clone()
     }
 
-    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
+    fn deserialize(bytes: &[u8], _strict: bool) -> Result<Self::T, MetaLoadError> {
         let maven = serde_xml_rs::from_reader::<MavenMetadataXml, _>(bytes)?;
 
         let mut versions: Vec<Ustr> = maven.versioning.versions.version.iter().cloned().collect();
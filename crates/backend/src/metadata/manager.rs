@@ -1,11 +1,11 @@
 use std::{
-    collections::{HashMap, VecDeque}, fmt::Display, path::Path, sync::Arc, time::{Duration, Instant}
+    borrow::Cow, collections::{HashMap, VecDeque}, fmt::Display, path::{Path, PathBuf}, sync::{atomic::{AtomicBool, Ordering}, Arc}, time::{Duration, Instant}
 };
 
 use bridge::keep_alive::{KeepAlive, KeepAliveHandle};
 use reqwest::StatusCode;
 use schema::{
-    assets_index::AssetsIndex, fabric_launch::FabricLaunch, fabric_loader_manifest::FabricLoaderManifest, forge::{ForgeMavenManifest, NeoforgeMavenManifest}, java_runtime_component::JavaRuntimeComponentManifest, java_runtimes::JavaRuntimes, maven::MavenMetadataXml, modrinth::{ModrinthProjectVersion, ModrinthProjectVersionsRequest, ModrinthProjectVersionsResult, ModrinthSearchRequest, ModrinthSearchResult, ModrinthVersionFileUpdateResult}, version::MinecraftVersion, version_manifest::MinecraftVersionManifest
+    assets_index::AssetsIndex, fabric_launch::FabricLaunch, fabric_loader_manifest::FabricLoaderManifest, forge::{ForgeMavenManifest, NeoforgeMavenManifest}, java_runtime_component::JavaRuntimeComponentManifest, java_runtimes::JavaRuntimes, maven::MavenMetadataXml, modrinth::{ModrinthProjectVersion, ModrinthProjectVersionsRequest, ModrinthProjectVersionsResult, ModrinthSearchRequest, ModrinthSearchResult, ModrinthVersionFileUpdateResult}, speedrun_mods::SpeedrunModsManifest, version::MinecraftVersion, version_manifest::MinecraftVersionManifest
 };
 use serde::Deserialize;
 use sha1::{Digest, Sha1};
@@ -25,6 +25,7 @@ pub struct MetadataManagerStates {
     pub(super) fabric_loader_manifest: MetaLoadStateWrapper<FabricLoaderManifest>,
     pub(super) neoforge_installer_maven_manifest: MetaLoadStateWrapper<NeoforgeMavenManifest>,
     pub(super) forge_installer_maven_manifest: MetaLoadStateWrapper<ForgeMavenManifest>,
+    pub(super) speedrun_mods: MetaLoadStateWrapper<SpeedrunModsManifest>,
     pub(super) fabric_launch: HashMap<(Ustr, Ustr), MetaLoadStateWrapper<FabricLaunch>>,
     pub(super) version_info: HashMap<Ustr, MetaLoadStateWrapper<MinecraftVersion>>,
     pub(super) assets_index: HashMap<Ustr, MetaLoadStateWrapper<AssetsIndex>>,
@@ -44,10 +45,12 @@ pub struct MetadataManager {
     pub(super) fabric_loader_manifest_cache: Arc<Path>,
     pub(super) neoforge_installer_maven_cache: Arc<Path>,
     pub(super) forge_installer_maven_cache: Arc<Path>,
+    pub(super) speedrun_mods_cache: Arc<Path>,
 
     expiring: tokio::sync::Mutex<VecDeque<(Instant, KeepAlive)>>,
 
     http_client: reqwest::Client,
+    strict_schema: AtomicBool,
 }
 
 #[derive(thiserror::Error, Clone, Debug)]
@@ -60,6 +63,7 @@ pub enum MetaLoadError {
     Error(Arc<str>),
     ErrorWithDescription(Arc<str>, Arc<str>),
     NonOK(u16),
+    Offline,
 }
 
 impl Display for MetaLoadError {
@@ -107,6 +111,9 @@ impl Display for MetaLoadError {
                 f.write_fmt(format_args!("Non-OK response: {}", *status_code))
             }
             Self::TokioJoin(error) => f.debug_tuple("TokioJoin").field(error).finish(),
+            Self::Offline => {
+                f.write_str("You're offline and this hasn't been downloaded before")
+            }
         }
     }
 }
@@ -154,14 +161,33 @@ impl MetadataManager {
             fabric_loader_manifest_cache: directory.join("fabric_loader_manifest.json").into(),
             neoforge_installer_maven_cache: directory.join("neoforge_installer_maven.xml").into(),
             forge_installer_maven_cache: directory.join("forge_installer_maven.xml").into(),
+            speedrun_mods_cache: directory.join("speedrun_mods.json").into(),
             metadata_cache: directory,
 
             expiring: Default::default(),
 
             http_client,
+            strict_schema: AtomicBool::new(crate::strict_schema::forced()),
         }
     }
 
+    /// Whether metadata with fields unknown to the schemas fails to load, which applies to what's loaded next
+    pub fn set_strict_schema(&self, strict: bool) {
+        self.strict_schema.store(strict || crate::strict_schema::forced(), Ordering::Relaxed);
+    }
+
+    pub fn is_strict_schema(&self) -> bool {
+        self.strict_schema.load(Ordering::Relaxed)
+    }
+
+    pub fn version_manifest_cache_path(&self) -> &Path {
+        &self.version_manifest_cache
+    }
+
+    pub fn version_info_cache_dir(&self) -> PathBuf {
+        self.metadata_cache.join("version_info")
+    }
+
     pub async fn expire(&self) {
         let now = Instant::now();
 
@@ -194,6 +220,7 @@ impl MetadataManager {
                 item,
                 cache_file,
                 &self.http_client,
+                self.is_strict_schema(),
             );
         }
     }
@@ -221,6 +248,7 @@ impl MetadataManager {
                 item,
                 cache_file,
                 &self.http_client,
+                self.is_strict_schema(),
             );
         }
 
@@ -255,10 +283,12 @@ impl MetadataManager {
         item: &I,
         cache_file: Option<impl AsRef<Path> + Send + Sync + 'static>,
         http_client: &reqwest::Client,
+        strict: bool,
     ) {
-        log::debug!("Loading metadata {:?}", item);
+        tracing::debug!("Loading metadata {:?}", item);
 
         let request = item.request(http_client);
+        let http_client = http_client.clone();
         let expected_hash = item.data_hash().and_then(|sha1| {
             let mut expected_hash = [0u8; 20];
             hex::decode_to_slice(sha1.as_str(), &mut expected_hash).ok()?;
@@ -285,17 +315,17 @@ impl MetadataManager {
                     };
 
                     if !correct_hash {
-                        log::info!("Sha1 mismatch for {:?}, downloading file again...", cache_file);
+                        tracing::info!("Sha1 mismatch for {:?}, downloading file again...", cache_file);
                         return None;
                     }
 
-                    let result = I::deserialize(&file);
+                    let result = I::deserialize(&file, strict);
                     match result {
                         Ok(meta) => {
                             Some(meta)
                         },
                         Err(error) => {
-                            log::warn!("Error parsing cached metadata file for {:?}, downloading file again... {}", cache_file, error);
+                            tracing::warn!("Error parsing cached metadata file for {:?}, downloading file again... {}", cache_file, error);
                             None
                         },
                     }
@@ -309,8 +339,15 @@ impl MetadataManager {
                 }
             }
 
+            // Requests would only run into their timeout, so whatever was cached before is used as-is
+            if crate::offline::is_offline() {
+                return file_fallback.ok_or(MetaLoadError::Offline);
+            }
+
             let mut result: Result<Arc<I::T>, MetaLoadError> = async move {
-                let response = request.send().await?;
+                let response = send_through_mirror(&http_client, request).await;
+                crate::offline::report_request(&response);
+                let response = response?;
 
                 let status = response.status();
                 if status != StatusCode::OK {
@@ -339,7 +376,7 @@ impl MetadataManager {
 
                 // We try to decode before checking the hash because it's a more
                 // useful error message to know that the content is invalid
-                let meta: I::T = I::deserialize(&bytes)?;
+                let meta: I::T = I::deserialize(&bytes, strict)?;
 
                 let correct_hash = if let Some(expected_hash) = &expected_hash {
                     let mut hasher = Sha1::new();
@@ -355,7 +392,7 @@ impl MetadataManager {
                     return Err(MetaLoadError::InvalidHash);
                 }
 
-                if let Some(cache_file) = &cache_file {
+                if let Some(cache_file) = &cache_file && crate::data_lock::check_writable(cache_file.as_ref()).is_ok() {
                     if let Some(parent) = cache_file.as_ref().parent() {
                         let _ = tokio::fs::create_dir_all(parent).await;
                     }
@@ -368,13 +405,13 @@ impl MetadataManager {
 
             if let Err(error) = &result {
                 if let Some(file_fallback) = file_fallback {
-                    log::warn!(
+                    tracing::warn!(
                         "Error while fetching metadata {:?}, using file fallback: {error:?}",
                         std::any::type_name::<I::T>()
                     );
                     result = Ok(file_fallback);
                 } else {
-                    log::error!("Error while fetching metadata {:?}: {error:?}", std::any::type_name::<I::T>());
+                    tracing::error!("Error while fetching metadata {:?}: {error:?}", std::any::type_name::<I::T>());
                 }
             }
 
@@ -384,3 +421,29 @@ impl MetadataManager {
         *state = MetaLoadState::Pending(join_handle);
     }
 }
+
+/// Sends a request for Mojang metadata to the configured mirror, going to Mojang's servers instead when the mirror
+/// can't be reached or doesn't have the file
+async fn send_through_mirror(http_client: &reqwest::Client, request: reqwest::RequestBuilder) -> reqwest::Result<reqwest::Response> {
+    let request = request.build()?;
+    let mirror_url = match crate::endpoints::resolve(request.url().as_str()) {
+        Cow::Owned(mirror_url) => reqwest::Url::parse(&mirror_url).ok(),
+        Cow::Borrowed(_) => None,
+    };
+    let Some((mirror_url, mut mirror_request)) = mirror_url.zip(request.try_clone()) else {
+        return http_client.execute(request).await;
+    };
+
+    *mirror_request.url_mut() = mirror_url;
+    match http_client.execute(mirror_request).await {
+        Ok(response) if response.status().is_success() => Ok(response),
+        Ok(response) => {
+            tracing::warn!("Mirror responded with {} for {}, falling back to {}", response.status(), response.url(), request.url());
+            http_client.execute(request).await
+        },
+        Err(error) => {
+            tracing::warn!("Mirror request failed, falling back to {}: {error}", request.url());
+            http_client.execute(request).await
+        },
+    }
+}
@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use bridge::{message::MessageToFrontend, meta::{MetadataRequest, MetadataResult}};
+use enumset::EnumSet;
+use schema::{loader::Loader, version_manifest::{MinecraftVersionManifest, MinecraftVersionType}};
+use ustr::Ustr;
+
+use crate::{BackendState, metadata::items::{FabricLoaderManifestMetadataItem, ForgeInstallerMavenMetadataItem, MinecraftVersionManifestMetadataItem, NeoforgeInstallerMavenMetadataItem}};
+
+/// How often the configured refresh interval is looked at, so changing it takes effect without a restart
+const TICK: Duration = Duration::from_secs(60);
+
+impl BackendState {
+    /// Refreshes the version manifest and the manifests of the loaders instances use on the configured interval,
+    /// sending the new data to the frontend and announcing Minecraft versions that weren't known before
+    pub async fn refresh_metadata_periodically(self) {
+        let mut interval = tokio::time::interval(TICK);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut since_refresh = Duration::ZERO;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {},
+                _ = self.shutdown.cancelled() => return,
+            }
+
+            since_refresh += interval.period();
+            let refresh_interval = self.config.write().get().metadata_refresh_interval.duration();
+            let Some(refresh_interval) = refresh_interval else {
+                since_refresh = Duration::ZERO;
+                continue;
+            };
+            if since_refresh < refresh_interval || crate::offline::is_offline() {
+                continue;
+            }
+            since_refresh = Duration::ZERO;
+
+            self.refresh_metadata().await;
+        }
+    }
+
+    async fn refresh_metadata(&self) {
+        tracing::info!("Refreshing metadata in the background");
+        let modal_action = self.send.start_background_task(rust_i18n::t!("progress_refreshing_metadata"));
+
+        let (result, keep_alive_handle) = self.meta.fetch_with_keepalive(&MinecraftVersionManifestMetadataItem, true).await;
+        match &result {
+            Ok(manifest) => self.announce_new_versions(manifest),
+            Err(error) => tracing::warn!("Unable to refresh the version manifest: {error}"),
+        }
+        self.send.send(MessageToFrontend::MetadataResult {
+            request: MetadataRequest::MinecraftVersionManifest,
+            result: result.map(MetadataResult::MinecraftVersionManifest).map_err(|error| error.to_string().into()),
+            keep_alive_handle,
+        });
+
+        let loaders: EnumSet<Loader> = self.instance_state.write().instances.iter_mut()
+            .map(|instance| instance.configuration.get().loader)
+            .collect();
+
+        for loader in loaders {
+            if modal_action.has_requested_cancel() {
+                return;
+            }
+            let (request, (result, keep_alive_handle)) = match loader {
+                Loader::Fabric => {
+                    let (result, handle) = self.meta.fetch_with_keepalive(&FabricLoaderManifestMetadataItem, true).await;
+                    (MetadataRequest::FabricLoaderManifest, (result.map(MetadataResult::FabricLoaderManifest), handle))
+                },
+                Loader::Forge => {
+                    let (result, handle) = self.meta.fetch_with_keepalive(&ForgeInstallerMavenMetadataItem, true).await;
+                    (MetadataRequest::ForgeMavenManifest, (result.map(MetadataResult::ForgeMavenManifest), handle))
+                },
+                Loader::NeoForge => {
+                    let (result, handle) = self.meta.fetch_with_keepalive(&NeoforgeInstallerMavenMetadataItem, true).await;
+                    (MetadataRequest::NeoforgeMavenManifest, (result.map(MetadataResult::NeoforgeMavenManifest), handle))
+                },
+                Loader::Vanilla | Loader::Unknown => continue,
+            };
+            if let Err(error) = &result {
+                tracing::warn!("Unable to refresh the {:?} manifest: {error}", loader);
+            }
+            self.send.send(MessageToFrontend::MetadataResult {
+                request,
+                result: result.map_err(|error| error.to_string().into()),
+                keep_alive_handle,
+            });
+        }
+    }
+
+    /// Announces the versions above the one seen by the previous refresh. The first refresh only remembers the
+    /// newest version, since every version would look new without one
+    fn announce_new_versions(&self, manifest: &MinecraftVersionManifest) {
+        let Some(newest) = manifest.versions.first().map(|version| version.id) else {
+            return;
+        };
+        let announced = self.config.write().get().announced_minecraft_version.clone();
+        if announced.as_deref() == Some(newest.as_str()) {
+            return;
+        }
+        self.config.write().modify(|config| {
+            config.announced_minecraft_version = Some(newest.as_str().into());
+        });
+        let Some(announced) = announced else {
+            return;
+        };
+
+        let text = match new_versions(&announced, manifest).as_slice() {
+            [] => return,
+            [version] => rust_i18n::t!("notification_minecraft_version_out", version = version).into_owned(),
+            versions => {
+                let versions = versions.iter().map(Ustr::as_str).collect::<Vec<_>>().join(", ");
+                rust_i18n::t!("notification_minecraft_versions_out", versions = versions).into_owned()
+            },
+        };
+        tracing::info!("{text}");
+
+        if self.config.write().get().new_version_desktop_notification {
+            self.send.send(MessageToFrontend::DesktopNotification {
+                title: "Pandora".into(),
+                body: text.as_str().into(),
+            });
+        }
+        self.send.send_info(text);
+    }
+}
+
+/// Releases and snapshots listed above `announced` in `manifest`, newest first. Nothing is new when `announced` is
+/// missing from the manifest, rather than every version
+fn new_versions(announced: &str, manifest: &MinecraftVersionManifest) -> Vec<Ustr> {
+    if !manifest.versions.iter().any(|version| version.id.as_str() == announced) {
+        return Vec::new();
+    }
+    manifest.versions.iter()
+        .take_while(|version| version.id.as_str() != announced)
+        .filter(|version| matches!(version.r#type, MinecraftVersionType::Release | MinecraftVersionType::Snapshot))
+        .map(|version| version.id)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(versions: &[(&str, &str)]) -> MinecraftVersionManifest {
+        serde_json::from_value(serde_json::json!({
+            "latest": { "release": versions[0].0, "snapshot": versions[0].0 },
+            "versions": versions.iter().map(|(id, kind)| serde_json::json!({
+                "id": id,
+                "type": kind,
+                "url": format!("https://piston-meta.mojang.com/{id}.json"),
+                "time": "2025-01-01T00:00:00+00:00",
+                "releaseTime": "2025-01-01T00:00:00+00:00",
+                "sha1": "0000000000000000000000000000000000000000",
+                "complianceLevel": 1,
+            })).collect::<Vec<_>>(),
+        })).unwrap()
+    }
+
+    #[test]
+    fn only_releases_and_snapshots_above_the_announced_version_are_new() {
+        let manifest = manifest(&[
+            ("25w03a", "snapshot"),
+            ("b1.8", "old_beta"),
+            ("1.21.5", "release"),
+            ("1.21.4", "release"),
+            ("25w02a", "snapshot"),
+        ]);
+        assert_eq!(new_versions("1.21.4", &manifest), vec![Ustr::from("25w03a"), Ustr::from("1.21.5")]);
+        assert!(new_versions("25w03a", &manifest).is_empty());
+        assert!(new_versions("unknown", &manifest).is_empty());
+    }
+}
@@ -0,0 +1,267 @@
+use std::{collections::BTreeMap, path::{Path, PathBuf}, sync::Arc};
+
+use bridge::modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType};
+use schema::version::{LaunchArgument, LaunchArgumentValue, MinecraftVersion};
+use serde::Serialize;
+
+use crate::{
+    BackendState, launch::ArgumentExpansionKey, metadata::{
+        items::{AssetsIndexMetadataItem, MinecraftVersionManifestMetadataItem, MinecraftVersionMetadataItem, MojangJavaRuntimeComponentMetadataItem, MojangJavaRuntimesMetadataItem},
+        manager::MetaLoadError,
+    }
+};
+
+/// Executables the launcher knows how to start a Java runtime from
+const KNOWN_JAVA_EXECUTABLES: &[&str] = &["bin/java", "bin/javaw.exe", "jre.bundle/Contents/Home/bin/java", "MinecraftJava.exe"];
+
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataProblemKind {
+    VersionManifest,
+    Version,
+    AssetsIndex,
+    Argument,
+    JavaRuntimes,
+    JavaRuntime,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MetadataProblem {
+    pub kind: MetadataProblemKind,
+    /// Version id or runtime component the problem was found in
+    pub id: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unknown_field: Option<String>,
+}
+
+/// Result of checking every piece of metadata against the schemas, written as JSON for contributors
+#[derive(Serialize, Debug, Default)]
+pub struct MetadataValidationReport {
+    /// Whether unknown fields are rejected, which is the case when the strict schema setting is enabled
+    pub strict: bool,
+    pub checked: usize,
+    pub problems: Vec<MetadataProblem>,
+    /// Unknown fields that were rejected and the ids of the documents containing them. Only the first unknown field
+    /// of each document is found, since deserialization stops there
+    pub unknown_fields: BTreeMap<String, Vec<String>>,
+    /// Unknown fields ignored since the launcher started, by JSON path, with the number of documents containing them
+    pub ignored_fields: BTreeMap<String, usize>,
+}
+
+impl MetadataValidationReport {
+    fn add(&mut self, kind: MetadataProblemKind, id: impl Into<String>, message: String) {
+        let id = id.into();
+        let unknown_field = unknown_field(&message).map(str::to_string);
+        if let Some(field) = &unknown_field {
+            self.unknown_fields.entry(field.clone()).or_default().push(id.clone());
+        }
+        self.problems.push(MetadataProblem { kind, id, message, unknown_field });
+    }
+
+    fn add_error(&mut self, kind: MetadataProblemKind, id: impl Into<String>, error: MetaLoadError) {
+        // MetaLoadError's message hides serde's description, which names the offending field
+        let message = match error {
+            MetaLoadError::SerdeJson(error) => serde_json::Error::to_string(&error),
+            error => error.to_string(),
+        };
+        self.add(kind, id, message);
+    }
+}
+
+/// Extracts the field name from serde's "unknown field `name`, expected ..." message
+fn unknown_field(message: &str) -> Option<&str> {
+    let start = message.find("unknown field `")? + "unknown field `".len();
+    let length = message[start..].find('`')?;
+    Some(&message[start..start + length])
+}
+
+/// Returns the `${...}` keys in the argument that the launcher doesn't know how to expand
+fn unsupported_expansions(argument: &str) -> Vec<&str> {
+    let mut unsupported = Vec::new();
+    let mut remaining = argument;
+    while let Some(start) = remaining.find("${") {
+        let Some(end) = remaining[start..].find('}') else {
+            break;
+        };
+        let key = &remaining[start + 2..start + end];
+        if ArgumentExpansionKey::from_str(key).is_none() {
+            unsupported.push(key);
+        }
+        remaining = &remaining[start + end + 1..];
+    }
+    unsupported
+}
+
+fn version_arguments(version: &MinecraftVersion) -> Vec<&str> {
+    let mut arguments = Vec::new();
+    if let Some(launch_arguments) = &version.arguments {
+        for argument in launch_arguments.game.iter().chain(launch_arguments.jvm.iter()) {
+            let value = match argument {
+                LaunchArgument::Single(value) => value,
+                LaunchArgument::Ruled(ruled) => &ruled.value,
+            };
+            match value {
+                LaunchArgumentValue::Single(argument) => arguments.push(argument.as_str()),
+                LaunchArgumentValue::Multiple(values) => arguments.extend(values.iter().map(|argument| argument.as_str())),
+            }
+        }
+    } else if let Some(legacy_arguments) = &version.minecraft_arguments {
+        arguments.extend(legacy_arguments.split_ascii_whitespace());
+    }
+    arguments
+}
+
+impl BackendState {
+    /// Downloads every version, assets index and Java runtime manifest, collecting anything that doesn't match the
+    /// schemas or that the launcher wouldn't be able to use into a report written to `report_path`
+    pub async fn validate_all_metadata(&self, report_path: PathBuf, modal_action: &ModalAction) {
+        let mut report = MetadataValidationReport {
+            strict: self.meta.is_strict_schema(),
+            ..Default::default()
+        };
+
+        let tracker = ProgressTracker::new(rust_i18n::t!("progress_validating_metadata").into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.notify();
+
+        match self.meta.fetch(&MinecraftVersionManifestMetadataItem).await {
+            Ok(versions) => {
+                tracker.set_total(versions.versions.len() as u64 + 1);
+                tracker.notify();
+
+                for link in &versions.versions {
+                    if modal_action.has_requested_cancel() {
+                        break;
+                    }
+                    report.checked += 1;
+                    match self.meta.fetch(&MinecraftVersionMetadataItem(link)).await {
+                        Ok(version) => {
+                            self.validate_version(link.id.as_str(), &version, &mut report).await;
+                        },
+                        Err(error) => report.add_error(MetadataProblemKind::Version, link.id.as_str(), error),
+                    }
+                    tracker.add_count(1);
+                    tracker.notify();
+                }
+            },
+            Err(error) => report.add_error(MetadataProblemKind::VersionManifest, "version_manifest_v2", error),
+        }
+
+        if !modal_action.has_requested_cancel() {
+            self.validate_java_runtimes(&mut report).await;
+        }
+        tracker.add_count(1);
+        report.ignored_fields = crate::strict_schema::unknown_fields();
+
+        let result = serde_json::to_vec_pretty(&report)
+            .map_err(std::io::Error::from)
+            .and_then(|bytes| crate::write_safe(&report_path, &bytes));
+
+        tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
+        tracker.notify();
+
+        if let Err(error) = result {
+            modal_action.set_error_message(format!("Unable to write report: {error}").into());
+            return;
+        }
+
+        let summary = format!("Checked {} documents, found {} problems", report.checked, report.problems.len());
+        let fields: Vec<&str> = report.unknown_fields.keys().chain(report.ignored_fields.keys()).map(String::as_str).collect();
+        if fields.is_empty() {
+            if report.problems.is_empty() {
+                self.send.send_success(summary);
+            } else {
+                self.send.send_warning(summary);
+            }
+        } else {
+            self.send.send_warning(format!("{summary}. Unknown fields: {}", fields.join(", ")));
+        }
+    }
+
+    async fn validate_version(&self, id: &str, version: &MinecraftVersion, report: &mut MetadataValidationReport) {
+        let assets_index = self.meta.fetch(&AssetsIndexMetadataItem {
+            url: version.asset_index.url,
+            cache: self.directories.assets_index_dir.join(format!("{}.json", version.assets)).into(),
+            hash: version.asset_index.sha1,
+        }).await;
+        if let Err(error) = assets_index {
+            report.add_error(MetadataProblemKind::AssetsIndex, id, error);
+        }
+
+        for argument in version_arguments(version) {
+            for key in unsupported_expansions(argument) {
+                report.add(MetadataProblemKind::Argument, id, format!("Unsupported argument expansion ${{{key}}} in {argument:?}"));
+            }
+        }
+    }
+
+    async fn validate_java_runtimes(&self, report: &mut MetadataValidationReport) {
+        let runtimes = match self.meta.fetch(&MojangJavaRuntimesMetadataItem).await {
+            Ok(runtimes) => runtimes,
+            Err(error) => {
+                report.add_error(MetadataProblemKind::JavaRuntimes, "all.json", error);
+                return;
+            },
+        };
+
+        for (platform_name, platform) in &runtimes.platforms {
+            for (jre_component, components) in &platform.components {
+                let id = format!("{jre_component}/{platform_name}");
+                report.checked += 1;
+
+                // Kept in memory only, caching the manifests would create a folder for every runtime as if it was installed
+                for runtime_component in components {
+                    let manifest = self.meta.fetch(&MojangJavaRuntimeComponentMetadataItem {
+                        url: runtime_component.manifest.url,
+                        cache: None,
+                        hash: runtime_component.manifest.sha1,
+                    }).await;
+
+                    match manifest {
+                        Ok(manifest) => {
+                            let known_executable = KNOWN_JAVA_EXECUTABLES.iter()
+                                .any(|executable| manifest.files.contains_key(&Arc::<Path>::from(Path::new(executable))));
+                            if !known_executable {
+                                report.add(MetadataProblemKind::JavaRuntime, id.clone(), "Doesn't contain a known Java executable".to_string());
+                            }
+                        },
+                        Err(error) => report.add_error(MetadataProblemKind::JavaRuntime, id.clone(), error),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn problems_name_unknown_fields_and_expansions() {
+        let message = "unknown field `complianceLevel2`, expected one of `arguments`, `assetIndex` at line 1 column 20";
+        assert_eq!(unknown_field(message), Some("complianceLevel2"));
+        assert_eq!(unknown_field("missing field `id`"), None);
+
+        assert_eq!(unsupported_expansions("--username ${auth_player_name} --foo ${not_a_key}"), vec!["not_a_key"]);
+        assert!(unsupported_expansions("${unterminated").is_empty());
+    }
+
+    #[test]
+    fn json_errors_keep_the_unknown_field() {
+        #[derive(serde::Deserialize, Debug)]
+        #[serde(deny_unknown_fields)]
+        #[allow(dead_code)]
+        struct Strict {
+            id: String,
+        }
+
+        let error = serde_json::from_str::<Strict>(r#"{"id": "1.21.1", "extra": true}"#).unwrap_err();
+        let mut report = MetadataValidationReport::default();
+        report.add_error(MetadataProblemKind::Version, "1.21.1", MetaLoadError::SerdeJson(Arc::new(error)));
+
+        assert_eq!(report.problems[0].unknown_field.as_deref(), Some("extra"));
+        assert_eq!(report.unknown_fields.get("extra"), Some(&vec!["1.21.1".to_string()]));
+    }
+}
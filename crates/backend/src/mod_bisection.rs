@@ -0,0 +1,130 @@
+use std::{path::Path, sync::Arc};
+
+use bridge::{instance::{ContentType, InstanceID}, message::ModBisectionState};
+use serde::{Deserialize, Serialize};
+
+use crate::{BackendState, instance::ContentFolder};
+
+/// Progress of a search for the mod that makes an instance crash, kept in the instance folder so it survives
+/// restarting the launcher between launches
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ModBisection {
+    pub step: usize,
+    pub total: usize,
+    /// Mods that could still be causing the crash
+    pub suspects: Vec<String>,
+    /// The half of the suspects that is enabled for the next launch, empty once the search is over
+    pub testing: Vec<String>,
+}
+
+impl ModBisection {
+    pub fn new(mut mods: Vec<String>) -> Self {
+        mods.sort();
+        let mut bisection = Self {
+            step: 1,
+            total: mods.len(),
+            suspects: mods,
+            testing: Vec::new(),
+        };
+        bisection.split();
+        bisection
+    }
+
+    fn split(&mut self) {
+        self.testing = self.suspects[..self.suspects.len().div_ceil(2)].to_vec();
+    }
+
+    /// Narrows the suspects down to the enabled half if the game crashed, or the disabled half if it didn't
+    pub fn report(&mut self, crashed: bool) {
+        if crashed {
+            self.suspects = std::mem::take(&mut self.testing);
+        } else {
+            self.suspects.retain(|suspect| !self.testing.contains(suspect));
+        }
+        self.step += 1;
+
+        if self.suspects.len() > 1 {
+            self.split();
+        } else {
+            self.testing.clear();
+        }
+    }
+
+    pub fn to_state(&self) -> ModBisectionState {
+        let finished = self.testing.is_empty();
+        ModBisectionState {
+            step: self.step,
+            total: self.total,
+            suspects: self.suspects.iter().map(|suspect| Arc::from(suspect.as_str())).collect(),
+            testing: self.testing.iter().map(|mod_name| Arc::from(mod_name.as_str())).collect(),
+            culprit: self.suspects.first().filter(|_| finished).map(|culprit| Arc::from(culprit.as_str())),
+        }
+    }
+}
+
+pub fn load(instance_root: &Path) -> Option<ModBisection> {
+    std::fs::read(instance_root.join("mod_bisection.json")).ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+}
+
+pub fn save(instance_root: &Path, bisection: &ModBisection) -> std::io::Result<()> {
+    let json = serde_json::to_vec_pretty(bisection).map_err(std::io::Error::other)?;
+    crate::write_safe(&instance_root.join("mod_bisection.json"), &json)
+}
+
+pub fn clear(instance_root: &Path) {
+    _ = std::fs::remove_file(instance_root.join("mod_bisection.json"));
+}
+
+impl BackendState {
+    /// Starts a bisection over the enabled mods. Modpacks in the mods folder are left out since their contents are
+    /// only added by prelaunch, which is skipped when launching a bisection step
+    pub async fn start_mod_bisection(&self, id: InstanceID) -> Result<ModBisectionState, String> {
+        let Some(root_path) = self.instance_state.read().instances.get(id).map(|instance| instance.root_path.clone()) else {
+            return Err("Unknown instance".to_string());
+        };
+        let Some(summaries) = self.clone().load_instance_content(id, ContentFolder::Mods).await else {
+            return Err("Unable to load the instance's mods".to_string());
+        };
+
+        let mods: Vec<String> = summaries.iter()
+            .filter(|summary| summary.enabled && !matches!(summary.content_summary.extra, ContentType::ModrinthModpack { .. }))
+            .filter_map(|summary| summary.path.file_name().map(|name| name.to_string_lossy().into_owned()))
+            .collect();
+        if mods.is_empty() {
+            return Err("The instance doesn't have any enabled mods".to_string());
+        }
+
+        let bisection = ModBisection::new(mods);
+        save(&root_path, &bisection).map_err(|error| format!("Unable to save mod bisection: {error}"))?;
+        Ok(bisection.to_state())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bisection_finds_culprit() {
+        let mods = ["a.jar", "b.jar", "c.jar", "d.jar", "e.jar"].map(String::from).to_vec();
+        let mut bisection = ModBisection::new(mods);
+        assert_eq!(bisection.testing, ["a.jar", "b.jar", "c.jar"]);
+
+        // d.jar is the culprit
+        bisection.report(false);
+        assert_eq!(bisection.testing, ["d.jar"]);
+        bisection.report(true);
+        assert!(bisection.testing.is_empty());
+        assert_eq!(bisection.to_state().culprit.as_deref(), Some("d.jar"));
+        assert_eq!(bisection.step, 3);
+    }
+
+    #[test]
+    fn bisection_without_crashing_mod_has_no_culprit() {
+        let mut bisection = ModBisection::new(vec!["a.jar".into(), "b.jar".into()]);
+        bisection.report(false);
+        bisection.report(false);
+        assert!(bisection.to_state().culprit.is_none());
+    }
+}
@@ -0,0 +1,142 @@
+use std::{fmt::Write, path::PathBuf, sync::Arc};
+
+use bridge::{instance::{InstanceContentSummary, InstanceID}, message::ModListFormat, modal_action::ModalAction};
+use schema::content::ContentSource;
+
+use crate::{BackendState, instance::ContentFolder};
+
+#[derive(Debug, PartialEq, Eq)]
+struct ModListEntry {
+    name: Arc<str>,
+    version: Arc<str>,
+    filename: Arc<str>,
+    source: &'static str,
+    link: Option<String>,
+}
+
+impl ModListEntry {
+    fn new(summary: &InstanceContentSummary) -> Self {
+        let content = &summary.content_summary;
+        let (source, link) = match &summary.content_source {
+            ContentSource::Manual => ("Manual", None),
+            ContentSource::ModrinthUnknown => ("Modrinth", None),
+            ContentSource::ModrinthProject { project } => ("Modrinth", Some(format!("https://modrinth.com/project/{project}"))),
+            ContentSource::CurseForgeProject { project } => ("CurseForge", Some(format!("https://www.curseforge.com/projects/{project}"))),
+        };
+        Self {
+            name: content.name.clone().or_else(|| content.id.clone()).unwrap_or_else(|| summary.filename.clone()),
+            version: content.version_str.clone(),
+            filename: summary.filename.clone(),
+            source,
+            link,
+        }
+    }
+}
+
+/// Pipes would end the table cell and newlines the row
+fn escape_markdown(value: &str) -> String {
+    value.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn render(title: &str, subtitle: &str, entries: &[ModListEntry], format: ModListFormat) -> String {
+    let mut out = String::new();
+    match format {
+        ModListFormat::Markdown => {
+            _ = writeln!(out, "# {}\n\n{}\n", escape_markdown(title), escape_markdown(subtitle));
+            out.push_str("| Mod | Version | Source |\n|---|---|---|\n");
+            for entry in entries {
+                let name = escape_markdown(&entry.name);
+                let name = match &entry.link {
+                    Some(link) => format!("[{name}]({link})"),
+                    None => name,
+                };
+                _ = writeln!(out, "| {name} | {} | {} |", escape_markdown(&entry.version), entry.source);
+            }
+        },
+        ModListFormat::Csv => {
+            out.push_str("Name,Version,Filename,Source,Link\n");
+            for entry in entries {
+                _ = writeln!(out, "{},{},{},{},{}", escape_csv(&entry.name), escape_csv(&entry.version),
+                    escape_csv(&entry.filename), entry.source, escape_csv(entry.link.as_deref().unwrap_or("")));
+            }
+        },
+    }
+    out
+}
+
+impl BackendState {
+    pub async fn mod_list(&self, id: InstanceID, format: ModListFormat) -> Option<Arc<str>> {
+        let (title, subtitle) = {
+            let mut instance_state = self.instance_state.write();
+            let instance = instance_state.instances.get_mut(id)?;
+            let configuration = instance.configuration.get();
+            (instance.name.to_string(), format!("Minecraft {}, {}", configuration.minecraft_version, configuration.loader.name()))
+        };
+
+        let mods = self.clone().load_instance_content(id, ContentFolder::Mods).await?;
+        let mut entries: Vec<ModListEntry> = mods.iter()
+            .filter(|summary| summary.enabled)
+            .map(ModListEntry::new)
+            .collect();
+        entries.sort_by_cached_key(|entry| entry.name.to_lowercase());
+
+        Some(render(&title, &subtitle, &entries, format).into())
+    }
+
+    pub async fn export_mod_list(&self, id: InstanceID, path: PathBuf, modal_action: &ModalAction) {
+        let format = if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv")) {
+            ModListFormat::Csv
+        } else {
+            ModListFormat::Markdown
+        };
+
+        let Some(mod_list) = self.mod_list(id, format).await else {
+            modal_action.set_error_message("Unable to load the instance's mods".into());
+            return;
+        };
+
+        if let Err(error) = crate::write_safe(&path, mod_list.as_bytes()) {
+            modal_action.set_error_message(format!("Unable to write mod list: {error}").into());
+            return;
+        }
+
+        self.send.send_success(rust_i18n::t!("notification_mod_list_saved", path = path.display()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, link: Option<&str>) -> ModListEntry {
+        ModListEntry {
+            name: name.into(),
+            version: "1.0".into(),
+            filename: format!("{name}.jar").into(),
+            source: if link.is_some() { "Modrinth" } else { "Manual" },
+            link: link.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn markdown_links_modrinth_projects_and_escapes_pipes() {
+        let entries = [entry("Sodium", Some("https://modrinth.com/project/AANobbMI")), entry("A|B", None)];
+        let markdown = render("Pack", "Minecraft 1.21.1, Fabric", &entries, ModListFormat::Markdown);
+        assert!(markdown.contains("| [Sodium](https://modrinth.com/project/AANobbMI) | 1.0 | Modrinth |"));
+        assert!(markdown.contains("| A\\|B | 1.0 | Manual |"));
+    }
+
+    #[test]
+    fn csv_quotes_fields_with_commas() {
+        let csv = render("Pack", "", &[entry("Hello, World", None)], ModListFormat::Csv);
+        assert_eq!(csv.lines().nth(1), Some("\"Hello, World\",1.0,\"Hello, World.jar\",Manual,"));
+    }
+}
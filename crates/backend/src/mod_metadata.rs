@@ -2,14 +2,14 @@ use std::{
     io::{BufRead, Cursor, Read, Write}, path::{Path, PathBuf}, sync::Arc
 };
 
-use bridge::{instance::{AtomicContentUpdateStatus, ContentUpdateStatus, ContentType, ContentSummary}, safe_path::SafePath};
+use bridge::{content_source::{ContentFile, ContentSourceId}, instance::{AtomicContentUpdateStatus, ContentUpdateStatus, ContentType, ContentSummary}, safe_path::SafePath};
 use image::imageops::FilterType;
 use indexmap::IndexMap;
 use parking_lot::{RwLock, RwLockReadGuard};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use rc_zip_sync::EntryHandle;
 use rustc_hash::{FxHashMap, FxHashSet};
-use schema::{content::ContentSource, fabric_mod::{FabricModJson, Icon, Person}, forge_mod::{JarJarMetadata, ModsToml}, modrinth::{ModrinthFile, ModrinthSideRequirement}, mrpack::ModrinthIndexJson, resourcepack::PackMcmeta};
+use schema::{content::ContentSource, fabric_mod::{FabricModJson, Icon, Person}, forge_mod::{JarJarMetadata, ModsToml}, modrinth::ModrinthSideRequirement, mrpack::ModrinthIndexJson, resourcepack::PackMcmeta};
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DeserializeAs};
 use sha1::{Digest, Sha1};
@@ -20,8 +20,9 @@ pub enum ModUpdateAction {
     ErrorInvalidHash,
     AlreadyUpToDate,
     ManualInstall,
-    Modrinth {
-        file: ModrinthFile,
+    Update {
+        source: ContentSourceId,
+        file: ContentFile,
         project_id: Arc<str>,
     },
 }
@@ -33,7 +34,7 @@ impl ModUpdateAction {
             ModUpdateAction::ErrorInvalidHash => ContentUpdateStatus::ErrorInvalidHash,
             ModUpdateAction::AlreadyUpToDate => ContentUpdateStatus::AlreadyUpToDate,
             ModUpdateAction::ManualInstall => ContentUpdateStatus::ManualInstall,
-            ModUpdateAction::Modrinth { .. } => ContentUpdateStatus::Modrinth,
+            ModUpdateAction::Update { source, .. } => ContentUpdateStatus::update_available(*source),
         }
     }
 }
@@ -186,7 +187,7 @@ impl ModMetadataManager {
         }
 
         let fabric_mod_json: FabricModJson = serde_json::from_slice(&bytes).inspect_err(|e| {
-            log::error!("Error parsing fabric.mod.json: {e}");
+            tracing::error!("Error parsing fabric.mod.json: {e}");
         }).ok()?;
 
         drop(file);
@@ -215,6 +216,11 @@ impl ModMetadataManager {
             "".into()
         };
 
+        let dependencies = fabric_mod_json.depends.into_iter().flatten()
+            .map(|(id, _)| id)
+            .filter(|id| !is_platform_dependency(id))
+            .collect();
+
         Some(Arc::new(ContentSummary {
             id: Some(fabric_mod_json.id),
             hash,
@@ -223,7 +229,8 @@ impl ModMetadataManager {
             version_str: format!("v{}", fabric_mod_json.version).into(),
             png_icon,
             update_status: Arc::new(AtomicContentUpdateStatus::new(ContentUpdateStatus::Unknown)),
-            extra: ContentType::Fabric
+            extra: ContentType::Fabric,
+            dependencies,
         }))
     }
 
@@ -231,7 +238,7 @@ impl ModMetadataManager {
         let bytes = file.bytes().ok()?;
 
         let mods_toml: ModsToml = toml::from_slice(&bytes).inspect_err(|e| {
-            log::error!("Error parsing mods.toml/neoforge.mods.toml: {e}");
+            tracing::error!("Error parsing mods.toml/neoforge.mods.toml: {e}");
         }).ok()?;
 
         let Some(first) = mods_toml.mods.first() else {
@@ -267,6 +274,16 @@ impl ModMetadataManager {
             }
         }
 
+        // Jars can contain several mods that depend on each other, those aren't external dependencies
+        let mut dependencies: Vec<Arc<str>> = mods_toml.dependencies.iter().flatten()
+            .flat_map(|(_, dependencies)| dependencies)
+            .filter(|dependency| dependency.is_required() && !is_platform_dependency(&dependency.mod_id))
+            .filter(|dependency| !mods_toml.mods.iter().any(|m| m.mod_id == dependency.mod_id))
+            .map(|dependency| dependency.mod_id.clone())
+            .collect();
+        dependencies.sort();
+        dependencies.dedup();
+
         Some(Arc::new(ContentSummary {
             id: Some(first.mod_id.clone()),
             hash,
@@ -276,12 +293,13 @@ impl ModMetadataManager {
             png_icon,
             update_status: Arc::new(AtomicContentUpdateStatus::new(ContentUpdateStatus::Unknown)),
             extra,
+            dependencies: dependencies.into(),
         }))
     }
 
     fn load_modrinth_modpack<R: rc_zip_sync::HasCursor>(self: &Arc<Self>, hash: [u8; 20], archive: &rc_zip_sync::ArchiveHandle<R>, file: EntryHandle<'_, R>) -> Option<Arc<ContentSummary>> {
         let modrinth_index_json: ModrinthIndexJson = serde_json::from_slice(&file.bytes().ok()?).inspect_err(|e| {
-            log::error!("Error parsing modrinth.index.json: {e}");
+            tracing::error!("Error parsing modrinth.index.json: {e}");
         }).ok()?;
 
         let mut overrides: IndexMap<SafePath, Arc<[u8]>> = IndexMap::new();
@@ -377,7 +395,8 @@ impl ModMetadataManager {
                 downloads: modrinth_index_json.files,
                 summaries: summaries.into(),
                 overrides: overrides.into_iter().collect(),
-            }
+            },
+            dependencies: Arc::from([]),
         }))
     }
 
@@ -385,7 +404,7 @@ impl ModMetadataManager {
         let bytes = file.bytes().ok()?;
 
         let metadata_json: JarJarMetadata = serde_json::from_slice(&bytes).inspect_err(|e| {
-            log::error!("Error parsing jarjar/metadata.json: {e}");
+            tracing::error!("Error parsing jarjar/metadata.json: {e}");
         }).ok()?;
 
         drop(file);
@@ -446,7 +465,8 @@ impl ModMetadataManager {
             version_str: version.unwrap_or_default(),
             png_icon: None,
             update_status: Arc::new(AtomicContentUpdateStatus::new(ContentUpdateStatus::Unknown)),
-            extra: ContentType::JavaModule
+            extra: ContentType::JavaModule,
+            dependencies: Arc::from([]),
         }))
     }
 
@@ -454,7 +474,7 @@ impl ModMetadataManager {
         let bytes = file.bytes().ok()?;
 
         let pack_mcmeta: PackMcmeta = serde_json::from_slice(&bytes).inspect_err(|e| {
-            log::error!("Error parsing jarjar/metadata.json: {e}");
+            tracing::error!("Error parsing jarjar/metadata.json: {e}");
         }).ok()?;
 
         drop(file);
@@ -472,11 +492,17 @@ impl ModMetadataManager {
             version_str: pack_mcmeta.pack.description,
             png_icon,
             update_status: Arc::new(AtomicContentUpdateStatus::new(ContentUpdateStatus::Unknown)),
-            extra: ContentType::ResourcePack
+            extra: ContentType::ResourcePack,
+            dependencies: Arc::from([]),
         }))
     }
 }
 
+/// Dependencies on the game or mod loader itself, which are always present
+fn is_platform_dependency(id: &str) -> bool {
+    matches!(id, "minecraft" | "java" | "fabricloader" | "fabric-loader" | "quilt_loader" | "forge" | "neoforge" | "javafml" | "lowcodefml")
+}
+
 fn load_icon<R: rc_zip_sync::HasCursor>(icon_file: rc_zip_sync::EntryHandle<R>) -> Option<Arc<[u8]>> {
     let Ok(mut icon_bytes) = icon_file.bytes() else {
         return None;
@@ -554,7 +580,7 @@ impl ContentSources {
                 let skip = match old_source {
                     ContentSource::Manual => value == ContentSource::Manual,
                     ContentSource::ModrinthUnknown => value == ContentSource::ModrinthUnknown,
-                    ContentSource::ModrinthProject { project: _ } => {
+                    ContentSource::ModrinthProject { project: _ } | ContentSource::CurseForgeProject { project: _ } => {
                         old_source == &value || value == ContentSource::ModrinthUnknown
                     },
                 };
@@ -573,6 +599,9 @@ impl ContentSources {
     }
 
     pub fn write_all_to_file(&self, dir: &Path) {
+        if crate::data_lock::check_writable(dir).is_err() {
+            return;
+        }
         _ = std::fs::create_dir_all(dir);
 
         for (first_byte, values) in self.by_first_byte.iter().enumerate() {
@@ -619,6 +648,14 @@ impl ContentSources {
                 data.push(project.len() as u8);
                 data.extend_from_slice(project.as_bytes());
             },
+            ContentSource::CurseForgeProject { project } => {
+                data.push(3_u8);
+                if project.len() > 127 {
+                    panic!("curseforge project id was unexpectedly big: {:?}", &project);
+                }
+                data.push(project.len() as u8);
+                data.extend_from_slice(project.as_bytes());
+            },
         }
     }
 
@@ -708,6 +745,19 @@ impl ContentSources {
 
                         ContentSource::ModrinthProject { project: project_id.into() }
                     },
+                    3 => {
+                        let mut project_buf = vec![0_u8; type_and_size_buf[1] as usize];
+
+                        if cursor.read_exact(&mut project_buf).is_err() {
+                            break;
+                        }
+
+                        let Ok(project_id) = str::from_utf8(&project_buf) else {
+                            continue;
+                        };
+
+                        ContentSource::CurseForgeProject { project: project_id.into() }
+                    },
                     _ => {
                         cursor.consume(type_and_size_buf[1] as usize);
                         continue;
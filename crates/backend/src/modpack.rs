@@ -0,0 +1,326 @@
+use std::{collections::BTreeMap, io::{BufWriter, Write}, path::{Path, PathBuf}, sync::Arc};
+
+use bridge::{
+    content_source::ContentSourceId, install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath, InstallTarget}, instance::{ContentType, InstanceID}, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath
+};
+use rc_zip_sync::ReadZip;
+use schema::{
+    content::ContentSource, instance::InstanceConfiguration, loader::Loader, modrinth::ModrinthSideRequirement, mrpack::{ModrinthIndexJson, ModrinthIndexJsonExport, ModrinthModpackFileExport, ModrinthModpackFileHashes, MRPACK_LOADERS}
+};
+use sha2::Digest;
+use strum::IntoEnumIterator;
+use ustr::Ustr;
+
+use crate::{BackendState, content_sources::LocalContentFile, instance::ContentFolder, zip_writer::ZipWriter};
+
+/// Folders in .minecraft that are exported as overrides alongside the content
+const EXPORTED_OVERRIDE_FOLDERS: &[&str] = &["config", "defaultconfigs", "kubejs"];
+
+struct ExportedContent {
+    path: String,
+    sha1: String,
+    data: Vec<u8>,
+}
+
+impl BackendState {
+    /// Creates a new instance from a Modrinth modpack. The pack is kept in the mods folder like packs installed from
+    /// Modrinth, so its files are downloaded into the content library here and its overrides are applied on launch
+    pub async fn import_mrpack(&self, path: PathBuf, modal_action: &ModalAction) {
+        let index = {
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || -> Result<ModrinthIndexJson, String> {
+                let file = std::fs::File::open(&path).map_err(|error| error.to_string())?;
+                let archive = file.read_zip().map_err(|error| error.to_string())?;
+                let entry = archive.by_name("modrinth.index.json").ok_or("modrinth.index.json is missing")?;
+                let bytes = entry.bytes().map_err(|error| error.to_string())?;
+                serde_json::from_slice(&bytes).map_err(|error| error.to_string())
+            }).await.unwrap()
+        };
+        let index = match index {
+            Ok(index) => index,
+            Err(error) => {
+                modal_action.set_error_message(format!("Unable to read modpack: {error}").into());
+                return;
+            },
+        };
+
+        let Some(minecraft_version) = index.minecraft_version().map(Ustr::from) else {
+            modal_action.set_error_message("The modpack doesn't specify a Minecraft version".into());
+            return;
+        };
+        let (loader, loader_version) = index.loader();
+        if loader == Loader::Unknown {
+            modal_action.set_error_message("The modpack uses a loader that isn't supported".into());
+            return;
+        }
+
+        let Some(name) = self.unused_instance_name(&index.name) else {
+            modal_action.set_error_message(format!("Can't import modpack, the name '{}' is already used", index.name).into());
+            return;
+        };
+
+        let client_files = || index.files.iter().filter(|file| file.env.is_none_or(|env| env.client != ModrinthSideRequirement::Unsupported));
+        // Files already in the content library are counted too, it's better to ask for too much than to run out halfway
+        let required = std::fs::metadata(&path).map(|metadata| metadata.len()).unwrap_or_default()
+            + client_files().map(|file| file.file_size as u64).sum::<u64>();
+        if let Err(error) = crate::download_conditions::ensure_space(&self.directories.content_library_dir, required) {
+            modal_action.set_error_message(error.to_string().into());
+            return;
+        }
+
+        tracing::info!("Importing modpack {} as {name}", index.name);
+
+        // Work in the temp dir so the instances watcher only sees the finished instance
+        let temp_dir: Arc<Path> = self.directories.temp_dir.join(format!("import-{:016x}", rand::random::<u64>())).into();
+        let mut configuration = InstanceConfiguration::new(minecraft_version, loader);
+        configuration.preferred_loader_version = loader_version.map(|version| loader.maven_version(&minecraft_version, version).into());
+
+        let prepared = {
+            let temp_dir = temp_dir.clone();
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || {
+                crate::write_safe(&temp_dir.join("info_v1.json"), &serde_json::to_vec(&configuration)?)?;
+                let mods_dir = temp_dir.join(".minecraft").join("mods");
+                std::fs::create_dir_all(&mods_dir)?;
+                let file_name = path.file_name().map(PathBuf::from).unwrap_or_else(|| PathBuf::from("modpack.mrpack"));
+                std::fs::copy(&path, mods_dir.join(file_name))?;
+                std::io::Result::Ok(())
+            }).await.unwrap()
+        };
+        if let Err(error) = prepared {
+            _ = std::fs::remove_dir_all(&temp_dir);
+            modal_action.set_error_message(format!("Unable to create instance: {error}").into());
+            return;
+        }
+
+        let files = client_files()
+            .filter_map(|file| {
+                Some(ContentInstallFile {
+                    replace_old: None,
+                    path: ContentInstallPath::Safe(SafePath::new(&file.path)?),
+                    download: ContentDownload::Url {
+                        url: file.downloads.first()?.clone(),
+                        sha1: file.hashes.sha1.clone(),
+                        size: file.file_size,
+                    },
+                    content_source: ContentSource::ModrinthUnknown,
+                })
+            })
+            .collect();
+        self.install_content(ContentInstall {
+            target: InstallTarget::Library,
+            loader_hint: loader,
+            version_hint: Some(minecraft_version.into()),
+            files,
+        }, modal_action.clone()).await;
+
+        if modal_action.has_requested_cancel() || modal_action.error.read().unwrap().is_some() {
+            _ = std::fs::remove_dir_all(&temp_dir);
+            return;
+        }
+
+        self.file_watching.write().watch_filesystem(self.directories.instances_dir.clone(), crate::backend::WatchTarget::InstancesDir);
+        if let Err(error) = std::fs::rename(&temp_dir, self.directories.instances_dir.join(&name)) {
+            _ = std::fs::remove_dir_all(&temp_dir);
+            modal_action.set_error_message(format!("Unable to create instance: {error}").into());
+            return;
+        }
+
+        self.send.send_success(rust_i18n::t!("notification_modpack_imported", pack = index.name, name = name));
+    }
+
+    /// Writes the instance as a Modrinth modpack. Content that Modrinth knows about is referenced by its download url,
+    /// anything else is stored in the pack's overrides along with the instance's configs
+    pub async fn export_mrpack(&self, id: InstanceID, destination: PathBuf, modal_action: &ModalAction) {
+        let Some((name, dot_minecraft, configuration)) = self.instance_state.write().instances.get_mut(id)
+            .map(|instance| (instance.name, instance.dot_minecraft_path.clone(), instance.configuration.get().clone()))
+        else {
+            modal_action.set_error_message("Can't export instance, unknown id".into());
+            return;
+        };
+
+        let mut dependencies = BTreeMap::new();
+        dependencies.insert("minecraft", Arc::from(configuration.minecraft_version.as_str()));
+        if let Some((key, _)) = MRPACK_LOADERS.iter().find(|(_, loader)| *loader == configuration.loader) {
+            let loader_version = match (configuration.preferred_loader_version, configuration.loader) {
+                (Some(version), _) => version,
+                (None, Loader::Fabric) => match self.launcher.resolve_fabric_loader_version(&configuration).await {
+                    Ok(version) => version,
+                    Err(error) => {
+                        modal_action.set_error_message(format!("Unable to find the Fabric loader version: {error}").into());
+                        return;
+                    },
+                },
+                (None, _) => {
+                    modal_action.set_error_message("Pick a loader version in the instance settings before exporting".into());
+                    return;
+                },
+            };
+            // Forge's maven versions are prefixed with the Minecraft version, modpacks only list the loader's own version
+            let loader_version = loader_version.strip_prefix(&format!("{}-", configuration.minecraft_version)).unwrap_or(loader_version.as_str());
+            dependencies.insert(*key, Arc::from(loader_version));
+        } else if configuration.loader != Loader::Vanilla {
+            modal_action.set_error_message("Instances using this loader can't be exported as a Modrinth modpack".into());
+            return;
+        }
+
+        let tracker = ProgressTracker::new(rust_i18n::t!("progress_exporting", name = name).into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.notify();
+
+        let mut content = Vec::new();
+        for folder in ContentFolder::iter() {
+            let Some(summaries) = self.clone().load_instance_content(id, folder).await else {
+                modal_action.set_error_message("Unable to load the instance's content".into());
+                return;
+            };
+            for summary in summaries.iter() {
+                if !summary.enabled {
+                    continue;
+                }
+                if matches!(summary.content_summary.extra, ContentType::ModrinthModpack { .. }) {
+                    self.send.send_warning(rust_i18n::t!("notification_modpack_left_out", file = summary.filename));
+                    continue;
+                }
+                let data = match tokio::fs::read(&summary.path).await {
+                    Ok(data) => data,
+                    Err(error) => {
+                        modal_action.set_error_message(format!("Unable to read {}: {error}", summary.path.display()).into());
+                        return;
+                    },
+                };
+                content.push(ExportedContent {
+                    path: format!("{}/{}", folder.path(), summary.filename),
+                    sha1: hex::encode(summary.content_summary.hash),
+                    data,
+                });
+            }
+        }
+        tracker.set_total(content.len() as u64 + 1);
+        tracker.notify();
+
+        // Only Modrinth's CDN is allowed in the downloads of a .mrpack, so other sources aren't asked
+        let versions = {
+            let files: Vec<LocalContentFile> = content.iter()
+                .map(|content| LocalContentFile { sha1: &content.sha1, data: &content.data })
+                .collect();
+            let result = match self.content_source(ContentSourceId::Modrinth) {
+                Ok(source) => source.lookup_hashes(&files).await,
+                Err(error) => Err(error),
+            };
+            result.unwrap_or_else(|error| {
+                tracing::warn!("Unable to look up content on Modrinth, everything will be stored in overrides: {error}");
+                Vec::new()
+            })
+        };
+        tracker.add_count(1);
+        tracker.notify();
+
+        let result = {
+            let tracker = tracker.clone();
+            tokio::task::spawn_blocking(move || -> std::io::Result<usize> {
+                crate::data_lock::check_writable(&destination)?;
+
+                // Written next to the destination and moved into place once complete, so a failed export doesn't
+                // leave a broken pack behind
+                let mut part_path = destination.clone().into_os_string();
+                part_path.push(".part");
+                let part_path = PathBuf::from(part_path);
+                let mut writer = ZipWriter::new(BufWriter::new(std::fs::File::create(&part_path)?));
+
+                let write = || -> std::io::Result<usize> {
+                    let mut files = Vec::new();
+
+                    for (index, content) in content.into_iter().enumerate() {
+                        let download = versions.get(index).and_then(Option::as_ref)
+                            .and_then(|version| version.files.iter().find(|file| *file.sha1 == *content.sha1))
+                            .and_then(|file| file.url.clone());
+                        if let Some(download) = download {
+                            files.push(ModrinthModpackFileExport {
+                                path: content.path.into(),
+                                hashes: ModrinthModpackFileHashes {
+                                    sha1: content.sha1.into(),
+                                    sha512: hex::encode(sha2::Sha512::digest(&content.data)).into(),
+                                },
+                                downloads: vec![download],
+                                file_size: content.data.len(),
+                            });
+                        } else {
+                            writer.add_file(&format!("overrides/{}", content.path), &content.data)?;
+                        }
+                        tracker.add_count(1);
+                        tracker.notify();
+                    }
+
+                    for folder in EXPORTED_OVERRIDE_FOLDERS {
+                        add_folder(&mut writer, &dot_minecraft.join(folder), &format!("overrides/{folder}"))?;
+                    }
+
+                    let referenced = files.len();
+                    let index = ModrinthIndexJsonExport {
+                        format_version: 1,
+                        game: "minecraft",
+                        version_id: "1.0.0".into(),
+                        name: name.as_str().into(),
+                        files,
+                        dependencies,
+                    };
+                    writer.add_file("modrinth.index.json", &serde_json::to_vec_pretty(&index)?)?;
+
+                    writer.finish()?.flush()?;
+                    Ok(referenced)
+                };
+
+                match write() {
+                    Ok(referenced) => {
+                        std::fs::rename(&part_path, &destination)?;
+                        Ok(referenced)
+                    },
+                    Err(error) => {
+                        _ = std::fs::remove_file(&part_path);
+                        Err(error)
+                    },
+                }
+            }).await.unwrap()
+        };
+
+        tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
+        tracker.notify();
+
+        match result {
+            Ok(referenced) => self.send.send_success(rust_i18n::t!("notification_modpack_exported", name = name, count = referenced)),
+            Err(error) => modal_action.set_error_message(format!("Unable to export modpack: {error}").into()),
+        }
+    }
+
+    pub(crate) fn unused_instance_name(&self, name: &str) -> Option<String> {
+        let name = sanitize_filename::sanitize_with_options(name, sanitize_filename::Options { windows: true, ..Default::default() });
+        let is_used = |name: &str| {
+            name.is_empty() || self.directories.instances_dir.join(name).exists()
+                || self.instance_state.read().instances.iter().any(|instance| instance.name == name)
+        };
+
+        if !is_used(&name) {
+            return Some(name);
+        }
+        (1..32).map(|i| format!("{name} ({i})")).find(|name| !is_used(name))
+    }
+}
+
+fn add_folder<W: Write>(writer: &mut ZipWriter<W>, folder: &Path, prefix: &str) -> std::io::Result<()> {
+    let Ok(read_dir) = std::fs::read_dir(folder) else {
+        return Ok(());
+    };
+    for entry in read_dir {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = format!("{prefix}/{}", name.to_string_lossy());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            add_folder(writer, &entry.path(), &name)?;
+        } else if file_type.is_file() {
+            writer.add_file(&name, &std::fs::read(entry.path())?)?;
+        }
+    }
+    Ok(())
+}
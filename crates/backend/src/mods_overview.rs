@@ -0,0 +1,93 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use bridge::{
+    instance::{InstanceContentID, InstanceContentSummary, InstanceID, ModInstallEntry, ModInstallation}, modal_action::ModalAction
+};
+use indexmap::IndexMap;
+use schema::content::ContentSource;
+
+use crate::{instance::ContentFolder, BackendState};
+
+/// Mods are grouped by their mod id, so copies installed from different sources still end up together. Mods without
+/// an id are grouped by their Modrinth or CurseForge project, or their filename as a last resort
+fn group_key(summary: &InstanceContentSummary) -> Arc<str> {
+    if let Some(id) = &summary.content_summary.id {
+        return format!("id:{id}").into();
+    }
+    match &summary.content_source {
+        ContentSource::ModrinthProject { project } => format!("modrinth:{project}").into(),
+        ContentSource::CurseForgeProject { project } => format!("curseforge:{project}").into(),
+        _ => format!("file:{}", summary.filename).into(),
+    }
+}
+
+impl BackendState {
+    /// The mods of one instance, keyed so the frontend can group them with the mods of other instances
+    pub async fn instance_mod_installs(&self, id: InstanceID) -> Arc<[ModInstallEntry]> {
+        let instance_name: Arc<str> = {
+            let instance_state = self.instance_state.read();
+            let Some(instance) = instance_state.instances.get(id) else {
+                return Arc::from([]);
+            };
+            Arc::from(instance.name.as_str())
+        };
+
+        let Some(mods) = self.clone().load_instance_content(id, ContentFolder::Mods).await else {
+            return Arc::from([]);
+        };
+        mods.iter()
+            .map(|summary| {
+                let content = &summary.content_summary;
+                ModInstallEntry {
+                    group_key: group_key(summary),
+                    name: content.name.clone().or_else(|| content.id.clone()).unwrap_or_else(|| summary.filename.clone()),
+                    png_icon: content.png_icon.clone(),
+                    install: ModInstallation {
+                        instance: id,
+                        instance_name: instance_name.clone(),
+                        content_id: summary.id,
+                        version: content.version_str.clone(),
+                        enabled: summary.enabled,
+                        can_update: content.update_status.load(Ordering::Relaxed).can_update(),
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Checks every instance of the targets for updates once, then updates the targets that have a newer version
+    pub async fn update_content_in_instances(&self, targets: Vec<(InstanceID, InstanceContentID)>, modal_action: &ModalAction) {
+        let mut by_instance: IndexMap<InstanceID, Vec<InstanceContentID>> = IndexMap::new();
+        for (id, content_id) in targets {
+            by_instance.entry(id).or_default().push(content_id);
+        }
+
+        for (id, content_ids) in by_instance {
+            if modal_action.has_requested_cancel() {
+                return;
+            }
+            if !self.check_content_updates(id, modal_action).await {
+                continue;
+            }
+
+            let updatable: Vec<InstanceContentID> = {
+                let mut instance_state = self.instance_state.write();
+                let Some(instance) = instance_state.instances.get_mut(id) else {
+                    continue;
+                };
+                content_ids.into_iter()
+                    .filter(|content_id| instance.try_get_content(*content_id).is_some_and(|(summary, _)| {
+                        summary.content_summary.update_status.load(Ordering::Relaxed).can_update()
+                    }))
+                    .collect()
+            };
+
+            for content_id in updatable {
+                if modal_action.has_requested_cancel() {
+                    return;
+                }
+                self.update_content(id, content_id, modal_action).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::{path::{Path, PathBuf}, time::{Duration, Instant}};
+
+use reqwest::{Certificate, ClientBuilder, Proxy, Url};
+use schema::backend_config::ProxySettings;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum NetworkSettingsError {
+    #[error("Invalid proxy URL: {0}")]
+    InvalidProxyUrl(#[from] url::ParseError),
+    #[error("Invalid proxy: {0}")]
+    InvalidProxy(reqwest::Error),
+    #[error("Unable to read CA certificates from {0}: {1}")]
+    ReadCertificates(PathBuf, std::io::Error),
+    #[error("Invalid CA certificates in {0}: {1}")]
+    InvalidCertificates(PathBuf, reqwest::Error),
+    #[error("No certificates found in {0}")]
+    NoCertificates(PathBuf),
+}
+
+/// Proxy and extra trusted certificates, applied to every HTTP client the backend creates
+#[derive(Debug, Default, Clone)]
+pub struct NetworkSettings {
+    proxy: Option<Proxy>,
+    certificates: Vec<Certificate>,
+}
+
+impl NetworkSettings {
+    pub fn new(proxy: &ProxySettings, ca_certificates: Option<&Path>) -> Result<Self, NetworkSettingsError> {
+        let proxy = match &proxy.url {
+            Some(url) => {
+                // Credentials are passed in the URL since that's the only way SOCKS proxies accept them
+                let mut url = Url::parse(url)?;
+                if let Some(username) = &proxy.username {
+                    _ = url.set_username(username);
+                    _ = url.set_password(proxy.password.as_deref());
+                }
+                Some(Proxy::all(url).map_err(NetworkSettingsError::InvalidProxy)?)
+            },
+            None => None,
+        };
+
+        let certificates = match ca_certificates {
+            Some(path) => {
+                let bytes = std::fs::read(path).map_err(|error| NetworkSettingsError::ReadCertificates(path.into(), error))?;
+                let certificates = Certificate::from_pem_bundle(&bytes)
+                    .map_err(|error| NetworkSettingsError::InvalidCertificates(path.into(), error))?;
+                if certificates.is_empty() {
+                    return Err(NetworkSettingsError::NoCertificates(path.into()));
+                }
+                certificates
+            },
+            None => Vec::new(),
+        };
+
+        Ok(Self { proxy, certificates })
+    }
+
+    pub fn apply(&self, mut builder: ClientBuilder) -> ClientBuilder {
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        for certificate in &self.certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+        builder
+    }
+}
+
+pub fn user_agent(custom_user_agent: Option<&str>) -> String {
+    if let Some(custom_user_agent) = custom_user_agent {
+        custom_user_agent.to_string()
+    } else if let Some(version) = option_env!("PANDORA_RELEASE_VERSION") {
+        format!("PandoraLauncher/{version} (https://github.com/Moulberry/PandoraLauncher)")
+    } else {
+        "PandoraLauncher/dev (https://github.com/Moulberry/PandoraLauncher)".to_string()
+    }
+}
+
+/// Reaches Mojang's version manifest through the given settings before they are saved, returning how long it took
+pub async fn test_connection(user_agent: &str, proxy: &ProxySettings, ca_certificates: Option<&Path>) -> Result<Duration, String> {
+    let settings = NetworkSettings::new(proxy, ca_certificates).map_err(|error| error.to_string())?;
+    let client = settings.apply(ClientBuilder::new())
+        .use_rustls_tls()
+        .user_agent(user_agent)
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|error| format!("Unable to create HTTP client: {error}"))?;
+
+    let url = crate::endpoints::resolve(schema::version_manifest::MOJANG_VERSION_MANIFEST_URL);
+    let started = Instant::now();
+    let response = client.head(&*url).send().await.map_err(|error| {
+        if error.is_connect() {
+            format!("Unable to connect to {url}: {}", std::error::Error::source(&error).map(ToString::to_string).unwrap_or_default())
+        } else if error.is_timeout() {
+            format!("Connection to {url} timed out")
+        } else {
+            error.to_string()
+        }
+    })?;
+    if !response.status().is_success() {
+        return Err(format!("{url} responded with {}", response.status()));
+    }
+    Ok(started.elapsed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_proxy_and_certificates_are_reported() {
+        let proxy = ProxySettings { url: Some("not a url".into()), ..Default::default() };
+        assert!(matches!(NetworkSettings::new(&proxy, None), Err(NetworkSettingsError::InvalidProxyUrl(_))));
+
+        let dir = crate::test_harness::TempDir::new();
+        let path = dir.path().join("empty.pem");
+        std::fs::write(&path, "").unwrap();
+        assert!(matches!(NetworkSettings::new(&ProxySettings::default(), Some(&path)), Err(NetworkSettingsError::NoCertificates(_))));
+
+        let proxy = ProxySettings {
+            url: Some("socks5h://proxy.example:1080".into()),
+            username: Some("user".into()),
+            password: Some("secret".into()),
+        };
+        assert!(NetworkSettings::new(&proxy, None).unwrap().proxy.is_some());
+    }
+}
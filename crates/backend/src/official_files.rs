@@ -0,0 +1,319 @@
+use std::{io::Read, path::{Path, PathBuf}, sync::Arc};
+
+use bridge::{message::MessageToFrontend, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+
+use crate::{BackendState, directories::LauncherDirectories};
+
+/// Asset objects and libraries from the official launcher that can be copied into this launcher's stores
+#[derive(Debug, Default)]
+struct SeedCandidates {
+    /// Source path and destination path, the expected SHA-1 is the object's file name
+    objects: Vec<(PathBuf, PathBuf, Arc<str>)>,
+    /// Source path, destination path and the SHA-1 listed by a version json
+    libraries: Vec<(PathBuf, PathBuf, Arc<str>)>,
+    /// Asset indexes are small and verified when loaded, so they're copied as-is
+    indexes: Vec<(PathBuf, PathBuf)>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct SeedSummary {
+    copied: usize,
+    skipped: usize,
+    bytes: u64,
+}
+
+#[derive(Deserialize)]
+struct VersionJson {
+    #[serde(default)]
+    libraries: Vec<VersionLibrary>,
+}
+
+#[derive(Deserialize)]
+struct VersionLibrary {
+    #[serde(default)]
+    downloads: Option<VersionLibraryDownloads>,
+}
+
+#[derive(Deserialize)]
+struct VersionLibraryDownloads {
+    #[serde(default)]
+    artifact: Option<VersionArtifact>,
+    #[serde(default)]
+    classifiers: FxHashMap<Arc<str>, VersionArtifact>,
+}
+
+#[derive(Deserialize)]
+struct VersionArtifact {
+    path: Arc<str>,
+    sha1: Arc<str>,
+}
+
+impl BackendState {
+    /// Offers to seed the stores from the official launcher, but only while nothing has been downloaded yet and only
+    /// once per launcher directory
+    pub async fn offer_official_launcher_files(&self) {
+        if crate::data_lock::is_read_only() || self.config.write().get().offered_official_launcher_files {
+            return;
+        }
+        if !is_empty_dir(&self.directories.assets_objects_dir) || !is_empty_dir(&self.directories.libraries_dir) {
+            return;
+        }
+        let Some(minecraft_dir) = crate::launcher_import::default_official_minecraft_dir() else {
+            return;
+        };
+
+        let found = tokio::task::spawn_blocking({
+            let minecraft_dir = minecraft_dir.clone();
+            move || count_objects(&minecraft_dir)
+        }).await.unwrap_or_default();
+        let Some((objects, bytes)) = found.filter(|(objects, _)| *objects > 0) else {
+            return;
+        };
+
+        tracing::info!("Found {objects} asset objects from the official launcher in {}", minecraft_dir.display());
+        self.config.write().modify(|config| config.offered_official_launcher_files = true);
+        self.send.send(MessageToFrontend::OfficialLauncherFilesFound {
+            minecraft_dir: minecraft_dir.into(),
+            objects,
+            bytes,
+        });
+    }
+
+    pub async fn seed_from_official_launcher(&self, minecraft_dir: Arc<Path>, modal_action: &ModalAction) {
+        let tracker = ProgressTracker::new(rust_i18n::t!("progress_copying_official_files").into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+
+        let directories = self.directories.clone();
+        let result = tokio::task::spawn_blocking({
+            let tracker = tracker.clone();
+            let modal_action = modal_action.clone();
+            move || {
+                let candidates = find_candidates(&minecraft_dir, &directories);
+                seed(candidates, &tracker, &modal_action)
+            }
+        }).await;
+
+        match result {
+            Ok(summary) => {
+                tracker.set_finished(ProgressTrackerFinishType::Normal);
+                tracker.notify();
+                tracing::info!("Copied {} files from the official launcher, skipped {}", summary.copied, summary.skipped);
+                let gib = format!("{:.1}", summary.bytes as f64 / (1024.0 * 1024.0 * 1024.0));
+                self.send.send_success(rust_i18n::t!("notification_official_files_reused", count = summary.copied, size = gib));
+            },
+            Err(error) => {
+                tracker.set_finished(ProgressTrackerFinishType::Error);
+                tracker.notify();
+                modal_action.set_error_message(format!("Unable to copy files from the Minecraft Launcher: {error}").into());
+            },
+        }
+    }
+}
+
+fn is_empty_dir(path: &Path) -> bool {
+    std::fs::read_dir(path).map(|mut entries| entries.next().is_none()).unwrap_or(true)
+}
+
+fn is_sha1(name: &str) -> bool {
+    name.len() == 40 && name.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+/// Counts the official launcher's asset objects and their total size
+fn count_objects(minecraft_dir: &Path) -> Option<(usize, u64)> {
+    let mut objects = 0;
+    let mut bytes = 0;
+    for prefix in std::fs::read_dir(minecraft_dir.join("assets").join("objects")).ok()?.flatten() {
+        let Ok(entries) = std::fs::read_dir(prefix.path()) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if let Ok(metadata) = entry.metadata() && metadata.is_file() {
+                objects += 1;
+                bytes += metadata.len();
+            }
+        }
+    }
+    Some((objects, bytes))
+}
+
+fn find_candidates(minecraft_dir: &Path, directories: &LauncherDirectories) -> SeedCandidates {
+    let mut candidates = SeedCandidates::default();
+
+    let objects_dir = minecraft_dir.join("assets").join("objects");
+    for prefix in std::fs::read_dir(&objects_dir).into_iter().flatten().flatten() {
+        let prefix_name = prefix.file_name();
+        let Some(prefix_name) = prefix_name.to_str().filter(|name| name.len() == 2) else {
+            continue;
+        };
+        for entry in std::fs::read_dir(prefix.path()).into_iter().flatten().flatten() {
+            let file_name = entry.file_name();
+            let Some(hash) = file_name.to_str().filter(|name| is_sha1(name) && name.starts_with(prefix_name)) else {
+                continue;
+            };
+            let target = directories.assets_objects_dir.join(prefix_name).join(hash);
+            candidates.objects.push((entry.path(), target, hash.into()));
+        }
+    }
+
+    for entry in std::fs::read_dir(minecraft_dir.join("assets").join("indexes")).into_iter().flatten().flatten() {
+        let file_name = entry.file_name();
+        if file_name.to_str().is_some_and(|name| name.ends_with(".json") && crate::is_single_component_path(name)) {
+            candidates.indexes.push((entry.path(), directories.assets_index_dir.join(file_name)));
+        }
+    }
+
+    // Libraries aren't named by their hash, so only those listed with a hash by one of the official launcher's
+    // version jsons are considered
+    let libraries_dir = minecraft_dir.join("libraries");
+    let mut seen = FxHashSet::default();
+    for version in std::fs::read_dir(minecraft_dir.join("versions")).into_iter().flatten().flatten() {
+        let Some(id) = version.file_name().to_str().map(str::to_owned) else {
+            continue;
+        };
+        let Ok(bytes) = std::fs::read(version.path().join(format!("{id}.json"))) else {
+            continue;
+        };
+        let Ok(version) = serde_json::from_slice::<VersionJson>(&bytes) else {
+            continue;
+        };
+        for library in version.libraries {
+            let Some(downloads) = library.downloads else {
+                continue;
+            };
+            for artifact in downloads.artifact.into_iter().chain(downloads.classifiers.into_values()) {
+                let Some(path) = SafePath::new(&artifact.path) else {
+                    continue;
+                };
+                if !is_sha1(&artifact.sha1) || !seen.insert(artifact.path.clone()) {
+                    continue;
+                }
+                let source = path.to_path(&libraries_dir);
+                if source.is_file() {
+                    candidates.libraries.push((source, path.to_path(&directories.libraries_dir), artifact.sha1));
+                }
+            }
+        }
+    }
+
+    candidates
+}
+
+fn seed(candidates: SeedCandidates, tracker: &ProgressTracker, modal_action: &ModalAction) -> SeedSummary {
+    let mut summary = SeedSummary::default();
+
+    tracker.set_total((candidates.objects.len() + candidates.libraries.len() + candidates.indexes.len()) as u64);
+    tracker.notify();
+
+    for (source, target) in candidates.indexes {
+        if !target.exists() && copy_or_link(&source, &target).is_ok() {
+            summary.copied += 1;
+        }
+        tracker.add_count(1);
+    }
+
+    for (source, target, sha1) in candidates.objects.into_iter().chain(candidates.libraries) {
+        if modal_action.has_requested_cancel() {
+            break;
+        }
+
+        if target.exists() {
+            summary.skipped += 1;
+        } else {
+            match hash_file(&source) {
+                Ok(actual) if actual.eq_ignore_ascii_case(&sha1) => match copy_or_link(&source, &target) {
+                    Ok(bytes) => {
+                        summary.copied += 1;
+                        summary.bytes += bytes;
+                    },
+                    Err(error) => {
+                        tracing::warn!("Unable to copy {}: {error}", source.display());
+                        summary.skipped += 1;
+                    },
+                },
+                Ok(_) => {
+                    tracing::warn!("Skipping {} because its hash doesn't match", source.display());
+                    summary.skipped += 1;
+                },
+                Err(error) => {
+                    tracing::warn!("Unable to read {}: {error}", source.display());
+                    summary.skipped += 1;
+                },
+            }
+        }
+
+        tracker.add_count(1);
+        tracker.notify();
+    }
+
+    summary
+}
+
+fn hash_file(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha1::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Hardlinks when both folders are on the same drive so nothing takes up extra space, otherwise copies. Either goes
+/// to a temporary file first so an interrupted copy never leaves a truncated file at the target
+fn copy_or_link(source: &Path, target: &Path) -> std::io::Result<u64> {
+    crate::data_lock::check_writable(target)?;
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let len = source.metadata()?.len();
+
+    let mut temp = target.to_path_buf();
+    temp.add_extension(format!("{}", rand::random::<u32>()));
+    temp.add_extension("new");
+
+    let result = std::fs::hard_link(source, &temp)
+        .or_else(|_| std::fs::copy(source, &temp).map(|_| ()))
+        .and_then(|_| std::fs::rename(&temp, target));
+    if result.is_err() {
+        _ = std::fs::remove_file(&temp);
+    }
+    result.map(|_| len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::TempDir;
+
+    #[test]
+    fn objects_with_a_mismatched_hash_are_skipped() {
+        let dir = TempDir::new();
+        let minecraft_dir = dir.path().join(".minecraft");
+        let directories = LauncherDirectories::new(dir.path().join("launcher"));
+
+        let good = hex::encode(Sha1::digest(b"good"));
+        let bad = hex::encode(Sha1::digest(b"expected"));
+        for (hash, contents) in [(&good, b"good".as_slice()), (&bad, b"corrupted".as_slice())] {
+            let path = minecraft_dir.join("assets").join("objects").join(&hash[..2]).join(hash);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+
+        let candidates = find_candidates(&minecraft_dir, &directories);
+        assert_eq!(candidates.objects.len(), 2);
+
+        let tracker = ProgressTracker::new("Test".into(), crate::test_harness::frontend_handle());
+        let summary = seed(candidates, &tracker, &ModalAction::default());
+
+        assert_eq!(summary, SeedSummary { copied: 1, skipped: 1, bytes: 4 });
+        assert!(directories.assets_objects_dir.join(&good[..2]).join(&good).is_file());
+        assert!(!directories.assets_objects_dir.join(&bad[..2]).join(&bad).exists());
+    }
+}
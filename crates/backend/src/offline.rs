@@ -0,0 +1,123 @@
+use std::{sync::{Arc, atomic::{AtomicBool, AtomicU32, Ordering}}, time::Duration};
+
+use bridge::message::MessageToFrontend;
+use rustc_hash::FxHashMap;
+use schema::version_manifest::MinecraftVersionManifest;
+use ustr::Ustr;
+
+use crate::BackendState;
+
+/// Set by the user to never touch the network
+static FORCED: AtomicBool = AtomicBool::new(false);
+/// Set when a request fails because there's no connection, cleared once a request gets through again
+static DETECTED: AtomicBool = AtomicBool::new(false);
+static CHANGED: AtomicBool = AtomicBool::new(false);
+/// Connect failures since the last request that got through
+static CONNECT_FAILURES: AtomicU32 = AtomicU32::new(0);
+
+/// A single failed connection is often just a flaky server or DNS hiccup
+const FAILURES_UNTIL_OFFLINE: u32 = 3;
+
+const RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+pub fn is_offline() -> bool {
+    FORCED.load(Ordering::Relaxed) || DETECTED.load(Ordering::Relaxed)
+}
+
+pub fn set_forced(offline: bool) {
+    if FORCED.swap(offline, Ordering::Relaxed) != offline {
+        CHANGED.store(true, Ordering::Relaxed);
+    }
+}
+
+fn set_detected(offline: bool) {
+    if DETECTED.swap(offline, Ordering::Relaxed) != offline {
+        CHANGED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Called with the result of every metadata request, errors other than not being able to connect mean the
+/// network is there. Timeouts say nothing either way, a slow connection is still a connection
+pub fn report_request<T>(result: &Result<T, reqwest::Error>) {
+    match result {
+        Err(error) if error.is_connect() => {
+            if CONNECT_FAILURES.fetch_add(1, Ordering::Relaxed) + 1 >= FAILURES_UNTIL_OFFLINE {
+                set_detected(true);
+            }
+        },
+        Err(error) if error.is_timeout() => {},
+        Ok(_) | Err(_) => {
+            CONNECT_FAILURES.store(0, Ordering::Relaxed);
+            set_detected(false);
+        },
+    }
+}
+
+impl BackendState {
+    /// Keeps the frontend told whether the launcher is offline, checking every so often whether the connection has
+    /// come back
+    pub async fn watch_connectivity(self) {
+        self.check_connectivity().await;
+        self.send_offline_state();
+
+        let mut interval = tokio::time::interval(Duration::from_secs(5));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut since_check = Duration::ZERO;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {},
+                _ = self.shutdown.cancelled() => return,
+            }
+
+            since_check += interval.period();
+            if DETECTED.load(Ordering::Relaxed) && !FORCED.load(Ordering::Relaxed) && since_check >= RECHECK_INTERVAL {
+                since_check = Duration::ZERO;
+                self.check_connectivity().await;
+            }
+
+            if CHANGED.swap(false, Ordering::Relaxed) {
+                let offline = is_offline();
+                tracing::info!("Launcher is now {}", if offline { "offline" } else { "online" });
+                if offline {
+                    self.send.send_warning(rust_i18n::t!("notification_offline"));
+                } else {
+                    self.send.send_info(rust_i18n::t!("notification_online"));
+                }
+                self.send_offline_state();
+            }
+        }
+    }
+
+    async fn check_connectivity(&self) {
+        if FORCED.load(Ordering::Relaxed) {
+            return;
+        }
+        let url = crate::endpoints::resolve(schema::version_manifest::MOJANG_VERSION_MANIFEST_URL);
+        let request = self.http_client.head(&*url).timeout(Duration::from_secs(5));
+        report_request(&request.send().await);
+    }
+
+    pub fn send_offline_state(&self) {
+        let offline = is_offline();
+        let cached_versions = if offline {
+            self.cached_minecraft_versions()
+        } else {
+            Arc::from([])
+        };
+        self.send.send(MessageToFrontend::OfflineStateChanged { offline, cached_versions });
+    }
+
+    /// Versions that can be launched without the network because their version info has been downloaded before
+    fn cached_minecraft_versions(&self) -> Arc<[Ustr]> {
+        let Ok(manifest) = crate::read_json::<MinecraftVersionManifest>(&self.meta.version_manifest_cache_path()) else {
+            return Arc::from([]);
+        };
+        let by_sha1: FxHashMap<&str, Ustr> = manifest.versions.iter()
+            .map(|version| (version.sha1.as_str(), version.id))
+            .collect();
+
+        std::fs::read_dir(self.meta.version_info_cache_dir()).into_iter().flatten().flatten()
+            .filter_map(|entry| by_sha1.get(entry.file_name().to_str()?).copied())
+            .collect()
+    }
+}
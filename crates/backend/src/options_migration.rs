@@ -0,0 +1,185 @@
+use std::path::Path;
+
+use indexmap::IndexMap;
+use schema::version_manifest::MinecraftVersionManifest;
+
+use crate::BackendState;
+
+type Options = IndexMap<String, String>;
+
+/// A change to options.txt made by a Minecraft release that the game doesn't convert by itself
+struct OptionsMigration {
+    /// The first release that reads the new form
+    since: &'static str,
+    upgrade: fn(&mut Options),
+    /// Turns the new form back into the old one when going back to an earlier release
+    downgrade: Option<fn(&mut Options)>,
+}
+
+const MIGRATIONS: &[OptionsMigration] = &[
+    // Language codes became lowercase, e.g. en_US -> en_us
+    OptionsMigration {
+        since: "1.11",
+        upgrade: |options| {
+            if let Some(lang) = options.get_mut("lang") {
+                *lang = lang.to_ascii_lowercase();
+            }
+        },
+        downgrade: Some(|options| {
+            if let Some(lang) = options.get_mut("lang") && let Some((language, country)) = lang.split_once('_') {
+                *lang = format!("{language}_{}", country.to_ascii_uppercase());
+            }
+        }),
+    },
+    // The tutorial hints would otherwise be shown again to players who already know the game
+    OptionsMigration {
+        since: "1.14",
+        upgrade: |options| {
+            options.entry("tutorialStep".into()).or_insert_with(|| "none".into());
+        },
+        downgrade: None,
+    },
+    // Fancy graphics turned into a graphics mode with the extra "fabulous" level
+    OptionsMigration {
+        since: "1.16",
+        upgrade: |options| {
+            if let Some(fancy) = options.shift_remove("fancyGraphics") {
+                let mode = if fancy == "false" { "0" } else { "1" };
+                options.entry("graphicsMode".into()).or_insert_with(|| mode.into());
+            }
+        },
+        downgrade: Some(|options| {
+            if let Some(mode) = options.shift_remove("graphicsMode") {
+                let fancy = if mode == "0" { "false" } else { "true" };
+                options.entry("fancyGraphics".into()).or_insert_with(|| fancy.into());
+            }
+        }),
+    },
+    // Otherwise the accessibility onboarding is shown as if the game was started for the first time
+    OptionsMigration {
+        since: "1.19.3",
+        upgrade: |options| {
+            options.entry("onboardAccessibility".into()).or_insert_with(|| "false".into());
+        },
+        downgrade: None,
+    },
+];
+
+fn parse_options(content: &str) -> Options {
+    content.lines()
+        .filter_map(|line| line.trim_ascii().split_once(':'))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+fn write_options(options: &Options) -> String {
+    let mut content = String::new();
+    for (key, value) in options {
+        content.push_str(key);
+        content.push(':');
+        content.push_str(value);
+        content.push('\n');
+    }
+    content
+}
+
+/// Applies the migrations between `from` and `to`, in either direction. `released` orders versions by when they were
+/// released, migrations are skipped when a version is unknown. Returns whether anything changed
+fn migrate_options<K: Ord>(options: &mut Options, from: &str, to: &str, released: impl Fn(&str) -> Option<K>) -> bool {
+    let (Some(from), Some(to)) = (released(from), released(to)) else {
+        return false;
+    };
+    let before = options.clone();
+
+    if from < to {
+        for migration in MIGRATIONS {
+            if let Some(since) = released(migration.since) && from < since && since <= to {
+                (migration.upgrade)(options);
+            }
+        }
+    } else {
+        for migration in MIGRATIONS.iter().rev() {
+            if let Some(since) = released(migration.since) && to < since && since <= from
+                && let Some(downgrade) = migration.downgrade
+            {
+                downgrade(options);
+            }
+        }
+    }
+
+    *options != before
+}
+
+/// Migrates the options.txt in `dot_minecraft` from one version of Minecraft to another. Returns whether it changed
+fn migrate_file(dot_minecraft: &Path, manifest: &MinecraftVersionManifest, from: &str, to: &str) -> std::io::Result<bool> {
+    let path = dot_minecraft.join("options.txt");
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(error) => return Err(error),
+    };
+
+    let mut options = parse_options(&content);
+    let released = |id: &str| manifest.versions.iter().find(|version| version.id == id).map(|version| version.release_time);
+    if !migrate_options(&mut options, from, to, released) {
+        return Ok(false);
+    }
+
+    crate::write_safe(&path, write_options(&options).as_bytes())?;
+    Ok(true)
+}
+
+impl BackendState {
+    pub async fn migrate_options_txt(&self, dot_minecraft: &Path, from: &str, to: &str) {
+        if from == to {
+            return;
+        }
+        let Ok(manifest) = self.meta.fetch(&crate::metadata::items::MinecraftVersionManifestMetadataItem).await else {
+            tracing::warn!("Unable to migrate options.txt from {from} to {to}, the version manifest couldn't be loaded");
+            return;
+        };
+
+        match migrate_file(dot_minecraft, &manifest, from, to) {
+            Ok(true) => tracing::info!("Migrated options.txt in {dot_minecraft:?} from {from} to {to}"),
+            Ok(false) => {},
+            Err(error) => {
+                tracing::error!("Unable to migrate options.txt in {dot_minecraft:?}: {error}");
+                self.send.send_warning(format!("Unable to migrate game options to {to}: {error}"));
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERSIONS: &[&str] = &["1.8.9", "1.11", "1.12.2", "1.14", "1.16", "1.16.5", "1.19.3", "1.20.1"];
+
+    fn released(id: &str) -> Option<usize> {
+        VERSIONS.iter().position(|version| *version == id)
+    }
+
+    #[test]
+    fn upgrading_applies_migrations_in_between() {
+        let mut options = parse_options("lang:de_DE\nfancyGraphics:false\nfov:0.0\n");
+        assert!(migrate_options(&mut options, "1.8.9", "1.16.5", released));
+        assert_eq!(write_options(&options), "lang:de_de\nfov:0.0\ntutorialStep:none\ngraphicsMode:0\n");
+
+        // Already migrated options are left alone
+        assert!(!migrate_options(&mut options, "1.16", "1.16.5", released));
+    }
+
+    #[test]
+    fn downgrading_restores_the_old_form() {
+        let mut options = parse_options("lang:pt_br\ngraphicsMode:2\nonboardAccessibility:false\n");
+        assert!(migrate_options(&mut options, "1.20.1", "1.8.9", released));
+        assert_eq!(write_options(&options), "lang:pt_BR\nonboardAccessibility:false\nfancyGraphics:true\n");
+    }
+
+    #[test]
+    fn unknown_versions_are_not_migrated() {
+        let mut options = parse_options("lang:de_DE\n");
+        assert!(!migrate_options(&mut options, "1.12.2", "custom", released));
+    }
+}
@@ -0,0 +1,612 @@
+use std::{io::Read, path::{Path, PathBuf}, sync::Arc, time::{Duration, SystemTime}};
+
+use bridge::{
+    install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath, InstallTarget}, instance::InstanceID, safe_path::SafePath
+};
+use md5::Md5;
+use notify::RecursiveMode;
+use notify_debouncer_full::{DebounceEventResult, Debouncer, RecommendedCache};
+use rustc_hash::{FxHashMap, FxHashSet};
+use schema::{content::ContentSource, loader::Loader, modrinth::ModrinthSideRequirement, mrpack::ModrinthIndexJson};
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+use sha2::{Sha256, Sha512};
+
+use crate::BackendState;
+
+/// Saving in an editor often writes several files, so changes are collected for a moment before syncing
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(thiserror::Error, Debug)]
+pub enum PackWatchError {
+    #[error("Found neither a pack.toml (packwiz) nor a modrinth.index.json (unpacked .mrpack)")]
+    UnknownLayout,
+    #[error("Unable to parse {0}: {1}")]
+    Parse(Arc<str>, String),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Keeps the source folder watched for as long as it's alive
+pub struct PackWatch {
+    source: Arc<Path>,
+    _debouncer: Debouncer<notify::RecommendedWatcher, RecommendedCache>,
+}
+
+/// What has to happen for the instance to match the pack source
+#[derive(Debug, Default)]
+struct PackSyncPlan {
+    /// Files that are copied from the source because they're missing or differ
+    copies: Vec<(PathBuf, SafePath)>,
+    /// Files the pack downloads that aren't in place yet
+    downloads: Vec<(SafePath, ContentDownload)>,
+    /// Every path the pack puts into .minecraft, including the ones already up to date
+    paths: FxHashSet<SafePath>,
+}
+
+/// Hashes of files by path and hash format, kept between syncs so files that haven't changed aren't read again
+#[derive(Debug, Default)]
+struct HashCache {
+    hashes: FxHashMap<(PathBuf, Arc<str>), (u64, SystemTime, String)>,
+}
+
+impl HashCache {
+    /// The hash of the file, or None if it doesn't exist or the format isn't supported
+    fn hash(&mut self, path: &Path, hash_format: &str) -> Option<&str> {
+        let metadata = path.metadata().ok()?;
+        let modified = metadata.modified().ok()?;
+        let key = (path.to_path_buf(), Arc::from(hash_format));
+
+        let cached = self.hashes.get(&key).is_some_and(|(len, cached_modified, _)| *len == metadata.len() && *cached_modified == modified);
+        if !cached {
+            let mut bytes = Vec::new();
+            std::fs::File::open(path).ok()?.read_to_end(&mut bytes).ok()?;
+            let hash = match hash_format {
+                "sha1" => hex::encode(Sha1::digest(&bytes)),
+                "sha256" => hex::encode(Sha256::digest(&bytes)),
+                "sha512" => hex::encode(Sha512::digest(&bytes)),
+                "md5" => hex::encode(Md5::digest(&bytes)),
+                _ => return None,
+            };
+            self.hashes.insert(key.clone(), (metadata.len(), modified, hash));
+        }
+        self.hashes.get(&key).map(|(_, _, hash)| hash.as_str())
+    }
+}
+
+#[derive(Deserialize)]
+struct PackwizPack {
+    #[serde(default)]
+    index: Option<PackwizIndexLink>,
+}
+
+#[derive(Deserialize)]
+struct PackwizIndexLink {
+    file: Arc<str>,
+}
+
+#[derive(Deserialize)]
+struct PackwizIndex {
+    #[serde(default)]
+    files: Vec<PackwizIndexFile>,
+}
+
+#[derive(Deserialize)]
+struct PackwizIndexFile {
+    file: Arc<str>,
+    #[serde(default)]
+    metafile: bool,
+}
+
+#[derive(Deserialize)]
+struct PackwizMetafile {
+    filename: Arc<str>,
+    #[serde(default)]
+    side: Option<Arc<str>>,
+    download: PackwizDownload,
+    #[serde(default)]
+    update: Option<PackwizUpdate>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct PackwizDownload {
+    #[serde(default)]
+    url: Option<Arc<str>>,
+    hash_format: Arc<str>,
+    hash: Arc<str>,
+}
+
+#[derive(Deserialize)]
+struct PackwizUpdate {
+    #[serde(default)]
+    modrinth: Option<PackwizModrinthUpdate>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct PackwizModrinthUpdate {
+    mod_id: Arc<str>,
+    version: Arc<str>,
+}
+
+impl BackendState {
+    /// Starts or stops mirroring the instance's pack source to match its configuration
+    pub fn update_pack_watch(&self, id: InstanceID) {
+        // Syncing writes into the instance, which waits until the launcher directory is writable again
+        if crate::data_lock::is_read_only() {
+            return;
+        }
+        let source = {
+            let mut instance_state = self.instance_state.write();
+            let Some(instance) = instance_state.instances.get_mut(id) else {
+                self.pack_watches.lock().remove(&id);
+                return;
+            };
+            instance.configuration.get().pack_source.clone()
+        };
+
+        let mut pack_watches = self.pack_watches.lock();
+        let Some(source) = source else {
+            pack_watches.remove(&id);
+            return;
+        };
+        if pack_watches.get(&id).is_some_and(|watch| watch.source == source) {
+            return;
+        }
+
+        // A capacity of one is enough, a sync that's already queued picks up any further changes
+        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+        _ = sender.try_send(());
+
+        let debouncer = notify_debouncer_full::new_debouncer(DEBOUNCE, None, move |result: DebounceEventResult| {
+            if result.is_ok_and(|events| !events.is_empty()) {
+                _ = sender.try_send(());
+            }
+        });
+        let mut debouncer = match debouncer {
+            Ok(debouncer) => debouncer,
+            Err(error) => {
+                self.send.send_error(rust_i18n::t!("notification_pack_source_watch_failed", error = error));
+                return;
+            },
+        };
+        if let Err(error) = debouncer.watch(&source, RecursiveMode::Recursive) {
+            self.send.send_error(rust_i18n::t!("notification_pack_source_watch_path_failed", path = source.display(), error = error));
+            return;
+        }
+
+        tracing::info!("Watching pack source {} for instance {id:?}", source.display());
+        pack_watches.insert(id, PackWatch { source: source.clone(), _debouncer: debouncer });
+        tokio::task::spawn(self.clone().run_pack_watch(id, source, receiver));
+    }
+
+    async fn run_pack_watch(self, id: InstanceID, source: Arc<Path>, mut receiver: tokio::sync::mpsc::Receiver<()>) {
+        // Files are only removed if an earlier sync put them there, so nothing the game wrote is ever deleted
+        let mut synced = FxHashSet::default();
+        let mut hashes = HashCache::default();
+
+        while receiver.recv().await.is_some() {
+            let Some((name, dot_minecraft)) = self.instance_state.write().instances.get_mut(id)
+                .map(|instance| (instance.name, instance.dot_minecraft_path.clone()))
+            else {
+                self.pack_watches.lock().remove(&id);
+                return;
+            };
+
+            let (plan, returned_hashes) = {
+                let source = source.clone();
+                let dot_minecraft = dot_minecraft.clone();
+                tokio::task::spawn_blocking(move || {
+                    let plan = plan(&source, &dot_minecraft, &mut hashes);
+                    (plan, hashes)
+                }).await.unwrap()
+            };
+            hashes = returned_hashes;
+            let mut plan = match plan {
+                Ok(plan) => plan,
+                Err(error) => {
+                    self.send.send_error(rust_i18n::t!("notification_pack_source_sync_failed", name = name, error = error));
+                    continue;
+                },
+            };
+
+            let stale: Vec<SafePath> = synced.iter().filter(|path| !plan.paths.contains(*path)).cloned().collect();
+            let copies = plan.copies;
+            let changed = {
+                let dot_minecraft = dot_minecraft.clone();
+                tokio::task::spawn_blocking(move || apply_copies(&dot_minecraft, &copies, &stale)).await.unwrap()
+            };
+            let changed = match changed {
+                Ok(changed) => changed,
+                Err(error) => {
+                    self.send.send_error(rust_i18n::t!("notification_pack_source_sync_failed", name = name, error = error));
+                    continue;
+                },
+            };
+
+            let mut downloads = 0;
+            if !plan.downloads.is_empty() {
+                let download_paths: Vec<SafePath> = plan.downloads.iter().map(|(path, _)| path.clone()).collect();
+                let mut files: Vec<ContentInstallFile> = plan.downloads.into_iter().map(|(path, download)| ContentInstallFile {
+                    replace_old: None,
+                    path: ContentInstallPath::Safe(path),
+                    download,
+                    content_source: ContentSource::Manual,
+                }).collect();
+                // packwiz metafiles only have a hash
+                if let Err(error) = self.resolve_download_sizes(&mut files).await {
+                    self.send.send_error(rust_i18n::t!("notification_pack_source_sync_failed", name = name, error = error));
+                    continue;
+                }
+                let modal_action = self.send.start_background_task(rust_i18n::t!("progress_syncing_pack_source", name = name));
+                self.install_content(ContentInstall {
+                    target: InstallTarget::Instance(id),
+                    loader_hint: Loader::Unknown,
+                    version_hint: None,
+                    files: files.into(),
+                }, modal_action.clone()).await;
+
+                let error = modal_action.error.read().unwrap().clone();
+                if let Some(error) = error {
+                    self.send.send_error(rust_i18n::t!("notification_pack_source_sync_failed", name = name, error = error));
+                    // Downloads that failed are retried with the next change, until then they keep counting as
+                    // synced only if an earlier sync put them there
+                    for path in download_paths {
+                        if !synced.contains(&path) {
+                            plan.paths.remove(&path);
+                        }
+                    }
+                } else {
+                    downloads = download_paths.len();
+                }
+            }
+
+            synced = plan.paths;
+            if changed + downloads > 0 {
+                tracing::info!("Synced {} changes from {} into {name}", changed + downloads, source.display());
+                self.send.send_info(rust_i18n::t!("notification_pack_source_synced", count = changed + downloads, name = name));
+            }
+        }
+    }
+}
+
+/// packwiz packs list their files in the index named by pack.toml. Packs that haven't been refreshed yet don't have
+/// one, then every file that .packwizignore doesn't exclude is part of the pack
+fn plan(source: &Path, dot_minecraft: &Path, hashes: &mut HashCache) -> Result<PackSyncPlan, PackWatchError> {
+    let mut plan = PackSyncPlan::default();
+
+    if source.join("pack.toml").is_file() {
+        let pack: PackwizPack = read_toml(source, "pack.toml")?;
+        let index_file = pack.index.map(|index| index.file).unwrap_or_else(|| "index.toml".into());
+        let index_path = SafePath::new(&index_file).map(|index_file| index_file.to_path(source));
+
+        if let Some(index_path) = index_path.filter(|index_path| index_path.is_file()) {
+            let index: PackwizIndex = read_toml(source, &index_file)?;
+            for file in index.files {
+                let Some(relative) = SafePath::new(&file.file) else {
+                    continue;
+                };
+                let path = relative.to_path(source);
+                if path == index_path {
+                    continue;
+                }
+                if file.metafile {
+                    plan_packwiz_metafile(&mut plan, &path, relative.as_str(), dot_minecraft, hashes)?;
+                } else {
+                    plan_copy(&mut plan, path, relative.as_str(), dot_minecraft, hashes)?;
+                }
+            }
+        } else {
+            let ignore = PackwizIgnore::read(source);
+            for (path, relative) in walk(source)? {
+                if relative == "pack.toml" || *relative == *index_file || ignore.is_ignored(&relative) {
+                    continue;
+                }
+                if relative.ends_with(".pw.toml") {
+                    plan_packwiz_metafile(&mut plan, &path, &relative, dot_minecraft, hashes)?;
+                } else {
+                    plan_copy(&mut plan, path, &relative, dot_minecraft, hashes)?;
+                }
+            }
+        }
+    } else if source.join("modrinth.index.json").is_file() {
+        let index: ModrinthIndexJson = serde_json::from_slice(&std::fs::read(source.join("modrinth.index.json"))?)
+            .map_err(|error| PackWatchError::Parse("modrinth.index.json".into(), error.to_string()))?;
+        for file in index.files.iter() {
+            if file.env.is_some_and(|env| env.client == ModrinthSideRequirement::Unsupported) {
+                continue;
+            }
+            let (Some(path), Some(url)) = (SafePath::new(&file.path), file.downloads.first()) else {
+                continue;
+            };
+            let target = path.to_path(dot_minecraft);
+            if !file_matches(hashes, &target, "sha1", &file.hashes.sha1) {
+                plan.downloads.push((path.clone(), ContentDownload::Url {
+                    url: url.clone(),
+                    sha1: file.hashes.sha1.clone(),
+                    size: file.file_size,
+                }));
+            }
+            plan.paths.insert(path);
+        }
+        // Client overrides are applied last so they win over the shared ones
+        for overrides in ["overrides", "client-overrides"] {
+            let overrides = source.join(overrides);
+            if overrides.is_dir() {
+                for (path, relative) in walk(&overrides)? {
+                    plan_copy(&mut plan, path, &relative, dot_minecraft, hashes)?;
+                }
+            }
+        }
+    } else {
+        return Err(PackWatchError::UnknownLayout);
+    }
+
+    Ok(plan)
+}
+
+fn read_toml<T: serde::de::DeserializeOwned>(source: &Path, relative: &str) -> Result<T, PackWatchError> {
+    toml::from_str(&std::fs::read_to_string(source.join(relative))?)
+        .map_err(|error| PackWatchError::Parse(relative.into(), error.to_string()))
+}
+
+/// The patterns of a .packwizignore. Like a .gitignore, patterns without a slash match any file or folder name and
+/// patterns with one match from the root of the pack. Negated patterns aren't supported and are left out
+#[derive(Debug, Default)]
+struct PackwizIgnore {
+    patterns: Vec<(String, bool)>,
+}
+
+impl PackwizIgnore {
+    fn read(source: &Path) -> Self {
+        let Ok(contents) = std::fs::read_to_string(source.join(".packwizignore")) else {
+            return Self::default();
+        };
+        let patterns = contents.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
+            .map(|line| {
+                let line = line.trim_end_matches('/');
+                match line.strip_prefix('/') {
+                    Some(anchored) => (anchored.to_string(), true),
+                    None => (line.to_string(), line.contains('/')),
+                }
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// Whether the file or any folder it's in is ignored
+    fn is_ignored(&self, relative: &str) -> bool {
+        let components: Vec<&str> = relative.split('/').collect();
+        self.patterns.iter().any(|(pattern, anchored)| {
+            (1..=components.len()).any(|len| {
+                if *anchored {
+                    glob_matches(pattern.as_bytes(), components[..len].join("/").as_bytes())
+                } else {
+                    glob_matches(pattern.as_bytes(), components[len - 1].as_bytes())
+                }
+            })
+        })
+    }
+}
+
+/// Matches `*` within a path component, `**` across components and `?` for a single character
+fn glob_matches(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', rest @ ..] => {
+            let rest = rest.strip_prefix(b"/").unwrap_or(rest);
+            (0..=text.len()).any(|skip| glob_matches(rest, &text[skip..]))
+        },
+        [b'*', rest @ ..] => {
+            (0..=text.len()).take_while(|&skip| skip == 0 || text[skip - 1] != b'/').any(|skip| glob_matches(rest, &text[skip..]))
+        },
+        [b'?', rest @ ..] => text.first().is_some_and(|&c| c != b'/') && glob_matches(rest, &text[1..]),
+        [c, rest @ ..] => text.first() == Some(c) && glob_matches(rest, &text[1..]),
+    }
+}
+
+/// Lists the files in `root` with their path relative to it, skipping hidden files like `.git`
+fn walk(root: &Path) -> std::io::Result<Vec<(PathBuf, String)>> {
+    let mut files = Vec::new();
+    let mut pending = vec![(root.to_path_buf(), String::new())];
+    while let Some((dir, prefix)) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+                continue;
+            };
+            if name.starts_with('.') {
+                continue;
+            }
+            let relative = if prefix.is_empty() { name } else { format!("{prefix}/{name}") };
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                pending.push((entry.path(), relative));
+            } else {
+                files.push((entry.path(), relative));
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn plan_copy(plan: &mut PackSyncPlan, path: PathBuf, relative: &str, dot_minecraft: &Path, hashes: &mut HashCache) -> std::io::Result<()> {
+    let Some(safe_path) = SafePath::new(relative) else {
+        return Ok(());
+    };
+    if !same_contents(hashes, &path, &safe_path.to_path(dot_minecraft))? {
+        plan.copies.retain(|(_, existing)| existing != &safe_path);
+        plan.copies.push((path, safe_path.clone()));
+    }
+    plan.paths.insert(safe_path);
+    Ok(())
+}
+
+fn plan_packwiz_metafile(plan: &mut PackSyncPlan, path: &Path, relative: &str, dot_minecraft: &Path, hashes: &mut HashCache) -> Result<(), PackWatchError> {
+    let metafile: PackwizMetafile = toml::from_str(&std::fs::read_to_string(path)?)
+        .map_err(|error| PackWatchError::Parse(relative.into(), error.to_string()))?;
+    if metafile.side.as_deref() == Some("server") {
+        return Ok(());
+    }
+
+    let folder = relative.rsplit_once('/').map(|(folder, _)| folder);
+    let target = match folder {
+        Some(folder) => format!("{folder}/{}", metafile.filename),
+        None => metafile.filename.to_string(),
+    };
+    let Some(target) = SafePath::new(&target) else {
+        return Ok(());
+    };
+
+    let download = &metafile.download;
+    if !file_matches(hashes, &target.to_path(dot_minecraft), &download.hash_format, &download.hash) {
+        let content_download = if let Some(url) = download.url.clone().filter(|_| &*download.hash_format == "sha1") {
+            ContentDownload::Url { url, sha1: download.hash.clone(), size: 0 }
+        } else if let Some(modrinth) = metafile.update.and_then(|update| update.modrinth) {
+            ContentDownload::Modrinth { project_id: modrinth.mod_id, version_id: Some(modrinth.version) }
+        } else {
+            tracing::warn!("Skipping {relative}, it has neither a SHA-1 download nor a Modrinth version");
+            return Ok(());
+        };
+        plan.downloads.push((target.clone(), content_download));
+    }
+    plan.paths.insert(target);
+    Ok(())
+}
+
+fn same_contents(hashes: &mut HashCache, source: &Path, target: &Path) -> std::io::Result<bool> {
+    let Ok(target_metadata) = target.metadata() else {
+        return Ok(false);
+    };
+    if source.metadata()?.len() != target_metadata.len() {
+        return Ok(false);
+    }
+    let source_hash = hashes.hash(source, "sha1").map(str::to_owned);
+    Ok(source_hash.is_some() && hashes.hash(target, "sha1").map(str::to_owned) == source_hash)
+}
+
+/// Whether the file exists with the expected hash. Hash formats that can't be checked only require the file to exist
+fn file_matches(hashes: &mut HashCache, path: &Path, hash_format: &str, expected: &str) -> bool {
+    if !path.is_file() {
+        return false;
+    }
+    if !matches!(hash_format, "sha1" | "sha256" | "sha512" | "md5") {
+        return true;
+    }
+    hashes.hash(path, hash_format).is_some_and(|actual| actual.eq_ignore_ascii_case(expected))
+}
+
+fn apply_copies(dot_minecraft: &Path, copies: &[(PathBuf, SafePath)], stale: &[SafePath]) -> std::io::Result<usize> {
+    crate::data_lock::check_writable(dot_minecraft)?;
+    for (source, target) in copies {
+        let target = target.to_path(dot_minecraft);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(source, target)?;
+    }
+    let mut removed = 0;
+    for path in stale {
+        match std::fs::remove_file(path.to_path(dot_minecraft)) {
+            Ok(()) => removed += 1,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {},
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(copies.len() + removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::TempDir;
+
+    #[test]
+    fn packwiz_copies_changed_files_and_downloads_missing_mods() {
+        let dir = TempDir::new();
+        let source = dir.path().join("pack");
+        let dot_minecraft = dir.path().join(".minecraft");
+        std::fs::create_dir_all(source.join("mods")).unwrap();
+        std::fs::create_dir_all(source.join("config")).unwrap();
+        std::fs::create_dir_all(dot_minecraft.join("config")).unwrap();
+
+        std::fs::write(source.join("pack.toml"), "name = \"Test\"").unwrap();
+        std::fs::write(source.join("config").join("changed.txt"), "new").unwrap();
+        std::fs::write(source.join("config").join("same.txt"), "same").unwrap();
+        std::fs::write(dot_minecraft.join("config").join("changed.txt"), "old").unwrap();
+        std::fs::write(dot_minecraft.join("config").join("same.txt"), "same").unwrap();
+        std::fs::write(source.join("mods").join("sodium.pw.toml"), r#"
+            name = "Sodium"
+            filename = "sodium.jar"
+            side = "client"
+
+            [download]
+            url = "https://cdn.modrinth.com/sodium.jar"
+            hash-format = "sha1"
+            hash = "0000000000000000000000000000000000000000"
+        "#).unwrap();
+
+        let plan = plan(&source, &dot_minecraft, &mut HashCache::default()).unwrap();
+
+        let copies: Vec<&str> = plan.copies.iter().map(|(_, path)| path.as_str()).collect();
+        assert_eq!(copies, ["config/changed.txt"]);
+        assert_eq!(plan.downloads.len(), 1);
+        assert_eq!(plan.downloads[0].0.as_str(), "mods/sodium.jar");
+        assert_eq!(plan.paths.len(), 3);
+    }
+
+    #[test]
+    fn unknown_layouts_are_rejected() {
+        let dir = TempDir::new();
+        assert!(matches!(plan(dir.path(), &dir.path().join(".minecraft"), &mut HashCache::default()), Err(PackWatchError::UnknownLayout)));
+    }
+
+    #[test]
+    fn packwiz_index_lists_the_files_of_the_pack() {
+        let dir = TempDir::new();
+        let source = dir.path().join("pack");
+        let dot_minecraft = dir.path().join(".minecraft");
+        std::fs::create_dir_all(source.join("config")).unwrap();
+
+        std::fs::write(source.join("pack.toml"), "name = \"Test\"\n[index]\nfile = \"index.toml\"").unwrap();
+        std::fs::write(source.join("index.toml"), "hash-format = \"sha256\"\n[[files]]\nfile = \"config/listed.txt\"\nhash = \"\"").unwrap();
+        std::fs::write(source.join("config").join("listed.txt"), "listed").unwrap();
+        std::fs::write(source.join("README.md"), "not part of the pack").unwrap();
+
+        let plan = plan(&source, &dot_minecraft, &mut HashCache::default()).unwrap();
+
+        let copies: Vec<&str> = plan.copies.iter().map(|(_, path)| path.as_str()).collect();
+        assert_eq!(copies, ["config/listed.txt"]);
+        assert_eq!(plan.paths.len(), 1);
+    }
+
+    #[test]
+    fn packwizignore_excludes_files_without_an_index() {
+        let dir = TempDir::new();
+        let source = dir.path().join("pack");
+        std::fs::create_dir_all(source.join("config")).unwrap();
+        std::fs::create_dir_all(source.join("build")).unwrap();
+
+        std::fs::write(source.join("pack.toml"), "name = \"Test\"").unwrap();
+        std::fs::write(source.join(".packwizignore"), "# Not for players\n*.md\n/build/\n").unwrap();
+        std::fs::write(source.join("config").join("kept.txt"), "kept").unwrap();
+        std::fs::write(source.join("config").join("notes.md"), "notes").unwrap();
+        std::fs::write(source.join("build").join("pack.zip"), "zip").unwrap();
+
+        let plan = plan(&source, &dir.path().join(".minecraft"), &mut HashCache::default()).unwrap();
+
+        let copies: Vec<&str> = plan.copies.iter().map(|(_, path)| path.as_str()).collect();
+        assert_eq!(copies, ["config/kept.txt"]);
+    }
+
+    #[test]
+    fn glob_patterns_stay_within_components() {
+        assert!(glob_matches(b"*.md", b"notes.md"));
+        assert!(!glob_matches(b"config/*.md", b"config/docs/notes.md"));
+        assert!(glob_matches(b"config/**/*.md", b"config/docs/notes.md"));
+        assert!(glob_matches(b"mod?.jar", b"mod1.jar"));
+    }
+}
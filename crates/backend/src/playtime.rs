@@ -0,0 +1,359 @@
+use std::{collections::{HashMap, HashSet}, path::Path, sync::Arc, time::{Duration, Instant}};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use ustr::Ustr;
+use uuid::Uuid;
+
+use crate::persistent::Persistent;
+
+/// Minutes of remaining playtime at which a reminder is shown, 0 meaning the limit was reached
+const REMINDER_MINUTES: &[i64] = &[15, 5, 0];
+
+/// Playtime is written to disk at most this often while games are running
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PlaytimeLedger {
+    #[serde(default)]
+    pub date: Option<NaiveDate>,
+    /// Seconds played today, keyed by [`instance_key`]
+    #[serde(default)]
+    pub seconds_by_instance: HashMap<Arc<str>, u64>,
+    /// Instances that were allowed to go over the limit for the rest of the day
+    #[serde(default)]
+    pub overridden: HashSet<Arc<str>>,
+    /// Seconds played since the instance was created, kept across days
+    #[serde(default)]
+    pub total_seconds_by_instance: HashMap<Arc<str>, u64>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlaytimeLimits {
+    /// Daily limit for the instance itself, in minutes
+    pub instance: Option<u32>,
+    /// Daily limit across all instances, in minutes
+    pub global: Option<u32>,
+}
+
+pub struct RunningInstance {
+    pub key: Arc<str>,
+    pub name: Ustr,
+    pub limits: PlaytimeLimits,
+}
+
+pub struct PlaytimeTracker {
+    ledger: Persistent<PlaytimeLedger>,
+    unsaved: HashMap<Arc<str>, Duration>,
+    last_tick: Instant,
+    last_save: Instant,
+    reminded: HashSet<(Arc<str>, i64)>,
+}
+
+/// Ledger key of an instance, its stable uuid so that renaming the instance folder keeps its playtime
+pub fn instance_key(uuid: Uuid) -> Arc<str> {
+    uuid.to_string().into()
+}
+
+fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+impl PlaytimeTracker {
+    pub fn load(path: Arc<Path>) -> Self {
+        Self {
+            ledger: Persistent::load(path),
+            unsaved: HashMap::new(),
+            last_tick: Instant::now(),
+            last_save: Instant::now(),
+            reminded: HashSet::new(),
+        }
+    }
+
+    /// Starts a fresh ledger when the day has changed. Time that wasn't saved yet was played before midnight, so it is
+    /// written to the old day and the totals first
+    fn roll_over(&mut self) {
+        let today = today();
+        if self.ledger.get().date == Some(today) {
+            return;
+        }
+        self.flush_unsaved();
+        self.reminded.clear();
+        self.ledger.modify(|ledger| {
+            *ledger = PlaytimeLedger {
+                date: Some(today),
+                total_seconds_by_instance: std::mem::take(&mut ledger.total_seconds_by_instance),
+                ..Default::default()
+            };
+        });
+    }
+
+    pub fn played_seconds(&mut self, key: &str) -> u64 {
+        self.roll_over();
+        let saved = self.ledger.get().seconds_by_instance.get(key).copied().unwrap_or(0);
+        saved + self.unsaved.get(key).map(Duration::as_secs).unwrap_or(0)
+    }
+
+    /// Seconds played across all days
+    pub fn total_played_seconds(&mut self, key: &str) -> u64 {
+        let saved = self.ledger.get().total_seconds_by_instance.get(key).copied().unwrap_or(0);
+        saved + self.unsaved.get(key).map(Duration::as_secs).unwrap_or(0)
+    }
+
+    /// Adds playtime that was recorded somewhere else to the total
+    pub fn add_total(&mut self, key: Arc<str>, seconds: u64) {
+        self.ledger.modify(|ledger| {
+            *ledger.total_seconds_by_instance.entry(key).or_default() += seconds;
+        });
+    }
+
+    fn total_seconds(&mut self) -> u64 {
+        self.roll_over();
+        let saved: u64 = self.ledger.get().seconds_by_instance.values().sum();
+        saved + self.unsaved.values().map(Duration::as_secs).sum::<u64>()
+    }
+
+    /// Seconds that can still be played today, None if there is no limit
+    pub fn remaining_seconds(&mut self, key: &str, limits: PlaytimeLimits) -> Option<i64> {
+        let instance_remaining = limits.instance.map(|limit| limit as i64 * 60 - self.played_seconds(key) as i64);
+        let global_remaining = limits.global.map(|limit| limit as i64 * 60 - self.total_seconds() as i64);
+        match (instance_remaining, global_remaining) {
+            (Some(instance), Some(global)) => Some(instance.min(global)),
+            (instance, global) => instance.or(global),
+        }
+    }
+
+    pub fn is_overridden(&mut self, key: &str) -> bool {
+        self.roll_over();
+        self.ledger.get().overridden.contains(key)
+    }
+
+    pub fn set_overridden(&mut self, key: Arc<str>) {
+        self.roll_over();
+        self.ledger.modify(|ledger| {
+            ledger.overridden.insert(key);
+        });
+    }
+
+    /// Moves playtime recorded under an older key, e.g. the folder name used by earlier versions
+    pub fn migrate_key(&mut self, old: &str, new: Arc<str>) {
+        if let Some(duration) = self.unsaved.remove(old) {
+            *self.unsaved.entry(new.clone()).or_default() += duration;
+        }
+        let ledger = self.ledger.get();
+        if !ledger.seconds_by_instance.contains_key(old) && !ledger.overridden.contains(old)
+            && !ledger.total_seconds_by_instance.contains_key(old)
+        {
+            return;
+        }
+        self.ledger.modify(|ledger| {
+            if let Some(seconds) = ledger.seconds_by_instance.remove(old) {
+                *ledger.seconds_by_instance.entry(new.clone()).or_default() += seconds;
+            }
+            if let Some(seconds) = ledger.total_seconds_by_instance.remove(old) {
+                *ledger.total_seconds_by_instance.entry(new.clone()).or_default() += seconds;
+            }
+            if ledger.overridden.remove(old) {
+                ledger.overridden.insert(new);
+            }
+        });
+    }
+
+    /// Adds the time since the last tick to every running instance, returning the reminders that are due
+    pub fn tick(&mut self, running: &[RunningInstance]) -> Vec<String> {
+        self.tick_at(Instant::now(), running)
+    }
+
+    fn tick_at(&mut self, now: Instant, running: &[RunningInstance]) -> Vec<String> {
+        let elapsed = now - self.last_tick;
+        self.last_tick = now;
+
+        self.roll_over();
+        for instance in running {
+            *self.unsaved.entry(instance.key.clone()).or_default() += elapsed;
+        }
+
+        let mut reminders = Vec::new();
+        for instance in running {
+            if self.is_overridden(&instance.key) {
+                continue;
+            }
+            let Some(remaining) = self.remaining_seconds(&instance.key, instance.limits) else {
+                continue;
+            };
+            // Only the closest threshold is shown, so going over several at once doesn't stack reminders
+            let Some(threshold) = REMINDER_MINUTES.iter().copied().filter(|minutes| remaining <= minutes * 60).min() else {
+                continue;
+            };
+            if !self.reminded.insert((instance.key.clone(), threshold)) {
+                continue;
+            }
+            let name = instance.name;
+            if threshold == 0 {
+                reminders.push(rust_i18n::t!("notification_playtime_limit_reached", name = name).into_owned());
+            } else {
+                reminders.push(rust_i18n::t!("notification_playtime_minutes_left", name = name, minutes = remaining.div_ceil(60)).into_owned());
+            }
+        }
+
+        if !self.unsaved.is_empty() && (running.is_empty() || now - self.last_save >= SAVE_INTERVAL) {
+            self.save_at(now);
+        }
+
+        reminders
+    }
+
+    pub fn save(&mut self) {
+        self.save_at(Instant::now());
+    }
+
+    fn save_at(&mut self, now: Instant) {
+        self.last_save = now;
+        self.flush_unsaved();
+    }
+
+    fn flush_unsaved(&mut self) {
+        let unsaved = std::mem::take(&mut self.unsaved);
+        if unsaved.is_empty() {
+            return;
+        }
+        self.ledger.modify(|ledger| {
+            for (key, duration) in unsaved {
+                *ledger.seconds_by_instance.entry(key.clone()).or_default() += duration.as_secs();
+                *ledger.total_seconds_by_instance.entry(key).or_default() += duration.as_secs();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::TempDir;
+
+    fn running(key: &Arc<str>, limits: PlaytimeLimits) -> RunningInstance {
+        RunningInstance {
+            key: key.clone(),
+            name: Ustr::from("Test"),
+            limits,
+        }
+    }
+
+    #[test]
+    fn tick_accumulates_and_saves_when_stopped() {
+        let dir = TempDir::new();
+        let path: Arc<Path> = dir.path().join("playtime.json").into();
+        let key = instance_key(Uuid::new_v4());
+
+        let mut tracker = PlaytimeTracker::load(path.clone());
+        let start = tracker.last_tick;
+        let playing = [running(&key, PlaytimeLimits::default())];
+        tracker.tick_at(start + Duration::from_secs(30), &playing);
+        tracker.tick_at(start + Duration::from_secs(45), &playing);
+        assert_eq!(tracker.played_seconds(&key), 45);
+
+        // Nothing is running anymore, so the time is written to disk
+        tracker.tick_at(start + Duration::from_secs(50), &[]);
+        let mut reloaded = PlaytimeTracker::load(path);
+        assert_eq!(reloaded.played_seconds(&key), 45);
+    }
+
+    #[test]
+    fn total_survives_the_day_changing() {
+        let dir = TempDir::new();
+        let path: Arc<Path> = dir.path().join("playtime.json").into();
+        let key = instance_key(Uuid::new_v4());
+
+        let mut tracker = PlaytimeTracker::load(path.clone());
+        tracker.add_total(key.clone(), 600);
+        let start = tracker.last_tick;
+        tracker.tick_at(start + Duration::from_secs(90), &[running(&key, PlaytimeLimits::default())]);
+        assert_eq!(tracker.total_played_seconds(&key), 690);
+
+        // The game is still running when the launcher exits
+        tracker.save();
+        tracker.ledger.modify(|ledger| {
+            ledger.date = Some(today() - chrono::Days::new(1));
+        });
+
+        let mut reloaded = PlaytimeTracker::load(path);
+        assert_eq!(reloaded.played_seconds(&key), 0);
+        assert_eq!(reloaded.total_played_seconds(&key), 690);
+    }
+
+    #[test]
+    fn sessions_over_midnight_keep_their_playtime() {
+        let dir = TempDir::new();
+        let key = instance_key(Uuid::new_v4());
+
+        let mut tracker = PlaytimeTracker::load(dir.path().join("playtime.json").into());
+        let start = tracker.last_tick;
+        let playing = [running(&key, PlaytimeLimits::default())];
+        tracker.tick_at(start + Duration::from_secs(30), &playing);
+
+        // Midnight passes while the game is running, before the last 30 seconds were saved
+        tracker.ledger.modify(|ledger| {
+            ledger.date = Some(today() - chrono::Days::new(1));
+        });
+        tracker.tick_at(start + Duration::from_secs(50), &playing);
+
+        assert_eq!(tracker.played_seconds(&key), 20);
+        assert_eq!(tracker.total_played_seconds(&key), 50);
+    }
+
+    #[test]
+    fn reminders_are_shown_once_per_threshold() {
+        let dir = TempDir::new();
+        let key = instance_key(Uuid::new_v4());
+        let limits = PlaytimeLimits { instance: Some(20), global: None };
+
+        let mut tracker = PlaytimeTracker::load(dir.path().join("playtime.json").into());
+        let start = tracker.last_tick;
+        let playing = [running(&key, limits)];
+        assert!(tracker.tick_at(start + Duration::from_secs(60), &playing).is_empty());
+        assert_eq!(tracker.tick_at(start + Duration::from_secs(6 * 60), &playing).len(), 1);
+        assert!(tracker.tick_at(start + Duration::from_secs(7 * 60), &playing).is_empty());
+        assert_eq!(tracker.remaining_seconds(&key, limits), Some(13 * 60));
+
+        tracker.tick_at(start + Duration::from_secs(20 * 60), &playing);
+        assert_eq!(tracker.remaining_seconds(&key, limits), Some(0));
+        tracker.set_overridden(key.clone());
+        assert!(tracker.tick_at(start + Duration::from_secs(25 * 60), &playing).is_empty());
+    }
+
+    #[test]
+    fn global_limit_counts_every_instance() {
+        let dir = TempDir::new();
+        let first = instance_key(Uuid::new_v4());
+        let second = instance_key(Uuid::new_v4());
+        let limits = PlaytimeLimits { instance: None, global: Some(10) };
+
+        let mut tracker = PlaytimeTracker::load(dir.path().join("playtime.json").into());
+        let start = tracker.last_tick;
+        tracker.tick_at(start + Duration::from_secs(4 * 60), &[running(&first, limits), running(&second, limits)]);
+        assert_eq!(tracker.remaining_seconds(&first, limits), Some(2 * 60));
+        assert_eq!(tracker.remaining_seconds(&second, limits), Some(2 * 60));
+    }
+
+    #[test]
+    fn migrate_key_moves_folder_name_entries() {
+        let dir = TempDir::new();
+        let path: Arc<Path> = dir.path().join("playtime.json").into();
+        let key = instance_key(Uuid::new_v4());
+
+        let mut tracker = PlaytimeTracker::load(path.clone());
+        tracker.roll_over();
+        tracker.ledger.modify(|ledger| {
+            ledger.seconds_by_instance.insert("My Instance".into(), 120);
+            ledger.overridden.insert("My Instance".into());
+        });
+
+        tracker.migrate_key("My Instance", key.clone());
+        assert_eq!(tracker.played_seconds("My Instance"), 0);
+        assert_eq!(tracker.played_seconds(&key), 120);
+        assert!(tracker.is_overridden(&key));
+
+        let mut reloaded = PlaytimeTracker::load(path);
+        assert_eq!(reloaded.played_seconds(&key), 120);
+    }
+}
@@ -0,0 +1,186 @@
+use std::{process::{Child, Command, ExitStatus}, sync::Arc, time::SystemTime};
+
+use bridge::instance::InstanceProcessInfo;
+
+use crate::{lan::{LanWatch, PortForward}, lockfile::Lockfile};
+
+#[derive(Debug)]
+pub struct GameProcess {
+    child: Child,
+    started_at: SystemTime,
+    pub lan_watch: Arc<LanWatch>,
+    /// Set while the port of the game's LAN world is forwarded through UPnP
+    pub port_forward: Option<PortForward>,
+    /// Memory the game may use for its heap, counted against the memory budget of the launch limits
+    pub max_heap_mib: u64,
+    /// Keeps other launchers from starting the instance until this process exits
+    pub instance_lock: Option<Lockfile>,
+    #[cfg(windows)]
+    job: Option<windows::JobObject>,
+}
+
+/// Spawns the game into its own process group (unix) so that the JVM and anything
+/// it starts can be terminated together
+pub fn configure_command(command: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = command;
+    }
+}
+
+impl GameProcess {
+    pub fn new(child: Child, kill_on_launcher_exit: bool, lan_watch: Arc<LanWatch>, max_heap_mib: u64) -> Self {
+        #[cfg(windows)]
+        let job = match windows::JobObject::create(kill_on_launcher_exit) {
+            Ok(job) => {
+                if let Err(error) = job.assign(&child) {
+                    tracing::error!("Unable to assign game process to job object: {error}");
+                }
+                Some(job)
+            },
+            Err(error) => {
+                tracing::error!("Unable to create job object: {error}");
+                None
+            },
+        };
+        #[cfg(not(windows))]
+        let _ = kill_on_launcher_exit;
+
+        Self {
+            child,
+            started_at: SystemTime::now(),
+            lan_watch,
+            port_forward: None,
+            max_heap_mib,
+            instance_lock: None,
+            #[cfg(windows)]
+            job,
+        }
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.child.id()
+    }
+
+    pub fn info(&self) -> InstanceProcessInfo {
+        InstanceProcessInfo {
+            pid: self.pid(),
+            started_at: self.started_at,
+            lan: crate::lan::lan_game_info(&self.lan_watch, self.port_forward.as_ref()),
+        }
+    }
+
+    /// Returns the exit status if the process is no longer alive
+    pub fn try_wait(&mut self) -> Option<ExitStatus> {
+        match self.child.try_wait() {
+            Ok(None) => None,
+            Ok(Some(status)) => Some(status),
+            Err(error) => {
+                tracing::error!("Unable to query game process {}: {error}", self.pid());
+                Some(ExitStatus::default())
+            },
+        }
+    }
+
+    /// Kills the game process along with every process it has spawned
+    pub fn kill(&mut self) -> std::io::Result<()> {
+        #[cfg(unix)]
+        {
+            // The game is the leader of its own process group, so the pgid is equal to the pid
+            let pgid = self.pid() as libc::pid_t;
+            if unsafe { libc::killpg(pgid, libc::SIGKILL) } != 0 {
+                let error = std::io::Error::last_os_error();
+                if error.raw_os_error() != Some(libc::ESRCH) {
+                    return Err(error);
+                }
+            }
+        }
+
+        #[cfg(windows)]
+        if let Some(job) = &self.job {
+            job.terminate()?;
+        }
+
+        match self.child.kill() {
+            Ok(()) => {},
+            Err(error) if error.kind() == std::io::ErrorKind::InvalidInput => {}, // Already exited
+            Err(error) => return Err(error),
+        }
+        let _ = self.child.wait();
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::{os::windows::io::AsRawHandle, process::Child};
+
+    use windows_sys::Win32::{
+        Foundation::{CloseHandle, HANDLE},
+        System::JobObjects::{
+            AssignProcessToJobObject, CreateJobObjectW, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+            JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE, JobObjectExtendedLimitInformation, SetInformationJobObject,
+            TerminateJobObject,
+        },
+    };
+
+    #[derive(Debug)]
+    pub struct JobObject(HANDLE);
+
+    // Job object handles may be used from any thread
+    unsafe impl Send for JobObject {}
+    unsafe impl Sync for JobObject {}
+
+    impl JobObject {
+        pub fn create(kill_on_close: bool) -> std::io::Result<Self> {
+            let handle = unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) };
+            if handle.is_null() {
+                return Err(std::io::Error::last_os_error());
+            }
+            let job = Self(handle);
+
+            if kill_on_close {
+                let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+                info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+                let result = unsafe {
+                    SetInformationJobObject(
+                        job.0,
+                        JobObjectExtendedLimitInformation,
+                        &info as *const _ as *const _,
+                        std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                    )
+                };
+                if result == 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+
+            Ok(job)
+        }
+
+        pub fn assign(&self, child: &Child) -> std::io::Result<()> {
+            if unsafe { AssignProcessToJobObject(self.0, child.as_raw_handle() as HANDLE) } == 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        }
+
+        pub fn terminate(&self) -> std::io::Result<()> {
+            if unsafe { TerminateJobObject(self.0, 1) } == 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for JobObject {
+        fn drop(&mut self) {
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::path::{Path, PathBuf};
+
+/// Folder next to the mods folder that holds the mods while the game runs in safe mode
+pub fn aside_path(mods_dir: &Path) -> PathBuf {
+    mods_dir.with_file_name(".pandora.safe_mode_mods")
+}
+
+/// Moves the whole mods folder aside in a single rename and leaves a new one in its place, containing only the
+/// files named in `keep`
+pub fn move_mods_aside(mods_dir: &Path, keep: &[String]) -> std::io::Result<()> {
+    // Mods left aside by a previous session that didn't get to restore them have to go back first
+    restore_mods(mods_dir)?;
+
+    let aside = aside_path(mods_dir);
+    match std::fs::rename(mods_dir, &aside) {
+        Ok(()) => {},
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error),
+    }
+    std::fs::create_dir_all(mods_dir)?;
+
+    for file_name in keep {
+        let from = aside.join(file_name);
+        if from.exists() {
+            std::fs::rename(from, mods_dir.join(file_name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Puts mods moved aside by [`move_mods_aside`] back, returning whether there were any. Files added to the mods
+/// folder in the meantime are kept, unless they have the same name as one of the moved mods
+pub fn restore_mods(mods_dir: &Path) -> std::io::Result<bool> {
+    let aside = aside_path(mods_dir);
+    if !aside.is_dir() {
+        return Ok(false);
+    }
+
+    if let Ok(read_dir) = std::fs::read_dir(mods_dir) {
+        for entry in read_dir {
+            let entry = entry?;
+            let target = aside.join(entry.file_name());
+            if !target.exists() {
+                std::fs::rename(entry.path(), target)?;
+            }
+        }
+        std::fs::remove_dir_all(mods_dir)?;
+    }
+
+    std::fs::rename(&aside, mods_dir)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::TempDir;
+
+    #[test]
+    fn mods_are_moved_aside_and_restored() {
+        let dir = TempDir::new();
+        let mods_dir = dir.path().join("mods");
+        std::fs::create_dir_all(&mods_dir).unwrap();
+        std::fs::write(mods_dir.join("sodium.jar"), b"mod").unwrap();
+        std::fs::write(mods_dir.join("iris.jar"), b"mod").unwrap();
+
+        move_mods_aside(&mods_dir, &["iris.jar".to_string()]).unwrap();
+        assert!(mods_dir.is_dir());
+        assert!(!mods_dir.join("sodium.jar").exists());
+        assert!(mods_dir.join("iris.jar").exists());
+
+        std::fs::write(mods_dir.join("lithium.jar"), b"mod").unwrap();
+        assert!(restore_mods(&mods_dir).unwrap());
+        assert!(mods_dir.join("sodium.jar").exists());
+        assert!(mods_dir.join("iris.jar").exists());
+        assert!(mods_dir.join("lithium.jar").exists());
+        assert!(!restore_mods(&mods_dir).unwrap());
+    }
+}
@@ -0,0 +1,57 @@
+use std::{path::Path, sync::Arc, time::Duration};
+
+use bridge::{instance::InstanceID, message::MessageToFrontend};
+
+use crate::{BackendState, WatchTarget};
+
+/// The game may still be writing the screenshot when it first shows up
+const WRITE_GRACE_PERIOD: Duration = Duration::from_millis(500);
+
+fn is_screenshot(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("png"))
+}
+
+impl BackendState {
+    /// Starts watching the screenshots folder of an instance that was just launched
+    pub fn watch_screenshots(&self, id: InstanceID) {
+        let Some(screenshots_dir) = self.instance_state.write().instances.get_mut(id).map(|instance| {
+            instance.notified_screenshots.clear();
+            instance.dot_minecraft_path.join("screenshots")
+        }) else {
+            return;
+        };
+
+        if let Err(error) = crate::data_lock::check_writable(&screenshots_dir).and_then(|_| std::fs::create_dir_all(&screenshots_dir)) {
+            tracing::warn!("Unable to create screenshots folder: {error}");
+            return;
+        }
+        self.file_watching.write().watch_filesystem(screenshots_dir.into(), WatchTarget::InstanceScreenshotsDir { id });
+    }
+
+    /// Shows screenshots taken while the instance is running. The watch stays after the game exits, so changes made
+    /// to the folder afterwards are ignored here
+    pub fn on_screenshot_changed(&self, id: InstanceID, path: &Arc<Path>) {
+        if !is_screenshot(path) {
+            return;
+        }
+        let name = {
+            let mut instance_state = self.instance_state.write();
+            let Some(instance) = instance_state.instances.get_mut(id) else {
+                return;
+            };
+            if instance.process.is_none() || !instance.notified_screenshots.insert(path.clone()) {
+                return;
+            }
+            instance.name
+        };
+
+        let send = self.send.clone();
+        let path = path.clone();
+        tokio::task::spawn(async move {
+            tokio::time::sleep(WRITE_GRACE_PERIOD).await;
+            if path.is_file() {
+                send.send(MessageToFrontend::ScreenshotTaken { id, name, path });
+            }
+        });
+    }
+}
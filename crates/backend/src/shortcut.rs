@@ -3,61 +3,70 @@ use std::path::Path;
 use std::path::PathBuf;
 
 #[cfg(target_os = "linux")]
-pub fn create_shortcut(mut path: PathBuf, name: &str, bin: &Path, args: &[&str]) {
-    log::info!("Creating linux shortcut at {:?}", path);
+pub fn create_shortcut(mut path: PathBuf, name: &str, bin: &Path, args: &[&str]) -> std::io::Result<PathBuf> {
+    tracing::info!("Creating linux shortcut at {:?}", path);
 
     if !has_extension(&path, "desktop") {
         path.add_extension("desktop");
     }
 
-    let Some(bin) = bin.to_str() else {
-        return;
-    };
+    let bin = bin_str(bin)?;
     let exec = shell_words::join(std::iter::once(bin).chain(args.iter().map(|s| *s)));
 
-    _ = std::fs::write(&path, format!(r#"[Desktop Entry]
+    std::fs::write(&path, format!(r#"[Desktop Entry]
 Type=Application
 Version=1.0
 Name={name}
 Exec=sh -c "{exec}"
 Categories=Games;Minecraft;Launcher;
-"#).as_bytes());
+"#).as_bytes())?;
 
     use std::os::unix::fs::PermissionsExt;
-    let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755));
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    Ok(path)
 }
 
 #[cfg(target_os = "windows")]
-pub fn create_shortcut(mut path: PathBuf, name: &str, bin: &Path, args: &[&str]) {
-    log::info!("Creating windows shortcut at {:?}", path);
+pub fn create_shortcut(mut path: PathBuf, name: &str, bin: &Path, args: &[&str]) -> std::io::Result<PathBuf> {
+    tracing::info!("Creating windows shortcut at {:?}", path);
 
     if !has_extension(&path, "lnk") {
         path.add_extension("lnk");
     }
-    let Ok(mut sl) = mslnk::ShellLink::new(bin) else {
-        return;
-    };
+    let mut sl = mslnk::ShellLink::new(bin).map_err(std::io::Error::other)?;
     let args_str = shell_words::join(args);
     sl.set_arguments(Some(args_str.into()));
     sl.set_name(Some(name.into()));
-    _ = sl.create_lnk(path);
-
+    sl.create_lnk(&path).map_err(std::io::Error::other)?;
+    Ok(path)
 }
 
 #[cfg(target_os = "macos")]
-pub fn create_shortcut(mut path: PathBuf, name: &str, bin: &Path, args: &[&str]) {
-    log::info!("Creating macos shortcut at {:?}", path);
+pub fn create_shortcut(mut path: PathBuf, name: &str, bin: &Path, args: &[&str]) -> std::io::Result<PathBuf> {
+    tracing::info!("Creating macos shortcut at {:?}", path);
+
+    let exec = shell_words::join(std::iter::once(bin_str(bin)?).chain(args.iter().map(|s| *s)));
+
+    // A .command script opens a Terminal window, but unlike an app bundle it can be edited and moved freely
+    if has_extension(&path, "command") {
+        std::fs::write(&path, format!("#!/bin/sh\n{exec}\n").as_bytes())?;
+
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        return Ok(path);
+    }
 
     if !has_extension(&path, "app") {
         path.add_extension("app");
     }
+    let app_path = path.clone();
 
     path.push("Contents");
 
-    _ = std::fs::create_dir_all(&path);
+    std::fs::create_dir_all(&path)?;
 
     let info_plist = path.join("Info.plist");
-    _ = std::fs::write(&info_plist, format!(r#"<?xml version="1.0" encoding="UTF-8"?>
+    std::fs::write(&info_plist, format!(r#"<?xml version="1.0" encoding="UTF-8"?>
 <!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
 <plist version="1.0">
 <dict>
@@ -80,22 +89,23 @@ pub fn create_shortcut(mut path: PathBuf, name: &str, bin: &Path, args: &[&str])
    	<key>CFBundleVersion</key>
    	<string>0</string>
 </dict>
-</plist>"#).as_bytes());
+</plist>"#).as_bytes())?;
 
     let macos = path.join("MacOS");
-    _ = std::fs::create_dir_all(&macos);
-
-    let Some(bin) = bin.to_str() else {
-        return;
-    };
-    let exec = shell_words::join(std::iter::once(bin).chain(args.iter().map(|s| *s)));
+    std::fs::create_dir_all(&macos)?;
 
-    let script_path = path.join("run.sh");
-    _ = std::fs::write(&script_path, format!(r#"#!/bin/sh
-{}"#, exec).as_bytes());
+    let script_path = macos.join("run.sh");
+    std::fs::write(&script_path, format!(r#"#!/bin/sh
+{}"#, exec).as_bytes())?;
 
     use std::os::unix::fs::PermissionsExt;
-    let _ = std::fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o755));
+    std::fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o755))?;
+    Ok(app_path)
+}
+
+#[cfg(unix)]
+fn bin_str(bin: &Path) -> std::io::Result<&str> {
+    bin.to_str().ok_or_else(|| std::io::Error::other("the launcher's path isn't valid UTF-8"))
 }
 
 fn has_extension(path: &Path, extension: &str) -> bool {
@@ -0,0 +1,341 @@
+use std::{io::Cursor, path::Path, sync::Arc};
+
+use auth::authenticator::Authenticator;
+use base64::Engine;
+use bridge::{
+    account::{LibrarySkin, SkinModel, SkinProfile, SkinSource, SkinTexture}, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}
+};
+use image::{imageops, RgbaImage};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+use crate::BackendState;
+
+const SESSION_PROFILE_URL: &str = "https://sessionserver.mojang.com/session/minecraft/profile";
+/// Previews are scaled up so they stay sharp rather than being smoothed when shown large
+const PREVIEW_SCALE: u32 = 8;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SkinError {
+    #[error("No account is selected")]
+    NoAccount,
+    #[error("Offline accounts don't have a skin on Mojang's servers")]
+    OfflineAccount,
+    #[error("The skin is no longer in the library")]
+    NotInLibrary,
+    #[error("Not a skin, skins are 64x64 or 64x32 PNG images")]
+    InvalidSize,
+    #[error("Unexpected profile response from Mojang")]
+    InvalidProfile,
+    #[error("Mojang responded with status {0}")]
+    NonOkHttpStatus(reqwest::StatusCode),
+    #[error("Connection error: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("Upload failed: {0}")]
+    Upload(#[from] auth::authenticator::XboxAuthenticateError),
+    #[error("Invalid image: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Deserialize)]
+struct SessionProfile {
+    properties: Vec<SessionProfileProperty>,
+}
+
+#[derive(Deserialize)]
+struct SessionProfileProperty {
+    name: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct TexturesProperty {
+    textures: Textures,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+struct Textures {
+    skin: Option<Texture>,
+    cape: Option<Texture>,
+}
+
+#[derive(Deserialize)]
+struct Texture {
+    url: String,
+    metadata: Option<TextureMetadata>,
+}
+
+#[derive(Deserialize)]
+struct TextureMetadata {
+    model: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LibraryEntry {
+    id: Arc<str>,
+    name: Arc<str>,
+    slim: bool,
+}
+
+fn load_library_entries(skins_dir: &Path) -> Vec<LibraryEntry> {
+    std::fs::read(skins_dir.join("library.json")).ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save_library_entries(skins_dir: &Path, entries: &[LibraryEntry]) -> std::io::Result<()> {
+    let json = serde_json::to_vec_pretty(entries).map_err(std::io::Error::other)?;
+    crate::write_safe(&skins_dir.join("library.json"), &json)
+}
+
+fn model_from_slim(slim: bool) -> SkinModel {
+    if slim { SkinModel::Slim } else { SkinModel::Classic }
+}
+
+fn decode_skin(png: &[u8]) -> Result<RgbaImage, SkinError> {
+    let image = image::load_from_memory_with_format(png, image::ImageFormat::Png)?.into_rgba8();
+    if image.width() != 64 || (image.height() != 64 && image.height() != 32) {
+        return Err(SkinError::InvalidSize);
+    }
+    Ok(image)
+}
+
+fn encode_png(image: &RgbaImage) -> Result<Arc<[u8]>, SkinError> {
+    let mut bytes = Vec::new();
+    image.write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Png)?;
+    Ok(bytes.into())
+}
+
+/// The front of the player as it looks in game, with the second layer drawn over the first
+fn front_view(skin: &RgbaImage, model: SkinModel) -> RgbaImage {
+    let legacy = skin.height() == 32;
+    let arm_width = if model == SkinModel::Slim { 3 } else { 4 };
+    let mut view = RgbaImage::new(16, 32);
+
+    let mut draw = |x: u32, y: u32, width: u32, height: u32, to_x: u32, to_y: u32, mirror: bool, overlay: bool| {
+        let mut part = imageops::crop_imm(skin, x, y, width, height).to_image();
+        if mirror {
+            part = imageops::flip_horizontal(&part);
+        }
+        if overlay {
+            imageops::overlay(&mut view, &part, to_x as i64, to_y as i64);
+        } else {
+            imageops::replace(&mut view, &part, to_x as i64, to_y as i64);
+        }
+    };
+
+    draw(8, 8, 8, 8, 4, 0, false, false);
+    draw(20, 20, 8, 12, 4, 8, false, false);
+    draw(44, 20, arm_width, 12, 4 - arm_width, 8, false, false);
+    draw(4, 20, 4, 12, 4, 20, false, false);
+    if legacy {
+        // Skins from before 1.8 only have the right limbs, the left ones are mirrored
+        draw(44, 20, arm_width, 12, 12, 8, true, false);
+        draw(4, 20, 4, 12, 8, 20, true, false);
+    } else {
+        draw(36, 52, arm_width, 12, 12, 8, false, false);
+        draw(20, 52, 4, 12, 8, 20, false, false);
+
+        draw(20, 36, 8, 12, 4, 8, false, true);
+        draw(44, 36, arm_width, 12, 4 - arm_width, 8, false, true);
+        draw(52, 52, arm_width, 12, 12, 8, false, true);
+        draw(4, 36, 4, 12, 4, 20, false, true);
+        draw(4, 52, 4, 12, 8, 20, false, true);
+    }
+    draw(40, 8, 8, 8, 4, 0, false, true);
+
+    view
+}
+
+fn scale_up(image: &RgbaImage) -> RgbaImage {
+    imageops::resize(image, image.width() * PREVIEW_SCALE, image.height() * PREVIEW_SCALE, imageops::FilterType::Nearest)
+}
+
+pub fn skin_texture(png: Arc<[u8]>, model: SkinModel) -> Result<SkinTexture, SkinError> {
+    let skin = decode_skin(&png)?;
+    let preview = encode_png(&scale_up(&front_view(&skin, model)))?;
+    Ok(SkinTexture { model, png, preview })
+}
+
+fn cape_texture(png: Arc<[u8]>) -> Result<SkinTexture, SkinError> {
+    let cape = image::load_from_memory_with_format(&png, image::ImageFormat::Png)?.into_rgba8();
+    // Capes are 64x32 or a multiple of it, the outside of the cape is at (1, 1) scaled the same way
+    let scale = (cape.width() / 64).max(1);
+    if cape.width() < 22 * scale || cape.height() < 17 * scale {
+        return Err(SkinError::InvalidSize);
+    }
+    let front = imageops::crop_imm(&cape, scale, scale, 10 * scale, 16 * scale).to_image();
+    let front = imageops::resize(&front, 10 * PREVIEW_SCALE, 16 * PREVIEW_SCALE, imageops::FilterType::Nearest);
+    Ok(SkinTexture { model: SkinModel::Classic, png, preview: encode_png(&front)? })
+}
+
+pub fn load_library(skins_dir: &Path) -> Vec<LibrarySkin> {
+    load_library_entries(skins_dir).into_iter().filter_map(|entry| {
+        let png: Arc<[u8]> = std::fs::read(skins_dir.join(format!("{}.png", entry.id))).ok()?.into();
+        match skin_texture(png, model_from_slim(entry.slim)) {
+            Ok(texture) => Some(LibrarySkin { id: entry.id, name: entry.name, texture }),
+            Err(error) => {
+                tracing::warn!("Skipping skin {} in the library: {error}", entry.id);
+                None
+            },
+        }
+    }).collect()
+}
+
+/// Adds a skin to the library, or updates the name and model when it's already there
+pub fn add_to_library(skins_dir: &Path, name: Arc<str>, png: &[u8], model: SkinModel) -> Result<(), SkinError> {
+    decode_skin(png)?;
+
+    let id: Arc<str> = hex::encode(Sha1::digest(png)).into();
+    crate::write_safe(&skins_dir.join(format!("{id}.png")), png)?;
+
+    let mut entries = load_library_entries(skins_dir);
+    entries.retain(|entry| entry.id != id);
+    entries.push(LibraryEntry { id, name, slim: model == SkinModel::Slim });
+    save_library_entries(skins_dir, &entries)?;
+    Ok(())
+}
+
+pub fn remove_from_library(skins_dir: &Path, id: &str) -> std::io::Result<()> {
+    let mut entries = load_library_entries(skins_dir);
+    entries.retain(|entry| &*entry.id != id);
+    save_library_entries(skins_dir, &entries)?;
+
+    match std::fs::remove_file(skins_dir.join(format!("{id}.png"))) {
+        Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+impl BackendState {
+    fn selected_online_account(&self) -> Result<uuid::Uuid, SkinError> {
+        let mut account_info = self.account_info.write();
+        let account_info = account_info.get();
+        let uuid = account_info.selected_account.ok_or(SkinError::NoAccount)?;
+        match account_info.accounts.get(&uuid) {
+            None => Err(SkinError::NoAccount),
+            Some(account) if account.offline => Err(SkinError::OfflineAccount),
+            Some(_) => Ok(uuid),
+        }
+    }
+
+    async fn download_texture(&self, url: &str) -> Result<Arc<[u8]>, SkinError> {
+        let response = self.http_client.get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(SkinError::NonOkHttpStatus(response.status()));
+        }
+        Ok(response.bytes().await?.to_vec().into())
+    }
+
+    /// The public profile of the selected account, which doesn't need it to be logged in
+    pub async fn skin_profile(&self) -> Result<SkinProfile, SkinError> {
+        let uuid = self.selected_online_account()?;
+
+        let url = format!("{SESSION_PROFILE_URL}/{}", uuid.simple());
+        let response = self.http_client.get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(SkinError::NonOkHttpStatus(response.status()));
+        }
+        let profile: SessionProfile = serde_json::from_slice(&response.bytes().await?).map_err(|_| SkinError::InvalidProfile)?;
+
+        let textures = profile.properties.iter()
+            .find(|property| property.name == "textures")
+            .and_then(|property| base64::engine::general_purpose::STANDARD.decode(&property.value).ok())
+            .and_then(|json| serde_json::from_slice::<TexturesProperty>(&json).ok())
+            .ok_or(SkinError::InvalidProfile)?
+            .textures;
+
+        let skin = match textures.skin {
+            Some(texture) => {
+                let slim = texture.metadata.and_then(|metadata| metadata.model).is_some_and(|model| model == "slim");
+                Some(skin_texture(self.download_texture(&texture.url).await?, model_from_slim(slim))?)
+            },
+            None => None,
+        };
+        let cape = match textures.cape {
+            Some(texture) => Some(cape_texture(self.download_texture(&texture.url).await?)?),
+            None => None,
+        };
+
+        let username = self.account_info.write().get().accounts.get(&uuid).map(|account| account.username.clone()).unwrap_or_default();
+        Ok(SkinProfile { username, skin, cape })
+    }
+
+    pub async fn add_skin_to_library(&self, source: SkinSource, model: SkinModel) -> Result<(), SkinError> {
+        let skins_dir = self.directories.skins_dir.clone();
+        tokio::task::spawn_blocking(move || {
+            let (name, png): (Arc<str>, Arc<[u8]>) = match source {
+                SkinSource::File(path) => {
+                    let name = path.file_stem().map(|stem| stem.to_string_lossy().into()).unwrap_or_else(|| "Skin".into());
+                    (name, std::fs::read(&path)?.into())
+                },
+                SkinSource::Texture { name, png } => (name, png),
+            };
+            add_to_library(&skins_dir, name, &png, model)
+        }).await.unwrap()
+    }
+
+    /// Returns false when logging in didn't succeed, which has already been reported to the modal
+    pub async fn upload_skin(&self, id: &str, modal_action: &ModalAction) -> Result<bool, SkinError> {
+        let uuid = self.selected_online_account()?;
+
+        let skins_dir = self.directories.skins_dir.clone();
+        let entry = load_library_entries(&skins_dir).into_iter().find(|entry| &*entry.id == id).ok_or(SkinError::NotInLibrary)?;
+        let png = std::fs::read(skins_dir.join(format!("{id}.png")))?;
+
+        let Some((_, access_token)) = self.login_flow(modal_action, Some(uuid)).await else {
+            return Ok(false);
+        };
+
+        let tracker = ProgressTracker::new(rust_i18n::t!("progress_uploading_skin").into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.set_total(1);
+        tracker.notify();
+
+        let result = Authenticator::new(self.http_client.clone()).upload_skin(&access_token, entry.slim, &png).await;
+        let profile = match result {
+            Ok(profile) => profile,
+            Err(error) => {
+                tracker.set_finished(ProgressTrackerFinishType::Error);
+                tracker.notify();
+                return Err(error.into());
+            },
+        };
+
+        tracker.set_count(1);
+        tracker.set_finished(ProgressTrackerFinishType::Normal);
+        tracker.notify();
+
+        self.update_profile_head(&profile);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_skins_mirror_the_right_limbs() {
+        let mut skin = RgbaImage::new(64, 32);
+        // Outer column of the right arm's front
+        for y in 20..32 {
+            skin.put_pixel(44, y, image::Rgba([255, 0, 0, 255]));
+        }
+
+        let view = front_view(&skin, SkinModel::Classic);
+        assert_eq!(view.get_pixel(0, 8).0, [255, 0, 0, 255]);
+        assert_eq!(view.get_pixel(15, 8).0, [255, 0, 0, 255]);
+        assert_eq!(view.get_pixel(12, 8).0, [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn rejects_images_that_are_not_skins() {
+        let image = RgbaImage::new(32, 32);
+        let png = encode_png(&image).unwrap();
+        assert!(matches!(decode_skin(&png), Err(SkinError::InvalidSize)));
+    }
+}
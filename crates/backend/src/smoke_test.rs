@@ -0,0 +1,144 @@
+use std::{io::Read, path::Path, process::{Child, Output}, sync::Arc, thread::JoinHandle, time::{Duration, Instant}};
+
+use bridge::modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType};
+use schema::{instance::InstanceConfiguration, loader::Loader};
+use ustr::Ustr;
+
+use crate::{BackendState, account::MinecraftLoginInfo, launch::LaunchError, metadata::items::MinecraftVersionManifestMetadataItem, verification_report::VerificationReport};
+
+/// Java prints its version and exits immediately, so anything longer means it's stuck
+const JAVA_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(thiserror::Error, Debug)]
+enum SmokeTestError {
+    #[error("Unable to write to the launcher directory: {0}")]
+    Disk(std::io::Error),
+    #[error("Unable to fetch the version manifest: {0}")]
+    Manifest(#[from] crate::metadata::manager::MetaLoadError),
+    #[error("{0}")]
+    Launch(#[from] LaunchError),
+    #[error("Java didn't exit within {} seconds", JAVA_TIMEOUT.as_secs())]
+    JavaTimeout,
+    #[error("Java exited with {status}:\n{output}")]
+    JavaFailed {
+        status: std::process::ExitStatus,
+        output: String,
+    },
+}
+
+/// Removes the throwaway instance once the smoke test is done with it
+struct ThrowawayDir(Arc<Path>);
+
+impl Drop for ThrowawayDir {
+    fn drop(&mut self) {
+        _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+impl BackendState {
+    /// Checks network, disk and Java by launching a throwaway instance, reporting the outcome as a notification
+    pub async fn smoke_test(&self, minecraft_version: Option<Ustr>, modal_action: &ModalAction) {
+        let started = Instant::now();
+        match self.run_smoke_test(minecraft_version, modal_action).await {
+            Ok(java_version) => {
+                let summary = format!("Smoke test passed in {:.1}s using {java_version}", started.elapsed().as_secs_f32());
+                tracing::info!("{summary}");
+                self.send.send_success(summary);
+            },
+            Err(SmokeTestError::Launch(LaunchError::CancelledByUser)) => {},
+            Err(error) => {
+                tracing::error!("Smoke test failed: {error}");
+                modal_action.set_error_message(format!("Smoke test failed: {error}").into());
+            },
+        }
+    }
+
+    async fn run_smoke_test(&self, minecraft_version: Option<Ustr>, modal_action: &ModalAction) -> Result<String, SmokeTestError> {
+        let dir: Arc<Path> = self.directories.temp_dir.join(format!("smoke-test-{:016x}", rand::random::<u64>())).into();
+        std::fs::create_dir_all(&dir).map_err(SmokeTestError::Disk)?;
+        let dir = ThrowawayDir(dir);
+        crate::write_safe(&dir.0.join("probe"), b"probe").map_err(SmokeTestError::Disk)?;
+
+        let minecraft_version = match minecraft_version {
+            Some(minecraft_version) => minecraft_version,
+            None => self.meta.fetch(&MinecraftVersionManifestMetadataItem).await?.latest.release,
+        };
+        tracing::info!("Running smoke test with {minecraft_version}");
+
+        let login_info = MinecraftLoginInfo {
+            uuid: crate::account::offline_uuid("SmokeTest"),
+            username: "SmokeTest".into(),
+            access_token: None,
+        };
+
+        let launch_tracker = ProgressTracker::new(rust_i18n::t!("progress_smoke_test").into(), self.send.clone());
+        modal_action.trackers.push(launch_tracker.clone());
+
+        let dot_minecraft: Arc<Path> = dir.0.join(".minecraft").into();
+        let configuration = InstanceConfiguration::new(minecraft_version, Loader::Vanilla);
+        let only_selected_language = self.config.write().get().download_only_selected_language;
+        let result = self.launcher.launch(&self.redirecting_http_client, dot_minecraft, None, configuration, None, login_info, Vec::new(),
+            only_selected_language, true, &VerificationReport::default(), &launch_tracker, modal_action).await;
+
+        let child = match result {
+            Ok(child) => child,
+            Err(error) => {
+                launch_tracker.set_finished(ProgressTrackerFinishType::Error);
+                launch_tracker.notify();
+                return Err(error.into());
+            },
+        };
+
+        let output = tokio::task::spawn_blocking(move || wait_with_timeout(child, JAVA_TIMEOUT)).await.unwrap();
+        launch_tracker.set_finished(ProgressTrackerFinishType::from_err(!matches!(output, Ok(Some(ref output)) if output.status.success())));
+        launch_tracker.notify();
+
+        let Some(output) = output.map_err(LaunchError::from)? else {
+            return Err(SmokeTestError::JavaTimeout);
+        };
+
+        // `-version` writes to stderr, but some runtimes use stdout
+        let mut text = String::from_utf8_lossy(&output.stderr).into_owned();
+        text.push_str(&String::from_utf8_lossy(&output.stdout));
+
+        if !output.status.success() {
+            return Err(SmokeTestError::JavaFailed { status: output.status, output: text.trim().to_string() });
+        }
+
+        Ok(text.lines().next().unwrap_or("unknown Java").trim().to_string())
+    }
+}
+
+fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+/// Like [`Child::wait_with_output`], but kills the process and returns None once the timeout has passed
+fn wait_with_timeout(mut child: Child, timeout: Duration) -> std::io::Result<Option<Output>> {
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if Instant::now() >= deadline {
+            tracing::warn!("Killing Java process {} because it didn't exit in time", child.id());
+            _ = child.kill();
+            _ = child.wait();
+            break None;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    Ok(status.map(|status| Output { status, stdout, stderr }))
+}
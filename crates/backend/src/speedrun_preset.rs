@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use bridge::{
+    install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath, InstallTarget}, modal_action::ModalAction, safe_path::SafePath
+};
+use schema::{content::ContentSource, loader::Loader, speedrun_mods::SPEEDRUN_MINECRAFT_VERSION};
+
+use crate::{BackendState, metadata::items::SpeedrunModsMetadataItem};
+
+impl BackendState {
+    /// Creates a Fabric instance for random-seed speedruns with the recommended legal mods. Standard
+    /// options are applied in-game by StandardSettings, which is part of the recommended set
+    pub async fn create_speedrun_instance(&self, name: Arc<str>, modal_action: ModalAction) {
+        let manifest = match self.meta.fetch(&SpeedrunModsMetadataItem).await {
+            Ok(manifest) => manifest,
+            Err(error) => {
+                modal_action.set_error_message(format!("Unable to load the speedrun mod list: {error}").into());
+                return;
+            },
+        };
+
+        let mut files: Vec<ContentInstallFile> = manifest.recommended_for(SPEEDRUN_MINECRAFT_VERSION).into_iter().filter_map(|(speedrun_mod, version)| {
+            let filename = format!("{}-{}.jar", speedrun_mod.modid, version.version);
+            let Some(path) = SafePath::new(&format!("mods/{filename}")) else {
+                tracing::warn!("Skipping speedrun mod {} with unusable filename {filename}", speedrun_mod.modid);
+                return None;
+            };
+            Some(ContentInstallFile {
+                replace_old: None,
+                path: ContentInstallPath::Safe(path),
+                download: ContentDownload::Url {
+                    url: version.url.clone(),
+                    sha1: version.hash.clone(),
+                    size: 0,
+                },
+                content_source: ContentSource::Manual,
+            })
+        }).collect();
+
+        if files.is_empty() {
+            modal_action.set_error_message(format!("The speedrun mod list has no mods for {SPEEDRUN_MINECRAFT_VERSION}").into());
+            return;
+        }
+
+        // The list only has hashes, the sizes come from the servers hosting the mods
+        if let Err(error) = self.resolve_download_sizes(&mut files).await {
+            modal_action.set_error_message(format!("Unable to download the speedrun mods: {error}").into());
+            return;
+        }
+
+        self.install_content(ContentInstall {
+            target: InstallTarget::NewInstance { name },
+            loader_hint: Loader::Fabric,
+            version_hint: Some(SPEEDRUN_MINECRAFT_VERSION.into()),
+            files: files.into(),
+        }, modal_action).await;
+    }
+}
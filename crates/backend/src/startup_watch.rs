@@ -0,0 +1,151 @@
+use std::{collections::VecDeque, io::{BufRead, BufReader, Read}, sync::{Arc, atomic::{AtomicBool, Ordering}}, time::{Duration, Instant}};
+
+use bridge::{handle::FrontendHandle, instance::InstanceID, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}};
+use parking_lot::{Mutex, RwLock};
+
+use crate::BackendStateInstances;
+
+const MAX_EARLY_LINES: usize = 40;
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(90);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Lines that show the game got past the point where it usually fails on broken installs
+const STARTED_MARKERS: &[&str] = &[
+    "Setting user:",
+    "Backend library: LWJGL",
+    "LWJGL Version:",
+];
+
+/// Keeps the first lines of game output and notices when the game is far enough into
+/// startup that a launch can be reported as successful
+#[derive(Default)]
+pub struct StartupWatch {
+    started: AtomicBool,
+    early_lines: Mutex<VecDeque<String>>,
+}
+
+impl StartupWatch {
+    pub fn observe(&self, line: &str) {
+        if self.is_started() {
+            return;
+        }
+
+        if STARTED_MARKERS.iter().any(|marker| line.contains(marker)) {
+            self.started.store(true, Ordering::Release);
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            return;
+        }
+        let mut early_lines = self.early_lines.lock();
+        if early_lines.len() >= MAX_EARLY_LINES {
+            early_lines.pop_front();
+        }
+        early_lines.push_back(line.to_string());
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started.load(Ordering::Acquire)
+    }
+
+    pub fn early_output(&self) -> String {
+        let early_lines = self.early_lines.lock();
+        if early_lines.is_empty() {
+            return "<no output>".to_string();
+        }
+        early_lines.iter().map(String::as_str).collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// Reads output that isn't shown to the user until the game has started, so that early output
+/// is still available if the game fails to start. The stream is closed afterwards
+pub fn watch_until_started(output: impl Read + Send + 'static, watch: Arc<StartupWatch>) {
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(output);
+        let mut line = String::new();
+        while !watch.is_started() {
+            line.clear();
+            match reader.read_line(&mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => watch.observe(&line),
+            }
+        }
+    });
+}
+
+enum StartupResult {
+    Started,
+    Exited,
+    TimedOut,
+    Cancelled,
+}
+
+/// Keeps the launch modal open until the game has reported that it started. If the process exits
+/// or stalls first, the modal shows the early output instead of closing as if the launch succeeded
+pub async fn wait_for_startup(
+    instance_state: Arc<RwLock<BackendStateInstances>>,
+    send: FrontendHandle,
+    id: InstanceID,
+    pid: u32,
+    watch: Arc<StartupWatch>,
+    launch_tracker: ProgressTracker,
+    modal_action: ModalAction,
+) {
+    let deadline = Instant::now() + STARTUP_TIMEOUT;
+
+    let result = loop {
+        if watch.is_started() {
+            break StartupResult::Started;
+        }
+        if modal_action.has_requested_cancel() {
+            break StartupResult::Cancelled;
+        }
+
+        let running = instance_state.read().instances.get(id)
+            .and_then(|instance| instance.process.as_ref())
+            .is_some_and(|process| process.pid() == pid);
+        if !running {
+            // The output may still be catching up with the exit
+            tokio::time::sleep(POLL_INTERVAL).await;
+            if watch.is_started() {
+                break StartupResult::Started;
+            }
+            break StartupResult::Exited;
+        }
+
+        if Instant::now() >= deadline {
+            break StartupResult::TimedOut;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    };
+
+    match result {
+        StartupResult::Started | StartupResult::Cancelled => {},
+        StartupResult::Exited => {
+            tracing::warn!("Game process {pid} exited during startup");
+            modal_action.set_error_message(format!("The game exited during startup. Early output:\n{}", watch.early_output()).into());
+        },
+        StartupResult::TimedOut => {
+            tracing::warn!("Game process {pid} hasn't finished starting after {}s", STARTUP_TIMEOUT.as_secs());
+            modal_action.set_error_message(format!(
+                "The game is still running but hasn't finished starting after {} seconds. Early output:\n{}",
+                STARTUP_TIMEOUT.as_secs(),
+                watch.early_output()
+            ).into());
+        },
+    }
+
+    if let Some(instance) = instance_state.write().instances.get_mut(id) {
+        instance.launching = None;
+        send.send(instance.create_modify_message());
+    }
+
+    let finish_type = match result {
+        StartupResult::Started | StartupResult::Cancelled => ProgressTrackerFinishType::Normal,
+        StartupResult::Exited | StartupResult::TimedOut => ProgressTrackerFinishType::Error,
+    };
+    launch_tracker.set_finished(finish_type);
+    launch_tracker.notify();
+    modal_action.set_finished();
+}
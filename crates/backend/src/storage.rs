@@ -0,0 +1,269 @@
+use std::{path::{Path, PathBuf}, sync::Arc, time::Duration};
+
+use bridge::message::{InstanceStorage, StorageCleanup, StorageReport};
+use rustc_hash::{FxHashMap, FxHashSet};
+use schema::{assets_index::AssetsIndex, version::MinecraftVersion, version_manifest::MinecraftVersionManifest};
+use ustr::Ustr;
+
+use crate::{BackendState, directories::LauncherDirectories};
+
+/// Unfinished downloads and temporary directories are only treated as orphaned once they haven't been touched for
+/// this long, so ones that are still being written to are left alone
+const ORPHAN_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(thiserror::Error, Debug)]
+pub enum StorageCleanupError {
+    #[error("Files can't be cleaned up while an instance is running or launching")]
+    InstanceRunning,
+    #[error("Unable to tell which assets Minecraft {0} uses, launch an instance of it once before clearing assets")]
+    UnknownAssets(Ustr),
+    #[error("Unable to tell which libraries {0} uses, launch it once before clearing libraries")]
+    UnknownLibraries(Arc<str>),
+}
+
+impl BackendState {
+    pub fn storage_report(&self) -> StorageReport {
+        let directories = &self.directories;
+
+        let unused_assets = self.referenced_assets().ok().map(|referenced| {
+            unused_assets(&directories.assets_objects_dir, &referenced).iter().map(|(_, size)| size).sum()
+        });
+
+        let unused_libraries = self.unused_libraries().ok().map(|unused| unused.iter().map(|(_, size)| size).sum());
+
+        let mut instances: Vec<InstanceStorage> = instance_folders(directories).into_iter()
+            .map(|name| InstanceStorage {
+                size: directory_size(&directories.instances_dir.join(&*name)),
+                name,
+            })
+            .collect();
+        instances.sort_by(|a, b| b.size.cmp(&a.size));
+
+        StorageReport {
+            assets: directory_size(&directories.assets_root_dir),
+            unused_assets,
+            libraries: directory_size(&directories.libraries_dir),
+            unused_libraries,
+            java_runtimes: directory_size(&directories.runtime_base_dir),
+            metadata_cache: directory_size(&directories.metadata_dir),
+            natives: directory_size(&directories.temp_natives_base_dir),
+            orphaned_files: orphaned_files(directories).iter().map(|path| directory_size(path)).sum(),
+            instances,
+        }
+    }
+
+    /// Removes the files of `cleanup`, returning how many bytes were freed
+    pub fn cleanup_storage(&self, cleanup: StorageCleanup) -> Result<u64, StorageCleanupError> {
+        let busy = self.instance_state.read().instances.iter()
+            .any(|instance| instance.process.is_some() || instance.launching.is_some());
+        if busy {
+            return Err(StorageCleanupError::InstanceRunning);
+        }
+
+        let directories = &self.directories;
+        let freed = match cleanup {
+            StorageCleanup::UnusedAssets => {
+                let referenced = self.referenced_assets()?;
+                unused_assets(&directories.assets_objects_dir, &referenced).into_iter()
+                    .filter(|(path, _)| std::fs::remove_file(path).is_ok())
+                    .map(|(_, size)| size)
+                    .sum()
+            },
+            StorageCleanup::UnusedLibraries => {
+                let freed = self.unused_libraries()?.into_iter()
+                    .filter(|(path, _)| std::fs::remove_file(path).is_ok())
+                    .map(|(_, size)| size)
+                    .sum();
+                crate::library_store::remove_empty_dirs(&directories.libraries_dir);
+                freed
+            },
+            StorageCleanup::NativesDirs => {
+                std::fs::read_dir(&directories.temp_natives_base_dir).into_iter().flatten().flatten()
+                    .map(|entry| remove(&entry.path()))
+                    .sum()
+            },
+            StorageCleanup::OrphanedFiles => {
+                orphaned_files(directories).iter().map(|path| remove(path)).sum()
+            },
+        };
+
+        tracing::info!("Storage cleanup {:?} freed {} bytes", cleanup, freed);
+        Ok(freed)
+    }
+
+    /// Hashes of the assets used by the Minecraft version of any instance, read from the version info and asset
+    /// indexes downloaded when they were launched
+    fn referenced_assets(&self) -> Result<FxHashSet<Ustr>, StorageCleanupError> {
+        let mut versions: Vec<Ustr> = self.instance_state.write().instances.iter_mut()
+            .map(|instance| instance.configuration.get().minecraft_version)
+            .collect();
+        versions.sort_unstable();
+        versions.dedup();
+
+        let manifest = crate::read_json::<MinecraftVersionManifest>(self.meta.version_manifest_cache_path()).ok();
+        let sha1_by_version: FxHashMap<Ustr, &str> = manifest.iter()
+            .flat_map(|manifest| manifest.versions.iter())
+            .map(|version| (version.id, version.sha1.as_str()))
+            .collect();
+        let version_info_dir = self.meta.version_info_cache_dir();
+
+        let mut referenced = FxHashSet::default();
+        for version in versions {
+            let index = sha1_by_version.get(&version)
+                .and_then(|sha1| crate::read_json::<MinecraftVersion>(&version_info_dir.join(sha1)).ok())
+                .and_then(|info| crate::read_json::<AssetsIndex>(&self.directories.assets_index_dir.join(format!("{}.json", info.assets))).ok())
+                .ok_or(StorageCleanupError::UnknownAssets(version))?;
+            referenced.extend(index.objects.values().map(|object| object.hash));
+        }
+        Ok(referenced)
+    }
+
+    fn unused_libraries(&self) -> Result<Vec<(PathBuf, u64)>, StorageCleanupError> {
+        let directories = &self.directories;
+        crate::library_store::unused_libraries(&directories.libraries_dir, &directories.library_references_json, &instance_folders(directories))
+            .map_err(StorageCleanupError::UnknownLibraries)
+    }
+}
+
+/// Names of the folders in the instances directory
+fn instance_folders(directories: &LauncherDirectories) -> Vec<Arc<str>> {
+    std::fs::read_dir(&directories.instances_dir).into_iter().flatten().flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .map(|entry| entry.file_name().to_string_lossy().into())
+        .collect()
+}
+
+/// Total size of the files under `path`. Links aren't followed, so synced folders are only counted once
+pub fn directory_size(path: &Path) -> u64 {
+    let Ok(metadata) = path.symlink_metadata() else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path).into_iter().flatten().flatten()
+        .map(|entry| directory_size(&entry.path()))
+        .sum()
+}
+
+/// Asset objects, stored as `objects/<first two characters>/<hash>`, whose hash isn't in `referenced`
+fn unused_assets(objects_dir: &Path, referenced: &FxHashSet<Ustr>) -> Vec<(PathBuf, u64)> {
+    let mut unused = Vec::new();
+    for prefix in std::fs::read_dir(objects_dir).into_iter().flatten().flatten() {
+        for entry in std::fs::read_dir(prefix.path()).into_iter().flatten().flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let used = entry.file_name().to_str().is_some_and(|name| referenced.contains(&Ustr::from(name)));
+            if metadata.is_file() && !used {
+                unused.push((entry.path(), metadata.len()));
+            }
+        }
+    }
+    unused
+}
+
+/// `.part` files of downloads that were never finished and temporary directories of imports or copies that were
+/// interrupted, left untouched for at least `ORPHAN_AGE`
+fn orphaned_files(directories: &LauncherDirectories) -> Vec<PathBuf> {
+    let mut orphaned = Vec::new();
+
+    for entry in std::fs::read_dir(&directories.temp_dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path != *directories.temp_natives_base_dir && is_stale(&path) {
+            orphaned.push(path);
+        }
+    }
+
+    for dir in [&directories.assets_objects_dir, &directories.libraries_dir, &directories.runtime_base_dir, &directories.content_library_dir] {
+        collect_stale_part_files(dir, &mut orphaned);
+    }
+
+    orphaned
+}
+
+fn collect_stale_part_files(dir: &Path, orphaned: &mut Vec<PathBuf>) {
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_dir() {
+            collect_stale_part_files(&path, orphaned);
+        } else if path.extension().is_some_and(|extension| extension == "part") && is_stale(&path) {
+            orphaned.push(path);
+        }
+    }
+}
+
+fn is_stale(path: &Path) -> bool {
+    path.symlink_metadata()
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|modified| modified.elapsed().is_ok_and(|elapsed| elapsed > ORPHAN_AGE))
+}
+
+/// Removes a file or directory, returning the size of what was removed
+fn remove(path: &Path) -> u64 {
+    let size = directory_size(path);
+    let is_dir = path.symlink_metadata().is_ok_and(|metadata| metadata.is_dir());
+    let result = if is_dir {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    };
+    match result {
+        Ok(()) => size,
+        Err(error) => {
+            tracing::warn!("Unable to remove {:?}: {}", path, error);
+            0
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::*;
+
+    fn age(path: &Path) {
+        let old = SystemTime::now() - ORPHAN_AGE * 2;
+        std::fs::File::open(path).unwrap().set_modified(old).unwrap();
+    }
+
+    #[test]
+    fn finds_unused_assets_and_orphaned_files() {
+        let dir = crate::test_harness::TempDir::new();
+        let directories = LauncherDirectories::new(dir.path().to_path_buf());
+
+        let used = directories.assets_objects_dir.join("ab").join("ab01");
+        let unused = directories.assets_objects_dir.join("cd").join("cd02");
+        for path in [&used, &unused] {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "asset").unwrap();
+        }
+        let referenced = FxHashSet::from_iter([Ustr::from("ab01")]);
+        assert_eq!(unused_assets(&directories.assets_objects_dir, &referenced), vec![(unused, 5)]);
+
+        let library = directories.libraries_dir.join("com").join("example").join("lib.jar");
+        std::fs::create_dir_all(library.parent().unwrap()).unwrap();
+        std::fs::write(&library, "jar").unwrap();
+        let stale_part = crate::download_manager::part_path(&library);
+        std::fs::write(&stale_part, "ja").unwrap();
+        age(&stale_part);
+        let fresh_part = directories.runtime_base_dir.join("java.part");
+        std::fs::create_dir_all(&directories.runtime_base_dir).unwrap();
+        std::fs::write(&fresh_part, "j").unwrap();
+
+        std::fs::create_dir_all(&directories.temp_natives_base_dir).unwrap();
+        let stale_import = directories.temp_dir.join("lan-import-0000000000000001.zip");
+        std::fs::write(&stale_import, "zip").unwrap();
+        age(&stale_import);
+
+        let mut orphaned = orphaned_files(&directories);
+        orphaned.sort();
+        assert_eq!(orphaned, vec![stale_part, stale_import]);
+
+        assert_eq!(directory_size(&directories.libraries_dir), 5);
+    }
+}
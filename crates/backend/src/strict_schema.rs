@@ -0,0 +1,107 @@
+use std::{collections::BTreeMap, sync::LazyLock};
+
+use parking_lot::Mutex;
+use serde::de::{DeserializeOwned, Error};
+
+/// Unknown fields by their JSON path, with the number of documents they were found in
+static UNKNOWN_FIELDS: LazyLock<Mutex<BTreeMap<String, usize>>> = LazyLock::new(Default::default);
+
+/// Setting `PANDORA_STRICT_SCHEMA` enables strict mode regardless of the setting, for developing the schemas
+pub fn forced() -> bool {
+    std::env::var_os("PANDORA_STRICT_SCHEMA").is_some_and(|value| !value.is_empty() && value != "0")
+}
+
+pub fn unknown_fields() -> BTreeMap<String, usize> {
+    UNKNOWN_FIELDS.lock().clone()
+}
+
+fn schema_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Deserializes JSON into one of the schemas. Mojang adds fields without notice, so unknown fields are logged and
+/// collected instead of failing, unless `strict` is set.
+///
+/// Fields are only seen while they pass through the deserializer, so unknown fields inside `#[serde(flatten)]`
+/// and `#[serde(untagged)]` types, which are buffered before the schema looks at them, go unnoticed. The schemas
+/// checked here use `#[serde(transparent)]` for their maps instead, and their untagged enums don't contain structs
+pub fn from_slice<T: DeserializeOwned>(bytes: &[u8], strict: bool) -> serde_json::Result<T> {
+    let mut unknown = Vec::new();
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+    let value: T = serde_ignored::deserialize(&mut deserializer, |path| {
+        unknown.push(format!("{}.{path}", schema_name::<T>()));
+    })?;
+    deserializer.end()?;
+
+    if unknown.is_empty() {
+        return Ok(value);
+    }
+
+    // Same wording as serde's own error, so the validation report picks the field out of the message
+    if strict {
+        return Err(serde_json::Error::custom(format!("unknown field `{}`", unknown[0])));
+    }
+
+    let mut unknown_fields = UNKNOWN_FIELDS.lock();
+    for path in unknown {
+        if !unknown_fields.contains_key(&path) {
+            tracing::warn!("Ignoring unknown field `{path}`");
+        }
+        *unknown_fields.entry(path).or_default() += 1;
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use schema::java_runtimes::JavaRuntimes;
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Library {
+        #[allow(dead_code)]
+        name: String,
+    }
+
+    #[derive(Deserialize)]
+    struct Version {
+        #[allow(dead_code)]
+        libraries: Vec<Library>,
+    }
+
+    const VERSION: &[u8] = br#"{"libraries": [{"name": "a"}, {"name": "b", "natives": {}}], "complianceLevel": 1}"#;
+
+    #[test]
+    fn reports_unknown_fields_by_path() {
+        assert!(from_slice::<Version>(VERSION, false).is_ok());
+
+        let unknown = unknown_fields();
+        assert!(unknown.contains_key("Version.libraries.1.natives"));
+        assert!(unknown.contains_key("Version.complianceLevel"));
+    }
+
+    #[test]
+    fn strict_rejects_unknown_fields() {
+        let error = from_slice::<Version>(VERSION, true).err().unwrap();
+
+        assert!(error.to_string().starts_with("unknown field `Version.libraries.1.natives`"));
+    }
+
+    #[test]
+    fn finds_unknown_fields_in_java_runtimes() {
+        let json = br#"{"linux": {"java-runtime-gamma": [{
+            "availability": {"group": 1, "progress": 100},
+            "manifest": {"sha1": "a", "size": 1, "url": "https://example.com", "mirror": "b"},
+            "version": {"name": "17", "released": "2023-01-01T00:00:00+00:00"}
+        }]}}"#;
+
+        let error = from_slice::<JavaRuntimes>(json, true).err().unwrap();
+
+        assert!(error.to_string().starts_with("unknown field `JavaRuntimes.linux.java-runtime-gamma.0.manifest.mirror`"));
+    }
+}
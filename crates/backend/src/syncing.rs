@@ -1,15 +1,18 @@
 use std::{path::{Path, PathBuf}, sync::Arc, time::SystemTime};
 
-use bridge::message::SyncState;
+use bridge::message::{InstanceSyncEntry, InstanceSyncStatus, SyncState};
 use enum_map::EnumMap;
 use enumset::EnumSet;
 use rustc_hash::FxHashMap;
-use schema::backend_config::SyncTarget;
+use schema::{backend_config::{SyncMembership, SyncTarget}, instance::InstanceConfiguration};
 use strum::IntoEnumIterator;
 
 use crate::directories::LauncherDirectories;
 
 pub fn apply_to_instance(sync_targets: EnumSet<SyncTarget>, directories: &LauncherDirectories, dot_minecraft: Arc<Path>) {
+    if crate::data_lock::is_read_only() || !is_owned_game_dir(&dot_minecraft, directories) {
+        return;
+    }
     _ = std::fs::create_dir_all(&dot_minecraft);
 
     for target in SyncTarget::iter() {
@@ -67,27 +70,244 @@ pub fn apply_to_instance(sync_targets: EnumSet<SyncTarget>, directories: &Launch
                     }
                 },
                 _ => {
-                    log::error!("Don't know how to sync {target:?}")
+                    tracing::error!("Don't know how to sync {target:?}")
                 }
             }
         }
     }
 }
 
-fn find_latest(filename: &'static str, directories: &LauncherDirectories) -> Option<PathBuf> {
-    let mut latest_time = SystemTime::UNIX_EPOCH;
-    let mut latest_path = None;
+pub const SYNC_MEMBERSHIP_FILENAME: &str = "sync_membership.json";
+
+/// Whether the game folder is inside the launcher's instances folder. Instances can reuse a game directory from
+/// elsewhere, such as the official launcher's .minecraft, which is never linked into the synced folder
+fn is_owned_game_dir(dot_minecraft: &Path, directories: &LauncherDirectories) -> bool {
+    dot_minecraft.starts_with(&directories.instances_dir)
+}
+
+/// The game folders of all instances with the names of their folders, leaving out the game directories instances reuse
+fn instance_game_dirs(directories: &LauncherDirectories) -> std::io::Result<Vec<(Arc<str>, PathBuf)>> {
+    let mut game_dirs = Vec::new();
+    for entry in std::fs::read_dir(&directories.instances_dir)? {
+        let entry = entry?;
+        let name: Arc<str> = entry.file_name().to_string_lossy().into();
+        let dot_minecraft = crate::instance::read_dot_minecraft_path(&entry.path());
+        if is_owned_game_dir(&dot_minecraft, directories) {
+            game_dirs.push((name, dot_minecraft));
+        }
+    }
+    Ok(game_dirs)
+}
+
+fn synced_target_dir(sync_folder: &str, directories: &LauncherDirectories) -> PathBuf {
+    let non_hidden_sync_folder = if sync_folder.starts_with(".") {
+        &sync_folder[1..]
+    } else {
+        sync_folder
+    };
+    directories.synced_dir.join(non_hidden_sync_folder)
+}
+
+/// Copies an instance folder, leaving out folders that are links into the synced directory and
+/// recording them in a membership file instead, so the copy gets its own links when loaded. The copy
+/// of an instance that reuses a game directory elsewhere gets its own copy of that directory
+pub fn copy_instance(from: &Path, to: &Path, sync_targets: EnumSet<SyncTarget>, directories: &LauncherDirectories) -> std::io::Result<()> {
+    crate::data_lock::check_writable(to)?;
+    let dot_minecraft = crate::instance::read_dot_minecraft_path(from);
+
+    let mut linked = vec![from.join(crate::data_lock::INSTANCE_LOCK_FILENAME)];
+    let mut membership = EnumSet::new();
+    for target in SyncTarget::iter() {
+        if let Some(sync_folder) = target.get_folder() {
+            let path = dot_minecraft.join(sync_folder);
+            if linking::is_targeting(&synced_target_dir(sync_folder, directories), &path) {
+                linked.push(path);
+                membership.insert(target);
+            }
+        } else if sync_targets.contains(target) {
+            membership.insert(target);
+        }
+    }
+
+    copy_dir_skipping(from, to, &linked)?;
+
+    if dot_minecraft != from.join(".minecraft") {
+        linked.extend(crate::launcher_import::official_launcher_files(&dot_minecraft));
+        copy_dir_skipping(&dot_minecraft, &to.join(".minecraft"), &linked)?;
+
+        let info_path = to.join("info_v1.json");
+        let mut configuration: InstanceConfiguration = crate::read_json(&info_path)
+            .map_err(|error| std::io::Error::other(format!("{error:?}")))?;
+        configuration.game_directory = None;
+        crate::write_safe(&info_path, &serde_json::to_vec(&configuration)?)?;
+    }
+
+    if !membership.is_empty() {
+        let membership = SyncMembership {
+            targets: membership,
+            synced_dir: directories.synced_dir.clone(),
+        };
+        crate::write_safe(&to.join(SYNC_MEMBERSHIP_FILENAME), serde_json::to_string(&membership)?.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn copy_dir_skipping(from: &Path, to: &Path, skip: &[PathBuf]) -> std::io::Result<()> {
+    crate::data_lock::check_writable(to)?;
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let path = entry.path();
+        if skip.contains(&path) {
+            continue;
+        }
+
+        let file_type = entry.file_type()?;
+        let target = to.join(entry.file_name());
+        if file_type.is_dir() {
+            copy_dir_skipping(&path, &target, skip)?;
+        } else if file_type.is_file() {
+            std::fs::copy(&path, &target)?;
+        } else if file_type.is_symlink() {
+            // Links that aren't synced folders (e.g. made by the user) are copied as they are
+            let link_target = std::fs::read_link(&path)?;
+            if std::fs::metadata(&path).is_ok_and(|metadata| metadata.is_dir()) {
+                linking::link_dir(&link_target, &target)?;
+            } else {
+                std::fs::copy(&path, &target)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Re-links the synced folders recorded when the instance was duplicated or exported. If the
+/// instance came from a different launcher directory, returns the targets so the user can decide
+/// whether to merge the instance's copies into this launcher's synced folders
+pub fn restore_membership(instance_root: &Path, dot_minecraft: &Path, directories: &LauncherDirectories) -> Option<EnumSet<SyncTarget>> {
+    // Left for when the launcher directory is writable again
+    if crate::data_lock::is_read_only() {
+        return None;
+    }
+    let membership_path = instance_root.join(SYNC_MEMBERSHIP_FILENAME);
+    let bytes = std::fs::read(&membership_path).ok()?;
+
+    let membership: SyncMembership = match serde_json::from_slice(&bytes) {
+        Ok(membership) => membership,
+        Err(error) => {
+            tracing::warn!("Ignoring invalid sync membership in {:?}: {error}", instance_root);
+            _ = std::fs::remove_file(&membership_path);
+            return None;
+        },
+    };
+
+    if *membership.synced_dir != *directories.synced_dir {
+        return Some(membership.targets);
+    }
+
+    link_targets(membership.targets, dot_minecraft, directories);
+    _ = std::fs::remove_file(&membership_path);
+    None
+}
+
+/// Moves the instance's copies of the given targets into the synced folders (keeping any files that are
+/// already synced) and links them, used for instances imported from another launcher directory
+pub fn adopt_membership(targets: EnumSet<SyncTarget>, instance_root: &Path, dot_minecraft: &Path, directories: &LauncherDirectories) -> std::io::Result<()> {
+    crate::data_lock::check_writable(&directories.synced_dir)?;
+    for target in targets {
+        let Some(sync_folder) = target.get_folder() else {
+            continue;
+        };
+
+        let path = dot_minecraft.join(sync_folder);
+        let target_dir = synced_target_dir(sync_folder, directories);
+        if linking::is_targeting(&target_dir, &path) || !path.is_dir() {
+            continue;
+        }
+
+        if target_dir.exists() {
+            if let Some(backup) = merge_into_synced(&path, &target_dir)? {
+                tracing::warn!("Kept the files of {:?} that differ from the synced ones in {:?}", path, backup);
+            }
+        } else if std::fs::rename(&path, &target_dir).is_err() {
+            copy_dir_skipping(&path, &target_dir, &[])?;
+            std::fs::remove_dir_all(&path)?;
+        }
+    }
+
+    link_targets(targets, dot_minecraft, directories);
+    _ = std::fs::remove_file(instance_root.join(SYNC_MEMBERSHIP_FILENAME));
+    Ok(())
+}
 
-    let read_dir = std::fs::read_dir(&directories.instances_dir).ok()?;
+/// Copies the files of `from` into the synced folder `to`, then removes `from`. Files that already exist in `to`
+/// with different contents aren't overwritten, instead `from` is kept under a backup name next to it, which is
+/// returned. Nothing is removed unless every file was copied
+fn merge_into_synced(from: &Path, to: &Path) -> std::io::Result<Option<PathBuf>> {
+    if !merge_dir(from, to)? {
+        std::fs::remove_dir_all(from)?;
+        return Ok(None);
+    }
 
-    for entry in read_dir {
-        let Ok(entry) = entry else {
+    let file_name = from.file_name().unwrap_or_default().to_string_lossy();
+    let mut backup = from.with_file_name(format!("{file_name}.unsynced"));
+    let mut index = 1;
+    while backup.exists() {
+        index += 1;
+        backup = from.with_file_name(format!("{file_name}.unsynced-{index}"));
+    }
+    std::fs::rename(from, &backup)?;
+    Ok(Some(backup))
+}
+
+/// Copies the files missing from `to`, returns whether any file conflicted with a different one in `to`
+fn merge_dir(from: &Path, to: &Path) -> std::io::Result<bool> {
+    std::fs::create_dir_all(to)?;
+    let mut conflicts = false;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            conflicts |= merge_dir(&entry.path(), &target)?;
+        } else if !target.exists() {
+            std::fs::copy(entry.path(), &target)?;
+        } else if !same_contents(&entry.path(), &target)? {
+            conflicts = true;
+        }
+    }
+    Ok(conflicts)
+}
+
+fn same_contents(a: &Path, b: &Path) -> std::io::Result<bool> {
+    if std::fs::metadata(a)?.len() != std::fs::metadata(b)?.len() {
+        return Ok(false);
+    }
+    Ok(std::fs::read(a)? == std::fs::read(b)?)
+}
+
+fn link_targets(targets: EnumSet<SyncTarget>, dot_minecraft: &Path, directories: &LauncherDirectories) {
+    for target in targets {
+        let Some(sync_folder) = target.get_folder() else {
             continue;
         };
+        let path = dot_minecraft.join(sync_folder);
+        if path.exists() {
+            continue;
+        }
+        let target_dir = synced_target_dir(sync_folder, directories);
+        _ = std::fs::create_dir_all(&target_dir);
+        _ = std::fs::create_dir_all(dot_minecraft);
+        _ = linking::link_dir(&target_dir, &path);
+    }
+}
 
-        let mut path = entry.path();
-        path.push(".minecraft");
-        path.push(filename);
+fn find_latest(filename: &'static str, directories: &LauncherDirectories) -> Option<PathBuf> {
+    let mut latest_time = SystemTime::UNIX_EPOCH;
+    let mut latest_path = None;
+
+    for (_, dot_minecraft) in instance_game_dirs(directories).ok()? {
+        let path = dot_minecraft.join(filename);
 
         if let Ok(metadata) = std::fs::metadata(&path) {
             let mut time = SystemTime::UNIX_EPOCH;
@@ -112,20 +332,14 @@ fn find_latest(filename: &'static str, directories: &LauncherDirectories) -> Opt
 fn create_combined_options_txt(fallback: &Path, current: &Path, directories: &LauncherDirectories) -> String {
     let mut values = read_options_txt(fallback);
 
-    let Ok(read_dir) = std::fs::read_dir(&directories.instances_dir) else {
+    let Ok(game_dirs) = instance_game_dirs(directories) else {
         return create_options_txt(values);
     };
 
     let mut paths = Vec::new();
 
-    for entry in read_dir {
-        let Ok(entry) = entry else {
-            continue;
-        };
-
-        let mut path = entry.path();
-        path.push(".minecraft");
-        path.push("options.txt");
+    for (_, dot_minecraft) in game_dirs {
+        let path = dot_minecraft.join("options.txt");
 
         let mut time = SystemTime::UNIX_EPOCH;
 
@@ -172,7 +386,7 @@ fn create_options_txt(values: FxHashMap<String, String>) -> String {
     options
 }
 
-fn read_options_txt(path: &Path) -> FxHashMap<String, String> {
+pub fn read_options_txt(path: &Path) -> FxHashMap<String, String> {
     let Ok(content) = std::fs::read_to_string(path) else {
         return FxHashMap::default();
     };
@@ -188,18 +402,13 @@ fn read_options_txt(path: &Path) -> FxHashMap<String, String> {
 }
 
 pub fn get_sync_state(want_sync: EnumSet<SyncTarget>, directories: &LauncherDirectories) -> std::io::Result<SyncState> {
-    let mut paths = Vec::new();
-
-    let read_dir = std::fs::read_dir(&directories.instances_dir)?;
-    for entry in read_dir {
-        let mut path = entry?.path();
-        path.push(".minecraft");
-        paths.push(path);
-    }
+    let mut paths = instance_game_dirs(directories)?;
+    paths.sort_by(|(a, _), (b, _)| lexical_sort::natural_lexical_cmp(a, b));
 
     let total = paths.len();
     let mut synced = EnumMap::default();
     let mut cannot_sync = EnumMap::default();
+    let mut instances: EnumMap<SyncTarget, Vec<InstanceSyncEntry>> = EnumMap::default();
 
     for target in SyncTarget::iter() {
         let want = want_sync.contains(target);
@@ -222,14 +431,19 @@ pub fn get_sync_state(want_sync: EnumSet<SyncTarget>, directories: &LauncherDire
         let mut synced_count = 0;
         let mut cannot_sync_count = 0;
 
-        for path in &paths {
+        for (name, path) in &paths {
             let path = path.join(sync_folder);
 
-            if linking::is_targeting(&target_dir, &path) {
+            let status = if linking::is_targeting(&target_dir, &path) {
                 synced_count += 1;
+                InstanceSyncStatus::Linked
             } else if path.exists() {
                 cannot_sync_count += 1;
-            }
+                InstanceSyncStatus::Conflicting
+            } else {
+                InstanceSyncStatus::Unlinked
+            };
+            instances[target].push(InstanceSyncEntry { name: name.clone(), status });
         }
 
         synced[target] = synced_count;
@@ -241,24 +455,20 @@ pub fn get_sync_state(want_sync: EnumSet<SyncTarget>, directories: &LauncherDire
         want_sync,
         total,
         synced,
-        cannot_sync
+        cannot_sync,
+        instances,
     })
 }
 
 pub fn enable_all(target: SyncTarget, directories: &LauncherDirectories) -> std::io::Result<bool> {
+    crate::data_lock::check_writable(&directories.synced_dir)?;
     let Some(sync_folder) = target.get_folder() else {
         return Ok(true);
     };
 
-    let mut paths = Vec::new();
-
-    let read_dir = std::fs::read_dir(&directories.instances_dir)?;
-    for entry in read_dir {
-        let mut path = entry?.path();
-        path.push(".minecraft");
-        path.push(sync_folder);
-        paths.push(path);
-    }
+    let mut paths: Vec<PathBuf> = instance_game_dirs(directories)?.into_iter()
+        .map(|(_, dot_minecraft)| dot_minecraft.join(sync_folder))
+        .collect();
 
     let non_hidden_sync_folder = if sync_folder.starts_with(".") {
         &sync_folder[1..]
@@ -290,20 +500,69 @@ pub fn enable_all(target: SyncTarget, directories: &LauncherDirectories) -> std:
     Ok(true)
 }
 
-pub fn disable_all(target: SyncTarget, directories: &LauncherDirectories) -> std::io::Result<()> {
+/// Links the folder of a single instance, merging the instance's own copy into the synced folder first. If some of
+/// its files differ from the synced ones, the instance's copy is kept under a backup name, which is returned
+pub fn link_instance(target: SyncTarget, dot_minecraft: &Path, directories: &LauncherDirectories) -> std::io::Result<Option<PathBuf>> {
+    crate::data_lock::check_writable(&directories.synced_dir)?;
+    let Some(sync_folder) = target.get_folder() else {
+        return Ok(None);
+    };
+
+    if !is_owned_game_dir(dot_minecraft, directories) {
+        return Err(std::io::Error::other("the instance uses a game directory outside the launcher, which can't be synced"));
+    }
+
+    let path = dot_minecraft.join(sync_folder);
+    let target_dir = synced_target_dir(sync_folder, directories);
+    if linking::is_targeting(&target_dir, &path) {
+        return Ok(None);
+    }
+
+    let backup = if path.is_dir() {
+        merge_into_synced(&path, &target_dir)?
+    } else {
+        None
+    };
+
+    std::fs::create_dir_all(&target_dir)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    linking::link_dir(&target_dir, &path)?;
+    Ok(backup)
+}
+
+/// Replaces the link of a single instance with a copy of the synced folder. The copy keeps the folder from being
+/// linked again when the instance is launched while the target is synced
+pub fn unlink_instance(target: SyncTarget, dot_minecraft: &Path, directories: &LauncherDirectories) -> std::io::Result<()> {
+    crate::data_lock::check_writable(&directories.synced_dir)?;
     let Some(sync_folder) = target.get_folder() else {
         return Ok(());
     };
 
-    let mut paths = Vec::new();
+    let path = dot_minecraft.join(sync_folder);
+    let target_dir = synced_target_dir(sync_folder, directories);
+    if !linking::is_targeting(&target_dir, &path) {
+        return Ok(());
+    }
 
-    let read_dir = std::fs::read_dir(&directories.instances_dir)?;
-    for entry in read_dir {
-        let mut path = entry?.path();
-        path.push(".minecraft");
-        path.push(sync_folder);
-        paths.push(path);
+    linking::unlink_dir_if_targeting(&target_dir, &path)?;
+    if target_dir.is_dir() {
+        copy_dir_skipping(&target_dir, &path, &[])
+    } else {
+        std::fs::create_dir_all(&path)
     }
+}
+
+pub fn disable_all(target: SyncTarget, directories: &LauncherDirectories) -> std::io::Result<()> {
+    crate::data_lock::check_writable(&directories.synced_dir)?;
+    let Some(sync_folder) = target.get_folder() else {
+        return Ok(());
+    };
+
+    let paths: Vec<PathBuf> = instance_game_dirs(directories)?.into_iter()
+        .map(|(_, dot_minecraft)| dot_minecraft.join(sync_folder))
+        .collect();
 
     let non_hidden_sync_folder = if sync_folder.starts_with(".") {
         &sync_folder[1..]
@@ -377,3 +636,69 @@ mod linking {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::TempDir;
+
+    #[test]
+    fn merging_keeps_the_instance_folder_when_files_conflict() {
+        let dir = TempDir::new();
+        let instance = dir.path().join("saves");
+        let synced = dir.path().join("synced");
+        std::fs::create_dir_all(instance.join("world")).unwrap();
+        std::fs::create_dir_all(synced.join("world")).unwrap();
+        std::fs::write(instance.join("world").join("level.dat"), "instance").unwrap();
+        std::fs::write(instance.join("world").join("same.dat"), "same").unwrap();
+        std::fs::write(instance.join("new.dat"), "new").unwrap();
+        std::fs::write(synced.join("world").join("level.dat"), "synced").unwrap();
+        std::fs::write(synced.join("world").join("same.dat"), "same").unwrap();
+
+        let backup = merge_into_synced(&instance, &synced).unwrap();
+
+        assert_eq!(backup.as_deref(), Some(dir.path().join("saves.unsynced").as_path()));
+        assert!(!instance.exists());
+        assert_eq!(std::fs::read_to_string(dir.path().join("saves.unsynced").join("world").join("level.dat")).unwrap(), "instance");
+        assert_eq!(std::fs::read_to_string(synced.join("world").join("level.dat")).unwrap(), "synced");
+        assert_eq!(std::fs::read_to_string(synced.join("new.dat")).unwrap(), "new");
+    }
+
+    #[test]
+    fn merging_removes_the_instance_folder_without_conflicts() {
+        let dir = TempDir::new();
+        let instance = dir.path().join("saves");
+        let synced = dir.path().join("synced");
+        std::fs::create_dir_all(&instance).unwrap();
+        std::fs::create_dir_all(&synced).unwrap();
+        std::fs::write(instance.join("same.dat"), "same").unwrap();
+        std::fs::write(synced.join("same.dat"), "same").unwrap();
+        std::fs::write(instance.join("new.dat"), "new").unwrap();
+
+        assert_eq!(merge_into_synced(&instance, &synced).unwrap(), None);
+        assert!(!instance.exists());
+        assert_eq!(std::fs::read_to_string(synced.join("new.dat")).unwrap(), "new");
+    }
+
+    #[test]
+    fn reused_game_directories_are_not_linked() {
+        let dir = TempDir::new();
+        let directories = LauncherDirectories::new(dir.path().to_path_buf());
+        let owned = directories.instances_dir.join("Owned");
+        let reused = directories.instances_dir.join("Reused");
+        let official = dir.path().join("official/.minecraft");
+        std::fs::create_dir_all(owned.join(".minecraft")).unwrap();
+        std::fs::create_dir_all(&reused).unwrap();
+        std::fs::create_dir_all(&official).unwrap();
+        let mut configuration = InstanceConfiguration::new("1.21.1".into(), schema::loader::Loader::Vanilla);
+        configuration.game_directory = Some(official.clone().into());
+        std::fs::write(reused.join("info_v1.json"), serde_json::to_vec(&configuration).unwrap()).unwrap();
+
+        assert!(enable_all(SyncTarget::Saves, &directories).unwrap());
+        apply_to_instance(SyncTarget::Saves.into(), &directories, official.clone().into());
+
+        assert!(linking::is_targeting(&directories.synced_dir.join("saves"), &owned.join(".minecraft/saves")));
+        assert!(!official.join("saves").exists());
+        assert!(link_instance(SyncTarget::Saves, &official, &directories).is_err());
+    }
+}
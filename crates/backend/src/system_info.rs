@@ -0,0 +1,175 @@
+/// Returns the total amount of physical memory in MiB, if it can be determined
+#[cfg(target_os = "linux")]
+pub fn total_memory_mib() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|line| line.starts_with("MemTotal:"))?;
+    let kib: u64 = line.strip_prefix("MemTotal:")?.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some(kib / 1024)
+}
+
+#[cfg(target_os = "macos")]
+pub fn total_memory_mib() -> Option<u64> {
+    let mut bytes = 0u64;
+    let mut size = std::mem::size_of::<u64>();
+    let result = unsafe {
+        libc::sysctlbyname(c"hw.memsize".as_ptr(), (&raw mut bytes).cast(), &mut size, std::ptr::null_mut(), 0)
+    };
+    if result != 0 {
+        return None;
+    }
+    Some(bytes / 1024 / 1024)
+}
+
+#[cfg(windows)]
+pub fn total_memory_mib() -> Option<u64> {
+    use windows_sys::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+    let mut status: MEMORYSTATUSEX = unsafe { std::mem::zeroed() };
+    status.dwLength = std::mem::size_of::<MEMORYSTATUSEX>() as u32;
+    if unsafe { GlobalMemoryStatusEx(&mut status) } == 0 {
+        return None;
+    }
+    Some(status.ullTotalPhys / 1024 / 1024)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn total_memory_mib() -> Option<u64> {
+    None
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuInfo {
+    pub name: String,
+    pub driver: Option<String>,
+}
+
+impl std::fmt::Display for GpuInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.driver {
+            Some(driver) => write!(f, "{} (driver {})", self.name, driver),
+            None => f.write_str(&self.name),
+        }
+    }
+}
+
+/// Runs a command and returns its stdout, or None if it isn't installed or fails
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Finds the graphics cards and their driver versions. This shells out to platform tools and can
+/// take a moment, so it should only be called off the async runtime
+#[cfg(target_os = "linux")]
+pub fn query_gpus() -> Vec<GpuInfo> {
+    // glxinfo reports the renderer that the game will actually use, including the driver version
+    if let Some(output) = command_output("glxinfo", &["-B"]) {
+        let renderer = output.lines().find_map(|line| line.trim().strip_prefix("OpenGL renderer string:"));
+        let version = output.lines().find_map(|line| line.trim().strip_prefix("OpenGL version string:"));
+        if let Some(renderer) = renderer {
+            return vec![GpuInfo {
+                name: renderer.trim().to_string(),
+                driver: version.map(|version| version.trim().to_string()),
+            }];
+        }
+    }
+
+    if let Some(output) = command_output("vulkaninfo", &["--summary"]) {
+        let mut gpus: Vec<GpuInfo> = Vec::new();
+        for line in output.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key.trim() {
+                "deviceName" => gpus.push(GpuInfo { name: value.trim().to_string(), driver: None }),
+                // driverInfo comes after deviceName within each device block
+                "driverInfo" => if let Some(gpu) = gpus.last_mut() {
+                    gpu.driver = Some(value.trim().to_string());
+                },
+                _ => {},
+            }
+        }
+        if !gpus.is_empty() {
+            return gpus;
+        }
+    }
+
+    command_output("lspci", &[]).map(|output| {
+        output.lines()
+            .filter(|line| line.contains("VGA compatible controller") || line.contains("3D controller"))
+            .filter_map(|line| line.splitn(3, ':').nth(2))
+            .map(|name| GpuInfo { name: name.trim().to_string(), driver: None })
+            .collect()
+    }).unwrap_or_default()
+}
+
+#[cfg(windows)]
+pub fn query_gpus() -> Vec<GpuInfo> {
+    let Some(output) = command_output("powershell", &[
+        "-NoProfile",
+        "-NonInteractive",
+        "-Command",
+        "Get-CimInstance Win32_VideoController | ForEach-Object { $_.Name + '|' + $_.DriverVersion }",
+    ]) else {
+        return Vec::new();
+    };
+
+    output.lines()
+        .filter_map(|line| {
+            let (name, driver) = line.trim().split_once('|')?;
+            Some(GpuInfo {
+                name: name.trim().to_string(),
+                driver: Some(driver.trim().to_string()).filter(|driver| !driver.is_empty()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+pub fn query_gpus() -> Vec<GpuInfo> {
+    let Some(output) = command_output("system_profiler", &["SPDisplaysDataType"]) else {
+        return Vec::new();
+    };
+
+    let mut gpus: Vec<GpuInfo> = Vec::new();
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix("Chipset Model:") {
+            gpus.push(GpuInfo { name: name.trim().to_string(), driver: None });
+        } else if let Some(metal) = line.strip_prefix("Metal Support:").or_else(|| line.strip_prefix("Metal Family:"))
+            && let Some(gpu) = gpus.last_mut()
+        {
+            gpu.driver = Some(metal.trim().to_string());
+        }
+    }
+    gpus
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn query_gpus() -> Vec<GpuInfo> {
+    Vec::new()
+}
+
+/// Plain text summary of the system to attach to crash reports
+pub fn describe(gpus: &[GpuInfo]) -> String {
+    let mut description = format!("OS: {}\n", os_info::get());
+    description.push_str(&format!("Architecture: {}\n", std::env::consts::ARCH));
+    if let Some(memory) = total_memory_mib() {
+        description.push_str(&format!("Memory: {memory} MiB\n"));
+    }
+    if gpus.is_empty() {
+        description.push_str("GPU: unknown\n");
+    }
+    for gpu in gpus {
+        description.push_str(&format!("GPU: {gpu}\n"));
+    }
+    description
+}
@@ -0,0 +1,179 @@
+//! Helpers for exercising the backend against canned HTTP responses and throwaway directories
+
+use std::{collections::HashMap, path::{Path, PathBuf}, sync::Arc};
+
+use bridge::{handle::{BackendReceiver, FrontendHandle, FrontendReceiver}, message::{BridgeNotificationType, MessageToFrontend}};
+use parking_lot::Mutex;
+use sha1::{Digest, Sha1};
+use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::TcpListener};
+
+use crate::BackendState;
+
+// The Mojang endpoint overrides are global, and loading a backend resets them from its config
+static ENDPOINT_OVERRIDES: Mutex<()> = parking_lot::const_mutex(());
+
+/// Directory under the system temp dir that is removed when dropped
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new() -> Self {
+        let path = std::env::temp_dir().join(format!("pandora-test-{:016x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Minimal HTTP/1.1 server that answers GET requests from a fixed set of routes. A route without a query string also
+/// answers requests for its path with any query
+pub struct MockServer {
+    base_url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+    _task: tokio::task::JoinHandle<()>,
+}
+
+impl MockServer {
+    pub async fn start(routes: HashMap<String, Vec<u8>>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let routes = Arc::new(routes);
+        let requests: Arc<Mutex<Vec<String>>> = Default::default();
+
+        let task = tokio::task::spawn({
+            let requests = requests.clone();
+            async move {
+                loop {
+                    let Ok((mut stream, _)) = listener.accept().await else {
+                        return;
+                    };
+                    let routes = routes.clone();
+                    let requests = requests.clone();
+                    tokio::task::spawn(async move {
+                        let mut head = Vec::new();
+                        let mut buf = [0u8; 1024];
+                        while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+                            match stream.read(&mut buf).await {
+                                Ok(0) | Err(_) => return,
+                                Ok(read) => head.extend_from_slice(&buf[..read]),
+                            }
+                        }
+
+                        let head = String::from_utf8_lossy(&head);
+                        let path = head.split_whitespace().nth(1).unwrap_or("/").to_string();
+                        requests.lock().push(path.clone());
+
+                        let without_query = path.split('?').next().unwrap_or(&path);
+                        let response = if let Some(body) = routes.get(&path).or_else(|| routes.get(without_query)) {
+                            let mut response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len()).into_bytes();
+                            response.extend_from_slice(body);
+                            response
+                        } else {
+                            b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec()
+                        };
+                        let _ = stream.write_all(&response).await;
+                        let _ = stream.shutdown().await;
+                    });
+                }
+            }
+        });
+
+        Self {
+            base_url,
+            requests,
+            _task: task,
+        }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Paths that have been requested so far, in order
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().clone()
+    }
+}
+
+/// Frontend handle whose messages are discarded, so progress trackers can notify freely
+pub fn frontend_handle() -> FrontendHandle {
+    let (_, _, mut frontend_recv, frontend_handle) = bridge::handle::create_pair();
+    tokio::task::spawn(async move {
+        while frontend_recv.recv().await.is_some() {}
+    });
+    frontend_handle
+}
+
+pub fn http_client() -> reqwest::Client {
+    reqwest::ClientBuilder::new().build().unwrap()
+}
+
+/// Points the Mojang endpoints at a mirror until the guard is dropped, keeping backends from being loaded meanwhile
+pub fn override_endpoints(overrides: schema::backend_config::EndpointOverrides) -> EndpointOverridesGuard {
+    let lock = ENDPOINT_OVERRIDES.lock();
+    crate::endpoints::set_overrides(overrides);
+    EndpointOverridesGuard { _lock: lock }
+}
+
+pub struct EndpointOverridesGuard {
+    _lock: parking_lot::MutexGuard<'static, ()>,
+}
+
+impl Drop for EndpointOverridesGuard {
+    fn drop(&mut self) {
+        crate::endpoints::set_overrides(Default::default());
+    }
+}
+
+pub fn sha1_hex(bytes: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(bytes);
+    hex::encode(hasher.finalize())
+}
+
+/// Backend loaded from a launcher directory the way it is on startup, with the messages it sends to the frontend kept
+pub struct TestBackend {
+    pub runtime: tokio::runtime::Runtime,
+    pub state: BackendState,
+    frontend_recv: FrontendReceiver,
+    _backend_recv: BackendReceiver,
+    _watcher_rx: tokio::sync::mpsc::Receiver<notify_debouncer_full::DebounceEventResult>,
+}
+
+impl TestBackend {
+    pub fn load(launcher_dir: &Path) -> Self {
+        let (backend_recv, backend_handle, frontend_recv, frontend_handle) = bridge::handle::create_pair();
+        let loaded = {
+            let _endpoints = ENDPOINT_OVERRIDES.lock();
+            crate::backend::load(launcher_dir.to_path_buf(), frontend_handle, backend_handle)
+        };
+        Self {
+            runtime: loaded.runtime,
+            state: loaded.state,
+            frontend_recv,
+            _backend_recv: backend_recv,
+            _watcher_rx: loaded.watcher_rx,
+        }
+    }
+
+    /// Notifications sent to the frontend since the last call
+    pub fn notifications(&mut self) -> Vec<(BridgeNotificationType, Arc<str>)> {
+        std::iter::from_fn(|| self.frontend_recv.try_recv())
+            .filter_map(|message| match message {
+                MessageToFrontend::AddNotification { notification_type, message } => Some((notification_type, message)),
+                _ => None,
+            })
+            .collect()
+    }
+}
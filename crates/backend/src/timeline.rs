@@ -0,0 +1,141 @@
+use std::{path::Path, sync::Arc, time::SystemTime};
+
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use schema::instance::{InstanceTimelineEvent, InstanceTimelineEventKind};
+
+/// Older events are dropped once the timeline grows past this
+const MAX_TIMELINE_EVENTS: usize = 500;
+
+static LINE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[(\d{2}:\d{2}:\d{2})\] \[[^\]]*\]: (.*)$").unwrap());
+static USER: Lazy<Regex> = Lazy::new(|| Regex::new(r"^Setting user: (\S+)").unwrap());
+static SEED: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[CHAT\] Seed: \[(-?\d+)\]").unwrap());
+// Vanilla doesn't log coordinates on death, but many mods and servers print them in this form
+static DIED_AT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)died at\D*?(-?\d+)\D+?(-?\d+)\D+?(-?\d+)").unwrap());
+
+/// Phrases that only appear in vanilla death messages
+const DEATH_PHRASES: &[&str] = &[
+    " was slain by ", " was shot by ", " was killed", " was blown up", " was fireballed", " was pricked",
+    " was squashed", " was impaled", " was stung", " was struck by lightning", " was burnt to a crisp",
+    " was poked to death", " was obliterated", " died", " drowned", " blew up", " burned to death",
+    " fell from a high place", " fell off ", " fell out of the world", " hit the ground too hard",
+    " starved to death", " suffocated in a wall", " tried to swim in lava", " went up in flames",
+    " walked into ", " withered away", " froze to death", " experienced kinetic energy",
+];
+
+/// Extracts seeds, deaths and death coordinates from a vanilla-formatted game log. Log lines only
+/// carry a time of day, so they are placed on `date`
+pub fn parse_events(log: &str, date: NaiveDate) -> Vec<InstanceTimelineEvent> {
+    let mut events = Vec::new();
+    let mut username: Option<String> = None;
+
+    for line in log.lines() {
+        let Some(captures) = LINE.captures(line) else {
+            continue;
+        };
+        let message = captures.get(2).unwrap().as_str();
+
+        if let Some(user) = USER.captures(message) {
+            username = Some(user[1].to_string());
+            continue;
+        }
+
+        let Some(chat) = message.strip_prefix("[CHAT] ") else {
+            continue;
+        };
+
+        let (kind, text) = if let Some(seed) = SEED.captures(message) {
+            (InstanceTimelineEventKind::Seed, format!("Seed: {}", &seed[1]))
+        } else if let Some(coordinates) = DIED_AT.captures(chat) {
+            (InstanceTimelineEventKind::Coordinates, format!("Died at {} {} {}", &coordinates[1], &coordinates[2], &coordinates[3]))
+        } else if let Some(username) = &username
+            && let Some(rest) = chat.strip_prefix(username.as_str())
+            && DEATH_PHRASES.iter().any(|phrase| rest.starts_with(phrase))
+        {
+            (InstanceTimelineEventKind::Death, chat.to_string())
+        } else {
+            continue;
+        };
+
+        let Ok(time_of_day) = NaiveTime::parse_from_str(&captures[1], "%H:%M:%S") else {
+            continue;
+        };
+        let time = Local.from_local_datetime(&date.and_time(time_of_day)).earliest()
+            .map(|time| time.timestamp_millis())
+            .unwrap_or_default();
+
+        events.push(InstanceTimelineEvent {
+            time,
+            kind,
+            text: text.into(),
+        });
+    }
+
+    events
+}
+
+/// Reads latest.log if it was written by the session that started at `since`
+pub fn capture_from_latest_log(dot_minecraft: &Path, since: SystemTime) -> Vec<InstanceTimelineEvent> {
+    let path = dot_minecraft.join("logs").join("latest.log");
+    let Ok(modified) = path.metadata().and_then(|metadata| metadata.modified()) else {
+        return Vec::new();
+    };
+    if modified < since {
+        return Vec::new();
+    }
+
+    let Ok(bytes) = std::fs::read(&path) else {
+        return Vec::new();
+    };
+    let started: chrono::DateTime<Local> = since.into();
+    parse_events(&String::from_utf8_lossy(&bytes), started.date_naive())
+}
+
+pub fn load(instance_root: &Path) -> Arc<[InstanceTimelineEvent]> {
+    std::fs::read(instance_root.join("timeline.json")).ok()
+        .and_then(|bytes| serde_json::from_slice::<Vec<InstanceTimelineEvent>>(&bytes).ok())
+        .map(Arc::from)
+        .unwrap_or_default()
+}
+
+pub fn append(instance_root: &Path, events: Vec<InstanceTimelineEvent>) -> std::io::Result<()> {
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let mut timeline = load(instance_root).to_vec();
+    timeline.extend(events);
+    if timeline.len() > MAX_TIMELINE_EVENTS {
+        timeline.drain(..timeline.len() - MAX_TIMELINE_EVENTS);
+    }
+
+    let json = serde_json::to_vec_pretty(&timeline).map_err(std::io::Error::other)?;
+    crate::write_safe(&instance_root.join("timeline.json"), &json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds_deaths_and_coordinates_are_recognised() {
+        let log = "\
+[18:01:02] [Render thread/INFO]: Setting user: Steve
+[18:05:00] [Render thread/INFO]: [CHAT] Seed: [-4172144997902289642]
+[18:06:00] [Render thread/INFO]: [CHAT] <Alex> Steve died at spawn lol
+[18:07:00] [Render thread/INFO]: [CHAT] Steve was slain by Zombie
+[18:07:00] [Render thread/INFO]: [CHAT] You died at X: 120 Y: 64 Z: -35
+[18:08:00] [Render thread/INFO]: [CHAT] Steve joined the game
+";
+        let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let events = parse_events(log, date);
+
+        let summary: Vec<(InstanceTimelineEventKind, &str)> = events.iter().map(|event| (event.kind, &*event.text)).collect();
+        assert_eq!(summary, vec![
+            (InstanceTimelineEventKind::Seed, "Seed: -4172144997902289642"),
+            (InstanceTimelineEventKind::Death, "Steve was slain by Zombie"),
+            (InstanceTimelineEventKind::Coordinates, "Died at 120 64 -35"),
+        ]);
+    }
+}
@@ -1,77 +1,153 @@
-use std::{ffi::{OsStr, OsString}, io::Cursor, path::{Path, PathBuf}, sync::Arc};
+use std::{ffi::{OsStr, OsString}, io::Cursor, path::{Path, PathBuf}, sync::Arc, time::Duration};
 
 use base64::Engine;
 use bridge::{handle::FrontendHandle, message::MessageToFrontend, modal_action::{ModalAction, ProgressTracker}};
+use chrono::Utc;
 use reqwest::StatusCode;
-use schema::pandora_update::{UpdateInstallType, UpdateManifest, UpdatePrompt};
+use schema::pandora_update::{GithubRelease, GithubReleaseAsset, UpdateChannel, UpdateCheckFrequency, UpdateInstallType, UpdateManifest, UpdatePrompt};
+use serde::de::DeserializeOwned;
 use sha1::{Digest, Sha1};
 use rand::RngCore;
 
-use crate::directories::LauncherDirectories;
-
-pub async fn check_for_updates(http_client: reqwest::Client, send: FrontendHandle) {
-    if option_env!("PANDORA_UPDATE_PUBKEY").is_none() {
-        return;
-    }
-
-    let Some(version) = option_env!("PANDORA_RELEASE_VERSION") else {
-        log::warn!("Skipping update check because PANDORA_RELEASE_VERSION isn't set");
-
-        #[cfg(not(debug_assertions))] // Don't show error in non-release builds
-        send.send_warning("Unable to check for updates, missing PANDORA_RELEASE_VERSION");
-        return;
-    };
+use crate::{BackendState, directories::LauncherDirectories};
+
+/// GitHub releases looked through for the newest one on the update channel
+const RELEASES_PER_PAGE: usize = 20;
+
+/// How often the launcher looks at the configured update frequency to see whether a check is due
+const WATCH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(thiserror::Error, Debug)]
+pub enum UpdateCheckError {
+    #[error("This build of Pandora can't update itself")]
+    Unsupported,
+    #[error("Unable to check for updates, missing {0}")]
+    MissingBuildVariable(&'static str),
+    #[error("Unable to fetch Pandora update information:\n{0}")]
+    Request(#[from] reqwest::Error),
+    #[error("Unable to fetch Pandora update information, non-200 status code: {0}")]
+    Status(StatusCode),
+    #[error("Unable to parse Pandora update information:\n{0}")]
+    Parse(#[from] serde_json::Error),
+}
 
-    let Some(repository_url) = option_env!("GITHUB_REPOSITORY_URL") else {
-        log::warn!("Skipping update check because GITHUB_REPOSITORY_URL isn't set");
+impl BackendState {
+    /// Checks for updates on startup and then whenever the configured frequency says another check is due. A version
+    /// is only offered once per session, declining it isn't undone by the next check
+    pub async fn watch_for_updates(self) {
+        let mut interval = tokio::time::interval(WATCH_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut startup = true;
+        let mut offered: Option<Arc<str>> = None;
+        // Checks are retried every tick while they fail, only the first failure since the last success is shown
+        let mut reported_error = false;
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {},
+                _ = self.shutdown.cancelled() => return,
+            }
 
-        #[cfg(not(debug_assertions))] // Don't show error in non-release builds
-        send.send_warning("Unable to check for updates, missing GITHUB_REPOSITORY_URL");
-        return;
-    };
+            let (channel, frequency, last_check) = {
+                let mut config = self.config.write();
+                let config = config.get();
+                (config.update_channel, config.update_check_frequency, config.last_update_check)
+            };
+            let due = match frequency.interval() {
+                _ if frequency == UpdateCheckFrequency::Never => false,
+                None => startup,
+                Some(interval) => last_check.is_none_or(|last| (Utc::now() - last).to_std().is_ok_and(|elapsed| elapsed >= interval)),
+            };
+            startup = false;
+            if !due || crate::offline::is_offline() {
+                continue;
+            }
 
-    let current_version = schema::forge::VersionFragment::string_to_parts(version);
+            let result = find_update(&self.redirecting_http_client, channel).await;
+            // A failed check is tried again on the next tick instead of waiting out the whole interval
+            if result.is_ok() {
+                reported_error = false;
+                self.config.write().modify(|config| {
+                    config.last_update_check = Some(Utc::now());
+                });
+            }
 
-    let url = format!("{repository_url}/releases/latest/download/update_{}.json", std::env::consts::OS);
-    let response = http_client.get(url).send().await;
+            match result {
+                Ok(Some(update)) => {
+                    if offered.as_ref() != Some(&update.new_version) {
+                        offered = Some(update.new_version.clone());
+                        self.send.send(MessageToFrontend::UpdateAvailable { update });
+                    }
+                },
+                Ok(None) => {},
+                Err(UpdateCheckError::Unsupported) => return,
+                Err(error) => {
+                    tracing::error!("Error while checking for updates: {error}");
+                    // Development builds are expected to be missing them
+                    if cfg!(debug_assertions) && matches!(error, UpdateCheckError::MissingBuildVariable(_)) {
+                        return;
+                    }
+                    if !reported_error {
+                        reported_error = true;
+                        self.send.send_error(error.to_string());
+                    }
+                },
+            }
+        }
+    }
+}
 
-    let response = match response {
-        Ok(response) => response,
-        Err(err) => {
-            log::error!("Error while requesting update manifest: {}", err);
-            send.send_error("Unable to fetch Pandora update manifest, see logs for more details");
-            return;
+/// Check asked for by the user, which also reports when the launcher is already up-to-date
+pub async fn check_for_updates(http_client: reqwest::Client, send: FrontendHandle, channel: UpdateChannel) {
+    match find_update(&http_client, channel).await {
+        Ok(Some(update)) => send.send(MessageToFrontend::UpdateAvailable { update }),
+        Ok(None) => send.send_info(rust_i18n::t!("notification_up_to_date")),
+        Err(error) => {
+            tracing::error!("Error while checking for updates: {error}");
+            send.send_error(error.to_string());
         },
-    };
-
-    if response.status() != StatusCode::OK {
-        send.send_error(format!("Unable to fetch Pandora update manifest, non-200 status code: {}", response.status()));
-        return;
     }
+}
 
-    let manifest_bytes = match response.bytes().await {
-        Ok(manifest_bytes) => manifest_bytes,
-        Err(err) => {
-            log::error!("Error while downloading update manifest: {}", err);
-            send.send_error("Unable to download Pandora update manifest, see logs for more details");
-            return;
+async fn find_update(http_client: &reqwest::Client, channel: UpdateChannel) -> Result<Option<UpdatePrompt>, UpdateCheckError> {
+    if option_env!("PANDORA_UPDATE_PUBKEY").is_none() {
+        return Err(UpdateCheckError::Unsupported);
+    }
+    let version = option_env!("PANDORA_RELEASE_VERSION").ok_or(UpdateCheckError::MissingBuildVariable("PANDORA_RELEASE_VERSION"))?;
+    let repository_url = option_env!("GITHUB_REPOSITORY_URL").ok_or(UpdateCheckError::MissingBuildVariable("GITHUB_REPOSITORY_URL"))?;
+
+    let manifest_name = format!("update_{}.json", std::env::consts::OS);
+    // Without the releases API only the latest stable release can be found, and not its notes
+    let latest_manifest_url = format!("{repository_url}/releases/latest/download/{manifest_name}");
+    let (manifest_url, changelog) = match releases_api_url(repository_url) {
+        Some(api_url) => {
+            let request = http_client.get(api_url).header(reqwest::header::ACCEPT, "application/vnd.github+json");
+            match get_json::<Vec<GithubRelease>>(request).await {
+                Ok(releases) => {
+                    let Some((release, manifest)) = newest_release(&releases, channel, &manifest_name) else {
+                        tracing::info!("No {:?} release has {}", channel, manifest_name);
+                        return Ok(None);
+                    };
+                    let changelog = release.body.as_deref().map(plain_changelog).filter(|body| !body.is_empty());
+                    (manifest.browser_download_url.to_string(), changelog.map(Arc::from))
+                },
+                // The API is rate limited per address, the download link of the latest release isn't
+                Err(error) => {
+                    tracing::warn!("Unable to list releases, falling back to the latest release: {error}");
+                    (latest_manifest_url, None)
+                },
+            }
         },
+        None => (latest_manifest_url, None),
     };
 
-    let manifest = match serde_json::from_slice::<UpdateManifest>(&manifest_bytes) {
-        Ok(manifest) => manifest,
-        Err(err) => {
-            log::error!("Error while parsing update manifest: {}", err);
-            send.send_error("Unable to parse update manifest, see logs for more details");
-            return;
-        },
-    };
+    let manifest: UpdateManifest = get_json(http_client.get(manifest_url)).await?;
 
+    let current_version = schema::forge::VersionFragment::string_to_parts(version);
     let update_version = schema::forge::VersionFragment::string_to_parts(&manifest.version);
 
     if current_version >= update_version {
-        log::info!("Pandora is up-to-date");
-        return;
+        tracing::info!("Pandora is up-to-date");
+        return Ok(None);
     }
 
     let exes = if let Some(universal) = manifest.downloads.archs.get("universal") {
@@ -79,29 +155,110 @@ pub async fn check_for_updates(http_client: reqwest::Client, send: FrontendHandl
     } else if let Some(exes) = manifest.downloads.archs.get(std::env::consts::ARCH) {
         exes
     } else {
-        log::warn!("Unable to update, can't find arch \"{}\" in {:?}", std::env::consts::ARCH, manifest.downloads.archs.keys());
-        return;
+        tracing::warn!("Unable to update, can't find arch \"{}\" in {:?}", std::env::consts::ARCH, manifest.downloads.archs.keys());
+        return Ok(None);
     };
 
     let Some(install_type) = determine_update_install_type() else {
-        log::warn!("Unable to update, can't determine installation type");
-        return;
+        tracing::warn!("Unable to update, can't determine installation type");
+        return Ok(None);
     };
 
     let install_type_key = install_type.key();
     let Some(executable) = exes.exes.get(install_type_key) else {
-        log::warn!("Unable to update, installation type \"{}\" not in {:?}", install_type_key, exes.exes.keys());
-        return;
+        tracing::warn!("Unable to update, installation type \"{}\" not in {:?}", install_type_key, exes.exes.keys());
+        return Ok(None);
     };
 
-    send.send(MessageToFrontend::UpdateAvailable {
-        update: UpdatePrompt {
-            old_version: version.into(),
-            new_version: manifest.version.clone(),
-            install_type,
-            exe: executable.clone(),
+    Ok(Some(UpdatePrompt {
+        old_version: version.into(),
+        new_version: manifest.version.clone(),
+        install_type,
+        exe: executable.clone(),
+        changelog,
+    }))
+}
+
+async fn get_json<T: DeserializeOwned>(request: reqwest::RequestBuilder) -> Result<T, UpdateCheckError> {
+    let response = request.send().await?;
+    if response.status() != StatusCode::OK {
+        return Err(UpdateCheckError::Status(response.status()));
+    }
+    Ok(serde_json::from_slice(&response.bytes().await?)?)
+}
+
+/// API listing the releases of a repository on github.com, None for other hosts
+fn releases_api_url(repository_url: &str) -> Option<String> {
+    let repository = repository_url.strip_prefix("https://github.com/")?.trim_end_matches('/');
+    Some(format!("https://api.github.com/repos/{repository}/releases?per_page={RELEASES_PER_PAGE}"))
+}
+
+/// Newest published release on `channel` with an update manifest for this platform. The API lists releases newest
+/// first, so that's the first one that qualifies
+fn newest_release<'a>(releases: &'a [GithubRelease], channel: UpdateChannel, manifest_name: &str) -> Option<(&'a GithubRelease, &'a GithubReleaseAsset)> {
+    releases.iter()
+        .filter(|release| !release.draft && (channel == UpdateChannel::Prerelease || !release.prerelease))
+        .find_map(|release| {
+            let manifest = release.assets.iter().find(|asset| &*asset.name == manifest_name)?;
+            Some((release, manifest))
+        })
+}
+
+/// Release notes are written in markdown, which the update prompt shows as plain text. Strips the markup that
+/// would otherwise show up literally: heading markers, bullets, bold, inline code, link targets and html comments
+fn plain_changelog(markdown: &str) -> String {
+    let mut without_comments = String::new();
+    let mut rest = markdown;
+    while let Some(start) = rest.find("<!--") {
+        without_comments.push_str(&rest[..start]);
+        rest = rest[start..].find("-->").map_or("", |end| &rest[start + end + 3..]);
+    }
+    without_comments.push_str(rest);
+
+    let mut lines = Vec::new();
+    for line in without_comments.lines() {
+        let trimmed = line.trim_end();
+        let indent = &trimmed[..trimmed.len() - trimmed.trim_start().len()];
+        let mut content = trimmed.trim_start();
+
+        let heading = content.len() - content.trim_start_matches('#').len();
+        if heading > 0 && content[heading..].starts_with(' ') {
+            content = content[heading..].trim_start();
         }
-    });
+        let bullet = if let Some(item) = content.strip_prefix("- ").or_else(|| content.strip_prefix("* ")) {
+            content = item;
+            "• "
+        } else {
+            ""
+        };
+
+        // [text](target) keeps the text
+        let mut plain = String::with_capacity(content.len());
+        let mut rest = content;
+        while let Some(open) = rest.find('[') {
+            let after = &rest[open + 1..];
+            let Some(close) = after.find("](") else {
+                break;
+            };
+            let Some(end) = after[close + 2..].find(')') else {
+                break;
+            };
+            plain.push_str(&rest[..open]);
+            plain.push_str(&after[..close]);
+            rest = &after[close + 2 + end + 1..];
+        }
+        plain.push_str(rest);
+        let plain = plain.replace("**", "").replace("__", "").replace('`', "");
+
+        if plain.is_empty() && lines.last().is_none_or(|last: &String| last.is_empty()) {
+            continue;
+        }
+        lines.push(format!("{indent}{bullet}{plain}"));
+    }
+    while lines.last().is_some_and(|last| last.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
 }
 
 fn determine_update_install_type() -> Option<UpdateInstallType> {
@@ -150,7 +307,7 @@ pub async fn install_update(http_client: reqwest::Client, dirs: Arc<LauncherDire
 }
 
 async fn install_update_inner(http_client: reqwest::Client, dirs: &LauncherDirectories, send: FrontendHandle, update: UpdatePrompt, modal_action: ModalAction) -> Result<(), Arc<str>> {
-    let title = format!("Downloading Pandora {}", update.new_version);
+    let title = rust_i18n::t!("progress_downloading_update", version = update.new_version);
     let tracker = ProgressTracker::new(title.into(), send.clone());
     modal_action.trackers.push(tracker.clone());
 
@@ -167,7 +324,8 @@ async fn install_update_inner(http_client: reqwest::Client, dirs: &LauncherDirec
         return Err("Download URL returned non-200 status code".into());
     }
 
-    tracker.set_total(update.exe.size);
+    tracker.set_counts_bytes();
+    tracker.set_total(update.exe.size as u64);
     tracker.notify();
 
     use futures::StreamExt;
@@ -181,7 +339,7 @@ async fn install_update_inner(http_client: reqwest::Client, dirs: &LauncherDirec
         };
 
         bytes.extend_from_slice(&*item);
-        tracker.add_count(item.len());
+        tracker.add_count(item.len() as u64);
         tracker.notify();
     }
 
@@ -241,13 +399,13 @@ async fn install_update_inner(http_client: reqwest::Client, dirs: &LauncherDirec
         UpdateInstallType::App(current_app_folder) => {
             let mut temp_extract = dirs.temp_dir.join(format!("app_unpack_{}", rand::thread_rng().next_u64()));
             while temp_extract.exists() {
-                log::warn!("Randomly generated app_unpack folder exists... what are the chances? ({:?})", temp_extract);
+                tracing::warn!("Randomly generated app_unpack folder exists... what are the chances? ({:?})", temp_extract);
                 temp_extract = dirs.temp_dir.join(format!("app_unpack_{}", rand::thread_rng().next_u64()));
             }
 
             let mut temp_backup = dirs.temp_dir.join(format!("app_backup_{}", rand::thread_rng().next_u64()));
             while temp_backup.exists() {
-                log::warn!("Randomly generated app_backup folder exists... what are the chances? ({:?})", temp_backup);
+                tracing::warn!("Randomly generated app_backup folder exists... what are the chances? ({:?})", temp_backup);
                 temp_backup = dirs.temp_dir.join(format!("app_backup_{}", rand::thread_rng().next_u64()));
             }
 
@@ -270,12 +428,12 @@ async fn install_update_inner(http_client: reqwest::Client, dirs: &LauncherDirec
 fn write_new_exe(old_exe: PathBuf, new_exe: PathBuf, data: &[u8], dirs: &LauncherDirectories) -> Result<(), String> {
     let mut new_exe_data = dirs.temp_dir.join(format!("new_exe_data_{}", rand::thread_rng().next_u64()));
     while new_exe_data.exists() {
-        log::warn!("Randomly generated new_exe_data file exists... what are the chances? ({:?})", new_exe_data);
+        tracing::warn!("Randomly generated new_exe_data file exists... what are the chances? ({:?})", new_exe_data);
         new_exe_data = dirs.temp_dir.join(format!("new_exe_data_{}", rand::thread_rng().next_u64()));
     }
 
     if let Err(err) = std::fs::write(&new_exe_data, data) {
-        log::error!("Error while writing new executable: {}", err);
+        tracing::error!("Error while writing new executable: {}", err);
         return Err("Error while writing new executable, see logs for more details".into());
     }
 
@@ -333,7 +491,7 @@ fn move_new_exe_into(old_exe_path: PathBuf, new_exe_path: PathBuf, new_exe_data:
                         command.push("' }");
                     }
 
-                    log::info!("{}", command.to_string_lossy());
+                    tracing::info!("{}", command.to_string_lossy());
 
                     runas::Command::new("powershell.exe")
                         .arg("-Command")
@@ -347,11 +505,11 @@ fn move_new_exe_into(old_exe_path: PathBuf, new_exe_path: PathBuf, new_exe_data:
                         return Ok(())
                     },
                     Ok(status) => {
-                        log::error!("Error completing elevated executable install: {}", status);
+                        tracing::error!("Error completing elevated executable install: {}", status);
                         return Err("Error completing elevated executable installation, see logs for more details".into());
                     },
                     Err(err) => {
-                        log::error!("Error completing elevated executable install: {}", err);
+                        tracing::error!("Error completing elevated executable install: {}", err);
                         return Err("Error completing elevated executable installation, see logs for more details".into());
                     },
                 }
@@ -379,7 +537,7 @@ fn install_app_update(current_app_folder: PathBuf, bytes: &[u8], temp_extract: &
     let mut archive = tar::Archive::new(gz_decoder);
 
     if let Err(err) = archive.unpack(&temp_extract) {
-        log::error!("Unable to unpack .app.tar.gz: {}", err);
+        tracing::error!("Unable to unpack .app.tar.gz: {}", err);
         return Err("Error while unpacking .app.tar.gz archive, see logs for more details".into());
     }
 
@@ -388,7 +546,7 @@ fn install_app_update(current_app_folder: PathBuf, bytes: &[u8], temp_extract: &
             return Err("Unable to find .app folder in extracted archive".into());
         },
         Err(err) => {
-            log::error!("Unable to find .app folder: {}", err);
+            tracing::error!("Unable to find .app folder: {}", err);
             return Err("I/O error while finding .app folder, see logs for more details".into());
         },
         Ok(Some(app_dir)) => app_dir,
@@ -399,7 +557,7 @@ fn install_app_update(current_app_folder: PathBuf, bytes: &[u8], temp_extract: &
         Ok(_) => false,
         Err(err) if err.kind() == std::io::ErrorKind::PermissionDenied => true,
         Err(err) => {
-            log::error!("Unable to backup current .app: {}", err);
+            tracing::error!("Unable to backup current .app: {}", err);
             return Err("I/O error while backing up current .app, see logs for more details".into());
         }
     };
@@ -426,11 +584,11 @@ fn install_app_update(current_app_folder: PathBuf, bytes: &[u8], temp_extract: &
         let success = match result {
             Ok(status) if status.success() => true,
             Ok(status) => {
-                log::error!("Error completing elevated .app install: {}", status);
+                tracing::error!("Error completing elevated .app install: {}", status);
                 false
             },
             Err(err) => {
-                log::error!("Error completing elevated .app install: {}", err);
+                tracing::error!("Error completing elevated .app install: {}", err);
                 false
             },
         };
@@ -452,7 +610,7 @@ fn install_app_update(current_app_folder: PathBuf, bytes: &[u8], temp_extract: &
     } else {
         if let Err(err) = std::fs::rename(&app_dir, &current_app_folder) {
             _ = std::fs::rename(&temp_backup, &current_app_folder);
-            log::error!("Error renaming new .app to old .app: {}", err);
+            tracing::error!("Error renaming new .app to old .app: {}", err);
             return Err("Error completing elevated .app installation, see logs for more details".into());
         }
     }
@@ -495,3 +653,52 @@ fn find_child_with_extension(folder: &Path, extension: &OsStr) -> std::io::Resul
 
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str, prerelease: bool, assets: &[&str]) -> GithubRelease {
+        serde_json::from_value(serde_json::json!({
+            "tag_name": tag,
+            "prerelease": prerelease,
+            "body": format!("Changes in {tag}"),
+            "assets": assets.iter().map(|name| serde_json::json!({
+                "name": name,
+                "browser_download_url": format!("https://example.com/{tag}/{name}"),
+            })).collect::<Vec<_>>(),
+        })).unwrap()
+    }
+
+    #[test]
+    fn newest_release_follows_the_channel() {
+        let releases = [
+            release("v1.3.0-beta", true, &["update_linux.json"]),
+            release("v1.2.1", false, &["update_windows.json"]),
+            release("v1.2.0", false, &["update_linux.json", "update_windows.json"]),
+        ];
+
+        let (stable, manifest) = newest_release(&releases, UpdateChannel::Stable, "update_linux.json").unwrap();
+        assert_eq!(&*stable.tag_name, "v1.2.0");
+        assert_eq!(&*manifest.browser_download_url, "https://example.com/v1.2.0/update_linux.json");
+
+        let (prerelease, _) = newest_release(&releases, UpdateChannel::Prerelease, "update_linux.json").unwrap();
+        assert_eq!(&*prerelease.tag_name, "v1.3.0-beta");
+        assert!(newest_release(&releases, UpdateChannel::Stable, "update_macos.json").is_none());
+    }
+
+    #[test]
+    fn changelog_markup_is_stripped() {
+        let markdown = "## What's Changed\r\n<!-- generated -->\r\n* **Fix** crash in `mods_page` by @someone in [#12](https://example.com/pull/12)\r\n  - nested_item\r\n\r\n\r\n\r\nDone\r\n";
+        assert_eq!(plain_changelog(markdown), "What's Changed\n\n• Fix crash in mods_page by @someone in #12\n  • nested_item\n\nDone");
+    }
+
+    #[test]
+    fn releases_api_is_only_used_for_github() {
+        assert_eq!(
+            releases_api_url("https://github.com/Moulberry/PandoraLauncher").as_deref(),
+            Some("https://api.github.com/repos/Moulberry/PandoraLauncher/releases?per_page=20")
+        );
+        assert_eq!(releases_api_url("https://git.example.com/pandora"), None);
+    }
+}
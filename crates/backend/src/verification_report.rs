@@ -0,0 +1,58 @@
+use std::{sync::atomic::{AtomicU64, AtomicUsize, Ordering}, time::Duration};
+
+/// Counts what happened to each file while preparing a launch, so slow launches can be explained
+#[derive(Default)]
+pub struct VerificationReport {
+    verified: AtomicUsize,
+    downloaded: AtomicUsize,
+    repaired: AtomicUsize,
+    bytes_downloaded: AtomicU64,
+}
+
+impl VerificationReport {
+    /// The file was already on disk and matched its expected hash
+    pub fn record_verified(&self) {
+        self.verified.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The file was downloaded, `repaired` being true if a corrupt copy was replaced
+    pub fn record_downloaded(&self, bytes: usize, repaired: bool) {
+        if repaired {
+            self.repaired.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.downloaded.fetch_add(1, Ordering::Relaxed);
+        }
+        self.bytes_downloaded.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn verified(&self) -> usize {
+        self.verified.load(Ordering::Relaxed)
+    }
+
+    pub fn downloaded(&self) -> usize {
+        self.downloaded.load(Ordering::Relaxed)
+    }
+
+    pub fn repaired(&self) -> usize {
+        self.repaired.load(Ordering::Relaxed)
+    }
+
+    pub fn summary(&self, duration: Duration) -> String {
+        let mut summary = format!("Prepared launch in {:.1}s: {} files verified", duration.as_secs_f32(), self.verified());
+
+        let downloaded = self.downloaded();
+        let repaired = self.repaired();
+        if downloaded > 0 {
+            summary.push_str(&format!(", {downloaded} downloaded"));
+        }
+        if repaired > 0 {
+            summary.push_str(&format!(", {repaired} repaired"));
+        }
+        if downloaded > 0 || repaired > 0 {
+            let mib = self.bytes_downloaded.load(Ordering::Relaxed) as f64 / (1024.0 * 1024.0);
+            summary.push_str(&format!(" ({mib:.1} MiB)"));
+        }
+
+        summary
+    }
+}
@@ -0,0 +1,357 @@
+use std::{
+    collections::HashMap, io::{Cursor, Read}, path::Path, sync::Arc
+};
+
+use anyhow::Context;
+use bridge::instance::{InstanceID, WorldPreview, WorldPreviewBiome, WorldPreviewStructure};
+use image::{Rgba, RgbaImage};
+use nbt::{CompoundRef, NBT};
+
+use crate::BackendState;
+
+/// The preview covers this many chunks in each direction from the spawn
+const RADIUS_CHUNKS: i32 = 32;
+/// Biomes are stored per 4x4x4 cell, so that's the resolution of the preview
+const BLOCKS_PER_PIXEL: i32 = 4;
+const CELLS_PER_CHUNK: i32 = 16 / BLOCKS_PER_PIXEL;
+/// Lowest block of the overworld since 1.18, heightmaps are relative to it
+const MIN_Y: i32 = -64;
+/// Used when a chunk has no heightmap yet
+const SEA_LEVEL: i32 = 63;
+
+const SPAWN_COLOR: Rgba<u8> = Rgba([255, 0, 0, 255]);
+const STRUCTURE_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+/// Roughly the colors used by seed mapping tools, so the previews look familiar
+fn vanilla_biome_color(id: &str) -> Option<u32> {
+    Some(match id.strip_prefix("minecraft:")? {
+        "ocean" => 0x000070,
+        "deep_ocean" => 0x000030,
+        "warm_ocean" => 0x0000ac,
+        "lukewarm_ocean" => 0x000090,
+        "deep_lukewarm_ocean" => 0x000040,
+        "cold_ocean" => 0x202070,
+        "deep_cold_ocean" => 0x202038,
+        "frozen_ocean" => 0x7070d6,
+        "deep_frozen_ocean" => 0x404090,
+        "river" => 0x0000ff,
+        "frozen_river" => 0xa0a0ff,
+        "beach" => 0xfade55,
+        "snowy_beach" => 0xfaf0c0,
+        "stony_shore" => 0xa2a284,
+        "plains" => 0x8db360,
+        "sunflower_plains" => 0xb5db88,
+        "snowy_plains" => 0xffffff,
+        "ice_spikes" => 0xb4dcdc,
+        "desert" => 0xfa9418,
+        "swamp" => 0x07f9b2,
+        "mangrove_swamp" => 0x2ccc8e,
+        "forest" => 0x056621,
+        "flower_forest" => 0x2d8e49,
+        "birch_forest" => 0x307444,
+        "old_growth_birch_forest" => 0x589c6c,
+        "dark_forest" => 0x40511a,
+        "pale_garden" => 0x8a948a,
+        "taiga" => 0x0b6659,
+        "old_growth_pine_taiga" => 0x596651,
+        "old_growth_spruce_taiga" => 0x818e79,
+        "snowy_taiga" => 0x31554a,
+        "savanna" => 0xbdb25f,
+        "savanna_plateau" => 0xa79d64,
+        "windswept_savanna" => 0xe5da87,
+        "windswept_hills" => 0x606060,
+        "windswept_gravelly_hills" => 0x888888,
+        "windswept_forest" => 0x507050,
+        "jungle" => 0x537b09,
+        "sparse_jungle" => 0x628b17,
+        "bamboo_jungle" => 0x768e14,
+        "badlands" => 0xd94515,
+        "eroded_badlands" => 0xff6d3d,
+        "wooded_badlands" => 0xb09765,
+        "meadow" => 0x60a445,
+        "cherry_grove" => 0xffb7c5,
+        "grove" => 0x47726c,
+        "snowy_slopes" => 0xc4c4c4,
+        "frozen_peaks" => 0xa0a0a0,
+        "jagged_peaks" => 0xdcdcc8,
+        "stony_peaks" => 0x7b8f74,
+        "mushroom_fields" => 0xff00ff,
+        "dripstone_caves" => 0x4e3012,
+        "lush_caves" => 0x283c00,
+        "deep_dark" => 0x031f29,
+        _ => return None,
+    })
+}
+
+/// Modded biomes get a stable color derived from their id
+fn biome_color(id: &str) -> u32 {
+    vanilla_biome_color(id).unwrap_or_else(|| {
+        let hash = id.bytes().fold(0x811c9dc5_u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x01000193));
+        hash & 0xffffff
+    })
+}
+
+fn rgba(color: u32) -> Rgba<u8> {
+    let [_, r, g, b] = color.to_be_bytes();
+    Rgba([r, g, b, 255])
+}
+
+/// Entry of a long array where the entries don't span across longs, as used for paletted containers and heightmaps
+fn unpack(data: &[i64], bits: usize, index: usize) -> Option<usize> {
+    let per_long = 64 / bits;
+    let long = *data.get(index / per_long)? as u64;
+    Some(((long >> ((index % per_long) * bits)) & ((1 << bits) - 1)) as usize)
+}
+
+/// Reads one chunk from the contents of a region file, None if it hasn't been generated
+fn read_chunk(region: &[u8], local_x: i32, local_z: i32) -> anyhow::Result<Option<NBT>> {
+    let header = 4 * (local_x + local_z * 32) as usize;
+    let location = u32::from_be_bytes(region.get(header..header + 4).context("Region header is truncated")?.try_into()?);
+    let offset = (location >> 8) as usize * 4096;
+    if offset == 0 {
+        return Ok(None);
+    }
+
+    let length = u32::from_be_bytes(region.get(offset..offset + 4).context("Chunk is outside the region file")?.try_into()?) as usize;
+    let compression = *region.get(offset + 4).context("Chunk is outside the region file")?;
+    let data = region.get(offset + 5..offset + 4 + length).context("Chunk is truncated")?;
+
+    // Chunks stored outside the region file or compressed with LZ4 are left out of the preview
+    let mut decompressed = Vec::new();
+    match compression {
+        1 => _ = flate2::bufread::GzDecoder::new(data).read_to_end(&mut decompressed)?,
+        2 => _ = flate2::bufread::ZlibDecoder::new(data).read_to_end(&mut decompressed)?,
+        3 => return Ok(Some(nbt::decode::read_named(&mut &*data)?)),
+        compression => anyhow::bail!("Unsupported chunk compression {compression}"),
+    };
+    Ok(Some(nbt::decode::read_named(&mut decompressed.as_slice())?))
+}
+
+/// Biome of the surface for each 4x4 column of the chunk, x fastest. Only chunks from 1.18 and later are supported
+fn surface_biomes(chunk: &CompoundRef) -> Option<Vec<String>> {
+    let sections = chunk.find_list("sections", nbt::TAG_COMPOUND_ID)?;
+    let heightmap = chunk.find_compound("Heightmaps")
+        .and_then(|heightmaps| heightmaps.find_long_array("WORLD_SURFACE").cloned());
+
+    let mut biomes = Vec::with_capacity((CELLS_PER_CHUNK * CELLS_PER_CHUNK) as usize);
+    for cell_z in 0..CELLS_PER_CHUNK {
+        for cell_x in 0..CELLS_PER_CHUNK {
+            let column = (cell_x * 4 + 2 + (cell_z * 4 + 2) * 16) as usize;
+            let y = heightmap.as_deref()
+                .and_then(|heightmap| unpack(heightmap, 9, column))
+                .map_or(SEA_LEVEL, |height| height as i32 + MIN_Y - 1);
+
+            let section = sections.iter()
+                .filter_map(|section| section.as_compound())
+                .find(|section| section.find_numeric::<i32>("Y") == Some(y.div_euclid(16)))?;
+            let section_biomes = section.find_compound("biomes")?;
+            let palette = section_biomes.find_list("palette", nbt::TAG_STRING_ID)?;
+
+            let index = if palette.len() <= 1 {
+                0
+            } else {
+                let bits = (usize::BITS - (palette.len() - 1).leading_zeros()) as usize;
+                let cell = ((y.rem_euclid(16) / 4) * 16 + cell_z * 4 + cell_x) as usize;
+                unpack(section_biomes.find_long_array("data")?, bits, cell)?
+            };
+            biomes.push(palette.get_string(index)?.clone());
+        }
+    }
+    Some(biomes)
+}
+
+/// Structures that start in the chunk
+fn structure_starts(chunk: &CompoundRef) -> Vec<String> {
+    let Some(starts) = chunk.find_compound("structures").and_then(|structures| structures.find_compound("starts")) else {
+        return Vec::new();
+    };
+    starts.entries()
+        .filter(|(_, start)| start.as_compound()
+            .and_then(|start| start.find_string("id").cloned())
+            .is_some_and(|id| id != "INVALID"))
+        .map(|(id, _)| id.to_string())
+        .collect()
+}
+
+struct Level {
+    spawn: (i32, i32),
+    seed: Option<i64>,
+}
+
+fn read_level(level_path: &Path) -> anyhow::Result<Level> {
+    let compressed = std::fs::read(level_path.join("level.dat"))?;
+    let mut decompressed = Vec::new();
+    flate2::bufread::GzDecoder::new(compressed.as_slice()).read_to_end(&mut decompressed)?;
+    let level = nbt::decode::read_named(&mut decompressed.as_slice())?;
+
+    let root = level.as_compound().context("Unable to get root compound")?;
+    let data = root.find_compound("Data").context("Unable to get Data")?;
+
+    // The seed moved into the world generation settings in 1.16
+    let seed = data.find_compound("WorldGenSettings")
+        .and_then(|settings| settings.find_numeric::<i64>("seed"))
+        .or_else(|| data.find_numeric::<i64>("RandomSeed"));
+
+    let spawn = if let (Some(x), Some(z)) = (data.find_numeric("SpawnX"), data.find_numeric("SpawnZ")) {
+        (x, z)
+    } else {
+        // Newer versions store the spawn as a position with a dimension
+        let pos = data.find_compound("spawn").and_then(|spawn| spawn.find_int_array("pos").cloned());
+        pos.and_then(|pos| Some((*pos.first()?, *pos.get(2)?))).unwrap_or((0, 0))
+    };
+    Ok(Level { spawn, seed })
+}
+
+/// Renders the chunks around the spawn that have been explored. Generating the rest from the seed would need the
+/// whole world generator, so that's left to seed maps
+pub fn render(level_path: &Path) -> anyhow::Result<WorldPreview> {
+    let Level { spawn: (spawn_x, spawn_z), seed } = read_level(level_path)?;
+    let (center_chunk_x, center_chunk_z) = (spawn_x.div_euclid(16), spawn_z.div_euclid(16));
+    let (min_chunk_x, min_chunk_z) = (center_chunk_x - RADIUS_CHUNKS, center_chunk_z - RADIUS_CHUNKS);
+
+    let size = (RADIUS_CHUNKS * 2 * CELLS_PER_CHUNK) as u32;
+    let mut image = RgbaImage::new(size, size);
+    let mut biome_counts: HashMap<String, usize> = HashMap::new();
+    let mut structures = Vec::new();
+    let mut skipped_chunks = 0;
+
+    for region_z in min_chunk_z.div_euclid(32)..=(center_chunk_z + RADIUS_CHUNKS - 1).div_euclid(32) {
+        for region_x in min_chunk_x.div_euclid(32)..=(center_chunk_x + RADIUS_CHUNKS - 1).div_euclid(32) {
+            let Ok(region) = std::fs::read(level_path.join("region").join(format!("r.{region_x}.{region_z}.mca"))) else {
+                continue;
+            };
+
+            for local_z in 0..32 {
+                for local_x in 0..32 {
+                    let (chunk_x, chunk_z) = (region_x * 32 + local_x, region_z * 32 + local_z);
+                    let (offset_x, offset_z) = (chunk_x - min_chunk_x, chunk_z - min_chunk_z);
+                    if !(0..RADIUS_CHUNKS * 2).contains(&offset_x) || !(0..RADIUS_CHUNKS * 2).contains(&offset_z) {
+                        continue;
+                    }
+                    let chunk = match read_chunk(&region, local_x, local_z) {
+                        Ok(Some(chunk)) => chunk,
+                        Ok(None) => continue,
+                        Err(error) => {
+                            tracing::debug!("Leaving chunk {chunk_x}, {chunk_z} out of the preview of {level_path:?}: {error}");
+                            skipped_chunks += 1;
+                            continue;
+                        },
+                    };
+                    let Some(chunk) = chunk.as_compound() else {
+                        tracing::debug!("Leaving chunk {chunk_x}, {chunk_z} out of the preview of {level_path:?}: not a compound");
+                        skipped_chunks += 1;
+                        continue;
+                    };
+
+                    for id in structure_starts(&chunk) {
+                        structures.push(WorldPreviewStructure { id: id.into(), x: chunk_x * 16 + 8, z: chunk_z * 16 + 8 });
+                    }
+
+                    let Some(biomes) = surface_biomes(&chunk) else {
+                        tracing::debug!("Leaving chunk {chunk_x}, {chunk_z} out of the preview of {level_path:?}: no biomes in the 1.18 format");
+                        skipped_chunks += 1;
+                        continue;
+                    };
+                    for (cell, biome) in biomes.into_iter().enumerate() {
+                        let pixel_x = (offset_x * CELLS_PER_CHUNK + cell as i32 % CELLS_PER_CHUNK) as u32;
+                        let pixel_z = (offset_z * CELLS_PER_CHUNK + cell as i32 / CELLS_PER_CHUNK) as u32;
+                        image.put_pixel(pixel_x, pixel_z, rgba(biome_color(&biome)));
+                        *biome_counts.entry(biome).or_default() += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    let center_x = center_chunk_x * 16 + 8;
+    let center_z = center_chunk_z * 16 + 8;
+    let to_pixel = |x: i32, z: i32| {
+        let pixel_x = (x - min_chunk_x * 16).div_euclid(BLOCKS_PER_PIXEL);
+        let pixel_z = (z - min_chunk_z * 16).div_euclid(BLOCKS_PER_PIXEL);
+        (pixel_x, pixel_z)
+    };
+    let mut mark = |(pixel_x, pixel_z): (i32, i32), color| {
+        for (dx, dz) in [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let (x, z) = (pixel_x + dx, pixel_z + dz);
+            if (0..size as i32).contains(&x) && (0..size as i32).contains(&z) {
+                image.put_pixel(x as u32, z as u32, color);
+            }
+        }
+    };
+    for structure in &structures {
+        mark(to_pixel(structure.x, structure.z), STRUCTURE_COLOR);
+    }
+    mark(to_pixel(spawn_x, spawn_z), SPAWN_COLOR);
+
+    if skipped_chunks > 0 {
+        tracing::warn!("Left {skipped_chunks} chunks that couldn't be read out of the preview of {level_path:?}");
+    }
+
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)?;
+
+    let total: usize = biome_counts.values().sum();
+    let mut biomes: Vec<WorldPreviewBiome> = biome_counts.into_iter()
+        .map(|(id, count)| WorldPreviewBiome {
+            color: biome_color(&id),
+            id: id.into(),
+            share: count as f32 / total as f32,
+        })
+        .collect();
+    biomes.sort_by(|a, b| b.share.total_cmp(&a.share));
+    structures.sort_by_key(|structure| (structure.x - center_x).pow(2) + (structure.z - center_z).pow(2));
+
+    Ok(WorldPreview {
+        png: png.into(),
+        blocks_per_pixel: BLOCKS_PER_PIXEL as u32,
+        center_x,
+        center_z,
+        biomes: biomes.into(),
+        structures: structures.into(),
+        seed,
+        skipped_chunks,
+    })
+}
+
+impl BackendState {
+    pub async fn world_preview(&self, id: InstanceID, level_path: &Path) -> Result<WorldPreview, Arc<str>> {
+        let Some((level_path, _, _)) = self.instance_world_path(id, level_path) else {
+            return Err("The world doesn't belong to the instance".into());
+        };
+        tokio::task::spawn_blocking(move || render(&level_path)).await.unwrap()
+            .map_err(|error| format!("Unable to render preview: {error}").into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpacks_entries_without_spanning_longs() {
+        // 9 bit entries, 7 per long, with the top bit of each long unused
+        let data = [(5 << 9) | 3, 42];
+        assert_eq!(unpack(&data, 9, 0), Some(3));
+        assert_eq!(unpack(&data, 9, 1), Some(5));
+        assert_eq!(unpack(&data, 9, 7), Some(42));
+        assert_eq!(unpack(&data, 9, 14), None);
+    }
+
+    #[test]
+    fn reports_unreadable_chunks() {
+        let mut region = vec![0; 8192 + 4096];
+        // Chunk 0, 0 is stored outside the region file
+        region[0..4].copy_from_slice(&((2 << 8) | 1u32).to_be_bytes());
+        region[8192..8197].copy_from_slice(&[0, 0, 0, 1, 0x82]);
+
+        assert!(read_chunk(&region, 1, 0).unwrap().is_none());
+        assert!(read_chunk(&region, 0, 0).is_err());
+    }
+
+    #[test]
+    fn modded_biomes_get_stable_colors() {
+        assert_eq!(biome_color("minecraft:plains"), 0x8db360);
+        assert_eq!(biome_color("biomesoplenty:lavender_field"), biome_color("biomesoplenty:lavender_field"));
+        assert_ne!(biome_color("biomesoplenty:lavender_field"), biome_color("biomesoplenty:cold_desert"));
+    }
+}
@@ -0,0 +1,132 @@
+use std::{io::{BufWriter, Write}, path::{Path, PathBuf}};
+
+use bridge::{
+    instance::InstanceID, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}
+};
+
+use crate::{BackendState, zip_writer::ZipWriter};
+
+/// Name of the backup archive, in the same format as the backups made by the game when optimizing a world
+fn backup_file_name(world_folder: &str, now: chrono::DateTime<chrono::Local>) -> String {
+    format!("{}_{world_folder}.zip", now.format("%Y-%m-%d_%H-%M-%S"))
+}
+
+/// Zips the world folder, with the world folder as the top level entry of the archive
+fn write_backup(level_path: &Path, world_folder: &str, destination: &Path, tracker: &ProgressTracker,
+    modal_action: &ModalAction) -> std::io::Result<bool>
+{
+    let mut files = Vec::new();
+    crate::instance_export::collect_files(level_path, world_folder, &mut files)?;
+    tracker.set_total(files.len() as u64);
+    tracker.notify();
+
+    let mut part_path = destination.to_path_buf().into_os_string();
+    part_path.push(".part");
+    let part_path = PathBuf::from(part_path);
+    let mut writer = ZipWriter::new(BufWriter::new(std::fs::File::create(&part_path)?));
+
+    let write = || -> std::io::Result<bool> {
+        for (name, path) in files {
+            if modal_action.has_requested_cancel() {
+                return Ok(false);
+            }
+            // The game may be writing the world while it is being backed up, files that disappear are skipped
+            match std::fs::read(&path) {
+                Ok(data) => writer.add_file(&name, &data)?,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {},
+                Err(error) => return Err(error),
+            }
+            tracker.add_count(1);
+            tracker.notify();
+        }
+
+        writer.finish()?.flush()?;
+        Ok(true)
+    };
+
+    match write() {
+        Ok(true) => {
+            std::fs::rename(&part_path, destination)?;
+            Ok(true)
+        },
+        result => {
+            _ = std::fs::remove_file(&part_path);
+            result
+        },
+    }
+}
+
+impl BackendState {
+    /// When the world belongs to the instance, returns the world folder, the instance's backups folder and whether the
+    /// instance is running
+    pub(crate) fn instance_world_path(&self, id: InstanceID, level_path: &Path) -> Option<(PathBuf, PathBuf, bool)> {
+        let mut instance_state = self.instance_state.write();
+        let instance = instance_state.instances.get_mut(id)?;
+        if level_path.parent() != Some(&*instance.saves_path) || level_path.file_name().is_none() {
+            return None;
+        }
+        Some((level_path.to_path_buf(), instance.dot_minecraft_path.join("backups"), instance.process.is_some()))
+    }
+
+    /// Backs the world up to `.minecraft/backups`, where the game puts its own backups
+    pub async fn backup_world(&self, id: InstanceID, level_path: &Path, modal_action: &ModalAction) {
+        let Some((level_path, backups_dir, _)) = self.instance_world_path(id, level_path) else {
+            modal_action.set_error_message("Can't back up world, it doesn't belong to the instance".into());
+            return;
+        };
+        let world_folder = level_path.file_name().unwrap().to_string_lossy().into_owned();
+
+        let tracker = ProgressTracker::new(rust_i18n::t!("progress_backing_up_world", name = world_folder).into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.notify();
+
+        let destination = backups_dir.join(backup_file_name(&world_folder, chrono::Local::now()));
+        let result = {
+            let tracker = tracker.clone();
+            let modal_action = modal_action.clone();
+            let destination = destination.clone();
+            tokio::task::spawn_blocking(move || {
+                std::fs::create_dir_all(&backups_dir)?;
+                write_backup(&level_path, &world_folder, &destination, &tracker, &modal_action)
+            }).await.unwrap()
+        };
+
+        tracker.set_finished(ProgressTrackerFinishType::from_err(!matches!(result, Ok(true))));
+        tracker.notify();
+
+        match result {
+            Ok(true) => self.send.send_success(rust_i18n::t!("notification_world_backed_up", path = destination.display())),
+            Ok(false) => {},
+            Err(error) => modal_action.set_error_message(format!("Unable to back up world: {error}").into()),
+        }
+    }
+
+    pub async fn delete_world(&self, id: InstanceID, level_path: &Path) {
+        let Some((level_path, _, running)) = self.instance_world_path(id, level_path) else {
+            self.send.send_error(rust_i18n::t!("notification_world_not_in_instance"));
+            return;
+        };
+        if running {
+            self.send.send_error(rust_i18n::t!("notification_world_delete_running"));
+            return;
+        }
+
+        // The saves folder is watched, so the world disappears from the list once it's removed
+        if let Err(error) = tokio::fs::remove_dir_all(&level_path).await {
+            self.send.send_error(rust_i18n::t!("notification_world_delete_failed", error = error));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn names_backups_like_the_game() {
+        let now = chrono::Local.with_ymd_and_hms(2024, 3, 9, 14, 5, 7).unwrap();
+        assert_eq!(backup_file_name("New World", now), "2024-03-09_14-05-07_New World.zip");
+    }
+}
@@ -0,0 +1,229 @@
+use std::io::Write;
+
+use chrono::{Datelike, Timelike};
+use flate2::{Compression, Crc, write::DeflateEncoder};
+
+const LOCAL_FILE_HEADER: u32 = 0x04034b50;
+const CENTRAL_DIRECTORY_HEADER: u32 = 0x02014b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY: u32 = 0x06064b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR: u32 = 0x07064b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x06054b50;
+const ZIP64_EXTRA_FIELD: u16 = 0x0001;
+const VERSION: u16 = 20;
+const VERSION_ZIP64: u16 = 45;
+const FLAG_UTF8: u16 = 1 << 11;
+const METHOD_DEFLATE: u16 = 8;
+
+struct CentralDirectoryEntry {
+    name: String,
+    crc32: u32,
+    compressed_size: u64,
+    uncompressed_size: u64,
+    offset: u64,
+}
+
+/// Minimal writer for deflate-compressed zip archives. Sizes and offsets that don't fit the classic format are written
+/// as zip64, so archives and entries can be 4 GiB or larger
+pub struct ZipWriter<W: Write> {
+    inner: W,
+    offset: u64,
+    dos_time: u16,
+    dos_date: u16,
+    entries: Vec<CentralDirectoryEntry>,
+    /// Values above this are moved into zip64 fields, only lowered by tests
+    zip64_threshold: u64,
+}
+
+impl<W: Write> ZipWriter<W> {
+    pub fn new(inner: W) -> Self {
+        let now = chrono::Local::now();
+        let dos_time = ((now.hour() << 11) | (now.minute() << 5) | (now.second() / 2)) as u16;
+        let dos_date = (((now.year() - 1980).max(0) as u32) << 9 | (now.month() << 5) | now.day()) as u16;
+
+        Self {
+            inner,
+            offset: 0,
+            dos_time,
+            dos_date,
+            entries: Vec::new(),
+            zip64_threshold: u32::MAX as u64 - 1,
+        }
+    }
+
+    /// The classic field value, or the marker telling readers to look in the zip64 extra field
+    fn field32(&self, value: u64) -> u32 {
+        if value > self.zip64_threshold {
+            u32::MAX
+        } else {
+            value as u32
+        }
+    }
+
+    /// Adds a file, `name` uses forward slashes as separators
+    pub fn add_file(&mut self, name: &str, data: &[u8]) -> std::io::Result<()> {
+        let mut crc = Crc::new();
+        crc.update(data);
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data)?;
+        let compressed = encoder.finish()?;
+
+        let entry = CentralDirectoryEntry {
+            name: name.to_string(),
+            crc32: crc.sum(),
+            compressed_size: compressed.len() as u64,
+            uncompressed_size: data.len() as u64,
+            offset: self.offset,
+        };
+
+        // The local header has to carry both sizes in the zip64 field as soon as either is too large
+        let zip64 = entry.compressed_size > self.zip64_threshold || entry.uncompressed_size > self.zip64_threshold;
+        let mut extra = Vec::new();
+        if zip64 {
+            extra.extend_from_slice(&ZIP64_EXTRA_FIELD.to_le_bytes());
+            extra.extend_from_slice(&16u16.to_le_bytes());
+            extra.extend_from_slice(&entry.uncompressed_size.to_le_bytes());
+            extra.extend_from_slice(&entry.compressed_size.to_le_bytes());
+        }
+        let (compressed_size, uncompressed_size) = if zip64 {
+            (u32::MAX, u32::MAX)
+        } else {
+            (entry.compressed_size as u32, entry.uncompressed_size as u32)
+        };
+
+        let mut header = Vec::with_capacity(30 + name.len() + extra.len());
+        header.extend_from_slice(&LOCAL_FILE_HEADER.to_le_bytes());
+        header.extend_from_slice(&(if zip64 { VERSION_ZIP64 } else { VERSION }).to_le_bytes());
+        header.extend_from_slice(&FLAG_UTF8.to_le_bytes());
+        header.extend_from_slice(&METHOD_DEFLATE.to_le_bytes());
+        header.extend_from_slice(&self.dos_time.to_le_bytes());
+        header.extend_from_slice(&self.dos_date.to_le_bytes());
+        header.extend_from_slice(&entry.crc32.to_le_bytes());
+        header.extend_from_slice(&compressed_size.to_le_bytes());
+        header.extend_from_slice(&uncompressed_size.to_le_bytes());
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&(extra.len() as u16).to_le_bytes());
+        header.extend_from_slice(name.as_bytes());
+        header.extend_from_slice(&extra);
+
+        self.inner.write_all(&header)?;
+        self.inner.write_all(&compressed)?;
+        self.offset += (header.len() + compressed.len()) as u64;
+        self.entries.push(entry);
+
+        Ok(())
+    }
+
+    /// Writes the central directory and returns the inner writer
+    pub fn finish(mut self) -> std::io::Result<W> {
+        let central_directory_offset = self.offset;
+
+        let mut central_directory = Vec::new();
+        for entry in &self.entries {
+            // Only the fields that overflow are stored in the extra field, in this order
+            let mut extra = Vec::new();
+            for value in [entry.uncompressed_size, entry.compressed_size, entry.offset] {
+                if value > self.zip64_threshold {
+                    extra.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            if !extra.is_empty() {
+                let mut field = Vec::with_capacity(4 + extra.len());
+                field.extend_from_slice(&ZIP64_EXTRA_FIELD.to_le_bytes());
+                field.extend_from_slice(&(extra.len() as u16).to_le_bytes());
+                field.extend_from_slice(&extra);
+                extra = field;
+            }
+            let version = if extra.is_empty() { VERSION } else { VERSION_ZIP64 };
+
+            central_directory.extend_from_slice(&CENTRAL_DIRECTORY_HEADER.to_le_bytes());
+            central_directory.extend_from_slice(&version.to_le_bytes());
+            central_directory.extend_from_slice(&version.to_le_bytes());
+            central_directory.extend_from_slice(&FLAG_UTF8.to_le_bytes());
+            central_directory.extend_from_slice(&METHOD_DEFLATE.to_le_bytes());
+            central_directory.extend_from_slice(&self.dos_time.to_le_bytes());
+            central_directory.extend_from_slice(&self.dos_date.to_le_bytes());
+            central_directory.extend_from_slice(&entry.crc32.to_le_bytes());
+            central_directory.extend_from_slice(&self.field32(entry.compressed_size).to_le_bytes());
+            central_directory.extend_from_slice(&self.field32(entry.uncompressed_size).to_le_bytes());
+            central_directory.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            central_directory.extend_from_slice(&(extra.len() as u16).to_le_bytes());
+            // Comment, disk number, internal and external attributes
+            central_directory.extend_from_slice(&[0; 10]);
+            central_directory.extend_from_slice(&self.field32(entry.offset).to_le_bytes());
+            central_directory.extend_from_slice(entry.name.as_bytes());
+            central_directory.extend_from_slice(&extra);
+        }
+
+        let entry_count = self.entries.len() as u64;
+        let central_directory_size = central_directory.len() as u64;
+        let zip64 = entry_count >= u16::MAX as u64
+            || central_directory_size > self.zip64_threshold
+            || central_directory_offset > self.zip64_threshold;
+
+        if zip64 {
+            let zip64_end_offset = central_directory_offset + central_directory_size;
+            central_directory.extend_from_slice(&ZIP64_END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+            // Size of the rest of the record
+            central_directory.extend_from_slice(&44u64.to_le_bytes());
+            central_directory.extend_from_slice(&VERSION_ZIP64.to_le_bytes());
+            central_directory.extend_from_slice(&VERSION_ZIP64.to_le_bytes());
+            central_directory.extend_from_slice(&[0; 8]);
+            central_directory.extend_from_slice(&entry_count.to_le_bytes());
+            central_directory.extend_from_slice(&entry_count.to_le_bytes());
+            central_directory.extend_from_slice(&central_directory_size.to_le_bytes());
+            central_directory.extend_from_slice(&central_directory_offset.to_le_bytes());
+
+            central_directory.extend_from_slice(&ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR.to_le_bytes());
+            central_directory.extend_from_slice(&0u32.to_le_bytes());
+            central_directory.extend_from_slice(&zip64_end_offset.to_le_bytes());
+            central_directory.extend_from_slice(&1u32.to_le_bytes());
+        }
+
+        let entry_count16 = if zip64 { u16::MAX } else { entry_count as u16 };
+        central_directory.extend_from_slice(&END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        central_directory.extend_from_slice(&[0; 4]);
+        central_directory.extend_from_slice(&entry_count16.to_le_bytes());
+        central_directory.extend_from_slice(&entry_count16.to_le_bytes());
+        central_directory.extend_from_slice(&(if zip64 { u32::MAX } else { central_directory_size as u32 }).to_le_bytes());
+        central_directory.extend_from_slice(&(if zip64 { u32::MAX } else { central_directory_offset as u32 }).to_le_bytes());
+        central_directory.extend_from_slice(&0u16.to_le_bytes());
+
+        self.inner.write_all(&central_directory)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rc_zip_sync::ReadZip;
+
+    use super::*;
+
+    #[test]
+    fn written_archives_can_be_read_back() {
+        let mut writer = ZipWriter::new(Vec::new());
+        writer.add_file("modrinth.index.json", b"{}").unwrap();
+        writer.add_file("overrides/config/example.toml", "value = \"ü\"\n".repeat(100).as_bytes()).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let archive = bytes.read_zip().unwrap();
+        assert_eq!(archive.by_name("modrinth.index.json").unwrap().bytes().unwrap(), b"{}");
+        assert_eq!(archive.by_name("overrides/config/example.toml").unwrap().bytes().unwrap(), "value = \"ü\"\n".repeat(100).as_bytes());
+    }
+
+    #[test]
+    fn zip64_archives_can_be_read_back() {
+        // Writing 4 GiB in a test is too slow, so everything past a few bytes is treated as too large instead
+        let mut writer = ZipWriter::new(Vec::new());
+        writer.zip64_threshold = 16;
+        writer.add_file("small.txt", b"tiny").unwrap();
+        writer.add_file("large.txt", "large file contents\n".repeat(100).as_bytes()).unwrap();
+        let bytes = writer.finish().unwrap();
+
+        let archive = bytes.read_zip().unwrap();
+        assert_eq!(archive.by_name("small.txt").unwrap().bytes().unwrap(), b"tiny");
+        assert_eq!(archive.by_name("large.txt").unwrap().bytes().unwrap(), "large file contents\n".repeat(100).as_bytes());
+    }
+}
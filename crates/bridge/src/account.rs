@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 use uuid::Uuid;
 
@@ -7,4 +7,43 @@ pub struct Account {
     pub uuid: Uuid,
     pub username: Arc<str>,
     pub head: Option<Arc<[u8]>>,
+    pub offline: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkinModel {
+    Classic,
+    Slim,
+}
+
+/// The skin and cape an account currently has, as textures and as front view previews
+#[derive(Debug, Clone)]
+pub struct SkinProfile {
+    pub username: Arc<str>,
+    /// None when the account uses the default skin
+    pub skin: Option<SkinTexture>,
+    pub cape: Option<SkinTexture>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SkinTexture {
+    pub model: SkinModel,
+    pub png: Arc<[u8]>,
+    pub preview: Arc<[u8]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LibrarySkin {
+    pub id: Arc<str>,
+    pub name: Arc<str>,
+    pub texture: SkinTexture,
+}
+
+#[derive(Debug, Clone)]
+pub enum SkinSource {
+    File(Arc<Path>),
+    Texture {
+        name: Arc<str>,
+        png: Arc<[u8]>,
+    },
 }
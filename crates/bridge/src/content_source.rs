@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use schema::{loader::Loader, modrinth::{ModrinthLoader, ModrinthProjectType, ModrinthSideRequirement}};
+use ustr::Ustr;
+
+/// A service that content can be searched for and downloaded from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentSourceId {
+    Modrinth,
+    CurseForge,
+}
+
+impl ContentSourceId {
+    pub fn name(self) -> &'static str {
+        match self {
+            ContentSourceId::Modrinth => "Modrinth",
+            ContentSourceId::CurseForge => "CurseForge",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ContentSearchQuery {
+    pub query: Option<Arc<str>>,
+    pub project_type: ModrinthProjectType,
+    /// Projects for any of the loaders are found, all projects if empty
+    pub loaders: Arc<[Loader]>,
+    /// Category names of the source, every category has to match
+    pub categories: Arc<[Arc<str>]>,
+    pub game_version: Option<Arc<str>>,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContentSearchResults {
+    pub hits: Arc<[ContentSearchHit]>,
+    pub total_hits: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContentSearchHit {
+    pub project_id: Arc<str>,
+    pub project_type: ModrinthProjectType,
+    pub title: Arc<str>,
+    pub description: Option<Arc<str>>,
+    pub author: Arc<str>,
+    pub icon_url: Option<Arc<str>>,
+    pub downloads: usize,
+    pub client_side: Option<ModrinthSideRequirement>,
+    pub server_side: Option<ModrinthSideRequirement>,
+    pub categories: Arc<[Ustr]>,
+}
+
+/// A downloadable version of a project, in the same form for every source
+#[derive(Debug, Clone)]
+pub struct ContentVersion {
+    pub project_id: Arc<str>,
+    pub version_id: Arc<str>,
+    pub name: Option<Arc<str>>,
+    pub loaders: Arc<[ModrinthLoader]>,
+    pub files: Arc<[ContentFile]>,
+}
+
+impl ContentVersion {
+    pub fn primary_file(&self) -> Option<&ContentFile> {
+        self.files.iter().find(|file| file.primary).or(self.files.first())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ContentFile {
+    /// Missing when the source doesn't allow the file to be downloaded by launchers
+    pub url: Option<Arc<str>>,
+    pub filename: Arc<str>,
+    pub sha1: Arc<str>,
+    pub size: usize,
+    pub primary: bool,
+}
@@ -1,11 +1,11 @@
-use std::sync::Arc;
+use std::sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex, RwLock};
 
 #[cfg(debug_assertions)]
 use tokio::sync::mpsc::{Receiver, Sender};
 #[cfg(not(debug_assertions))]
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
-use crate::{message::{BridgeNotificationType, MessageToBackend, MessageToFrontend}, serial::{AtomicOptionSerial, AtomicSerialProvider, AtomicSetSerial, Serial}};
+use crate::{message::{BridgeNotificationType, MessageToBackend, MessageToFrontend}, modal_action::{ModalAction, WeakModalAction}, serial::{AtomicOptionSerial, AtomicSerialProvider, AtomicSetSerial, Serial}};
 
 pub fn create_pair() -> (BackendReceiver, BackendHandle, FrontendReceiver, FrontendHandle) {
     #[cfg(debug_assertions)]
@@ -27,9 +27,12 @@ pub fn create_pair() -> (BackendReceiver, BackendHandle, FrontendReceiver, Front
             processed_serial: backend_serial.clone(),
         },
         BackendHandle {
-            sender: backend_send,
+            sender: Arc::new(RwLock::new(backend_send)),
             processed_serial: backend_serial.clone(),
             next_serial: Default::default(),
+            modal_actions: Default::default(),
+            restart: Default::default(),
+            crash: Default::default(),
         },
         FrontendReceiver {
             receiver: frontend_recv,
@@ -91,12 +94,17 @@ impl FrontendReceiver {
 
 #[derive(Clone, Debug)]
 pub struct BackendHandle {
+    /// Shared between clones, so every handle sends to the new channel once a restarted backend reconnects
     #[cfg(debug_assertions)]
-    sender: Sender<(MessageToBackend, Option<Serial>)>,
+    sender: Arc<RwLock<Sender<(MessageToBackend, Option<Serial>)>>>,
     #[cfg(not(debug_assertions))]
-    sender: UnboundedSender<(MessageToBackend, Option<Serial>)>,
+    sender: Arc<RwLock<UnboundedSender<(MessageToBackend, Option<Serial>)>>>,
     processed_serial: AtomicSetSerial,
     next_serial: AtomicSerialProvider,
+    /// The actions of messages that were sent, so they can be finished if the backend is restarted before it does
+    modal_actions: Arc<Mutex<Vec<WeakModalAction>>>,
+    restart: Arc<SupervisorSignal>,
+    crash: Arc<SupervisorSignal>,
 }
 
 unsafe impl Send for BackendHandle {}
@@ -104,10 +112,12 @@ unsafe impl Sync for BackendHandle {}
 
 impl BackendHandle {
     pub fn send(&self, message: MessageToBackend) {
+        self.track_modal_action(&message);
+        let sender = self.sender.read().unwrap();
         #[cfg(debug_assertions)]
-        self.sender.try_send((message, None)).unwrap();
+        sender.try_send((message, None)).unwrap();
         #[cfg(not(debug_assertions))]
-        let _ = self.sender.send((message, None));
+        let _ = sender.send((message, None));
     }
 
     pub fn send_with_serial(&self, message: MessageToBackend, serial: &AtomicOptionSerial) {
@@ -118,14 +128,101 @@ impl BackendHandle {
         let next_serial = self.next_serial.next();
         serial.set(next_serial);
 
+        self.track_modal_action(&message);
+        let sender = self.sender.read().unwrap();
         #[cfg(debug_assertions)]
-        self.sender.try_send((message, Some(next_serial))).unwrap();
+        sender.try_send((message, Some(next_serial))).unwrap();
         #[cfg(not(debug_assertions))]
-        let _ = self.sender.send((message, Some(next_serial)));
+        let _ = sender.send((message, Some(next_serial)));
     }
 
     pub fn is_closed(&self) -> bool {
-        self.sender.is_closed()
+        self.sender.read().unwrap().is_closed()
+    }
+
+    fn track_modal_action(&self, message: &MessageToBackend) {
+        if let Some(modal_action) = message.modal_action() {
+            let mut modal_actions = self.modal_actions.lock().unwrap();
+            modal_actions.retain(|modal_action| modal_action.upgrade().is_some_and(|modal_action| modal_action.get_finished_at().is_none()));
+            modal_actions.push(modal_action.downgrade());
+        }
+    }
+
+    /// Finishes the actions the backend hasn't finished with `error`, for when it's restarted and won't anymore
+    pub fn fail_pending_modal_actions(&self, error: Arc<str>) {
+        for modal_action in self.modal_actions.lock().unwrap().drain(..) {
+            if let Some(modal_action) = modal_action.upgrade() && modal_action.get_finished_at().is_none() {
+                modal_action.set_error_message(error.clone());
+                modal_action.set_finished();
+            }
+        }
+    }
+
+    /// Replaces the channel to the backend with a new one for a restarted backend to receive from. Messages sent
+    /// to the old backend are lost, so their serials are treated as processed. Restarts and crashes that were
+    /// signalled before are handled by this restart
+    pub fn reconnect(&self) -> BackendReceiver {
+        #[cfg(debug_assertions)]
+        let (sender, receiver) = tokio::sync::mpsc::channel(64);
+        #[cfg(not(debug_assertions))]
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+
+        self.processed_serial.set(self.next_serial.current());
+        *self.sender.write().unwrap() = sender;
+        self.restart.clear();
+        self.crash.clear();
+
+        BackendReceiver {
+            receiver,
+            processed_serial: self.processed_serial.clone(),
+        }
+    }
+
+    /// Asks the supervisor of the backend to restart it, whether it's still running or has stopped
+    pub fn request_restart(&self) {
+        self.restart.raise();
+    }
+
+    pub async fn restart_requested(&self) {
+        self.restart.wait().await;
+    }
+
+    /// Tells the supervisor of the backend that it has crashed, for panics in its tasks which tokio catches
+    pub fn report_crash(&self) {
+        self.crash.raise();
+    }
+
+    pub async fn crash_reported(&self) {
+        self.crash.wait().await;
+    }
+}
+
+/// A signal to the supervisor of the backend that's kept until it's waited for, so it isn't lost while the
+/// supervisor is busy with something else
+#[derive(Default, Debug)]
+struct SupervisorSignal {
+    raised: AtomicBool,
+    notify: tokio::sync::Notify,
+}
+
+impl SupervisorSignal {
+    fn raise(&self) {
+        self.raised.store(true, Ordering::Release);
+        self.notify.notify_one();
+    }
+
+    fn clear(&self) {
+        self.raised.store(false, Ordering::Release);
+    }
+
+    async fn wait(&self) {
+        loop {
+            let notified = self.notify.notified();
+            if self.raised.swap(false, Ordering::AcqRel) {
+                return;
+            }
+            notified.await;
+        }
     }
 }
 
@@ -196,6 +293,17 @@ impl FrontendHandle {
         })
     }
 
+    /// Creates the action for work the backend starts on its own, so it's listed in the task center where it can
+    /// be cancelled. The task is done once every clone of the action has been dropped
+    pub fn start_background_task(&self, title: impl Into<Arc<str>>) -> ModalAction {
+        let modal_action = ModalAction::default();
+        self.send(MessageToFrontend::BackgroundTaskStarted {
+            title: title.into(),
+            modal_action: modal_action.clone(),
+        });
+        modal_action
+    }
+
     pub fn is_closed(&self) -> bool {
         self.sender.is_closed()
     }
@@ -204,3 +312,89 @@ impl FrontendHandle {
         self.processed_serial.get()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn is_signalled(signal: impl Future<Output = ()>) -> bool {
+        tokio::select! {
+            biased;
+            _ = signal => true,
+            _ = std::future::ready(()) => false,
+        }
+    }
+
+    #[test]
+    fn reconnect_sends_to_new_receiver() {
+        let (mut old_receiver, handle, _, _) = create_pair();
+        let mut receiver = handle.reconnect();
+
+        handle.clone().send(MessageToBackend::Sleep5s);
+
+        assert!(matches!(receiver.receiver.try_recv(), Ok((MessageToBackend::Sleep5s, None))));
+        assert!(old_receiver.receiver.try_recv().is_err());
+        assert!(!handle.is_closed());
+    }
+
+    #[test]
+    fn reconnect_treats_lost_serials_as_processed() {
+        let (_old_receiver, handle, _, _) = create_pair();
+        let serial = AtomicOptionSerial::default();
+
+        handle.send_with_serial(MessageToBackend::Sleep5s, &serial);
+        let mut receiver = handle.reconnect();
+        handle.send_with_serial(MessageToBackend::Sleep5s, &serial);
+
+        assert!(matches!(receiver.receiver.try_recv(), Ok((MessageToBackend::Sleep5s, Some(_)))));
+    }
+
+    #[test]
+    fn pending_modal_actions_are_failed() {
+        let (_receiver, handle, _, _) = create_pair();
+        let pending = ModalAction::default();
+        let finished = ModalAction::default();
+        handle.send(MessageToBackend::SmokeTest { minecraft_version: None, modal_action: pending.clone() });
+        handle.send(MessageToBackend::SmokeTest { minecraft_version: None, modal_action: finished.clone() });
+        finished.set_finished();
+
+        handle.fail_pending_modal_actions("Restarted".into());
+
+        assert!(pending.get_finished_at().is_some());
+        assert_eq!(pending.error.read().unwrap().as_deref(), Some("Restarted"));
+        assert!(finished.error.read().unwrap().is_none());
+    }
+
+    #[test]
+    fn tracked_modal_actions_are_not_held() {
+        let (mut receiver, handle, _, _) = create_pair();
+        let modal_action = ModalAction::default();
+        handle.send(MessageToBackend::SmokeTest { minecraft_version: None, modal_action: modal_action.clone() });
+
+        drop(receiver.receiver.try_recv());
+
+        assert_eq!(modal_action.refcnt(), 1);
+    }
+
+    #[tokio::test]
+    async fn restart_requested_before_waiting_is_kept() {
+        let (_receiver, handle, _, _) = create_pair();
+
+        handle.request_restart();
+
+        assert!(is_signalled(handle.restart_requested()).await);
+        assert!(!is_signalled(handle.restart_requested()).await);
+    }
+
+    #[tokio::test]
+    async fn reconnect_handles_earlier_signals() {
+        let (_receiver, handle, _, _) = create_pair();
+
+        handle.request_restart();
+        handle.report_crash();
+        let _receiver = handle.reconnect();
+
+        assert!(!is_signalled(handle.restart_requested()).await);
+        assert!(!is_signalled(handle.crash_reported()).await);
+    }
+}
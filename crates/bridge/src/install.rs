@@ -1,6 +1,6 @@
 use std::{path::{Path, PathBuf}, sync::Arc};
 
-use schema::{content::ContentSource, loader::Loader};
+use schema::{content::ContentSource, loader::Loader, modrinth::ModrinthProjectType};
 
 use crate::{instance::InstanceID, safe_path::SafePath};
 
@@ -42,9 +42,17 @@ pub enum ContentDownload {
         project_id: Arc<str>,
         version_id: Option<Arc<str>>,
     },
+    CurseForge {
+        project_id: Arc<str>,
+        /// The file id, the newest compatible file is installed if None
+        version_id: Option<Arc<str>>,
+        /// CurseForge files don't say which folder they belong in, so it's taken from the project's class
+        project_type: ModrinthProjectType,
+    },
     Url {
         url: Arc<str>,
         sha1: Arc<str>,
+        /// Expected size in bytes, 0 if the source doesn't provide one and the server has to be asked for it before installing
         size: usize,
     },
     File {
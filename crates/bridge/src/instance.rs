@@ -1,8 +1,8 @@
-use std::{collections::HashSet, path::Path, sync::Arc};
+use std::{collections::HashSet, net::{SocketAddr, SocketAddrV4}, path::Path, sync::Arc, time::SystemTime};
 
 use schema::{auxiliary::AuxDisabledChildren, content::ContentSource, modification::ModrinthModpackFileDownload};
 
-use crate::safe_path::SafePath;
+use crate::{content_source::ContentSourceId, safe_path::SafePath};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct InstanceID {
@@ -39,6 +39,94 @@ pub enum InstanceStatus {
     NotRunning,
     Launching,
     Running,
+    Crashed,
+}
+
+impl InstanceStatus {
+    pub fn is_running(self) -> bool {
+        matches!(self, Self::Launching | Self::Running)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InstanceProcessInfo {
+    pub pid: u32,
+    pub started_at: SystemTime,
+    /// Set once the game has been opened to LAN
+    pub lan: Option<LanGameInfo>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LanGameInfo {
+    pub local_address: SocketAddr,
+    pub port_forwarded: bool,
+    /// Set while the port is forwarded through UPnP and the gateway reported its external address
+    pub public_address: Option<SocketAddrV4>,
+}
+
+/// An instance that another launcher on the local network is sharing
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LanShare {
+    pub name: Arc<str>,
+    pub address: SocketAddr,
+    pub url: Arc<str>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFileChange {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLineKind {
+    Unchanged,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub text: Arc<str>,
+}
+
+/// How a config file changed between a snapshot and the files currently on disk
+#[derive(Debug, Clone)]
+pub struct ConfigFileDiff {
+    pub path: Arc<str>,
+    pub change: ConfigFileChange,
+    /// Empty for files that aren't text
+    pub lines: Arc<[DiffLine]>,
+}
+
+/// A mod installed in one instance, with the key it is grouped by in the mods overview
+#[derive(Debug, Clone)]
+pub struct ModInstallEntry {
+    pub group_key: Arc<str>,
+    pub name: Arc<str>,
+    pub png_icon: Option<Arc<[u8]>>,
+    pub install: ModInstallation,
+}
+
+/// A mod and every instance it is installed in
+#[derive(Debug, Clone)]
+pub struct ModOverview {
+    pub name: Arc<str>,
+    pub png_icon: Option<Arc<[u8]>>,
+    pub installs: Arc<[ModInstallation]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModInstallation {
+    pub instance: InstanceID,
+    pub instance_name: Arc<str>,
+    pub content_id: InstanceContentID,
+    pub version: Arc<str>,
+    pub enabled: bool,
+    /// Whether the last update check found a newer version
+    pub can_update: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -47,9 +135,44 @@ pub struct InstanceWorldSummary {
     pub subtitle: Arc<str>,
     pub level_path: Arc<Path>,
     pub last_played: i64,
+    /// Name of the Minecraft version the world was last played in
+    pub version: Option<Arc<str>>,
     pub png_icon: Option<Arc<[u8]>>,
 }
 
+/// Top-down biome map of the area around the spawn of a world, as explored so far
+#[derive(Debug, Clone)]
+pub struct WorldPreview {
+    pub png: Arc<[u8]>,
+    pub blocks_per_pixel: u32,
+    /// Block coordinates at the center of the image
+    pub center_x: i32,
+    pub center_z: i32,
+    /// Biomes in the preview, most common first
+    pub biomes: Arc<[WorldPreviewBiome]>,
+    pub structures: Arc<[WorldPreviewStructure]>,
+    /// Only explored chunks are in the preview, the seed lets the rest be looked up on a seed map
+    pub seed: Option<i64>,
+    /// Generated chunks that couldn't be read, e.g. ones from before 1.18 or stored outside the region file
+    pub skipped_chunks: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldPreviewBiome {
+    pub id: Arc<str>,
+    /// 0xRRGGBB, the same color as in the image
+    pub color: u32,
+    /// Fraction of the explored area
+    pub share: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldPreviewStructure {
+    pub id: Arc<str>,
+    pub x: i32,
+    pub z: i32,
+}
+
 #[derive(Debug, Clone)]
 pub struct InstanceServerSummary {
     pub name: Arc<str>,
@@ -80,6 +203,8 @@ pub struct ContentSummary {
     pub png_icon: Option<Arc<[u8]>>,
     pub update_status: Arc<AtomicContentUpdateStatus>,
     pub extra: ContentType,
+    /// Ids of mods that are required for this one to load, excluding the game and loader
+    pub dependencies: Arc<[Arc<str>]>,
 }
 
 #[derive(Debug, Clone)]
@@ -106,13 +231,28 @@ pub enum ContentUpdateStatus {
     ErrorInvalidHash,
     AlreadyUpToDate,
     Modrinth,
+    CurseForge,
 }
 
 impl ContentUpdateStatus {
     pub fn can_update(&self) -> bool {
+        self.update_source().is_some()
+    }
+
+    /// An update is available from the source
+    pub fn update_available(source: ContentSourceId) -> Self {
+        match source {
+            ContentSourceId::Modrinth => ContentUpdateStatus::Modrinth,
+            ContentSourceId::CurseForge => ContentUpdateStatus::CurseForge,
+        }
+    }
+
+    /// The source an update is available from
+    pub fn update_source(&self) -> Option<ContentSourceId> {
         match self {
-            ContentUpdateStatus::Modrinth => true,
-            _ => false,
+            ContentUpdateStatus::Modrinth => Some(ContentSourceId::Modrinth),
+            ContentUpdateStatus::CurseForge => Some(ContentSourceId::CurseForge),
+            _ => None,
         }
     }
 }
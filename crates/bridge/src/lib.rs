@@ -1,4 +1,5 @@
 pub mod account;
+pub mod content_source;
 pub mod game_output;
 pub mod handle;
 pub mod install;
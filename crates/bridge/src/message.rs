@@ -6,21 +6,22 @@ use std::{
 
 use enumset::{EnumSet, EnumSetType};
 use schema::{
-    backend_config::{BackendConfig, SyncTarget}, instance::{
-        InstanceConfiguration, InstanceJvmBinaryConfiguration, InstanceJvmFlagsConfiguration,
-        InstanceLinuxWrapperConfiguration, InstanceMemoryConfiguration,
-    }, loader::Loader, pandora_update::{UpdateManifest, UpdateManifestExe, UpdatePrompt}
+    backend_config::{BackendConfig, DownloadLimits, EndpointOverrides, LaunchLimits, MetadataRefreshInterval, ProxySettings, SyncTarget}, instance::{
+        ConfigSnapshot, InstanceBrandingConfiguration, InstanceColorTag, InstanceConfiguration, InstanceEnvironmentConfiguration, InstanceIcon, InstanceJvmBinaryConfiguration, InstanceJvmFlagsConfiguration,
+        InstanceLinuxWrapperConfiguration, InstanceMemoryConfiguration, InstanceTimelineEvent,
+    }, loader::Loader, pandora_update::{UpdateChannel, UpdateCheckFrequency, UpdateManifest, UpdateManifestExe, UpdatePrompt}
 };
 use ustr::Ustr;
 use uuid::Uuid;
 
 use crate::{
-    account::Account,
+    account::{Account, LibrarySkin, SkinModel, SkinProfile, SkinSource},
+    content_source::{ContentSearchQuery, ContentSearchResults, ContentSourceId},
     game_output::GameOutputLogLevel,
     install::ContentInstall,
     instance::{
-        InstanceContentID, InstanceContentSummary, InstanceID, InstanceServerSummary, InstanceStatus,
-        InstanceWorldSummary,
+        ConfigFileDiff, InstanceContentID, InstanceContentSummary, InstanceID, InstanceProcessInfo, InstanceServerSummary,
+        InstanceStatus, InstanceWorldSummary, LanShare, ModInstallEntry, WorldPreview,
     },
     keep_alive::{KeepAlive, KeepAliveHandle},
     meta::{MetadataRequest, MetadataResult},
@@ -37,14 +38,117 @@ pub enum MessageToBackend {
         name: Ustr,
         version: Ustr,
         loader: Loader,
+        modal_action: ModalAction,
     },
     DeleteInstance {
         id: InstanceID,
     },
+    DeleteInstances {
+        ids: Vec<InstanceID>,
+    },
+    /// Downloads everything the instances need to launch without starting them
+    PrepareInstances {
+        ids: Vec<InstanceID>,
+        modal_action: ModalAction,
+    },
     RenameInstance {
         id: InstanceID,
         name: Ustr,
     },
+    DuplicateInstance {
+        id: InstanceID,
+    },
+    /// Creates a copy of the instance for another Minecraft version, switching its content to compatible versions
+    CloneInstanceToVersion {
+        id: InstanceID,
+        name: Arc<str>,
+        minecraft_version: Ustr,
+        modal_action: ModalAction,
+    },
+    /// Creates a new instance from a Modrinth modpack (.mrpack) file
+    ImportModpack {
+        path: PathBuf,
+        modal_action: ModalAction,
+    },
+    /// Creates instances from a MultiMC/Prism Launcher instance folder, or every instance in a launcher folder
+    ImportMultiMcInstances {
+        path: PathBuf,
+        modal_action: ModalAction,
+    },
+    /// Lists the profiles of the official Minecraft Launcher in `minecraft_dir`, or its default location if `None`
+    GetOfficialLauncherProfiles {
+        minecraft_dir: Option<PathBuf>,
+        channel: tokio::sync::oneshot::Sender<Result<OfficialLauncherProfiles, Arc<str>>>,
+    },
+    /// Creates instances from the given official launcher profiles, either running in the profile's game directory
+    /// or in a copy of it
+    ImportOfficialLauncherProfiles {
+        minecraft_dir: Arc<Path>,
+        profile_ids: Vec<Arc<str>>,
+        reuse_game_dir: bool,
+        modal_action: ModalAction,
+    },
+    /// Answer to `MessageToFrontend::OfficialLauncherFilesFound`, copies or hardlinks the official launcher's asset
+    /// objects and libraries whose hashes check out
+    SeedFromOfficialLauncher {
+        minecraft_dir: Arc<Path>,
+        modal_action: ModalAction,
+    },
+    /// Writes the instance as a Modrinth modpack (.mrpack) to `path`
+    ExportModpack {
+        id: InstanceID,
+        path: PathBuf,
+        modal_action: ModalAction,
+    },
+    /// Writes the instance's configuration and the selected folders of its .minecraft to a zip at `path`
+    ExportInstance {
+        id: InstanceID,
+        path: PathBuf,
+        folders: EnumSet<InstanceExportFolder>,
+        modal_action: ModalAction,
+    },
+    /// Lists the instance's enabled mods with their versions and where they were installed from
+    GetModList {
+        id: InstanceID,
+        format: ModListFormat,
+        channel: tokio::sync::oneshot::Sender<Option<Arc<str>>>,
+    },
+    /// Writes the mod list to `path`, as CSV if it ends in `.csv` and as Markdown otherwise
+    ExportModList {
+        id: InstanceID,
+        path: PathBuf,
+        modal_action: ModalAction,
+    },
+    /// Zips the launcher logs, instance configurations and latest game logs into `path`, with secrets redacted
+    CreateDiagnosticsBundle {
+        path: PathBuf,
+        modal_action: ModalAction,
+    },
+    /// Serves an archive of the instance to other launchers on the local network until the modal action is cancelled
+    ShareInstanceOnLan {
+        id: InstanceID,
+        modal_action: ModalAction,
+    },
+    /// Listens for a few seconds for instances shared by other launchers on the local network
+    DiscoverLanShares {
+        channel: tokio::sync::oneshot::Sender<Result<Vec<LanShare>, Arc<str>>>,
+    },
+    /// Downloads an instance shared by another launcher and creates a new instance from it
+    ImportLanShare {
+        share: LanShare,
+        modal_action: ModalAction,
+    },
+    /// Creates a Fabric instance for speedrunning with the mods from the community legal mods list
+    CreateSpeedrunInstance {
+        name: Arc<str>,
+        modal_action: ModalAction,
+    },
+    /// Answer to `MessageToFrontend::ForeignSyncMembership`, when `adopt` is false the instance keeps its own copies
+    ResolveSyncMembership {
+        id: InstanceID,
+        targets: EnumSet<SyncTarget>,
+        adopt: bool,
+    },
     SetInstanceMinecraftVersion {
         id: InstanceID,
         version: Ustr
@@ -61,10 +165,24 @@ pub enum MessageToBackend {
         id: InstanceID,
         memory: InstanceMemoryConfiguration,
     },
+    SetInstancesMemory {
+        ids: Vec<InstanceID>,
+        memory: InstanceMemoryConfiguration,
+    },
     SetInstanceJvmFlags {
         id: InstanceID,
         jvm_flags: InstanceJvmFlagsConfiguration,
     },
+    /// Overrides the launcher name and version the game is told about and adds system properties
+    SetInstanceBranding {
+        id: InstanceID,
+        branding: InstanceBrandingConfiguration,
+    },
+    /// Environment variables set for the game process
+    SetInstanceEnvironment {
+        id: InstanceID,
+        environment: InstanceEnvironmentConfiguration,
+    },
     SetInstanceJvmBinary {
         id: InstanceID,
         jvm_binary: InstanceJvmBinaryConfiguration,
@@ -73,20 +191,142 @@ pub enum MessageToBackend {
         id: InstanceID,
         linux_wrapper: InstanceLinuxWrapperConfiguration,
     },
+    SetInstanceAccount {
+        id: InstanceID,
+        account: Option<Uuid>,
+    },
+    /// Sent when an instance is selected, so its launch metadata can be loaded before Start is pressed
+    PrewarmInstance {
+        id: InstanceID,
+    },
+    SetInstanceLocked {
+        id: InstanceID,
+        locked: bool,
+    },
+    SetInstanceCaptureLogEvents {
+        id: InstanceID,
+        capture: bool,
+    },
+    /// Starts mirroring a local pack source into the instance, or stops if `source` is None
+    SetInstancePackSource {
+        id: InstanceID,
+        source: Option<PathBuf>,
+    },
+    SetInstanceIcon {
+        id: InstanceID,
+        icon: Option<InstanceIcon>,
+    },
+    /// Copies the image into the launcher as a square PNG and makes it the icon of the instance
+    SetInstanceIconImage {
+        id: InstanceID,
+        path: PathBuf,
+    },
+    SetInstanceColorTag {
+        id: InstanceID,
+        color_tag: Option<InstanceColorTag>,
+    },
+    SetInstancesColorTag {
+        ids: Vec<InstanceID>,
+        color_tag: Option<InstanceColorTag>,
+    },
+    /// Seeds, deaths and coordinates recovered from the logs of previous sessions, oldest first
+    GetInstanceTimeline {
+        id: InstanceID,
+        channel: tokio::sync::oneshot::Sender<Arc<[InstanceTimelineEvent]>>,
+    },
+    /// Snapshots of the instance's config files, oldest first
+    GetConfigSnapshots {
+        id: InstanceID,
+        channel: tokio::sync::oneshot::Sender<Arc<[ConfigSnapshot]>>,
+    },
+    /// Changes from the snapshot to the config files currently on disk
+    GetConfigSnapshotDiff {
+        id: InstanceID,
+        time: i64,
+        channel: tokio::sync::oneshot::Sender<Result<Arc<[ConfigFileDiff]>, Arc<str>>>,
+    },
+    TakeConfigSnapshot {
+        id: InstanceID,
+        channel: tokio::sync::oneshot::Sender<()>,
+    },
+    /// Overwrites the config files with the snapshot, after snapshotting the current ones
+    RestoreConfigSnapshot {
+        id: InstanceID,
+        time: i64,
+        channel: tokio::sync::oneshot::Sender<()>,
+    },
+    SetInstancePlaytimeLimit {
+        id: InstanceID,
+        daily_limit: Option<u32>,
+    },
+    /// Seconds the instance has been played today
+    GetPlaytimeToday {
+        id: InstanceID,
+        channel: tokio::sync::oneshot::Sender<u64>,
+    },
     KillInstance {
         id: InstanceID,
     },
+    /// Forwards the port of the running game's LAN world through UPnP, or removes the forward
+    SetLanPortForwarding {
+        id: InstanceID,
+        enabled: bool,
+    },
     StartInstance {
         id: InstanceID,
         quick_play: Option<QuickPlayLaunch>,
+        mods: LaunchMods,
         modal_action: ModalAction,
     },
+    /// Works out what launching the instance would do without doing it. Only metadata is downloaded
+    GetLaunchPreflight {
+        id: InstanceID,
+        channel: tokio::sync::oneshot::Sender<Result<LaunchPreflight, Arc<str>>>,
+    },
+    GetModBisection {
+        id: InstanceID,
+        channel: tokio::sync::oneshot::Sender<Option<ModBisectionState>>,
+    },
+    /// Starts searching for the mod that makes the instance crash, among its enabled mods
+    StartModBisection {
+        id: InstanceID,
+        channel: tokio::sync::oneshot::Sender<Result<ModBisectionState, Arc<str>>>,
+    },
+    /// Records whether the game crashed with the mods of the current step, moving on to the next step
+    ReportModBisectionResult {
+        id: InstanceID,
+        crashed: bool,
+        channel: tokio::sync::oneshot::Sender<Option<ModBisectionState>>,
+    },
+    StopModBisection {
+        id: InstanceID,
+    },
+    /// The action of the instance's launch in progress, if it is currently launching
+    GetLaunchProgress {
+        id: InstanceID,
+        channel: tokio::sync::oneshot::Sender<Option<ModalAction>>,
+    },
     RequestLoadWorlds {
         id: InstanceID,
     },
     RequestLoadServers {
         id: InstanceID,
     },
+    /// Zips the world to the instance's backups folder
+    BackupWorld {
+        id: InstanceID,
+        level_path: Arc<Path>,
+        modal_action: ModalAction,
+    },
+    DeleteWorld {
+        id: InstanceID,
+        level_path: Arc<Path>,
+    },
+    GetWorldPreview {
+        id: InstanceID,
+        level_path: Arc<Path>,
+        channel: tokio::sync::oneshot::Sender<Result<WorldPreview, Arc<str>>>,
+    },
     RequestLoadMods {
         id: InstanceID,
     },
@@ -114,7 +354,23 @@ pub enum MessageToBackend {
         content: ContentInstall,
         modal_action: ModalAction,
     },
-    DownloadAllMetadata,
+    SearchContent {
+        source: ContentSourceId,
+        query: ContentSearchQuery,
+        channel: tokio::sync::oneshot::Sender<Result<ContentSearchResults, Arc<str>>>,
+    },
+    /// Downloads all version metadata and Java runtime manifests, writing a JSON report of schema mismatches and
+    /// unsupported arguments to `report_path`
+    ValidateAllMetadata {
+        report_path: PathBuf,
+        modal_action: ModalAction,
+    },
+    /// Goes through the whole launch of a throwaway vanilla instance, but only starts Java to check its arguments.
+    /// Uses the latest release when no version is given
+    SmokeTest {
+        minecraft_version: Option<Ustr>,
+        modal_action: ModalAction,
+    },
     UpdateCheck {
         instance: InstanceID,
         modal_action: ModalAction
@@ -124,6 +380,16 @@ pub enum MessageToBackend {
         content_id: InstanceContentID,
         modal_action: ModalAction,
     },
+    /// Loads the mods of one instance for the mods overview, which groups them across instances
+    GetInstanceModInstalls {
+        id: InstanceID,
+        channel: tokio::sync::oneshot::Sender<Arc<[ModInstallEntry]>>,
+    },
+    /// Checks the instances of the targets for updates and updates the targets that have one
+    UpdateContentInInstances {
+        targets: Vec<(InstanceID, InstanceContentID)>,
+        modal_action: ModalAction,
+    },
     Sleep5s,
     ReadLog {
         path: Arc<Path>,
@@ -139,10 +405,54 @@ pub enum MessageToBackend {
     GetBackendConfiguration {
         channel: tokio::sync::oneshot::Sender<BackendConfig>,
     },
+    GetDownloadConditions {
+        channel: tokio::sync::oneshot::Sender<DownloadConditions>,
+    },
+    /// The end of the launcher's own log, for following it in the debug page
+    GetLauncherLog {
+        channel: tokio::sync::oneshot::Sender<Arc<str>>,
+    },
+    /// Disk usage of the launcher's directories and of each instance
+    GetStorageReport {
+        channel: tokio::sync::oneshot::Sender<StorageReport>,
+    },
+    /// Answers with the number of bytes freed. Refused while an instance is running or launching, since they may
+    /// use the files that would be removed
+    CleanupStorage {
+        cleanup: StorageCleanup,
+        channel: tokio::sync::oneshot::Sender<Result<u64, Arc<str>>>,
+    },
+    /// Installed JVMs found in common locations, newest first
+    ScanJavaInstallations {
+        channel: tokio::sync::oneshot::Sender<Vec<JavaInstallation>>,
+    },
+    /// Total physical memory in MiB, None if it couldn't be determined
+    GetSystemMemory {
+        channel: tokio::sync::oneshot::Sender<Option<u64>>,
+    },
+    /// Hashes every game file the instance needs against its manifest, repairing and reporting the ones that don't match
+    VerifyInstanceFiles {
+        id: InstanceID,
+        modal_action: ModalAction,
+    },
+    /// Memory suggested from the installed RAM and the mods of the instance, or of the most heavily modded
+    /// instance when no id is given. None when the installed RAM is unknown
+    GetRecommendedMemory {
+        id: Option<InstanceID>,
+        channel: tokio::sync::oneshot::Sender<Option<InstanceMemoryConfiguration>>,
+    },
     SetSyncing {
         target: SyncTarget,
         value: bool,
     },
+    /// Links or unlinks the folder of one instance, by the name of its folder. Linking merges the instance's own
+    /// folder into the synced one, unlinking leaves the instance with a copy. The channel is answered or dropped once done
+    SetInstanceSyncing {
+        target: SyncTarget,
+        instance: Arc<str>,
+        value: bool,
+        channel: tokio::sync::oneshot::Sender<()>,
+    },
     SetGlobalInstanceOverrides {
         memory_enabled: bool,
         memory: Option<InstanceMemoryConfiguration>,
@@ -163,7 +473,8 @@ pub enum MessageToBackend {
     },
     AddOfflineAccount {
         name: Arc<str>,
-        uuid: Uuid
+        /// Derived from the name if not specified
+        uuid: Option<Uuid>,
     },
     SelectAccount {
         uuid: Uuid,
@@ -171,9 +482,109 @@ pub enum MessageToBackend {
     DeleteAccount {
         uuid: Uuid,
     },
+    /// The skin of the selected account
+    GetSkinProfile {
+        channel: tokio::sync::oneshot::Sender<Result<SkinProfile, Arc<str>>>,
+    },
+    GetSkinLibrary {
+        channel: tokio::sync::oneshot::Sender<Arc<[LibrarySkin]>>,
+    },
+    AddSkinToLibrary {
+        source: SkinSource,
+        model: SkinModel,
+        channel: tokio::sync::oneshot::Sender<Result<(), Arc<str>>>,
+    },
+    RemoveSkinFromLibrary {
+        id: Arc<str>,
+        channel: tokio::sync::oneshot::Sender<()>,
+    },
+    /// Makes a skin from the library the skin of the selected account, replying once it's uploaded
+    UploadSkin {
+        id: Arc<str>,
+        modal_action: ModalAction,
+        channel: tokio::sync::oneshot::Sender<()>,
+    },
     SetOpenGameOutputAfterLaunching {
         value: bool,
     },
+    SetKillGamesOnLauncherExit {
+        value: bool,
+    },
+    SetOfflineFallback {
+        value: bool,
+    },
+    SetOfflineMode {
+        value: bool,
+    },
+    SetDownloadOnlySelectedLanguage {
+        value: bool,
+    },
+    SetUpdateChannel {
+        channel: UpdateChannel,
+    },
+    SetUpdateCheckFrequency {
+        frequency: UpdateCheckFrequency,
+    },
+    /// Checks for a launcher update right away, reporting the result even when there is none
+    CheckForLauncherUpdate,
+    SetMetadataRefreshInterval {
+        interval: MetadataRefreshInterval,
+    },
+    SetNewVersionDesktopNotification {
+        value: bool,
+    },
+    /// Whether metadata with fields unknown to the schemas fails to load instead of only being reported
+    SetStrictSchema {
+        value: bool,
+    },
+    /// Proxy and certificate changes apply after restarting the launcher
+    SetNetworkOverrides {
+        user_agent: Option<Arc<str>>,
+        endpoints: EndpointOverrides,
+        proxy: ProxySettings,
+        ca_certificates: Option<Arc<Path>>,
+    },
+    /// Tries to reach Mojang through the given settings without saving them, answers with the time it took in
+    /// milliseconds
+    TestConnection {
+        user_agent: Option<Arc<str>>,
+        proxy: ProxySettings,
+        ca_certificates: Option<Arc<Path>>,
+        channel: tokio::sync::oneshot::Sender<Result<u64, Arc<str>>>,
+    },
+    SetDownloadLimits {
+        limits: DownloadLimits,
+    },
+    SetLaunchLimits {
+        limits: LaunchLimits,
+    },
+    SetCurseForgeApiKey {
+        api_key: Option<Arc<str>>,
+    },
+    SetLauncherPin {
+        pin: Option<Arc<str>>,
+    },
+    /// Unlocks the launcher for the rest of the session when the PIN is correct
+    CheckLauncherPin {
+        pin: Arc<str>,
+        channel: tokio::sync::oneshot::Sender<bool>,
+    },
+    /// Forgets that the PIN was entered, so gated messages are refused until it's entered again
+    LockLauncher,
+    SetPlaytimeLimits {
+        daily_limit: Option<u32>,
+        refuse_launch: bool,
+    },
+    /// Allows an instance to be launched past its playtime limit for the rest of the day, answers
+    /// false if the launcher PIN is wrong
+    OverridePlaytimeLimit {
+        id: InstanceID,
+        pin: Arc<str>,
+        channel: tokio::sync::oneshot::Sender<bool>,
+    },
+    LauncherExiting {
+        channel: tokio::sync::oneshot::Sender<()>,
+    },
     CreateInstanceShortcut {
         id: InstanceID,
         path: PathBuf
@@ -184,6 +595,142 @@ pub enum MessageToBackend {
     }
 }
 
+impl MessageToBackend {
+    /// Whether the message is refused while a launcher PIN is set and hasn't been entered this session. This is
+    /// enforced by the backend, so it also covers the CLI and anything else that talks to it. Only messages that read
+    /// state or unlock the launcher are let through, so new messages are gated unless they're added here
+    pub fn requires_unlock(&self) -> bool {
+        !matches!(self,
+            Self::RequestMetadata { .. }
+            | Self::GetOfficialLauncherProfiles { .. }
+            | Self::GetModList { .. }
+            | Self::DiscoverLanShares { .. }
+            | Self::PrewarmInstance { .. }
+            | Self::GetInstanceTimeline { .. }
+            | Self::GetConfigSnapshots { .. }
+            | Self::GetConfigSnapshotDiff { .. }
+            | Self::GetPlaytimeToday { .. }
+            | Self::KillInstance { .. }
+            | Self::GetLaunchPreflight { .. }
+            | Self::GetModBisection { .. }
+            | Self::GetLaunchProgress { .. }
+            | Self::RequestLoadWorlds { .. }
+            | Self::RequestLoadServers { .. }
+            | Self::GetWorldPreview { .. }
+            | Self::RequestLoadMods { .. }
+            | Self::RequestLoadResourcePacks { .. }
+            | Self::SearchContent { .. }
+            | Self::UpdateCheck { .. }
+            | Self::GetInstanceModInstalls { .. }
+            | Self::Sleep5s
+            | Self::ReadLog { .. }
+            | Self::GetLogFiles { .. }
+            | Self::GetSyncState { .. }
+            | Self::GetBackendConfiguration { .. }
+            | Self::CheckForLauncherUpdate
+            | Self::TestConnection { .. }
+            | Self::GetDownloadConditions { .. }
+            | Self::GetStorageReport { .. }
+            | Self::GetLauncherLog { .. }
+            | Self::ScanJavaInstallations { .. }
+            | Self::GetSystemMemory { .. }
+            | Self::GetRecommendedMemory { .. }
+            | Self::GetSkinProfile { .. }
+            | Self::GetSkinLibrary { .. }
+            | Self::CheckLauncherPin { .. }
+            | Self::LockLauncher
+            | Self::OverridePlaytimeLimit { .. }
+            | Self::LauncherExiting { .. }
+        )
+    }
+
+    /// The action a modal is waiting on, for messages that are refused before they're handled or that are lost when
+    /// the backend is restarted
+    pub fn modal_action(&self) -> Option<&ModalAction> {
+        match self {
+            Self::CreateInstance { modal_action, .. }
+            | Self::PrepareInstances { modal_action, .. }
+            | Self::CloneInstanceToVersion { modal_action, .. }
+            | Self::ImportModpack { modal_action, .. }
+            | Self::ImportMultiMcInstances { modal_action, .. }
+            | Self::ImportOfficialLauncherProfiles { modal_action, .. }
+            | Self::SeedFromOfficialLauncher { modal_action, .. }
+            | Self::ExportModpack { modal_action, .. }
+            | Self::ExportInstance { modal_action, .. }
+            | Self::ExportModList { modal_action, .. }
+            | Self::CreateDiagnosticsBundle { modal_action, .. }
+            | Self::ShareInstanceOnLan { modal_action, .. }
+            | Self::ImportLanShare { modal_action, .. }
+            | Self::CreateSpeedrunInstance { modal_action, .. }
+            | Self::StartInstance { modal_action, .. }
+            | Self::BackupWorld { modal_action, .. }
+            | Self::InstallContent { modal_action, .. }
+            | Self::ValidateAllMetadata { modal_action, .. }
+            | Self::SmokeTest { modal_action, .. }
+            | Self::UpdateCheck { modal_action, .. }
+            | Self::UpdateContent { modal_action, .. }
+            | Self::UpdateContentInInstances { modal_action, .. }
+            | Self::VerifyInstanceFiles { modal_action, .. }
+            | Self::UploadLogFile { modal_action, .. }
+            | Self::AddNewAccount { modal_action, .. }
+            | Self::UploadSkin { modal_action, .. }
+            | Self::InstallUpdate { modal_action, .. } => Some(modal_action),
+            _ => None,
+        }
+    }
+
+    /// Whether handling the message may write to the launcher directory, which isn't allowed while it's open
+    /// read-only
+    pub fn modifies_data(&self) -> bool {
+        !matches!(self,
+            Self::RequestMetadata { .. }
+            | Self::GetOfficialLauncherProfiles { .. }
+            | Self::ExportModpack { .. }
+            | Self::ExportInstance { .. }
+            | Self::GetModList { .. }
+            | Self::ExportModList { .. }
+            | Self::CreateDiagnosticsBundle { .. }
+            | Self::DiscoverLanShares { .. }
+            | Self::GetInstanceTimeline { .. }
+            | Self::GetConfigSnapshots { .. }
+            | Self::GetConfigSnapshotDiff { .. }
+            | Self::GetPlaytimeToday { .. }
+            | Self::KillInstance { .. }
+            | Self::GetLaunchPreflight { .. }
+            | Self::GetModBisection { .. }
+            | Self::GetLaunchProgress { .. }
+            | Self::RequestLoadWorlds { .. }
+            | Self::RequestLoadServers { .. }
+            | Self::GetWorldPreview { .. }
+            | Self::RequestLoadMods { .. }
+            | Self::RequestLoadResourcePacks { .. }
+            | Self::SearchContent { .. }
+            | Self::UpdateCheck { .. }
+            | Self::GetInstanceModInstalls { .. }
+            | Self::Sleep5s
+            | Self::ReadLog { .. }
+            | Self::GetLogFiles { .. }
+            | Self::GetSyncState { .. }
+            | Self::GetBackendConfiguration { .. }
+            | Self::CheckForLauncherUpdate
+            | Self::TestConnection { .. }
+            | Self::GetDownloadConditions { .. }
+            | Self::GetStorageReport { .. }
+            | Self::GetLauncherLog { .. }
+            | Self::ScanJavaInstallations { .. }
+            | Self::GetSystemMemory { .. }
+            | Self::GetRecommendedMemory { .. }
+            | Self::UploadLogFile { .. }
+            | Self::GetSkinProfile { .. }
+            | Self::GetSkinLibrary { .. }
+            | Self::CheckLauncherPin { .. }
+            | Self::LockLauncher
+            | Self::LauncherExiting { .. }
+            | Self::CreateInstanceShortcut { .. }
+        )
+    }
+}
+
 #[derive(Debug)]
 pub enum MessageToFrontend {
     InstanceAdded {
@@ -191,6 +738,8 @@ pub enum MessageToFrontend {
         name: Ustr,
         dot_minecraft_folder: Arc<Path>,
         configuration: InstanceConfiguration,
+        /// Seconds played in total, from the playtime ledger
+        total_playtime: u64,
         worlds_state: Arc<AtomicBridgeDataLoadState>,
         servers_state: Arc<AtomicBridgeDataLoadState>,
         mods_state: Arc<AtomicBridgeDataLoadState>,
@@ -204,7 +753,9 @@ pub enum MessageToFrontend {
         name: Ustr,
         dot_minecraft_folder: Arc<Path>,
         configuration: InstanceConfiguration,
+        total_playtime: u64,
         status: InstanceStatus,
+        process: Option<InstanceProcessInfo>,
     },
     InstanceWorldsUpdated {
         id: InstanceID,
@@ -241,6 +792,9 @@ pub enum MessageToFrontend {
         selected_account: Option<Uuid>,
     },
     Refresh,
+    /// The backend was restarted with a new channel. It sends its accounts and instances again, so the ones
+    /// from before are forgotten
+    BackendRestarted,
     CloseModal,
     MoveInstanceToTop {
         id: InstanceID,
@@ -250,9 +804,79 @@ pub enum MessageToFrontend {
         result: Result<MetadataResult, Arc<str>>,
         keep_alive_handle: Option<KeepAliveHandle>,
     },
+    /// Shown by the operating system, outside of the launcher's windows
+    DesktopNotification {
+        title: Arc<str>,
+        body: Arc<str>,
+    },
     UpdateAvailable {
         update: UpdatePrompt,
     },
+    InstanceCrashed {
+        id: InstanceID,
+        name: Ustr,
+        exit_code: Option<i32>,
+        crash_report: Option<Arc<Path>>,
+        excerpt: Option<Arc<str>>,
+        /// OS, memory and GPU/driver details
+        system_info: Arc<str>,
+        /// File combining the system info and the crash report, for attaching to bug reports
+        bundle: Option<Arc<Path>>,
+    },
+    /// A screenshot was saved while the instance was running
+    ScreenshotTaken {
+        id: InstanceID,
+        name: Ustr,
+        path: Arc<Path>,
+    },
+    /// A game launched for a step of a mod bisection has exited
+    ModBisectionLaunchExited {
+        id: InstanceID,
+        name: Ustr,
+        crashed: bool,
+    },
+    LaunchFailed {
+        id: InstanceID,
+        name: Ustr,
+        error: Arc<str>,
+    },
+    /// An instance was loaded that had been synced in a different launcher directory
+    ForeignSyncMembership {
+        id: InstanceID,
+        name: Ustr,
+        targets: EnumSet<SyncTarget>,
+    },
+    /// A launch was refused because the daily playtime limit was used up
+    PlaytimeLimitReached {
+        id: InstanceID,
+        name: Ustr,
+        /// Whether a launcher PIN is set that can be used to allow playing anyway
+        can_override: bool,
+    },
+    /// Sent on first run when the official launcher has asset objects that could be reused
+    OfficialLauncherFilesFound {
+        minecraft_dir: Arc<Path>,
+        objects: usize,
+        bytes: u64,
+    },
+    /// Sent on startup and whenever the launcher goes offline or back online
+    OfflineStateChanged {
+        offline: bool,
+        /// Minecraft versions whose files were downloaded before, empty while online
+        cached_versions: Arc<[Ustr]>,
+    },
+    /// The launcher was started again while already running
+    FocusWindow,
+    /// The launcher was started again with `--launch` while already running
+    LaunchRequested {
+        id: InstanceID,
+        name: Ustr,
+    },
+    /// Work the backend started on its own, without a modal showing its progress
+    BackgroundTaskStarted {
+        title: Arc<str>,
+        modal_action: ModalAction,
+    },
 }
 
 #[derive(Debug, Default)]
@@ -261,6 +885,156 @@ pub struct LogFiles {
     pub total_gzipped_size: usize,
 }
 
+#[derive(Debug, EnumSetType)]
+pub enum InstanceExportFolder {
+    Mods,
+    Config,
+    ResourcePacks,
+    ShaderPacks,
+    Saves,
+}
+
+impl InstanceExportFolder {
+    pub fn folder_name(self) -> &'static str {
+        match self {
+            InstanceExportFolder::Mods => "mods",
+            InstanceExportFolder::Config => "config",
+            InstanceExportFolder::ResourcePacks => "resourcepacks",
+            InstanceExportFolder::ShaderPacks => "shaderpacks",
+            InstanceExportFolder::Saves => "saves",
+        }
+    }
+
+    pub fn translation_key(self) -> &'static str {
+        match self {
+            InstanceExportFolder::Mods => "export_folder_mods",
+            InstanceExportFolder::Config => "export_folder_config",
+            InstanceExportFolder::ResourcePacks => "export_folder_resourcepacks",
+            InstanceExportFolder::ShaderPacks => "export_folder_shaderpacks",
+            InstanceExportFolder::Saves => "export_folder_saves",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModListFormat {
+    Markdown,
+    Csv,
+}
+
+impl ModListFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ModListFormat::Markdown => "md",
+            ModListFormat::Csv => "csv",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OfficialLauncherProfiles {
+    pub minecraft_dir: Arc<Path>,
+    pub profiles: Arc<[OfficialLauncherProfile]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OfficialLauncherProfile {
+    pub id: Arc<str>,
+    pub name: Arc<str>,
+    /// Version folder the profile launches, e.g. "fabric-loader-0.16.5-1.21.1"
+    pub version: Arc<str>,
+    pub game_dir: Arc<Path>,
+}
+
+#[derive(Debug, Clone)]
+pub struct JavaInstallation {
+    pub path: Arc<Path>,
+    pub major_version: u32,
+    /// Full version, e.g. `17.0.9`
+    pub version: Option<Arc<str>>,
+    pub vendor: Option<Arc<str>>,
+    pub arch: Option<Arc<str>>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DownloadConditions {
+    /// Free space in bytes on the drive containing the launcher directory, if known
+    pub available_space: Option<u64>,
+    /// Whether the OS reports the active network connection as metered
+    pub metered: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct LaunchPreflight {
+    pub account: PreflightAccount,
+    pub java: PreflightJava,
+    /// Game files that are already on disk with the expected size. Their hashes are only checked when launching
+    pub cached_files: usize,
+    pub files_to_download: usize,
+    /// Bytes transferred to download the missing files, which is less than they take up when Java is compressed
+    pub download_size: u64,
+    /// Bytes the missing files take up once written to disk
+    pub install_size: u64,
+    /// Free space in bytes on the drive containing the launcher directory, if known
+    pub available_space: Option<u64>,
+    /// Forge and NeoForge run their installer when launching, the libraries it adds aren't counted above
+    pub runs_loader_installer: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum PreflightAccount {
+    /// The saved session can be used as it is
+    Ready { username: Arc<str> },
+    /// The saved session expired and is refreshed without asking
+    Refresh { username: Arc<str> },
+    Offline { username: Arc<str> },
+    /// Launching opens the Microsoft login
+    LoginRequired,
+}
+
+#[derive(Debug, Clone)]
+pub enum PreflightJava {
+    /// A binary chosen in the settings or through `FORCE_EXTERNAL_JAVA`
+    External { path: Arc<Path> },
+    /// The runtime provided by Mojang, which is downloaded when files are missing
+    Mojang { component: Arc<str>, missing_files: usize },
+    Unavailable { error: Arc<str> },
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct StorageReport {
+    pub assets: u64,
+    /// Size of the assets that no installed version uses, None when it can't be told which assets an installed
+    /// version uses because its version info isn't downloaded
+    pub unused_assets: Option<u64>,
+    pub libraries: u64,
+    /// Size of the libraries that no installed instance used on its last launch, None when an instance hasn't been
+    /// launched since the launcher started keeping track
+    pub unused_libraries: Option<u64>,
+    pub java_runtimes: u64,
+    pub metadata_cache: u64,
+    pub natives: u64,
+    /// Unfinished downloads and leftovers of interrupted imports
+    pub orphaned_files: u64,
+    /// Sorted by size, largest first
+    pub instances: Vec<InstanceStorage>,
+}
+
+#[derive(Debug, Clone)]
+pub struct InstanceStorage {
+    /// Name of the instance's folder
+    pub name: Arc<str>,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageCleanup {
+    UnusedAssets,
+    UnusedLibraries,
+    NativesDirs,
+    OrphanedFiles,
+}
+
 #[derive(Debug, Default)]
 pub struct SyncState {
     pub sync_folder: Option<Arc<Path>>,
@@ -268,6 +1042,25 @@ pub struct SyncState {
     pub total: usize,
     pub synced: enum_map::EnumMap<SyncTarget, usize>,
     pub cannot_sync: enum_map::EnumMap<SyncTarget, usize>,
+    /// Every instance for targets that are folders, sorted by name
+    pub instances: enum_map::EnumMap<SyncTarget, Vec<InstanceSyncEntry>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct InstanceSyncEntry {
+    /// Name of the instance's folder
+    pub name: Arc<str>,
+    pub status: InstanceSyncStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstanceSyncStatus {
+    /// The folder is a link to the synced folder
+    Linked,
+    /// The instance doesn't have the folder yet
+    Unlinked,
+    /// The instance has its own folder, which keeps it from being linked
+    Conflicting,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -300,6 +1093,30 @@ impl BridgeDataLoadState {
     }
 }
 
+/// Which of the instance's mods are loaded for a single launch
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchMods {
+    #[default]
+    All,
+    /// Every mod is moved aside until the game exits, to check whether a crash is caused by a mod
+    SafeMode,
+    /// Only the mods tested in the current step of the instance's mod bisection are loaded
+    Bisection,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModBisectionState {
+    pub step: usize,
+    /// Number of mods the search started with
+    pub total: usize,
+    /// File names of the mods that could still be causing the crash
+    pub suspects: Arc<[Arc<str>]>,
+    /// File names of the mods enabled for the next launch, empty once the search is over
+    pub testing: Arc<[Arc<str>]>,
+    /// The only remaining suspect, once the search is over
+    pub culprit: Option<Arc<str>>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum QuickPlayLaunch {
     Singleplayer(OsString),
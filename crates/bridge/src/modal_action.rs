@@ -1,10 +1,11 @@
 use std::{
+    collections::VecDeque,
     ops::Deref,
     sync::{
-        Arc, RwLock,
-        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, RwLock, Weak,
+        atomic::{AtomicBool, AtomicU64, Ordering},
     },
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use atomic_time::AtomicOptionInstant;
@@ -21,6 +22,28 @@ impl ModalAction {
     pub fn refcnt(&self) -> usize {
         Arc::strong_count(&self.inner)
     }
+
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// A reference that doesn't count towards [`ModalAction::refcnt`], so holding it doesn't keep the action open
+    pub fn downgrade(&self) -> WeakModalAction {
+        WeakModalAction {
+            inner: Arc::downgrade(&self.inner),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WeakModalAction {
+    inner: Weak<ModalActionInner>,
+}
+
+impl WeakModalAction {
+    pub fn upgrade(&self) -> Option<ModalAction> {
+        self.inner.upgrade().map(|inner| ModalAction { inner })
+    }
 }
 
 impl Deref for ModalAction {
@@ -111,12 +134,20 @@ pub struct ProgressTracker {
     notify_serial: AtomicOptionSerial,
 }
 
+/// How far back the transfer speed is measured
+const SPEED_WINDOW: Duration = Duration::from_secs(5);
+const SPEED_SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
 struct ProgressTrackerInner {
-    count: AtomicUsize,
-    total: AtomicUsize,
+    count: AtomicU64,
+    total: AtomicU64,
     finished_at: AtomicOptionInstant,
     finish_type: AtomicProgressTrackerFinishType,
     title: RwLock<Arc<str>>,
+    /// Whether the count is in bytes, only then a speed and time remaining are measured
+    counts_bytes: AtomicBool,
+    /// Counts at points in time over the last `SPEED_WINDOW`, oldest first
+    samples: Mutex<VecDeque<(Instant, u64)>>,
 }
 
 #[atomic_enum::atomic_enum]
@@ -151,11 +182,13 @@ impl ProgressTracker {
     pub fn new(title: Arc<str>, sender: FrontendHandle) -> Self {
         Self {
             inner: Arc::new(ProgressTrackerInner {
-                count: AtomicUsize::new(0),
-                total: AtomicUsize::new(0),
+                count: AtomicU64::new(0),
+                total: AtomicU64::new(0),
                 finished_at: AtomicOptionInstant::none(),
                 finish_type: AtomicProgressTrackerFinishType::new(ProgressTrackerFinishType::Normal),
                 title: RwLock::new(title),
+                counts_bytes: AtomicBool::new(false),
+                samples: Mutex::new(VecDeque::new()),
             }),
             sender,
             notify_serial: AtomicOptionSerial::default(),
@@ -183,7 +216,7 @@ impl ProgressTracker {
         }
     }
 
-    pub fn get(&self) -> (usize, usize) {
+    pub fn get(&self) -> (u64, u64) {
         (
             self.inner.count.load(Ordering::SeqCst),
             self.inner.total.load(Ordering::SeqCst)
@@ -203,22 +236,85 @@ impl ProgressTracker {
         self.inner.finish_type.load(Ordering::SeqCst)
     }
 
-    pub fn add_count(&self, count: usize) {
-        self.inner.count.fetch_add(count, Ordering::SeqCst);
+    pub fn add_count(&self, count: u64) {
+        let count = self.inner.count.fetch_add(count, Ordering::SeqCst).wrapping_add(count);
+        self.record_sample(count);
+    }
+
+    pub fn sub_count(&self, count: u64) {
+        let count = self.inner.count.fetch_sub(count, Ordering::SeqCst).wrapping_sub(count);
+        self.record_sample(count);
     }
 
-    pub fn set_count(&self, count: usize) {
+    pub fn set_count(&self, count: u64) {
         self.inner.count.store(count, Ordering::SeqCst);
+        self.record_sample(count);
     }
 
-    pub fn add_total(&self, total: usize) {
+    pub fn add_total(&self, total: u64) {
         self.inner.total.fetch_add(total, Ordering::SeqCst);
     }
 
-    pub fn set_total(&self, total: usize) {
+    pub fn set_total(&self, total: u64) {
         self.inner.total.store(total, Ordering::SeqCst);
     }
 
+    /// Marks the count as being in bytes, which makes the speed and time remaining available
+    pub fn set_counts_bytes(&self) {
+        self.inner.counts_bytes.store(true, Ordering::Relaxed);
+    }
+
+    pub fn counts_bytes(&self) -> bool {
+        self.inner.counts_bytes.load(Ordering::Relaxed)
+    }
+
+    fn record_sample(&self, count: u64) {
+        if !self.counts_bytes() {
+            return;
+        }
+        // Downloads running in parallel all advance the same tracker, one of them recording the sample is enough
+        let Ok(mut samples) = self.inner.samples.try_lock() else {
+            return;
+        };
+        let now = Instant::now();
+        if samples.back().is_some_and(|(time, _)| now.duration_since(*time) < SPEED_SAMPLE_INTERVAL) {
+            return;
+        }
+        samples.push_back((now, count));
+        while samples.front().is_some_and(|(time, _)| now.duration_since(*time) > SPEED_WINDOW) {
+            samples.pop_front();
+        }
+    }
+
+    /// Bytes per second over the last few seconds, None until there's been enough progress to tell
+    pub fn bytes_per_second(&self) -> Option<f64> {
+        if !self.counts_bytes() || self.get_finished_at().is_some() {
+            return None;
+        }
+        let samples = self.inner.samples.lock().unwrap();
+        let (oldest_time, oldest_count) = *samples.front()?;
+        let elapsed = Instant::now().duration_since(oldest_time);
+        // A stalled download has no recent samples and counts as not moving
+        if elapsed > SPEED_WINDOW * 2 {
+            return Some(0.0);
+        }
+        if elapsed < Duration::from_secs(1) {
+            return None;
+        }
+        let count = self.inner.count.load(Ordering::SeqCst);
+        Some(count.saturating_sub(oldest_count) as f64 / elapsed.as_secs_f64())
+    }
+
+    /// Time left at the current speed
+    pub fn remaining(&self) -> Option<Duration> {
+        let speed = self.bytes_per_second().filter(|speed| *speed >= 1.0)?;
+        let (count, total) = self.get();
+        if total == 0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(total.saturating_sub(count) as f64 / speed))
+    }
+
     pub fn notify(&self) {
         self.sender.send_with_serial(MessageToFrontend::Refresh, &self.notify_serial);
     }
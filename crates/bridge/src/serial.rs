@@ -40,6 +40,11 @@ impl AtomicSerialProvider {
     pub fn next(&self) -> Serial {
         Serial(self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed).wrapping_add(1))
     }
+
+    /// The serial that was handed out last
+    pub(crate) fn current(&self) -> Serial {
+        Serial(self.0.load(std::sync::atomic::Ordering::Relaxed))
+    }
 }
 
 #[derive(Default, Debug, Clone)]
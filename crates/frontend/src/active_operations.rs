@@ -0,0 +1,123 @@
+use std::{rc::Rc, sync::Arc, time::Instant};
+
+use bridge::modal_action::{ModalAction, ProgressTracker};
+use gpui::{App, SharedString, Window};
+
+/// Finished operations kept around so they can still be seen and retried in the task center
+const MAX_FINISHED: usize = 20;
+
+/// Long-running operations started from the frontend or by the backend on its own, shown in the task center
+#[derive(Default)]
+pub struct ActiveOperations {
+    operations: Vec<ActiveOperation>,
+}
+
+#[derive(Clone)]
+pub struct ActiveOperation {
+    pub fallback_title: SharedString,
+    pub modal_action: ModalAction,
+    pub started_at: Instant,
+    pub retry: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskState {
+    /// Waiting for the backend to start working on it, e.g. for other downloads to finish
+    Queued,
+    Running,
+    Failed(Arc<str>),
+    Done,
+}
+
+impl gpui::Global for ActiveOperations {}
+
+impl ActiveOperation {
+    pub fn title(&self) -> SharedString {
+        let trackers = self.modal_action.trackers.trackers.read().unwrap();
+        if let Some(tracker) = trackers.first() {
+            SharedString::new(tracker.get_title())
+        } else {
+            self.fallback_title.clone()
+        }
+    }
+
+    pub fn state(&self) -> TaskState {
+        if let Some(error) = &*self.modal_action.error.read().unwrap() {
+            return TaskState::Failed(error.clone());
+        }
+        if self.modal_action.get_finished_at().is_some() || self.modal_action.refcnt() <= 1 {
+            if self.modal_action.has_requested_cancel() {
+                return TaskState::Failed("Cancelled".into());
+            }
+            return TaskState::Done;
+        }
+        if self.modal_action.trackers.trackers.read().unwrap().is_empty() {
+            TaskState::Queued
+        } else {
+            TaskState::Running
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self.state(), TaskState::Failed(_) | TaskState::Done)
+    }
+
+    /// Trackers that haven't finished yet, e.g. the Java runtime, assets and libraries of a launch
+    pub fn running_trackers(&self) -> Vec<ProgressTracker> {
+        let trackers = self.modal_action.trackers.trackers.read().unwrap();
+        trackers.iter().filter(|tracker| tracker.get_finished_at().is_none()).cloned().collect()
+    }
+}
+
+impl ActiveOperations {
+    pub fn register(cx: &mut App, fallback_title: SharedString, modal_action: ModalAction) {
+        let operations = &mut cx.default_global::<Self>().operations;
+        if operations.iter().any(|operation| operation.modal_action.ptr_eq(&modal_action)) {
+            return;
+        }
+        operations.push(ActiveOperation { fallback_title, modal_action, started_at: Instant::now(), retry: None });
+
+        let finished = operations.iter().filter(|operation| operation.is_finished()).count();
+        let mut excess = finished.saturating_sub(MAX_FINISHED);
+        operations.retain(|operation| {
+            if excess > 0 && operation.is_finished() {
+                excess -= 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Lets the task center start the operation again, `retry` should start it the same way it was started before
+    pub fn set_retry(cx: &mut App, modal_action: &ModalAction, retry: Rc<dyn Fn(&mut Window, &mut App)>) {
+        let operations = &mut cx.default_global::<Self>().operations;
+        if let Some(operation) = operations.iter_mut().find(|operation| operation.modal_action.ptr_eq(modal_action)) {
+            operation.retry = Some(retry);
+        }
+    }
+
+    /// Every operation, newest first
+    pub fn all(cx: &mut App) -> Vec<ActiveOperation> {
+        cx.default_global::<Self>().operations.iter().rev().cloned().collect()
+    }
+
+    pub fn in_progress(cx: &mut App) -> Vec<ActiveOperation> {
+        let operations = &cx.default_global::<Self>().operations;
+        operations.iter().filter(|operation| !operation.is_finished()).cloned().collect()
+    }
+
+    pub fn clear_finished(cx: &mut App) {
+        cx.default_global::<Self>().operations.retain(|operation| !operation.is_finished());
+    }
+
+    pub fn remove(cx: &mut App, modal_action: &ModalAction) {
+        cx.default_global::<Self>().operations.retain(|operation| !operation.modal_action.ptr_eq(modal_action));
+    }
+
+    pub fn cancel_all(cx: &mut App) {
+        for operation in Self::in_progress(cx) {
+            operation.modal_action.request_cancel();
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use gpui::{App, Hsla, SharedString, WindowAppearance};
+use gpui_component::{Colorize, Theme, ThemeMode, ThemeRegistry};
+use serde::{Deserialize, Serialize};
+
+use crate::interface_config::InterfaceConfig;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AppearanceMode {
+    /// Follows the light or dark appearance of the operating system
+    System,
+    Light,
+    #[default]
+    Dark,
+}
+
+pub fn current_mode(cx: &App) -> ThemeMode {
+    match InterfaceConfig::get(cx).appearance_mode {
+        AppearanceMode::System => match cx.window_appearance() {
+            WindowAppearance::Light | WindowAppearance::VibrantLight => ThemeMode::Light,
+            WindowAppearance::Dark | WindowAppearance::VibrantDark => ThemeMode::Dark,
+        },
+        AppearanceMode::Light => ThemeMode::Light,
+        AppearanceMode::Dark => ThemeMode::Dark,
+    }
+}
+
+pub fn parse_accent(accent: &str) -> Option<Hsla> {
+    Hsla::parse_hex(accent).ok()
+}
+
+/// Applies the mode, the selected theme and the accent color from the interface config. The selected theme is only
+/// used when it's made for the current mode, otherwise the default theme for the mode is used
+pub fn apply(cx: &mut App) {
+    let mode = current_mode(cx);
+    let config = InterfaceConfig::get(cx);
+    let theme_name = SharedString::new(config.active_theme.trim_ascii());
+    let accent = config.accent_color.as_deref().and_then(parse_accent);
+
+    Theme::change(mode, None, cx);
+
+    if !theme_name.is_empty()
+        && let Some(theme) = ThemeRegistry::global(cx).themes().get(&theme_name).cloned()
+        && theme.mode == mode
+    {
+        Theme::global_mut(cx).apply_config(&theme);
+    }
+
+    if let Some(accent) = accent {
+        let theme = Theme::global_mut(cx);
+        let (hover, active) = if mode.is_dark() {
+            (accent.lighten(0.1), accent.darken(0.1))
+        } else {
+            (accent.darken(0.1), accent.darken(0.2))
+        };
+        theme.primary = accent;
+        theme.primary_hover = hover;
+        theme.primary_active = active;
+        theme.ring = accent;
+    }
+
+    cx.refresh_windows();
+}
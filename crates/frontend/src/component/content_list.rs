@@ -12,7 +12,7 @@ use gpui_component::{
 use parking_lot::Mutex;
 use rustc_hash::FxHashSet;
 
-use crate::{interface_config::InterfaceConfig, png_render_cache};
+use crate::{interface_config::InterfaceConfig, png_render_cache, ts};
 
 #[derive(Clone)]
 struct ContentEntryChild {
@@ -34,6 +34,8 @@ pub struct ContentListDelegate {
     id: InstanceID,
     backend_handle: BackendHandle,
     content: Vec<InstanceContentSummary>,
+    /// Ids of enabled content, used to point out missing dependencies
+    installed_ids: FxHashSet<Arc<str>>,
     searched: Option<Vec<SummaryOrChild>>,
     children: Vec<Vec<ContentEntryChild>>,
     expanded: Arc<AtomicUsize>,
@@ -51,6 +53,7 @@ impl ContentListDelegate {
             id,
             backend_handle,
             content: Vec::new(),
+            installed_ids: FxHashSet::default(),
             searched: None,
             children: Vec::new(),
             expanded: Arc::new(AtomicUsize::new(0)),
@@ -134,25 +137,26 @@ impl ContentListDelegate {
             bridge::instance::ContentUpdateStatus::Unknown => None,
             bridge::instance::ContentUpdateStatus::ManualInstall => Some(
                 Button::new(("update", element_id)).warning().icon(Icon::default().path("icons/file-question-mark.svg"))
-                    .tooltip("Installed manually - cannot automatically update")
+                    .tooltip(ts!("content_update_manual_install"))
             ),
             bridge::instance::ContentUpdateStatus::ErrorNotFound => Some(
                 Button::new(("update", element_id)).danger().icon(Icon::default().path("icons/triangle-alert.svg"))
-                    .tooltip("Error while checking updates - 404 not found")
+                    .tooltip(ts!("content_update_not_found"))
             ),
             bridge::instance::ContentUpdateStatus::ErrorInvalidHash => Some(
                 Button::new(("update", element_id)).danger().icon(Icon::default().path("icons/triangle-alert.svg"))
-                    .tooltip("Error while checking updates - returned invalid hash")
+                    .tooltip(ts!("content_update_invalid_hash"))
             ),
             bridge::instance::ContentUpdateStatus::AlreadyUpToDate => Some(
                 Button::new(("update", element_id)).icon(Icon::default().path("icons/check.svg"))
-                    .tooltip("Up-to-date as of last check")
+                    .tooltip(ts!("content_update_up_to_date"))
             ),
-            bridge::instance::ContentUpdateStatus::Modrinth => {
+            status @ (bridge::instance::ContentUpdateStatus::Modrinth | bridge::instance::ContentUpdateStatus::CurseForge) => {
                 let loading = self.updating.lock().contains(&element_id);
+                let source = status.update_source().map(|source| source.name()).unwrap_or_default();
                 Some(
                     Button::new(("update", element_id)).success().loading(loading).icon(Icon::default().path("icons/download.svg"))
-                        .tooltip("Download update from Modrinth").on_click({
+                        .tooltip(SharedString::from(rust_i18n::t!("content_update_download", source = source).into_owned())).on_click({
                             let backend_handle = self.backend_handle.clone();
                             let updating = self.updating.clone();
                             cx.listener(move |this, _, window, cx| {
@@ -180,6 +184,19 @@ impl ContentListDelegate {
             },
         };
 
+        let missing_dependencies: Vec<&str> = if summary.enabled {
+            summary.content_summary.dependencies.iter()
+                .map(|dependency| dependency.as_ref())
+                .filter(|dependency| !self.is_dependency_installed(dependency))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let dependencies_warning = (!missing_dependencies.is_empty()).then(|| {
+            Button::new(("dependencies", element_id)).warning().icon(Icon::default().path("icons/triangle-alert.svg"))
+                .tooltip(SharedString::from(rust_i18n::t!("content_missing_dependencies", dependencies = missing_dependencies.join(", ")).into_owned()))
+        });
+
         let backend_handle = self.backend_handle.clone();
 
         let toggle_control = Switch::new(("toggle", element_id))
@@ -249,8 +266,11 @@ impl ContentListDelegate {
             .border_1()
             .when(selected, |content| content.border_color(cx.theme().selection).bg(cx.theme().selection.alpha(0.2)));
 
-        if let Some(update_button) = update_button {
-            item_content = item_content.child(h_flex().absolute().right_4().gap_2().child(update_button).child(delete_button))
+        if update_button.is_some() || dependencies_warning.is_some() {
+            item_content = item_content.child(h_flex().absolute().right_4().gap_2()
+                .children(dependencies_warning)
+                .children(update_button)
+                .child(delete_button))
         } else {
             item_content = item_content.child(delete_button.absolute().right_4())
         }
@@ -406,6 +426,7 @@ impl ContentListDelegate {
             png_icon: None,
             update_status: Arc::new(AtomicContentUpdateStatus::new(bridge::instance::ContentUpdateStatus::Unknown)),
             extra: ContentType::Fabric,
+            dependencies: Arc::from([]),
         });
 
         for modification in new_content.iter() {
@@ -461,6 +482,10 @@ impl ContentListDelegate {
         }
         drop(updating);
 
+        self.installed_ids = mods.iter()
+            .filter(|summary| summary.enabled)
+            .filter_map(|summary| summary.content_summary.id.clone())
+            .collect();
         self.content = mods.clone();
         self.children = children;
         self.searched = None;
@@ -511,6 +536,14 @@ impl ContentListDelegate {
         self.searched = Some(searched);
     }
 
+    fn is_dependency_installed(&self, id: &str) -> bool {
+        if self.installed_ids.contains(id) {
+            return true;
+        }
+        // Fabric API bundles its modules as nested jars, which aren't listed separately
+        (id == "fabric" || id.starts_with("fabric-")) && self.installed_ids.contains("fabric-api")
+    }
+
     fn is_selected(&self, element_id: u64) -> bool {
         self.selected.contains(&element_id) || self.selected_range.contains(&element_id)
     }
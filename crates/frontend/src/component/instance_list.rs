@@ -1,21 +1,72 @@
-use bridge::handle::BackendHandle;
+use std::{sync::Arc, time::SystemTime};
+
+use bridge::{handle::BackendHandle, instance::{InstanceID, InstanceStatus}, message::MessageToBackend};
 use gpui::{prelude::*, *};
 use gpui_component::{
+    Icon,
     Sizable,
     button::{Button, ButtonVariants},
+    checkbox::Checkbox,
     h_flex,
     table::{Column, ColumnSort, TableDelegate, TableState},
 };
 
+use rustc_hash::FxHashSet;
+use schema::instance::{InstanceColorTag, InstanceIcon};
+
 use crate::{
     entity::{
         instance::{InstanceAddedEvent, InstanceEntry, InstanceModifiedEvent, InstanceRemovedEvent}, DataEntities
-    }, pages::instance::instance_page::InstanceSubpageType, root, ui
+    }, interface_config::InterfaceConfig, pages::instance::instance_page::InstanceSubpageType, root, ts, ui
 };
 
+/// Icons from `assets/icons` that can be picked for an instance
+pub const BUNDLED_INSTANCE_ICONS: &[&str] = &[
+    "box", "anvil", "swords", "diamond", "tree-pine", "mountain", "compass", "map", "house", "carrot", "cat", "feather",
+    "heart", "star", "zap", "wand-sparkles", "globe", "server", "bug", "scroll",
+];
+
+pub fn tag_color(tag: InstanceColorTag) -> Hsla {
+    match tag {
+        InstanceColorTag::Red => rgb(0xef4444),
+        InstanceColorTag::Orange => rgb(0xf97316),
+        InstanceColorTag::Yellow => rgb(0xeab308),
+        InstanceColorTag::Green => rgb(0x22c55e),
+        InstanceColorTag::Blue => rgb(0x3b82f6),
+        InstanceColorTag::Purple => rgb(0xa855f7),
+        InstanceColorTag::Pink => rgb(0xec4899),
+        InstanceColorTag::Gray => rgb(0x9ca3af),
+    }.into()
+}
+
+/// The instance's icon, tinted with its tag when it's one of the bundled icons
+pub fn render_instance_icon(icon: Option<&InstanceIcon>, color_tag: Option<InstanceColorTag>, size: Pixels) -> AnyElement {
+    let bundled = match icon {
+        Some(InstanceIcon::Image(path)) => {
+            return img(path.clone()).size(size).rounded_sm().object_fit(ObjectFit::Cover).into_any_element();
+        },
+        Some(InstanceIcon::Bundled(name)) => name.as_str(),
+        None => BUNDLED_INSTANCE_ICONS[0],
+    };
+    let icon = Icon::default().path(format!("icons/{bundled}.svg")).size(size);
+    match color_tag {
+        Some(tag) => icon.text_color(tag_color(tag)).into_any_element(),
+        None => icon.into_any_element(),
+    }
+}
+
+/// Untagged instances go last
+fn tag_order(item: &InstanceEntry) -> (bool, Option<InstanceColorTag>) {
+    (item.configuration.color_tag.is_none(), item.configuration.color_tag)
+}
+
 pub struct InstanceList {
     columns: Vec<Column>,
     items: Vec<InstanceEntry>,
+    selected: FxHashSet<InstanceID>,
+    last_clicked: Option<InstanceID>,
+    /// Keeps instances with the same tag next to each other
+    group_by_tag: bool,
     backend_handle: BackendHandle,
     _instance_added_subscription: Subscription,
     _instance_removed_subscription: Subscription,
@@ -28,52 +79,130 @@ impl InstanceList {
         let items = instances.read(cx).entries.values().map(|i| i.read(cx).clone()).collect();
         cx.new(|cx| {
             let _instance_added_subscription = cx.subscribe::<_, InstanceAddedEvent>(&instances, |table: &mut TableState<InstanceList>, _, event, cx| {
-                table.delegate_mut().items.insert(0, event.instance.clone());
+                let delegate = table.delegate_mut();
+                delegate.items.insert(0, event.instance.clone());
+                delegate.group_items();
                 cx.notify();
             });
             let _instance_removed_subscription = cx.subscribe::<_, InstanceRemovedEvent>(&instances, |table, _, event, cx| {
-                table.delegate_mut().items.retain(|instance| {
+                let delegate = table.delegate_mut();
+                delegate.items.retain(|instance| {
                     instance.id != event.id
                 });
+                delegate.selected.remove(&event.id);
                 cx.notify();
             });
             let _instance_modified_subscription = cx.subscribe::<_, InstanceModifiedEvent>(&instances, |table, _, event, cx| {
-                if let Some(entry) = table.delegate_mut().items.iter_mut().find(|entry| entry.id == event.instance.id) {
+                let delegate = table.delegate_mut();
+                if let Some(entry) = delegate.items.iter_mut().find(|entry| entry.id == event.instance.id) {
                     *entry = event.instance.clone();
+                    delegate.group_items();
                     cx.notify();
                 }
             });
-            let instance_list = Self {
+            let mut instance_list = Self {
                 columns: vec![
+                    Column::new("select", "")
+                        .width(40.)
+                        .fixed_left()
+                        .movable(false)
+                        .resizable(false),
+                    Column::new("icon", "")
+                        .width(40.)
+                        .fixed_left()
+                        .movable(false)
+                        .resizable(false),
                     Column::new("controls", "")
-                        .width(150.)
+                        .width(225.)
                         .fixed_left()
                         .movable(false)
                         .resizable(false),
-                    Column::new("name", "Name")
+                    Column::new("name", ts!("instances_column_name"))
                         .width(150.)
                         .fixed_left()
                         .sortable()
                         .resizable(true),
-                    Column::new("version", "Version")
+                    Column::new("version", ts!("instances_column_version"))
                         .width(150.)
                         .fixed_left()
                         .sortable()
                         .resizable(true),
-                    Column::new("loader", "Loader")
+                    Column::new("loader", ts!("instances_column_loader"))
                         .width(150.)
                         .fixed_left()
                         .resizable(true),
+                    Column::new("tag", ts!("instances_column_tag"))
+                        .width(100.)
+                        .sortable()
+                        .resizable(true),
+                    Column::new("last_played", ts!("instances_column_last_played"))
+                        .width(130.)
+                        .sortable()
+                        .resizable(true),
+                    Column::new("playtime", ts!("instances_column_playtime"))
+                        .width(100.)
+                        .sortable()
+                        .resizable(true),
+                    Column::new("status", ts!("instances_column_status"))
+                        .width(200.)
+                        .resizable(true),
                 ],
                 items,
+                selected: FxHashSet::default(),
+                last_clicked: None,
+                group_by_tag: InterfaceConfig::get(cx).group_instances_by_tag,
                 backend_handle: data.backend_handle.clone(),
                 _instance_added_subscription,
                 _instance_removed_subscription,
                 _instance_modified_subscription,
             };
+            instance_list.group_items();
             TableState::new(instance_list, window, cx)
         })
     }
+
+    pub fn set_group_by_tag(&mut self, group_by_tag: bool) {
+        self.group_by_tag = group_by_tag;
+        self.group_items();
+    }
+
+    fn group_items(&mut self) {
+        if self.group_by_tag {
+            self.items.sort_by_key(tag_order);
+        }
+    }
+
+    pub fn selected(&self) -> &FxHashSet<InstanceID> {
+        &self.selected
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selected.clear();
+        self.last_clicked = None;
+    }
+
+    fn toggle_selected(&mut self, id: InstanceID, select: bool, extend_range: bool) {
+        if extend_range
+            && let Some(last_clicked) = self.last_clicked
+            && let Some(from) = self.items.iter().position(|item| item.id == last_clicked)
+            && let Some(to) = self.items.iter().position(|item| item.id == id)
+        {
+            let (start, end) = if from <= to { (from, to) } else { (to, from) };
+            for item in &self.items[start..=end] {
+                if select {
+                    self.selected.insert(item.id);
+                } else {
+                    self.selected.remove(&item.id);
+                }
+            }
+        } else if select {
+            self.selected.insert(id);
+            self.backend_handle.send(MessageToBackend::PrewarmInstance { id });
+        } else {
+            self.selected.remove(&id);
+        }
+        self.last_clicked = Some(id);
+    }
 }
 
 impl TableDelegate for InstanceList {
@@ -106,15 +235,55 @@ impl TableDelegate for InstanceList {
                     ColumnSort::Descending => lexical_sort::natural_lexical_cmp(&a.configuration.minecraft_version, &b.configuration.minecraft_version).reverse(),
                     _ => lexical_sort::natural_lexical_cmp(&a.configuration.minecraft_version, &b.configuration.minecraft_version),
                 }),
+                "tag" => self.items.sort_by(|a, b| match sort {
+                    ColumnSort::Descending => tag_order(a).cmp(&tag_order(b)).reverse(),
+                    _ => tag_order(a).cmp(&tag_order(b)),
+                }),
+                "last_played" => self.items.sort_by(|a, b| match sort {
+                    ColumnSort::Descending => a.configuration.last_played.cmp(&b.configuration.last_played).reverse(),
+                    _ => a.configuration.last_played.cmp(&b.configuration.last_played),
+                }),
+                "playtime" => self.items.sort_by(|a, b| match sort {
+                    ColumnSort::Descending => a.total_playtime.cmp(&b.total_playtime).reverse(),
+                    _ => a.total_playtime.cmp(&b.total_playtime),
+                }),
                 _ => {},
             }
+            // Sorting by another column sorts within each group
+            if col.key.as_ref() != "tag" {
+                self.group_items();
+            }
         }
     }
 
-    fn render_td(&mut self, row_ix: usize, col_ix: usize, _window: &mut Window, _cx: &mut Context<TableState<Self>>) -> impl IntoElement {
+    fn render_td(&mut self, row_ix: usize, col_ix: usize, _window: &mut Window, cx: &mut Context<TableState<Self>>) -> impl IntoElement {
         let item = &self.items[row_ix];
         if let Some(col) = self.columns.get(col_ix) {
             match col.key.as_ref() {
+                "select" => {
+                    let id = item.id;
+                    Checkbox::new("select")
+                        .checked(self.selected.contains(&id))
+                        .on_click(cx.listener(move |table, checked: &bool, window, cx| {
+                            let extend_range = window.modifiers().shift;
+                            table.delegate_mut().toggle_selected(id, *checked, extend_range);
+                            cx.notify();
+                        }))
+                        .into_any_element()
+                },
+                "icon" => h_flex()
+                    .size_full()
+                    .justify_center()
+                    .child(render_instance_icon(item.configuration.icon.as_ref(), item.configuration.color_tag, px(20.)))
+                    .into_any_element(),
+                "tag" => match item.configuration.color_tag {
+                    Some(tag) => h_flex()
+                        .gap_2()
+                        .child(div().size_3().rounded_full().bg(tag_color(tag)))
+                        .child(ts!(tag.translation_key()))
+                        .into_any_element(),
+                    None => "".into_any_element(),
+                },
                 "name" => item.name.clone().into_any_element(),
                 "version" => item.configuration.minecraft_version.as_str().into_any_element(),
                 "controls" => {
@@ -123,27 +292,68 @@ impl TableDelegate for InstanceList {
                         .size_full()
                         .gap_2()
                         .border_r_4()
-                        .child(Button::new("start").w(relative(0.5)).small().success().label("Start").on_click({
-                            let name = item.name.clone();
+                        .child(if item.status == InstanceStatus::Running {
+                            Button::new("kill").w(relative(0.33)).small().danger().label(ts!("instances_kill")).on_click({
+                                let id = item.id;
+                                let backend_handle = backend_handle.clone();
+                                move |_, _, _| {
+                                    backend_handle.send(MessageToBackend::KillInstance { id });
+                                }
+                            })
+                        } else {
+                            Button::new("start").w(relative(0.33)).small().success().label(ts!("instances_start")).on_click({
+                                let name = item.name.clone();
+                                let id = item.id;
+                                let backend_handle = backend_handle.clone();
+                                move |_, window, cx| {
+                                    root::start_instance(id, name.clone(), None, &backend_handle, window, cx);
+                                }
+                            })
+                        })
+                        .child(Button::new("view").w(relative(0.33)).small().info().label(ts!("instances_view")).on_click({
                             let id = item.id;
                             move |_, window, cx| {
-                                root::start_instance(id, name.clone(), None, &backend_handle, window, cx);
+                                root::switch_page(ui::PageType::InstancePage(id, InstanceSubpageType::Quickplay),
+                                    &[ui::PageType::Instances], window, cx);
                             }
                         }))
-                        .child(Button::new("view").w(relative(0.5)).small().info().label("View").on_click({
+                        .child(Button::new("rename").w(relative(0.34)).small().label(ts!("rename_instance_rename")).on_click({
                             let id = item.id;
+                            let name = item.name.clone();
+                            let instance_names: Arc<[SharedString]> = self.items.iter().map(|item| item.name.clone()).collect();
+                            let backend_handle = self.backend_handle.clone();
                             move |_, window, cx| {
-                                root::switch_page(ui::PageType::InstancePage(id, InstanceSubpageType::Quickplay),
-                                    &[ui::PageType::Instances], window, cx);
+                                crate::modals::rename_instance::open_rename_instance(id, name.clone(), instance_names.clone(),
+                                    backend_handle.clone(), window, cx);
                             }
                         }))
                         .into_any_element()
                 },
                 "loader" => item.configuration.loader.name().into_any_element(),
-                _ => "Unknown".into_any_element(),
+                "last_played" => match item.configuration.last_played {
+                    Some(last_played) => crate::format::relative_time(last_played).into_any_element(),
+                    None => ts!("instances_never_played").into_any_element(),
+                },
+                "playtime" => if item.total_playtime > 0 {
+                    crate::format::duration(item.total_playtime).into_any_element()
+                } else {
+                    "".into_any_element()
+                },
+                "status" => match item.status {
+                    InstanceStatus::NotRunning => ts!("instances_status_stopped").into_any_element(),
+                    InstanceStatus::Launching => ts!("instances_status_launching").into_any_element(),
+                    InstanceStatus::Crashed => ts!("instances_status_crashed").into_any_element(),
+                    InstanceStatus::Running => if let Some(process) = item.process {
+                        let uptime = SystemTime::now().duration_since(process.started_at).unwrap_or_default();
+                        rust_i18n::t!("instances_status_running_process", pid = process.pid, uptime = crate::format::duration(uptime.as_secs())).into_owned().into_any_element()
+                    } else {
+                        ts!("instances_status_running").into_any_element()
+                    },
+                },
+                _ => ts!("instances_status_unknown").into_any_element(),
             }
         } else {
-            "Unknown".into_any_element()
+            ts!("instances_status_unknown").into_any_element()
         }
     }
 }
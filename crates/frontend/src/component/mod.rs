@@ -8,3 +8,4 @@ pub mod page_path;
 pub mod progress_bar;
 pub mod readonly_text_field;
 pub mod search_helper;
+pub mod version_entries;
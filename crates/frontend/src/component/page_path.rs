@@ -23,7 +23,12 @@ impl PagePath {
         for i in 0..pages.len() {
             let title = match pages[i] {
                 PageType::Instances => "Instances".into(),
+                PageType::Worlds => "Worlds".into(),
+                PageType::Accounts => "Accounts".into(),
                 PageType::Syncing => "Syncing".into(),
+                PageType::Storage => "Storage".into(),
+                PageType::Debug => "Debug".into(),
+                PageType::Mods => "Mods".into(),
                 PageType::Modrinth { installing_for, .. } => {
                     if installing_for.is_some() {
                         "Add from Modrinth".into()
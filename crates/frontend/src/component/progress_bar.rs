@@ -1,5 +1,6 @@
+use bridge::modal_action::ProgressTracker;
 use gpui::{
-    App, Hsla, IntoElement, ParentElement, RenderOnce, Styled, Window, div, prelude::FluentBuilder, px, relative,
+    App, Hsla, IntoElement, ParentElement, RenderOnce, SharedString, Styled, Window, div, prelude::FluentBuilder, px, relative,
 };
 use gpui_component::ActiveTheme;
 
@@ -89,3 +90,13 @@ impl RenderOnce for ProgressBar {
         )
     }
 }
+
+/// Speed and time remaining of a tracker counting bytes, e.g. "2.5 MiB/s, 0m 40s left"
+pub fn transfer_details(tracker: &ProgressTracker) -> Option<SharedString> {
+    let speed = crate::format::bytes(tracker.bytes_per_second()? as u64);
+    let details = match tracker.remaining() {
+        Some(remaining) => rust_i18n::t!("progress_time_left", speed = speed, time = crate::format::duration(remaining.as_secs())),
+        None => rust_i18n::t!("progress_transfer_rate", speed = speed),
+    };
+    Some(SharedString::new(details))
+}
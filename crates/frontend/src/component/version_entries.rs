@@ -0,0 +1,222 @@
+use chrono::{DateTime, Utc};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    ActiveTheme as _, IndexPath, h_flex,
+    select::{SelectDelegate, SelectItem, SelectState},
+};
+use schema::version_manifest::{MinecraftVersionManifest, MinecraftVersionType};
+
+use crate::connectivity::Connectivity;
+
+/// Which versions besides releases are shown
+#[derive(Debug, Default, Clone, Copy)]
+pub struct VersionFilter {
+    pub snapshots: bool,
+    pub old_beta: bool,
+    pub old_alpha: bool,
+}
+
+impl VersionFilter {
+    fn allows(self, version_type: MinecraftVersionType) -> bool {
+        match version_type {
+            MinecraftVersionType::Release => true,
+            MinecraftVersionType::Snapshot => self.snapshots,
+            MinecraftVersionType::OldBeta => self.old_beta,
+            MinecraftVersionType::OldAlpha => self.old_alpha,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VersionEntry {
+    pub id: SharedString,
+    pub version_type: MinecraftVersionType,
+    pub release_time: DateTime<Utc>,
+    /// False while offline if the version was never downloaded
+    pub available: bool,
+}
+
+impl SelectItem for VersionEntry {
+    type Value = SharedString;
+
+    fn title(&self) -> SharedString {
+        self.id.clone()
+    }
+
+    fn render(&self, _window: &mut Window, cx: &mut App) -> impl IntoElement {
+        let (badge, color) = match self.version_type {
+            MinecraftVersionType::Release => ("Release", cx.theme().success),
+            MinecraftVersionType::Snapshot => ("Snapshot", cx.theme().warning),
+            MinecraftVersionType::OldBeta => ("Beta", cx.theme().info),
+            MinecraftVersionType::OldAlpha => ("Alpha", cx.theme().danger),
+        };
+        let date = if self.available {
+            crate::format::date(self.release_time.timestamp_millis())
+        } else {
+            "Not downloaded".to_string()
+        };
+        h_flex()
+            .w_full()
+            .gap_2()
+            .when(!self.available, |this| this.opacity(0.5))
+            .child(self.id.clone())
+            .child(div().px_1().rounded_sm().text_xs().text_color(color).border_1().border_color(color).child(badge))
+            .child(div().ml_auto().text_xs().text_color(cx.theme().muted_foreground).child(date))
+    }
+
+    fn value(&self) -> &Self::Value {
+        &self.id
+    }
+}
+
+#[derive(Debug, Clone)]
+struct VersionGroup {
+    title: SharedString,
+    entries: Vec<VersionEntry>,
+}
+
+/// Minecraft versions grouped by major version, newest first
+#[derive(Default)]
+pub struct VersionEntries {
+    groups: Vec<VersionGroup>,
+    matched_groups: Vec<VersionGroup>,
+}
+
+impl VersionEntries {
+    pub fn new(manifest: &MinecraftVersionManifest, filter: VersionFilter, cx: &App) -> Self {
+        let groups = group_versions(manifest.versions.iter()
+            .filter(|version| filter.allows(version.r#type))
+            .map(|version| VersionEntry {
+                id: version.id.as_str().into(),
+                version_type: version.r#type,
+                release_time: version.release_time,
+                available: Connectivity::is_version_available(cx, &version.id),
+            }));
+        Self {
+            matched_groups: groups.clone(),
+            groups,
+        }
+    }
+
+    pub fn contains(&self, id: &SharedString) -> bool {
+        self.groups.iter().any(|group| group.entries.iter().any(|entry| &entry.id == id))
+    }
+
+    pub fn first(&self) -> Option<SharedString> {
+        self.groups.first().and_then(|group| group.entries.first()).map(|entry| entry.id.clone())
+    }
+}
+
+/// "1.21.4-rc1" and "1.21" are both part of 1.21, "26.1-snapshot-2" is part of 26.1
+fn major_version(id: &str) -> Option<&str> {
+    let end = id.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(id.len());
+    let version = &id[..end];
+    let mut parts = version.split('.').filter(|part| !part.is_empty());
+    let major = parts.next()?;
+    let minor = parts.next().map_or(0, |minor| minor.len() + 1);
+    Some(&version[..major.len() + minor])
+}
+
+/// Weekly snapshots like 24w14a have no version in their name, so they join the release listed above them, which is
+/// the one they led up to
+fn group_versions(entries: impl Iterator<Item = VersionEntry>) -> Vec<VersionGroup> {
+    let mut groups: Vec<VersionGroup> = Vec::new();
+    for entry in entries {
+        let title: SharedString = match entry.version_type {
+            MinecraftVersionType::OldBeta => "Beta".into(),
+            MinecraftVersionType::OldAlpha => "Alpha".into(),
+            MinecraftVersionType::Release | MinecraftVersionType::Snapshot => match major_version(&entry.id) {
+                Some(major) if major.contains('.') => major.to_string().into(),
+                _ => groups.last().map(|group| group.title.clone()).unwrap_or_else(|| "Upcoming".into()),
+            },
+        };
+        match groups.last_mut() {
+            Some(group) if group.title == title => group.entries.push(entry),
+            _ => groups.push(VersionGroup { title, entries: vec![entry] }),
+        }
+    }
+    groups
+}
+
+impl SelectDelegate for VersionEntries {
+    type Item = VersionEntry;
+
+    fn sections_count(&self, _cx: &App) -> usize {
+        self.matched_groups.len()
+    }
+
+    fn section(&self, section: usize) -> Option<AnyElement> {
+        let group = self.matched_groups.get(section)?;
+        Some(div().px_2().py_1().text_xs().font_semibold().child(group.title.clone()).into_any_element())
+    }
+
+    fn items_count(&self, section: usize) -> usize {
+        self.matched_groups.get(section).map_or(0, |group| group.entries.len())
+    }
+
+    fn item(&self, ix: IndexPath) -> Option<&Self::Item> {
+        self.matched_groups.get(ix.section)?.entries.get(ix.row)
+    }
+
+    fn position<V>(&self, value: &V) -> Option<IndexPath>
+    where
+        Self::Item: SelectItem<Value = V>,
+        V: PartialEq,
+    {
+        for (section, group) in self.matched_groups.iter().enumerate() {
+            for (row, entry) in group.entries.iter().enumerate() {
+                if entry.value() == value {
+                    return Some(IndexPath::default().section(section).row(row));
+                }
+            }
+        }
+
+        None
+    }
+
+    fn perform_search(&mut self, query: &str, _window: &mut Window, _: &mut Context<SelectState<Self>>) -> Task<()> {
+        let lower_query = query.to_lowercase();
+
+        self.matched_groups = self.groups.iter()
+            .filter_map(|group| {
+                let entries: Vec<VersionEntry> = group.entries.iter()
+                    .filter(|entry| entry.id.to_lowercase().starts_with(&lower_query))
+                    .cloned()
+                    .collect();
+                (!entries.is_empty()).then(|| VersionGroup { title: group.title.clone(), entries })
+            })
+            .collect();
+
+        Task::ready(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &'static str, version_type: MinecraftVersionType) -> VersionEntry {
+        VersionEntry {
+            id: id.into(),
+            version_type,
+            release_time: DateTime::default(),
+            available: true,
+        }
+    }
+
+    #[test]
+    fn snapshots_join_the_release_they_led_up_to() {
+        let groups = group_versions([
+            entry("25w14a", MinecraftVersionType::Snapshot),
+            entry("1.21.5", MinecraftVersionType::Release),
+            entry("1.21.5-rc1", MinecraftVersionType::Snapshot),
+            entry("25w10a", MinecraftVersionType::Snapshot),
+            entry("1.20.6", MinecraftVersionType::Release),
+            entry("b1.7.3", MinecraftVersionType::OldBeta),
+        ].into_iter());
+
+        let titles: Vec<(&str, usize)> = groups.iter().map(|group| (group.title.as_ref(), group.entries.len())).collect();
+        assert_eq!(titles, [("Upcoming", 1), ("1.21", 3), ("1.20", 1), ("Beta", 1)]);
+        assert_eq!(major_version("26.1-snapshot-2"), Some("26.1"));
+    }
+}
@@ -0,0 +1,34 @@
+use gpui::App;
+use rustc_hash::FxHashSet;
+use ustr::Ustr;
+
+/// Whether the backend is offline, kept up to date by `MessageToFrontend::OfflineStateChanged`
+#[derive(Default)]
+pub struct Connectivity {
+    offline: bool,
+    cached_versions: FxHashSet<Ustr>,
+}
+
+impl gpui::Global for Connectivity {}
+
+impl Connectivity {
+    pub fn set(cx: &mut App, offline: bool, cached_versions: &[Ustr]) {
+        cx.set_global(Self {
+            offline,
+            cached_versions: cached_versions.iter().copied().collect(),
+        });
+        cx.refresh_windows();
+    }
+
+    pub fn is_offline(cx: &App) -> bool {
+        cx.try_global::<Self>().is_some_and(|connectivity| connectivity.offline)
+    }
+
+    /// Whether the Minecraft version can be used right now, only versions downloaded before can be used offline
+    pub fn is_version_available(cx: &App, version: &str) -> bool {
+        match cx.try_global::<Self>() {
+            Some(connectivity) if connectivity.offline => connectivity.cached_versions.contains(&Ustr::from(version)),
+            _ => true,
+        }
+    }
+}
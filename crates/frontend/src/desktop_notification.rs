@@ -0,0 +1,63 @@
+use std::process::{Command, Stdio};
+
+/// Shows a notification through the operating system, using `notify-send` on Linux, `osascript` on macOS and a
+/// PowerShell toast on Windows. On other systems, or without the tool, only the notification inside the launcher
+/// is shown
+pub fn show(title: &str, body: &str) {
+    let Some(mut command) = notification_command(title, body) else {
+        return;
+    };
+    command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+
+    std::thread::spawn(move || {
+        if let Err(error) = command.status() {
+            tracing::warn!("Unable to show desktop notification: {error}");
+        }
+    });
+}
+
+#[cfg(target_os = "linux")]
+fn notification_command(title: &str, body: &str) -> Option<Command> {
+    let mut command = Command::new("notify-send");
+    command.arg("--app-name=Pandora").arg(title).arg(body);
+    Some(command)
+}
+
+#[cfg(target_os = "macos")]
+fn notification_command(title: &str, body: &str) -> Option<Command> {
+    // Passed as arguments instead of being put into the script, so they don't need escaping
+    let mut command = Command::new("osascript");
+    command.args(["-e", "on run argv", "-e", "display notification (item 2 of argv) with title (item 1 of argv)", "-e", "end run"]);
+    command.arg(title).arg(body);
+    Some(command)
+}
+
+/// Toasts need the id of an installed app, Pandora isn't installed so it borrows the one of PowerShell
+#[cfg(target_os = "windows")]
+const WINDOWS_TOAST_SCRIPT: &str = r#"
+[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null
+$template = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+$text = $template.GetElementsByTagName('text')
+$text.Item(0).AppendChild($template.CreateTextNode($env:PANDORA_NOTIFICATION_TITLE)) | Out-Null
+$text.Item(1).AppendChild($template.CreateTextNode($env:PANDORA_NOTIFICATION_BODY)) | Out-Null
+$toast = [Windows.UI.Notifications.ToastNotification]::new($template)
+[Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell\v1.0\powershell.exe').Show($toast)
+"#;
+
+#[cfg(target_os = "windows")]
+fn notification_command(title: &str, body: &str) -> Option<Command> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    // Passed through the environment instead of being put into the script, so they don't need escaping
+    let mut command = Command::new("powershell.exe");
+    command.args(["-NoProfile", "-NonInteractive", "-Command", WINDOWS_TOAST_SCRIPT]);
+    command.env("PANDORA_NOTIFICATION_TITLE", title).env("PANDORA_NOTIFICATION_BODY", body);
+    command.creation_flags(CREATE_NO_WINDOW);
+    Some(command)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn notification_command(_title: &str, _body: &str) -> Option<Command> {
+    None
+}
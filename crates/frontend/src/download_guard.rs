@@ -0,0 +1,78 @@
+use std::{cell::RefCell, rc::Rc};
+
+use bridge::{handle::BackendHandle, message::{DownloadConditions, MessageToBackend}};
+use gpui::{App, SharedString, Window};
+use gpui_component::{WindowExt, dialog::DialogButtonProps, v_flex};
+
+/// Below this amount of free space, large downloads will ask for confirmation first
+const LOW_SPACE_THRESHOLD: u64 = 4 * 1024 * 1024 * 1024;
+
+/// Set once the user picks "Download anyway", lasts until the launcher is restarted
+#[derive(Default)]
+struct DownloadAnyway(bool);
+
+impl gpui::Global for DownloadAnyway {}
+
+/// Runs `action` immediately if downloading is fine, otherwise asks the user first when the
+/// connection is metered or the launcher directory is low on space
+pub fn when_download_allowed(
+    backend_handle: &BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+    action: impl FnOnce(&mut Window, &mut App) + 'static,
+) {
+    if cx.try_global::<DownloadAnyway>().is_some_and(|download_anyway| download_anyway.0) {
+        action(window, cx);
+        return;
+    }
+
+    let (send, recv) = tokio::sync::oneshot::channel();
+    backend_handle.send(MessageToBackend::GetDownloadConditions { channel: send });
+
+    window.spawn(cx, async move |cx| {
+        let conditions = recv.await.unwrap_or_default();
+        _ = cx.update(move |window, cx| {
+            let warnings = warnings(conditions);
+            if warnings.is_empty() {
+                action(window, cx);
+            } else {
+                confirm_download(warnings, window, cx, action);
+            }
+        });
+    }).detach();
+}
+
+fn warnings(conditions: DownloadConditions) -> Vec<SharedString> {
+    let mut warnings = Vec::new();
+    if conditions.metered {
+        warnings.push("You are on a metered connection.".into());
+    }
+    if let Some(available_space) = conditions.available_space && available_space < LOW_SPACE_THRESHOLD {
+        warnings.push(format!("Only {} of disk space is available.", crate::format::bytes(available_space)).into());
+    }
+    warnings
+}
+
+fn confirm_download(warnings: Vec<SharedString>, window: &mut Window, cx: &mut App, action: impl FnOnce(&mut Window, &mut App) + 'static) {
+    let action: Rc<RefCell<Option<Box<dyn FnOnce(&mut Window, &mut App)>>>> = Rc::new(RefCell::new(Some(Box::new(action))));
+
+    window.open_dialog(cx, move |dialog, _, _| {
+        let action = Rc::clone(&action);
+        dialog
+            .confirm()
+            .title("Large download")
+            .child(v_flex()
+                .gap_2()
+                .children(warnings.iter().cloned())
+                .child("This may download several gigabytes of data. Continue anyway?"))
+            .button_props(DialogButtonProps::default().ok_text("Download anyway"))
+            .on_ok(move |_, window, cx| {
+                cx.set_global(DownloadAnyway(true));
+                // Deferred so that the confirm dialog is closed before the action opens its own modal
+                if let Some(action) = action.borrow_mut().take() {
+                    window.defer(cx, action);
+                }
+                true
+            })
+    });
+}
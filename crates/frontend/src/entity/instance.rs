@@ -1,7 +1,7 @@
 use std::{path::Path, sync::Arc};
 
 use bridge::{
-    instance::{InstanceID, InstanceContentSummary, InstanceServerSummary, InstanceStatus, InstanceWorldSummary},
+    instance::{InstanceID, InstanceContentSummary, InstanceProcessInfo, InstanceServerSummary, InstanceStatus, InstanceWorldSummary},
     message::AtomicBridgeDataLoadState,
 };
 use gpui::{prelude::*, *};
@@ -20,6 +20,7 @@ impl InstanceEntries {
         name: SharedString,
         dot_minecraft_folder: Arc<Path>,
         configuration: InstanceConfiguration,
+        total_playtime: u64,
         worlds_state: Arc<AtomicBridgeDataLoadState>,
         servers_state: Arc<AtomicBridgeDataLoadState>,
         mods_state: Arc<AtomicBridgeDataLoadState>,
@@ -33,7 +34,9 @@ impl InstanceEntries {
                 title: "".into(),
                 dot_minecraft_folder,
                 configuration,
+                total_playtime,
                 status: InstanceStatus::NotRunning,
+                process: None,
                 worlds_state,
                 worlds: cx.new(|_| [].into()),
                 servers_state,
@@ -73,6 +76,14 @@ impl InstanceEntries {
         None
     }
 
+    pub fn clear(entity: &Entity<Self>, cx: &mut App) {
+        entity.update(cx, |entries, cx| {
+            for (id, _) in entries.entries.drain(..) {
+                cx.emit(InstanceRemovedEvent { id });
+            }
+        });
+    }
+
     pub fn remove(entity: &Entity<Self>, id: InstanceID, cx: &mut App) {
         entity.update(cx, |entries, cx| {
             if let Some(_) = entries.entries.shift_remove(&id) {
@@ -87,7 +98,9 @@ impl InstanceEntries {
         name: SharedString,
         dot_minecraft_folder: Arc<Path>,
         configuration: InstanceConfiguration,
+        total_playtime: u64,
         status: InstanceStatus,
+        process: Option<InstanceProcessInfo>,
         cx: &mut App,
     ) {
         entity.update(cx, |entries, cx| {
@@ -96,7 +109,9 @@ impl InstanceEntries {
                     instance.name = name.clone();
                     instance.dot_minecraft_folder = dot_minecraft_folder.clone();
                     instance.configuration = configuration.clone();
+                    instance.total_playtime = total_playtime;
                     instance.status = status;
+                    instance.process = process;
                     instance.title = instance.create_title().into();
                     cx.notify();
 
@@ -190,7 +205,10 @@ pub struct InstanceEntry {
     pub title: SharedString,
     pub dot_minecraft_folder: Arc<Path>,
     pub configuration: InstanceConfiguration,
+    /// Seconds played in total
+    pub total_playtime: u64,
     pub status: InstanceStatus,
+    pub process: Option<InstanceProcessInfo>,
     pub worlds_state: Arc<AtomicBridgeDataLoadState>,
     pub worlds: Entity<Arc<[InstanceWorldSummary]>>,
     pub servers_state: Arc<AtomicBridgeDataLoadState>,
@@ -92,6 +92,20 @@ impl FrontendMetadata {
         })
     }
 
+    /// Asks a restarted backend for the metadata that was still loading, since the old one won't answer anymore
+    pub fn request_pending_again(entity: &Entity<Self>, cx: &mut App) {
+        entity.update(cx, |this, cx| {
+            for (request, state) in &this.data {
+                if let FrontendMetadataState::Loading = state.read(cx) {
+                    this.backend_handle.send(MessageToBackend::RequestMetadata {
+                        request: request.clone(),
+                        force_reload: false,
+                    });
+                }
+            }
+        });
+    }
+
     pub fn set(
         entity: &Entity<Self>,
         request: MetadataRequest,
@@ -100,7 +114,12 @@ impl FrontendMetadata {
         cx: &mut App,
     ) {
         entity.update(cx, |this, cx| {
-            this.data.get(&request).unwrap().update(cx, |value, cx| {
+            // Background refreshes also send results for metadata nothing has asked for yet
+            let Some(state) = this.data.get(&request) else {
+                this.data.insert(request, cx.new(|_| FrontendMetadataState::Loaded { result, keep_alive }));
+                return;
+            };
+            state.update(cx, |value, cx| {
                 *value = FrontendMetadataState::Loaded { result, keep_alive };
                 cx.notify();
             });
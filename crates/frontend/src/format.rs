@@ -0,0 +1,162 @@
+//! Locale-aware formatting for numbers, sizes, durations and dates shown in the interface
+
+struct LocaleFormat {
+    decimal_separator: char,
+    group_separator: char,
+    byte_units: [&'static str; 5],
+    date: &'static str,
+    date_time: &'static str,
+}
+
+const BINARY_UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+const ENGLISH: LocaleFormat = LocaleFormat {
+    decimal_separator: '.',
+    group_separator: ',',
+    byte_units: BINARY_UNITS,
+    date: "%Y-%m-%d",
+    date_time: "%Y-%m-%d %H:%M",
+};
+
+const GERMAN: LocaleFormat = LocaleFormat {
+    decimal_separator: ',',
+    group_separator: '.',
+    byte_units: BINARY_UNITS,
+    date: "%d.%m.%Y",
+    date_time: "%d.%m.%Y %H:%M",
+};
+
+const FRENCH: LocaleFormat = LocaleFormat {
+    decimal_separator: ',',
+    group_separator: '\u{202F}',
+    byte_units: ["o", "Kio", "Mio", "Gio", "Tio"],
+    date: "%d/%m/%Y",
+    date_time: "%d/%m/%Y %H:%M",
+};
+
+const SPACE_GROUPED: LocaleFormat = LocaleFormat {
+    decimal_separator: ',',
+    group_separator: '\u{A0}',
+    byte_units: BINARY_UNITS,
+    date: "%d.%m.%Y",
+    date_time: "%d.%m.%Y %H:%M",
+};
+
+fn locale_format() -> &'static LocaleFormat {
+    let locale = rust_i18n::locale();
+    let language = locale.split(['-', '_']).next().unwrap_or_default();
+    match language {
+        "de" | "nl" | "es" | "it" | "pt" | "da" | "id" | "tr" => &GERMAN,
+        "fr" => &FRENCH,
+        "ru" | "uk" | "pl" | "cs" | "sv" | "fi" | "nb" | "no" => &SPACE_GROUPED,
+        _ => &ENGLISH,
+    }
+}
+
+/// Formats `value` with `decimals` digits after the decimal separator and grouped thousands
+pub fn number(value: f64, decimals: usize) -> String {
+    number_in(locale_format(), value, decimals)
+}
+
+fn number_in(format: &LocaleFormat, value: f64, decimals: usize) -> String {
+    let formatted = format!("{:.*}", decimals, value.abs());
+    let (integer, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+
+    let mut result = String::with_capacity(formatted.len() + integer.len() / 3 + 1);
+    if value.is_sign_negative() && formatted.bytes().any(|byte| byte.is_ascii_digit() && byte != b'0') {
+        result.push('-');
+    }
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            result.push(format.group_separator);
+        }
+        result.push(digit);
+    }
+    if !fraction.is_empty() {
+        result.push(format.decimal_separator);
+        result.push_str(fraction);
+    }
+    result
+}
+
+pub fn integer(value: u64) -> String {
+    number(value as f64, 0)
+}
+
+/// Formats a byte count with binary units, e.g. "1.5 MiB"
+pub fn bytes(bytes: u64) -> String {
+    bytes_in(locale_format(), bytes)
+}
+
+fn bytes_in(format: &LocaleFormat, bytes: u64) -> String {
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < format.byte_units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    let decimals = if unit == 0 { 0 } else { 1 };
+    format!("{}\u{A0}{}", number_in(format, value, decimals), format.byte_units[unit])
+}
+
+/// Formats a progress fraction between 0 and 1 as a whole percentage
+pub fn percent(fraction: f32) -> String {
+    format!("{}%", number((fraction.clamp(0.0, 1.0) * 100.0).floor() as f64, 0))
+}
+
+/// Formats a duration compactly, e.g. "2h 5m" or "4m 10s"
+pub fn duration(seconds: u64) -> String {
+    let hours = seconds / 3600;
+    let minutes = (seconds / 60) % 60;
+    if hours > 0 {
+        format!("{}h {minutes}m", integer(hours))
+    } else {
+        format!("{minutes}m {}s", seconds % 60)
+    }
+}
+
+/// Formats a unix time in milliseconds as a local date
+pub fn date(millis: i64) -> String {
+    local_time(millis).map(|time| time.format(locale_format().date).to_string()).unwrap_or_default()
+}
+
+/// Formats a unix time in milliseconds as a local date and time
+pub fn date_time(millis: i64) -> String {
+    local_time(millis).map(|time| time.format(locale_format().date_time).to_string()).unwrap_or_default()
+}
+
+fn local_time(millis: i64) -> Option<chrono::DateTime<chrono::Local>> {
+    chrono::DateTime::from_timestamp_millis(millis).map(|time| time.with_timezone(&chrono::Local))
+}
+
+/// Describes how long ago a unix time in milliseconds was, e.g. "5 minutes ago". Falls back to the
+/// date once it's more than a week in the past
+pub fn relative_time(millis: i64) -> String {
+    let elapsed = (chrono::Utc::now().timestamp_millis() - millis).max(0) / 1000;
+    let (key, count) = match elapsed {
+        0..60 => return rust_i18n::t!("time_just_now").into_owned(),
+        60..3600 => ("time_minutes_ago", elapsed / 60),
+        3600..86400 => ("time_hours_ago", elapsed / 3600),
+        86400..604800 => ("time_days_ago", elapsed / 86400),
+        _ => return date(millis),
+    };
+    let key = if count == 1 { format!("{key}_one") } else { key.to_string() };
+    rust_i18n::t!(&key, count = count).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_and_sizes_follow_the_locale() {
+        assert_eq!(number_in(&ENGLISH, 1234567.891, 2), "1,234,567.89");
+        assert_eq!(number_in(&GERMAN, 1234567.891, 2), "1.234.567,89");
+        assert_eq!(number_in(&ENGLISH, -0.001, 1), "0.0");
+        assert_eq!(number_in(&ENGLISH, -1500.0, 0), "-1,500");
+
+        assert_eq!(bytes_in(&ENGLISH, 512), "512\u{A0}B");
+        assert_eq!(bytes_in(&ENGLISH, 1536 * 1024), "1.5\u{A0}MiB");
+        assert_eq!(bytes_in(&FRENCH, 1536 * 1024), "1,5\u{A0}Mio");
+    }
+}
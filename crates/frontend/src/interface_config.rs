@@ -1,11 +1,18 @@
-use std::{io::Write, path::Path, sync::Arc, time::Duration};
+use std::{io::Write, path::Path, sync::{atomic::{AtomicBool, Ordering}, Arc}, time::Duration};
 
 use gpui::{App, SharedString, Task};
 use rand::RngCore;
 use schema::{modrinth::ModrinthProjectType, instance::{InstanceMemoryConfiguration, InstanceJvmFlagsConfiguration, InstanceJvmBinaryConfiguration}};
 use serde::{Deserialize, Serialize};
 
-use crate::ui::SerializedPageType;
+use crate::{appearance::AppearanceMode, ui::SerializedPageType};
+
+/// Set when another launcher holds the launcher directory, nothing is written to it then
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn set_read_only() {
+    READ_ONLY.store(true, Ordering::Relaxed);
+}
 
 struct InterfaceConfigHolder {
     config: InterfaceConfig,
@@ -20,6 +27,14 @@ pub struct InterfaceConfig {
     #[serde(default, deserialize_with = "schema::try_deserialize")]
     pub active_theme: SharedString,
     #[serde(default, deserialize_with = "schema::try_deserialize")]
+    pub appearance_mode: AppearanceMode,
+    /// Hex color that replaces the primary color of the theme
+    #[serde(default, deserialize_with = "schema::try_deserialize")]
+    pub accent_color: Option<SharedString>,
+    /// Locale of the launcher's interface, see `language::LANGUAGES`
+    #[serde(default, deserialize_with = "schema::try_deserialize")]
+    pub language: SharedString,
+    #[serde(default, deserialize_with = "schema::try_deserialize")]
     pub main_window_bounds: WindowBounds,
     #[serde(default, deserialize_with = "schema::try_deserialize")]
     pub sidebar_width: f32,
@@ -35,8 +50,15 @@ pub struct InterfaceConfig {
     pub modrinth_install_normally: bool,
     #[serde(default, deserialize_with = "schema::try_deserialize")]
     pub modrinth_page_project_type: ModrinthProjectType,
-    #[serde(default, deserialize_with = "schema::try_deserialize")]
+    /// Replaced by `launch_window_behavior`, only read to carry the setting over
+    #[serde(default, skip_serializing, deserialize_with = "schema::try_deserialize")]
     pub hide_main_window_on_launch: bool,
+    #[serde(default, deserialize_with = "schema::try_deserialize")]
+    pub launch_window_behavior: LaunchWindowBehavior,
+    #[serde(default, deserialize_with = "schema::try_deserialize")]
+    pub launch_preflight: bool,
+    #[serde(default, deserialize_with = "schema::try_deserialize")]
+    pub group_instances_by_tag: bool,
     #[serde(default)]
     pub global_override_enabled: bool,
     #[serde(default)]
@@ -47,6 +69,19 @@ pub struct InterfaceConfig {
     pub global_jvm_binary: Option<InstanceJvmBinaryConfiguration>,
 }
 
+/// What happens to the main window when a game starts. A minimized or hidden window comes back when the game exits,
+/// so crash dialogs are still shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchWindowBehavior {
+    #[default]
+    KeepOpen,
+    Minimize,
+    Hide,
+    /// Exits the launcher, the game keeps running on its own
+    Quit,
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum WindowBounds {
@@ -74,8 +109,12 @@ pub enum WindowBounds {
 
 impl InterfaceConfig {
     pub fn init(cx: &mut App, path: Arc<Path>) {
+        let mut config: Self = try_read_json(&path);
+        if std::mem::take(&mut config.hide_main_window_on_launch) {
+            config.launch_window_behavior = LaunchWindowBehavior::Hide;
+        }
         cx.set_global(InterfaceConfigHolder {
-            config: try_read_json(&path),
+            config,
             write_task: None,
             path,
         });
@@ -124,7 +163,12 @@ pub(crate) fn try_read_json<T: std::fmt::Debug + Default + for <'de> Deserialize
     serde_json::from_slice(&data).unwrap_or_default()
 }
 
+/// Every file the frontend saves goes through here, so nothing is written while the launcher directory is read-only
 pub(crate) fn write_safe(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    if READ_ONLY.load(Ordering::Relaxed) {
+        return Err(std::io::Error::new(std::io::ErrorKind::ReadOnlyFilesystem, "the launcher directory is open read-only"));
+    }
+
     if let Some(parent) = path.parent() {
         let _ = std::fs::create_dir_all(parent);
     }
@@ -0,0 +1,26 @@
+use gpui::App;
+
+use crate::interface_config::InterfaceConfig;
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Locales with a translation, along with the name of the language in that language
+pub const LANGUAGES: &[(&str, &str)] = &[
+    ("en", "English"),
+    ("de", "Deutsch"),
+];
+
+/// The locale picked in the settings, or the default one when it isn't available anymore
+pub fn current_locale(cx: &App) -> &'static str {
+    let language = &InterfaceConfig::get(cx).language;
+    LANGUAGES.iter()
+        .map(|(locale, _)| *locale)
+        .find(|locale| *locale == &**language)
+        .unwrap_or(DEFAULT_LOCALE)
+}
+
+/// Switches the locale of the launcher, including progress titles sent from the backend, and redraws every window
+pub fn apply(cx: &mut App) {
+    rust_i18n::set_locale(current_locale(cx));
+    cx.refresh_windows();
+}
@@ -0,0 +1,98 @@
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use bridge::{handle::BackendHandle, message::MessageToBackend};
+use gpui::{App, AppContext, SharedString, Window};
+use gpui_component::{WindowExt, dialog::DialogButtonProps, input::{Input, InputState}, notification::NotificationType, v_flex};
+
+use crate::ts;
+
+/// Set once the correct PIN has been entered, lasts until the launcher is restarted or locked again
+#[derive(Default)]
+struct Unlocked(bool);
+
+impl gpui::Global for Unlocked {}
+
+/// Forgets that the PIN was entered, so the next gated action asks for it again. The backend refuses gated
+/// messages until then as well
+pub fn lock(backend_handle: &BackendHandle, cx: &mut App) {
+    cx.set_global(Unlocked(false));
+    backend_handle.send(MessageToBackend::LockLauncher);
+}
+
+/// A restarted backend has forgotten that the PIN was entered, so the frontend has to ask for it again too
+pub fn forget_unlock(cx: &mut App) {
+    cx.set_global(Unlocked(false));
+}
+
+/// Runs `action` immediately if there is no launcher PIN or it was already entered this session,
+/// otherwise asks for the PIN first
+pub fn when_unlocked(
+    backend_handle: &BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+    action: impl FnOnce(&mut Window, &mut App) + 'static,
+) {
+    if cx.try_global::<Unlocked>().is_some_and(|unlocked| unlocked.0) {
+        action(window, cx);
+        return;
+    }
+
+    let (send, recv) = tokio::sync::oneshot::channel();
+    backend_handle.send(MessageToBackend::GetBackendConfiguration { channel: send });
+
+    let backend_handle = backend_handle.clone();
+    window.spawn(cx, async move |cx| {
+        let Ok(config) = recv.await else {
+            return;
+        };
+        _ = cx.update(move |window, cx| {
+            if config.launcher_lock.is_none() {
+                action(window, cx);
+            } else {
+                ask_for_pin(backend_handle, window, cx, action);
+            }
+        });
+    }).detach();
+}
+
+fn ask_for_pin(backend_handle: BackendHandle, window: &mut Window, cx: &mut App, action: impl FnOnce(&mut Window, &mut App) + 'static) {
+    let action: Rc<RefCell<Option<Box<dyn FnOnce(&mut Window, &mut App)>>>> = Rc::new(RefCell::new(Some(Box::new(action))));
+
+    let pin_input = cx.new(|cx| InputState::new(window, cx).masked(true).placeholder(ts!("pin_placeholder")));
+    pin_input.update(cx, |input, cx| input.focus(window, cx));
+
+    window.open_dialog(cx, move |dialog, _, _| {
+        let action = Rc::clone(&action);
+        let backend_handle = backend_handle.clone();
+        let pin_input = pin_input.clone();
+        dialog
+            .confirm()
+            .title(ts!("launcher_lock_title"))
+            .child(v_flex()
+                .gap_2()
+                .child(ts!("launcher_lock_description"))
+                .child(Input::new(&pin_input)))
+            .button_props(DialogButtonProps::default().ok_text(ts!("launcher_lock_unlock")))
+            .on_ok(move |_, window, cx| {
+                let pin: Arc<str> = pin_input.read(cx).value().as_str().into();
+                let (send, recv) = tokio::sync::oneshot::channel();
+                backend_handle.send(MessageToBackend::CheckLauncherPin { pin, channel: send });
+
+                let action = Rc::clone(&action);
+                window.spawn(cx, async move |cx| {
+                    let unlocked = recv.await.unwrap_or(false);
+                    _ = cx.update(move |window, cx| {
+                        if !unlocked {
+                            window.push_notification((NotificationType::Error, ts!("pin_incorrect")), cx);
+                            return;
+                        }
+                        cx.set_global(Unlocked(true));
+                        if let Some(action) = action.borrow_mut().take() {
+                            action(window, cx);
+                        }
+                    });
+                }).detach();
+                true
+            })
+    });
+}
@@ -4,34 +4,45 @@ use std::{
     path::{Path, PathBuf}, sync::{Arc, atomic::AtomicBool}
 };
 
-use bridge::
-    handle::{BackendHandle, FrontendReceiver}
-;
+use bridge::{
+    handle::{BackendHandle, FrontendReceiver}, message::MessageToBackend,
+};
 use gpui::*;
 use gpui_component::{
     notification::{Notification, NotificationType}, Root, StyledExt, WindowExt
 };
 use indexmap::IndexMap;
 use parking_lot::RwLock;
+use schema::backend_config::BackendConfig;
 
 use crate::{
-    entity::{
+    active_operations::ActiveOperations, entity::{
         DataEntities, PanicMessages, account::AccountEntries, instance::InstanceEntries, metadata::FrontendMetadata
     }, interface_config::InterfaceConfig, processor::Processor, root::{LauncherRoot, LauncherRootGlobal}
 };
 
+pub mod active_operations;
+pub mod appearance;
 pub mod component;
+pub mod connectivity;
+pub mod desktop_notification;
+pub mod download_guard;
 pub mod entity;
+pub mod format;
 pub mod game_output;
+pub mod language;
 pub mod modals;
 pub mod pages;
 pub mod interface_config;
+pub mod launcher_lock;
+pub mod notification_history;
 pub mod png_render_cache;
 pub mod processor;
 pub mod root;
+pub mod screenshots;
 pub mod ui;
 
-rust_i18n::i18n!("locales");
+rust_i18n::i18n!("locales", fallback = "en");
 
 macro_rules! ts {
     ($($all:tt)*) => {
@@ -83,20 +94,39 @@ pub const MAIN_FONT: &'static str = "Inter 24pt";
 
 actions!([Quit, CloseWindow]);
 
+/// The proxy from the backend's configuration, so images and other requests the interface makes go through it as
+/// well. The interface only reads it on startup, changing it applies to these after a restart
+fn configured_proxy(launcher_dir: &Path) -> Option<gpui::http_client::Url> {
+    let bytes = std::fs::read(launcher_dir.join("config.json")).ok()?;
+    let config: BackendConfig = serde_json::from_slice(&bytes).ok()?;
+    let mut url = gpui::http_client::Url::parse(config.proxy.url.as_deref()?).ok()?;
+    if let Some(username) = &config.proxy.username {
+        _ = url.set_username(username);
+        _ = url.set_password(config.proxy.password.as_deref());
+    }
+    Some(url)
+}
+
 pub fn start(
     launcher_dir: PathBuf,
     panic_message: Arc<RwLock<Option<String>>>,
     deadlock_message: Arc<RwLock<Option<String>>>,
     backend_handle: BackendHandle,
     mut recv: FrontendReceiver,
+    read_only: bool,
 ) {
+    if read_only {
+        interface_config::set_read_only();
+    }
+
     let user_agent = if let Some(version) = option_env!("PANDORA_RELEASE_VERSION") {
         format!("PandoraLauncher/{version} (https://github.com/Moulberry/PandoraLauncher)")
     } else {
         "PandoraLauncher/dev (https://github.com/Moulberry/PandoraLauncher)".to_string()
     };
 
-    let http_client = Arc::new(reqwest_client::ReqwestClient::user_agent(&user_agent).unwrap());
+    let proxy = configured_proxy(&launcher_dir);
+    let http_client = Arc::new(reqwest_client::ReqwestClient::proxy_and_user_agent(proxy, &user_agent).unwrap());
 
     Application::new().with_http_client(http_client).with_assets(Assets).run(move |cx: &mut App| {
         let _ = cx.text_system().add_fonts(vec![
@@ -106,31 +136,33 @@ pub fn start(
 
         gpui_component::init(cx);
         InterfaceConfig::init(cx, launcher_dir.join("interface.json").into());
+        notification_history::NotificationHistory::init(cx, launcher_dir.join("notifications.json").into());
 
-        gpui_component::Theme::change(gpui_component::ThemeMode::Dark, None, cx);
+        appearance::apply(cx);
+        language::apply(cx);
 
         let theme_folder = launcher_dir.join("themes");
 
         _ = gpui_component::ThemeRegistry::watch_dir(theme_folder.clone(), cx, move |cx| {
-            let theme_name = InterfaceConfig::get(cx).active_theme.clone();
-            if theme_name.is_empty() {
-                return;
-            }
-
-            let Some(theme) = gpui_component::ThemeRegistry::global(cx).themes().get(&SharedString::new(theme_name.trim_ascii())).cloned() else {
-                return;
-            };
-
-            gpui_component::Theme::global_mut(cx).apply_config(&theme);
+            appearance::apply(cx);
         });
 
         let theme = gpui_component::Theme::global_mut(cx);
         theme.font_family = SharedString::new_static(MAIN_FONT);
         theme.scrollbar_show = gpui_component::scroll::ScrollbarShow::Always;
 
-        cx.on_app_quit(|cx| {
-            InterfaceConfig::force_save(cx);
-            async {}
+        cx.on_app_quit({
+            let backend_handle = backend_handle.clone();
+            move |cx| {
+                InterfaceConfig::force_save(cx);
+                notification_history::NotificationHistory::force_save(cx);
+
+                let (send, recv) = tokio::sync::oneshot::channel();
+                backend_handle.send(MessageToBackend::LauncherExiting { channel: send });
+                async move {
+                    _ = recv.await;
+                }
+            }
         }).detach();
 
         let main_window_hidden = Arc::new(AtomicBool::new(false));
@@ -147,6 +179,7 @@ pub fn start(
         cx.bind_keys([
             KeyBinding::new("secondary-q", Quit, None),
             KeyBinding::new("secondary-w", CloseWindow, None),
+            KeyBinding::new("secondary-r", pages::instance::instance_page::RelaunchInstance, Some("InstancePage")),
         ]);
 
         cx.on_action(|_: &Quit, cx| {
@@ -263,9 +296,37 @@ pub fn open_main_window(data: &DataEntities, cx: &mut App) -> AnyWindowHandle {
                     }
                 }).detach();
 
+                cx.observe_window_appearance(window, |_, _, cx| {
+                    if InterfaceConfig::get(cx).appearance_mode == appearance::AppearanceMode::System {
+                        appearance::apply(cx);
+                    }
+                }).detach();
+
                 LauncherRoot::new(&data, window, cx)
             });
 
+            window.on_window_should_close(cx, |window, cx| {
+                let in_progress = ActiveOperations::in_progress(cx);
+                if in_progress.is_empty() {
+                    return true;
+                }
+
+                let titles: Vec<String> = in_progress.iter().map(|operation| operation.title().to_string()).collect();
+                let message = SharedString::new(format!("The following operations are still in progress and will be cancelled: {}", titles.join(", ")));
+                window.open_dialog(cx, move |dialog, _, _| {
+                    dialog
+                        .confirm()
+                        .title("Quit Pandora?")
+                        .child(message.clone())
+                        .on_ok(|_, window, cx| {
+                            ActiveOperations::cancel_all(cx);
+                            window.remove_window();
+                            true
+                        })
+                });
+                false
+            });
+
             cx.set_global(LauncherRootGlobal {
                 root: launcher_root.clone(),
             });
@@ -297,18 +358,18 @@ pub(crate) fn is_single_component_path(path: &str) -> bool {
 }
 
 #[inline]
-pub(crate) fn labelled(label: &'static str, element: impl IntoElement) -> Div {
-    gpui_component::v_flex().gap_0p5().child(div().text_sm().font_medium().child(label)).child(element)
+pub(crate) fn labelled(label: impl Into<SharedString>, element: impl IntoElement) -> Div {
+    gpui_component::v_flex().gap_0p5().child(div().text_sm().font_medium().child(label.into())).child(element)
 }
 
 pub(crate) fn open_folder(path: &Path, window: &mut Window, cx: &mut App) {
     if path.is_dir() {
         if let Err(err) = open::that_detached(path) {
-            let notification: Notification = (NotificationType::Error, SharedString::from(format!("Unable to open folder: {err}"))).into();
+            let notification: Notification = (NotificationType::Error, SharedString::from(rust_i18n::t!("open_folder_error", error = err).into_owned())).into();
             window.push_notification(notification.autohide(false), cx);
         }
     } else {
-        let notification: Notification = (NotificationType::Error, SharedString::from("Unable to open folder: not a directory")).into();
+        let notification: Notification = (NotificationType::Error, ts!("open_folder_not_a_directory")).into();
         window.push_notification(notification.autohide(false), cx);
     }
 }
@@ -0,0 +1,52 @@
+use bridge::{handle::BackendHandle, message::MessageToBackend};
+use gpui::{prelude::*, *};
+use gpui_component::{button::{Button, ButtonVariants}, input::{Input, InputState}, v_flex, Disableable, WindowExt};
+use uuid::Uuid;
+
+use crate::ts;
+
+pub fn open_add_offline_account(backend_handle: BackendHandle, window: &mut Window, cx: &mut App) {
+    crate::launcher_lock::when_unlocked(&backend_handle.clone(), window, cx, move |window, cx| {
+        open_add_offline_account_unlocked(backend_handle, window, cx);
+    });
+}
+
+fn open_add_offline_account_unlocked(backend_handle: BackendHandle, window: &mut Window, cx: &mut App) {
+    let name_input = cx.new(|cx| {
+        InputState::new(window, cx)
+    });
+    let uuid_input = cx.new(|cx| {
+        InputState::new(window, cx).placeholder(ts!("offline_account_uuid_placeholder"))
+    });
+    window.open_dialog(cx, move |dialog, _, cx| {
+        let username = name_input.read(cx).value();
+        let valid_name = username.len() >= 1 && username.len() <= 16 &&
+            username.as_bytes().iter().all(|c| *c > 32 && *c < 127);
+        let uuid = uuid_input.read(cx).value();
+        let valid_uuid = uuid.is_empty() || Uuid::try_parse(&uuid).is_ok();
+
+        let valid = valid_name && valid_uuid;
+
+        let backend_handle = backend_handle.clone();
+        let mut add_button = Button::new("add").label(ts!("offline_account_add")).disabled(!valid).on_click(move |_, window, cx| {
+            window.close_all_dialogs(cx);
+
+            backend_handle.send(MessageToBackend::AddOfflineAccount {
+                name: username.clone().into(),
+                uuid: Uuid::try_parse(&uuid).ok(),
+            });
+        });
+
+        if valid {
+            add_button = add_button.success();
+        }
+
+        dialog.title(ts!("accounts_add_offline_account"))
+            .child(v_flex()
+                .gap_2()
+                .child(crate::labelled(ts!("offline_account_name"), Input::new(&name_input)))
+                .child(crate::labelled(ts!("offline_account_uuid"), Input::new(&uuid_input)))
+                .child(add_button)
+            )
+    });
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use bridge::{handle::BackendHandle, instance::InstanceID, message::MessageToBackend, meta::MetadataRequest};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, input::{Input, InputState}, select::{Select, SelectState}, v_flex, Disableable, WindowExt
+};
+use schema::version_manifest::{MinecraftVersionManifest, MinecraftVersionType};
+
+use crate::{entity::metadata::{FrontendMetadata, FrontendMetadataResult}, pages::instances_page::VersionList, ts};
+
+pub fn open_clone_to_version(
+    instance: InstanceID,
+    instance_name: SharedString,
+    instance_names: Arc<[SharedString]>,
+    metadata: Entity<FrontendMetadata>,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let title = SharedString::new(rust_i18n::t!("clone_to_version_title", name = instance_name));
+
+    let name_input_state = cx.new(|cx| InputState::new(window, cx).default_value(format!("{instance_name} (copy)")));
+    let version_select_state = cx.new(|cx| SelectState::new(VersionList::default(), None, window, cx).searchable(true));
+
+    let versions = FrontendMetadata::request(&metadata, MetadataRequest::MinecraftVersionManifest, cx);
+
+    let reload_versions = {
+        let versions = versions.clone();
+        let version_select_state = version_select_state.clone();
+        move |window: &mut Window, cx: &mut App| {
+            let result: FrontendMetadataResult<MinecraftVersionManifest> = versions.read(cx).result();
+            let FrontendMetadataResult::Loaded(manifest) = result else {
+                return;
+            };
+
+            let releases: Vec<SharedString> = manifest.versions.iter()
+                .filter(|version| matches!(version.r#type, MinecraftVersionType::Release))
+                .map(|version| SharedString::from(version.id.as_str()))
+                .collect();
+            let latest = SharedString::from(manifest.latest.release.as_str());
+
+            version_select_state.update(cx, |select_state, cx| {
+                select_state.set_items(VersionList {
+                    versions: releases.clone(),
+                    matched_versions: releases,
+                }, window, cx);
+                select_state.set_selected_value(&latest, window, cx);
+            });
+        }
+    };
+    reload_versions(window, cx);
+
+    let _versions_subscription = {
+        let window_handle = window.window_handle();
+        cx.observe(&versions, move |_, cx| {
+            let _ = window_handle.update(cx, |_, window, cx| reload_versions(window, cx));
+        })
+    };
+
+    window.open_dialog(cx, move |dialog, _, cx| {
+        let _ = &_versions_subscription;
+
+        let name = name_input_state.read(cx).value();
+        let name_valid = !name.is_empty()
+            && crate::is_valid_instance_name(name.as_str())
+            && !instance_names.contains(&name);
+        let version = version_select_state.read(cx).selected_value().cloned();
+
+        let content = v_flex()
+            .gap_2()
+            .child(ts!("clone_to_version_description"))
+            .child(Input::new(&name_input_state))
+            .child(Select::new(&version_select_state).w_full().placeholder(ts!("clone_to_version_loading_versions")))
+            .child(Button::new("clone").label(ts!("clone_to_version_clone")).success().disabled(!name_valid || version.is_none()).on_click({
+                let backend_handle = backend_handle.clone();
+                move |_, window, cx| {
+                    let Some(version) = &version else {
+                        return;
+                    };
+                    window.close_all_dialogs(cx);
+                    let backend_handle = backend_handle.clone();
+                    let clone_name: Arc<str> = name.as_str().into();
+                    let minecraft_version = ustr::Ustr::from(version.as_str());
+                    crate::modals::generic::show_retryable_modal(window, cx, rust_i18n::t!("clone_to_version_cloning", name = name).into_owned().into(),
+                        ts!("clone_to_version_error"), move |modal_action| {
+                            backend_handle.send(MessageToBackend::CloneInstanceToVersion {
+                                id: instance,
+                                name: clone_name.clone(),
+                                minecraft_version,
+                                modal_action,
+                            });
+                        });
+                }
+            }));
+
+        dialog
+            .title(title.clone())
+            .child(content)
+    });
+}
@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use bridge::{
+    handle::BackendHandle, instance::{ConfigFileChange, ConfigFileDiff, DiffLine, DiffLineKind, InstanceID}, message::MessageToBackend
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, dialog::Dialog, h_flex, spinner::Spinner, v_flex, ActiveTheme, Selectable, Sizable
+};
+use schema::instance::{ConfigSnapshot, ConfigSnapshotReason};
+
+use crate::ts;
+
+/// Unchanged lines shown around each change
+const CONTEXT_LINES: usize = 2;
+
+struct ConfigHistory {
+    id: InstanceID,
+    title: SharedString,
+    backend_handle: BackendHandle,
+    snapshots: Option<Arc<[ConfigSnapshot]>>,
+    selected: Option<i64>,
+    diff: Option<Result<Arc<[ConfigFileDiff]>, Arc<str>>>,
+    _snapshots_task: Task<()>,
+    _diff_task: Task<()>,
+}
+
+/// Lists the config snapshots of the instance, showing how the configs changed since the selected one
+pub fn open_config_snapshots(id: InstanceID, name: SharedString, backend_handle: BackendHandle, window: &mut Window, cx: &mut App) {
+    let history = cx.new(|cx| {
+        let mut history = ConfigHistory {
+            id,
+            title: rust_i18n::t!("config_history_title", name = name).into_owned().into(),
+            backend_handle,
+            snapshots: None,
+            selected: None,
+            diff: None,
+            _snapshots_task: Task::ready(()),
+            _diff_task: Task::ready(()),
+        };
+        history.reload(cx);
+        history
+    });
+    window.open_dialog(cx, move |dialog, window, cx| {
+        history.update(cx, |history, cx| history.render(dialog, window, cx))
+    });
+}
+
+fn reason_label(reason: ConfigSnapshotReason) -> SharedString {
+    match reason {
+        ConfigSnapshotReason::Launch => ts!("config_history_reason_launch"),
+        ConfigSnapshotReason::ModpackUpdate => ts!("config_history_reason_modpack_update"),
+        ConfigSnapshotReason::Rollback => ts!("config_history_reason_rollback"),
+        ConfigSnapshotReason::Manual => ts!("config_history_reason_manual"),
+    }
+}
+
+/// The changed lines with some context, None marks skipped unchanged lines
+fn visible_lines(lines: &[DiffLine]) -> Vec<Option<&DiffLine>> {
+    let mut shown = vec![false; lines.len()];
+    for (index, line) in lines.iter().enumerate() {
+        if line.kind != DiffLineKind::Unchanged {
+            let end = (index + CONTEXT_LINES + 1).min(lines.len());
+            shown[index.saturating_sub(CONTEXT_LINES)..end].fill(true);
+        }
+    }
+
+    let mut visible = Vec::new();
+    for (line, shown) in lines.iter().zip(shown) {
+        if shown {
+            visible.push(Some(line));
+        } else if visible.last().is_none_or(Option::is_some) {
+            visible.push(None);
+        }
+    }
+    visible
+}
+
+impl ConfigHistory {
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self._snapshots_task = cx.spawn(async move |history, cx| {
+            let Ok(snapshots) = recv.await else {
+                return;
+            };
+            let _ = history.update(cx, move |history, cx| {
+                let selected = history.selected
+                    .filter(|time| snapshots.iter().any(|snapshot| snapshot.time == *time))
+                    .or_else(|| snapshots.last().map(|snapshot| snapshot.time));
+                history.snapshots = Some(snapshots);
+                history.selected = None;
+                if let Some(time) = selected {
+                    history.select(time, cx);
+                }
+                cx.notify();
+            });
+        });
+
+        self.backend_handle.send(MessageToBackend::GetConfigSnapshots { id: self.id, channel: send });
+    }
+
+    fn select(&mut self, time: i64, cx: &mut Context<Self>) {
+        self.selected = Some(time);
+        self.diff = None;
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self._diff_task = cx.spawn(async move |history, cx| {
+            let Ok(diff) = recv.await else {
+                return;
+            };
+            let _ = history.update(cx, move |history, cx| {
+                history.diff = Some(diff);
+                cx.notify();
+            });
+        });
+
+        self.backend_handle.send(MessageToBackend::GetConfigSnapshotDiff { id: self.id, time, channel: send });
+    }
+
+    /// Reloads once the backend has finished the action
+    fn send_then_reload(&mut self, message: impl FnOnce(tokio::sync::oneshot::Sender<()>) -> MessageToBackend, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self._snapshots_task = cx.spawn(async move |history, cx| {
+            if recv.await.is_err() {
+                return;
+            }
+            let _ = history.update(cx, |history, cx| history.reload(cx));
+        });
+        self.backend_handle.send(message(send));
+    }
+
+    fn render_diff(&self, cx: &App) -> AnyElement {
+        let diff = match &self.diff {
+            None if self.selected.is_none() => return div().child(ts!("config_history_select")).into_any_element(),
+            None => return Spinner::new().into_any_element(),
+            Some(Err(error)) => return div().text_color(cx.theme().danger).child(SharedString::from(error.clone())).into_any_element(),
+            Some(Ok(diff)) => diff,
+        };
+        if diff.is_empty() {
+            return div().child(ts!("config_history_unchanged")).into_any_element();
+        }
+
+        let mut files = v_flex().gap_2().font_family("Roboto Mono").text_xs();
+        for file in diff.iter() {
+            let (label, color) = match file.change {
+                ConfigFileChange::Added => (ts!("config_history_added"), cx.theme().success),
+                ConfigFileChange::Removed => (ts!("config_history_removed"), cx.theme().danger),
+                ConfigFileChange::Modified => (ts!("config_history_modified"), cx.theme().warning),
+            };
+            let mut lines = v_flex();
+            if file.lines.is_empty() {
+                lines = lines.child(div().text_color(cx.theme().muted_foreground).child(ts!("config_history_binary_file")));
+            }
+            for line in visible_lines(&file.lines) {
+                lines = lines.child(match line {
+                    None => div().text_color(cx.theme().muted_foreground).child("…"),
+                    Some(line) => {
+                        let (prefix, color) = match line.kind {
+                            DiffLineKind::Unchanged => (' ', cx.theme().muted_foreground),
+                            DiffLineKind::Added => ('+', cx.theme().success),
+                            DiffLineKind::Removed => ('-', cx.theme().danger),
+                        };
+                        div().text_color(color).child(format!("{prefix} {}", line.text))
+                    },
+                });
+            }
+
+            files = files.child(v_flex()
+                .gap_1()
+                .child(h_flex().gap_2()
+                    .child(div().font_medium().child(SharedString::from(file.path.clone())))
+                    .child(div().text_color(color).child(label)))
+                .child(lines));
+        }
+        files.into_any_element()
+    }
+
+    fn render(&mut self, dialog: Dialog, _window: &mut Window, cx: &mut Context<Self>) -> Dialog {
+        let dialog = dialog.title(self.title.clone()).width(px(820.));
+        let Some(snapshots) = self.snapshots.clone() else {
+            return dialog.child(Spinner::new());
+        };
+
+        let mut list = v_flex().id("snapshots").gap_1().w(px(220.)).max_h(px(420.)).overflow_y_scroll();
+        if snapshots.is_empty() {
+            list = list.child(ts!("config_history_empty"));
+        }
+        for snapshot in snapshots.iter().rev() {
+            let time = snapshot.time;
+            list = list.child(Button::new(("snapshot", time as u64)).small()
+                .selected(self.selected == Some(time))
+                .label(format!("{} • {}", crate::format::relative_time(time), reason_label(snapshot.reason)))
+                .on_click(cx.listener(move |history, _, _, cx| {
+                    history.select(time, cx);
+                    cx.notify();
+                })));
+        }
+
+        let diff = div().id("diff").flex_1().max_h(px(420.)).overflow_y_scroll().p_2()
+            .border_1()
+            .rounded(cx.theme().radius)
+            .border_color(cx.theme().border)
+            .child(self.render_diff(cx));
+
+        let mut actions = h_flex().gap_2().justify_end()
+            .child(Button::new("snapshot-now").label(ts!("config_history_snapshot_now")).on_click(cx.listener(|history, _, _, cx| {
+                let id = history.id;
+                history.send_then_reload(|channel| MessageToBackend::TakeConfigSnapshot { id, channel }, cx);
+            })));
+        if let Some(time) = self.selected {
+            // Rolling back snapshots the current configs first, so it can be undone from this list
+            actions = actions.child(Button::new("roll-back").label(ts!("config_history_roll_back")).danger().on_click(cx.listener(move |history, _, _, cx| {
+                let id = history.id;
+                history.send_then_reload(|channel| MessageToBackend::RestoreConfigSnapshot { id, time, channel }, cx);
+            })));
+        }
+
+        dialog.child(v_flex()
+            .gap_2()
+            .child(h_flex().gap_2().items_start().child(list).child(diff))
+            .child(actions))
+    }
+}
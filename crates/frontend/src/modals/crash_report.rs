@@ -0,0 +1,91 @@
+use std::{path::Path, sync::Arc};
+
+use gpui::{prelude::*, *};
+use gpui_component::{
+    ActiveTheme, WindowExt, button::{Button, ButtonVariants}, h_flex, notification::{Notification, NotificationType}, v_flex
+};
+
+use crate::ts;
+
+pub struct CrashDetails {
+    pub exit_code: Option<i32>,
+    pub crash_report: Option<Arc<Path>>,
+    pub excerpt: Option<Arc<str>>,
+    pub system_info: Arc<str>,
+    pub bundle: Option<Arc<Path>>,
+}
+
+pub fn open_crash_report(
+    name: SharedString,
+    details: CrashDetails,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let CrashDetails { exit_code, crash_report, excerpt, system_info, bundle } = details;
+    let title = SharedString::new(rust_i18n::t!("crash_report_title", name = name));
+    let message = match exit_code {
+        Some(code) => SharedString::new(rust_i18n::t!("crash_report_exit_code", code = code)),
+        None => ts!("crash_report_exited"),
+    };
+    let excerpt = excerpt.map(|excerpt| SharedString::new(excerpt.trim_end()));
+    let system_info = SharedString::new(system_info.trim_end());
+
+    window.open_dialog(cx, move |dialog, _, cx| {
+        let mut content = v_flex().gap_2().child(message.clone());
+
+        if let Some(excerpt) = &excerpt {
+            content = content.child(div()
+                .id("excerpt")
+                .max_h_80()
+                .overflow_y_scroll()
+                .p_2()
+                .border_1()
+                .rounded(cx.theme().radius)
+                .border_color(cx.theme().border)
+                .bg(cx.theme().secondary)
+                .font_family("Roboto Mono")
+                .text_xs()
+                .child(excerpt.clone()));
+        } else {
+            content = content.child(ts!("crash_report_not_found"));
+        }
+
+        content = content.child(div()
+            .text_xs()
+            .font_family("Roboto Mono")
+            .text_color(cx.theme().muted_foreground)
+            .child(system_info.clone()));
+
+        let mut buttons = h_flex().w_full().gap_2();
+        if let Some(crash_report) = &crash_report {
+            buttons = buttons.child(Button::new("open").flex_1().info().label(ts!("crash_report_open")).on_click({
+                let crash_report = crash_report.clone();
+                move |_, window, cx| {
+                    if let Err(err) = open::that_detached(&*crash_report) {
+                        let notification: Notification = (NotificationType::Error, SharedString::from(rust_i18n::t!("crash_report_error_opening", error = err).into_owned())).into();
+                        window.push_notification(notification.autohide(false), cx);
+                    }
+                }
+            }));
+        }
+        if let Some(bundle) = &bundle {
+            buttons = buttons.child(Button::new("open-bundle").flex_1().info().label(ts!("crash_report_open_bundle")).on_click({
+                let bundle = bundle.clone();
+                move |_, window, cx| {
+                    if let Err(err) = open::that_detached(&*bundle) {
+                        let notification: Notification = (NotificationType::Error, SharedString::from(rust_i18n::t!("crash_report_error_opening", error = err).into_owned())).into();
+                        window.push_notification(notification.autohide(false), cx);
+                    }
+                }
+            }));
+        }
+        buttons = buttons.child(Button::new("close").flex_1().label(ts!("crash_report_close")).on_click(|_, window, cx| {
+            window.close_all_dialogs(cx);
+        }));
+
+        dialog
+            .title(title.clone())
+            .width(px(720.))
+            .child(content.child(buttons))
+    });
+}
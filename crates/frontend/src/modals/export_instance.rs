@@ -0,0 +1,86 @@
+use std::{path::Path, sync::{atomic::{AtomicBool, Ordering}, Arc}};
+
+use bridge::{
+    handle::BackendHandle, instance::InstanceID, message::{InstanceExportFolder, MessageToBackend}
+};
+use enumset::EnumSet;
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, checkbox::Checkbox, v_flex, WindowExt
+};
+
+use crate::ts;
+
+/// Asks which folders to include, then where to save the zip
+pub fn open_export_instance(id: InstanceID, name: SharedString, backend_handle: BackendHandle, window: &mut Window, cx: &mut App) {
+    let folders: Vec<InstanceExportFolder> = EnumSet::<InstanceExportFolder>::all().iter().collect();
+    // Worlds can be very large, so they're left out unless asked for
+    let selected: Arc<[AtomicBool]> = folders.iter()
+        .map(|folder| AtomicBool::new(*folder != InstanceExportFolder::Saves))
+        .collect();
+
+    let title = SharedString::new(rust_i18n::t!("export_title", name = name));
+
+    window.open_dialog(cx, move |dialog, _, _| {
+        let mut list = v_flex().gap_1();
+        for (index, folder) in folders.iter().enumerate() {
+            let selected = selected.clone();
+            list = list.child(Checkbox::new(("folder", index))
+                .label(ts!(folder.translation_key()))
+                .checked(selected[index].load(Ordering::Relaxed))
+                .on_click(move |checked, window, _| {
+                    selected[index].store(*checked, Ordering::Relaxed);
+                    window.refresh();
+                }));
+        }
+
+        let content = v_flex()
+            .gap_2()
+            .child(ts!("export_description"))
+            .child(list)
+            .child(Button::new("export").label(ts!("export_export")).success().on_click({
+                let backend_handle = backend_handle.clone();
+                let folders = folders.clone();
+                let selected = selected.clone();
+                let name = name.clone();
+                move |_, window, cx| {
+                    let folders: EnumSet<InstanceExportFolder> = folders.iter().zip(selected.iter())
+                        .filter(|(_, selected)| selected.load(Ordering::Relaxed))
+                        .map(|(folder, _)| *folder)
+                        .collect();
+                    window.close_all_dialogs(cx);
+                    prompt_for_destination(id, name.clone(), folders, backend_handle.clone(), window, cx);
+                }
+            }));
+
+        dialog
+            .title(title.clone())
+            .child(content)
+    });
+}
+
+fn prompt_for_destination(
+    id: InstanceID,
+    name: SharedString,
+    folders: EnumSet<InstanceExportFolder>,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let user_dirs = directories::UserDirs::new();
+    let directory = user_dirs.as_ref()
+        .and_then(directories::UserDirs::download_dir).unwrap_or(Path::new("."));
+
+    let receiver = cx.prompt_for_new_path(directory, Some(&format!("{name}.zip")));
+    window.spawn(cx, async move |cx| {
+        let Ok(Ok(Some(path))) = receiver.await else {
+            return;
+        };
+        _ = cx.update(|window, cx| {
+            crate::modals::generic::show_retryable_modal(window, cx, rust_i18n::t!("export_exporting", name = name).into_owned().into(),
+                ts!("instances_export_error"), move |modal_action| {
+                    backend_handle.send(MessageToBackend::ExportInstance { id, path: path.clone(), folders, modal_action });
+                });
+        });
+    }).detach();
+}
@@ -1,14 +1,17 @@
-use std::sync::Arc;
+use std::{rc::Rc, sync::Arc};
 
 use bridge::modal_action::{ModalAction, ProgressTrackerFinishType};
 use gpui::{prelude::*, *};
 use gpui_component::{
-    button::{Button, ButtonVariants}, dialog::DialogButtonProps, notification::Notification, v_flex, IconName, WindowExt
+    ActiveTheme as _, button::{Button, ButtonVariants}, dialog::DialogButtonProps, h_flex, notification::Notification, v_flex, IconName, WindowExt
 };
 
-use crate::component::{
-    error_alert::ErrorAlert,
-    progress_bar::{ProgressBar, ProgressBarColor},
+use crate::{
+    active_operations::ActiveOperations,
+    component::{
+        error_alert::ErrorAlert,
+        progress_bar::{transfer_details, ProgressBar, ProgressBarColor},
+    },
 };
 
 pub fn show_notification(
@@ -27,6 +30,8 @@ pub fn show_notification_with_note(
     modal_action: ModalAction,
     mut notification: Notification
 ) {
+    ActiveOperations::register(cx, error_title.clone(), modal_action.clone());
+
     let notification = notification
         .autohide(false)
         .content(move |notification, window, cx| {
@@ -76,7 +81,9 @@ pub fn show_notification_with_note(
                 }
 
                 let title = tracker.get_title();
-                progress_entries.push(div().gap_3().child(SharedString::from(title)).child(progress_bar).opacity(opacity));
+                let percent = tracker.get_float().map(crate::format::percent);
+                let details = transfer_details(tracker).map(|details| div().text_xs().text_color(cx.theme().muted_foreground).child(details));
+                progress_entries.push(div().gap_3().child(h_flex().justify_between().child(SharedString::from(title)).children(percent)).child(progress_bar).children(details).opacity(opacity));
             }
             drop(trackers);
 
@@ -95,6 +102,33 @@ pub fn show_notification_with_note(
     window.push_notification(notification, cx);
 }
 
+/// Starts an operation with `start` and shows its progress like [`show_modal`]. When it fails, the task center can
+/// start it again with a new action
+pub fn show_retryable_modal(
+    window: &mut Window,
+    cx: &mut App,
+    title: SharedString,
+    error_title: SharedString,
+    start: impl Fn(ModalAction) + 'static,
+) {
+    show_retryable_modal_rc(window, cx, title, error_title, Rc::new(start));
+}
+
+fn show_retryable_modal_rc(
+    window: &mut Window,
+    cx: &mut App,
+    title: SharedString,
+    error_title: SharedString,
+    start: Rc<dyn Fn(ModalAction)>,
+) {
+    let modal_action = ModalAction::default();
+    start(modal_action.clone());
+    show_modal(window, cx, title.clone(), error_title.clone(), modal_action.clone());
+    ActiveOperations::set_retry(cx, &modal_action, Rc::new(move |window, cx| {
+        show_retryable_modal_rc(window, cx, title.clone(), error_title.clone(), start.clone());
+    }));
+}
+
 pub fn show_modal(
     window: &mut Window,
     cx: &mut App,
@@ -102,6 +136,8 @@ pub fn show_modal(
     error_title: SharedString,
     modal_action: ModalAction,
 ) {
+    ActiveOperations::register(cx, title.clone(), modal_action.clone());
+
     window.open_dialog(cx, move |modal, window, cx| {
         if let Some(error) = &*modal_action.error.read().unwrap() {
             let error_widget = ErrorAlert::new("error", error_title.clone(), error.clone().into());
@@ -171,7 +207,9 @@ pub fn show_modal(
             }
 
             let title = tracker.get_title();
-            progress_entries.push(div().gap_3().child(SharedString::from(title)).child(progress_bar).opacity(opacity));
+            let percent = tracker.get_float().map(crate::format::percent);
+            let details = transfer_details(tracker).map(|details| div().text_xs().text_color(cx.theme().muted_foreground).child(details));
+            progress_entries.push(div().gap_3().child(h_flex().justify_between().child(SharedString::from(title)).children(percent)).child(progress_bar).children(details).opacity(opacity));
         }
         drop(trackers);
 
@@ -0,0 +1,115 @@
+use std::{path::PathBuf, sync::{atomic::{AtomicBool, Ordering}, Arc}};
+
+use bridge::{handle::BackendHandle, message::{MessageToBackend, OfficialLauncherProfiles}, modal_action::ModalAction};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, checkbox::Checkbox, notification::NotificationType, v_flex, Disableable, WindowExt
+};
+
+use crate::ts;
+
+/// Lists the official Minecraft Launcher's profiles to import, asking for its folder if it isn't in the default location
+pub fn open_import_launcher_profiles(backend_handle: BackendHandle, window: &mut Window, cx: &mut App) {
+    request_profiles(None, backend_handle, window, cx);
+}
+
+fn request_profiles(minecraft_dir: Option<PathBuf>, backend_handle: BackendHandle, window: &mut Window, cx: &mut App) {
+    let picked_folder = minecraft_dir.is_some();
+    let (send, recv) = tokio::sync::oneshot::channel();
+    backend_handle.send(MessageToBackend::GetOfficialLauncherProfiles { minecraft_dir, channel: send });
+
+    window.spawn(cx, async move |cx| {
+        let Ok(result) = recv.await else {
+            return;
+        };
+        _ = cx.update(move |window, cx| match result {
+            Ok(profiles) => open_profile_picker(profiles, backend_handle, window, cx),
+            Err(_) if !picked_folder => prompt_for_folder(backend_handle, window, cx),
+            Err(error) => window.push_notification((NotificationType::Error, SharedString::from(error.to_string())), cx),
+        });
+    }).detach();
+}
+
+fn prompt_for_folder(backend_handle: BackendHandle, window: &mut Window, cx: &mut App) {
+    let receiver = cx.prompt_for_paths(PathPromptOptions {
+        files: false,
+        directories: true,
+        multiple: false,
+        prompt: Some(ts!("import_profiles_select_folder"))
+    });
+    window.spawn(cx, async move |cx| {
+        let Ok(Ok(Some(paths))) = receiver.await else {
+            return;
+        };
+        let Some(path) = paths.into_iter().next() else {
+            return;
+        };
+        _ = cx.update(|window, cx| request_profiles(Some(path), backend_handle, window, cx));
+    }).detach();
+}
+
+fn open_profile_picker(profiles: OfficialLauncherProfiles, backend_handle: BackendHandle, window: &mut Window, cx: &mut App) {
+    if profiles.profiles.is_empty() {
+        window.push_notification((NotificationType::Warning, ts!("import_profiles_none")), cx);
+        return;
+    }
+
+    let selected: Arc<[AtomicBool]> = profiles.profiles.iter().map(|_| AtomicBool::new(false)).collect();
+    let reuse_game_dir = Arc::new(AtomicBool::new(false));
+
+    window.open_dialog(cx, move |dialog, _, _| {
+        let mut list = v_flex().id("profiles").gap_1().max_h(px(320.0)).overflow_y_scroll();
+        for (index, profile) in profiles.profiles.iter().enumerate() {
+            let selected = selected.clone();
+            list = list.child(Checkbox::new(("profile", index))
+                .label(format!("{} ({})", profile.name, profile.version))
+                .checked(selected[index].load(Ordering::Relaxed))
+                .on_click(move |checked, window, _| {
+                    selected[index].store(*checked, Ordering::Relaxed);
+                    window.refresh();
+                }));
+        }
+        let any_selected = selected.iter().any(|selected| selected.load(Ordering::Relaxed));
+
+        let content = v_flex()
+            .gap_2()
+            .child(ts!("import_profiles_description"))
+            .child(list)
+            .child(Checkbox::new("reuse_game_dir")
+                .label(ts!("import_profiles_reuse_game_dir"))
+                .checked(reuse_game_dir.load(Ordering::Relaxed))
+                .on_click({
+                    let reuse_game_dir = reuse_game_dir.clone();
+                    move |checked, window, _| {
+                        reuse_game_dir.store(*checked, Ordering::Relaxed);
+                        window.refresh();
+                    }
+                }))
+            .child(Button::new("import").label(ts!("import_profiles_import")).success().disabled(!any_selected).on_click({
+                let backend_handle = backend_handle.clone();
+                let profiles = profiles.clone();
+                let selected = selected.clone();
+                let reuse_game_dir = reuse_game_dir.clone();
+                move |_, window, cx| {
+                    let profile_ids = profiles.profiles.iter().zip(selected.iter())
+                        .filter(|(_, selected)| selected.load(Ordering::Relaxed))
+                        .map(|(profile, _)| profile.id.clone())
+                        .collect();
+                    let modal_action = ModalAction::default();
+                    backend_handle.send(MessageToBackend::ImportOfficialLauncherProfiles {
+                        minecraft_dir: profiles.minecraft_dir.clone(),
+                        profile_ids,
+                        reuse_game_dir: reuse_game_dir.load(Ordering::Relaxed),
+                        modal_action: modal_action.clone(),
+                    });
+                    window.close_all_dialogs(cx);
+                    crate::modals::generic::show_modal(window, cx, ts!("import_profiles_importing"),
+                        ts!("import_profiles_error"), modal_action);
+                }
+            }));
+
+        dialog
+            .title(ts!("import_profiles_title"))
+            .child(content)
+    });
+}
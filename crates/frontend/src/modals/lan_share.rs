@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use bridge::{handle::BackendHandle, instance::LanShare, message::MessageToBackend};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, h_flex, notification::NotificationType, v_flex, ActiveTheme, WindowExt
+};
+
+use crate::ts;
+
+/// Searches the local network for instances shared by other launchers and lets the user import one of them
+pub fn open_import_lan_share(backend_handle: BackendHandle, window: &mut Window, cx: &mut App) {
+    let (send, recv) = tokio::sync::oneshot::channel();
+    backend_handle.send(MessageToBackend::DiscoverLanShares { channel: send });
+    window.push_notification((NotificationType::Info, ts!("lan_share_searching")), cx);
+
+    window.spawn(cx, async move |cx| {
+        let Ok(result) = recv.await else {
+            return;
+        };
+        _ = cx.update(move |window, cx| match result {
+            Ok(shares) => open_share_picker(shares.into(), backend_handle, window, cx),
+            Err(error) => window.push_notification((NotificationType::Error, SharedString::from(error.to_string())), cx),
+        });
+    }).detach();
+}
+
+fn open_share_picker(shares: Arc<[LanShare]>, backend_handle: BackendHandle, window: &mut Window, cx: &mut App) {
+    if shares.is_empty() {
+        window.push_notification((NotificationType::Warning, ts!("lan_share_none_found")), cx);
+        return;
+    }
+
+    window.open_dialog(cx, move |dialog, _, cx| {
+        let mut list = v_flex().id("shares").gap_1().max_h(px(320.0)).overflow_y_scroll();
+        for (index, share) in shares.iter().enumerate() {
+            let backend_handle = backend_handle.clone();
+            let share = share.clone();
+            list = list.child(h_flex()
+                .gap_2()
+                .justify_between()
+                .child(v_flex()
+                    .child(SharedString::new(share.name.clone()))
+                    .child(div().text_xs().text_color(cx.theme().muted_foreground).child(share.address.ip().to_string())))
+                .child(Button::new(("import", index)).label(ts!("lan_share_import")).success().on_click(move |_, window, cx| {
+                    window.close_all_dialogs(cx);
+                    let backend_handle = backend_handle.clone();
+                    let share = share.clone();
+                    crate::modals::generic::show_retryable_modal(window, cx, rust_i18n::t!("lan_share_importing", name = share.name).into_owned().into(),
+                        ts!("lan_share_error_importing"), move |modal_action| {
+                            backend_handle.send(MessageToBackend::ImportLanShare { share: share.clone(), modal_action });
+                        });
+                })));
+        }
+
+        dialog
+            .title(ts!("lan_share_title"))
+            .child(v_flex().gap_2().child(ts!("lan_share_description")).child(list))
+    });
+}
@@ -0,0 +1,144 @@
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use bridge::{handle::BackendHandle, instance::InstanceID, message::{LaunchPreflight, MessageToBackend, PreflightAccount, PreflightJava}};
+use gpui::{prelude::*, *};
+use gpui_component::{dialog::DialogButtonProps, h_flex, v_flex, ActiveTheme, WindowExt};
+
+use crate::{format, ts};
+
+#[derive(Clone, Copy)]
+enum Status {
+    Ok,
+    Warning,
+    Problem,
+}
+
+/// Works out what launching the instance involves and lets the user confirm it before `launch` is run
+pub fn open_launch_preflight(
+    id: InstanceID,
+    name: SharedString,
+    backend_handle: &BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+    launch: impl FnOnce(&mut Window, &mut App) + 'static,
+) {
+    let (send, recv) = tokio::sync::oneshot::channel();
+    backend_handle.send(MessageToBackend::GetLaunchPreflight { id, channel: send });
+
+    window.spawn(cx, async move |cx| {
+        let Ok(result) = recv.await else {
+            return;
+        };
+        _ = cx.update(move |window, cx| open_summary(name, result, window, cx, launch));
+    }).detach();
+}
+
+fn summary_rows(preflight: &LaunchPreflight) -> Vec<(SharedString, SharedString, Status)> {
+    let mut rows = Vec::new();
+
+    rows.push(match &preflight.account {
+        PreflightAccount::Ready { username } => {
+            (ts!("preflight_account"), rust_i18n::t!("preflight_account_ready", username = username).into_owned().into(), Status::Ok)
+        },
+        PreflightAccount::Refresh { username } => {
+            (ts!("preflight_account"), rust_i18n::t!("preflight_account_refresh", username = username).into_owned().into(), Status::Ok)
+        },
+        PreflightAccount::Offline { username } => {
+            (ts!("preflight_account"), rust_i18n::t!("preflight_account_offline", username = username).into_owned().into(), Status::Warning)
+        },
+        PreflightAccount::LoginRequired => (ts!("preflight_account"), ts!("preflight_account_login_required"), Status::Warning),
+    });
+
+    rows.push(match &preflight.java {
+        PreflightJava::External { path } => {
+            (ts!("preflight_java"), rust_i18n::t!("preflight_java_external", path = path.display()).into_owned().into(), Status::Ok)
+        },
+        PreflightJava::Mojang { component, missing_files: 0 } => {
+            (ts!("preflight_java"), rust_i18n::t!("preflight_java_installed", component = component).into_owned().into(), Status::Ok)
+        },
+        PreflightJava::Mojang { component, missing_files } => {
+            (ts!("preflight_java"), rust_i18n::t!("preflight_java_missing_files", component = component,
+                count = format::integer(*missing_files as u64)).into_owned().into(), Status::Warning)
+        },
+        PreflightJava::Unavailable { error } => {
+            (ts!("preflight_java"), rust_i18n::t!("preflight_java_unavailable", error = error).into_owned().into(), Status::Problem)
+        },
+    });
+
+    rows.push(if preflight.files_to_download == 0 {
+        (ts!("preflight_game_files"), rust_i18n::t!("preflight_game_files_cached",
+            count = format::integer(preflight.cached_files as u64)).into_owned().into(), Status::Ok)
+    } else {
+        (ts!("preflight_game_files"), rust_i18n::t!("preflight_game_files_download",
+            cached = format::integer(preflight.cached_files as u64),
+            count = format::integer(preflight.files_to_download as u64),
+            size = format::bytes(preflight.download_size)).into_owned().into(), Status::Warning)
+    });
+
+    rows.push(match preflight.available_space {
+        Some(available) if available < preflight.install_size => {
+            (ts!("preflight_disk_space"), rust_i18n::t!("preflight_disk_space_insufficient", needed = format::bytes(preflight.install_size),
+                available = format::bytes(available)).into_owned().into(), Status::Problem)
+        },
+        Some(available) => {
+            (ts!("preflight_disk_space"), rust_i18n::t!("preflight_disk_space_available", needed = format::bytes(preflight.install_size),
+                available = format::bytes(available)).into_owned().into(), Status::Ok)
+        },
+        None => {
+            (ts!("preflight_disk_space"), rust_i18n::t!("preflight_disk_space_unknown",
+                needed = format::bytes(preflight.install_size)).into_owned().into(), Status::Warning)
+        },
+    });
+
+    if preflight.runs_loader_installer {
+        rows.push((ts!("preflight_loader"), ts!("preflight_loader_installer"), Status::Warning));
+    }
+
+    rows
+}
+
+fn open_summary(
+    name: SharedString,
+    result: Result<LaunchPreflight, Arc<str>>,
+    window: &mut Window,
+    cx: &mut App,
+    launch: impl FnOnce(&mut Window, &mut App) + 'static,
+) {
+    let rows = match &result {
+        Ok(preflight) => summary_rows(preflight),
+        Err(error) => vec![(ts!("preflight_error"), SharedString::new(error.as_ref()), Status::Problem)],
+    };
+    let launch: Rc<RefCell<Option<Box<dyn FnOnce(&mut Window, &mut App)>>>> = Rc::new(RefCell::new(Some(Box::new(launch))));
+
+    window.open_dialog(cx, move |dialog, _, cx| {
+        let launch = Rc::clone(&launch);
+        let content = v_flex()
+            .gap_2()
+            .children(rows.iter().map(|(label, value, status)| {
+                let color = match status {
+                    Status::Ok => cx.theme().foreground,
+                    Status::Warning => cx.theme().warning,
+                    Status::Problem => cx.theme().danger,
+                };
+                h_flex()
+                    .gap_2()
+                    .items_start()
+                    .child(div().w_24().flex_shrink_0().text_sm().font_medium().child(label.clone()))
+                    .child(div().text_sm().text_color(color).child(value.clone()))
+            }));
+
+        dialog
+            .confirm()
+            .title(rust_i18n::t!("preflight_title", name = name).into_owned())
+            .width(px(560.))
+            .child(content)
+            .button_props(DialogButtonProps::default().ok_text(ts!("preflight_launch")))
+            .on_ok(move |_, window, cx| {
+                // Deferred so that this dialog is closed before the launch opens its own modal
+                if let Some(launch) = launch.borrow_mut().take() {
+                    window.defer(cx, launch);
+                }
+                true
+            })
+    });
+}
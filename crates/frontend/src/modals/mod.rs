@@ -1,6 +1,19 @@
+pub mod add_offline_account;
+pub mod clone_to_version;
+pub mod config_snapshots;
+pub mod crash_report;
+pub mod export_instance;
 pub mod generic;
+pub mod import_launcher_profiles;
+pub mod lan_share;
+pub mod launch_preflight;
+pub mod mod_bisection;
+pub mod notification_center;
 pub mod modrinth_install;
 pub mod modrinth_install_auto;
+pub mod playtime_limit;
 pub mod delete_instance;
+pub mod rename_instance;
 pub mod settings;
+pub mod task_center;
 pub mod update_prompt;
@@ -0,0 +1,161 @@
+use bridge::{handle::BackendHandle, instance::InstanceID, message::{LaunchMods, MessageToBackend, ModBisectionState}};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, h_flex, notification::NotificationType, v_flex, ActiveTheme, WindowExt
+};
+
+use crate::ts;
+
+/// Opens the current step of the instance's mod bisection, or offers to start one. `exited` is whether the game
+/// crashed, when opened because a bisection launch has just exited
+pub fn open_mod_bisection(id: InstanceID, name: SharedString, exited: Option<bool>, backend_handle: BackendHandle, window: &mut Window, cx: &mut App) {
+    let (send, recv) = tokio::sync::oneshot::channel();
+    backend_handle.send(MessageToBackend::GetModBisection { id, channel: send });
+
+    window.spawn(cx, async move |cx| {
+        let Ok(state) = recv.await else {
+            return;
+        };
+        _ = cx.update(move |window, cx| match state {
+            Some(state) => open_step(id, name, state, exited, backend_handle, window, cx),
+            None => open_intro(id, name, backend_handle, window, cx),
+        });
+    }).detach();
+}
+
+fn open_intro(id: InstanceID, name: SharedString, backend_handle: BackendHandle, window: &mut Window, cx: &mut App) {
+    window.open_dialog(cx, move |dialog, _, _| {
+        let content = v_flex()
+            .gap_2()
+            .child(ts!("bisection_description"))
+            .child(ts!("bisection_dependencies"))
+            .child(Button::new("start").label(ts!("bisection_start")).success().on_click({
+                let name = name.clone();
+                let backend_handle = backend_handle.clone();
+                move |_, window, cx| {
+                    window.close_all_dialogs(cx);
+                    let (send, recv) = tokio::sync::oneshot::channel();
+                    backend_handle.send(MessageToBackend::StartModBisection { id, channel: send });
+
+                    let name = name.clone();
+                    let backend_handle = backend_handle.clone();
+                    window.spawn(cx, async move |cx| {
+                        let Ok(result) = recv.await else {
+                            return;
+                        };
+                        _ = cx.update(move |window, cx| match result {
+                            Ok(state) => open_step(id, name, state, None, backend_handle, window, cx),
+                            Err(error) => window.push_notification((NotificationType::Error, SharedString::from(error.to_string())), cx),
+                        });
+                    }).detach();
+                }
+            }));
+
+        dialog
+            .title(rust_i18n::t!("bisection_title", name = name).into_owned())
+            .child(content)
+    });
+}
+
+fn report(id: InstanceID, name: SharedString, crashed: bool, backend_handle: BackendHandle, window: &mut Window, cx: &mut App) {
+    window.close_all_dialogs(cx);
+    let (send, recv) = tokio::sync::oneshot::channel();
+    backend_handle.send(MessageToBackend::ReportModBisectionResult { id, crashed, channel: send });
+
+    window.spawn(cx, async move |cx| {
+        let Ok(Some(state)) = recv.await else {
+            return;
+        };
+        _ = cx.update(move |window, cx| open_step(id, name, state, None, backend_handle, window, cx));
+    }).detach();
+}
+
+fn open_step(id: InstanceID, name: SharedString, state: ModBisectionState, exited: Option<bool>, backend_handle: BackendHandle, window: &mut Window, cx: &mut App) {
+    if state.testing.is_empty() {
+        open_result(id, name, state, backend_handle, window, cx);
+        return;
+    }
+
+    let description = SharedString::new(rust_i18n::t!("bisection_step", step = state.step, testing = state.testing.len(),
+        suspects = state.suspects.len(), total = state.total));
+    let outcome = exited.map(|crashed| if crashed {
+        ts!("bisection_crashed")
+    } else {
+        ts!("bisection_exited_normally")
+    });
+
+    window.open_dialog(cx, move |dialog, _, cx| {
+        let mut list = v_flex().id("testing").max_h(px(240.0)).overflow_y_scroll().text_sm();
+        for mod_name in state.testing.iter() {
+            list = list.child(SharedString::new(mod_name.as_ref()));
+        }
+
+        let content = v_flex()
+            .gap_2()
+            .child(description.clone())
+            .children(outcome.clone())
+            .child(div()
+                .p_2()
+                .border_1()
+                .rounded(cx.theme().radius)
+                .border_color(cx.theme().border)
+                .child(list))
+            .child(h_flex().gap_2()
+                .child(Button::new("launch").flex_1().success().label(ts!("bisection_launch")).on_click({
+                    let name = name.clone();
+                    let backend_handle = backend_handle.clone();
+                    move |_, window, cx| {
+                        window.close_all_dialogs(cx);
+                        crate::root::start_instance_with_mods(id, name.clone(), None, LaunchMods::Bisection, &backend_handle, window, cx);
+                    }
+                }))
+                .child(Button::new("crashed").flex_1().danger().label(ts!("bisection_it_crashed")).on_click({
+                    let name = name.clone();
+                    let backend_handle = backend_handle.clone();
+                    move |_, window, cx| {
+                        report(id, name.clone(), true, backend_handle.clone(), window, cx);
+                    }
+                }))
+                .child(Button::new("worked").flex_1().info().label(ts!("bisection_it_worked")).on_click({
+                    let name = name.clone();
+                    let backend_handle = backend_handle.clone();
+                    move |_, window, cx| {
+                        report(id, name.clone(), false, backend_handle.clone(), window, cx);
+                    }
+                }))
+                .child(Button::new("stop").flex_1().label(ts!("bisection_stop")).on_click({
+                    let backend_handle = backend_handle.clone();
+                    move |_, window, cx| {
+                        backend_handle.send(MessageToBackend::StopModBisection { id });
+                        window.close_all_dialogs(cx);
+                    }
+                })));
+
+        dialog
+            .title(rust_i18n::t!("bisection_title", name = name).into_owned())
+            .width(px(640.))
+            .child(content)
+    });
+}
+
+fn open_result(id: InstanceID, name: SharedString, state: ModBisectionState, backend_handle: BackendHandle, window: &mut Window, cx: &mut App) {
+    let message = match &state.culprit {
+        Some(culprit) => SharedString::new(rust_i18n::t!("bisection_culprit", name = culprit)),
+        None => ts!("bisection_no_culprit"),
+    };
+
+    window.open_dialog(cx, move |dialog, _, _| {
+        dialog
+            .title(rust_i18n::t!("bisection_title", name = name).into_owned())
+            .child(v_flex()
+                .gap_2()
+                .child(message.clone())
+                .child(Button::new("done").success().label(ts!("bisection_done")).on_click({
+                    let backend_handle = backend_handle.clone();
+                    move |_, window, cx| {
+                        backend_handle.send(MessageToBackend::StopModBisection { id });
+                        window.close_all_dialogs(cx);
+                    }
+                })))
+    });
+}
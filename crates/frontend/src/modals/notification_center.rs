@@ -0,0 +1,89 @@
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, h_flex, notification::NotificationType, sheet::Sheet, v_flex, ActiveTheme, IconName, Sizable, WindowExt
+};
+
+use crate::{
+    entity::{instance::InstanceEntries, DataEntities},
+    notification_history::{NotificationAction, NotificationEntry, NotificationHistory, NotificationKind},
+    ts,
+};
+
+pub fn build_notification_center_sheet(data: &DataEntities) -> impl Fn(Sheet, &mut Window, &mut App) -> Sheet + 'static {
+    let data = data.clone();
+    move |sheet, window, cx| {
+        let entries = NotificationHistory::entries(cx);
+
+        let content = if entries.is_empty() {
+            v_flex().p_4().child(div().text_sm().text_color(cx.theme().muted_foreground).child(ts!("notifications_empty")))
+        } else {
+            v_flex()
+                .child(h_flex().px_4().py_2().justify_end().child(Button::new("clear-notifications").label(ts!("notifications_clear_all")).small().on_click(|_, _, cx| {
+                    NotificationHistory::clear(cx);
+                })))
+                .children(entries.iter().enumerate().map(|(index, entry)| render_entry(index, entry, &data, cx)))
+        };
+
+        sheet
+            .title(ts!("notifications_title"))
+            .overlay_top(crate::root::sheet_margin_top(window))
+            .p_0()
+            .child(v_flex().border_t_1().border_color(cx.theme().border).child(content))
+    }
+}
+
+fn render_entry(index: usize, entry: &NotificationEntry, data: &DataEntities, cx: &App) -> Div {
+    let color = match entry.kind {
+        NotificationKind::Success => cx.theme().success,
+        NotificationKind::Info => cx.theme().info,
+        NotificationKind::Warning => cx.theme().warning,
+        NotificationKind::Error => cx.theme().danger,
+    };
+
+    let details = v_flex()
+        .flex_1()
+        .min_w_0()
+        .gap_1()
+        .child(div().text_sm().child(SharedString::new(entry.message.clone())))
+        .child(div().text_xs().text_color(cx.theme().muted_foreground).child(crate::format::relative_time(entry.time)));
+
+    let mut row = h_flex()
+        .gap_3()
+        .px_4()
+        .py_2()
+        .border_b_1()
+        .border_color(cx.theme().border)
+        .child(div().size_2().flex_none().rounded_full().bg(color))
+        .child(details);
+
+    if let Some(action) = &entry.action {
+        let action = action.clone();
+        let data = data.clone();
+        row = row.child(Button::new(("action", index)).label(action.label()).small().on_click(move |_, window, cx| {
+            perform_action(&action, &data, window, cx);
+        }));
+    }
+
+    row.child(Button::new(("dismiss", index)).icon(IconName::Close).ghost().small().on_click(move |_, _, cx| {
+        NotificationHistory::remove(cx, index);
+        // Anything that arrived while the sheet was open has been seen by now
+        NotificationHistory::mark_read(cx);
+    }))
+}
+
+fn perform_action(action: &NotificationAction, data: &DataEntities, window: &mut Window, cx: &mut App) {
+    match action {
+        NotificationAction::LaunchInstance { name } => {
+            let name = SharedString::new(name.clone());
+            match InstanceEntries::find_id_by_name(&data.instances, &name, cx) {
+                Some(id) => crate::root::start_instance(id, name, None, &data.backend_handle, window, cx),
+                None => window.push_notification((NotificationType::Error, ts!("notifications_instance_missing")), cx),
+            }
+        },
+        NotificationAction::OpenFile { path } => {
+            if let Err(err) = open::that_detached(&**path) {
+                window.push_notification((NotificationType::Error, SharedString::from(rust_i18n::t!("notifications_error_opening_file", error = err).into_owned())), cx);
+            }
+        },
+    }
+}
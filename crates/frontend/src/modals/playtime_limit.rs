@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use bridge::{handle::BackendHandle, instance::InstanceID, message::MessageToBackend};
+use gpui::{prelude::*, *};
+use gpui_component::{dialog::DialogButtonProps, input::{Input, InputState}, notification::{Notification, NotificationType}, v_flex, WindowExt};
+
+use crate::ts;
+
+pub fn open_playtime_limit_reached(
+    id: InstanceID,
+    name: SharedString,
+    can_override: bool,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    if !can_override {
+        let message = SharedString::from(rust_i18n::t!("playtime_limit_refused", name = name).into_owned());
+        let notification: Notification = (NotificationType::Warning, message).into();
+        window.push_notification(notification.autohide(false), cx);
+        return;
+    }
+
+    let pin_input = cx.new(|cx| InputState::new(window, cx).masked(true).placeholder(ts!("pin_placeholder")));
+    pin_input.update(cx, |input, cx| input.focus(window, cx));
+
+    window.open_dialog(cx, move |dialog, _, _| {
+        let backend_handle = backend_handle.clone();
+        let pin_input = pin_input.clone();
+        let name = name.clone();
+        dialog
+            .confirm()
+            .title(ts!("playtime_limit_title"))
+            .child(v_flex()
+                .gap_2()
+                .child(rust_i18n::t!("playtime_limit_description", name = name).into_owned())
+                .child(Input::new(&pin_input)))
+            .button_props(DialogButtonProps::default().ok_text(ts!("playtime_limit_play_anyway")))
+            .on_ok(move |_, window, cx| {
+                let pin: Arc<str> = pin_input.read(cx).value().as_str().into();
+                let (send, recv) = tokio::sync::oneshot::channel();
+                backend_handle.send(MessageToBackend::OverridePlaytimeLimit { id, pin, channel: send });
+
+                let backend_handle = backend_handle.clone();
+                let name = name.clone();
+                window.spawn(cx, async move |cx| {
+                    let allowed = recv.await.unwrap_or(false);
+                    _ = cx.update(move |window, cx| {
+                        if allowed {
+                            crate::root::start_instance(id, name, None, &backend_handle, window, cx);
+                        } else {
+                            window.push_notification((NotificationType::Error, ts!("pin_incorrect")), cx);
+                        }
+                    });
+                }).detach();
+                true
+            })
+    });
+}
@@ -0,0 +1,65 @@
+use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+
+use bridge::{handle::BackendHandle, instance::InstanceID, message::MessageToBackend};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, input::{Input, InputEvent, InputState}, v_flex, ActiveTheme, Disableable, WindowExt
+};
+
+use crate::ts;
+
+pub fn open_rename_instance(
+    instance: InstanceID,
+    instance_name: SharedString,
+    instance_names: Arc<[SharedString]>,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let name_valid = Arc::new(AtomicBool::new(false));
+
+    let title = SharedString::new(rust_i18n::t!("rename_instance_title", name = instance_name));
+
+    let input_state = cx.new(|cx| InputState::new(window, cx).default_value(instance_name.clone()));
+    input_state.update(cx, |input_state, cx| {
+        input_state.focus(window, cx);
+    });
+
+    let name_valid2 = name_valid.clone();
+    let _input_subscription = cx.subscribe(&input_state, move |state, event: &InputEvent, cx| {
+        if let InputEvent::Change = event {
+            let value = state.read(cx).value();
+            let valid = !value.is_empty()
+                && crate::is_valid_instance_name(value.as_str())
+                && !instance_names.contains(&value);
+            name_valid2.store(valid, Ordering::Relaxed);
+        }
+    });
+
+    window.open_dialog(cx, move |dialog, _, cx| {
+        let _ = &_input_subscription;
+
+        let valid = name_valid.load(Ordering::Relaxed);
+        let changed = input_state.read(cx).value() != instance_name;
+
+        let content = v_flex()
+            .gap_2()
+            .child(Input::new(&input_state).when(changed && !valid, |this| this.border_color(cx.theme().danger)))
+            .child(Button::new("rename").label(ts!("rename_instance_rename")).success().disabled(!valid).on_click({
+                let backend_handle = backend_handle.clone();
+                let input_state = input_state.clone();
+                move |_, window, cx| {
+                    let name = input_state.read(cx).value();
+                    backend_handle.send(MessageToBackend::RenameInstance {
+                        id: instance,
+                        name: name.as_str().into(),
+                    });
+                    window.close_all_dialogs(cx);
+                }
+            }));
+
+        dialog
+            .title(title.clone())
+            .child(content)
+    });
+}
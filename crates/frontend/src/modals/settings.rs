@@ -1,19 +1,38 @@
 use std::{path::Path, sync::Arc};
 
-use bridge::{handle::BackendHandle, message::MessageToBackend};
+use bridge::{handle::BackendHandle, message::MessageToBackend, modal_action::ModalAction};
 use gpui::*;
-use gpui_component::{button::{Button, ButtonVariants}, checkbox::Checkbox, select::{SearchableVec, Select, SelectEvent, SelectState}, sheet::Sheet, spinner::Spinner, tab::{Tab, TabBar, TabVariant}, v_flex, ActiveTheme, IconName, Sizable, ThemeRegistry};
-use schema::backend_config::BackendConfig;
+use gpui_component::{button::{Button, ButtonGroup, ButtonVariants}, checkbox::Checkbox, color_picker::{ColorPicker, ColorPickerEvent, ColorPickerState}, h_flex, input::{Input, InputState}, notification::NotificationType, select::{SearchableVec, Select, SelectEvent, SelectState}, sheet::Sheet, spinner::Spinner, tab::{Tab, TabBar, TabVariant}, v_flex, ActiveTheme, Colorize, Disableable, IconName, Selectable, Sizable, ThemeRegistry, WindowExt};
+use schema::{backend_config::{BackendConfig, DownloadLimits, EndpointOverrides, LaunchLimits, MetadataRefreshInterval, ProxySettings}, pandora_update::{UpdateChannel, UpdateCheckFrequency}};
 
-use crate::{entity::DataEntities, interface_config::InterfaceConfig};
+use crate::{appearance::{self, AppearanceMode}, entity::DataEntities, interface_config::{InterfaceConfig, LaunchWindowBehavior}, language, ts};
 
 struct Settings {
     theme_folder: Arc<Path>,
     theme_select: Entity<SelectState<SearchableVec<SharedString>>>,
+    accent_picker: Entity<ColorPickerState>,
     backend_handle: BackendHandle,
     pending_request: bool,
     backend_config: Option<BackendConfig>,
     get_configuration_task: Option<Task<()>>,
+    user_agent_input: Entity<InputState>,
+    meta_url_input: Entity<InputState>,
+    assets_url_input: Entity<InputState>,
+    libraries_url_input: Entity<InputState>,
+    proxy_url_input: Entity<InputState>,
+    proxy_username_input: Entity<InputState>,
+    proxy_password_input: Entity<InputState>,
+    ca_certificates_input: Entity<InputState>,
+    testing_connection: bool,
+    max_downloads_input: Entity<InputState>,
+    download_speed_input: Entity<InputState>,
+    download_retries_input: Entity<InputState>,
+    network_inputs_loaded: bool,
+    curseforge_api_key_input: Entity<InputState>,
+    launcher_pin_input: Entity<InputState>,
+    playtime_limit_input: Entity<InputState>,
+    max_running_input: Entity<InputState>,
+    memory_budget_input: Entity<InputState>,
 }
 
 pub fn build_settings_sheet(data: &DataEntities, window: &mut Window, cx: &mut App) -> impl Fn(Sheet, &mut Window, &mut App) -> Sheet + 'static {
@@ -35,20 +54,54 @@ pub fn build_settings_sheet(data: &DataEntities, window: &mut Window, cx: &mut A
 
             InterfaceConfig::get_mut(cx).active_theme = theme_name.clone();
 
-            let Some(theme) = gpui_component::ThemeRegistry::global(cx).themes().get(&SharedString::new(theme_name.trim_ascii())).cloned() else {
+            // Picking a theme made for the other mode switches to that mode, otherwise it wouldn't be visible
+            let theme_mode = ThemeRegistry::global(cx).themes().get(&SharedString::new(theme_name.trim_ascii())).map(|theme| theme.mode);
+            if let Some(theme_mode) = theme_mode && theme_mode != appearance::current_mode(cx) {
+                InterfaceConfig::get_mut(cx).appearance_mode = if theme_mode.is_dark() { AppearanceMode::Dark } else { AppearanceMode::Light };
+            }
+
+            appearance::apply(cx);
+        }).detach();
+
+        let accent_picker = cx.new(|cx| {
+            let accent = InterfaceConfig::get(cx).accent_color.as_deref().and_then(appearance::parse_accent);
+            ColorPickerState::new(window, cx).default_value(accent.unwrap_or(cx.theme().primary))
+        });
+
+        cx.subscribe_in(&accent_picker, window, |_, _, event: &ColorPickerEvent, _, cx| {
+            let ColorPickerEvent::Change(Some(color)) = event else {
                 return;
             };
-
-            gpui_component::Theme::global_mut(cx).apply_config(&theme);
+            InterfaceConfig::get_mut(cx).accent_color = Some(color.to_hex().into());
+            appearance::apply(cx);
         }).detach();
 
         let mut settings = Settings {
             theme_folder,
             theme_select,
+            accent_picker,
             backend_handle: data.backend_handle.clone(),
             pending_request: false,
             backend_config: None,
             get_configuration_task: None,
+            user_agent_input: cx.new(|cx| InputState::new(window, cx).placeholder(ts!("settings_default"))),
+            meta_url_input: cx.new(|cx| InputState::new(window, cx).placeholder("https://piston-meta.mojang.com")),
+            assets_url_input: cx.new(|cx| InputState::new(window, cx).placeholder("https://resources.download.minecraft.net")),
+            libraries_url_input: cx.new(|cx| InputState::new(window, cx).placeholder("https://libraries.minecraft.net")),
+            proxy_url_input: cx.new(|cx| InputState::new(window, cx).placeholder("socks5://127.0.0.1:1080")),
+            proxy_username_input: cx.new(|cx| InputState::new(window, cx).placeholder(ts!("settings_not_set"))),
+            proxy_password_input: cx.new(|cx| InputState::new(window, cx).masked(true).placeholder(ts!("settings_not_set"))),
+            ca_certificates_input: cx.new(|cx| InputState::new(window, cx).placeholder(ts!("settings_not_set"))),
+            testing_connection: false,
+            max_downloads_input: cx.new(|cx| InputState::new(window, cx).placeholder("8")),
+            download_speed_input: cx.new(|cx| InputState::new(window, cx).placeholder(ts!("settings_no_limit"))),
+            download_retries_input: cx.new(|cx| InputState::new(window, cx).placeholder("3")),
+            network_inputs_loaded: false,
+            curseforge_api_key_input: cx.new(|cx| InputState::new(window, cx).masked(true).placeholder(ts!("settings_not_set"))),
+            launcher_pin_input: cx.new(|cx| InputState::new(window, cx).masked(true).placeholder(ts!("settings_new_pin"))),
+            playtime_limit_input: cx.new(|cx| InputState::new(window, cx).placeholder(ts!("settings_no_limit"))),
+            max_running_input: cx.new(|cx| InputState::new(window, cx).placeholder(ts!("settings_no_limit"))),
+            memory_budget_input: cx.new(|cx| InputState::new(window, cx).placeholder(ts!("settings_no_limit"))),
         };
 
         settings.update_backend_configuration(cx);
@@ -61,14 +114,14 @@ pub fn build_settings_sheet(data: &DataEntities, window: &mut Window, cx: &mut A
             .prefix(div().w_4())
             .selected_index(0)
             .underline()
-            .child(Tab::new().label("Interface"))
+            .child(Tab::new().label(ts!("settings_tab_interface")))
             // .child(Tab::new().label("Game"))
             .on_click(|index, window, cx| {
                 // todo: switch
             });
 
         sheet
-            .title("Settings")
+            .title(ts!("settings_title"))
             .overlay_top(crate::root::sheet_margin_top(window))
             .p_0()
             .child(v_flex()
@@ -106,10 +159,227 @@ impl Settings {
             channel: send,
         });
     }
+
+    fn load_network_inputs(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(backend_config) = &self.backend_config else {
+            return;
+        };
+        if self.network_inputs_loaded {
+            return;
+        }
+        self.network_inputs_loaded = true;
+
+        let values = [
+            (&self.user_agent_input, &backend_config.custom_user_agent),
+            (&self.meta_url_input, &backend_config.endpoint_overrides.meta),
+            (&self.assets_url_input, &backend_config.endpoint_overrides.assets),
+            (&self.libraries_url_input, &backend_config.endpoint_overrides.libraries),
+            (&self.proxy_url_input, &backend_config.proxy.url),
+            (&self.proxy_username_input, &backend_config.proxy.username),
+            (&self.proxy_password_input, &backend_config.proxy.password),
+            (&self.curseforge_api_key_input, &backend_config.curseforge_api_key),
+        ];
+        for (input, value) in values {
+            let value = value.as_deref().unwrap_or_default().to_string();
+            input.update(cx, |input, cx| input.set_value(value, window, cx));
+        }
+
+        let ca_certificates = backend_config.custom_ca_certificates.as_ref()
+            .map(|path| path.to_string_lossy().into_owned()).unwrap_or_default();
+        self.ca_certificates_input.update(cx, |input, cx| input.set_value(ca_certificates, window, cx));
+
+        let limits = [
+            (&self.max_downloads_input, backend_config.download_limits.max_concurrent),
+            (&self.download_speed_input, backend_config.download_limits.max_kib_per_second),
+            (&self.download_retries_input, backend_config.download_limits.max_retries),
+        ];
+        for (input, value) in limits {
+            let value = value.map(|value| value.to_string()).unwrap_or_default();
+            input.update(cx, |input, cx| input.set_value(value, window, cx));
+        }
+
+        let playtime_limit = backend_config.daily_playtime_limit.map(|minutes| minutes.to_string()).unwrap_or_default();
+        self.playtime_limit_input.update(cx, |input, cx| input.set_value(playtime_limit, window, cx));
+
+        let launch_limits = [
+            (&self.max_running_input, backend_config.launch_limits.max_running),
+            (&self.memory_budget_input, backend_config.launch_limits.memory_budget_percent),
+        ];
+        for (input, value) in launch_limits {
+            let value = value.map(|value| value.to_string()).unwrap_or_default();
+            input.update(cx, |input, cx| input.set_value(value, window, cx));
+        }
+    }
+
+    fn fill_endpoint_inputs(&mut self, endpoints: EndpointOverrides, window: &mut Window, cx: &mut Context<Self>) {
+        let values = [
+            (&self.meta_url_input, endpoints.meta),
+            (&self.assets_url_input, endpoints.assets),
+            (&self.libraries_url_input, endpoints.libraries),
+        ];
+        for (input, value) in values {
+            let value = value.as_deref().unwrap_or_default().to_string();
+            input.update(cx, |input, cx| input.set_value(value, window, cx));
+        }
+    }
+
+    fn read_input(input: &Entity<InputState>, cx: &App) -> Option<Arc<str>> {
+        let value = input.read(cx).value();
+        let value = value.trim();
+        if value.is_empty() {
+            None
+        } else {
+            Some(value.into())
+        }
+    }
+
+    fn read_proxy(&self, cx: &App) -> (ProxySettings, Option<Arc<Path>>) {
+        let proxy = ProxySettings {
+            url: Self::read_input(&self.proxy_url_input, cx),
+            username: Self::read_input(&self.proxy_username_input, cx),
+            password: Self::read_input(&self.proxy_password_input, cx),
+        };
+        let ca_certificates = Self::read_input(&self.ca_certificates_input, cx).map(|path| Path::new(&*path).into());
+        (proxy, ca_certificates)
+    }
+
+    fn save_network_overrides(&mut self, cx: &mut Context<Self>) {
+        let read = |input: &Entity<InputState>| Self::read_input(input, cx);
+
+        let (proxy, ca_certificates) = self.read_proxy(cx);
+        self.backend_handle.send(MessageToBackend::SetNetworkOverrides {
+            user_agent: read(&self.user_agent_input),
+            endpoints: EndpointOverrides {
+                meta: read(&self.meta_url_input),
+                assets: read(&self.assets_url_input),
+                libraries: read(&self.libraries_url_input),
+            },
+            proxy,
+            ca_certificates,
+        });
+        self.backend_handle.send(MessageToBackend::SetCurseForgeApiKey {
+            api_key: read(&self.curseforge_api_key_input),
+        });
+
+        let read_limit = |input: &Entity<InputState>| input.read(cx).value().trim().parse::<u32>().ok().filter(|value| *value > 0);
+        self.backend_handle.send(MessageToBackend::SetDownloadLimits {
+            limits: DownloadLimits {
+                max_concurrent: read_limit(&self.max_downloads_input),
+                max_kib_per_second: read_limit(&self.download_speed_input),
+                // Unlike the limits, zero retries is meaningful
+                max_retries: self.download_retries_input.read(cx).value().trim().parse::<u32>().ok(),
+            },
+        });
+        self.update_backend_configuration(cx);
+    }
+
+    fn test_connection(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.testing_connection {
+            return;
+        }
+        self.testing_connection = true;
+        cx.notify();
+
+        let (proxy, ca_certificates) = self.read_proxy(cx);
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::TestConnection {
+            user_agent: Self::read_input(&self.user_agent_input, cx),
+            proxy,
+            ca_certificates,
+            channel: send,
+        });
+
+        cx.spawn_in(window, async move |settings, cx| {
+            let result = recv.await.unwrap_or_else(|_| Err("The backend didn't respond".into()));
+            _ = settings.update_in(cx, |settings, window, cx| {
+                settings.testing_connection = false;
+                let notification = match result {
+                    Ok(millis) => (NotificationType::Success, SharedString::from(ts!("settings_connection_ok", millis = millis))),
+                    Err(error) => (NotificationType::Error, SharedString::from(error.to_string())),
+                };
+                window.push_notification(notification, cx);
+                cx.notify();
+            });
+        }).detach();
+    }
+
+    fn set_launch_limits(&mut self, refuse_over_memory_budget: bool, cx: &mut Context<Self>) {
+        let read_limit = |input: &Entity<InputState>| input.read(cx).value().trim().parse::<u32>().ok().filter(|value| *value > 0);
+        self.backend_handle.send(MessageToBackend::SetLaunchLimits {
+            limits: LaunchLimits {
+                max_running: read_limit(&self.max_running_input),
+                memory_budget_percent: read_limit(&self.memory_budget_input).map(|percent| percent.min(100)),
+                refuse_over_memory_budget,
+            },
+        });
+        self.update_backend_configuration(cx);
+    }
+
+    fn set_playtime_limits(&mut self, refuse_launch: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let daily_limit = self.playtime_limit_input.read(cx).value().trim().parse::<u32>().ok().filter(|minutes| *minutes > 0);
+        let backend_handle = self.backend_handle.clone();
+        let settings = cx.entity();
+        crate::launcher_lock::when_unlocked(&self.backend_handle, window, cx, move |_, cx| {
+            backend_handle.send(MessageToBackend::SetPlaytimeLimits { daily_limit, refuse_launch });
+            settings.update(cx, |settings, cx| settings.update_backend_configuration(cx));
+        });
+    }
+
+    fn set_launcher_pin(&mut self, pin: Option<Arc<str>>, window: &mut Window, cx: &mut Context<Self>) {
+        let backend_handle = self.backend_handle.clone();
+        let settings = cx.entity();
+        crate::launcher_lock::when_unlocked(&self.backend_handle, window, cx, move |window, cx| {
+            backend_handle.send(MessageToBackend::SetLauncherPin { pin });
+            settings.update(cx, |settings, cx| {
+                settings.launcher_pin_input.update(cx, |input, cx| input.set_value("", window, cx));
+                settings.update_backend_configuration(cx);
+            });
+        });
+    }
+}
+
+impl Settings {
+    fn render_launcher_lock(&self, has_pin: bool, cx: &mut Context<Self>) -> Div {
+        let mut content = v_flex().gap_2()
+            .child(div().text_sm().text_color(cx.theme().muted_foreground)
+                .child(ts!("settings_launcher_lock_description")))
+            .child(Input::new(&self.launcher_pin_input).small())
+            .child(Button::new("set-pin")
+                .success()
+                .label(if has_pin { ts!("settings_change_pin") } else { ts!("settings_set_pin") })
+                .on_click(cx.listener(|settings, _, window, cx| {
+                    let pin: Arc<str> = settings.launcher_pin_input.read(cx).value().trim().into();
+                    if pin.len() < 4 {
+                        window.push_notification((NotificationType::Error, ts!("settings_pin_too_short")), cx);
+                        return;
+                    }
+                    settings.set_launcher_pin(Some(pin), window, cx);
+                })));
+
+        if has_pin {
+            content = content
+                .child(Button::new("remove-pin").danger().label(ts!("settings_remove_pin")).on_click(cx.listener(|settings, _, window, cx| {
+                    settings.set_launcher_pin(None, window, cx);
+                })))
+                .child(Button::new("lock-now").info().label(ts!("settings_lock_now")).on_click(cx.listener(|settings, _, _, cx| {
+                    crate::launcher_lock::lock(&settings.backend_handle, cx);
+                })));
+        }
+
+        content
+    }
 }
 
 impl Render for Settings {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        self.load_network_inputs(window, cx);
+
+        let current_locale = language::current_locale(cx);
+        let mut language_group = ButtonGroup::new("language").outline();
+        for (index, (locale, name)) in language::LANGUAGES.iter().enumerate() {
+            language_group = language_group.child(Button::new(("language", index)).label(*name).selected(*locale == current_locale));
+        }
+
         let interface_config = InterfaceConfig::get(cx);
 
         let mut div = v_flex()
@@ -117,30 +387,64 @@ impl Render for Settings {
             .py_3()
             .gap_3()
             .child(crate::labelled(
-                "Theme",
-                Select::new(&self.theme_select)
+                ts!("settings_language"),
+                language_group
+                    .on_click(|selected: &Vec<usize>, _, cx| {
+                        let Some((locale, _)) = selected.first().and_then(|index| language::LANGUAGES.get(*index)) else {
+                            return;
+                        };
+                        InterfaceConfig::get_mut(cx).language = SharedString::new_static(locale);
+                        language::apply(cx);
+                    })
+            ))
+            .child(crate::labelled(
+                ts!("settings_appearance"),
+                v_flex().gap_2()
+                    .child(ButtonGroup::new("appearance-mode")
+                        .outline()
+                        .child(Button::new("mode-system").label(ts!("settings_mode_system")).selected(interface_config.appearance_mode == AppearanceMode::System))
+                        .child(Button::new("mode-light").label(ts!("settings_mode_light")).selected(interface_config.appearance_mode == AppearanceMode::Light))
+                        .child(Button::new("mode-dark").label(ts!("settings_mode_dark")).selected(interface_config.appearance_mode == AppearanceMode::Dark))
+                        .on_click(|selected: &Vec<usize>, _, cx| {
+                            let mode = match selected.first() {
+                                Some(0) => AppearanceMode::System,
+                                Some(1) => AppearanceMode::Light,
+                                _ => AppearanceMode::Dark,
+                            };
+                            InterfaceConfig::get_mut(cx).appearance_mode = mode;
+                            appearance::apply(cx);
+                        }))
+                    .child(crate::labelled(ts!("settings_theme"), Select::new(&self.theme_select)))
+                    .child(crate::labelled(ts!("settings_accent_color"), h_flex().gap_2()
+                        .child(ColorPicker::new(&self.accent_picker).small())
+                        .child(Button::new("reset-accent").label(ts!("settings_use_theme_color")).small().on_click(cx.listener(|settings, _, window, cx| {
+                            InterfaceConfig::get_mut(cx).accent_color = None;
+                            appearance::apply(cx);
+                            let primary = cx.theme().primary;
+                            settings.accent_picker.update(cx, |picker, cx| picker.set_value(primary, window, cx));
+                        })))))
             ))
-            .child(Button::new("open-theme-folder").info().icon(IconName::FolderOpen).label("Open theme folder").on_click({
+            .child(Button::new("open-theme-folder").info().icon(IconName::FolderOpen).label(ts!("settings_open_theme_folder")).on_click({
                 let theme_folder = self.theme_folder.clone();
                 move |_, window, cx| {
                     crate::open_folder(&theme_folder, window, cx);
                 }
             }))
-            .child(Button::new("open-theme-repo").info().icon(IconName::Globe).label("Open theme repository").on_click({
+            .child(Button::new("open-theme-repo").info().icon(IconName::Globe).label(ts!("settings_open_theme_repository")).on_click({
                 move |_, _, cx| {
                     cx.open_url("https://github.com/longbridge/gpui-component/tree/main/themes");
                 }
             }))
-            .child(crate::labelled("Deletion",
+            .child(crate::labelled(ts!("settings_deletion"),
                 v_flex().gap_2()
                     .child(Checkbox::new("confirm-delete-mods")
-                        .label("Shift+Click to skip mod delete confirmation")
+                        .label(ts!("settings_quick_delete_mods"))
                         .checked(interface_config.quick_delete_mods)
                         .on_click(|value, _, cx| {
                             InterfaceConfig::get_mut(cx).quick_delete_mods = *value;
                         }))
                     .child(Checkbox::new("confirm-delete-instance")
-                        .label("Shift+Click to skip instance delete confirmation")
+                        .label(ts!("settings_quick_delete_instance"))
                         .checked(interface_config.quick_delete_instance).on_click(|value, _, cx| {
                             InterfaceConfig::get_mut(cx).quick_delete_instance = *value;
                         }))
@@ -150,16 +454,36 @@ impl Render for Settings {
         if let Some(backend_config) = &self.backend_config {
             div = div
                 .child(crate::labelled(
-                    "Launching",
+                    ts!("settings_launching"),
                     v_flex().gap_2()
-                        .child(Checkbox::new("hide-on-launch")
-                            .label("Hide main window on launch")
-                            .checked(interface_config.hide_main_window_on_launch)
+                        .child(crate::labelled(ts!("settings_launch_window_behavior"), ButtonGroup::new("launch-window-behavior")
+                            .outline()
+                            .child(Button::new("launch-keep-open").label(ts!("settings_launch_keep_open"))
+                                .selected(interface_config.launch_window_behavior == LaunchWindowBehavior::KeepOpen))
+                            .child(Button::new("launch-minimize").label(ts!("settings_launch_minimize"))
+                                .selected(interface_config.launch_window_behavior == LaunchWindowBehavior::Minimize))
+                            .child(Button::new("launch-hide").label(ts!("settings_launch_hide"))
+                                .selected(interface_config.launch_window_behavior == LaunchWindowBehavior::Hide))
+                            // Quitting would take the game down with it
+                            .child(Button::new("launch-quit").label(ts!("settings_launch_quit"))
+                                .disabled(backend_config.kill_games_on_launcher_exit)
+                                .selected(interface_config.launch_window_behavior == LaunchWindowBehavior::Quit))
+                            .on_click(|selected: &Vec<usize>, _, cx| {
+                                InterfaceConfig::get_mut(cx).launch_window_behavior = match selected.first() {
+                                    Some(1) => LaunchWindowBehavior::Minimize,
+                                    Some(2) => LaunchWindowBehavior::Hide,
+                                    Some(3) => LaunchWindowBehavior::Quit,
+                                    _ => LaunchWindowBehavior::KeepOpen,
+                                };
+                            })))
+                        .child(Checkbox::new("launch-preflight")
+                            .label(ts!("settings_launch_preflight"))
+                            .checked(interface_config.launch_preflight)
                             .on_click(|value, _, cx| {
-                                InterfaceConfig::get_mut(cx).hide_main_window_on_launch = *value;
+                                InterfaceConfig::get_mut(cx).launch_preflight = *value;
                             }))
                         .child(Checkbox::new("open-game-output")
-                            .label("Open game output on launch")
+                            .label(ts!("settings_open_game_output"))
                             .checked(!backend_config.dont_open_game_output_when_launching)
                             .on_click(cx.listener({
                                 let backend_handle = self.backend_handle.clone();
@@ -170,6 +494,256 @@ impl Render for Settings {
                                     settings.update_backend_configuration(cx);
                                 }
                             })))
+                        .child(Checkbox::new("kill-on-exit")
+                            .label(ts!("settings_kill_on_exit"))
+                            .checked(backend_config.kill_games_on_launcher_exit)
+                            .on_click(cx.listener({
+                                let backend_handle = self.backend_handle.clone();
+                                move |settings, value, _, cx| {
+                                    backend_handle.send(MessageToBackend::SetKillGamesOnLauncherExit {
+                                        value: *value
+                                    });
+                                    if *value && InterfaceConfig::get(cx).launch_window_behavior == LaunchWindowBehavior::Quit {
+                                        InterfaceConfig::get_mut(cx).launch_window_behavior = LaunchWindowBehavior::Hide;
+                                    }
+                                    settings.update_backend_configuration(cx);
+                                }
+                            })))
+                        .child(Checkbox::new("offline-fallback")
+                            .label(ts!("settings_offline_fallback"))
+                            .checked(backend_config.offline_fallback)
+                            .on_click(cx.listener({
+                                let backend_handle = self.backend_handle.clone();
+                                move |settings, value, _, cx| {
+                                    backend_handle.send(MessageToBackend::SetOfflineFallback {
+                                        value: *value
+                                    });
+                                    settings.update_backend_configuration(cx);
+                                }
+                            })))
+                        .child(Checkbox::new("offline-mode")
+                            .label(ts!("settings_offline_mode"))
+                            .checked(backend_config.offline_mode)
+                            .on_click(cx.listener({
+                                let backend_handle = self.backend_handle.clone();
+                                move |settings, value, _, cx| {
+                                    backend_handle.send(MessageToBackend::SetOfflineMode {
+                                        value: *value
+                                    });
+                                    settings.update_backend_configuration(cx);
+                                }
+                            })))
+                        .child(Checkbox::new("only-selected-language")
+                            .label(ts!("settings_only_selected_language"))
+                            .checked(backend_config.download_only_selected_language)
+                            .on_click(cx.listener({
+                                let backend_handle = self.backend_handle.clone();
+                                move |settings, value, _, cx| {
+                                    backend_handle.send(MessageToBackend::SetDownloadOnlySelectedLanguage {
+                                        value: *value
+                                    });
+                                    settings.update_backend_configuration(cx);
+                                }
+                            })))
+                        .child(Checkbox::new("strict-schema")
+                            .label(ts!("settings_strict_schema"))
+                            .checked(backend_config.strict_schema)
+                            .on_click(cx.listener({
+                                let backend_handle = self.backend_handle.clone();
+                                move |settings, value, _, cx| {
+                                    backend_handle.send(MessageToBackend::SetStrictSchema {
+                                        value: *value
+                                    });
+                                    settings.update_backend_configuration(cx);
+                                }
+                            })))
+                ))
+                .child(crate::labelled(
+                    ts!("settings_updates"),
+                    v_flex().gap_2()
+                        .child(crate::labelled(ts!("settings_update_channel"), ButtonGroup::new("update-channel")
+                            .outline()
+                            .child(Button::new("channel-stable").label(ts!("settings_update_channel_stable"))
+                                .selected(backend_config.update_channel == UpdateChannel::Stable))
+                            .child(Button::new("channel-prerelease").label(ts!("settings_update_channel_prerelease"))
+                                .selected(backend_config.update_channel == UpdateChannel::Prerelease))
+                            .on_click(cx.listener({
+                                let backend_handle = self.backend_handle.clone();
+                                move |settings, selected: &Vec<usize>, _, cx| {
+                                    let channel = match selected.first() {
+                                        Some(1) => UpdateChannel::Prerelease,
+                                        _ => UpdateChannel::Stable,
+                                    };
+                                    backend_handle.send(MessageToBackend::SetUpdateChannel { channel });
+                                    settings.update_backend_configuration(cx);
+                                }
+                            }))))
+                        .child(crate::labelled(ts!("settings_update_frequency"), ButtonGroup::new("update-frequency")
+                            .outline()
+                            .child(Button::new("frequency-startup").label(ts!("settings_update_on_startup"))
+                                .selected(backend_config.update_check_frequency == UpdateCheckFrequency::OnStartup))
+                            .child(Button::new("frequency-daily").label(ts!("settings_update_daily"))
+                                .selected(backend_config.update_check_frequency == UpdateCheckFrequency::Daily))
+                            .child(Button::new("frequency-weekly").label(ts!("settings_update_weekly"))
+                                .selected(backend_config.update_check_frequency == UpdateCheckFrequency::Weekly))
+                            .child(Button::new("frequency-never").label(ts!("settings_update_never"))
+                                .selected(backend_config.update_check_frequency == UpdateCheckFrequency::Never))
+                            .on_click(cx.listener({
+                                let backend_handle = self.backend_handle.clone();
+                                move |settings, selected: &Vec<usize>, _, cx| {
+                                    let frequency = match selected.first() {
+                                        Some(1) => UpdateCheckFrequency::Daily,
+                                        Some(2) => UpdateCheckFrequency::Weekly,
+                                        Some(3) => UpdateCheckFrequency::Never,
+                                        _ => UpdateCheckFrequency::OnStartup,
+                                    };
+                                    backend_handle.send(MessageToBackend::SetUpdateCheckFrequency { frequency });
+                                    settings.update_backend_configuration(cx);
+                                }
+                            }))))
+                        .child(crate::labelled(ts!("settings_metadata_refresh"), ButtonGroup::new("metadata-refresh")
+                            .outline()
+                            .child(Button::new("refresh-never").label(ts!("settings_update_never"))
+                                .selected(backend_config.metadata_refresh_interval == MetadataRefreshInterval::Never))
+                            .child(Button::new("refresh-hourly").label(ts!("settings_metadata_refresh_hourly"))
+                                .selected(backend_config.metadata_refresh_interval == MetadataRefreshInterval::Hourly))
+                            .child(Button::new("refresh-six-hours").label(ts!("settings_metadata_refresh_six_hours"))
+                                .selected(backend_config.metadata_refresh_interval == MetadataRefreshInterval::EverySixHours))
+                            .child(Button::new("refresh-daily").label(ts!("settings_update_daily"))
+                                .selected(backend_config.metadata_refresh_interval == MetadataRefreshInterval::Daily))
+                            .on_click(cx.listener({
+                                let backend_handle = self.backend_handle.clone();
+                                move |settings, selected: &Vec<usize>, _, cx| {
+                                    let interval = match selected.first() {
+                                        Some(1) => MetadataRefreshInterval::Hourly,
+                                        Some(2) => MetadataRefreshInterval::EverySixHours,
+                                        Some(3) => MetadataRefreshInterval::Daily,
+                                        _ => MetadataRefreshInterval::Never,
+                                    };
+                                    backend_handle.send(MessageToBackend::SetMetadataRefreshInterval { interval });
+                                    settings.update_backend_configuration(cx);
+                                }
+                            }))))
+                        .child(Checkbox::new("new-version-desktop-notification")
+                            .label(ts!("settings_new_version_desktop_notification"))
+                            .checked(backend_config.new_version_desktop_notification)
+                            .disabled(backend_config.metadata_refresh_interval == MetadataRefreshInterval::Never)
+                            .on_click(cx.listener({
+                                let backend_handle = self.backend_handle.clone();
+                                move |settings, value, _, cx| {
+                                    backend_handle.send(MessageToBackend::SetNewVersionDesktopNotification {
+                                        value: *value
+                                    });
+                                    settings.update_backend_configuration(cx);
+                                }
+                            })))
+                        .child(Button::new("check-for-updates").label(ts!("settings_check_for_updates")).on_click({
+                            let backend_handle = self.backend_handle.clone();
+                            move |_, _, _| {
+                                backend_handle.send(MessageToBackend::CheckForLauncherUpdate);
+                            }
+                        }))
+                ))
+                .child(crate::labelled(
+                    ts!("settings_troubleshooting"),
+                    v_flex().gap_2()
+                        .child(div().text_sm().text_color(cx.theme().muted_foreground).child(ts!("settings_diagnostics_bundle_description")))
+                        .child(Button::new("create-diagnostics-bundle").label(ts!("settings_create_diagnostics_bundle")).on_click({
+                            let backend_handle = self.backend_handle.clone();
+                            move |_, window, cx| {
+                                let user_dirs = directories::UserDirs::new();
+                                let directory = user_dirs.as_ref()
+                                    .and_then(directories::UserDirs::download_dir).unwrap_or(Path::new("."));
+
+                                let receiver = cx.prompt_for_new_path(directory, Some("pandora-diagnostics.zip"));
+                                let backend_handle = backend_handle.clone();
+                                window.spawn(cx, async move |cx| {
+                                    let Ok(Ok(Some(path))) = receiver.await else {
+                                        return;
+                                    };
+                                    _ = cx.update(|window, cx| {
+                                        let modal_action = ModalAction::default();
+                                        backend_handle.send(MessageToBackend::CreateDiagnosticsBundle { path, modal_action: modal_action.clone() });
+                                        crate::modals::generic::show_modal(window, cx, ts!("settings_creating_diagnostics_bundle"),
+                                            ts!("settings_diagnostics_bundle_error"), modal_action);
+                                    });
+                                }).detach();
+                            }
+                        }))
+                ))
+                .child(crate::labelled(
+                    ts!("settings_launch_limits"),
+                    v_flex().gap_2()
+                        .child(crate::labelled(ts!("settings_max_running"), Input::new(&self.max_running_input).small()))
+                        .child(crate::labelled(ts!("settings_memory_budget"), Input::new(&self.memory_budget_input).small()))
+                        .child(Checkbox::new("refuse-over-memory-budget")
+                            .label(ts!("settings_refuse_over_memory_budget"))
+                            .checked(backend_config.launch_limits.refuse_over_memory_budget)
+                            .on_click(cx.listener(|settings, value, _, cx| {
+                                settings.set_launch_limits(*value, cx);
+                            })))
+                        .child(Button::new("save-launch-limits").success().label(ts!("settings_save_launch_limits")).on_click(cx.listener({
+                            let refuse_over_memory_budget = backend_config.launch_limits.refuse_over_memory_budget;
+                            move |settings, _, _, cx| {
+                                settings.set_launch_limits(refuse_over_memory_budget, cx);
+                            }
+                        })))
+                ))
+                .child(crate::labelled(
+                    ts!("settings_playtime"),
+                    v_flex().gap_2()
+                        .child(crate::labelled(ts!("settings_daily_limit"), Input::new(&self.playtime_limit_input).small()))
+                        .child(Checkbox::new("refuse-over-playtime")
+                            .label(ts!("settings_refuse_over_playtime"))
+                            .checked(backend_config.refuse_launch_over_playtime_limit)
+                            .on_click(cx.listener(|settings, value, window, cx| {
+                                settings.set_playtime_limits(*value, window, cx);
+                            })))
+                        .child(Button::new("save-playtime").success().label(ts!("settings_save_playtime")).on_click(cx.listener({
+                            let refuse_launch = backend_config.refuse_launch_over_playtime_limit;
+                            move |settings, _, window, cx| {
+                                settings.set_playtime_limits(refuse_launch, window, cx);
+                            }
+                        })))
+                ))
+                .child(crate::labelled(
+                    ts!("settings_launcher_lock"),
+                    self.render_launcher_lock(backend_config.launcher_lock.is_some(), cx)
+                ))
+                .child(crate::labelled(
+                    ts!("settings_network"),
+                    v_flex().gap_2()
+                        .child(crate::labelled(ts!("settings_user_agent"), Input::new(&self.user_agent_input).small()))
+                        .child(crate::labelled(ts!("settings_meta_url"), Input::new(&self.meta_url_input).small()))
+                        .child(crate::labelled(ts!("settings_assets_url"), Input::new(&self.assets_url_input).small()))
+                        .child(crate::labelled(ts!("settings_libraries_url"), Input::new(&self.libraries_url_input).small()))
+                        .child(h_flex().gap_2()
+                            .child(Button::new("use-bmclapi").label(ts!("settings_use_bmclapi")).on_click(cx.listener(|settings, _, window, cx| {
+                                settings.fill_endpoint_inputs(EndpointOverrides::bmclapi(), window, cx);
+                            })))
+                            .child(Button::new("use-mojang").label(ts!("settings_use_mojang_servers")).on_click(cx.listener(|settings, _, window, cx| {
+                                settings.fill_endpoint_inputs(EndpointOverrides::default(), window, cx);
+                            }))))
+                        .child(div().text_sm().text_color(cx.theme().muted_foreground).child(ts!("settings_mirror_fallback")))
+                        .child(crate::labelled(ts!("settings_concurrent_downloads"), Input::new(&self.max_downloads_input).small()))
+                        .child(crate::labelled(ts!("settings_download_speed_limit"), Input::new(&self.download_speed_input).small()))
+                        .child(crate::labelled(ts!("settings_download_retries"), Input::new(&self.download_retries_input).small()))
+                        .child(crate::labelled(ts!("settings_curseforge_api_key"), Input::new(&self.curseforge_api_key_input).small()))
+                        .child(crate::labelled(ts!("settings_proxy_url"), Input::new(&self.proxy_url_input).small()))
+                        .child(crate::labelled(ts!("settings_proxy_username"), Input::new(&self.proxy_username_input).small()))
+                        .child(crate::labelled(ts!("settings_proxy_password"), Input::new(&self.proxy_password_input).small()))
+                        .child(crate::labelled(ts!("settings_ca_certificates"), Input::new(&self.ca_certificates_input).small()))
+                        .child(div().text_sm().text_color(cx.theme().muted_foreground).child(ts!("settings_proxy_restart")))
+                        .child(h_flex().gap_2()
+                            .child(Button::new("save-network").success().label(ts!("settings_save_network")).on_click(cx.listener(|settings, _, _, cx| {
+                                settings.save_network_overrides(cx);
+                            })))
+                            .child(Button::new("test-connection")
+                                .label(ts!("settings_test_connection"))
+                                .loading(self.testing_connection)
+                                .on_click(cx.listener(|settings, _, window, cx| {
+                                    settings.test_connection(window, cx);
+                                }))))
                 ))
         } else {
             div = div.child(Spinner::new().large());
@@ -0,0 +1,111 @@
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, h_flex, sheet::Sheet, v_flex, ActiveTheme, Icon, Sizable
+};
+
+use crate::{
+    active_operations::{ActiveOperation, ActiveOperations, TaskState},
+    component::progress_bar::{transfer_details, ProgressBar},
+    ts,
+};
+
+pub fn build_task_center_sheet() -> impl Fn(Sheet, &mut Window, &mut App) -> Sheet + 'static {
+    move |sheet, window, cx| {
+        let operations = ActiveOperations::all(cx);
+
+        // Keeps progress bars moving while something is running
+        if operations.iter().any(|operation| !operation.is_finished()) {
+            window.request_animation_frame();
+        }
+
+        let content = if operations.is_empty() {
+            v_flex().p_4().child(div().text_sm().text_color(cx.theme().muted_foreground).child(ts!("tasks_empty")))
+        } else {
+            v_flex()
+                .child(h_flex().px_4().py_2().justify_end().child(Button::new("clear-finished").label(ts!("tasks_clear_finished")).small().on_click(|_, _, cx| {
+                    ActiveOperations::clear_finished(cx);
+                })))
+                .children(operations.iter().enumerate().map(|(index, operation)| render_operation(index, operation, cx)))
+        };
+
+        sheet
+            .title(ts!("tasks_title"))
+            .overlay_top(crate::root::sheet_margin_top(window))
+            .p_0()
+            .child(v_flex().border_t_1().border_color(cx.theme().border).child(content))
+    }
+}
+
+fn render_operation(index: usize, operation: &ActiveOperation, cx: &App) -> Div {
+    let state = operation.state();
+    let (label, color) = match &state {
+        TaskState::Queued => (ts!("tasks_queued"), cx.theme().muted_foreground),
+        TaskState::Running => (ts!("tasks_running"), cx.theme().foreground),
+        TaskState::Failed(_) => (ts!("tasks_failed"), cx.theme().danger),
+        TaskState::Done => (ts!("tasks_done"), cx.theme().success),
+    };
+    let elapsed = crate::format::duration(operation.started_at.elapsed().as_secs());
+
+    let mut details = v_flex()
+        .flex_1()
+        .min_w_0()
+        .gap_1()
+        .child(h_flex().gap_2().justify_between()
+            .child(div().text_sm().font_medium().truncate().child(operation.title()))
+            .child(div().text_xs().text_color(color).child(label)))
+        .child(div().text_xs().text_color(cx.theme().muted_foreground).child(rust_i18n::t!("tasks_started_ago", elapsed = elapsed).into_owned()));
+
+    if state == TaskState::Running {
+        for tracker in operation.running_trackers() {
+            let mut progress_bar = ProgressBar::new();
+            if let Some(progress) = tracker.get_float() {
+                progress_bar.amount = progress;
+            }
+            let percent = tracker.get_float().map(crate::format::percent);
+            let transfer = transfer_details(&tracker).map(|transfer| div().text_xs().text_color(cx.theme().muted_foreground).child(transfer));
+            details = details
+                .child(h_flex().gap_2().justify_between().text_xs().text_color(cx.theme().muted_foreground)
+                    .child(div().truncate().child(SharedString::new(tracker.get_title())))
+                    .children(percent))
+                .child(progress_bar)
+                .children(transfer);
+        }
+    }
+    if let TaskState::Failed(error) = &state {
+        details = details.child(div().text_xs().text_color(cx.theme().danger).child(SharedString::new(error.clone())));
+    }
+
+    let mut row = h_flex()
+        .gap_3()
+        .px_4()
+        .py_2()
+        .border_b_1()
+        .border_color(cx.theme().border)
+        .child(details);
+
+    match state {
+        TaskState::Queued | TaskState::Running => {
+            let modal_action = operation.modal_action.clone();
+            row = row.child(Button::new(("cancel", index)).label(ts!("tasks_cancel")).danger().small().on_click(move |_, _, _| {
+                modal_action.request_cancel();
+            }));
+        },
+        TaskState::Failed(_) => {
+            if let Some(retry) = &operation.retry {
+                let retry = retry.clone();
+                let modal_action = operation.modal_action.clone();
+                row = row.child(Button::new(("retry", index))
+                    .icon(Icon::default().path("icons/refresh-ccw.svg"))
+                    .label(ts!("tasks_retry"))
+                    .small()
+                    .on_click(move |_, window, cx| {
+                        ActiveOperations::remove(cx, &modal_action);
+                        retry(window, cx);
+                    }));
+            }
+        },
+        TaskState::Done => {},
+    }
+
+    row
+}
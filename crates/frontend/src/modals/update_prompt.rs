@@ -3,37 +3,29 @@ use std::sync::{atomic::{AtomicBool, AtomicU8, Ordering}, Arc};
 use bridge::{handle::BackendHandle, instance::InstanceID, modal_action::ModalAction};
 use gpui::{prelude::*, *};
 use gpui_component::{
-    Disableable, WindowExt, button::{Button, ButtonVariants}, h_flex, input::{Input, InputEvent, InputState}, v_flex
+    ActiveTheme as _, Disableable, WindowExt, button::{Button, ButtonVariants}, h_flex, input::{Input, InputEvent, InputState}, v_flex
 };
 use schema::pandora_update::UpdatePrompt;
 
+use crate::ts;
+
 pub fn open_update_prompt(
     update: UpdatePrompt,
     handle: BackendHandle,
     window: &mut Window,
     cx: &mut App,
 ) {
-    let title = SharedString::new_static("Update Pandora?");
-    let old_version = SharedString::new(format!("Current version: {}", update.old_version));
-    let new_version = SharedString::new(format!("New version: {}", update.new_version));
-
-    let size = if update.exe.size < 1000*10 {
-        format!("Update size: {} bytes", update.exe.size)
-    } else if update.exe.size < 1000*1000*10 {
-        format!("Update size: {}kb", update.exe.size/1000)
-    } else if update.exe.size < 1000*1000*1000*10 {
-        format!("Update size: {}MB", update.exe.size/1000/1000)
-    } else {
-        format!("Update size: {}GB", update.exe.size/1000/1000/1000)
-    };
-
-    let size = SharedString::new(size);
+    let title = ts!("update_title");
+    let old_version = SharedString::new(rust_i18n::t!("update_current_version", version = update.old_version));
+    let new_version = SharedString::new(rust_i18n::t!("update_new_version", version = update.new_version));
+    let size = SharedString::new(rust_i18n::t!("update_size", size = crate::format::bytes(update.exe.size as u64)));
+    let changelog = update.changelog.clone().map(SharedString::from);
 
-    window.open_dialog(cx, move |dialog, _, _| {
+    window.open_dialog(cx, move |dialog, _, cx| {
         let buttons = h_flex()
             .w_full()
             .gap_2()
-            .child(Button::new("update").flex_1().label("Update").success().on_click({
+            .child(Button::new("update").flex_1().label(ts!("update_update")).success().on_click({
                 let handle = handle.clone();
                 let update = update.clone();
                 move |_, window, cx| {
@@ -43,10 +35,10 @@ pub fn open_update_prompt(
                         modal_action: modal_action.clone(),
                     });
                     window.close_all_dialogs(cx);
-                    crate::modals::generic::show_notification(window, cx, "Unable to install update".into(), modal_action);
+                    crate::modals::generic::show_notification(window, cx, ts!("update_error"), modal_action);
                 }
             }))
-            .child(Button::new("later").flex_1().label("Later").on_click(|_, window, cx| {
+            .child(Button::new("later").flex_1().label(ts!("update_later")).on_click(|_, window, cx| {
                 window.close_all_dialogs(cx);
             }));
 
@@ -58,7 +50,15 @@ pub fn open_update_prompt(
                     .child(old_version.clone())
                     .child(new_version.clone())
                     .child(size.clone())
-                ).child(buttons))
+                )
+                .when_some(changelog.clone(), |this, changelog| {
+                    this.child(v_flex()
+                        .gap_1()
+                        .child(ts!("update_whats_new"))
+                        .child(div().id("changelog").max_h(px(320.0)).overflow_y_scroll().p_2().rounded_md().border_1()
+                            .border_color(cx.theme().border).text_sm().child(changelog)))
+                })
+                .child(buttons))
     });
 
 }
@@ -0,0 +1,179 @@
+use std::{path::Path, sync::Arc, time::Duration};
+
+use bridge::message::BridgeNotificationType;
+use gpui::{App, SharedString, Task};
+use gpui_component::notification::NotificationType;
+use serde::{Deserialize, Serialize};
+
+use crate::{interface_config::{try_read_json, write_safe}, ts};
+
+/// Older notifications are dropped once there are more than this
+const MAX_ENTRIES: usize = 200;
+
+struct NotificationHistoryHolder {
+    history: NotificationHistory,
+    write_task: Option<Task<()>>,
+    path: Arc<Path>,
+}
+
+impl gpui::Global for NotificationHistoryHolder {}
+
+/// Notifications sent by the backend, kept after their toast is gone so they can be looked at in the notification
+/// center. Saved next to the interface config so they survive restarts
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NotificationHistory {
+    #[serde(default, deserialize_with = "schema::try_deserialize")]
+    entries: Vec<NotificationEntry>,
+    #[serde(default, deserialize_with = "schema::try_deserialize")]
+    unread: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationEntry {
+    pub kind: NotificationKind,
+    pub message: Arc<str>,
+    /// Milliseconds since the unix epoch
+    pub time: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<NotificationAction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationKind {
+    Success,
+    Info,
+    Warning,
+    Error,
+}
+
+/// Something that can be done from the notification center about a notification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationAction {
+    /// Instances are referred to by name since ids don't survive restarts
+    LaunchInstance { name: Arc<str> },
+    OpenFile { path: Arc<Path> },
+}
+
+impl From<BridgeNotificationType> for NotificationKind {
+    fn from(notification_type: BridgeNotificationType) -> Self {
+        match notification_type {
+            BridgeNotificationType::Success => Self::Success,
+            BridgeNotificationType::Info => Self::Info,
+            BridgeNotificationType::Warning => Self::Warning,
+            BridgeNotificationType::Error => Self::Error,
+        }
+    }
+}
+
+impl NotificationKind {
+    pub fn notification_type(self) -> NotificationType {
+        match self {
+            Self::Success => NotificationType::Success,
+            Self::Info => NotificationType::Info,
+            Self::Warning => NotificationType::Warning,
+            Self::Error => NotificationType::Error,
+        }
+    }
+}
+
+impl NotificationAction {
+    pub fn label(&self) -> SharedString {
+        match self {
+            Self::LaunchInstance { .. } => ts!("notifications_launch_again"),
+            Self::OpenFile { .. } => ts!("notifications_open_file"),
+        }
+    }
+}
+
+impl NotificationHistory {
+    pub fn init(cx: &mut App, path: Arc<Path>) {
+        cx.set_global(NotificationHistoryHolder {
+            history: try_read_json(&path),
+            write_task: None,
+            path,
+        });
+    }
+
+    pub fn push(cx: &mut App, kind: NotificationKind, message: impl Into<Arc<str>>, action: Option<NotificationAction>) {
+        let entry = NotificationEntry {
+            kind,
+            message: message.into(),
+            time: chrono::Utc::now().timestamp_millis(),
+            action,
+        };
+        Self::modify(cx, |history| {
+            history.entries.push(entry);
+            if history.entries.len() > MAX_ENTRIES {
+                history.entries.drain(..history.entries.len() - MAX_ENTRIES);
+            }
+            history.unread = (history.unread + 1).min(history.entries.len());
+        });
+    }
+
+    /// Every notification, newest first
+    pub fn entries(cx: &App) -> Vec<NotificationEntry> {
+        cx.try_global::<NotificationHistoryHolder>()
+            .map(|holder| holder.history.entries.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn unread(cx: &App) -> usize {
+        cx.try_global::<NotificationHistoryHolder>().map_or(0, |holder| holder.history.unread)
+    }
+
+    pub fn mark_read(cx: &mut App) {
+        if Self::unread(cx) > 0 {
+            Self::modify(cx, |history| history.unread = 0);
+        }
+    }
+
+    /// Removes a notification, `index` counts from the newest like in `entries`
+    pub fn remove(cx: &mut App, index: usize) {
+        Self::modify(cx, |history| {
+            if index < history.entries.len() {
+                let index = history.entries.len() - 1 - index;
+                history.entries.remove(index);
+                history.unread = history.unread.min(history.entries.len());
+            }
+        });
+    }
+
+    pub fn clear(cx: &mut App) {
+        Self::modify(cx, |history| *history = NotificationHistory::default());
+    }
+
+    pub fn force_save(cx: &mut App) {
+        if let Some(holder) = cx.try_global::<NotificationHistoryHolder>() && holder.write_task.is_some() {
+            cx.global_mut::<NotificationHistoryHolder>().write_to_disk();
+        }
+    }
+
+    fn modify(cx: &mut App, modify: impl FnOnce(&mut NotificationHistory)) {
+        if !cx.has_global::<NotificationHistoryHolder>() {
+            return;
+        }
+        if cx.global::<NotificationHistoryHolder>().write_task.is_none() {
+            let task = cx.spawn(async |app| {
+                gpui::Timer::after(Duration::from_secs(5)).await;
+                _ = app.update_global::<NotificationHistoryHolder, _>(|holder, _| {
+                    holder.write_to_disk();
+                });
+            });
+            cx.global_mut::<NotificationHistoryHolder>().write_task = Some(task);
+        }
+        modify(&mut cx.global_mut::<NotificationHistoryHolder>().history);
+        cx.refresh_windows();
+    }
+}
+
+impl NotificationHistoryHolder {
+    fn write_to_disk(&mut self) {
+        self.write_task = None;
+        let Ok(bytes) = serde_json::to_vec(&self.history) else {
+            return;
+        };
+        _ = write_safe(&self.path, &bytes);
+    }
+}
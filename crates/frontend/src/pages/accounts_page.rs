@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use bridge::{account::Account, handle::BackendHandle, message::MessageToBackend};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    ActiveTheme as _, Disableable, Icon, IconName, WindowExt,
+    button::{Button, ButtonVariants},
+    h_flex,
+    v_flex,
+};
+
+use crate::{entity::{DataEntities, account::AccountEntries, instance::InstanceEntries}, png_render_cache, ui};
+
+pub struct AccountsPage {
+    accounts: Entity<AccountEntries>,
+    instances: Entity<InstanceEntries>,
+    backend_handle: BackendHandle,
+    scroll_handle: ScrollHandle,
+}
+
+impl AccountsPage {
+    pub fn new(data: &DataEntities, _window: &mut Window, cx: &mut Context<Self>) -> Self {
+        cx.observe(&data.accounts, |_, _, cx| cx.notify()).detach();
+        cx.observe(&data.instances, |_, _, cx| cx.notify()).detach();
+
+        Self {
+            accounts: data.accounts.clone(),
+            instances: data.instances.clone(),
+            backend_handle: data.backend_handle.clone(),
+            scroll_handle: ScrollHandle::new(),
+        }
+    }
+
+    fn render_account(&self, account: &Account, selected: bool, cx: &mut Context<Self>) -> Div {
+        let head = if let Some(head) = &account.head {
+            let resize = png_render_cache::ImageTransformation::Resize { width: 48, height: 48 };
+            png_render_cache::render_with_transform(Arc::clone(head), resize, cx)
+        } else {
+            gpui::img(ImageSource::Resource(Resource::Embedded("images/default_head.png".into())))
+        };
+
+        // Instances that always launch with this account regardless of the selected one
+        let overridden_by: Vec<SharedString> = self.instances.read(cx).entries.values()
+            .map(|entry| entry.read(cx))
+            .filter(|entry| entry.configuration.account == Some(account.uuid))
+            .map(|entry| entry.name.clone())
+            .collect();
+
+        let kind = if account.offline { "Offline account" } else { "Microsoft account" };
+        let username = SharedString::new(account.username.clone());
+        let uuid = account.uuid;
+
+        let mut details = v_flex()
+            .flex_1()
+            .gap_0p5()
+            .child(div().text_lg().child(username.clone()))
+            .child(div().text_sm().text_color(cx.theme().muted_foreground).child(kind))
+            .child(div().text_xs().font_family("Roboto Mono").text_color(cx.theme().muted_foreground).child(uuid.hyphenated().to_string()));
+        if !overridden_by.is_empty() {
+            let instances = overridden_by.iter().map(|name| name.as_ref()).collect::<Vec<&str>>().join(", ");
+            details = details.child(div().text_sm().child(format!("Used by: {instances}")));
+        }
+
+        let select_button = if selected {
+            Button::new(("select", uuid.as_u128() as u64)).label("Active").info().disabled(true)
+        } else {
+            Button::new(("select", uuid.as_u128() as u64)).label("Use").success().on_click({
+                let backend_handle = self.backend_handle.clone();
+                move |_, window, cx| {
+                    let select_backend_handle = backend_handle.clone();
+                    crate::launcher_lock::when_unlocked(&backend_handle, window, cx, move |_, _| {
+                        select_backend_handle.send(MessageToBackend::SelectAccount { uuid });
+                    });
+                }
+            })
+        };
+
+        let remove_button = Button::new(("remove", uuid.as_u128() as u64))
+            .icon(Icon::default().path("icons/trash-2.svg"))
+            .danger()
+            .on_click({
+                let backend_handle = self.backend_handle.clone();
+                move |_, window, cx| {
+                    let backend_handle = backend_handle.clone();
+                    let username = username.clone();
+                    crate::launcher_lock::when_unlocked(&backend_handle.clone(), window, cx, move |window, cx| window.open_dialog(cx, move |dialog, _, _| {
+                        let backend_handle = backend_handle.clone();
+                        dialog
+                            .confirm()
+                            .title("Remove account")
+                            .child(format!("Are you sure you want to remove {username}? Instances using this account will fall back to the active account."))
+                            .on_ok(move |_, _, _| {
+                                backend_handle.send(MessageToBackend::DeleteAccount { uuid });
+                                true
+                            })
+                    }));
+                }
+            });
+
+        h_flex()
+            .w_full()
+            .gap_3()
+            .p_3()
+            .border_1()
+            .rounded(cx.theme().radius)
+            .border_color(if selected { cx.theme().info } else { cx.theme().border })
+            .child(head.size_12().min_w_12().min_h_12())
+            .child(details)
+            .child(select_button)
+            .child(remove_button)
+    }
+}
+
+impl Render for AccountsPage {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let (accounts, selected_account) = {
+            let accounts = self.accounts.read(cx);
+            (accounts.accounts.clone(), accounts.selected_account_uuid)
+        };
+
+        let add_buttons = h_flex()
+            .gap_2()
+            .child(Button::new("add-account").success().icon(IconName::Plus).label("Add Microsoft account").on_click({
+                let backend_handle = self.backend_handle.clone();
+                move |_, window, cx| {
+                    crate::root::start_new_account_login(&backend_handle, window, cx);
+                }
+            }))
+            .child(Button::new("add-offline").success().icon(IconName::Plus).label("Add offline account").on_click({
+                let backend_handle = self.backend_handle.clone();
+                move |_, window, cx| {
+                    crate::modals::add_offline_account::open_add_offline_account(backend_handle.clone(), window, cx);
+                }
+            }));
+
+        let mut content = v_flex()
+            .size_full()
+            .p_3()
+            .gap_3()
+            .child("The active account is used to launch instances, unless an instance picks a different account in its settings")
+            .child(add_buttons);
+
+        if accounts.is_empty() {
+            content = content.child("No accounts have been added");
+        }
+        for account in accounts.iter() {
+            let selected = Some(account.uuid) == selected_account;
+            content = content.child(self.render_account(account, selected, cx));
+        }
+
+        ui::scroll_page(cx, h_flex().gap_8().child("Accounts"), &self.scroll_handle, content)
+    }
+}
@@ -0,0 +1,185 @@
+use std::{path::Path, str::FromStr, sync::Arc, time::Duration};
+
+use bridge::{handle::BackendHandle, message::MessageToBackend, modal_action::ModalAction};
+use gpui::{prelude::*, *};
+use gpui_component::{button::{Button, ButtonGroup, ButtonVariants}, h_flex, v_flex, ActiveTheme as _, Selectable, Sizable};
+
+use crate::{entity::DataEntities, ts, ui};
+
+/// How often the launcher log is read again while the page is open
+const LOG_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Lines of the launcher log that are shown, older ones are only in the file
+const MAX_LOG_LINES: usize = 1000;
+
+/// Tools for contributors, not shown in release builds
+pub struct DebugPage {
+    backend_handle: BackendHandle,
+    log: Arc<str>,
+    log_lines: Vec<(tracing::Level, SharedString)>,
+    /// Least severe level that is shown
+    log_level: tracing::Level,
+    log_scroll_handle: ScrollHandle,
+    _tail_log_task: Task<()>,
+}
+
+impl DebugPage {
+    pub fn new(data: &DataEntities, _window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let backend_handle = data.backend_handle.clone();
+        let _tail_log_task = cx.spawn({
+            let backend_handle = backend_handle.clone();
+            async move |page, cx| {
+                loop {
+                    let (send, recv) = tokio::sync::oneshot::channel();
+                    backend_handle.send(MessageToBackend::GetLauncherLog { channel: send });
+                    let Ok(log) = recv.await else {
+                        return;
+                    };
+                    let updated = page.update(cx, |page, cx| {
+                        page.set_log(log);
+                        cx.notify();
+                    });
+                    if updated.is_err() {
+                        return;
+                    }
+                    cx.background_executor().timer(LOG_POLL_INTERVAL).await;
+                }
+            }
+        });
+
+        Self {
+            backend_handle,
+            log: Arc::from(""),
+            log_lines: Vec::new(),
+            log_level: tracing::Level::INFO,
+            log_scroll_handle: ScrollHandle::new(),
+            _tail_log_task,
+        }
+    }
+
+    fn set_log(&mut self, log: Arc<str>) {
+        if log == self.log {
+            return;
+        }
+        // Only follow new lines when already at the end, so reading further up isn't interrupted
+        let at_end = -self.log_scroll_handle.offset().y >= self.log_scroll_handle.max_offset().height - px(1.0);
+        self.log_lines = parse_log(&log);
+        self.log = log;
+        if at_end {
+            self.log_scroll_handle.scroll_to_bottom();
+        }
+    }
+
+    fn render_log(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let levels = [tracing::Level::ERROR, tracing::Level::WARN, tracing::Level::INFO, tracing::Level::DEBUG, tracing::Level::TRACE];
+        let level_filter = levels.iter().fold(ButtonGroup::new("log-level").outline().small(), |group, level| {
+            group.child(Button::new(level.as_str()).label(level.as_str()).selected(*level == self.log_level))
+        }).on_click(cx.listener(move |page, selected: &Vec<usize>, _, cx| {
+            if let Some(level) = selected.first().and_then(|index| levels.get(*index)) {
+                page.log_level = *level;
+                page.log_scroll_handle.scroll_to_bottom();
+                cx.notify();
+            }
+        }));
+
+        let mut lines: Vec<_> = self.log_lines.iter().filter(|(level, _)| *level <= self.log_level).collect();
+        lines.drain(..lines.len().saturating_sub(MAX_LOG_LINES));
+
+        let lines = lines.into_iter().map(|(level, line)| {
+            let color = match *level {
+                tracing::Level::ERROR => cx.theme().danger,
+                tracing::Level::WARN => cx.theme().warning,
+                tracing::Level::INFO => cx.theme().foreground,
+                _ => cx.theme().muted_foreground,
+            };
+            div().text_color(color).child(line.clone())
+        });
+
+        v_flex()
+            .flex_1()
+            .min_h_0()
+            .gap_2()
+            .child(h_flex().gap_2().child(div().flex_1().text_lg().child(ts!("debug_launcher_log"))).child(level_filter))
+            .child(div()
+                .id("launcher-log")
+                .flex_1()
+                .p_2()
+                .border_1()
+                .border_color(cx.theme().border)
+                .rounded(cx.theme().radius)
+                .overflow_y_scroll()
+                .track_scroll(&self.log_scroll_handle)
+                .font_family("Roboto Mono")
+                .text_xs()
+                .children(lines))
+    }
+}
+
+/// Splits the log into lines with the level they were logged at. Lines that don't start with the
+/// `[time level target]` header, such as backtraces, belong to the line before them
+fn parse_log(log: &str) -> Vec<(tracing::Level, SharedString)> {
+    let mut level = tracing::Level::INFO;
+    log.lines().map(|line| {
+        let header_level = line.strip_prefix('[')
+            .and_then(|header| header.split_whitespace().nth(1))
+            .and_then(|level| tracing::Level::from_str(level).ok());
+        if let Some(header_level) = header_level {
+            level = header_level;
+        }
+        (level, SharedString::from(line.to_owned()))
+    }).collect()
+}
+
+impl Render for DebugPage {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let validate_metadata = Button::new("validate_metadata").label("Validate All Metadata").success().on_click({
+            let backend_handle = self.backend_handle.clone();
+            move |_, window, cx| {
+                let user_dirs = directories::UserDirs::new();
+                let directory = user_dirs.as_ref()
+                    .and_then(directories::UserDirs::download_dir).unwrap_or(Path::new("."));
+
+                let receiver = cx.prompt_for_new_path(directory, Some("metadata_report.json"));
+                let backend_handle = backend_handle.clone();
+                window.spawn(cx, async move |cx| {
+                    let Ok(Ok(Some(report_path))) = receiver.await else {
+                        return;
+                    };
+                    _ = cx.update(|window, cx| {
+                        let modal_action = ModalAction::default();
+                        backend_handle.send(MessageToBackend::ValidateAllMetadata { report_path, modal_action: modal_action.clone() });
+                        crate::modals::generic::show_modal(window, cx, ts!("debug_validating_metadata"),
+                            ts!("debug_validating_metadata_error"), modal_action);
+                    });
+                }).detach();
+            }
+        });
+
+        let smoke_test = Button::new("smoke_test").label("Run Smoke Test").success().on_click({
+            let backend_handle = self.backend_handle.clone();
+            move |_, window, cx| {
+                let modal_action = ModalAction::default();
+                backend_handle.send(MessageToBackend::SmokeTest { minecraft_version: None, modal_action: modal_action.clone() });
+                crate::modals::generic::show_modal(window, cx, ts!("debug_running_smoke_test"),
+                    ts!("debug_smoke_test_failed"), modal_action);
+            }
+        });
+
+        ui::page(cx, "Debug").child(v_flex()
+            .flex_1()
+            .min_h_0()
+            .p_4()
+            .gap_2()
+            .child(div().text_lg().child("Metadata"))
+            .child(div().text_sm().text_color(cx.theme().muted_foreground).child("Downloads every version, assets index and Java \
+                runtime manifest and checks them against the schemas. The report lists unknown fields with the versions they \
+                appear in, as well as argument expansions the launcher doesn't support."))
+            .child(validate_metadata)
+            .child(div().text_lg().child("Smoke Test"))
+            .child(div().text_sm().text_color(cx.theme().muted_foreground).child("Launches a throwaway instance of the \
+                latest release, downloading everything it needs, but only starts Java with -version instead of the game. \
+                Checks that the network, disk and Java work without opening the game."))
+            .child(smoke_test)
+            .child(self.render_log(cx)))
+    }
+}
@@ -1,19 +1,23 @@
 use bridge::{
     handle::BackendHandle,
     instance::{InstanceID, InstanceStatus},
-    message::MessageToBackend,
+    message::{LaunchMods, MessageToBackend},
 };
 use gpui::{prelude::*, *};
 use gpui_component::{
-    button::{Button, ButtonVariants}, h_flex, tab::{Tab, TabBar}, Icon, IconName
+    button::{Button, ButtonVariants}, checkbox::Checkbox, h_flex, tab::{Tab, TabBar}, Icon, IconName, Sizable
 };
+use schema::loader::Loader;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     component::page_path::PagePath, entity::{DataEntities, instance::InstanceEntry}, pages::instance::{logs_subpage::InstanceLogsSubpage, mods_subpage::InstanceModsSubpage, quickplay_subpage::InstanceQuickplaySubpage, resource_packs_subpage::InstanceResourcePacksSubpage, settings_subpage::InstanceSettingsSubpage}, root, ui
 };
 
+actions!([RelaunchInstance]);
+
 pub struct InstancePage {
+    focus_handle: FocusHandle,
     page_path: PagePath,
     backend_handle: BackendHandle,
     data: DataEntities,
@@ -27,7 +31,13 @@ impl InstancePage {
 
         let subpage = subpage.create(&instance, data, data.backend_handle.clone(), window, cx);
 
+        data.backend_handle.send(MessageToBackend::PrewarmInstance { id: instance_id });
+
+        let focus_handle = cx.focus_handle();
+        focus_handle.focus(window, cx);
+
         Self {
+            focus_handle,
             page_path,
             backend_handle: data.backend_handle.clone(),
             data: data.clone(),
@@ -36,12 +46,29 @@ impl InstancePage {
         }
     }
 
-    fn set_subpage(&mut self, page_type: InstanceSubpageType, window: &mut Window, cx: &mut Context<Self>) {
+    pub(crate) fn set_subpage(&mut self, page_type: InstanceSubpageType, window: &mut Window, cx: &mut Context<Self>) {
         if page_type == self.subpage.page_type() {
             return;
         }
         self.subpage = page_type.create(&self.instance, &self.data, self.backend_handle.clone(), window, cx);
     }
+
+    /// Kills the game if it's running and starts it again, for pack developers testing changes synced from their pack
+    /// source
+    fn relaunch(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let instance = self.instance.read(cx);
+        if instance.configuration.pack_source.is_none() {
+            return;
+        }
+        let id = instance.id;
+        let name = instance.name.clone();
+        match instance.status {
+            InstanceStatus::Launching => return,
+            InstanceStatus::Running => self.backend_handle.send(MessageToBackend::KillInstance { id }),
+            InstanceStatus::NotRunning | InstanceStatus::Crashed => {},
+        }
+        root::start_instance(id, name, None, &self.backend_handle, window, cx);
+    }
 }
 
 impl Render for InstancePage {
@@ -61,8 +88,35 @@ impl Render for InstancePage {
         let name = instance.name.clone();
         let backend_handle = self.backend_handle.clone();
 
+        // Mods can only cause a crash in modded instances
+        let can_diagnose_mods = instance.configuration.loader != Loader::Vanilla
+            && matches!(instance.status, InstanceStatus::NotRunning | InstanceStatus::Crashed);
+        let safe_mode_button = can_diagnose_mods
+            .then(|| Button::new("start_safe_mode")
+                .warning()
+                .label("Safe Mode")
+                .tooltip("Start without any mods to check whether a crash is caused by one")
+                .on_click({
+                    let name = name.clone();
+                    let backend_handle = self.backend_handle.clone();
+                    move |_, window, cx| {
+                        root::start_instance_with_mods(id, name.clone(), None, LaunchMods::SafeMode, &backend_handle, window, cx);
+                    }
+                }));
+        let bisection_button = can_diagnose_mods
+            .then(|| Button::new("mod_bisection")
+                .label("Find Crashing Mod")
+                .tooltip("Launch repeatedly with half of the mods to find the one causing a crash")
+                .on_click({
+                    let name = name.clone();
+                    let backend_handle = self.backend_handle.clone();
+                    move |_, window, cx| {
+                        crate::modals::mod_bisection::open_mod_bisection(id, name.clone(), None, backend_handle.clone(), window, cx);
+                    }
+                }));
+
         let button = match instance.status {
-            InstanceStatus::NotRunning => {
+            InstanceStatus::NotRunning | InstanceStatus::Crashed => {
                 Button::new("start_instance").success().icon(play_icon).label("Start Instance").on_click(
                     move |_, window, cx| {
                         root::start_instance(id, name.clone(), None, &backend_handle, window, cx);
@@ -70,7 +124,11 @@ impl Render for InstancePage {
                 )
             },
             InstanceStatus::Launching => {
-                Button::new("launching").warning().icon(IconName::Loader).label("Launching...")
+                Button::new("launching").warning().icon(IconName::Loader).label("Launching...").on_click(
+                    move |_, window, cx| {
+                        root::show_launch_progress(id, name.clone(), &backend_handle, window, cx);
+                    },
+                )
             },
             InstanceStatus::Running => Button::new("kill_instance")
                 .danger()
@@ -81,6 +139,15 @@ impl Render for InstancePage {
                 }),
         };
 
+        let relaunch_button = (instance.configuration.pack_source.is_some() && instance.status == InstanceStatus::Running)
+            .then(|| Button::new("relaunch_instance")
+                .warning()
+                .label("Restart")
+                .tooltip("Kill and start the game again to test changes from the pack source (Ctrl+R)")
+                .on_click(cx.listener(|page, _, window, cx| {
+                    page.relaunch(window, cx);
+                })));
+
         let open_dot_minecraft_button = Button::new("open_dot_minecraft")
             .info()
             .icon(IconName::FolderOpen)
@@ -92,14 +159,41 @@ impl Render for InstancePage {
             }
         });
 
+        let lan = instance.process.and_then(|process| process.lan)
+            .filter(|_| instance.status == InstanceStatus::Running)
+            .map(|lan| {
+                let address = match lan.public_address {
+                    Some(public_address) => format!("{} (public {public_address})", lan.local_address),
+                    None => lan.local_address.to_string(),
+                };
+                let copied = lan.public_address.map(|address| address.to_string()).unwrap_or_else(|| lan.local_address.to_string());
+                h_flex()
+                    .gap_2()
+                    .child(format!("Open to LAN: {address}"))
+                    .child(Button::new("copy_lan_address").small().icon(IconName::Copy).tooltip("Copy address").on_click(
+                        move |_, _, cx| {
+                            cx.write_to_clipboard(ClipboardItem::new_string(copied.clone()));
+                        },
+                    ))
+                    .child(Checkbox::new("lan_port_forwarding")
+                        .label("Forward port with UPnP")
+                        .checked(lan.port_forwarded)
+                        .on_click({
+                            let backend_handle = self.backend_handle.clone();
+                            move |checked, _, _| {
+                                backend_handle.send(MessageToBackend::SetLanPortForwarding { id, enabled: *checked });
+                            }
+                        }))
+            });
+
         let breadcrumb = self.page_path.create_breadcrumb(&self.data, cx);
-        ui::page(cx, h_flex().gap_8().child(breadcrumb).child(h_flex().gap_3().child(button).child(open_dot_minecraft_button)))
+        ui::page(cx, h_flex().gap_8().child(breadcrumb).child(h_flex().gap_3().child(button).children(relaunch_button).children(safe_mode_button).children(bisection_button).child(open_dot_minecraft_button)).children(lan))
             .child(
                 TabBar::new("bar")
                     .prefix(div().w_4())
                     .selected_index(selected_index)
                     .underline()
-                    .child(Tab::new().label("Quickplay"))
+                    .child(Tab::new().label("Overview"))
                     .child(Tab::new().label("Logs"))
                     .child(Tab::new().label("Mods"))
                     .child(Tab::new().label("Resource Packs"))
@@ -119,6 +213,11 @@ impl Render for InstancePage {
                     })),
             )
             .child(self.subpage.clone().into_any_element())
+            .key_context("InstancePage")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(|page, _: &RelaunchInstance, window, cx| {
+                page.relaunch(window, cx);
+            }))
     }
 }
 
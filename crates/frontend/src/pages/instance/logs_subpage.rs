@@ -7,6 +7,7 @@ use gpui::{prelude::*, *};
 use gpui_component::{
     button::{Button, ButtonVariants}, h_flex, select::{Select, SelectEvent, SelectState}, spinner::Spinner, v_flex, ActiveTheme as _, Sizable
 };
+use schema::instance::{InstanceTimelineEvent, InstanceTimelineEventKind};
 
 use crate::{component::{named_dropdown::{NamedDropdown, NamedDropdownItem}, readonly_text_field::{ReadonlyTextField, ReadonlyTextFieldWithControls}}, entity::instance::InstanceEntry, root};
 
@@ -18,6 +19,8 @@ pub struct InstanceLogsSubpage {
     available_logs: Option<Entity<SelectState<NamedDropdown<Arc<Path>>>>>,
     clean_old_logs_text: Option<SharedString>,
     last_selected_path: Option<Arc<Path>>,
+    timeline: Arc<[InstanceTimelineEvent]>,
+    _get_timeline_task: Task<()>,
     _read_log_task: Option<Task<()>>,
     _get_log_files_task: Task<()>,
     _dropdown_change_subscrption: Option<Subscription>,
@@ -41,6 +44,8 @@ impl InstanceLogsSubpage {
             available_logs: None,
             clean_old_logs_text: None,
             last_selected_path: None,
+            timeline: Arc::from([]),
+            _get_timeline_task: Task::ready(()),
             _read_log_task: None,
             _get_log_files_task: Task::ready(()),
             _dropdown_change_subscrption: None,
@@ -130,16 +135,7 @@ impl InstanceLogsSubpage {
                     page.available_logs = Some(dropdown);
 
                     if result.total_gzipped_size > 0 {
-                        let bytes = result.total_gzipped_size;
-                        let string = if bytes < 1000 {
-                            format!("Cleanup old log files ({} bytes)", bytes)
-                        } else if bytes < 1000*1000 {
-                            format!("Cleanup old log files ({}kB)", bytes/1000)
-                        } else if bytes < 1000*1000*1000 {
-                            format!("Cleanup old log files ({}MB)", bytes/1000/1000)
-                        } else {
-                            format!("Cleanup old log files ({}GB)", bytes/1000/1000/1000)
-                        };
+                        let string = format!("Cleanup old log files ({})", crate::format::bytes(result.total_gzipped_size as u64));
                         page.clean_old_logs_text = Some(string.into());
                     }
                 }
@@ -151,9 +147,55 @@ impl InstanceLogsSubpage {
             instance: self.instance,
             channel: send,
         });
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self._get_timeline_task = cx.spawn(async move |page, cx| {
+            let Ok(timeline) = recv.await else {
+                return;
+            };
+            let _ = page.update(cx, move |page, cx| {
+                page.timeline = timeline;
+                cx.notify();
+            });
+        });
+        self.backend_handle.send(MessageToBackend::GetInstanceTimeline {
+            id: self.instance,
+            channel: send,
+        });
+    }
+
+    fn render_timeline(&self, cx: &App) -> Option<Div> {
+        if self.timeline.is_empty() {
+            return None;
+        }
+
+        let mut timeline = v_flex()
+            .gap_0p5()
+            .mb_2()
+            .ml_1()
+            .child(div().text_lg().child("Timeline"));
+
+        for event in self.timeline.iter().rev().take(MAX_SHOWN_TIMELINE_EVENTS) {
+            let time = crate::format::date_time(event.time);
+            let kind = match event.kind {
+                InstanceTimelineEventKind::Seed => "Seed",
+                InstanceTimelineEventKind::Death => "Death",
+                InstanceTimelineEventKind::Coordinates => "Coordinates",
+            };
+            timeline = timeline.child(h_flex()
+                .gap_3()
+                .text_sm()
+                .child(div().w_40().text_color(cx.theme().muted_foreground).child(time))
+                .child(div().w_24().text_color(cx.theme().muted_foreground).child(kind))
+                .child(SharedString::new(event.text.clone())));
+        }
+
+        Some(timeline)
     }
 }
 
+const MAX_SHOWN_TIMELINE_EVENTS: usize = 8;
+
 impl Render for InstanceLogsSubpage {
     fn render(&mut self, _window: &mut gpui::Window, cx: &mut gpui::Context<Self>) -> impl gpui::IntoElement {
         let theme = cx.theme();
@@ -197,6 +239,6 @@ impl Render for InstanceLogsSubpage {
             }));
         }
 
-        v_flex().p_4().size_full().child(header).child(content)
+        v_flex().p_4().size_full().children(self.render_timeline(cx)).child(header).child(content)
     }
 }
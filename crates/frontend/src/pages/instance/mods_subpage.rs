@@ -3,7 +3,7 @@ use std::{hash::{DefaultHasher, Hash, Hasher}, path::Path, sync::{
 }};
 
 use bridge::{
-    handle::BackendHandle, install::{ContentDownload, ContentInstall, ContentInstallFile, InstallTarget}, instance::{AtomicContentUpdateStatus, InstanceID, InstanceContentID, InstanceContentSummary, ContentType, ContentSummary}, message::{AtomicBridgeDataLoadState, MessageToBackend}, serial::AtomicOptionSerial
+    handle::BackendHandle, install::{ContentDownload, ContentInstall, ContentInstallFile, InstallTarget}, instance::{AtomicContentUpdateStatus, InstanceID, InstanceContentID, InstanceContentSummary, ContentType, ContentSummary}, message::{AtomicBridgeDataLoadState, MessageToBackend, ModListFormat}, modal_action::ModalAction, serial::AtomicOptionSerial
 };
 use gpui::{prelude::*, *};
 use gpui_component::{
@@ -14,7 +14,7 @@ use rustc_hash::FxHashSet;
 use schema::{content::ContentSource, loader::Loader, modrinth::ModrinthProjectType};
 use ustr::Ustr;
 
-use crate::{component::content_list::ContentListDelegate, entity::instance::InstanceEntry, interface_config::InterfaceConfig, png_render_cache, root, ui::PageType};
+use crate::{component::content_list::ContentListDelegate, entity::instance::InstanceEntry, interface_config::InterfaceConfig, png_render_cache, root, ts, ui::PageType};
 
 use super::instance_page::InstanceSubpageType;
 
@@ -155,7 +155,52 @@ impl Render for InstanceModsSubpage {
                     });
                     this._add_from_file_task = Some(add_from_file_task);
                 })
-            }));
+            }))
+            .child(Button::new("copymodlist").label("Copy mod list").info().compact().small()
+                .tooltip("Copies the enabled mods as a Markdown table")
+                .on_click({
+                    let backend_handle = self.backend_handle.clone();
+                    let instance = self.instance;
+                    move |_, window, cx| {
+                        let (send, recv) = tokio::sync::oneshot::channel();
+                        backend_handle.send(MessageToBackend::GetModList { id: instance, format: ModListFormat::Markdown, channel: send });
+                        window.spawn(cx, async move |cx| {
+                            let Ok(Some(mod_list)) = recv.await else {
+                                return;
+                            };
+                            _ = cx.update(|window, cx| {
+                                cx.write_to_clipboard(ClipboardItem::new_string(mod_list.to_string()));
+                                window.push_notification((NotificationType::Success, ts!("mods_copied_mod_list")), cx);
+                            });
+                        }).detach();
+                    }
+                }))
+            .child(Button::new("exportmodlist").label("Export mod list").info().compact().small()
+                .tooltip("Saves the enabled mods as Markdown, or as CSV when saved with a .csv extension")
+                .on_click({
+                    let backend_handle = self.backend_handle.clone();
+                    let instance = self.instance;
+                    let instance_title = self.instance_title.clone();
+                    move |_, window, cx| {
+                        let user_dirs = directories::UserDirs::new();
+                        let directory = user_dirs.as_ref()
+                            .and_then(directories::UserDirs::documents_dir).unwrap_or(Path::new("."));
+
+                        let receiver = cx.prompt_for_new_path(directory, Some(&format!("{instance_title} mods.{}", ModListFormat::Markdown.extension())));
+                        let backend_handle = backend_handle.clone();
+                        window.spawn(cx, async move |cx| {
+                            let Ok(Ok(Some(path))) = receiver.await else {
+                                return;
+                            };
+                            _ = cx.update(|window, cx| {
+                                let modal_action = ModalAction::default();
+                                backend_handle.send(MessageToBackend::ExportModList { id: instance, path, modal_action: modal_action.clone() });
+                                crate::modals::generic::show_modal(window, cx, ts!("mods_exporting_mod_list"),
+                                    ts!("mods_export_mod_list_error"), modal_action);
+                            });
+                        }).detach();
+                    }
+                }));
 
         v_flex().p_4().size_full().child(header).child(
             div()
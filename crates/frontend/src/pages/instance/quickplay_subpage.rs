@@ -13,6 +13,7 @@ use gpui_component::{
     ActiveTheme as _, Icon, IndexPath,
     button::{Button, ButtonVariants},
     h_flex,
+    input::{Input, InputState},
     list::{ListDelegate, ListItem, ListState},
     v_flex,
 };
@@ -21,6 +22,7 @@ use crate::{entity::instance::InstanceEntry, png_render_cache, root};
 
 pub struct InstanceQuickplaySubpage {
     instance: InstanceID,
+    entry: Entity<InstanceEntry>,
     backend_handle: BackendHandle,
     worlds_state: Arc<AtomicBridgeDataLoadState>,
     world_list: Entity<ListState<WorldsListDelegate>>,
@@ -28,6 +30,7 @@ pub struct InstanceQuickplaySubpage {
     server_list: Entity<ListState<ServersListDelegate>>,
     worlds_serial: AtomicOptionSerial,
     servers_serial: AtomicOptionSerial,
+    realm_input: Entity<InputState>,
 }
 
 impl InstanceQuickplaySubpage {
@@ -37,6 +40,7 @@ impl InstanceQuickplaySubpage {
         mut window: &mut gpui::Window,
         cx: &mut gpui::Context<Self>,
     ) -> Self {
+        let entry = instance.clone();
         let instance = instance.read(cx);
         let instance_id = instance.id;
 
@@ -48,7 +52,8 @@ impl InstanceQuickplaySubpage {
             name: instance.name.clone(),
             backend_handle: backend_handle.clone(),
             worlds: instance.worlds.read(cx).to_vec(),
-            searched: instance.worlds.read(cx).to_vec(),
+            searched: (0..instance.worlds.read(cx).len()).collect(),
+            last_query: SharedString::default(),
         };
 
         let servers_list_delegate = ServersListDelegate {
@@ -56,19 +61,21 @@ impl InstanceQuickplaySubpage {
             name: instance.name.clone(),
             backend_handle: backend_handle.clone(),
             servers: instance.servers.read(cx).to_vec(),
-            searched: instance.servers.read(cx).to_vec(),
+            searched: (0..instance.servers.read(cx).len()).collect(),
+            last_query: SharedString::default(),
         };
 
         let worlds = instance.worlds.clone();
         let servers = instance.servers.clone();
 
+        let realm_input = cx.new(|cx| InputState::new(window, cx).placeholder("Realm ID"));
+
         let window2 = &mut window;
         let world_list = cx.new(move |cx| {
             cx.observe(&worlds, |list: &mut ListState<WorldsListDelegate>, worlds, cx| {
-                let worlds = worlds.read(cx).to_vec();
                 let delegate = list.delegate_mut();
-                delegate.worlds = worlds.clone();
-                delegate.searched = worlds;
+                delegate.worlds = worlds.read(cx).to_vec();
+                delegate.apply_search();
                 cx.notify();
             }).detach();
 
@@ -77,18 +84,20 @@ impl InstanceQuickplaySubpage {
 
         let server_list = cx.new(move |cx| {
             cx.observe(&servers, |list: &mut ListState<ServersListDelegate>, servers, cx| {
-                let servers = servers.read(cx).to_vec();
                 let delegate = list.delegate_mut();
-                delegate.servers = servers.clone();
-                delegate.searched = servers;
+                delegate.servers = servers.read(cx).to_vec();
+                delegate.apply_search();
                 cx.notify();
             }).detach();
 
             ListState::new(servers_list_delegate, window, cx).selectable(false).searchable(true)
         });
 
+        cx.observe(&entry, |_, _, cx| cx.notify()).detach();
+
         Self {
             instance: instance_id,
+            entry,
             backend_handle,
             worlds_state,
             world_list,
@@ -96,12 +105,14 @@ impl InstanceQuickplaySubpage {
             server_list,
             worlds_serial: AtomicOptionSerial::default(),
             servers_serial: AtomicOptionSerial::default(),
+            realm_input,
         }
     }
 }
 
 impl Render for InstanceQuickplaySubpage {
     fn render(&mut self, _window: &mut gpui::Window, cx: &mut gpui::Context<Self>) -> impl gpui::IntoElement {
+        let overview = self.render_overview(cx);
         let theme = cx.theme();
 
         let state = self.worlds_state.load(Ordering::SeqCst);
@@ -117,7 +128,7 @@ impl Render for InstanceQuickplaySubpage {
         let worlds_header = div().mb_1().ml_1().text_lg().child("Worlds");
         let servers_header = div().mb_1().ml_1().text_lg().child("Servers");
 
-        v_flex().p_4().gap_4().size_full().child(
+        v_flex().p_4().gap_4().size_full().child(overview).child(
             h_flex()
                 .size_full()
                 .gap_4()
@@ -147,12 +158,95 @@ impl Render for InstanceQuickplaySubpage {
     }
 }
 
+impl InstanceQuickplaySubpage {
+    fn render_overview(&self, cx: &mut gpui::Context<Self>) -> impl IntoElement {
+        let entry = self.entry.read(cx);
+
+        let mut details = format!("Minecraft {} · {}", entry.configuration.minecraft_version, entry.configuration.loader.name());
+        let mod_count = entry.mods.read(cx).len();
+        if mod_count > 0 {
+            details.push_str(&format!(" · {} mods", mod_count));
+        }
+
+        let play_icon = Icon::empty().path("icons/play.svg");
+
+        let mut shortcuts = h_flex().gap_2();
+
+        let recent_world = entry.worlds.read(cx).iter().max_by_key(|world| world.last_played).cloned();
+        if let Some(world) = recent_world {
+            let id = self.instance;
+            let name = entry.name.clone();
+            let backend_handle = self.backend_handle.clone();
+            let target = world.level_path.file_name().unwrap().to_owned();
+            shortcuts = shortcuts.child(Button::new("recent_world")
+                .success()
+                .icon(play_icon.clone())
+                .label(SharedString::new(format!("Continue {}", world.title)))
+                .on_click(move |_, window, cx| {
+                    root::start_instance(id, name.clone(), Some(QuickPlayLaunch::Singleplayer(target.clone())), &backend_handle, window, cx);
+                }));
+        }
+
+        let first_server = entry.servers.read(cx).first().cloned();
+        if let Some(server) = first_server {
+            let id = self.instance;
+            let name = entry.name.clone();
+            let backend_handle = self.backend_handle.clone();
+            let target = OsString::from(server.ip.to_string());
+            shortcuts = shortcuts.child(Button::new("first_server")
+                .info()
+                .icon(play_icon)
+                .label(SharedString::new(format!("Join {}", server.name)))
+                .on_click(move |_, window, cx| {
+                    root::start_instance(id, name.clone(), Some(QuickPlayLaunch::Multiplayer(target.clone())), &backend_handle, window, cx);
+                }));
+        }
+
+        let join_realm = {
+            let id = self.instance;
+            let name = entry.name.clone();
+            let backend_handle = self.backend_handle.clone();
+            let realm_input = self.realm_input.clone();
+            Button::new("join_realm")
+                .info()
+                .icon(Icon::empty().path("icons/play.svg"))
+                .label("Join Realm")
+                .on_click(move |_, window, cx| {
+                    let realm = realm_input.read(cx).value();
+                    let realm = realm.trim();
+                    if realm.is_empty() {
+                        return;
+                    }
+                    root::start_instance(id, name.clone(), Some(QuickPlayLaunch::Realms(realm.into())), &backend_handle, window, cx);
+                })
+        };
+        shortcuts = shortcuts.child(div().w_32().child(Input::new(&self.realm_input).small())).child(join_realm);
+
+        h_flex()
+            .justify_between()
+            .child(div().text_color(cx.theme().muted_foreground).child(details))
+            .child(shortcuts)
+    }
+}
+
 pub struct WorldsListDelegate {
     id: InstanceID,
     name: SharedString,
     backend_handle: BackendHandle,
     worlds: Vec<InstanceWorldSummary>,
-    searched: Vec<InstanceWorldSummary>,
+    /// Indices into `worlds` matching `last_query`, so large lists aren't cloned on every keystroke
+    searched: Vec<usize>,
+    last_query: SharedString,
+}
+
+impl WorldsListDelegate {
+    fn apply_search(&mut self) {
+        let query = self.last_query.to_lowercase();
+        self.searched = self.worlds.iter().enumerate()
+            .filter(|(_, world)| query.is_empty() || world.title.to_lowercase().contains(&query))
+            .map(|(index, _)| index)
+            .collect();
+    }
 }
 
 impl ListDelegate for WorldsListDelegate {
@@ -163,7 +257,7 @@ impl ListDelegate for WorldsListDelegate {
     }
 
     fn render_item(&mut self, ix: IndexPath, _window: &mut Window, cx: &mut Context<ListState<Self>>) -> Option<Self::Item> {
-        let summary = self.searched.get(ix.row)?;
+        let summary = self.worlds.get(*self.searched.get(ix.row)?)?;
 
         let icon = if let Some(png_icon) = summary.png_icon.as_ref() {
             png_render_cache::render(Arc::clone(png_icon), cx)
@@ -171,6 +265,12 @@ impl ListDelegate for WorldsListDelegate {
             gpui::img(ImageSource::Resource(Resource::Embedded("images/default_world.png".into())))
         };
 
+        let subtitle = if summary.last_played > 0 {
+            SharedString::new(format!("{} ({})", summary.subtitle, crate::format::relative_time(summary.last_played)))
+        } else {
+            SharedString::from(summary.subtitle.clone())
+        };
+
         let description = v_flex().child(SharedString::from(summary.title.clone())).child(
             div()
                 .text_color(Hsla {
@@ -179,7 +279,7 @@ impl ListDelegate for WorldsListDelegate {
                     l: 0.5,
                     a: 1.0,
                 })
-                .child(SharedString::from(summary.subtitle.clone())),
+                .child(subtitle),
         );
 
         let play_icon = Icon::empty().path("icons/play.svg");
@@ -216,7 +316,8 @@ impl ListDelegate for WorldsListDelegate {
     }
 
     fn perform_search(&mut self, query: &str, _window: &mut Window, _cx: &mut Context<ListState<Self>>) -> Task<()> {
-        self.searched = self.worlds.iter().filter(|w| w.title.contains(query)).cloned().collect();
+        self.last_query = SharedString::new(query.trim());
+        self.apply_search();
 
         Task::ready(())
     }
@@ -227,7 +328,19 @@ pub struct ServersListDelegate {
     name: SharedString,
     backend_handle: BackendHandle,
     servers: Vec<InstanceServerSummary>,
-    searched: Vec<InstanceServerSummary>,
+    /// Indices into `servers` matching `last_query`
+    searched: Vec<usize>,
+    last_query: SharedString,
+}
+
+impl ServersListDelegate {
+    fn apply_search(&mut self) {
+        let query = self.last_query.to_lowercase();
+        self.searched = self.servers.iter().enumerate()
+            .filter(|(_, server)| query.is_empty() || server.name.to_lowercase().contains(&query) || server.ip.to_lowercase().contains(&query))
+            .map(|(index, _)| index)
+            .collect();
+    }
 }
 
 impl ListDelegate for ServersListDelegate {
@@ -238,7 +351,7 @@ impl ListDelegate for ServersListDelegate {
     }
 
     fn render_item(&mut self, ix: IndexPath, _window: &mut Window, cx: &mut Context<ListState<Self>>) -> Option<Self::Item> {
-        let summary = self.searched.get(ix.row)?;
+        let summary = self.servers.get(*self.searched.get(ix.row)?)?;
 
         let icon = if let Some(png_icon) = summary.png_icon.as_ref() {
             png_render_cache::render(Arc::clone(png_icon), cx)
@@ -284,7 +397,8 @@ impl ListDelegate for ServersListDelegate {
     }
 
     fn perform_search(&mut self, query: &str, _window: &mut Window, _cx: &mut Context<ListState<Self>>) -> Task<()> {
-        self.searched = self.servers.iter().filter(|w| w.name.contains(query)).cloned().collect();
+        self.last_query = SharedString::new(query.trim());
+        self.apply_search();
 
         Task::ready(())
     }
@@ -1,15 +1,17 @@
 use std::{borrow::Cow, cmp::Ordering, path::Path, sync::Arc};
 
 use bridge::{
-    handle::BackendHandle, instance::InstanceID, message::MessageToBackend, meta::MetadataRequest
+    handle::BackendHandle, instance::InstanceID, message::{JavaInstallation, MessageToBackend}, meta::MetadataRequest, modal_action::ModalAction
 };
 use gpui::{prelude::*, *};
 use gpui_component::{
-    ActiveTheme as _, Disableable, Selectable, Sizable, WindowExt, button::{Button, ButtonGroup, ButtonVariants}, checkbox::Checkbox, h_flex, input::{Input, InputEvent, InputState, NumberInput, NumberInputEvent}, notification::{Notification, NotificationType}, select::{SearchableVec, Select, SelectEvent, SelectState}, spinner::Spinner, v_flex
+    ActiveTheme as _, Disableable, Icon, IconName, Selectable, Sizable, WindowExt, button::{Button, ButtonGroup, ButtonVariants}, checkbox::Checkbox, h_flex, input::{Input, InputEvent, InputState, NumberInput, NumberInputEvent}, notification::{Notification, NotificationType}, select::{SearchableVec, Select, SelectEvent, SelectState}, spinner::Spinner, v_flex
 };
-use schema::{fabric_loader_manifest::FabricLoaderManifest, forge::{ForgeMavenManifest, NeoforgeMavenManifest}, instance::{InstanceJvmBinaryConfiguration, InstanceJvmFlagsConfiguration, InstanceLinuxWrapperConfiguration, InstanceMemoryConfiguration}, loader::Loader, version_manifest::MinecraftVersionManifest};
+use schema::{fabric_loader_manifest::FabricLoaderManifest, forge::{ForgeMavenManifest, NeoforgeMavenManifest}, instance::{InstanceBrandingConfiguration, InstanceColorTag, InstanceEnvironmentConfiguration, InstanceIcon, InstanceJvmBinaryConfiguration, InstanceJvmFlagsConfiguration, InstanceLinuxWrapperConfiguration, InstanceMemoryConfiguration}, loader::Loader, version_manifest::MinecraftVersionManifest};
 
-use crate::{entity::{DataEntities, instance::InstanceEntry, metadata::{AsMetadataResult, FrontendMetadata, FrontendMetadataResult, FrontendMetadataState, TypelessFrontendMetadataResult}}, interface_config::InterfaceConfig, pages::instances_page::VersionList};
+use uuid::Uuid;
+
+use crate::{component::instance_list::{tag_color, BUNDLED_INSTANCE_ICONS}, entity::{DataEntities, instance::InstanceEntry, metadata::{AsMetadataResult, FrontendMetadata, FrontendMetadataResult, FrontendMetadataState, TypelessFrontendMetadataResult}}, interface_config::InterfaceConfig, pages::instances_page::VersionList, ts};
 
 #[derive(PartialEq, Eq)]
 enum NewNameChangeState {
@@ -18,6 +20,11 @@ enum NewNameChangeState {
     Pending,
 }
 
+struct EnvironmentVariableRow {
+    key_input_state: Entity<InputState>,
+    value_input_state: Entity<InputState>,
+}
+
 pub struct InstanceSettingsSubpage {
     data: DataEntities,
     instance: Entity<InstanceEntry>,
@@ -31,10 +38,27 @@ pub struct InstanceSettingsSubpage {
     memory_override_enabled: bool,
     memory_min_input_state: Entity<InputState>,
     memory_max_input_state: Entity<InputState>,
+    /// Whether the instance has ever had its memory configured, if not then enabling the
+    /// override starts from values recommended for this system
+    memory_configured: bool,
+    system_memory_mib: Option<u64>,
+    /// Suggested from the installed memory and the instance's mods
+    recommended_memory: Option<InstanceMemoryConfiguration>,
     jvm_flags_enabled: bool,
     jvm_flags_input_state: Entity<InputState>,
+    branding_enabled: bool,
+    launcher_name_input_state: Entity<InputState>,
+    launcher_version_input_state: Entity<InputState>,
+    system_properties_input_state: Entity<InputState>,
+    environment_enabled: bool,
+    environment_rows: Vec<EnvironmentVariableRow>,
     jvm_binary_enabled: bool,
     jvm_binary_path: Option<Arc<Path>>,
+    java_installations: Vec<(SharedString, JavaInstallation)>,
+    java_installation_select_state: Entity<SelectState<SearchableVec<SharedString>>>,
+    account: Option<Uuid>,
+    playtime_limit_input_state: Entity<InputState>,
+    played_today_seconds: Option<u64>,
     #[cfg(target_os = "linux")]
     use_mangohud: bool,
     #[cfg(target_os = "linux")]
@@ -42,9 +66,15 @@ pub struct InstanceSettingsSubpage {
     #[cfg(target_os = "linux")]
     use_discrete_gpu: bool,
     #[cfg(target_os = "linux")]
+    use_prime_run: bool,
+    #[cfg(target_os = "linux")]
+    wrapper_command_input_state: Entity<InputState>,
+    #[cfg(target_os = "linux")]
     mangohud_available: bool,
     #[cfg(target_os = "linux")]
     gamemode_available: bool,
+    #[cfg(target_os = "linux")]
+    prime_run_available: bool,
     new_name_change_state: NewNameChangeState,
     backend_handle: BackendHandle,
     _observe_loader_version_subscription: Option<Subscription>,
@@ -64,10 +94,17 @@ impl InstanceSettingsSubpage {
         let loader = entry.configuration.loader;
         let preferred_loader_version = entry.configuration.preferred_loader_version.map(|s| s.as_str()).unwrap_or("Latest");
 
+        let memory_configured = entry.configuration.memory.is_some();
         let memory = entry.configuration.memory.unwrap_or_default();
         let jvm_flags = entry.configuration.jvm_flags.clone().unwrap_or_default();
         let jvm_binary = entry.configuration.jvm_binary.clone().unwrap_or_default();
-        let linux_wrapper = entry.configuration.linux_wrapper.unwrap_or_default();
+        let branding = entry.configuration.branding.clone().unwrap_or_default();
+        let environment = entry.configuration.environment.clone().unwrap_or_default();
+        let linux_wrapper = entry.configuration.linux_wrapper.clone().unwrap_or_default();
+        let account = entry.configuration.account;
+        let playtime_limit = entry.configuration.daily_playtime_limit.map(|minutes| minutes.to_string()).unwrap_or_default();
+
+        cx.observe(&data.accounts, |_, _, cx| cx.notify()).detach();
 
         let new_name_input_state = cx.new(|cx| InputState::new(window, cx));
         cx.subscribe(&new_name_input_state, Self::on_new_name_input).detach();
@@ -87,6 +124,7 @@ impl InstanceSettingsSubpage {
                     select_state.set_selected_value(&version, window, cx);
                 });
             }
+            cx.notify();
         }).detach();
 
         let loader_version_select_state = cx.new(|cx| {
@@ -107,11 +145,47 @@ impl InstanceSettingsSubpage {
         cx.subscribe_in(&memory_max_input_state, window, Self::on_memory_step).detach();
         cx.subscribe(&memory_max_input_state, Self::on_memory_changed).detach();
 
+        let java_installation_select_state = cx.new(|cx| SelectState::new(SearchableVec::new(Vec::new()), None, window, cx));
+        cx.subscribe(&java_installation_select_state, Self::on_java_installation_selected).detach();
+
         let jvm_flags_input_state = cx.new(|cx| {
             InputState::new(window, cx).auto_grow(1, 8).default_value(jvm_flags.flags)
         });
         cx.subscribe(&jvm_flags_input_state, Self::on_jvm_flags_changed).detach();
 
+        let launcher_name_input_state = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("PandoraLauncher").default_value(branding.launcher_name)
+        });
+        cx.subscribe(&launcher_name_input_state, Self::on_branding_changed).detach();
+        let launcher_version_input_state = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("1.0.0").default_value(branding.launcher_version)
+        });
+        cx.subscribe(&launcher_version_input_state, Self::on_branding_changed).detach();
+        let system_properties_input_state = cx.new(|cx| {
+            InputState::new(window, cx).auto_grow(1, 8).placeholder("key=value").default_value(branding.system_properties)
+        });
+        cx.subscribe(&system_properties_input_state, Self::on_branding_changed).detach();
+
+        // Lines that don't parse are still shown so they can be fixed, the whole line becoming the key
+        let environment_rows = environment.variables.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let (key, value) = line.trim().split_once('=').unwrap_or((line.trim(), ""));
+                Self::new_environment_row(key, value, window, cx)
+            })
+            .collect();
+
+        #[cfg(target_os = "linux")]
+        let wrapper_command_input_state = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("e.g. obs-gamecapture").default_value(linux_wrapper.custom_command.clone())
+        });
+        #[cfg(target_os = "linux")]
+        cx.subscribe(&wrapper_command_input_state, Self::on_wrapper_command_changed).detach();
+
+        let playtime_limit_input_state = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("No limit").default_value(playtime_limit)
+        });
+
         let mut page = Self {
             data: data.clone(),
             instance: instance.clone(),
@@ -124,10 +198,24 @@ impl InstanceSettingsSubpage {
             memory_override_enabled: memory.enabled,
             memory_min_input_state,
             memory_max_input_state,
+            memory_configured,
+            system_memory_mib: None,
+            recommended_memory: None,
             jvm_flags_enabled: jvm_flags.enabled,
             jvm_flags_input_state,
+            branding_enabled: branding.enabled,
+            launcher_name_input_state,
+            launcher_version_input_state,
+            system_properties_input_state,
+            environment_enabled: environment.enabled,
+            environment_rows,
             jvm_binary_enabled: jvm_binary.enabled,
             jvm_binary_path: jvm_binary.path.clone(),
+            java_installations: Vec::new(),
+            java_installation_select_state,
+            account,
+            playtime_limit_input_state,
+            played_today_seconds: None,
             #[cfg(target_os = "linux")]
             use_mangohud: linux_wrapper.use_mangohud,
             #[cfg(target_os = "linux")]
@@ -135,9 +223,15 @@ impl InstanceSettingsSubpage {
             #[cfg(target_os = "linux")]
             use_discrete_gpu: linux_wrapper.use_discrete_gpu,
             #[cfg(target_os = "linux")]
+            use_prime_run: linux_wrapper.use_prime_run,
+            #[cfg(target_os = "linux")]
+            wrapper_command_input_state,
+            #[cfg(target_os = "linux")]
             mangohud_available: Self::is_command_available("mangohud"),
             #[cfg(target_os = "linux")]
             gamemode_available: Self::is_command_available("gamemoderun"),
+            #[cfg(target_os = "linux")]
+            prime_run_available: Self::is_command_available("prime-run"),
             new_name_change_state: NewNameChangeState::NoChange,
             backend_handle,
             loader_versions_state: TypelessFrontendMetadataResult::Loading,
@@ -146,6 +240,10 @@ impl InstanceSettingsSubpage {
         };
         page.update_minecraft_versions(minecraft_versions, window, cx);
         page.update_loader_versions(window, cx);
+        page.request_system_memory(window, cx);
+        page.request_recommended_memory(window, cx);
+        page.request_java_installations(window, cx);
+        page.request_played_today(window, cx);
         page
     }
 }
@@ -382,6 +480,125 @@ impl InstanceSettingsSubpage {
         }
     }
 
+    fn request_system_memory(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::GetSystemMemory { channel: send });
+
+        let this_entity = cx.entity();
+        window.spawn(cx, async move |cx| {
+            let Ok(Some(total)) = recv.await else {
+                return;
+            };
+            _ = cx.update_window_entity(&this_entity, move |this, _, cx| {
+                this.system_memory_mib = Some(total);
+                cx.notify();
+            });
+        }).detach();
+    }
+
+    fn request_played_today(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::GetPlaytimeToday { id: self.instance_id, channel: send });
+
+        let this_entity = cx.entity();
+        window.spawn(cx, async move |cx| {
+            let Ok(seconds) = recv.await else {
+                return;
+            };
+            _ = cx.update_window_entity(&this_entity, move |this, _, cx| {
+                this.played_today_seconds = Some(seconds);
+                cx.notify();
+            });
+        }).detach();
+    }
+
+    fn save_playtime_limit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let daily_limit = self.playtime_limit_input_state.read(cx).value().trim().parse::<u32>().ok().filter(|minutes| *minutes > 0);
+        let id = self.instance_id;
+        let backend_handle = self.backend_handle.clone();
+        crate::launcher_lock::when_unlocked(&self.backend_handle, window, cx, move |_, _| {
+            backend_handle.send(MessageToBackend::SetInstancePlaytimeLimit { id, daily_limit });
+        });
+    }
+
+    fn request_java_installations(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::ScanJavaInstallations { channel: send });
+
+        let this_entity = cx.entity();
+        window.spawn(cx, async move |cx| {
+            let Ok(installations) = recv.await else {
+                return;
+            };
+            _ = cx.update_window_entity(&this_entity, move |this, window, cx| {
+                this.java_installations = installations.into_iter().map(|installation| {
+                    let mut label = format!("Java {}", installation.major_version);
+                    for detail in [&installation.version, &installation.vendor, &installation.arch].into_iter().flatten() {
+                        label.push_str(" · ");
+                        label.push_str(detail);
+                    }
+                    label.push_str(&format!(" · {}", installation.path.display()));
+                    (SharedString::from(label), installation)
+                }).collect();
+
+                let labels = this.java_installations.iter().map(|(label, _)| label.clone()).collect();
+                this.java_installation_select_state.update(cx, |select_state, cx| {
+                    select_state.set_items(SearchableVec::new(labels), window, cx);
+                });
+                cx.notify();
+            });
+        }).detach();
+    }
+
+    pub fn on_java_installation_selected(
+        &mut self,
+        _state: Entity<SelectState<SearchableVec<SharedString>>>,
+        event: &SelectEvent<SearchableVec<SharedString>>,
+        cx: &mut Context<Self>,
+    ) {
+        let SelectEvent::Confirm(Some(value)) = event else {
+            return;
+        };
+        let Some((_, installation)) = self.java_installations.iter().find(|(label, _)| label == value) else {
+            return;
+        };
+
+        self.jvm_binary_path = Some(installation.path.clone());
+        self.backend_handle.send(MessageToBackend::SetInstanceJvmBinary {
+            id: self.instance_id,
+            jvm_binary: self.get_jvm_binary_configuration(),
+        });
+        cx.notify();
+    }
+
+    fn request_recommended_memory(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::GetRecommendedMemory { id: Some(self.instance_id), channel: send });
+
+        let this_entity = cx.entity();
+        window.spawn(cx, async move |cx| {
+            let Ok(Some(recommended)) = recv.await else {
+                return;
+            };
+            _ = cx.update_window_entity(&this_entity, move |this, _, cx| {
+                this.recommended_memory = Some(recommended);
+                cx.notify();
+            });
+        }).detach();
+    }
+
+    fn apply_recommended_memory(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(recommended) = self.recommended_memory.or_else(|| self.system_memory_mib.map(InstanceMemoryConfiguration::recommended)) else {
+            return;
+        };
+        self.memory_min_input_state.update(cx, |input, cx| {
+            input.set_value(recommended.min.to_string(), window, cx);
+        });
+        self.memory_max_input_state.update(cx, |input, cx| {
+            input.set_value(recommended.max.to_string(), window, cx);
+        });
+    }
+
     fn get_memory_configuration(&self, cx: &App) -> InstanceMemoryConfiguration {
         let min = self.memory_min_input_state.read(cx).value().parse::<u32>().unwrap_or(0);
         let max = self.memory_max_input_state.read(cx).value().parse::<u32>().unwrap_or(0);
@@ -416,19 +633,107 @@ impl InstanceSettingsSubpage {
         }
     }
 
+    pub fn on_branding_changed(
+        &mut self,
+        _: Entity<InputState>,
+        event: &InputEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Change = event {
+            self.backend_handle.send(MessageToBackend::SetInstanceBranding {
+                id: self.instance_id,
+                branding: self.get_branding_configuration(cx)
+            });
+        }
+    }
+
+    fn get_branding_configuration(&self, cx: &App) -> InstanceBrandingConfiguration {
+        InstanceBrandingConfiguration {
+            enabled: self.branding_enabled,
+            launcher_name: self.launcher_name_input_state.read(cx).value().trim().into(),
+            launcher_version: self.launcher_version_input_state.read(cx).value().trim().into(),
+            system_properties: self.system_properties_input_state.read(cx).value().into(),
+        }
+    }
+
+    pub fn on_environment_changed(
+        &mut self,
+        _: Entity<InputState>,
+        event: &InputEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Change = event {
+            self.send_environment_configuration(cx);
+            cx.notify();
+        }
+    }
+
+    fn new_environment_row(key: &str, value: &str, window: &mut Window, cx: &mut Context<Self>) -> EnvironmentVariableRow {
+        let key_input_state = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("KEY").default_value(SharedString::from(key.to_owned()))
+        });
+        cx.subscribe(&key_input_state, Self::on_environment_changed).detach();
+        let value_input_state = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("value").default_value(SharedString::from(value.to_owned()))
+        });
+        cx.subscribe(&value_input_state, Self::on_environment_changed).detach();
+        EnvironmentVariableRow { key_input_state, value_input_state }
+    }
+
+    /// The key and value of each row, leaving out rows that are entirely empty
+    fn get_environment_variables(&self, cx: &App) -> Vec<(SharedString, SharedString)> {
+        self.environment_rows.iter()
+            .map(|row| (row.key_input_state.read(cx).value(), row.value_input_state.read(cx).value()))
+            .filter(|(key, value)| !key.trim().is_empty() || !value.is_empty())
+            .collect()
+    }
+
+    fn get_environment_configuration(&self, cx: &App) -> InstanceEnvironmentConfiguration {
+        let variables = self.get_environment_variables(cx);
+        InstanceEnvironmentConfiguration {
+            enabled: self.environment_enabled,
+            variables: InstanceEnvironmentConfiguration::format_variables(variables.iter().map(|(key, value)| (key.trim(), value.as_ref()))),
+        }
+    }
+
+    fn send_environment_configuration(&self, cx: &App) {
+        self.backend_handle.send(MessageToBackend::SetInstanceEnvironment {
+            id: self.instance_id,
+            environment: self.get_environment_configuration(cx)
+        });
+    }
+
     fn get_jvm_binary_configuration(&self) -> InstanceJvmBinaryConfiguration {
         InstanceJvmBinaryConfiguration {
             enabled: self.jvm_binary_enabled,
             path: self.jvm_binary_path.clone(),
+            major_version: None,
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn on_wrapper_command_changed(
+        &mut self,
+        _: Entity<InputState>,
+        event: &InputEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Change = event {
+            self.backend_handle.send(MessageToBackend::SetInstanceLinuxWrapper {
+                id: self.instance_id,
+                linux_wrapper: self.get_linux_wrapper_configuration(cx)
+            });
         }
     }
 
     #[cfg(target_os = "linux")]
-    fn get_linux_wrapper_configuration(&self) -> InstanceLinuxWrapperConfiguration {
+    fn get_linux_wrapper_configuration(&self, cx: &App) -> InstanceLinuxWrapperConfiguration {
         InstanceLinuxWrapperConfiguration {
             use_mangohud: self.use_mangohud,
             use_gamemode: self.use_gamemode,
             use_discrete_gpu: self.use_discrete_gpu,
+            use_prime_run: self.use_prime_run,
+            custom_command: self.wrapper_command_input_state.read(cx).value().into(),
         }
     }
 
@@ -454,18 +759,171 @@ impl Render for InstanceSettingsSubpage {
             .child(div().text_lg().child("Settings"));
 
         let memory_override_enabled = self.memory_override_enabled;
+        let memory_hint = self.system_memory_mib.map(|total| {
+            let max = self.memory_max_input_state.read(cx).value().parse::<u64>().unwrap_or(0);
+            if memory_override_enabled && max > total {
+                div().text_sm().text_color(cx.theme().danger).child(format!("Max exceeds the {} of memory installed in this system", crate::format::bytes(total * 1024 * 1024)))
+            } else {
+                let recommended = self.recommended_memory.unwrap_or_else(|| InstanceMemoryConfiguration::recommended(total)).max;
+                div().text_sm().text_color(cx.theme().muted_foreground).child(format!("{} installed, {} recommended", crate::format::bytes(total * 1024 * 1024),
+                    crate::format::bytes(recommended as u64 * 1024 * 1024)))
+            }
+        });
         let jvm_flags_enabled = self.jvm_flags_enabled;
+        let jvm_flag_warnings = if jvm_flags_enabled {
+            schema::instance::jvm_flag_warnings(&self.get_jvm_flags_configuration(cx).split(), memory_override_enabled)
+        } else {
+            Vec::new()
+        };
+        let branding_enabled = self.branding_enabled;
+        let system_property_errors: Vec<String> = if branding_enabled {
+            self.get_branding_configuration(cx).system_properties().into_iter().filter_map(Result::err).collect()
+        } else {
+            Vec::new()
+        };
+        let environment_enabled = self.environment_enabled;
+        let environment_errors: Vec<String> = if environment_enabled {
+            self.get_environment_variables(cx).iter()
+                .filter_map(|(key, value)| InstanceEnvironmentConfiguration::check_variable(key.trim(), value).err())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let environment_rows = self.environment_rows.iter().enumerate().map(|(index, row)| {
+            h_flex()
+                .gap_1()
+                .child(div().flex_1().child(Input::new(&row.key_input_state).small().disabled(!environment_enabled)))
+                .child("=")
+                .child(div().flex_1().child(Input::new(&row.value_input_state).small().disabled(!environment_enabled)))
+                .child(Button::new(("remove-environment", index)).icon(IconName::Close).small().disabled(!environment_enabled)
+                    .on_click(cx.listener(move |page, _, _, cx| {
+                        page.environment_rows.remove(index);
+                        page.send_environment_configuration(cx);
+                        cx.notify();
+                    })))
+        }).collect::<Vec<_>>();
         let jvm_binary_enabled = self.jvm_binary_enabled;
 
+        let accounts = self.data.accounts.read(cx).accounts.clone();
+        let account_buttons = ButtonGroup::new("account")
+            .outline()
+            .child(Button::new("account-default").label("Default").selected(self.account.is_none()))
+            .children(accounts.iter().enumerate().map(|(index, account)| {
+                let label = if account.offline {
+                    SharedString::from(format!("{} (offline)", account.username))
+                } else {
+                    SharedString::new(account.username.clone())
+                };
+                Button::new(("account", index)).label(label).selected(self.account == Some(account.uuid))
+            }))
+            .on_click(cx.listener(move |page, selected: &Vec<usize>, window, cx| {
+                let account = match selected.first() {
+                    Some(0) | None => None,
+                    Some(index) => accounts.get(index - 1).map(|account| account.uuid),
+                };
+                if page.account != account {
+                    let page_entity = cx.entity();
+                    let backend_handle = page.backend_handle.clone();
+                    crate::launcher_lock::when_unlocked(&backend_handle.clone(), window, cx, move |_, cx| {
+                        page_entity.update(cx, |page, cx| {
+                            page.account = account;
+                            backend_handle.send(MessageToBackend::SetInstanceAccount {
+                                id: page.instance_id,
+                                account,
+                            });
+                            cx.notify();
+                        });
+                    });
+                }
+            }));
+
         let jvm_binary_label = if let Some(path) = &self.jvm_binary_path {
             SharedString::new(path.to_string_lossy())
         } else {
             SharedString::new_static("<unset>")
         };
 
+        // The backend runs `java -version` when the binary is picked and records the result in the configuration
+        let jvm_binary_hint = self.instance.read(cx).configuration.jvm_binary.as_ref()
+            .filter(|jvm_binary| jvm_binary.path.is_some() && jvm_binary.path == self.jvm_binary_path)
+            .map(|jvm_binary| match jvm_binary.major_version {
+                Some(major_version) => div().text_sm().text_color(cx.theme().muted_foreground).child(format!("Detected Java {major_version}")),
+                None => div().text_sm().text_color(cx.theme().danger).child("Unable to run this Java binary"),
+            });
+
+        let locked = self.instance.read(cx).configuration.locked;
+        let capture_log_events = self.instance.read(cx).configuration.capture_log_events;
+        let pack_source = self.instance.read(cx).configuration.pack_source.clone();
+
         let mut basic_content = v_flex()
             .gap_4()
             .size_full()
+            .child(v_flex()
+                .gap_1()
+                .child(Checkbox::new("locked").label("Lock this instance").checked(locked).on_click({
+                    let backend_handle = self.backend_handle.clone();
+                    let id = self.instance_id;
+                    move |value, window, cx| {
+                        let locked = *value;
+                        let lock_backend_handle = backend_handle.clone();
+                        crate::launcher_lock::when_unlocked(&backend_handle, window, cx, move |_, _| {
+                            lock_backend_handle.send(MessageToBackend::SetInstanceLocked { id, locked });
+                        });
+                    }
+                }))
+                .child(div().text_sm().text_color(cx.theme().muted_foreground)
+                    .child("Locked instances can still be launched, but their version, loader and content can't be changed and they can't be deleted")))
+            .child(v_flex()
+                .gap_1()
+                .child(Checkbox::new("capture-log-events").label("Record seeds and deaths from logs").checked(capture_log_events).on_click({
+                    let backend_handle = self.backend_handle.clone();
+                    let id = self.instance_id;
+                    move |value, _, _| {
+                        backend_handle.send(MessageToBackend::SetInstanceCaptureLogEvents { id, capture: *value });
+                    }
+                }))
+                .child(div().text_sm().text_color(cx.theme().muted_foreground)
+                    .child("After the game closes, seeds shown with /seed, death messages and death coordinates printed by mods are added to the timeline on the Logs page")))
+            .child(v_flex()
+                .gap_1()
+                .child("Pack source")
+                .child(h_flex()
+                    .gap_2()
+                    .child(Button::new("pack-source").success().label(match &pack_source {
+                        Some(pack_source) => SharedString::new(pack_source.to_string_lossy()),
+                        None => SharedString::new_static("Watch pack folder..."),
+                    }).on_click({
+                        let backend_handle = self.backend_handle.clone();
+                        let id = self.instance_id;
+                        move |_, window, cx| {
+                            let receiver = cx.prompt_for_paths(PathPromptOptions {
+                                files: false,
+                                directories: true,
+                                multiple: false,
+                                prompt: Some("Select packwiz or unpacked .mrpack folder".into())
+                            });
+                            let backend_handle = backend_handle.clone();
+                            window.spawn(cx, async move |_| {
+                                let Ok(Ok(Some(paths))) = receiver.await else {
+                                    return;
+                                };
+                                if let Some(source) = paths.into_iter().next() {
+                                    backend_handle.send(MessageToBackend::SetInstancePackSource { id, source: Some(source) });
+                                }
+                            }).detach();
+                        }
+                    }))
+                    .when(pack_source.is_some(), |this| {
+                        this.child(Button::new("stop-pack-source").danger().label("Stop watching").on_click({
+                            let backend_handle = self.backend_handle.clone();
+                            let id = self.instance_id;
+                            move |_, _, _| {
+                                backend_handle.send(MessageToBackend::SetInstancePackSource { id, source: None });
+                            }
+                        }))
+                    }))
+                .child(div().text_sm().text_color(cx.theme().muted_foreground)
+                    .child("For pack developers: changes saved in a packwiz or unpacked .mrpack folder are copied into this instance right away. Press Ctrl+R (Cmd+R on macOS) on the instance page to restart the game")))
             .child(v_flex()
                 .child("Instance name")
                 .child(h_flex()
@@ -503,7 +961,7 @@ impl Render for InstanceSettingsSubpage {
             TypelessFrontendMetadataResult::Loaded => {
                 basic_content = basic_content.child(crate::labelled(
                     "Version",
-                    Select::new(&self.version_select_state).w_full()
+                    Select::new(&self.version_select_state).w_full().disabled(locked)
                 ))
             },
             TypelessFrontendMetadataResult::Error(ref error) => {
@@ -514,6 +972,7 @@ impl Render for InstanceSettingsSubpage {
         basic_content = basic_content
             .child(ButtonGroup::new("loader")
                 .outline()
+                .disabled(locked)
                 .child(
                     Button::new("loader-vanilla")
                         .label("Vanilla")
@@ -568,7 +1027,7 @@ impl Render for InstanceSettingsSubpage {
                 TypelessFrontendMetadataResult::Loaded => {
                     basic_content = basic_content.child(crate::labelled(
                         "Loader Version",
-                        Select::new(&self.loader_version_select_state).w_full()
+                        Select::new(&self.loader_version_select_state).w_full().disabled(locked)
                     ))
                 },
                 TypelessFrontendMetadataResult::Error(ref error) => {
@@ -577,14 +1036,93 @@ impl Render for InstanceSettingsSubpage {
             }
         }
 
+        basic_content = basic_content.child(crate::labelled("Account", account_buttons));
+
+        let configuration = &self.instance.read(cx).configuration;
+        let current_icon = configuration.icon.clone();
+        let color_tag = configuration.color_tag;
+
+        let icon_buttons = h_flex()
+            .flex_wrap()
+            .gap_1()
+            .children(BUNDLED_INSTANCE_ICONS.iter().enumerate().map(|(index, name)| {
+                let selected = match &current_icon {
+                    Some(InstanceIcon::Bundled(icon)) => icon.as_str() == *name,
+                    Some(InstanceIcon::Image(_)) => false,
+                    None => index == 0,
+                };
+                Button::new(("icon", index))
+                    .icon(Icon::default().path(format!("icons/{name}.svg")))
+                    .outline()
+                    .selected(selected)
+                    .on_click(cx.listener(move |page, _, _, _| {
+                        page.backend_handle.send(MessageToBackend::SetInstanceIcon {
+                            id: page.instance_id,
+                            icon: Some(InstanceIcon::Bundled((*name).into())),
+                        });
+                    }))
+            }))
+            .child(Button::new("icon-image")
+                .label(ts!("instance_icon_custom_image"))
+                .outline()
+                .selected(matches!(current_icon, Some(InstanceIcon::Image(_))))
+                .on_click(cx.listener(|page, _, window, cx| {
+                    let receiver = cx.prompt_for_paths(PathPromptOptions {
+                        files: true,
+                        directories: false,
+                        multiple: false,
+                        prompt: Some(ts!("instance_icon_select_image"))
+                    });
+                    let backend_handle = page.backend_handle.clone();
+                    let id = page.instance_id;
+                    page._select_file_task = window.spawn(cx, async move |_| {
+                        let Ok(Ok(Some(paths))) = receiver.await else {
+                            return;
+                        };
+                        if let Some(path) = paths.into_iter().next() {
+                            backend_handle.send(MessageToBackend::SetInstanceIconImage { id, path });
+                        }
+                    });
+                })));
+
+        let tag_buttons = ButtonGroup::new("color-tag")
+            .outline()
+            .child(Button::new("color-tag-none").label(ts!("instances_tag_none")).selected(color_tag.is_none()))
+            .children(InstanceColorTag::ALL.iter().enumerate().map(|(index, tag)| {
+                Button::new(("color-tag", index))
+                    .child(div().size_3().rounded_full().bg(tag_color(*tag)))
+                    .label(ts!(tag.translation_key()))
+                    .selected(color_tag == Some(*tag))
+            }))
+            .on_click(cx.listener(move |page, selected: &Vec<usize>, _, _| {
+                let new_tag = match selected.first() {
+                    Some(0) | None => None,
+                    Some(index) => InstanceColorTag::ALL.get(index - 1).copied(),
+                };
+                if new_tag != color_tag {
+                    page.backend_handle.send(MessageToBackend::SetInstanceColorTag {
+                        id: page.instance_id,
+                        color_tag: new_tag,
+                    });
+                }
+            }));
+
+        basic_content = basic_content
+            .child(crate::labelled(ts!("instance_icon"), icon_buttons))
+            .child(crate::labelled(ts!("instance_tag"), tag_buttons));
+
         let runtime_content = v_flex()
             .gap_4()
             .size_full()
             .child(v_flex()
                 .gap_1()
-                .child(Checkbox::new("memory").label("Set Memory").checked(memory_override_enabled).on_click(cx.listener(|page, value, _, cx| {
+                .child(Checkbox::new("memory").label("Set Memory").checked(memory_override_enabled).on_click(cx.listener(|page, value, window, cx| {
                     if page.memory_override_enabled != *value {
                         page.memory_override_enabled = *value;
+                        if *value && !page.memory_configured {
+                            page.apply_recommended_memory(window, cx);
+                        }
+                        page.memory_configured = true;
                         page.backend_handle.send(MessageToBackend::SetInstanceMemory {
                             id: page.instance_id,
                             memory: page.get_memory_configuration(cx)
@@ -599,7 +1137,21 @@ impl Render for InstanceSettingsSubpage {
                 .child(h_flex()
                     .gap_1()
                     .child(NumberInput::new(&self.memory_max_input_state).small().suffix("MiB").disabled(!memory_override_enabled))
-                    .child("Max"))
+                    .child("Max")
+                    .child(Button::new("recommended_memory")
+                        .small()
+                        .label("Recommended")
+                        .tooltip("Based on the installed memory and the mods of this instance")
+                        .disabled(!memory_override_enabled || self.recommended_memory.is_none())
+                        .on_click(cx.listener(|page, _, window, cx| {
+                            page.apply_recommended_memory(window, cx);
+                            page.backend_handle.send(MessageToBackend::SetInstanceMemory {
+                                id: page.instance_id,
+                                memory: page.get_memory_configuration(cx)
+                            });
+                            cx.notify();
+                        }))))
+                .when_some(memory_hint, |this, hint| this.child(hint))
                 )
             .child(v_flex()
                 .gap_1()
@@ -614,6 +1166,47 @@ impl Render for InstanceSettingsSubpage {
                     }
                 })))
                 .child(Input::new(&self.jvm_flags_input_state).disabled(!jvm_flags_enabled))
+                .child(div().text_sm().text_color(cx.theme().muted_foreground).child("Added after the global JVM flags, replacing any global flag for the same option"))
+                .children(jvm_flag_warnings.into_iter().map(|warning| div().text_sm().text_color(cx.theme().warning).child(warning)))
+            )
+            .child(v_flex()
+                .gap_1()
+                .child(Checkbox::new("branding").label("Override Launcher Branding").checked(branding_enabled).on_click(cx.listener(|page, value, _, cx| {
+                    if page.branding_enabled != *value {
+                        page.branding_enabled = *value;
+                        page.backend_handle.send(MessageToBackend::SetInstanceBranding {
+                            id: page.instance_id,
+                            branding: page.get_branding_configuration(cx)
+                        });
+                        cx.notify();
+                    }
+                })))
+                .child(h_flex()
+                    .gap_1()
+                    .child(Input::new(&self.launcher_name_input_state).small().disabled(!branding_enabled))
+                    .child(Input::new(&self.launcher_version_input_state).small().disabled(!branding_enabled)))
+                .child(Input::new(&self.system_properties_input_state).disabled(!branding_enabled))
+                .child(div().text_sm().text_color(cx.theme().muted_foreground).child("Launcher name and version passed to the game, and system properties added as -Dkey=value"))
+                .children(system_property_errors.into_iter().map(|error| div().text_sm().text_color(cx.theme().warning).child(error)))
+            )
+            .child(v_flex()
+                .gap_1()
+                .child(Checkbox::new("environment").label("Set Environment Variables").checked(environment_enabled).on_click(cx.listener(|page, value, _, cx| {
+                    if page.environment_enabled != *value {
+                        page.environment_enabled = *value;
+                        page.send_environment_configuration(cx);
+                        cx.notify();
+                    }
+                })))
+                .children(environment_rows)
+                .child(Button::new("add-environment").small().icon(IconName::Plus).label("Add Variable").disabled(!environment_enabled)
+                    .on_click(cx.listener(|page, _, window, cx| {
+                        let row = Self::new_environment_row("", "", window, cx);
+                        page.environment_rows.push(row);
+                        cx.notify();
+                    })))
+                .child(div().text_sm().text_color(cx.theme().muted_foreground).child("e.g. MESA_GL_VERSION_OVERRIDE = 4.5 or __GL_THREADED_OPTIMIZATIONS = 1"))
+                .children(environment_errors.into_iter().map(|error| div().text_sm().text_color(cx.theme().warning).child(error)))
             )
             .child(v_flex()
                 .gap_1()
@@ -664,8 +1257,35 @@ impl Render for InstanceSettingsSubpage {
                     });
                     this._select_file_task = add_from_file_task;
                 })))
+                .when(!self.java_installations.is_empty(), |this| {
+                    this.child(Select::new(&self.java_installation_select_state)
+                        .placeholder("Pick a detected Java installation")
+                        .disabled(!jvm_binary_enabled)
+                        .w_full())
+                })
+                .when_some(jvm_binary_hint, |this, hint| this.child(hint))
+            )
+            .child(v_flex()
+                .gap_1()
+                .child("Daily playtime limit")
+                .child(h_flex()
+                    .gap_1()
+                    .child(Input::new(&self.playtime_limit_input_state).small().suffix("min"))
+                    .child(Button::new("save_playtime_limit").success().small().label("Save").on_click(cx.listener(|page, _, window, cx| {
+                        page.save_playtime_limit(window, cx);
+                    }))))
+                .when_some(self.played_today_seconds, |this, seconds| {
+                    this.child(div().text_sm().text_color(cx.theme().muted_foreground).child(format!("Played {} today", crate::format::duration(seconds))))
+                })
             );
 
+        #[cfg(target_os = "linux")]
+        let wrapper_preview = {
+            let mut command = self.get_linux_wrapper_configuration(cx).wrapper_command();
+            command.push("java".to_string());
+            format!("Runs {}", command.join(" "))
+        };
+
         #[cfg(target_os = "linux")]
         let runtime_content = runtime_content.child(v_flex()
             .gap_1()
@@ -675,7 +1295,7 @@ impl Render for InstanceSettingsSubpage {
                     page.use_mangohud = *value;
                     page.backend_handle.send(MessageToBackend::SetInstanceLinuxWrapper {
                         id: page.instance_id,
-                        linux_wrapper: page.get_linux_wrapper_configuration()
+                        linux_wrapper: page.get_linux_wrapper_configuration(cx)
                     });
                     cx.notify();
                 }
@@ -685,7 +1305,7 @@ impl Render for InstanceSettingsSubpage {
                     page.use_gamemode = *value;
                     page.backend_handle.send(MessageToBackend::SetInstanceLinuxWrapper {
                         id: page.instance_id,
-                        linux_wrapper: page.get_linux_wrapper_configuration()
+                        linux_wrapper: page.get_linux_wrapper_configuration(cx)
                     });
                     cx.notify();
                 }
@@ -695,17 +1315,30 @@ impl Render for InstanceSettingsSubpage {
                     page.use_discrete_gpu = *value;
                     page.backend_handle.send(MessageToBackend::SetInstanceLinuxWrapper {
                         id: page.instance_id,
-                        linux_wrapper: page.get_linux_wrapper_configuration()
+                        linux_wrapper: page.get_linux_wrapper_configuration(cx)
+                    });
+                    cx.notify();
+                }
+            })))
+            .child(Checkbox::new("use_prime_run").label("Use prime-run").checked(self.use_prime_run).disabled(!self.prime_run_available).on_click(cx.listener(|page, value, _, cx| {
+                if page.use_prime_run != *value {
+                    page.use_prime_run = *value;
+                    page.backend_handle.send(MessageToBackend::SetInstanceLinuxWrapper {
+                        id: page.instance_id,
+                        linux_wrapper: page.get_linux_wrapper_configuration(cx)
                     });
                     cx.notify();
                 }
             })))
+            .child("Wrapper command")
+            .child(Input::new(&self.wrapper_command_input_state))
+            .child(div().text_sm().text_color(cx.theme().muted_foreground).child(wrapper_preview))
         );
 
         let actions_content = v_flex()
             .gap_4()
             .size_full()
-            .child(Button::new("shortcut").label("Create shortcut").success().on_click({
+            .child(Button::new("shortcut").label("Create desktop shortcut").success().on_click({
                 let instance = self.instance.clone();
                 let backend_handle = self.backend_handle.clone();
                 move |_: &ClickEvent, _, cx| {
@@ -733,21 +1366,131 @@ impl Render for InstanceSettingsSubpage {
                     }).detach();
                 }
             }))
-            .child(Button::new("delete").label("Delete this instance").danger().on_click({
+            .child(Button::new("export-mrpack").label("Export as Modrinth modpack").info().on_click({
                 let instance = self.instance.clone();
                 let backend_handle = self.backend_handle.clone();
-                move |click: &ClickEvent, window, cx| {
+                move |_: &ClickEvent, window, cx| {
+                    let user_dirs = directories::UserDirs::new();
+                    let directory = user_dirs.as_ref()
+                        .and_then(directories::UserDirs::download_dir).unwrap_or(Path::new("."));
                     let instance = instance.read(cx);
                     let id = instance.id;
                     let name = instance.name.clone();
 
-                    if InterfaceConfig::get(cx).quick_delete_instance && click.modifiers().shift {
-                        backend_handle.send(bridge::message::MessageToBackend::DeleteInstance {
-                            id
+                    let receiver = cx.prompt_for_new_path(directory, Some(&format!("{name}.mrpack")));
+                    let backend_handle = backend_handle.clone();
+                    window.spawn(cx, async move |cx| {
+                        let Ok(Ok(Some(path))) = receiver.await else {
+                            return;
+                        };
+                        _ = cx.update(|window, cx| {
+                            crate::modals::generic::show_retryable_modal(window, cx, rust_i18n::t!("export_exporting", name = name).into_owned().into(),
+                                ts!("export_modpack_error"), move |modal_action| {
+                                    backend_handle.send(MessageToBackend::ExportModpack { id, path: path.clone(), modal_action });
+                                });
+                        });
+                    }).detach();
+                }
+            }))
+            .child(Button::new("export-zip").label("Export as zip").info().on_click({
+                let instance = self.instance.clone();
+                let backend_handle = self.backend_handle.clone();
+                move |_: &ClickEvent, window, cx| {
+                    let instance = instance.read(cx);
+                    let (id, name) = (instance.id, instance.name.clone());
+                    crate::modals::export_instance::open_export_instance(id, name, backend_handle.clone(), window, cx);
+                }
+            }))
+            .child(Button::new("share-lan").label("Share on local network").info()
+                .tooltip("Lets launchers on the same network import this instance without downloading its content again")
+                .on_click({
+                    let instance = self.instance.clone();
+                    let backend_handle = self.backend_handle.clone();
+                    move |_: &ClickEvent, window, cx| {
+                        let instance = instance.read(cx);
+                        let (id, name) = (instance.id, instance.name.clone());
+                        let share_backend_handle = backend_handle.clone();
+                        crate::launcher_lock::when_unlocked(&backend_handle, window, cx, move |window, cx| {
+                            crate::modals::generic::show_retryable_modal(window, cx, rust_i18n::t!("lan_share_sharing", name = name).into_owned().into(),
+                                ts!("lan_share_error_sharing"), move |modal_action| {
+                                    share_backend_handle.send(MessageToBackend::ShareInstanceOnLan { id, modal_action });
+                                });
                         });
-                    } else {
-                        crate::modals::delete_instance::open_delete_instance(id, name, backend_handle.clone(), window, cx);
                     }
+                }))
+            .child(Button::new("config-history").label("Config history").info()
+                .tooltip("Compare and roll back the configs as they were before earlier launches and modpack updates")
+                .on_click({
+                    let instance = self.instance.clone();
+                    let backend_handle = self.backend_handle.clone();
+                    move |_: &ClickEvent, window, cx| {
+                        let instance = instance.read(cx);
+                        let (id, name) = (instance.id, instance.name.clone());
+                        crate::modals::config_snapshots::open_config_snapshots(id, name, backend_handle.clone(), window, cx);
+                    }
+                }))
+            .child(Button::new("verify-files").label(ts!("instance_verify_files")).info()
+                .tooltip(ts!("instance_verify_files_tooltip"))
+                .on_click({
+                    let backend_handle = self.backend_handle.clone();
+                    let id = self.instance_id;
+                    move |_, window, cx| {
+                        let backend_handle = backend_handle.clone();
+                        crate::download_guard::when_download_allowed(&backend_handle.clone(), window, cx, move |window, cx| {
+                            let modal_action = ModalAction::default();
+                            backend_handle.send(MessageToBackend::VerifyInstanceFiles {
+                                id,
+                                modal_action: modal_action.clone(),
+                            });
+                            crate::modals::generic::show_notification(window, cx, ts!("instance_verify_files_error"), modal_action);
+                        });
+                    }
+                }))
+            .child(Button::new("duplicate").label("Duplicate this instance").info().on_click({
+                let backend_handle = self.backend_handle.clone();
+                let id = self.instance_id;
+                move |_, window, cx| {
+                    let duplicate_backend_handle = backend_handle.clone();
+                    crate::launcher_lock::when_unlocked(&backend_handle, window, cx, move |_, _| {
+                        duplicate_backend_handle.send(MessageToBackend::DuplicateInstance { id });
+                    });
+                }
+            }))
+            .child(Button::new("clone-to-version").label("Clone to another Minecraft version").info().on_click({
+                let instance = self.instance.clone();
+                let data = self.data.clone();
+                move |_, window, cx| {
+                    let instance = instance.read(cx);
+                    let id = instance.id;
+                    let name = instance.name.clone();
+                    let instance_names: Arc<[SharedString]> =
+                        data.instances.read(cx).entries.values().map(|entry| entry.read(cx).name.clone()).collect();
+                    let metadata = data.metadata.clone();
+                    let backend_handle = data.backend_handle.clone();
+                    crate::launcher_lock::when_unlocked(&data.backend_handle, window, cx, move |window, cx| {
+                        crate::modals::clone_to_version::open_clone_to_version(id, name, instance_names, metadata, backend_handle, window, cx);
+                    });
+                }
+            }))
+            .child(Button::new("delete").label("Delete this instance").danger().disabled(locked).on_click({
+                let instance = self.instance.clone();
+                let backend_handle = self.backend_handle.clone();
+                move |click: &ClickEvent, window, cx| {
+                    let instance = instance.read(cx);
+                    let id = instance.id;
+                    let name = instance.name.clone();
+
+                    let quick_delete = InterfaceConfig::get(cx).quick_delete_instance && click.modifiers().shift;
+                    let backend_handle = backend_handle.clone();
+                    crate::launcher_lock::when_unlocked(&backend_handle.clone(), window, cx, move |window, cx| {
+                        if quick_delete {
+                            backend_handle.send(bridge::message::MessageToBackend::DeleteInstance {
+                                id
+                            });
+                        } else {
+                            crate::modals::delete_instance::open_delete_instance(id, name, backend_handle, window, cx);
+                        }
+                    });
 
                 }
             }));
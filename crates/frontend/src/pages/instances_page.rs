@@ -3,7 +3,8 @@ use std::sync::{
     atomic::{AtomicBool, AtomicUsize, Ordering},
 };
 
-use bridge::{handle::BackendHandle, message::MessageToBackend};
+use bridge::{handle::BackendHandle, instance::InstanceID, message::{InstanceExportFolder, MessageToBackend}, modal_action::ModalAction};
+use enumset::EnumSet;
 use gpui::{prelude::*, *};
 use gpui_component::{
     ActiveTheme as _, IconName, IndexPath, Selectable, WindowExt,
@@ -12,21 +13,24 @@ use gpui_component::{
     checkbox::Checkbox,
     h_flex,
     input::{Input, InputEvent, InputState},
+    notification::NotificationType,
     select::{Select, SelectDelegate, SelectItem, SelectState},
     skeleton::Skeleton,
     table::{Table, TableState},
     v_flex,
 };
-use schema::{loader::Loader, version_manifest::{MinecraftVersionManifest, MinecraftVersionType}};
+use schema::{instance::{InstanceColorTag, InstanceMemoryConfiguration}, loader::Loader, version_manifest::MinecraftVersionManifest};
 
 use crate::{
-    component::{instance_list::InstanceList, page_path::PagePath},
+    connectivity::Connectivity,
+    component::{instance_list::{tag_color, InstanceList}, page_path::PagePath, version_entries::{VersionEntries, VersionFilter}},
     entity::{DataEntities, instance::InstanceEntries, metadata::{AsMetadataResult, FrontendMetadata, FrontendMetadataResult}},
-    ui,
+    interface_config::InterfaceConfig, ts, ui,
 };
 
 pub struct InstancesPage {
     instance_table: Entity<TableState<InstanceList>>,
+    _instance_table_observe: Subscription,
 
     metadata: Entity<FrontendMetadata>,
     instances: Entity<InstanceEntries>,
@@ -37,9 +41,11 @@ pub struct InstancesPage {
 impl InstancesPage {
     pub fn new(data: &DataEntities, window: &mut Window, cx: &mut Context<Self>) -> Self {
         let instance_table = InstanceList::create_table(data, window, cx);
+        let _instance_table_observe = cx.observe(&instance_table, |_, _, cx| cx.notify());
 
         Self {
             instance_table,
+            _instance_table_observe,
             metadata: data.metadata.clone(),
             instances: data.instances.clone(),
             backend_handle: data.backend_handle.clone(),
@@ -54,11 +60,324 @@ impl Render for InstancesPage {
             .icon(IconName::Plus)
             .label("Create Instance")
             .on_click(cx.listener(|this, _, window, cx| {
-                this.show_create_instance_modal(window, cx);
+                let page = cx.entity();
+                crate::launcher_lock::when_unlocked(&this.backend_handle.clone(), window, cx, move |window, cx| {
+                    page.update(cx, |page, cx| page.show_create_instance_modal(window, cx));
+                });
+            }));
+
+        let create_speedrun_instance = Button::new("create_speedrun_instance")
+            .icon(IconName::Plus)
+            .label("Speedrun Preset")
+            .tooltip("Creates a 1.16.1 Fabric instance with the recommended mods from the speedrunning community's legal mods list")
+            .on_click({
+                let backend_handle = self.backend_handle.clone();
+                move |_, window, cx| {
+                    let backend_handle = backend_handle.clone();
+                    crate::launcher_lock::when_unlocked(&backend_handle.clone(), window, cx, move |window, cx| {
+                        let backend_handle = backend_handle.clone();
+                        crate::download_guard::when_download_allowed(&backend_handle.clone(), window, cx, move |window, cx| {
+                            let modal_action = ModalAction::default();
+                            backend_handle.send(MessageToBackend::CreateSpeedrunInstance {
+                                name: "Speedrun 1.16.1".into(),
+                                modal_action: modal_action.clone(),
+                            });
+                            crate::modals::generic::show_modal(window, cx, ts!("instances_creating_speedrun"),
+                                ts!("instances_creating_speedrun_error"), modal_action);
+                        });
+                    });
+                }
+            });
+
+        let import_modpack = Button::new("import_modpack")
+            .icon(IconName::Plus)
+            .label("Import Modpack")
+            .tooltip("Creates an instance from a Modrinth modpack (.mrpack) file")
+            .on_click({
+                let backend_handle = self.backend_handle.clone();
+                move |_, window, cx| {
+                    let backend_handle = backend_handle.clone();
+                    crate::launcher_lock::when_unlocked(&backend_handle.clone(), window, cx, move |window, cx| {
+                        let receiver = cx.prompt_for_paths(PathPromptOptions {
+                            files: true,
+                            directories: false,
+                            multiple: false,
+                            prompt: Some("Select a Modrinth modpack".into())
+                        });
+                        let backend_handle = backend_handle.clone();
+                        window.spawn(cx, async move |cx| {
+                            let Ok(Ok(Some(paths))) = receiver.await else {
+                                return;
+                            };
+                            let Some(path) = paths.into_iter().next() else {
+                                return;
+                            };
+                            _ = cx.update(|window, cx| {
+                                crate::modals::generic::show_retryable_modal(window, cx, ts!("instances_importing_modpack"),
+                                    ts!("instances_importing_modpack_error"), move |modal_action| {
+                                        backend_handle.send(MessageToBackend::ImportModpack { path: path.clone(), modal_action });
+                                    });
+                            });
+                        }).detach();
+                    });
+                }
+            });
+
+        let import_multimc = Button::new("import_multimc")
+            .icon(IconName::Plus)
+            .label("Import from Prism/MultiMC")
+            .tooltip("Copies instances from a Prism Launcher or MultiMC instance folder, or from the launcher's own folder")
+            .on_click({
+                let backend_handle = self.backend_handle.clone();
+                move |_, window, cx| {
+                    let backend_handle = backend_handle.clone();
+                    crate::launcher_lock::when_unlocked(&backend_handle.clone(), window, cx, move |window, cx| {
+                        let receiver = cx.prompt_for_paths(PathPromptOptions {
+                            files: false,
+                            directories: true,
+                            multiple: false,
+                            prompt: Some(ts!("instances_select_multimc_folder"))
+                        });
+                        let backend_handle = backend_handle.clone();
+                        window.spawn(cx, async move |cx| {
+                            let Ok(Ok(Some(paths))) = receiver.await else {
+                                return;
+                            };
+                            let Some(path) = paths.into_iter().next() else {
+                                return;
+                            };
+                            _ = cx.update(|window, cx| {
+                                crate::modals::generic::show_retryable_modal(window, cx, ts!("instances_importing_instances"),
+                                    ts!("instances_importing_instances_error"), move |modal_action| {
+                                        backend_handle.send(MessageToBackend::ImportMultiMcInstances { path: path.clone(), modal_action });
+                                    });
+                            });
+                        }).detach();
+                    });
+                }
+            });
+
+        let import_official = Button::new("import_official")
+            .icon(IconName::Plus)
+            .label(ts!("import_profiles_title"))
+            .tooltip(ts!("import_profiles_tooltip"))
+            .on_click({
+                let backend_handle = self.backend_handle.clone();
+                move |_, window, cx| {
+                    let backend_handle = backend_handle.clone();
+                    crate::launcher_lock::when_unlocked(&backend_handle.clone(), window, cx, move |window, cx| {
+                        crate::modals::import_launcher_profiles::open_import_launcher_profiles(backend_handle, window, cx);
+                    });
+                }
+            });
+
+        let import_lan = Button::new("import_lan")
+            .icon(IconName::Plus)
+            .label(ts!("instances_import_lan"))
+            .tooltip(ts!("instances_import_lan_tooltip"))
+            .on_click({
+                let backend_handle = self.backend_handle.clone();
+                move |_, window, cx| {
+                    let backend_handle = backend_handle.clone();
+                    crate::launcher_lock::when_unlocked(&backend_handle.clone(), window, cx, move |window, cx| {
+                        crate::modals::lan_share::open_import_lan_share(backend_handle, window, cx);
+                    });
+                }
+            });
+
+        let group_by_tag = Checkbox::new("group_by_tag")
+            .label(ts!("instances_group_by_tag"))
+            .checked(InterfaceConfig::get(cx).group_instances_by_tag)
+            .on_click(cx.listener(|page, value: &bool, _, cx| {
+                InterfaceConfig::get_mut(cx).group_instances_by_tag = *value;
+                page.instance_table.update(cx, |table, cx| {
+                    table.delegate_mut().set_group_by_tag(*value);
+                    cx.notify();
+                });
+            }));
+
+        let selected: Vec<InstanceID> = self.instance_table.read(cx).delegate().selected().iter().copied().collect();
+
+        let mut page = ui::page(cx, h_flex().gap_8().child("Instances").child(h_flex().gap_2().child(create_instance).child(import_modpack).child(import_multimc).child(import_official).child(import_lan).child(create_speedrun_instance)).child(group_by_tag));
+
+        if !selected.is_empty() {
+            page = page.child(self.render_batch_actions(selected, cx));
+        }
+
+        page.child(Table::new(&self.instance_table).bordered(false))
+    }
+}
+
+const MEMORY_PRESETS: &[(&str, u32)] = &[
+    ("2 GiB", 2048),
+    ("4 GiB", 4096),
+    ("8 GiB", 8192),
+];
+
+impl InstancesPage {
+    fn render_batch_actions(&mut self, selected: Vec<InstanceID>, cx: &mut Context<Self>) -> impl IntoElement {
+        let selected: Arc<[InstanceID]> = selected.into();
+
+        let mut memory_buttons = h_flex().gap_2().child(ts!("instances_batch_memory"));
+        for (index, (label, max)) in MEMORY_PRESETS.iter().enumerate() {
+            let selected = Arc::clone(&selected);
+            let backend_handle = self.backend_handle.clone();
+            let max = *max;
+            memory_buttons = memory_buttons.child(Button::new(("memory_preset", index)).small().outline().label(*label).on_click(move |_, _, _| {
+                backend_handle.send(MessageToBackend::SetInstancesMemory {
+                    ids: selected.to_vec(),
+                    memory: InstanceMemoryConfiguration {
+                        enabled: true,
+                        min: InstanceMemoryConfiguration::DEFAULT_MIN.min(max),
+                        max,
+                    },
+                });
+            }));
+        }
+
+        let mut tag_buttons = h_flex().gap_1().child(ts!("instances_batch_tag")).child(Button::new("batch_tag_none").small().outline()
+            .label(ts!("instances_tag_none"))
+            .on_click({
+                let selected = Arc::clone(&selected);
+                let backend_handle = self.backend_handle.clone();
+                move |_, _, _| {
+                    backend_handle.send(MessageToBackend::SetInstancesColorTag { ids: selected.to_vec(), color_tag: None });
+                }
             }));
+        for (index, tag) in InstanceColorTag::ALL.iter().copied().enumerate() {
+            let selected = Arc::clone(&selected);
+            let backend_handle = self.backend_handle.clone();
+            tag_buttons = tag_buttons.child(Button::new(("batch_tag", index)).small().outline()
+                .child(div().size_3().rounded_full().bg(tag_color(tag)))
+                .tooltip(ts!(tag.translation_key()))
+                .on_click(move |_, _, _| {
+                    backend_handle.send(MessageToBackend::SetInstancesColorTag { ids: selected.to_vec(), color_tag: Some(tag) });
+                }));
+        }
 
-        ui::page(cx, h_flex().gap_8().child("Instances").child(create_instance))
-            .child(Table::new(&self.instance_table).bordered(false))
+        let prepare_selected = Button::new("prepare_selected")
+            .small()
+            .label(ts!("instances_batch_prepare"))
+            .tooltip(ts!("instances_batch_prepare_tooltip"))
+            .on_click({
+                let selected = Arc::clone(&selected);
+                let backend_handle = self.backend_handle.clone();
+                move |_, window, cx| {
+                    let selected = Arc::clone(&selected);
+                    let backend_handle = backend_handle.clone();
+                    crate::download_guard::when_download_allowed(&backend_handle.clone(), window, cx, move |window, cx| {
+                        let modal_action = ModalAction::default();
+                        backend_handle.send(MessageToBackend::PrepareInstances {
+                            ids: selected.to_vec(),
+                            modal_action: modal_action.clone(),
+                        });
+                        crate::modals::generic::show_notification(window, cx, ts!("instances_batch_prepare_error"), modal_action);
+                    });
+                }
+            });
+
+        let export_selected = Button::new("export_selected")
+            .small()
+            .label(ts!("instances_batch_export"))
+            .tooltip(ts!("instances_batch_export_tooltip"))
+            .on_click({
+                let selected: Vec<(InstanceID, SharedString)> = selected.iter()
+                    .filter_map(|id| Some((*id, self.instances.read(cx).entries.get(id)?.read(cx).name.clone())))
+                    .collect();
+                let backend_handle = self.backend_handle.clone();
+                move |_, window, cx| {
+                    let receiver = cx.prompt_for_paths(PathPromptOptions {
+                        files: false,
+                        directories: true,
+                        multiple: false,
+                        prompt: Some(ts!("instances_batch_export_prompt"))
+                    });
+                    let selected = selected.clone();
+                    let backend_handle = backend_handle.clone();
+                    window.spawn(cx, async move |cx| {
+                        let Ok(Ok(Some(paths))) = receiver.await else {
+                            return;
+                        };
+                        let Some(directory) = paths.into_iter().next() else {
+                            return;
+                        };
+                        // Worlds are left out like in the single export, they can be very large
+                        let folders = EnumSet::<InstanceExportFolder>::all() - InstanceExportFolder::Saves;
+                        _ = cx.update(|window, cx| {
+                            for (id, name) in selected {
+                                let modal_action = ModalAction::default();
+                                backend_handle.send(MessageToBackend::ExportInstance {
+                                    id,
+                                    path: directory.join(format!("{name}.zip")),
+                                    folders,
+                                    modal_action: modal_action.clone(),
+                                });
+                                crate::modals::generic::show_notification(window, cx, ts!("instances_export_error"), modal_action);
+                            }
+                        });
+                    }).detach();
+                }
+            });
+
+        let delete_selected = Button::new("delete_selected")
+            .small()
+            .danger()
+            .label(ts!("instances_batch_delete"))
+            .on_click({
+                let selected = Arc::clone(&selected);
+                let backend_handle = self.backend_handle.clone();
+                let instance_table = self.instance_table.clone();
+                move |_, window, cx| {
+                    let selected = Arc::clone(&selected);
+                    let backend_handle = backend_handle.clone();
+                    let instance_table = instance_table.clone();
+                    crate::launcher_lock::when_unlocked(&backend_handle.clone(), window, cx, move |window, cx| {
+                        let message = ts!("instances_batch_delete_message", count = selected.len());
+                        window.open_dialog(cx, move |dialog, _, _| {
+                            let selected = Arc::clone(&selected);
+                            let backend_handle = backend_handle.clone();
+                            let instance_table = instance_table.clone();
+                            dialog
+                                .confirm()
+                                .title(ts!("instances_batch_delete_title"))
+                                .child(message.clone())
+                                .on_ok(move |_, _, cx| {
+                                    backend_handle.send(MessageToBackend::DeleteInstances {
+                                        ids: selected.to_vec(),
+                                    });
+                                    instance_table.update(cx, |table, cx| {
+                                        table.delegate_mut().clear_selection();
+                                        cx.notify();
+                                    });
+                                    true
+                                })
+                        });
+                    });
+                }
+            });
+
+        let clear_selection = Button::new("clear_selection")
+            .small()
+            .label(ts!("instances_batch_clear_selection"))
+            .on_click(cx.listener(|this, _, _, cx| {
+                this.instance_table.update(cx, |table, cx| {
+                    table.delegate_mut().clear_selection();
+                    cx.notify();
+                });
+            }));
+
+        h_flex()
+            .px_4()
+            .py_2()
+            .gap_4()
+            .flex_wrap()
+            .child(ts!("instances_batch_selected", count = selected.len()))
+            .child(memory_buttons)
+            .child(tag_buttons)
+            .child(prepare_selected)
+            .child(export_selected)
+            .child(delete_selected)
+            .child(clear_selection)
     }
 }
 
@@ -112,14 +431,14 @@ impl InstancesPage {
         let selected_loader = Arc::new(AtomicUsize::new(0));
         let loaded_versions = Arc::new(AtomicBool::new(false));
         let error_loading_versions = Arc::new(RwLock::new(None));
-        let show_snapshots = Arc::new(AtomicBool::new(false));
+        let version_filter = Arc::new(Mutex::new(VersionFilter::default()));
         let name_invalid = Arc::new(AtomicBool::new(false));
 
         let instance_names: Arc<[SharedString]> =
             self.instances.read(cx).entries.iter().map(|(_, v)| v.read(cx).name.clone()).collect();
 
         let minecraft_version_dropdown =
-            cx.new(|cx| SelectState::new(VersionList::default(), None, window, cx).searchable(true));
+            cx.new(|cx| SelectState::new(VersionEntries::default(), None, window, cx).searchable(true));
 
         let unnamed_instance_name = SharedString::new_static("Unnamed Instance");
 
@@ -148,7 +467,7 @@ impl InstancesPage {
 
         let reload_version_dropdown = {
             let loaded_versions = Arc::clone(&loaded_versions);
-            let show_snapshots = Arc::clone(&show_snapshots);
+            let version_filter = Arc::clone(&version_filter);
             let error_loading_versions = Arc::clone(&error_loading_versions);
             let minecraft_version_dropdown = minecraft_version_dropdown.clone();
             let versions = versions.clone();
@@ -159,27 +478,18 @@ impl InstancesPage {
                     let (versions, latest) = match result {
                         FrontendMetadataResult::Loading => {
                             loaded_versions.store(false, Ordering::Relaxed);
-                            (Vec::new(), None)
+                            (VersionEntries::default(), None)
                         },
                         FrontendMetadataResult::Error(error) => {
                             loaded_versions.store(false, Ordering::Relaxed);
                             *error_loading_versions.write().unwrap() = Some(error);
-                            (Vec::new(), None)
+                            (VersionEntries::default(), None)
                         },
                         FrontendMetadataResult::Loaded(manifest) => {
                             loaded_versions.store(true, Ordering::Relaxed);
                             *error_loading_versions.write().unwrap() = None;
 
-                            let versions: Vec<SharedString> = if show_snapshots.load(Ordering::Relaxed) {
-                                manifest.versions.iter().map(|v| SharedString::from(v.id.as_str())).collect()
-                            } else {
-                                manifest
-                                    .versions
-                                    .iter()
-                                    .filter(|v| !matches!(v.r#type, MinecraftVersionType::Snapshot))
-                                    .map(|v| SharedString::from(v.id.as_str()))
-                                    .collect()
-                            };
+                            let versions = VersionEntries::new(&manifest, *version_filter.lock().unwrap(), cx);
 
                             (versions, Some(SharedString::from(manifest.latest.release.as_str())))
                         },
@@ -201,17 +511,10 @@ impl InstancesPage {
                     }
 
                     if to_select.is_none() {
-                        to_select = versions.first().cloned();
+                        to_select = versions.first();
                     }
 
-                    dropdown.set_items(
-                        VersionList {
-                            versions: versions.clone(),
-                            matched_versions: versions,
-                        },
-                        window,
-                        cx,
-                    );
+                    dropdown.set_items(versions, window, cx);
 
                     if let Some(to_select) = to_select {
                         dropdown.set_selected_value(&to_select, window, cx);
@@ -308,7 +611,7 @@ impl InstancesPage {
             };
 
             let version_dropdown;
-            let show_snapshots_button;
+            let version_filter_checkboxes;
             let loader_button_group;
 
             if !loaded_versions.load(Ordering::Relaxed) {
@@ -316,23 +619,34 @@ impl InstancesPage {
                     .w_full()
                     .disabled(true)
                     .placeholder("Loading Minecraft Versions...");
-                show_snapshots_button = Skeleton::new().w_full().min_h_4().max_h_4().rounded_md().into_any_element();
+                version_filter_checkboxes = Skeleton::new().w_full().min_h_4().max_h_4().rounded_md().into_any_element();
                 loader_button_group = Skeleton::new().w_full().min_h_8().max_h_8().rounded_md().into_any_element();
             } else {
-                let reload_version_dropdown = reload_version_dropdown.clone();
                 let selected_loader = selected_loader.clone();
 
-                let show_snapshots = Arc::clone(&show_snapshots);
-                let show_snapshots_value = show_snapshots.load(Ordering::Relaxed);
+                let filter = *version_filter.lock().unwrap();
+                let filter_checkbox = |id: &'static str, label: &'static str, checked: bool, set: fn(&mut VersionFilter, bool)| {
+                    let version_filter = Arc::clone(&version_filter);
+                    let reload_version_dropdown = reload_version_dropdown.clone();
+                    Checkbox::new(id)
+                        .checked(checked)
+                        .label(label)
+                        .on_click(move |show, window, cx| {
+                            set(&mut version_filter.lock().unwrap(), *show);
+                            (reload_version_dropdown)(window, cx);
+                        })
+                };
 
                 version_dropdown = Select::new(&minecraft_version_dropdown).title_prefix("Minecraft Version: ");
-                show_snapshots_button = Checkbox::new("show_snapshots")
-                    .checked(show_snapshots_value)
-                    .label("Show Snapshots")
-                    .on_click(move |show, window, cx| {
-                        show_snapshots.store(*show, Ordering::Relaxed);
-                        (reload_version_dropdown)(window, cx);
-                    })
+                version_filter_checkboxes = h_flex()
+                    .gap_4()
+                    .flex_wrap()
+                    .child(filter_checkbox("show_snapshots", "Show Snapshots", filter.snapshots,
+                        |filter, show| filter.snapshots = show))
+                    .child(filter_checkbox("show_old_beta", "Show Old Beta", filter.old_beta,
+                        |filter, show| filter.old_beta = show))
+                    .child(filter_checkbox("show_old_alpha", "Show Old Alpha", filter.old_alpha,
+                        |filter, show| filter.old_alpha = show))
                     .into_any_element();
                 loader_button_group = ButtonGroup::new("loader")
                     .outline()
@@ -379,8 +693,13 @@ impl InstancesPage {
                     "Name",
                     Input::new(&name_input_state).when(name_is_invalid, |this| this.border_color(cx.theme().danger)),
                 ))
-                .child(crate::labelled("Version", v_flex().gap_2().child(version_dropdown).child(show_snapshots_button)))
-                .child(crate::labelled("Modloader", loader_button_group));
+                .child(crate::labelled("Version", v_flex().gap_2().child(version_dropdown).child(version_filter_checkboxes)))
+                .child(crate::labelled("Modloader", loader_button_group))
+                .when(Connectivity::is_offline(cx), |this| {
+                    this.child(Alert::new("offline", "Only versions that were downloaded before can be used until you're back online")
+                        .icon(IconName::Globe)
+                        .title("You're offline"))
+                });
 
             let text_input_state = name_input_state.clone();
             let backend_handle = backend_handle.clone();
@@ -399,13 +718,18 @@ impl InstancesPage {
                 })
                 .overlay_closable(false)
                 .title("Create Instance")
-                .on_ok(move |_, _, cx| {
+                .on_ok(move |_, window, cx| {
                     if name_is_invalid {
                         return false;
                     }
                     let Some(selected_version) = minecraft_version_dropdown.read(cx).selected_value().cloned() else {
                         return false;
                     };
+                    if !Connectivity::is_version_available(cx, &selected_version) {
+                        window.push_notification((NotificationType::Error,
+                            format!("Minecraft {selected_version} hasn't been downloaded before and can't be used offline")), cx);
+                        return false;
+                    }
 
                     let mut name = text_input_state.read(cx).value().clone();
                     if name.is_empty() {
@@ -417,6 +741,7 @@ impl InstancesPage {
                         name: name.as_str().into(),
                         version: selected_version.as_str().into(),
                         loader: selected_loader_value,
+                        modal_action: ModalAction::default(),
                     });
 
                     true
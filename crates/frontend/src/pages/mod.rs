@@ -1,4 +1,10 @@
+pub mod accounts_page;
+pub mod debug_page;
 pub mod instance;
 pub mod instances_page;
 pub mod modrinth_page;
+pub mod mods_page;
+pub mod skins_page;
+pub mod storage_page;
 pub mod syncing_page;
+pub mod worlds_page;
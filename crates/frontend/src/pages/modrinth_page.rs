@@ -1,38 +1,42 @@
 use std::{ops::Range, sync::{atomic::AtomicBool, Arc}, time::Duration};
 
-use bridge::{instance::{AtomicContentUpdateStatus, ContentUpdateStatus, InstanceID, InstanceContentID, InstanceContentSummary}, message::MessageToBackend, meta::MetadataRequest, modal_action::ModalAction};
+use bridge::{
+    content_source::{ContentSearchHit, ContentSearchQuery, ContentSearchResults, ContentSourceId},
+    install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath, InstallTarget},
+    instance::{AtomicContentUpdateStatus, ContentUpdateStatus, InstanceID, InstanceContentID, InstanceContentSummary}, message::MessageToBackend, modal_action::ModalAction
+};
 use gpui::{prelude::*, *};
 use gpui_component::{
-    ActiveTheme, Icon, IconName, Selectable, StyledExt, WindowExt, breadcrumb::Breadcrumb, button::{Button, ButtonGroup, ButtonVariant, ButtonVariants}, checkbox::Checkbox, h_flex, input::{Input, InputEvent, InputState}, notification::NotificationType, scroll::{ScrollableElement, Scrollbar}, skeleton::Skeleton, tooltip::Tooltip, v_flex
+    ActiveTheme, Disableable, Icon, IconName, Selectable, StyledExt, WindowExt, breadcrumb::Breadcrumb, button::{Button, ButtonGroup, ButtonVariant, ButtonVariants}, checkbox::Checkbox, h_flex, input::{Input, InputEvent, InputState}, notification::NotificationType, scroll::{ScrollableElement, Scrollbar}, skeleton::Skeleton, tooltip::Tooltip, v_flex
 };
 use rustc_hash::{FxHashMap, FxHashSet};
-use schema::{content::ContentSource, loader::Loader, modrinth::{
-    ModrinthHit, ModrinthProjectType, ModrinthSearchRequest, ModrinthSearchResult, ModrinthSideRequirement
-}};
+use schema::{content::ContentSource, loader::Loader, modrinth::{ModrinthProjectType, ModrinthSideRequirement}};
 
 use crate::{
-    component::{error_alert::ErrorAlert, page_path::PagePath}, entity::{
-        DataEntities, instance::InstanceEntries, metadata::{AsMetadataResult, FrontendMetadata, FrontendMetadataResult}
-    }, interface_config::InterfaceConfig, ts, ts_short, ui
+    component::{error_alert::ErrorAlert, page_path::PagePath}, entity::{DataEntities, instance::InstanceEntries}, interface_config::InterfaceConfig, ts, ts_short, ui
 };
 
 pub struct ModrinthSearchPage {
     data: DataEntities,
-    hits: Vec<ModrinthHit>,
+    hits: Vec<ContentSearchHit>,
     page_path: PagePath,
     install_for: Option<InstanceID>,
-    loading: Option<Subscription>,
+    loading: Option<Task<()>>,
     pending_clear: bool,
     total_hits: usize,
     search_state: Entity<InputState>,
     _search_input_subscription: Subscription,
     _delayed_clear_task: Task<()>,
+    filter_source: ContentSourceId,
     filter_project_type: ModrinthProjectType,
     filter_loaders: FxHashSet<Loader>,
     filter_categories: FxHashSet<&'static str>,
+    /// Minecraft version of the instance being installed for, results are limited to it when `filter_game_version` is set
+    instance_game_version: Option<SharedString>,
+    filter_game_version: bool,
     show_categories: Arc<AtomicBool>,
     can_install_latest: bool,
-    installed_mods_by_project: FxHashMap<Arc<str>, Vec<InstalledMod>>,
+    installed_mods_by_project: FxHashMap<(ContentSourceId, Arc<str>), Vec<InstalledMod>>,
     last_search: Arc<str>,
     scroll_handle: UniformListScrollHandle,
     search_error: Option<SharedString>,
@@ -49,20 +53,28 @@ impl ModrinthSearchPage {
         let search_state = cx.new(|cx| InputState::new(window, cx).placeholder("Search mods...").clean_on_escape());
 
         let mut can_install_latest = false;
-        let mut installed_mods_by_project: FxHashMap<Arc<str>, Vec<InstalledMod>> = FxHashMap::default();
+        let mut instance_game_version = None;
+        let mut filter_loaders = FxHashSet::default();
+        let mut installed_mods_by_project: FxHashMap<(ContentSourceId, Arc<str>), Vec<InstalledMod>> = FxHashMap::default();
 
         if let Some(install_for) = install_for {
             if let Some(entry) = data.instances.read(cx).entries.get(&install_for) {
                 let instance = entry.read(cx);
                 can_install_latest = instance.configuration.loader != Loader::Vanilla;
+                instance_game_version = Some(SharedString::new(instance.configuration.minecraft_version.as_str()));
+                if instance.configuration.loader != Loader::Vanilla {
+                    filter_loaders.insert(instance.configuration.loader);
+                }
 
                 let mods = instance.mods.read(cx);
                 for summary in mods.iter() {
-                    let ContentSource::ModrinthProject { project } = &summary.content_source else {
-                        continue;
+                    let key = match &summary.content_source {
+                        ContentSource::ModrinthProject { project } => (ContentSourceId::Modrinth, project.clone()),
+                        ContentSource::CurseForgeProject { project } => (ContentSourceId::CurseForge, project.clone()),
+                        ContentSource::Manual | ContentSource::ModrinthUnknown => continue,
                     };
 
-                    let installed = installed_mods_by_project.entry(project.clone()).or_default();
+                    let installed = installed_mods_by_project.entry(key).or_default();
                     installed.push(InstalledMod {
                         mod_id: summary.id,
                         status: summary.content_summary.update_status.clone(),
@@ -94,9 +106,12 @@ impl ModrinthSearchPage {
             search_state,
             _search_input_subscription,
             _delayed_clear_task: Task::ready(()),
+            filter_source: ContentSourceId::Modrinth,
             filter_project_type,
-            filter_loaders: FxHashSet::default(),
+            filter_loaders,
             filter_categories: FxHashSet::default(),
+            filter_game_version: instance_game_version.is_some(),
+            instance_game_version,
             show_categories: Arc::new(AtomicBool::new(false)),
             can_install_latest,
             installed_mods_by_project,
@@ -132,6 +147,20 @@ impl ModrinthSearchPage {
         self.reload(cx);
     }
 
+    fn set_source(&mut self, source: ContentSourceId, window: &mut Window, cx: &mut Context<Self>) {
+        if self.filter_source == source {
+            return;
+        }
+        self.filter_source = source;
+        // Categories are Modrinth's, and CurseForge modpacks aren't in a format that can be installed
+        self.filter_categories.clear();
+        if source == ContentSourceId::CurseForge && self.filter_project_type == ModrinthProjectType::Modpack {
+            self.set_project_type(ModrinthProjectType::Mod, window, cx);
+        } else {
+            self.reload(cx);
+        }
+    }
+
     fn set_project_type(&mut self, project_type: ModrinthProjectType, window: &mut Window, cx: &mut Context<Self>) {
         if self.filter_project_type == project_type {
             return;
@@ -160,6 +189,14 @@ impl ModrinthSearchPage {
         self.reload(cx);
     }
 
+    fn set_filter_game_version(&mut self, filter_game_version: bool, cx: &mut Context<Self>) {
+        if self.filter_game_version == filter_game_version {
+            return;
+        }
+        self.filter_game_version = filter_game_version;
+        self.reload(cx);
+    }
+
     fn set_filter_categories(&mut self, categories: FxHashSet<&'static str>, _window: &mut Window, cx: &mut Context<Self>) {
         if self.filter_categories == categories {
             return;
@@ -200,94 +237,51 @@ impl ModrinthSearchPage {
         };
 
         let project_type = match self.filter_project_type {
-            ModrinthProjectType::Mod | ModrinthProjectType::Other => "mod",
-            ModrinthProjectType::Modpack => "modpack",
-            ModrinthProjectType::Resourcepack => "resourcepack",
-            ModrinthProjectType::Shader => "shader",
+            ModrinthProjectType::Other => ModrinthProjectType::Mod,
+            project_type => project_type,
         };
 
         let offset = if self.pending_clear { 0 } else { self.hits.len() };
 
-        let mut facets = format!("[[\"project_type={}\"]", project_type);
-
-        let is_mod = self.filter_project_type == ModrinthProjectType::Mod || self.filter_project_type == ModrinthProjectType::Modpack;
-        if !self.filter_loaders.is_empty() && is_mod {
-            facets.push_str(",[");
-
-            let mut first = true;
-            for loader in &self.filter_loaders {
-                if first {
-                    first = false;
-                } else {
-                    facets.push(',');
-                }
-                facets.push_str("\"categories:");
-                facets.push_str(loader.as_modrinth_loader().id());
-                facets.push('"');
-            }
-            facets.push(']');
-        }
-
-        if !self.filter_categories.is_empty() {
-            facets.push_str(",[");
-
-            let mut first = true;
-            for category in &self.filter_categories {
-                if first {
-                    first = false;
-                } else {
-                    facets.push(',');
-                }
-                facets.push_str("\"categories:");
-                facets.push_str(*category);
-                facets.push('"');
-            }
-            facets.push(']');
-        }
-
-        facets.push(']');
+        let game_version = if self.filter_game_version {
+            self.instance_game_version.as_ref().map(|game_version| Arc::from(game_version.as_str()))
+        } else {
+            None
+        };
 
-        let request = ModrinthSearchRequest {
+        let search = ContentSearchQuery {
             query,
-            facets: Some(facets.into()),
-            index: schema::modrinth::ModrinthSearchIndex::Relevance,
+            project_type,
+            loaders: self.filter_loaders.iter().copied().collect(),
+            categories: self.filter_categories.iter().map(|category| Arc::from(*category)).collect(),
+            game_version,
             offset,
             limit: 20,
         };
 
-        let data = FrontendMetadata::request(&self.data.metadata, MetadataRequest::ModrinthSearch(request), cx);
-
-        let result: FrontendMetadataResult<ModrinthSearchResult> = data.read(cx).result();
-        match result {
-            FrontendMetadataResult::Loading => {
-                let subscription = cx.observe(&data, |page, data, cx| {
-                    let result: FrontendMetadataResult<ModrinthSearchResult> = data.read(cx).result();
-                    match result {
-                        FrontendMetadataResult::Loading => {},
-                        FrontendMetadataResult::Loaded(result) => {
-                            page.apply_search_data(result);
-                            page.loading = None;
-                            cx.notify();
-                        },
-                        FrontendMetadataResult::Error(shared_string) => {
-                            page.search_error = Some(shared_string);
-                            page.loading = None;
-                            cx.notify();
-                        },
-                    }
-                });
-                self.loading = Some(subscription);
-            },
-            FrontendMetadataResult::Loaded(result) => {
-                self.apply_search_data(result);
-            },
-            FrontendMetadataResult::Error(shared_string) => {
-                self.search_error = Some(shared_string);
-            },
-        }
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.loading = Some(cx.spawn(async move |page, cx| {
+            let Ok(result) = recv.await else {
+                return;
+            };
+            let _ = page.update(cx, move |page, cx| {
+                match result {
+                    Ok(result) => page.apply_search_data(&result),
+                    Err(error) => page.search_error = Some(SharedString::from(error)),
+                }
+                page.loading = None;
+                cx.notify();
+            });
+        }));
+
+        self.data.backend_handle.send(MessageToBackend::SearchContent {
+            source: self.filter_source,
+            query: search,
+            channel: send,
+        });
     }
 
-    fn apply_search_data(&mut self, search_result: &ModrinthSearchResult) {
+    fn apply_search_data(&mut self, search_result: &ContentSearchResults) {
         if self.pending_clear {
             self.pending_clear = false;
             self.hits.clear();
@@ -315,7 +309,7 @@ impl ModrinthSearchPage {
                         return div()
                             .pl_3()
                             .pt_3()
-                            .child(ErrorAlert::new("search_error", "Error requesting from Modrinth".into(), search_error));
+                            .child(ErrorAlert::new("search_error", format!("Error requesting from {}", self.filter_source.name()).into(), search_error));
                     } else {
                         should_load_more = true;
                         return div()
@@ -336,12 +330,7 @@ impl ModrinthSearchPage {
                     )))
                 };
 
-                let name = hit
-                    .title
-                    .as_ref()
-                    .map(Arc::clone)
-                    .map(SharedString::new)
-                    .unwrap_or(SharedString::new_static("Unnamed"));
+                let name = SharedString::new(hit.title.clone());
                 let author = format!("by {}", hit.author.clone());
                 let description = hit
                     .description
@@ -380,13 +369,11 @@ impl ModrinthSearchPage {
 
                 let environment = h_flex().gap_1().font_bold().child(env_icon).child(env_name);
 
-                let categories = hit.display_categories.iter().flat_map(|categories| {
-                    categories.iter().map(|category| {
-                        let icon = icon_for(category).unwrap_or("icons/diamond.svg");
-                        let icon = Icon::empty().path(icon);
-                        let translated_category = ts!(category.as_str());
-                        h_flex().gap_0p5().child(icon).child(translated_category)
-                    })
+                let categories = hit.categories.iter().map(|category| {
+                    let icon = icon_for(category).unwrap_or("icons/diamond.svg");
+                    let icon = Icon::empty().path(icon);
+                    let translated_category = ts!(category.as_str());
+                    h_flex().gap_0p5().child(icon).child(translated_category)
                 });
 
                 let download_icon = Icon::empty().path("icons/download.svg");
@@ -396,6 +383,7 @@ impl ModrinthSearchPage {
                     .child(format_downloads(hit.downloads));
 
                 let primary_action = self.get_primary_action(&hit.project_id, cx);
+                let source = self.filter_source;
 
                 let buttons = ButtonGroup::new(("buttons", index))
                     .layout(Axis::Vertical)
@@ -414,6 +402,11 @@ impl ModrinthSearchPage {
                                 move |_, window, cx| {
                                     if project_type != ModrinthProjectType::Other {
                                         match primary_action {
+                                            PrimaryAction::Install | PrimaryAction::Reinstall | PrimaryAction::InstallLatest
+                                                if source == ContentSourceId::CurseForge =>
+                                            {
+                                                install_from_curseforge(project_id.clone(), project_type, install_for, &data, window, cx);
+                                            },
                                             PrimaryAction::Install | PrimaryAction::Reinstall => {
                                                 crate::modals::modrinth_install::open(
                                                     name.as_str(),
@@ -443,7 +436,7 @@ impl ModrinthSearchPage {
                                                     modal_action: modal_action.clone()
                                                 });
                                                 crate::modals::generic::show_notification(window, cx,
-                                                    "Error checking for updates".into(), modal_action);
+                                                    ts!("checking_for_updates_error"), modal_action);
                                             },
                                             PrimaryAction::ErrorCheckingForUpdates => {},
                                             PrimaryAction::UpToDate => {},
@@ -456,7 +449,7 @@ impl ModrinthSearchPage {
                                                         modal_action: modal_action.clone()
                                                     });
                                                     crate::modals::generic::show_notification(window, cx,
-                                                        "Error updating mod".into(), modal_action);
+                                                        ts!("updating_mod_error"), modal_action);
                                                 }
 
                                             },
@@ -482,10 +475,16 @@ impl ModrinthSearchPage {
                                 let project_type = hit.project_type.as_str();
                                 let project_id = hit.project_id.clone();
                                 move |_, _, cx| {
-                                    cx.open_url(&format!(
-                                        "https://modrinth.com/{}/{}",
-                                        project_type, project_id
-                                    ));
+                                    match source {
+                                        ContentSourceId::Modrinth => cx.open_url(&format!(
+                                            "https://modrinth.com/{}/{}",
+                                            project_type, project_id
+                                        )),
+                                        ContentSourceId::CurseForge => cx.open_url(&format!(
+                                            "https://www.curseforge.com/projects/{}",
+                                            project_id
+                                        )),
+                                    }
                                 }
                             }),
                     );
@@ -542,10 +541,14 @@ impl ModrinthSearchPage {
         items
     }
 
-    fn get_primary_action(&self, project_id: &str, cx: &App) -> PrimaryAction {
-        let install_latest = self.can_install_latest && !InterfaceConfig::get(cx).modrinth_install_normally;
+    fn get_primary_action(&self, project_id: &Arc<str>, cx: &App) -> PrimaryAction {
+        // CurseForge projects are always installed with their newest compatible file
+        let install_latest = match self.filter_source {
+            ContentSourceId::Modrinth => self.can_install_latest && !InterfaceConfig::get(cx).modrinth_install_normally,
+            ContentSourceId::CurseForge => self.install_for.is_some(),
+        };
 
-        let installed = self.installed_mods_by_project.get(project_id);
+        let installed = self.installed_mods_by_project.get(&(self.filter_source, project_id.clone()));
 
         if let Some(installed) = installed && !installed.is_empty() {
             if !install_latest {
@@ -561,7 +564,7 @@ impl ModrinthSearchPage {
                             action = PrimaryAction::UpToDate;
                         }
                     },
-                    ContentUpdateStatus::Modrinth => {
+                    ContentUpdateStatus::Modrinth | ContentUpdateStatus::CurseForge => {
                         if let PrimaryAction::Update(vec) = &mut action {
                             vec.push(installed_mod.mod_id);
                         } else {
@@ -669,7 +672,17 @@ impl Render for ModrinthSearchPage {
             .child(Input::new(&self.search_state));
 
 
-        if self.can_install_latest {
+        if let Some(game_version) = &self.instance_game_version {
+            top_bar = top_bar.child(Checkbox::new("compatible-only")
+                .label(SharedString::from(format!("Only {game_version}")))
+                .checked(self.filter_game_version)
+                .on_click(cx.listener(|page, value, _, cx| {
+                    page.set_filter_game_version(*value, cx);
+                }))
+            );
+        }
+
+        if self.can_install_latest && self.filter_source == ContentSourceId::Modrinth {
             let tooltip = |window: &mut Window, cx: &mut App| {
                 Tooltip::new(SharedString::new_static("Always install the latest version. Untick to be able to choose older versions of content to install")).build(window, cx)
             };
@@ -694,6 +707,17 @@ impl Render for ModrinthSearchPage {
             .child(top_bar)
             .child(div().size_full().rounded_lg().border_1().border_color(theme.border).child(list));
 
+        let source_button_group = ButtonGroup::new("source")
+            .layout(Axis::Vertical)
+            .outline()
+            .child(Button::new("modrinth").label("Modrinth").selected(self.filter_source == ContentSourceId::Modrinth))
+            .child(Button::new("curseforge").label("CurseForge").selected(self.filter_source == ContentSourceId::CurseForge))
+            .on_click(cx.listener(|page, clicked: &Vec<usize>, window, cx| match clicked[0] {
+                0 => page.set_source(ContentSourceId::Modrinth, window, cx),
+                1 => page.set_source(ContentSourceId::CurseForge, window, cx),
+                _ => {},
+            }));
+
         let type_button_group = ButtonGroup::new("type")
             .layout(Axis::Vertical)
             .outline()
@@ -701,7 +725,8 @@ impl Render for ModrinthSearchPage {
             .child(
                 Button::new("modpacks")
                     .label("Modpacks")
-                    .selected(self.filter_project_type == ModrinthProjectType::Modpack),
+                    .selected(self.filter_project_type == ModrinthProjectType::Modpack)
+                    .disabled(self.filter_source == ContentSourceId::CurseForge),
             )
             .child(
                 Button::new("resourcepacks")
@@ -745,8 +770,10 @@ impl Render for ModrinthSearchPage {
             ModrinthProjectType::Other => &[],
         };
 
-        let category = if self.show_categories.load(std::sync::atomic::Ordering::Relaxed) {
-            ButtonGroup::new("category_group")
+        let category = if self.filter_source != ContentSourceId::Modrinth {
+            None
+        } else if self.show_categories.load(std::sync::atomic::Ordering::Relaxed) {
+            Some(ButtonGroup::new("category_group")
                 .layout(Axis::Vertical)
                 .outline()
                 .multiple(true)
@@ -761,12 +788,12 @@ impl Render for ModrinthSearchPage {
                 }))
                 .on_click(cx.listener(|page, clicked: &Vec<usize>, window, cx| {
                     page.set_filter_categories(clicked.iter().filter_map(|index| categories.get(*index).map(|s| *s)).collect(), window, cx);
-                })).into_any_element()
+                })).into_any_element())
         } else {
             let show_categories = self.show_categories.clone();
-            Button::new("show-categories").icon(IconName::ArrowDown).label("Categories").outline().on_click(move |_, _, _| {
+            Some(Button::new("show-categories").icon(IconName::ArrowDown).label("Categories").outline().on_click(move |_, _, _| {
                 show_categories.store(true, std::sync::atomic::Ordering::Relaxed);
-            }).into_any_element()
+            }).into_any_element())
         };
 
         let parameters = h_flex()
@@ -775,9 +802,10 @@ impl Render for ModrinthSearchPage {
             .flex_1()
             .overflow_y_scrollbar()
             .child(v_flex().h_full().gap_3()
+                .child(source_button_group)
                 .child(type_button_group)
                 .when_some(loader_button_group, |this, group| this.child(group))
-                .child(category)
+                .when_some(category, |this, category| this.child(category))
             );
 
         ui::page(cx, self.page_path.create_breadcrumb(&self.data, cx))
@@ -785,6 +813,51 @@ impl Render for ModrinthSearchPage {
     }
 }
 
+/// CurseForge files can't be picked like Modrinth versions, the newest one that works with the instance is installed
+fn install_from_curseforge(
+    project_id: Arc<str>,
+    project_type: ModrinthProjectType,
+    install_for: Option<InstanceID>,
+    data: &DataEntities,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let (target, loader_hint, version_hint) = if let Some(install_for) = install_for
+        && let Some(entry) = data.instances.read(cx).entries.get(&install_for)
+    {
+        let configuration = &entry.read(cx).configuration;
+        (InstallTarget::Instance(install_for), configuration.loader, Some(configuration.minecraft_version.into()))
+    } else {
+        (InstallTarget::Library, Loader::Unknown, None)
+    };
+
+    let content_install = ContentInstall {
+        target,
+        loader_hint,
+        version_hint,
+        files: [ContentInstallFile {
+            replace_old: None,
+            path: ContentInstallPath::Automatic,
+            download: ContentDownload::CurseForge {
+                project_id: project_id.clone(),
+                version_id: None,
+                project_type,
+            },
+            content_source: ContentSource::CurseForgeProject {
+                project: project_id,
+            },
+        }].into(),
+    };
+    let modal_action = ModalAction::default();
+
+    data.backend_handle.send(MessageToBackend::InstallContent {
+        content: content_install,
+        modal_action: modal_action.clone(),
+    });
+
+    crate::modals::generic::show_notification(window, cx, ts!("install_error_installing"), modal_action);
+}
+
 fn format_downloads(downloads: usize) -> String {
     if downloads >= 1_000_000_000 {
         format!("{}B Downloads", (downloads / 10_000_000) as f64 / 100.0)
@@ -0,0 +1,347 @@
+use std::{collections::{BTreeMap, HashMap}, ops::Range, sync::Arc};
+
+use bridge::{
+    handle::BackendHandle, instance::{InstanceContentID, InstanceID, ModInstallEntry, ModInstallation, ModOverview}, message::MessageToBackend
+};
+use indexmap::IndexMap;
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, h_flex, input::{Input, InputEvent, InputState}, scroll::Scrollbar, spinner::Spinner, v_flex,
+    ActiveTheme as _, Icon, IconName, Sizable, WindowExt
+};
+
+use crate::{entity::{instance::InstanceEntries, DataEntities}, png_render_cache, ts, ui};
+
+struct ModGroup {
+    name: Arc<str>,
+    png_icon: Option<Arc<[u8]>>,
+    installs: Vec<ModInstallation>,
+}
+
+/// Groups the installations by key, sorted by name
+fn group<'a>(entries: impl IntoIterator<Item = &'a ModInstallEntry>) -> Vec<ModOverview> {
+    let mut groups: IndexMap<Arc<str>, ModGroup> = IndexMap::new();
+    for entry in entries {
+        let group = groups.entry(entry.group_key.clone())
+            .or_insert_with(|| ModGroup { name: entry.name.clone(), png_icon: None, installs: Vec::new() });
+        if group.png_icon.is_none() {
+            group.png_icon = entry.png_icon.clone();
+        }
+        group.installs.push(entry.install.clone());
+    }
+
+    let mut overview: Vec<ModOverview> = groups.into_values()
+        .map(|group| ModOverview {
+            name: group.name,
+            png_icon: group.png_icon,
+            installs: group.installs.into(),
+        })
+        .collect();
+    overview.sort_by_cached_key(|overview| overview.name.to_lowercase());
+    overview
+}
+
+/// A row of the list. Each mod is split into its header and one row per installation so every row is the same height
+#[derive(Clone, Copy)]
+enum ModRow {
+    Mod(usize),
+    Install(usize, usize),
+}
+
+/// Every mod installed in any instance, grouped by mod, with the instances using it
+pub struct ModsPage {
+    backend_handle: BackendHandle,
+    instances: Entity<InstanceEntries>,
+    /// The mods of each instance, so a change in one instance only loads that instance again
+    installs: BTreeMap<InstanceID, Arc<[ModInstallEntry]>>,
+    overview: Option<Arc<[ModOverview]>>,
+    search_input: Entity<InputState>,
+    watched: HashMap<InstanceID, Subscription>,
+    load_tasks: HashMap<InstanceID, Task<()>>,
+    /// Rows matching the search, rebuilt on every render so the list only renders the visible ones
+    rows: Vec<ModRow>,
+    scroll_handle: UniformListScrollHandle,
+}
+
+impl ModsPage {
+    pub fn new(data: &DataEntities, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        cx.observe(&data.instances, |page, _, cx| page.watch_instances(cx)).detach();
+
+        let search_input = cx.new(|cx| InputState::new(window, cx).placeholder(ts!("mods_search")));
+        cx.subscribe(&search_input, |_, _, event: &InputEvent, cx| {
+            if let InputEvent::Change = event {
+                cx.notify();
+            }
+        }).detach();
+
+        let mut page = Self {
+            backend_handle: data.backend_handle.clone(),
+            instances: data.instances.clone(),
+            installs: BTreeMap::new(),
+            overview: None,
+            search_input,
+            watched: HashMap::new(),
+            load_tasks: HashMap::new(),
+            rows: Vec::new(),
+            scroll_handle: UniformListScrollHandle::new(),
+        };
+        page.watch_instances(cx);
+        page
+    }
+
+    /// The mods of an instance are loaded again whenever they change, including after the bulk actions
+    fn watch_instances(&mut self, cx: &mut Context<Self>) {
+        let entries = self.instances.read(cx).entries.clone();
+        let watched_before = self.watched.len();
+        self.watched.retain(|id, _| entries.contains_key(id));
+        self.installs.retain(|id, _| entries.contains_key(id));
+        self.load_tasks.retain(|id, _| entries.contains_key(id));
+        let removed = self.watched.len() != watched_before;
+
+        for (id, entry) in entries {
+            if self.watched.contains_key(&id) {
+                continue;
+            }
+            let mods = entry.read(cx).mods.clone();
+            self.watched.insert(id, cx.observe(&mods, move |page, _, cx| page.reload(id, cx)));
+            self.reload(id, cx);
+        }
+
+        if removed || self.load_tasks.is_empty() {
+            self.regroup(cx);
+        }
+    }
+
+    fn reload(&mut self, id: InstanceID, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        let task = cx.spawn(async move |page, cx| {
+            let Ok(installs) = recv.await else {
+                return;
+            };
+            let _ = page.update(cx, move |page, cx| {
+                page.load_tasks.remove(&id);
+                if page.watched.contains_key(&id) {
+                    page.installs.insert(id, installs);
+                }
+                page.regroup(cx);
+            });
+        });
+        self.load_tasks.insert(id, task);
+
+        self.backend_handle.send(MessageToBackend::GetInstanceModInstalls { id, channel: send });
+    }
+
+    /// Groups the loaded mods again. Until every instance has been loaded once, the page keeps showing the spinner
+    fn regroup(&mut self, cx: &mut Context<Self>) {
+        if self.overview.is_none() && !self.load_tasks.is_empty() {
+            return;
+        }
+        self.overview = Some(group(self.installs.values().flat_map(|installs| installs.iter())).into());
+        cx.notify();
+    }
+
+    fn update_installs(installs: &[ModInstallation], title: SharedString, backend_handle: &BackendHandle, window: &mut Window, cx: &mut App) {
+        let targets: Vec<(InstanceID, InstanceContentID)> = installs.iter()
+            .map(|install| (install.instance, install.content_id))
+            .collect();
+        let backend_handle = backend_handle.clone();
+        crate::modals::generic::show_retryable_modal(window, cx, title, ts!("mods_error_updating"), move |modal_action| {
+            backend_handle.send(MessageToBackend::UpdateContentInInstances { targets: targets.clone(), modal_action });
+        });
+    }
+
+    fn remove_installs(installs: Arc<[ModInstallation]>, message: String, backend_handle: &BackendHandle, window: &mut Window, cx: &mut App) {
+        let backend_handle = backend_handle.clone();
+        window.open_dialog(cx, move |dialog, _, _| {
+            let backend_handle = backend_handle.clone();
+            let installs = installs.clone();
+            dialog
+                .confirm()
+                .title(ts!("mods_remove"))
+                .child(message.clone())
+                .on_ok(move |_, _, _| {
+                    let mut by_instance: HashMap<InstanceID, Vec<InstanceContentID>> = HashMap::new();
+                    for install in installs.iter() {
+                        by_instance.entry(install.instance).or_default().push(install.content_id);
+                    }
+                    for (id, content_ids) in by_instance {
+                        backend_handle.send(MessageToBackend::DeleteContent { id, content_ids });
+                    }
+                    true
+                })
+        });
+    }
+
+    fn render_mod(&self, index: usize, overview: &ModOverview, cx: &mut App) -> Div {
+        let icon = if let Some(png_icon) = overview.png_icon.as_ref() {
+            png_render_cache::render(Arc::clone(png_icon), cx)
+        } else {
+            gpui::img(ImageSource::Resource(Resource::Embedded("images/default_mod.png".into())))
+        };
+
+        let updatable: Vec<ModInstallation> = overview.installs.iter().filter(|install| install.can_update).cloned().collect();
+        let name = overview.name.clone();
+
+        let mut header = h_flex()
+            .gap_3()
+            .child(icon.size_10().min_w_10().min_h_10())
+            .child(v_flex()
+                .flex_1()
+                .child(SharedString::from(overview.name.clone()))
+                .child(div().text_sm().text_color(cx.theme().muted_foreground)
+                    .child(rust_i18n::t!("mods_used_by", count = overview.installs.len()).into_owned())));
+
+        if !updatable.is_empty() {
+            header = header.child(Button::new(("update", index)).success().small().icon(Icon::empty().path("icons/download.svg"))
+                .label(rust_i18n::t!("mods_update_count", count = updatable.len()).into_owned())
+                .on_click({
+                    let backend_handle = self.backend_handle.clone();
+                    let name = name.clone();
+                    move |_, window, cx| {
+                        let title = rust_i18n::t!("mods_updating", name = name).into_owned().into();
+                        Self::update_installs(&updatable, title, &backend_handle, window, cx);
+                    }
+                }));
+        }
+        header = header.child(Button::new(("remove", index)).danger().small().icon(Icon::empty().path("icons/trash-2.svg"))
+            .label(ts!("mods_remove_everywhere"))
+            .on_click({
+                let backend_handle = self.backend_handle.clone();
+                let installs = overview.installs.clone();
+                move |_, window, cx| {
+                    let message = rust_i18n::t!("mods_remove_confirm", name = name, count = installs.len()).into_owned();
+                    Self::remove_installs(installs.clone(), message, &backend_handle, window, cx);
+                }
+            }));
+
+        h_flex()
+            .h_12()
+            .px_2()
+            .border_t_1()
+            .border_color(cx.theme().border)
+            .child(header.flex_1())
+    }
+
+    fn render_install(&self, row: usize, install_index: usize, overview: &ModOverview, cx: &mut App) -> Div {
+        let install = &overview.installs[install_index];
+        let mut details = vec![install.version.to_string()];
+        if !install.enabled {
+            details.push(ts!("mods_disabled").to_string());
+        }
+        if install.can_update {
+            details.push(ts!("mods_update_available").to_string());
+        }
+
+        h_flex()
+            .h_12()
+            .gap_2()
+            .pl_12()
+            .pr_2()
+            .child(div().flex_1().child(SharedString::from(install.instance_name.clone())))
+            .child(div().text_sm().text_color(cx.theme().muted_foreground).child(details.join(" • ")))
+            .child(Button::new(("remove-install", row)).icon(IconName::Close).small().on_click({
+                let backend_handle = self.backend_handle.clone();
+                let install = install.clone();
+                let name = overview.name.clone();
+                move |_, window, cx| {
+                    let message = rust_i18n::t!("mods_remove_from_confirm", name = name, instance = install.instance_name).into_owned();
+                    Self::remove_installs([install.clone()].into(), message, &backend_handle, window, cx);
+                }
+            }))
+    }
+
+    fn render_items(&mut self, visible_range: Range<usize>, _window: &mut Window, cx: &mut Context<Self>) -> Vec<Div> {
+        let Some(overview) = self.overview.clone() else {
+            return Vec::new();
+        };
+        visible_range
+            .filter_map(|row| match *self.rows.get(row)? {
+                ModRow::Mod(index) => Some(self.render_mod(index, overview.get(index)?, cx)),
+                ModRow::Install(index, install_index) => Some(self.render_install(row, install_index, overview.get(index)?, cx)),
+            })
+            .collect()
+    }
+}
+
+impl Render for ModsPage {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(overview) = self.overview.clone() else {
+            return ui::page(cx, h_flex().gap_8().child(ts!("mods_title")))
+                .child(v_flex().size_full().p_3().child(Spinner::new().large()))
+                .into_any_element();
+        };
+
+        let query = self.search_input.read(cx).value().to_lowercase();
+        let shown: Vec<(usize, &ModOverview)> = overview.iter().enumerate()
+            .filter(|(_, overview)| query.is_empty() || overview.name.to_lowercase().contains(&query))
+            .collect();
+
+        // The instances are checked for updates before updating, so this also finds updates that weren't known yet
+        let actions = h_flex()
+            .gap_2()
+            .child(div().flex_1().child(Input::new(&self.search_input).small()))
+            .child(Button::new("update-all").success().icon(Icon::empty().path("icons/refresh-ccw.svg")).label(ts!("mods_update_all")).on_click({
+                let backend_handle = self.backend_handle.clone();
+                let installs: Vec<ModInstallation> = shown.iter().flat_map(|(_, overview)| overview.installs.iter()).cloned().collect();
+                move |_, window, cx| {
+                    Self::update_installs(&installs, ts!("mods_updating_all"), &backend_handle, window, cx);
+                }
+            }));
+
+        let mut content = v_flex().flex_1().min_h_0().p_3().gap_2().child(actions);
+        if shown.is_empty() {
+            content = content.child(ts!("mods_none"));
+        }
+
+        self.rows = shown.iter()
+            .flat_map(|(index, overview)| std::iter::once(ModRow::Mod(*index))
+                .chain((0..overview.installs.len()).map(|install_index| ModRow::Install(*index, install_index))))
+            .collect();
+        content = content.child(h_flex()
+            .flex_1()
+            .min_h_0()
+            .child(uniform_list("mods", self.rows.len(), cx.processor(Self::render_items))
+                .size_full()
+                .track_scroll(&self.scroll_handle))
+            .child(div().w_3().h_full().py_3().child(Scrollbar::vertical(&self.scroll_handle))));
+
+        ui::page(cx, h_flex().gap_8().child(ts!("mods_title"))).child(content).into_any_element()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(key: &str, name: &str, index: usize, version: &str) -> ModInstallEntry {
+        ModInstallEntry {
+            group_key: key.into(),
+            name: name.into(),
+            png_icon: None,
+            install: ModInstallation {
+                instance: InstanceID { index, generation: 0 },
+                instance_name: format!("Instance {index}").into(),
+                content_id: InstanceContentID { index: 0, generation: 0 },
+                version: version.into(),
+                enabled: true,
+                can_update: false,
+            },
+        }
+    }
+
+    #[test]
+    fn groups_installs_of_the_same_mod() {
+        let entries = [
+            entry("id:sodium", "Sodium", 0, "0.5"),
+            entry("id:lithium", "lithium", 0, "0.11"),
+            entry("id:sodium", "Sodium", 1, "0.6"),
+        ];
+        let overview = group(&entries);
+
+        assert_eq!(overview.len(), 2);
+        assert_eq!(&*overview[0].name, "lithium");
+        assert_eq!(&*overview[1].name, "Sodium");
+        let versions: Vec<&str> = overview[1].installs.iter().map(|install| &*install.version).collect();
+        assert_eq!(versions, ["0.5", "0.6"]);
+    }
+}
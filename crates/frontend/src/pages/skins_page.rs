@@ -0,0 +1,292 @@
+use std::sync::Arc;
+
+use bridge::{
+    account::{LibrarySkin, SkinModel, SkinProfile, SkinSource, SkinTexture}, handle::BackendHandle, message::MessageToBackend
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, h_flex, notification::{Notification, NotificationType}, spinner::Spinner, v_flex, ActiveTheme as _, Disableable, Icon, IconName, Selectable, Sizable, WindowExt
+};
+use uuid::Uuid;
+
+use crate::{entity::{account::AccountEntries, DataEntities}, png_render_cache, ts, ui};
+
+/// The skin and cape of the selected account, and skins kept locally to switch between
+pub struct SkinsPage {
+    backend_handle: BackendHandle,
+    accounts: Entity<AccountEntries>,
+    /// The account the profile was loaded for
+    account: Option<Uuid>,
+    /// None while loading
+    profile: Option<Result<SkinProfile, Arc<str>>>,
+    library: Option<Arc<[LibrarySkin]>>,
+    /// The model imported skins are added with
+    import_model: SkinModel,
+    scroll_handle: ScrollHandle,
+    _profile_task: Task<()>,
+    _library_task: Task<()>,
+}
+
+fn model_label(model: SkinModel) -> SharedString {
+    match model {
+        SkinModel::Classic => ts!("skins_model_classic"),
+        SkinModel::Slim => ts!("skins_model_slim"),
+    }
+}
+
+fn show_error(error: impl Into<SharedString>, window: &mut Window, cx: &mut App) {
+    let notification: Notification = (NotificationType::Error, error.into()).into();
+    window.push_notification(notification.autohide(false), cx);
+}
+
+impl SkinsPage {
+    pub fn new(data: &DataEntities, _window: &mut Window, cx: &mut Context<Self>) -> Self {
+        cx.observe(&data.accounts, |page, accounts, cx| {
+            if accounts.read(cx).selected_account_uuid != page.account {
+                page.reload_profile(cx);
+            }
+        }).detach();
+
+        let mut page = Self {
+            backend_handle: data.backend_handle.clone(),
+            accounts: data.accounts.clone(),
+            account: None,
+            profile: None,
+            library: None,
+            import_model: SkinModel::Classic,
+            scroll_handle: ScrollHandle::new(),
+            _profile_task: Task::ready(()),
+            _library_task: Task::ready(()),
+        };
+        page.reload_profile(cx);
+        page.reload_library(cx);
+        page
+    }
+
+    fn reload_profile(&mut self, cx: &mut Context<Self>) {
+        self.account = self.accounts.read(cx).selected_account_uuid;
+        self.profile = None;
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self._profile_task = cx.spawn(async move |page, cx| {
+            let Ok(profile) = recv.await else {
+                return;
+            };
+            let _ = page.update(cx, move |page, cx| {
+                page.profile = Some(profile);
+                cx.notify();
+            });
+        });
+        self.backend_handle.send(MessageToBackend::GetSkinProfile { channel: send });
+        cx.notify();
+    }
+
+    fn reload_library(&mut self, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self._library_task = cx.spawn(async move |page, cx| {
+            let Ok(library) = recv.await else {
+                return;
+            };
+            let _ = page.update(cx, move |page, cx| {
+                page.library = Some(library);
+                cx.notify();
+            });
+        });
+        self.backend_handle.send(MessageToBackend::GetSkinLibrary { channel: send });
+    }
+
+    fn add_to_library(&mut self, source: SkinSource, model: SkinModel, window: &mut Window, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::AddSkinToLibrary { source, model, channel: send });
+        cx.spawn_in(window, async move |page, cx| {
+            let Ok(result) = recv.await else {
+                return;
+            };
+            let _ = page.update_in(cx, move |page, window, cx| {
+                match result {
+                    Ok(()) => page.reload_library(cx),
+                    Err(error) => show_error(rust_i18n::t!("skins_error_adding", error = error).into_owned(), window, cx),
+                }
+            });
+        }).detach();
+    }
+
+    fn import_skin(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let receiver = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: true,
+            prompt: Some(ts!("skins_import_prompt")),
+        });
+        let model = self.import_model;
+        cx.spawn_in(window, async move |page, cx| {
+            let Ok(Ok(Some(paths))) = receiver.await else {
+                return;
+            };
+            let _ = page.update_in(cx, move |page, window, cx| {
+                for path in paths {
+                    page.add_to_library(SkinSource::File(path.into()), model, window, cx);
+                }
+            });
+        }).detach();
+    }
+
+    fn upload(&mut self, skin: &LibrarySkin, window: &mut Window, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        let modal_action = bridge::modal_action::ModalAction::default();
+        self.backend_handle.send(MessageToBackend::UploadSkin { id: skin.id.clone(), modal_action: modal_action.clone(), channel: send });
+
+        let title = SharedString::from(rust_i18n::t!("skins_uploading", name = skin.name).into_owned());
+        crate::modals::generic::show_modal(window, cx, title, ts!("skins_error_uploading"), modal_action);
+
+        self._profile_task = cx.spawn(async move |page, cx| {
+            if recv.await.is_err() {
+                return;
+            }
+            let _ = page.update(cx, |page, cx| page.reload_profile(cx));
+        });
+    }
+
+    fn render_texture(texture: &SkinTexture, width: Pixels, height: Pixels, cx: &mut App) -> Img {
+        png_render_cache::render(texture.preview.clone(), cx).w(width).h(height).object_fit(ObjectFit::Contain)
+    }
+
+    fn render_profile(&self, cx: &mut Context<Self>) -> AnyElement {
+        let profile = match &self.profile {
+            None => return Spinner::new().into_any_element(),
+            Some(Err(error)) => return div().text_color(cx.theme().danger).child(SharedString::from(error.clone())).into_any_element(),
+            Some(Ok(profile)) => profile,
+        };
+
+        let skin = match &profile.skin {
+            Some(skin) => Self::render_texture(skin, px(96.), px(192.), cx).into_any_element(),
+            None => div().w(px(96.)).child(ts!("skins_default_skin")).into_any_element(),
+        };
+
+        let mut details = v_flex()
+            .flex_1()
+            .gap_1()
+            .child(div().text_lg().child(SharedString::new(profile.username.clone())))
+            .child(div().text_sm().text_color(cx.theme().muted_foreground).child(match &profile.skin {
+                Some(skin) => model_label(skin.model),
+                None => ts!("skins_default_skin"),
+            }));
+        if profile.cape.is_none() {
+            details = details.child(div().text_sm().text_color(cx.theme().muted_foreground).child(ts!("skins_no_cape")));
+        }
+
+        let mut actions = h_flex().gap_2()
+            .child(Button::new("refresh-profile").icon(IconName::Redo).label(ts!("skins_refresh")).on_click(cx.listener(|page, _, _, cx| {
+                page.reload_profile(cx);
+            })));
+        if let Some(skin) = &profile.skin {
+            let source = SkinSource::Texture { name: profile.username.clone(), png: skin.png.clone() };
+            let model = skin.model;
+            actions = actions.child(Button::new("save-current").icon(IconName::Plus).label(ts!("skins_save_current")).on_click(cx.listener(move |page, _, window, cx| {
+                page.add_to_library(source.clone(), model, window, cx);
+            })));
+        }
+        details = details.child(actions);
+
+        let mut row = h_flex().gap_4().items_start().child(skin);
+        if let Some(cape) = &profile.cape {
+            row = row.child(Self::render_texture(cape, px(60.), px(96.), cx));
+        }
+        row.child(details).into_any_element()
+    }
+
+    fn render_library_skin(&self, index: usize, skin: &LibrarySkin, can_upload: bool, cx: &mut Context<Self>) -> Div {
+        let active = self.profile.as_ref()
+            .and_then(|profile| profile.as_ref().ok())
+            .and_then(|profile| profile.skin.as_ref())
+            .is_some_and(|current| current.png == skin.texture.png && current.model == skin.texture.model);
+
+        let use_button = Button::new(("use", index))
+            .small()
+            .label(if active { ts!("skins_active") } else { ts!("skins_use") })
+            .success()
+            .disabled(active || !can_upload)
+            .on_click(cx.listener({
+                let skin = skin.clone();
+                move |page, _, window, cx| page.upload(&skin, window, cx)
+            }));
+
+        let remove_button = Button::new(("remove", index))
+            .small()
+            .icon(Icon::default().path("icons/trash-2.svg"))
+            .danger()
+            .on_click(cx.listener({
+                let id = skin.id.clone();
+                move |page, _, _, cx| {
+                    let (send, recv) = tokio::sync::oneshot::channel();
+                    page.backend_handle.send(MessageToBackend::RemoveSkinFromLibrary { id: id.clone(), channel: send });
+                    page._library_task = cx.spawn(async move |page, cx| {
+                        if recv.await.is_err() {
+                            return;
+                        }
+                        let _ = page.update(cx, |page, cx| page.reload_library(cx));
+                    });
+                }
+            }));
+
+        v_flex()
+            .w(px(140.))
+            .gap_2()
+            .p_3()
+            .items_center()
+            .border_1()
+            .rounded(cx.theme().radius)
+            .border_color(if active { cx.theme().info } else { cx.theme().border })
+            .child(Self::render_texture(&skin.texture, px(64.), px(128.), cx))
+            .child(div().text_sm().truncate().max_w_full().child(SharedString::new(skin.name.clone())))
+            .child(div().text_xs().text_color(cx.theme().muted_foreground).child(model_label(skin.texture.model)))
+            .child(h_flex().gap_2().child(use_button).child(remove_button))
+    }
+}
+
+impl Render for SkinsPage {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let can_upload = matches!(self.profile, Some(Ok(_)));
+
+        let mut model_buttons = h_flex().gap_1();
+        for model in [SkinModel::Classic, SkinModel::Slim] {
+            model_buttons = model_buttons.child(Button::new(("import-model", model as usize))
+                .small()
+                .label(model_label(model))
+                .selected(self.import_model == model)
+                .on_click(cx.listener(move |page, _, _, cx| {
+                    page.import_model = model;
+                    cx.notify();
+                })));
+        }
+
+        let library_header = h_flex()
+            .gap_3()
+            .child(div().text_lg().child(ts!("skins_library")))
+            .child(model_buttons)
+            .child(Button::new("import").small().success().icon(IconName::Plus).label(ts!("skins_import")).on_click(cx.listener(|page, _, window, cx| {
+                page.import_skin(window, cx);
+            })));
+
+        let library = match self.library.clone() {
+            None => Spinner::new().into_any_element(),
+            Some(library) if library.is_empty() => div().child(ts!("skins_library_empty")).into_any_element(),
+            Some(library) => h_flex()
+                .flex_wrap()
+                .gap_3()
+                .children(library.iter().enumerate().map(|(index, skin)| self.render_library_skin(index, skin, can_upload, cx)))
+                .into_any_element(),
+        };
+
+        let content = v_flex()
+            .size_full()
+            .p_3()
+            .gap_4()
+            .child(div().text_lg().child(ts!("skins_current")))
+            .child(self.render_profile(cx))
+            .child(library_header)
+            .child(library);
+
+        ui::scroll_page(cx, h_flex().gap_8().child(ts!("skins_title")), &self.scroll_handle, content)
+    }
+}
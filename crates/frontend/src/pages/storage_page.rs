@@ -0,0 +1,183 @@
+use bridge::{handle::BackendHandle, message::{MessageToBackend, StorageCleanup, StorageReport}};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, h_flex, notification::NotificationType, spinner::Spinner, v_flex, ActiveTheme as _, Disableable, Icon, Sizable, WindowExt
+};
+
+use crate::{entity::DataEntities, format, ts, ui};
+
+/// How much disk space the launcher uses, with ways of getting rid of files that aren't needed anymore
+pub struct StoragePage {
+    backend_handle: BackendHandle,
+    report: Option<StorageReport>,
+    /// Cleanup currently running, only one runs at a time
+    cleaning: Option<StorageCleanup>,
+    _get_report_task: Task<()>,
+    scroll_handle: ScrollHandle,
+}
+
+impl StoragePage {
+    pub fn new(data: &DataEntities, _window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let mut page = Self {
+            backend_handle: data.backend_handle.clone(),
+            report: None,
+            cleaning: None,
+            _get_report_task: Task::ready(()),
+            scroll_handle: ScrollHandle::new(),
+        };
+        page.update_report(cx);
+        page
+    }
+
+    fn update_report(&mut self, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self._get_report_task = cx.spawn(async move |page, cx| {
+            let Ok(report) = recv.await else {
+                return;
+            };
+            _ = page.update(cx, move |page, cx| {
+                page.report = Some(report);
+                cx.notify();
+            });
+        });
+        self.report = None;
+        self.backend_handle.send(MessageToBackend::GetStorageReport { channel: send });
+        cx.notify();
+    }
+
+    fn cleanup(&mut self, cleanup: StorageCleanup, window: &mut Window, cx: &mut Context<Self>) {
+        if self.cleaning.is_some() {
+            return;
+        }
+        self.cleaning = Some(cleanup);
+        cx.notify();
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::CleanupStorage { cleanup, channel: send });
+
+        cx.spawn_in(window, async move |page, cx| {
+            let result = recv.await.unwrap_or_else(|_| Err("The backend didn't respond".into()));
+            _ = page.update_in(cx, |page, window, cx| {
+                page.cleaning = None;
+                let notification = match result {
+                    Ok(freed) => (NotificationType::Success, ts!("storage_freed", size = format::bytes(freed))),
+                    Err(error) => (NotificationType::Error, SharedString::from(error.to_string())),
+                };
+                window.push_notification(notification, cx);
+                page.update_report(cx);
+            });
+        }).detach();
+    }
+
+    fn render_row(
+        &self,
+        label: SharedString,
+        description: SharedString,
+        size: u64,
+        action: Option<(StorageCleanup, SharedString)>,
+        cx: &mut Context<Self>,
+    ) -> Div {
+        let details = v_flex()
+            .flex_1()
+            .min_w_0()
+            .child(div().child(label))
+            .child(div().text_sm().text_color(cx.theme().muted_foreground).child(description));
+
+        let mut row = h_flex()
+            .gap_4()
+            .py_2()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(details)
+            .child(div().flex_none().child(format::bytes(size)));
+
+        if let Some((cleanup, action_label)) = action {
+            let id: &'static str = match cleanup {
+                StorageCleanup::UnusedAssets => "clear-unused-assets",
+                StorageCleanup::UnusedLibraries => "clear-unused-libraries",
+                StorageCleanup::NativesDirs => "prune-natives",
+                StorageCleanup::OrphanedFiles => "remove-orphaned-files",
+            };
+            row = row.child(Button::new(id)
+                .small()
+                .label(action_label)
+                .loading(self.cleaning == Some(cleanup))
+                .disabled(self.cleaning.is_some())
+                .on_click(cx.listener(move |page, _, window, cx| {
+                    page.cleanup(cleanup, window, cx);
+                })));
+        }
+
+        row
+    }
+}
+
+impl Render for StoragePage {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let refresh = Button::new("refresh-storage").icon(Icon::default().path("icons/refresh-ccw.svg")).ghost().small().label(ts!("storage_refresh"))
+            .disabled(self.report.is_none())
+            .on_click(cx.listener(|page, _, _, cx| {
+                page.update_report(cx);
+            }));
+
+        let Some(report) = self.report.clone() else {
+            let content = v_flex().p_4().gap_2()
+                .child(h_flex().gap_2().child(Spinner::new()).child(ts!("storage_calculating")));
+            return ui::scroll_page(cx, ts!("storage_title"), &self.scroll_handle, content);
+        };
+
+        let instances_total: u64 = report.instances.iter().map(|instance| instance.size).sum();
+        let total = report.assets + report.libraries + report.java_runtimes + report.metadata_cache + report.natives
+            + report.orphaned_files + instances_total;
+
+        let unused_assets = match report.unused_assets {
+            Some(unused) => ts!("storage_assets_description", unused = format::bytes(unused)),
+            None => ts!("storage_assets_unknown"),
+        };
+        let unused_libraries = match report.unused_libraries {
+            Some(unused) => ts!("storage_libraries_description", unused = format::bytes(unused)),
+            None => ts!("storage_libraries_unknown"),
+        };
+        let libraries_action = report.unused_libraries.filter(|unused| *unused > 0)
+            .map(|_| (StorageCleanup::UnusedLibraries, ts!("storage_clear_unused_libraries")));
+        let assets_action = report.unused_assets.filter(|unused| *unused > 0)
+            .map(|_| (StorageCleanup::UnusedAssets, ts!("storage_clear_unused_assets")));
+        let natives_action = (report.natives > 0).then(|| (StorageCleanup::NativesDirs, ts!("storage_prune_natives")));
+        let orphaned_action = (report.orphaned_files > 0).then(|| (StorageCleanup::OrphanedFiles, ts!("storage_remove_orphaned")));
+
+        let launcher_files = v_flex()
+            .child(self.render_row(ts!("storage_assets"), unused_assets, report.assets, assets_action, cx))
+            .child(self.render_row(ts!("storage_libraries"), unused_libraries, report.libraries, libraries_action, cx))
+            .child(self.render_row(ts!("storage_java_runtimes"), ts!("storage_java_runtimes_description"), report.java_runtimes, None, cx))
+            .child(self.render_row(ts!("storage_metadata_cache"), ts!("storage_metadata_cache_description"), report.metadata_cache, None, cx))
+            .child(self.render_row(ts!("storage_natives"), ts!("storage_natives_description"), report.natives, natives_action, cx))
+            .child(self.render_row(ts!("storage_orphaned"), ts!("storage_orphaned_description"), report.orphaned_files, orphaned_action, cx));
+
+        let instances = v_flex().children(report.instances.iter().map(|instance| {
+            h_flex()
+                .gap_4()
+                .py_2()
+                .border_b_1()
+                .border_color(cx.theme().border)
+                .child(div().flex_1().min_w_0().truncate().child(SharedString::from(instance.name.clone())))
+                .child(div().flex_none().child(format::bytes(instance.size)))
+        }));
+
+        let content = v_flex()
+            .p_4()
+            .gap_4()
+            .child(h_flex()
+                .gap_2()
+                .child(div().flex_1().child(ts!("storage_total", size = format::bytes(total))))
+                .child(refresh))
+            .child(v_flex().gap_1().child(div().text_lg().child(ts!("storage_launcher_files"))).child(launcher_files))
+            .child(v_flex().gap_1()
+                .child(h_flex()
+                    .gap_2()
+                    .child(div().flex_1().text_lg().child(ts!("storage_instances")))
+                    .child(div().text_color(cx.theme().muted_foreground).child(format::bytes(instances_total))))
+                .child(instances));
+
+        ui::scroll_page(cx, ts!("storage_title"), &self.scroll_handle, content)
+    }
+}
@@ -1,11 +1,10 @@
-use bridge::{handle::BackendHandle, message::{MessageToBackend, SyncState}};
+use bridge::{handle::BackendHandle, message::{InstanceSyncStatus, MessageToBackend, SyncState}};
 use enumset::EnumSet;
 use gpui::{prelude::*, *};
 use gpui_component::{
     button::{Button, ButtonVariants},
     checkbox::Checkbox,
     h_flex,
-    scroll::ScrollableElement,
     spinner::Spinner,
     tooltip::Tooltip,
     v_flex,
@@ -27,6 +26,8 @@ pub struct SyncingPage {
     sync_state: SyncState,
     pending: EnumSet<SyncTarget>,
     loading: EnumSet<SyncTarget>,
+    /// Targets showing the status of each instance
+    expanded: EnumSet<SyncTarget>,
     _get_sync_state_task: Task<()>,
     // Global instance override configuration
     global_memory_enabled: bool,
@@ -37,6 +38,7 @@ pub struct SyncingPage {
     global_jvm_binary_enabled: bool,
     global_jvm_binary_path: Option<std::sync::Arc<Path>>,
     _select_jvm_binary_task: Task<()>,
+    scroll_handle: ScrollHandle,
 }
 
 impl SyncingPage {
@@ -65,6 +67,7 @@ impl SyncingPage {
             sync_state: SyncState::default(),
             pending: EnumSet::all(),
             loading: EnumSet::all(),
+            expanded: EnumSet::empty(),
             _get_sync_state_task: Task::ready(()),
             global_memory_enabled,
             global_memory_min_input_state: cx.new(|cx| InputState::new(window, cx).default_value(global_memory_min_value)),
@@ -74,6 +77,7 @@ impl SyncingPage {
             global_jvm_binary_enabled,
             global_jvm_binary_path,
             _select_jvm_binary_task: Task::ready(()),
+            scroll_handle: ScrollHandle::new(),
         };
 
         page.update_sync_state(cx);
@@ -178,7 +182,73 @@ impl SyncingPage {
             }
         }
 
-        base
+        if target.get_folder().is_none() {
+            return base;
+        }
+
+        let expanded = self.expanded.contains(target);
+        base = base.child(Button::new(ElementId::Name(format!("{id}-expand").into()))
+            .small()
+            .ghost()
+            .icon(if expanded { IconName::ChevronUp } else { IconName::ChevronDown })
+            .label(if expanded { "Hide instances" } else { "Show instances" })
+            .on_click(cx.listener(move |page, _, _, cx| {
+                if !page.expanded.remove(target) {
+                    page.expanded.insert(target);
+                }
+                cx.notify();
+            })));
+
+        if !expanded {
+            return base;
+        }
+
+        let instances = v_flex()
+            .pl_8()
+            .gap_1()
+            .children(self.sync_state.instances[target].iter().enumerate().map(|(index, instance)| {
+                let (status, color, action) = match instance.status {
+                    InstanceSyncStatus::Linked => ("Linked", info, "Unlink"),
+                    InstanceSyncStatus::Unlinked => ("Not linked", cx.theme().muted_foreground, "Link"),
+                    InstanceSyncStatus::Conflicting => ("Has its own folder", warning, "Merge and link"),
+                };
+                let link = instance.status != InstanceSyncStatus::Linked;
+                let name = instance.name.clone();
+                let button = Button::new((id, index))
+                    .small()
+                    .label(action)
+                    .disabled(self.loading.contains(target))
+                    .when(instance.status == InstanceSyncStatus::Conflicting, |button| {
+                        button.tooltip("Copies files the synced folder doesn't have yet into it, then links the instance. If some files differ, the instance's folder is kept as a backup")
+                    })
+                    .on_click(cx.listener(move |page, _, _, cx| {
+                        let (send, recv) = tokio::sync::oneshot::channel();
+                        page.backend_handle.send(MessageToBackend::SetInstanceSyncing {
+                            target,
+                            instance: name.clone(),
+                            value: link,
+                            channel: send,
+                        });
+
+                        page.loading.insert(target);
+                        cx.spawn(async move |page, cx| {
+                            _ = recv.await;
+                            _ = page.update(cx, |page, cx| {
+                                if page.pending.is_empty() {
+                                    page.pending.insert(target);
+                                    page.update_sync_state(cx);
+                                }
+                            });
+                        }).detach();
+                    }));
+                h_flex()
+                    .gap_2()
+                    .child(div().w_64().truncate().child(SharedString::new(instance.name.clone())))
+                    .child(div().w_40().text_color(color).child(status))
+                    .child(button)
+            }));
+
+        v_flex().gap_2().child(base).child(instances)
     }
 
     fn on_global_memory_changed(
@@ -187,6 +257,33 @@ impl SyncingPage {
         _event: &gpui_component::input::InputEvent,
         cx: &mut Context<Self>,
     ) {
+        self.save_global_memory(cx);
+    }
+
+    /// Fills in the memory suggested for the most heavily modded instance
+    fn apply_recommended_global_memory(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.backend_handle.send(MessageToBackend::GetRecommendedMemory { id: None, channel: send });
+
+        let this_entity = cx.entity();
+        window.spawn(cx, async move |cx| {
+            let Ok(Some(recommended)) = recv.await else {
+                return;
+            };
+            _ = cx.update_window_entity(&this_entity, move |this, window, cx| {
+                this.global_memory_min_input_state.update(cx, |input, cx| {
+                    input.set_value(recommended.min.to_string(), window, cx);
+                });
+                this.global_memory_max_input_state.update(cx, |input, cx| {
+                    input.set_value(recommended.max.to_string(), window, cx);
+                });
+                this.save_global_memory(cx);
+                cx.notify();
+            });
+        }).detach();
+    }
+
+    fn save_global_memory(&mut self, cx: &mut Context<Self>) {
         if !self.global_memory_enabled {
             return;
         }
@@ -240,11 +337,21 @@ impl Render for SyncingPage {
                 .gap_3()
                 .child("These options allow for syncing various files/folders across instances")
                 .child(Spinner::new().with_size(gpui_component::Size::Large));
-            return ui::page(cx, h_flex().gap_8().child("Syncing")).child(content).overflow_y_scrollbar();
+            return ui::scroll_page(cx, h_flex().gap_8().child("Syncing"), &self.scroll_handle, content);
         }
 
         let sync_folder = self.sync_state.sync_folder.clone();
 
+        let global_jvm_flag_warnings = if self.global_jvm_flags_enabled {
+            let flags = InstanceJvmFlagsConfiguration {
+                enabled: true,
+                flags: self.global_jvm_flags_input_state.read(cx).value().into(),
+            };
+            schema::instance::jvm_flag_warnings(&flags.split(), self.global_memory_enabled)
+        } else {
+            Vec::new()
+        };
+
         let warning = cx.theme().red;
         let info = cx.theme().blue;
         let content = v_flex()
@@ -396,6 +503,14 @@ impl Render for SyncingPage {
                                     .gap_1()
                                     .child(NumberInput::new(&self.global_memory_max_input_state).small().suffix("MiB").disabled(!self.global_memory_enabled))
                                     .child("Max")
+                                    .child(Button::new("global_recommended_memory")
+                                        .small()
+                                        .label("Recommended")
+                                        .tooltip("Based on the installed memory and the most heavily modded instance")
+                                        .disabled(!self.global_memory_enabled)
+                                        .on_click(cx.listener(|page, _, window, cx| {
+                                            page.apply_recommended_global_memory(window, cx);
+                                        })))
                             )
                     )
                     .child(
@@ -417,6 +532,7 @@ impl Render for SyncingPage {
                                 })))
                             .child("JVM Flags")
                             .child(Input::new(&self.global_jvm_flags_input_state).disabled(!self.global_jvm_flags_enabled))
+                            .children(global_jvm_flag_warnings.into_iter().map(|warning| div().text_sm().text_color(cx.theme().warning).child(warning)))
                     )
                     .child(
                         v_flex()
@@ -427,7 +543,7 @@ impl Render for SyncingPage {
                                 .on_click(cx.listener(|page, value, _, cx| {
                                     page.global_jvm_binary_enabled = *value;
                                     InterfaceConfig::get_mut(cx).global_jvm_binary = if *value && page.global_jvm_binary_path.is_some() {
-                                        page.global_jvm_binary_path.as_ref().map(|p| InstanceJvmBinaryConfiguration { enabled: true, path: Some(p.clone()) })
+                                        page.global_jvm_binary_path.as_ref().map(|p| InstanceJvmBinaryConfiguration { enabled: true, path: Some(p.clone()), major_version: None })
                                     } else {
                                         None
                                     };
@@ -471,6 +587,7 @@ impl Render for SyncingPage {
                                                                     let jvm_binary = InstanceJvmBinaryConfiguration {
                                                                         enabled: true,
                                                                         path: Some(path.as_path().into()),
+                                                                        major_version: None,
                                                                     };
                                                                     InterfaceConfig::get_mut(cx).global_jvm_binary = Some(jvm_binary.clone());
                                                                     this.send_global_overrides(cx);
@@ -488,6 +605,6 @@ impl Render for SyncingPage {
                     )
             );
 
-        ui::page(cx, h_flex().gap_8().child("Syncing")).child(content).overflow_y_scrollbar()
+        ui::scroll_page(cx, h_flex().gap_8().child("Syncing"), &self.scroll_handle, content)
     }
 }
@@ -0,0 +1,341 @@
+use std::{collections::HashMap, ops::Range, path::Path, sync::{atomic::Ordering, Arc}};
+
+use bridge::{
+    handle::BackendHandle, instance::{InstanceID, InstanceStatus, InstanceWorldSummary, WorldPreview}, message::{MessageToBackend, QuickPlayLaunch}, serial::AtomicOptionSerial
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::{Button, ButtonVariants}, h_flex, scroll::Scrollbar, spinner::Spinner, v_flex, ActiveTheme as _, Disableable, Icon,
+    Selectable, WindowExt
+};
+
+use crate::{entity::{instance::InstanceEntries, DataEntities}, png_render_cache, root, ts, ui};
+
+struct WatchedInstance {
+    serial: AtomicOptionSerial,
+    _entry_subscription: Subscription,
+    _worlds_subscription: Subscription,
+}
+
+/// Biomes and structures listed next to a preview
+const PREVIEW_LEGEND_LEN: usize = 8;
+
+struct WorldRow {
+    id: InstanceID,
+    instance_name: SharedString,
+    running: bool,
+    world: InstanceWorldSummary,
+}
+
+/// Lists the worlds of every instance, most recently played first
+pub struct WorldsPage {
+    backend_handle: BackendHandle,
+    instances: Entity<InstanceEntries>,
+    watched: HashMap<InstanceID, WatchedInstance>,
+    /// Rebuilt on every render, kept so the list only renders the visible rows
+    worlds: Vec<WorldRow>,
+    /// World with its map shown above the list, None while the map is rendering
+    preview: Option<(Arc<Path>, Option<Result<WorldPreview, Arc<str>>>)>,
+    scroll_handle: UniformListScrollHandle,
+}
+
+impl WorldsPage {
+    pub fn new(data: &DataEntities, _window: &mut Window, cx: &mut Context<Self>) -> Self {
+        cx.observe(&data.instances, |page, _, cx| {
+            page.watch_instances(cx);
+            cx.notify();
+        }).detach();
+
+        let mut page = Self {
+            backend_handle: data.backend_handle.clone(),
+            instances: data.instances.clone(),
+            watched: HashMap::new(),
+            worlds: Vec::new(),
+            preview: None,
+            scroll_handle: UniformListScrollHandle::new(),
+        };
+        page.watch_instances(cx);
+        page
+    }
+
+    fn watch_instances(&mut self, cx: &mut Context<Self>) {
+        let entries = self.instances.read(cx).entries.clone();
+        self.watched.retain(|id, _| entries.contains_key(id));
+        for (id, entry) in entries {
+            if self.watched.contains_key(&id) {
+                continue;
+            }
+            let worlds = entry.read(cx).worlds.clone();
+            self.watched.insert(id, WatchedInstance {
+                serial: AtomicOptionSerial::default(),
+                _entry_subscription: cx.observe(&entry, |_, _, cx| cx.notify()),
+                _worlds_subscription: cx.observe(&worlds, |_, _, cx| cx.notify()),
+            });
+        }
+    }
+
+    fn toggle_preview(&mut self, id: InstanceID, level_path: Arc<Path>, cx: &mut Context<Self>) {
+        if let Some((shown, _)) = self.preview.take() && shown == level_path {
+            cx.notify();
+            return;
+        }
+        self.preview = Some((level_path.clone(), None));
+        cx.notify();
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        cx.spawn({
+            let level_path = level_path.clone();
+            async move |page, cx| {
+                let Ok(preview) = recv.await else {
+                    return;
+                };
+                let _ = page.update(cx, move |page, cx| {
+                    // Only if the map wasn't hidden or replaced while rendering
+                    if let Some((shown, entry)) = &mut page.preview && *shown == level_path {
+                        *entry = Some(preview);
+                        cx.notify();
+                    }
+                });
+            }
+        }).detach();
+
+        self.backend_handle.send(MessageToBackend::GetWorldPreview { id, level_path, channel: send });
+    }
+
+    fn render_preview(preview: Option<&Result<WorldPreview, Arc<str>>>, cx: &mut App) -> Div {
+        let preview = match preview {
+            None => return h_flex().gap_2().child(Spinner::new()).child(ts!("worlds_preview_loading")),
+            Some(Err(error)) => return div().text_color(cx.theme().danger).child(SharedString::from(error.clone())),
+            Some(Ok(preview)) => preview,
+        };
+        // Only explored chunks are rendered, the seed map covers everything else
+        let seed_map_button = preview.seed.map(|seed| {
+            let url = format!("https://www.chunkbase.com/apps/seed-map#seed={seed}&dimension=overworld&x={}&z={}",
+                preview.center_x, preview.center_z);
+            Button::new("seed-map").label(ts!("worlds_preview_seed_map"))
+                .tooltip(ts!("worlds_preview_seed_map_tooltip"))
+                .on_click(move |_, _, cx| cx.open_url(&url))
+        });
+        let skipped_chunks = (preview.skipped_chunks > 0).then(|| div().text_color(cx.theme().muted_foreground)
+            .child(rust_i18n::t!("worlds_preview_skipped_chunks", count = preview.skipped_chunks).into_owned()));
+
+        if preview.biomes.is_empty() {
+            return v_flex()
+                .gap_2()
+                .items_start()
+                .text_sm()
+                .child(div().text_color(cx.theme().muted_foreground).child(ts!("worlds_preview_empty")))
+                .children(skipped_chunks)
+                .children(seed_map_button);
+        }
+
+        let mut biomes = v_flex().gap_1();
+        for biome in preview.biomes.iter().take(PREVIEW_LEGEND_LEN) {
+            let name = biome.id.strip_prefix("minecraft:").unwrap_or(&biome.id).replace('_', " ");
+            biomes = biomes.child(h_flex()
+                .gap_2()
+                .child(div().size_3().bg(rgb(biome.color)))
+                .child(format!("{name} ({:.0}%)", biome.share * 100.0)));
+        }
+
+        let mut details = v_flex()
+            .gap_2()
+            .text_sm()
+            .child(div().text_color(cx.theme().muted_foreground).child(rust_i18n::t!("worlds_preview_center",
+                x = preview.center_x, z = preview.center_z, blocks = preview.blocks_per_pixel).into_owned()))
+            .child(biomes);
+
+        if !preview.structures.is_empty() {
+            let mut structures = v_flex().child(div().font_medium().child(ts!("worlds_preview_structures")));
+            for structure in preview.structures.iter().take(PREVIEW_LEGEND_LEN) {
+                let name = structure.id.strip_prefix("minecraft:").unwrap_or(&structure.id).replace('_', " ");
+                structures = structures.child(format!("{name} • {}, {}", structure.x, structure.z));
+            }
+            details = details.child(structures);
+        }
+        details = details.children(skipped_chunks).children(seed_map_button);
+
+        h_flex()
+            .gap_4()
+            .items_start()
+            .child(png_render_cache::render(preview.png.clone(), cx).size(px(256.)).min_w(px(256.)))
+            .child(details)
+    }
+
+    fn render_world(&self, index: usize, id: InstanceID, instance_name: SharedString, running: bool,
+        world: &InstanceWorldSummary, cx: &mut Context<Self>) -> Div
+    {
+        let icon = if let Some(png_icon) = world.png_icon.as_ref() {
+            png_render_cache::render(Arc::clone(png_icon), cx)
+        } else {
+            gpui::img(ImageSource::Resource(Resource::Embedded("images/default_world.png".into())))
+        };
+
+        let mut details = vec![instance_name.to_string()];
+        if let Some(version) = &world.version {
+            details.push(version.to_string());
+        }
+        if world.last_played > 0 {
+            details.push(crate::format::relative_time(world.last_played).to_string());
+        }
+
+        let description = v_flex()
+            .flex_1()
+            .child(SharedString::from(world.title.clone()))
+            .child(div().text_sm().text_color(cx.theme().muted_foreground).child(details.join(" • ")));
+
+        let play_button = Button::new(("play", index)).success().icon(Icon::empty().path("icons/play.svg")).on_click({
+            let backend_handle = self.backend_handle.clone();
+            let target = world.level_path.file_name().unwrap().to_owned();
+            let instance_name = instance_name.clone();
+            move |_, window, cx| {
+                root::start_instance(id, instance_name.clone(), Some(QuickPlayLaunch::Singleplayer(target.clone())),
+                    &backend_handle, window, cx);
+            }
+        });
+
+        let open_button = Button::new(("open", index)).icon(Icon::empty().path("icons/folder-open.svg"))
+            .tooltip(ts!("worlds_open_folder"))
+            .on_click({
+                let level_path = world.level_path.clone();
+                move |_, window, cx| crate::open_folder(&level_path, window, cx)
+            });
+
+        let backup_button = Button::new(("backup", index)).icon(Icon::empty().path("icons/archive.svg"))
+            .tooltip(ts!("worlds_backup"))
+            .on_click({
+                let backend_handle = self.backend_handle.clone();
+                let level_path = world.level_path.clone();
+                let title = world.title.clone();
+                move |_, window, cx| {
+                    let backend_handle = backend_handle.clone();
+                    let level_path = level_path.clone();
+                    crate::modals::generic::show_retryable_modal(window, cx,
+                        rust_i18n::t!("worlds_backing_up", name = title).into_owned().into(),
+                        ts!("worlds_error_backing_up"), move |modal_action| {
+                            backend_handle.send(MessageToBackend::BackupWorld { id, level_path: level_path.clone(), modal_action });
+                        });
+                }
+            });
+
+        let previewed = self.preview.as_ref().is_some_and(|(shown, _)| *shown == world.level_path);
+        let preview_button = Button::new(("preview", index)).icon(Icon::empty().path("icons/map.svg"))
+            .selected(previewed)
+            .tooltip(ts!("worlds_preview"))
+            .on_click(cx.listener({
+                let level_path = world.level_path.clone();
+                move |page, _, _, cx| page.toggle_preview(id, level_path.clone(), cx)
+            }));
+
+        let delete_button = Button::new(("delete", index)).icon(Icon::empty().path("icons/trash-2.svg")).danger()
+            .disabled(running)
+            .tooltip(if running { ts!("worlds_delete_running") } else { ts!("worlds_delete") })
+            .on_click({
+                let backend_handle = self.backend_handle.clone();
+                let level_path = world.level_path.clone();
+                let title = world.title.clone();
+                move |_, window, cx| {
+                    let backend_handle = backend_handle.clone();
+                    let level_path = level_path.clone();
+                    let message = rust_i18n::t!("worlds_delete_confirm", name = title).into_owned();
+                    window.open_dialog(cx, move |dialog, _, _| {
+                        let backend_handle = backend_handle.clone();
+                        let level_path = level_path.clone();
+                        dialog
+                            .confirm()
+                            .title(ts!("worlds_delete"))
+                            .child(message.clone())
+                            .on_ok(move |_, _, _| {
+                                backend_handle.send(MessageToBackend::DeleteWorld { id, level_path: level_path.clone() });
+                                true
+                            })
+                    });
+                }
+            });
+
+        let row = h_flex()
+            .w_full()
+            .gap_3()
+            .child(play_button)
+            .child(icon.size_12().min_w_12().min_h_12())
+            .child(description)
+            .child(preview_button)
+            .child(open_button)
+            .child(backup_button)
+            .child(delete_button);
+
+        // Rows must all be the same height for the uniform list, so the spacing is padding rather than a gap
+        div().pb_2().child(h_flex()
+            .w_full()
+            .p_2()
+            .border_1()
+            .rounded(cx.theme().radius)
+            .border_color(cx.theme().border)
+            .child(row))
+    }
+
+    fn render_items(&mut self, visible_range: Range<usize>, _window: &mut Window, cx: &mut Context<Self>) -> Vec<Div> {
+        visible_range
+            .filter_map(|index| {
+                let row = self.worlds.get(index)?;
+                Some(self.render_world(index, row.id, row.instance_name.clone(), row.running, &row.world, cx))
+            })
+            .collect()
+    }
+}
+
+impl Render for WorldsPage {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let mut worlds = Vec::new();
+        for (id, entry) in &self.instances.read(cx).entries {
+            let entry = entry.read(cx);
+            if let Some(watched) = self.watched.get(id)
+                && entry.worlds_state.load(Ordering::SeqCst).should_send_load_request()
+            {
+                self.backend_handle.send_with_serial(MessageToBackend::RequestLoadWorlds { id: *id }, &watched.serial);
+            }
+
+            let running = entry.status != InstanceStatus::NotRunning && entry.status != InstanceStatus::Crashed;
+            for world in entry.worlds.read(cx).iter() {
+                worlds.push(WorldRow { id: *id, instance_name: entry.name.clone(), running, world: world.clone() });
+            }
+        }
+        worlds.sort_by_key(|row| std::cmp::Reverse(row.world.last_played));
+        self.worlds = worlds;
+
+        let mut content = v_flex().flex_1().min_h_0().p_3().gap_2();
+
+        // The map is shown above the list rather than in its row, since the rows need to be the same height
+        let previewed = self.preview.as_ref()
+            .and_then(|(level_path, _)| self.worlds.iter().find(|row| row.world.level_path == *level_path));
+        if let Some(row) = previewed {
+            let preview = self.preview.as_ref().and_then(|(_, preview)| preview.as_ref());
+            content = content.child(v_flex()
+                .w_full()
+                .gap_3()
+                .p_2()
+                .border_1()
+                .rounded(cx.theme().radius)
+                .border_color(cx.theme().border)
+                .child(div().font_medium().child(SharedString::from(row.world.title.clone())))
+                .child(Self::render_preview(preview, cx)));
+        } else {
+            // The world was deleted
+            self.preview = None;
+        }
+
+        if self.worlds.is_empty() {
+            content = content.child(ts!("worlds_none"));
+        } else {
+            content = content.child(h_flex()
+                .flex_1()
+                .min_h_0()
+                .child(uniform_list("worlds", self.worlds.len(), cx.processor(Self::render_items))
+                    .size_full()
+                    .track_scroll(&self.scroll_handle))
+                .child(div().w_3().h_full().py_3().child(Scrollbar::vertical(&self.scroll_handle))));
+        }
+
+        ui::page(cx, h_flex().gap_8().child(ts!("worlds_title"))).child(content)
+    }
+}
@@ -146,7 +146,7 @@ impl PngRenderCache {
         let render_image = match result {
             Ok(render_image) => Some(Arc::new(render_image)),
             Err(error) => {
-                log::warn!("Error loading png: {error:?}");
+                tracing::warn!("Error loading png: {error:?}");
                 None
             },
         };
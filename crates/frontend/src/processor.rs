@@ -1,16 +1,18 @@
 use std::{collections::HashMap, sync::{Arc, atomic::AtomicBool}};
 
-use bridge::{instance::InstanceStatus, message::{BridgeNotificationType, MessageToFrontend}};
+use bridge::{instance::{InstanceID, InstanceStatus}, message::{MessageToBackend, MessageToFrontend}, modal_action::ModalAction};
 use gpui::{AnyWindowHandle, App, AppContext, Entity, SharedString, TitlebarOptions, Window, WindowDecorations, WindowHandle, WindowOptions, px, size};
-use gpui_component::{notification::{Notification, NotificationType}, Root, WindowExt};
+use gpui_component::{dialog::DialogButtonProps, notification::{Notification, NotificationType}, Root, WindowExt};
 
-use crate::{entity::{DataEntities, account::AccountEntries, instance::InstanceEntries, metadata::FrontendMetadata}, game_output::{GameOutput, GameOutputRoot}, interface_config::InterfaceConfig};
+use crate::{active_operations::ActiveOperations, entity::{DataEntities, account::AccountEntries, instance::InstanceEntries, metadata::FrontendMetadata}, game_output::{GameOutput, GameOutputRoot}, interface_config::{InterfaceConfig, LaunchWindowBehavior}, notification_history::{NotificationAction, NotificationHistory, NotificationKind}, ts};
 
 pub struct Processor {
     data: DataEntities,
     game_output_windows: HashMap<usize, (WindowHandle<Root>, Entity<GameOutput>)>,
     main_window_handle: Option<AnyWindowHandle>,
     main_window_hidden: Arc<AtomicBool>,
+    /// Set when the main window was minimized because a game started, so it's only restored in that case
+    main_window_minimized: bool,
     waiting_for_window: Vec<MessageToFrontend>,
 }
 
@@ -21,6 +23,7 @@ impl Processor {
             game_output_windows: HashMap::new(),
             main_window_handle: None,
             main_window_hidden,
+            main_window_minimized: false,
             waiting_for_window: Vec::new(),
         }
     }
@@ -48,6 +51,17 @@ impl Processor {
         });
     }
 
+    /// Whether any instance other than `id` is still launching or running
+    fn other_instance_running(data: &DataEntities, id: InstanceID, cx: &App) -> bool {
+        data.instances.read(cx).entries.iter().any(|(other, entry)| *other != id && entry.read(cx).status.is_running())
+    }
+
+    fn reopen_main_window(&mut self, cx: &mut App) {
+        self.main_window_handle = Some(crate::open_main_window(&self.data, cx));
+        self.main_window_hidden.store(false, std::sync::atomic::Ordering::SeqCst);
+        self.process_messages_waiting_for_window(cx);
+    }
+
     pub fn process(&mut self, message: MessageToFrontend, cx: &mut App) {
         match message {
             MessageToFrontend::AccountsUpdated {
@@ -61,6 +75,7 @@ impl Processor {
                 name,
                 dot_minecraft_folder,
                 configuration,
+                total_playtime,
                 worlds_state,
                 servers_state,
                 mods_state,
@@ -72,6 +87,7 @@ impl Processor {
                     name.as_str().into(),
                     dot_minecraft_folder,
                     configuration,
+                    total_playtime,
                     worlds_state,
                     servers_state,
                     mods_state,
@@ -87,22 +103,42 @@ impl Processor {
                 name,
                 dot_minecraft_folder,
                 configuration,
+                total_playtime,
                 status,
+                process,
             } => {
                 if status == InstanceStatus::Running {
-                    if InterfaceConfig::get(cx).hide_main_window_on_launch {
-                        if let Some(handle) = self.main_window_handle.take() {
-                            self.main_window_hidden.store(true, std::sync::atomic::Ordering::SeqCst);
-                            _ = handle.update(cx, |_, window, _| {
-                                window.remove_window();
-                            });
-                        }
+                    match InterfaceConfig::get(cx).launch_window_behavior {
+                        LaunchWindowBehavior::KeepOpen => {},
+                        LaunchWindowBehavior::Minimize => {
+                            if !self.main_window_minimized && let Some(handle) = self.main_window_handle {
+                                self.main_window_minimized = true;
+                                _ = handle.update(cx, |_, window, _| {
+                                    window.minimize_window();
+                                });
+                            }
+                        },
+                        LaunchWindowBehavior::Hide => {
+                            if let Some(handle) = self.main_window_handle.take() {
+                                self.main_window_hidden.store(true, std::sync::atomic::Ordering::SeqCst);
+                                _ = handle.update(cx, |_, window, _| {
+                                    window.remove_window();
+                                });
+                            }
+                        },
+                        LaunchWindowBehavior::Quit => {
+                            // Not selectable while games are killed when the launcher exits
+                            cx.quit();
+                        },
                     }
-                } else if status == InstanceStatus::NotRunning {
+                } else if !status.is_running() && !Self::other_instance_running(&self.data, id, cx) {
                     if self.main_window_handle.is_none() && self.main_window_hidden.load(std::sync::atomic::Ordering::SeqCst) {
-                        self.main_window_handle = Some(crate::open_main_window(&self.data, cx));
-                        self.main_window_hidden.store(false, std::sync::atomic::Ordering::SeqCst);
-                        self.process_messages_waiting_for_window(cx);
+                        self.reopen_main_window(cx);
+                    } else if self.main_window_minimized && let Some(handle) = self.main_window_handle {
+                        self.main_window_minimized = false;
+                        _ = handle.update(cx, |_, window, _| {
+                            window.activate_window();
+                        });
                     }
                 }
 
@@ -112,7 +148,9 @@ impl Processor {
                     name.as_str().into(),
                     dot_minecraft_folder,
                     configuration,
+                    total_playtime,
                     status,
+                    process,
                     cx,
                 );
             },
@@ -128,18 +166,14 @@ impl Processor {
             MessageToFrontend::InstanceResourcePacksUpdated { id, resource_packs } => {
                 InstanceEntries::set_resource_packs(&self.data.instances, id, resource_packs, cx);
             },
-            MessageToFrontend::AddNotification { .. } => {
+            MessageToFrontend::AddNotification { notification_type, message: ref text } => {
+                NotificationHistory::push(cx, notification_type.into(), text.clone(), None);
                 self.with_main_window(message, cx, |_, message, window, cx| {
                     let MessageToFrontend::AddNotification { notification_type, message } = message else {
                         unreachable!();
                     };
 
-                    let notification_type = match notification_type {
-                        BridgeNotificationType::Success => NotificationType::Success,
-                        BridgeNotificationType::Info => NotificationType::Info,
-                        BridgeNotificationType::Error => NotificationType::Error,
-                        BridgeNotificationType::Warning => NotificationType::Warning,
-                    };
+                    let notification_type = NotificationKind::from(notification_type).notification_type();
                     let mut notification: Notification = (notification_type, SharedString::from(message)).into();
                     if let NotificationType::Error = notification_type {
                         notification = notification.autohide(false);
@@ -155,6 +189,13 @@ impl Processor {
                     window.refresh();
                 });
             },
+            MessageToFrontend::BackendRestarted => {
+                // The panic that crashed the old backend is no longer relevant once it's restarted on its own
+                *self.data.panic_messages.panic_message.write() = None;
+                crate::launcher_lock::forget_unlock(cx);
+                InstanceEntries::clear(&self.data.instances, cx);
+                FrontendMetadata::request_pending_again(&self.data.metadata, cx);
+            },
             MessageToFrontend::CloseModal => {
                 let Some(handle) = self.main_window_handle else {
                     return;
@@ -168,7 +209,7 @@ impl Processor {
                     app_id: Some("PandoraLauncher".into()),
                     window_min_size: Some(size(px(360.0), px(240.0))),
                     titlebar: Some(TitlebarOptions {
-                        title: Some(SharedString::new_static("Minecraft Game Output")),
+                        title: Some(ts!("game_output_title")),
                         ..Default::default()
                     }),
                     window_decorations: Some(WindowDecorations::Server),
@@ -205,6 +246,9 @@ impl Processor {
             MessageToFrontend::MetadataResult { request, result, keep_alive_handle } => {
                 FrontendMetadata::set(&self.data.metadata, request, result, keep_alive_handle, cx);
             },
+            MessageToFrontend::DesktopNotification { title, body } => {
+                crate::desktop_notification::show(&title, &body);
+            },
             MessageToFrontend::UpdateAvailable { .. } => {
                 self.with_main_window(message, cx, |this, message, window, cx| {
                     let MessageToFrontend::UpdateAvailable { update } = message else {
@@ -213,7 +257,151 @@ impl Processor {
 
                     crate::modals::update_prompt::open_update_prompt(update, this.data.backend_handle.clone(), window, cx);
                 });
-            }
+            },
+            MessageToFrontend::InstanceCrashed { name, exit_code, ref crash_report, .. } => {
+                let text = match exit_code {
+                    Some(exit_code) => rust_i18n::t!("notification_crashed_exit_code", name = name, code = exit_code).into_owned(),
+                    None => rust_i18n::t!("crash_report_title", name = name).into_owned(),
+                };
+                let action = crash_report.clone().map(|path| NotificationAction::OpenFile { path });
+                NotificationHistory::push(cx, NotificationKind::Error, text, action);
+                self.with_main_window(message, cx, |_, message, window, cx| {
+                    let MessageToFrontend::InstanceCrashed { name, exit_code, crash_report, excerpt, system_info, bundle, .. } = message else {
+                        unreachable!();
+                    };
+
+                    let details = crate::modals::crash_report::CrashDetails { exit_code, crash_report, excerpt, system_info, bundle };
+                    crate::modals::crash_report::open_crash_report(name.as_str().into(), details, window, cx);
+                });
+            },
+            MessageToFrontend::ScreenshotTaken { name, ref path, .. } => {
+                let action = Some(NotificationAction::OpenFile { path: path.clone() });
+                NotificationHistory::push(cx, NotificationKind::Info, rust_i18n::t!("screenshot_taken", name = name).into_owned(), action);
+                self.with_main_window(message, cx, |_, message, window, cx| {
+                    let MessageToFrontend::ScreenshotTaken { name, path, .. } = message else {
+                        unreachable!();
+                    };
+
+                    crate::screenshots::show_screenshot_notification(name.as_str().into(), path, window, cx);
+                });
+            },
+            MessageToFrontend::ModBisectionLaunchExited { .. } => {
+                let backend_handle = self.data.backend_handle.clone();
+                self.with_main_window(message, cx, move |_, message, window, cx| {
+                    let MessageToFrontend::ModBisectionLaunchExited { id, name, crashed } = message else {
+                        unreachable!();
+                    };
+
+                    crate::modals::mod_bisection::open_mod_bisection(id, name.as_str().into(), Some(crashed), backend_handle, window, cx);
+                });
+            },
+            MessageToFrontend::LaunchFailed { name, ref error, .. } => {
+                let action = Some(NotificationAction::LaunchInstance { name: name.as_str().into() });
+                NotificationHistory::push(cx, NotificationKind::Error, rust_i18n::t!("launch_failed", name = name, error = error).into_owned(), action);
+                self.with_main_window(message, cx, |_, message, window, cx| {
+                    let MessageToFrontend::LaunchFailed { name, error, .. } = message else {
+                        unreachable!();
+                    };
+
+                    let notification: Notification = (NotificationType::Error, SharedString::from(rust_i18n::t!("launch_failed", name = name, error = error).into_owned())).into();
+                    window.push_notification(notification.autohide(false), cx);
+                });
+            },
+            MessageToFrontend::PlaytimeLimitReached { .. } => {
+                let backend_handle = self.data.backend_handle.clone();
+                self.with_main_window(message, cx, move |_, message, window, cx| {
+                    let MessageToFrontend::PlaytimeLimitReached { id, name, can_override } = message else {
+                        unreachable!();
+                    };
+
+                    crate::modals::playtime_limit::open_playtime_limit_reached(id, name.as_str().into(), can_override, backend_handle, window, cx);
+                });
+            },
+            MessageToFrontend::ForeignSyncMembership { .. } => {
+                let backend_handle = self.data.backend_handle.clone();
+                self.with_main_window(message, cx, move |_, message, window, cx| {
+                    let MessageToFrontend::ForeignSyncMembership { id, name, targets } = message else {
+                        unreachable!();
+                    };
+
+                    let target_names = targets.iter().map(|target| format!("{target:?}")).collect::<Vec<_>>().join(", ");
+                    let message = SharedString::from(rust_i18n::t!("foreign_sync_description", name = name, targets = target_names).into_owned());
+                    window.open_dialog(cx, move |dialog, _, _| {
+                        let ok_backend_handle = backend_handle.clone();
+                        let cancel_backend_handle = backend_handle.clone();
+                        dialog
+                            .confirm()
+                            .title(ts!("foreign_sync_title"))
+                            .child(message.clone())
+                            .button_props(DialogButtonProps::default().ok_text(ts!("foreign_sync_adopt")).cancel_text(ts!("foreign_sync_keep_separate")))
+                            .on_ok(move |_, _, _| {
+                                ok_backend_handle.send(MessageToBackend::ResolveSyncMembership { id, targets, adopt: true });
+                                true
+                            })
+                            .on_cancel(move |_, _, _| {
+                                cancel_backend_handle.send(MessageToBackend::ResolveSyncMembership { id, targets, adopt: false });
+                                true
+                            })
+                    });
+                });
+            },
+            MessageToFrontend::OfficialLauncherFilesFound { .. } => {
+                let backend_handle = self.data.backend_handle.clone();
+                self.with_main_window(message, cx, move |_, message, window, cx| {
+                    let MessageToFrontend::OfficialLauncherFilesFound { minecraft_dir, objects, bytes } = message else {
+                        unreachable!();
+                    };
+
+                    let message = SharedString::from(rust_i18n::t!("official_files_description", path = minecraft_dir.display(),
+                        count = crate::format::integer(objects as u64), size = crate::format::bytes(bytes)).into_owned());
+                    window.open_dialog(cx, move |dialog, _, _| {
+                        let backend_handle = backend_handle.clone();
+                        let minecraft_dir = minecraft_dir.clone();
+                        dialog
+                            .confirm()
+                            .title(ts!("official_files_title"))
+                            .child(message.clone())
+                            .button_props(DialogButtonProps::default().ok_text(ts!("official_files_reuse")).cancel_text(ts!("official_files_download")))
+                            .on_ok(move |_, window, cx| {
+                                let modal_action = ModalAction::default();
+                                backend_handle.send(MessageToBackend::SeedFromOfficialLauncher {
+                                    minecraft_dir: minecraft_dir.clone(),
+                                    modal_action: modal_action.clone(),
+                                });
+                                // Deferred so that this dialog is closed before the progress modal opens
+                                window.defer(cx, move |window, cx| {
+                                    crate::modals::generic::show_modal(window, cx, ts!("official_files_copying"),
+                                        ts!("official_files_error"), modal_action);
+                                });
+                                true
+                            })
+                    });
+                });
+            },
+            MessageToFrontend::OfflineStateChanged { offline, cached_versions } => {
+                crate::connectivity::Connectivity::set(cx, offline, &cached_versions);
+            },
+            MessageToFrontend::FocusWindow => {
+                if self.main_window_handle.is_none() {
+                    self.reopen_main_window(cx);
+                }
+                self.with_main_window(message, cx, |_, _, window, _| {
+                    window.activate_window();
+                });
+            },
+            MessageToFrontend::LaunchRequested { .. } => {
+                let backend_handle = self.data.backend_handle.clone();
+                self.with_main_window(message, cx, move |_, message, window, cx| {
+                    let MessageToFrontend::LaunchRequested { id, name } = message else {
+                        unreachable!();
+                    };
+                    crate::root::start_instance(id, name.as_str().into(), None, &backend_handle, window, cx);
+                });
+            },
+            MessageToFrontend::BackgroundTaskStarted { title, modal_action } => {
+                ActiveOperations::register(cx, SharedString::new(title), modal_action);
+                cx.refresh_windows();
+            },
         }
     }
 }
@@ -1,17 +1,17 @@
-use std::{path::Path, sync::Arc};
+use std::{path::Path, rc::Rc, sync::Arc};
 
 use bridge::{
     handle::BackendHandle,
-    install::ContentInstall,
+    install::{ContentInstall, InstallTarget},
     instance::{InstanceID, InstanceContentID},
-    message::{MessageToBackend, QuickPlayLaunch},
+    message::{LaunchMods, MessageToBackend, QuickPlayLaunch},
     modal_action::ModalAction,
 };
 use gpui::{prelude::*, *};
-use gpui_component::{breadcrumb::Breadcrumb, scroll::{ScrollableElement, ScrollbarAxis}, v_flex, Root, StyledExt};
+use gpui_component::{breadcrumb::Breadcrumb, button::Button, scroll::{ScrollableElement, ScrollbarAxis}, v_flex, Root, StyledExt};
 use parking_lot::RwLock;
 
-use crate::{entity::DataEntities, modals, ui::{LauncherUI, PageType}, CloseWindow, MAIN_FONT};
+use crate::{active_operations::ActiveOperations, download_guard, entity::{DataEntities, instance::InstanceEntries}, interface_config::InterfaceConfig, launcher_lock, modals, ts, ui::{LauncherUI, PageType}, CloseWindow, MAIN_FONT};
 
 pub struct LauncherRootGlobal {
     pub root: Entity<LauncherRoot>,
@@ -24,6 +24,8 @@ pub struct LauncherRoot {
     pub panic_message: Arc<RwLock<Option<String>>>,
     pub deadlock_message: Arc<RwLock<Option<String>>>,
     pub backend_handle: BackendHandle,
+    instances: Entity<InstanceEntries>,
+    confirming_restart: bool,
     focus_handle: FocusHandle,
 }
 
@@ -43,9 +45,35 @@ impl LauncherRoot {
             panic_message: data.panic_messages.panic_message.clone(),
             deadlock_message: data.panic_messages.deadlock_message.clone(),
             backend_handle: data.backend_handle.clone(),
+            instances: data.instances.clone(),
+            confirming_restart: false,
             focus_handle,
         }
     }
+
+    /// A restarted backend loses track of games that are still running, so the restart is confirmed first then
+    fn restart_backend_button(&self, cx: &mut Context<Self>) -> AnyElement {
+        let games_running = self.instances.read(cx).entries.values().any(|entry| entry.read(cx).status.is_running());
+
+        if games_running && !self.confirming_restart {
+            return div().child(Button::new("restart-backend").label(ts!("restart_backend")).on_click(cx.listener(|this, _, _, cx| {
+                this.confirming_restart = true;
+                cx.notify();
+            }))).into_any_element();
+        }
+
+        let label = if games_running { ts!("restart_backend_anyway") } else { ts!("restart_backend") };
+        v_flex()
+            .gap_2()
+            .when(games_running, |this| this.child(ts!("restart_backend_games_running")))
+            .child(div().child(Button::new("restart-backend").label(label).on_click(cx.listener(|this, _, window, _| {
+                this.confirming_restart = false;
+                *this.panic_message.write() = None;
+                this.backend_handle.request_restart();
+                window.refresh();
+            }))))
+            .into_any_element()
+    }
 }
 
 impl Render for LauncherRoot {
@@ -60,10 +88,17 @@ impl Render for LauncherRoot {
             return v_flex().size_full().bg(purple).child(message.clone()).overflow_y_scrollbar().into_any_element();
         }
         if let Some(message) = &*self.panic_message.read() {
-            return v_flex().size_full().bg(gpui::blue()).child(message.clone()).overflow_y_scrollbar().into_any_element();
+            return v_flex().size_full().bg(gpui::blue())
+                .child(self.restart_backend_button(cx))
+                .child(message.clone())
+                .overflow_y_scrollbar()
+                .into_any_element();
         }
         if self.backend_handle.is_closed() {
-            return v_flex().size_full().bg(gpui::red()).child("Backend has abruptly shutdown").into_any_element();
+            return v_flex().size_full().gap_2().bg(gpui::red())
+                .child(ts!("backend_shutdown"))
+                .child(self.restart_backend_button(cx))
+                .into_any_element();
         }
 
         let sheet_layer = Root::render_sheet_layer(window, cx);
@@ -105,14 +140,17 @@ pub fn start_new_account_login(
     window: &mut Window,
     cx: &mut App,
 ) {
-    let modal_action = ModalAction::default();
+    let login_backend_handle = backend_handle.clone();
+    launcher_lock::when_unlocked(backend_handle, window, cx, move |window, cx| {
+        let modal_action = ModalAction::default();
 
-    backend_handle.send(MessageToBackend::AddNewAccount {
-        modal_action: modal_action.clone(),
-    });
+        login_backend_handle.send(MessageToBackend::AddNewAccount {
+            modal_action: modal_action.clone(),
+        });
 
-    let title = SharedString::new_static("Adding new account");
-    modals::generic::show_modal(window, cx, title, "Error adding account".into(), modal_action);
+        let title = ts!("adding_account");
+        modals::generic::show_modal(window, cx, title, ts!("adding_account_error"), modal_action);
+    });
 }
 
 pub fn start_instance(
@@ -123,16 +161,75 @@ pub fn start_instance(
     window: &mut Window,
     cx: &mut App,
 ) {
-    let modal_action = ModalAction::default();
+    start_instance_with_mods(id, name, quick_play, LaunchMods::All, backend_handle, window, cx);
+}
 
-    backend_handle.send(MessageToBackend::StartInstance {
-        id,
-        quick_play,
-        modal_action: modal_action.clone(),
+pub fn start_instance_with_mods(
+    id: InstanceID,
+    name: SharedString,
+    quick_play: Option<QuickPlayLaunch>,
+    mods: LaunchMods,
+    backend_handle: &BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let backend_handle = backend_handle.clone();
+    launcher_lock::when_unlocked(&backend_handle.clone(), window, cx, move |window, cx| {
+        let launch = {
+            let name = name.clone();
+            let backend_handle = backend_handle.clone();
+            move |window: &mut Window, cx: &mut App| {
+                let launch_backend_handle = backend_handle.clone();
+                download_guard::when_download_allowed(&backend_handle, window, cx, move |window, cx| {
+                    let modal_action = ModalAction::default();
+
+                    launch_backend_handle.send(MessageToBackend::StartInstance {
+                        id,
+                        quick_play: quick_play.clone(),
+                        mods,
+                        modal_action: modal_action.clone(),
+                    });
+
+                    let title: SharedString = rust_i18n::t!("launching_instance", name = name).into_owned().into();
+                    modals::generic::show_modal(window, cx, title, ts!("launching_instance_error"), modal_action.clone());
+
+                    // Retrying goes through the launcher lock and the preflight again
+                    ActiveOperations::set_retry(cx, &modal_action, Rc::new(move |window, cx| {
+                        start_instance_with_mods(id, name.clone(), quick_play.clone(), mods, &launch_backend_handle, window, cx);
+                    }));
+                });
+            }
+        };
+
+        if InterfaceConfig::get(cx).launch_preflight {
+            modals::launch_preflight::open_launch_preflight(id, name, &backend_handle, window, cx, launch);
+        } else {
+            launch(window, cx);
+        }
     });
+}
 
-    let title: SharedString = format!("Launching {}", name).into();
-    modals::generic::show_modal(window, cx, title, "Error starting instance".into(), modal_action);
+/// Reopens the progress modal of an instance that is still launching, using the
+/// backend's copy of the action so the progress reflects the launch as it is now
+pub fn show_launch_progress(
+    id: InstanceID,
+    name: SharedString,
+    backend_handle: &BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let (send, recv) = tokio::sync::oneshot::channel();
+    backend_handle.send(MessageToBackend::GetLaunchProgress { id, channel: send });
+
+    window.spawn(cx, async move |cx| {
+        let Ok(Some(modal_action)) = recv.await else {
+            return;
+        };
+        _ = cx.update(move |window, cx| {
+            let title: SharedString = rust_i18n::t!("launching_instance", name = name).into_owned().into();
+            modals::generic::show_modal(window, cx, title, ts!("launching_instance_error"), modal_action);
+        });
+    }).detach();
 }
 
 pub fn start_install(
@@ -141,14 +238,29 @@ pub fn start_install(
     window: &mut Window,
     cx: &mut App,
 ) {
-    let modal_action = ModalAction::default();
+    // Creating a new instance from a modpack can pull in the whole game along with it
+    let needs_download_check = matches!(content_install.target, InstallTarget::NewInstance { .. });
 
-    backend_handle.send(MessageToBackend::InstallContent {
-        content: content_install.clone(),
-        modal_action: modal_action.clone(),
-    });
+    let install_backend_handle = backend_handle.clone();
+    let install = move |window: &mut Window, cx: &mut App| {
+        let modal_action = ModalAction::default();
 
-    modals::generic::show_notification(window, cx, "Error installing content".into(), modal_action);
+        install_backend_handle.send(MessageToBackend::InstallContent {
+            content: content_install,
+            modal_action: modal_action.clone(),
+        });
+
+        modals::generic::show_notification(window, cx, ts!("install_error_installing"), modal_action);
+    };
+
+    let backend_handle = backend_handle.clone();
+    launcher_lock::when_unlocked(&backend_handle.clone(), window, cx, move |window, cx| {
+        if needs_download_check {
+            download_guard::when_download_allowed(&backend_handle, window, cx, install);
+        } else {
+            install(window, cx);
+        }
+    });
 }
 
 pub fn start_update_check(
@@ -164,8 +276,8 @@ pub fn start_update_check(
         modal_action: modal_action.clone(),
     });
 
-    let title: SharedString = "Checking for updates".into();
-    modals::generic::show_modal(window, cx, title, "Error checking for updates".into(), modal_action);
+    let title = ts!("checking_for_updates");
+    modals::generic::show_modal(window, cx, title, ts!("checking_for_updates_error"), modal_action);
 }
 
 pub fn update_single_mod(
@@ -183,7 +295,7 @@ pub fn update_single_mod(
         modal_action: modal_action.clone(),
     });
 
-    modals::generic::show_notification(window, cx, "Error downloading update".into(), modal_action);
+    modals::generic::show_notification(window, cx, ts!("downloading_update_error"), modal_action);
 }
 
 pub fn upload_log_file(
@@ -199,8 +311,8 @@ pub fn upload_log_file(
         modal_action: modal_action.clone(),
     });
 
-    let title: SharedString = "Uploading log file".into();
-    modals::generic::show_modal(window, cx, title, "Error uploading log file".into(), modal_action);
+    let title = ts!("uploading_log");
+    modals::generic::show_modal(window, cx, title, ts!("uploading_log_error"), modal_action);
 }
 
 pub fn switch_page(
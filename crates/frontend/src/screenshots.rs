@@ -0,0 +1,62 @@
+use std::{path::Path, sync::Arc};
+
+use gpui::{prelude::*, *};
+use gpui_component::{
+    button::Button, h_flex, notification::{Notification, NotificationType}, v_flex, Sizable, WindowExt
+};
+
+use crate::ts;
+
+struct ScreenshotNotification;
+
+/// Shows a screenshot the game just saved, with ways to pass it on
+pub fn show_screenshot_notification(instance_name: SharedString, path: Arc<Path>, window: &mut Window, cx: &mut App) {
+    let key = SharedString::new(path.to_string_lossy());
+    let notification = Notification::new()
+        .id1::<ScreenshotNotification>(key)
+        .title(rust_i18n::t!("screenshot_taken", name = instance_name).into_owned())
+        .content(move |_, _, _| {
+            let preview = img(path.to_path_buf()).w_full().h(px(140.)).object_fit(ObjectFit::Contain);
+
+            let actions = h_flex()
+                .gap_2()
+                .child(Button::new("copy").small().label(ts!("screenshot_copy")).on_click({
+                    let path = path.clone();
+                    move |_, window, cx| copy_to_clipboard(&path, window, cx)
+                }))
+                .child(Button::new("open").small().label(ts!("screenshot_open")).on_click({
+                    let path = path.clone();
+                    move |_, window, cx| {
+                        if let Err(err) = open::that_detached(&*path) {
+                            let notification: Notification = (NotificationType::Error, SharedString::from(rust_i18n::t!("screenshot_error_opening", error = err).into_owned())).into();
+                            window.push_notification(notification.autohide(false), cx);
+                        }
+                    }
+                }))
+                .child(Button::new("show-in-folder").small().label(ts!("screenshot_show_in_folder")).on_click({
+                    let path = path.clone();
+                    move |_, window, cx| {
+                        if let Some(folder) = path.parent() {
+                            crate::open_folder(folder, window, cx);
+                        }
+                    }
+                }));
+
+            v_flex().gap_2().child(preview).child(actions).into_any_element()
+        });
+
+    window.push_notification(notification, cx);
+}
+
+fn copy_to_clipboard(path: &Path, window: &mut Window, cx: &mut App) {
+    match std::fs::read(path) {
+        Ok(bytes) => {
+            cx.write_to_clipboard(ClipboardItem::new_image(&Image::from_bytes(ImageFormat::Png, bytes)));
+            window.push_notification((NotificationType::Success, ts!("screenshot_copied")), cx);
+        },
+        Err(err) => {
+            let notification: Notification = (NotificationType::Error, SharedString::from(rust_i18n::t!("screenshot_error_copying", error = err).into_owned())).into();
+            window.push_notification(notification.autohide(false), cx);
+        },
+    }
+}
@@ -3,22 +3,26 @@ use std::sync::Arc;
 use bridge::{instance::InstanceID, message::MessageToBackend};
 use gpui::{prelude::*, *};
 use gpui_component::{
-    ActiveTheme as _, Disableable, Icon, IconName, WindowExt, button::{Button, ButtonVariants}, h_flex, input::{Input, InputState}, resizable::{ResizablePanelEvent, ResizableState, h_resizable, resizable_panel}, scroll::ScrollableElement, sidebar::SidebarFooter, v_flex
+    ActiveTheme as _, Icon, IconName, WindowExt, button::{Button, ButtonVariants}, h_flex, resizable::{ResizablePanelEvent, ResizableState, h_resizable, resizable_panel}, scroll::{ScrollableElement, Scrollbar}, sidebar::SidebarFooter, v_flex
 };
-use rand::Rng;
 use schema::modrinth::ModrinthProjectType;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
 
 use crate::{
     component::{menu::{MenuGroup, MenuGroupItem}, page_path::PagePath}, entity::{
         DataEntities, instance::{InstanceAddedEvent, InstanceEntries, InstanceModifiedEvent, InstanceMovedToTopEvent, InstanceRemovedEvent}
-    }, interface_config::InterfaceConfig, modals, pages::{instance::instance_page::{InstancePage, InstanceSubpageType}, instances_page::InstancesPage, modrinth_page::ModrinthSearchPage, syncing_page::SyncingPage}, png_render_cache, root
+    }, interface_config::InterfaceConfig, modals, pages::{accounts_page::AccountsPage, debug_page::DebugPage, instance::instance_page::{InstancePage, InstanceSubpageType}, instances_page::InstancesPage, modrinth_page::ModrinthSearchPage, mods_page::ModsPage, skins_page::SkinsPage, storage_page::StoragePage, syncing_page::SyncingPage, worlds_page::WorldsPage}, png_render_cache, root, ts
 };
 
+/// Pages that were switched away from are kept alive up to this many, so switching back keeps their state
+const MAX_CACHED_PAGES: usize = 8;
+
 pub struct LauncherUI {
     data: DataEntities,
     page: LauncherPage,
+    breadcrumbs: Vec<PageType>,
+    /// Most recently shown first, with the breadcrumbs they were shown with
+    cached_pages: Vec<(Vec<PageType>, LauncherPage)>,
     sidebar_state: Entity<ResizableState>,
     default_sidebar_width: f32,
     recent_instances: heapless::Vec<(InstanceID, SharedString), 3>,
@@ -31,7 +35,13 @@ pub struct LauncherUI {
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum PageType {
     Instances,
+    Worlds,
+    Accounts,
+    Skins,
     Syncing,
+    Storage,
+    Debug,
+    Mods,
     Modrinth {
         installing_for: Option<InstanceID>,
         project_type: Option<ModrinthProjectType>,
@@ -40,10 +50,34 @@ pub enum PageType {
 }
 
 impl PageType {
+    /// Pages that share an entity when cached. An instance page switches between its tabs itself and the Modrinth
+    /// page keeps the project type it was last showing
+    fn cache_key(self) -> PageType {
+        match self {
+            PageType::InstancePage(id, _) => PageType::InstancePage(id, InstanceSubpageType::Quickplay),
+            PageType::Modrinth { installing_for, .. } => PageType::Modrinth { installing_for, project_type: None },
+            page => page,
+        }
+    }
+
+    fn refers_to(self, id: InstanceID) -> bool {
+        match self {
+            PageType::InstancePage(page_id, _) => page_id == id,
+            PageType::Modrinth { installing_for, .. } => installing_for == Some(id),
+            _ => false,
+        }
+    }
+
     fn to_serialized(&self, data: &DataEntities, cx: &App) -> SerializedPageType {
         match self {
             PageType::Instances => SerializedPageType::Instances,
+            PageType::Worlds => SerializedPageType::Worlds,
+            PageType::Accounts => SerializedPageType::Accounts,
+            PageType::Skins => SerializedPageType::Skins,
             PageType::Syncing => SerializedPageType::Syncing,
+            PageType::Storage => SerializedPageType::Storage,
+            PageType::Debug => SerializedPageType::Debug,
+            PageType::Mods => SerializedPageType::Mods,
             PageType::Modrinth { installing_for, .. } => {
                 if let Some(installing_for) = installing_for {
                     if let Some(name) = InstanceEntries::find_name_by_id(&data.instances, *installing_for, cx) {
@@ -65,7 +99,14 @@ impl PageType {
     fn from_serialized(serialized: &SerializedPageType, data: &DataEntities, cx: &App) -> Self {
         match serialized {
             SerializedPageType::Instances => PageType::Instances,
+            SerializedPageType::Worlds => PageType::Worlds,
+            SerializedPageType::Accounts => PageType::Accounts,
+            SerializedPageType::Skins => PageType::Skins,
             SerializedPageType::Syncing => PageType::Syncing,
+            SerializedPageType::Storage => PageType::Storage,
+            SerializedPageType::Debug if cfg!(debug_assertions) => PageType::Debug,
+            SerializedPageType::Debug => PageType::Instances,
+            SerializedPageType::Mods => PageType::Mods,
             SerializedPageType::Modrinth { installing_for } => {
                 if let Some(installing_for) = installing_for {
                     if let Some(id) = InstanceEntries::find_id_by_name(&data.instances, installing_for, cx) {
@@ -90,7 +131,13 @@ impl PageType {
 pub enum SerializedPageType {
     #[default]
     Instances,
+    Worlds,
+    Accounts,
+    Skins,
     Syncing,
+    Storage,
+    Debug,
+    Mods,
     Modrinth {
         installing_for: Option<SharedString>,
     },
@@ -100,7 +147,13 @@ pub enum SerializedPageType {
 #[derive(Clone)]
 pub enum LauncherPage {
     Instances(Entity<InstancesPage>),
+    Worlds(Entity<WorldsPage>),
+    Accounts(Entity<AccountsPage>),
+    Skins(Entity<SkinsPage>),
     Syncing(Entity<SyncingPage>),
+    Storage(Entity<StoragePage>),
+    Debug(Entity<DebugPage>),
+    Mods(Entity<ModsPage>),
     Modrinth {
         installing_for: Option<InstanceID>,
         page: Entity<ModrinthSearchPage>,
@@ -112,7 +165,13 @@ impl LauncherPage {
     pub fn into_any_element(self) -> AnyElement {
         match self {
             LauncherPage::Instances(entity) => entity.into_any_element(),
+            LauncherPage::Worlds(entity) => entity.into_any_element(),
+            LauncherPage::Accounts(entity) => entity.into_any_element(),
+            LauncherPage::Skins(entity) => entity.into_any_element(),
             LauncherPage::Syncing(entity) => entity.into_any_element(),
+            LauncherPage::Storage(entity) => entity.into_any_element(),
+            LauncherPage::Debug(entity) => entity.into_any_element(),
+            LauncherPage::Mods(entity) => entity.into_any_element(),
             LauncherPage::Modrinth { page, .. } => page.into_any_element(),
             LauncherPage::InstancePage(_, _, entity) => entity.into_any_element(),
         }
@@ -121,7 +180,13 @@ impl LauncherPage {
     pub fn page_type(&self) -> PageType {
         match self {
             LauncherPage::Instances(_) => PageType::Instances,
+            LauncherPage::Worlds(_) => PageType::Worlds,
+            LauncherPage::Accounts(_) => PageType::Accounts,
+            LauncherPage::Skins(_) => PageType::Skins,
             LauncherPage::Syncing(_) => PageType::Syncing,
+            LauncherPage::Storage(_) => PageType::Storage,
+            LauncherPage::Debug(_) => PageType::Debug,
+            LauncherPage::Mods(_) => PageType::Mods,
             LauncherPage::Modrinth { installing_for, .. } => PageType::Modrinth { installing_for: *installing_for, project_type: None },
             LauncherPage::InstancePage(id, subpage, _) => PageType::InstancePage(*id, *subpage),
         }
@@ -171,6 +236,9 @@ impl LauncherUI {
         let _instance_removed_subscription =
             cx.subscribe_in::<_, InstanceRemovedEvent>(&data.instances, window, |this, _, event, window, cx| {
                 this.recent_instances.retain(|entry| entry.0 != event.id);
+                this.cached_pages.retain(|(breadcrumbs, page)| {
+                    !std::iter::once(page.page_type()).chain(breadcrumbs.iter().copied()).any(|page| page.refers_to(event.id))
+                });
                 if let LauncherPage::InstancePage(id, _, _) = this.page
                     && id == event.id
                 {
@@ -200,6 +268,8 @@ impl LauncherUI {
         Self {
             data: data.clone(),
             page: Self::create_page(&data, page_type, &page_path, window, cx),
+            breadcrumbs: page_path,
+            cached_pages: Vec::new(),
             sidebar_state,
             default_sidebar_width,
             recent_instances,
@@ -216,9 +286,27 @@ impl LauncherUI {
             PageType::Instances => {
                 LauncherPage::Instances(cx.new(|cx| InstancesPage::new(data, window, cx)))
             },
+            PageType::Worlds => {
+                LauncherPage::Worlds(cx.new(|cx| WorldsPage::new(data, window, cx)))
+            },
+            PageType::Accounts => {
+                LauncherPage::Accounts(cx.new(|cx| AccountsPage::new(data, window, cx)))
+            },
+            PageType::Skins => {
+                LauncherPage::Skins(cx.new(|cx| SkinsPage::new(data, window, cx)))
+            },
             PageType::Syncing => {
                 LauncherPage::Syncing(cx.new(|cx| SyncingPage::new(data, window, cx)))
             },
+            PageType::Storage => {
+                LauncherPage::Storage(cx.new(|cx| StoragePage::new(data, window, cx)))
+            },
+            PageType::Debug => {
+                LauncherPage::Debug(cx.new(|cx| DebugPage::new(data, window, cx)))
+            },
+            PageType::Mods => {
+                LauncherPage::Mods(cx.new(|cx| ModsPage::new(data, window, cx)))
+            },
             PageType::Modrinth { installing_for, project_type } => {
                 let page = cx.new(|cx| {
                     ModrinthSearchPage::new(installing_for, project_type, path, data, window, cx)
@@ -247,41 +335,112 @@ impl LauncherUI {
         config.main_page = main_page;
         config.page_path = page_path;
 
-        self.page = Self::create_page(&self.data, page, breadcrumbs, window, cx);
+        if self.page.page_type().cache_key() == page.cache_key() && self.breadcrumbs == breadcrumbs {
+            Self::set_instance_subpage(&mut self.page, page, window, cx);
+            cx.notify();
+            return;
+        }
+
+        let cached = self.cached_pages.iter().position(|(cached_breadcrumbs, cached)| {
+            cached.page_type().cache_key() == page.cache_key() && cached_breadcrumbs == breadcrumbs
+        });
+        let next_page = match cached {
+            Some(index) => {
+                let (_, mut cached) = self.cached_pages.remove(index);
+                Self::set_instance_subpage(&mut cached, page, window, cx);
+                cached
+            },
+            None => Self::create_page(&self.data, page, breadcrumbs, window, cx),
+        };
+
+        let previous_page = std::mem::replace(&mut self.page, next_page);
+        let previous_breadcrumbs = std::mem::replace(&mut self.breadcrumbs, breadcrumbs.to_vec());
+        self.cached_pages.insert(0, (previous_breadcrumbs, previous_page));
+        self.cached_pages.truncate(MAX_CACHED_PAGES);
         cx.notify();
     }
+
+    fn set_instance_subpage(launcher_page: &mut LauncherPage, page: PageType, window: &mut Window, cx: &mut Context<Self>) {
+        if let (LauncherPage::InstancePage(_, current, entity), PageType::InstancePage(_, subpage)) = (launcher_page, page) {
+            entity.update(cx, |instance_page, cx| instance_page.set_subpage(subpage, window, cx));
+            *current = subpage;
+        }
+    }
+
+    /// The tab the instance's page was left on, so going back to the instance from the sidebar keeps it
+    fn instance_page_type(&self, id: InstanceID) -> PageType {
+        let subpage = std::iter::once(&self.page).chain(self.cached_pages.iter().map(|(_, page)| page)).find_map(|page| match page {
+            LauncherPage::InstancePage(page_id, subpage, _) if *page_id == id => Some(*subpage),
+            _ => None,
+        });
+        PageType::InstancePage(id, subpage.unwrap_or(InstanceSubpageType::Quickplay))
+    }
 }
 
 impl Render for LauncherUI {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let page_type = self.page.page_type();
 
-        let library_group = MenuGroup::new("Play")
-            .child(MenuGroupItem::new("Instances")
+        let library_group = MenuGroup::new(ts!("sidebar_play"))
+            .child(MenuGroupItem::new(ts!("sidebar_instances"))
                 .active(page_type == PageType::Instances)
                 .on_click(cx.listener(|launcher, _, window, cx| {
                     launcher.switch_page(PageType::Instances, &[], window, cx);
+                })))
+            .child(MenuGroupItem::new(ts!("sidebar_worlds"))
+                .active(page_type == PageType::Worlds)
+                .on_click(cx.listener(|launcher, _, window, cx| {
+                    launcher.switch_page(PageType::Worlds, &[], window, cx);
+                })))
+            .child(MenuGroupItem::new(ts!("sidebar_accounts"))
+                .active(page_type == PageType::Accounts)
+                .on_click(cx.listener(|launcher, _, window, cx| {
+                    launcher.switch_page(PageType::Accounts, &[], window, cx);
+                })))
+            .child(MenuGroupItem::new(ts!("sidebar_skins"))
+                .active(page_type == PageType::Skins)
+                .on_click(cx.listener(|launcher, _, window, cx| {
+                    launcher.switch_page(PageType::Skins, &[], window, cx);
                 })));
 
-        let launcher_group = MenuGroup::new("Content")
-            .child(MenuGroupItem::new("Modrinth")
+        let launcher_group = MenuGroup::new(ts!("sidebar_content"))
+            .child(MenuGroupItem::new(ts!("sidebar_mods"))
+                .active(page_type == PageType::Mods)
+                .on_click(cx.listener(|launcher, _, window, cx| {
+                    launcher.switch_page(PageType::Mods, &[], window, cx);
+                })))
+            .child(MenuGroupItem::new(ts!("sidebar_modrinth"))
                 .active(page_type == PageType::Modrinth { installing_for: None, project_type: None })
                 .on_click(cx.listener(|launcher, _, window, cx| {
                     launcher.switch_page(PageType::Modrinth { installing_for: None, project_type: None }, &[], window, cx);
                 })))
-            .child(MenuGroupItem::new("Syncing")
+            .child(MenuGroupItem::new(ts!("sidebar_syncing"))
                 .active(page_type == PageType::Syncing)
                 .on_click(cx.listener(|launcher, _, window, cx| {
                     launcher.switch_page(PageType::Syncing, &[], window, cx);
+                })))
+            .child(MenuGroupItem::new(ts!("sidebar_storage"))
+                .active(page_type == PageType::Storage)
+                .on_click(cx.listener(|launcher, _, window, cx| {
+                    launcher.switch_page(PageType::Storage, &[], window, cx);
                 })));
 
-        let mut groups: heapless::Vec<MenuGroup, 3> = heapless::Vec::new();
+        let mut groups: heapless::Vec<MenuGroup, 4> = heapless::Vec::new();
 
         let _ = groups.push(library_group);
         let _ = groups.push(launcher_group);
 
+        if cfg!(debug_assertions) {
+            let _ = groups.push(MenuGroup::new(ts!("sidebar_developer"))
+                .child(MenuGroupItem::new(ts!("sidebar_debug"))
+                    .active(page_type == PageType::Debug)
+                    .on_click(cx.listener(|launcher, _, window, cx| {
+                        launcher.switch_page(PageType::Debug, &[], window, cx);
+                    }))));
+        }
+
         if !self.recent_instances.is_empty() {
-            let mut recent_instances_group = MenuGroup::new("Recent Instances");
+            let mut recent_instances_group = MenuGroup::new(ts!("sidebar_recent_instances"));
 
             for (id, name) in &self.recent_instances {
                 let name = name.clone();
@@ -294,7 +453,8 @@ impl Render for LauncherUI {
                 let item = MenuGroupItem::new(name)
                     .active(active)
                     .on_click(cx.listener(move |launcher, _, window, cx| {
-                        launcher.switch_page(PageType::InstancePage(id, InstanceSubpageType::Quickplay), &[PageType::Instances], window, cx);
+                        let page = launcher.instance_page_type(id);
+                        launcher.switch_page(page, &[PageType::Instances], window, cx);
                     }));
                 recent_instances_group = recent_instances_group.child(item);
             }
@@ -315,7 +475,7 @@ impl Render for LauncherUI {
         } else {
             (
                 gpui::img(ImageSource::Resource(Resource::Embedded("images/default_head.png".into()))),
-                "No Account".into(),
+                ts!("sidebar_no_account"),
             )
         };
 
@@ -372,8 +532,11 @@ impl Render for LauncherUI {
                                         this.on_click({
                                             let backend_handle = backend_handle.clone();
                                             let uuid = account.uuid;
-                                            move |_, _, _| {
-                                                backend_handle.send(MessageToBackend::SelectAccount { uuid });
+                                            move |_, window, cx| {
+                                                let select_backend_handle = backend_handle.clone();
+                                                crate::launcher_lock::when_unlocked(&backend_handle, window, cx, move |_, _| {
+                                                    select_backend_handle.send(MessageToBackend::SelectAccount { uuid });
+                                                });
                                             }
                                         })
                                     }))
@@ -385,73 +548,31 @@ impl Render for LauncherUI {
                                     .on_click({
                                         let backend_handle = backend_handle.clone();
                                         let uuid = account.uuid;
-                                        move |_, _, _| {
-                                            backend_handle.send(MessageToBackend::DeleteAccount { uuid });
+                                        move |_, window, cx| {
+                                            let delete_backend_handle = backend_handle.clone();
+                                            crate::launcher_lock::when_unlocked(&backend_handle, window, cx, move |_, _| {
+                                                delete_backend_handle.send(MessageToBackend::DeleteAccount { uuid });
+                                            });
                                         }
                                     }))
 
                         });
 
                         sheet
-                            .title("Accounts")
+                            .title(ts!("sidebar_accounts"))
                             .overlay_top(crate::root::sheet_margin_top(window))
                             .child(v_flex()
                                 .gap_2()
-                                .child(Button::new("add-account").h_10().success().icon(IconName::Plus).label("Add account").on_click({
+                                .child(Button::new("add-account").h_10().success().icon(IconName::Plus).label(ts!("accounts_add_account")).on_click({
                                     let backend_handle = backend_handle.clone();
                                     move |_, window, cx| {
                                         crate::root::start_new_account_login(&backend_handle, window, cx);
                                     }
                                 }))
-                                .child(Button::new("add-offline").h_10().success().icon(IconName::Plus).label("Add offline account").on_click({
+                                .child(Button::new("add-offline").h_10().success().icon(IconName::Plus).label(ts!("accounts_add_offline_account")).on_click({
                                     let backend_handle = backend_handle.clone();
                                     move |_, window, cx| {
-                                        let name_input = cx.new(|cx| {
-                                            InputState::new(window, cx)
-                                        });
-                                        let uuid_input = cx.new(|cx| {
-                                            InputState::new(window, cx).placeholder("Random")
-                                        });
-                                        let backend_handle = backend_handle.clone();
-                                        window.open_dialog(cx, move |dialog, _, cx| {
-                                            let username = name_input.read(cx).value();
-                                            let valid_name = username.len() >= 1 && username.len() <= 16 &&
-                                                username.as_bytes().iter().all(|c| *c > 32 && *c < 127);
-                                            let uuid = uuid_input.read(cx).value();
-                                            let valid_uuid = uuid.is_empty() || Uuid::try_parse(&uuid).is_ok();
-
-                                            let valid = valid_name && valid_uuid;
-
-                                            let backend_handle = backend_handle.clone();
-                                            let mut add_button = Button::new("add").label("Add").disabled(!valid).on_click(move |_, window, cx| {
-                                                window.close_all_dialogs(cx);
-
-                                                let uuid = if let Ok(uuid) = Uuid::try_parse(&uuid) {
-                                                   uuid
-                                                } else {
-                                                    let uuid: u128 = rand::thread_rng().r#gen();
-                                                    let uuid = (uuid & !0xF0000000000000000000) | 0x30000000000000000000; // set version to 3
-                                                    Uuid::from_u128(uuid)
-                                                };
-
-                                                backend_handle.send(MessageToBackend::AddOfflineAccount {
-                                                    name: username.clone().into(),
-                                                    uuid
-                                                });
-                                            });
-
-                                            if valid {
-                                                add_button = add_button.success();
-                                            }
-
-                                            dialog.title("Add offline account")
-                                                .child(v_flex()
-                                                    .gap_2()
-                                                    .child(crate::labelled("Name", Input::new(&name_input)))
-                                                    .child(crate::labelled("UUID", Input::new(&uuid_input)))
-                                                    .child(add_button)
-                                                )
-                                        });
+                                        modals::add_offline_account::open_add_offline_account(backend_handle.clone(), window, cx);
                                     }
                                 }))
                                 .children(items)
@@ -479,6 +600,49 @@ impl Render for LauncherUI {
                 }
             });
 
+        let running_tasks = crate::active_operations::ActiveOperations::in_progress(cx).len();
+        let tasks_button = h_flex()
+            .id("tasks-button")
+            .gap_1()
+            .p_2()
+            .rounded(cx.theme().radius)
+            .hover(|this| {
+                this.bg(cx.theme().sidebar_accent)
+                    .text_color(cx.theme().sidebar_accent_foreground)
+            })
+            .child(IconName::Loader)
+            .when(running_tasks > 0, |this| this.child(div().text_xs().child(running_tasks.to_string())))
+            .on_click(|_, window, cx| {
+                window.open_sheet_at(gpui_component::Placement::Left, cx, modals::task_center::build_task_center_sheet());
+            });
+
+        let unread_notifications = crate::notification_history::NotificationHistory::unread(cx);
+        let notifications_button = h_flex()
+            .id("notifications-button")
+            .gap_1()
+            .p_2()
+            .rounded(cx.theme().radius)
+            .hover(|this| {
+                this.bg(cx.theme().sidebar_accent)
+                    .text_color(cx.theme().sidebar_accent_foreground)
+            })
+            .child(Icon::default().path("icons/bell.svg"))
+            .when(unread_notifications > 0, |this| this.child(div()
+                .px_1()
+                .rounded_full()
+                .bg(cx.theme().danger)
+                .text_color(cx.theme().danger_foreground)
+                .text_xs()
+                .child(unread_notifications.min(99).to_string())))
+            .on_click({
+                let data = self.data.clone();
+                move |_, window, cx| {
+                    // Marked here rather than while rendering the sheet, which must not modify state
+                    crate::notification_history::NotificationHistory::mark_read(cx);
+                    window.open_sheet_at(gpui_component::Placement::Left, cx, modals::notification_center::build_notification_center_sheet(&data));
+                }
+            });
+
         let header = h_flex()
             .pt_5()
             .px_5()
@@ -489,7 +653,7 @@ impl Render for LauncherUI {
             .text_size(rems(0.9375))
             .child(pandora_icon.size_8().min_w_8().min_h_8())
             .child("Pandora");
-        let footer = h_flex().pb_3().px_3().flex_wrap().justify_center().w_full().child(settings_button).child(account_button);
+        let footer = h_flex().pb_3().px_3().flex_wrap().justify_center().w_full().child(settings_button).child(tasks_button).child(notifications_button).child(account_button);
         let sidebar = v_flex()
             .w_full()
             .bg(cx.theme().sidebar)
@@ -511,6 +675,16 @@ impl Render for LauncherUI {
     }
 }
 
+/// A page with a fixed title, where the content scrolls. The scroll handle belongs to the page entity so the position
+/// survives switching pages
+pub fn scroll_page(cx: &App, title: impl IntoElement, scroll_handle: &ScrollHandle, content: impl IntoElement) -> gpui::Div {
+    page(cx, title).child(h_flex()
+        .flex_1()
+        .min_h_0()
+        .child(div().id("page-content").size_full().overflow_y_scroll().track_scroll(scroll_handle).child(content))
+        .child(div().w_3().h_full().py_3().child(Scrollbar::vertical(scroll_handle))))
+}
+
 pub fn page(cx: &App, title: impl IntoElement) -> gpui::Div {
     v_flex().size_full().child(
         h_flex()
@@ -0,0 +1,171 @@
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bridge::handle::{BackendHandle, FrontendReceiver};
+use bridge::message::{MessageToBackend, MessageToFrontend};
+use bridge::modal_action::ModalAction;
+use clap::Subcommand;
+use schema::loader::Loader;
+
+/// Creating an instance only writes its folder, so it shows up as soon as the backend notices the new folder
+const CREATE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Launch an instance by name without opening the launcher window
+    Launch {
+        name: String,
+    },
+    /// Print every instance with its Minecraft version and loader
+    List,
+    /// Create a new instance
+    Create {
+        /// Minecraft version, e.g. 1.21.1
+        #[arg(long)]
+        version: String,
+        /// vanilla, fabric, forge or neoforge
+        #[arg(long, default_value = "vanilla")]
+        loader: String,
+        /// Defaults to the version and loader
+        #[arg(long)]
+        name: Option<String>,
+    },
+}
+
+struct InstanceListing {
+    id: bridge::instance::InstanceID,
+    name: String,
+    version: String,
+    loader: Loader,
+}
+
+/// Runs the command against a backend without a window, returning the process exit code
+pub fn run(launcher_dir: PathBuf, command: Command) -> i32 {
+    let (backend_recv, backend_handle, mut frontend_recv, frontend_handle) = bridge::handle::create_pair();
+
+    backend::start(launcher_dir, frontend_handle, backend_handle.clone(), backend_recv);
+
+    // The initial load has finished by the time the backend has started, so every instance has been announced
+    let mut instances = Vec::new();
+    while let Some(message) = frontend_recv.try_recv() {
+        if let MessageToFrontend::InstanceAdded { id, name, configuration, .. } = message {
+            instances.push(InstanceListing {
+                id,
+                name: name.to_string(),
+                version: configuration.minecraft_version.to_string(),
+                loader: configuration.loader,
+            });
+        }
+    }
+
+    if !matches!(command, Command::List) && !unlock(&backend_handle) {
+        return 1;
+    }
+
+    match command {
+        Command::Launch { name } => launch(&backend_handle, &instances, &name),
+        Command::List => {
+            instances.sort_by_cached_key(|instance| instance.name.to_lowercase());
+            let width = instances.iter().map(|instance| instance.name.len()).max().unwrap_or(0);
+            for instance in &instances {
+                println!("{:width$}  {:10}  {}", instance.name, instance.version, instance.loader.name());
+            }
+            0
+        },
+        Command::Create { version, loader, name } => create(&backend_handle, &mut frontend_recv, version, &loader, name),
+    }
+}
+
+/// Asks for the launcher PIN on the terminal when one is set, since the backend refuses to launch or create
+/// instances until it has been entered
+fn unlock(backend_handle: &BackendHandle) -> bool {
+    let (send, recv) = tokio::sync::oneshot::channel();
+    backend_handle.send(MessageToBackend::GetBackendConfiguration { channel: send });
+    let Ok(config) = recv.blocking_recv() else {
+        return false;
+    };
+    if config.launcher_lock.is_none() {
+        return true;
+    }
+
+    eprint!("Launcher PIN: ");
+    _ = std::io::stderr().flush();
+    let mut pin = String::new();
+    if std::io::stdin().lock().read_line(&mut pin).is_err() {
+        return false;
+    }
+
+    let (send, recv) = tokio::sync::oneshot::channel();
+    backend_handle.send(MessageToBackend::CheckLauncherPin { pin: Arc::from(pin.trim()), channel: send });
+    if recv.blocking_recv().unwrap_or(false) {
+        true
+    } else {
+        eprintln!("Incorrect PIN");
+        false
+    }
+}
+
+fn launch(backend_handle: &BackendHandle, instances: &[InstanceListing], name: &str) -> i32 {
+    let Some(instance) = instances.iter().find(|instance| instance.name == name) else {
+        crate::show_error(format!("Unable to find instance {}", name));
+        return 1;
+    };
+
+    println!("Starting instance {}", name);
+    let modal_action = ModalAction::default();
+    backend_handle.send(MessageToBackend::StartInstance {
+        id: instance.id,
+        quick_play: None,
+        mods: Default::default(),
+        modal_action: modal_action.clone()
+    });
+    crate::run_modal_action(modal_action.clone());
+    std::thread::sleep(Duration::from_millis(100));
+
+    if modal_action.error.read().unwrap().is_some() { 1 } else { 0 }
+}
+
+fn create(backend_handle: &BackendHandle, frontend_recv: &mut FrontendReceiver, version: String, loader: &str, name: Option<String>) -> i32 {
+    let loader = Loader::from_name(loader);
+    if loader == Loader::Unknown {
+        eprintln!("Unknown loader, expected vanilla, fabric, forge or neoforge");
+        return 1;
+    }
+    let name = name.unwrap_or_else(|| match loader {
+        Loader::Vanilla => version.clone(),
+        _ => format!("{version} {}", loader.name()),
+    });
+
+    let modal_action = ModalAction::default();
+    backend_handle.send(MessageToBackend::CreateInstance {
+        name: name.as_str().into(),
+        version: version.as_str().into(),
+        loader,
+        modal_action: modal_action.clone(),
+    });
+
+    // Only errors of this operation count, other warnings the backend shows while it runs are unrelated
+    let deadline = Instant::now() + CREATE_TIMEOUT;
+    let mut created = false;
+    while Instant::now() < deadline {
+        if let Some(error) = &*modal_action.error.read().unwrap() {
+            eprintln!("{error}");
+            return 1;
+        }
+        while let Some(message) = frontend_recv.try_recv() {
+            if let MessageToFrontend::InstanceAdded { name: added, .. } = message && added.as_str() == name {
+                created = true;
+            }
+        }
+        if created && modal_action.get_finished_at().is_some() {
+            println!("Created instance {name}");
+            return 0;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    eprintln!("Timed out waiting for instance {name} to be created");
+    1
+}
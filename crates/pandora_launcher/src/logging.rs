@@ -0,0 +1,115 @@
+use std::{fs::File, io::Write, path::Path, sync::Mutex, time::SystemTime};
+
+use tracing::{Event, Level, Subscriber};
+use tracing_log::NormalizeEvent;
+use tracing_subscriber::{
+    filter::Targets, fmt::{format::Writer, FmtContext, FormatEvent, FormatFields}, layer::SubscriberExt,
+    registry::LookupSpan, util::SubscriberInitExt,
+};
+
+/// Lines logged before the file is opened, kept until then but not past this size
+const MAX_BUFFERED_BYTES: usize = 1024 * 1024;
+
+/// The launcher log is only rotated and opened once the launcher directory is known to be ours, otherwise a second
+/// launcher would move the log of the one that's running away from it. Lines logged before that are buffered
+enum LogFile {
+    Buffering(Vec<u8>),
+    Open(File),
+    Disabled,
+}
+
+static LOG_FILE: Mutex<LogFile> = Mutex::new(LogFile::Buffering(Vec::new()));
+
+struct LogFileWriter;
+
+impl Write for LogFileWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match &mut *LOG_FILE.lock().unwrap() {
+            LogFile::Buffering(buffer) => {
+                if buffer.len() + buf.len() <= MAX_BUFFERED_BYTES {
+                    buffer.extend_from_slice(buf);
+                }
+            },
+            LogFile::Open(file) => file.write_all(buf)?,
+            LogFile::Disabled => {},
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match &mut *LOG_FILE.lock().unwrap() {
+            LogFile::Open(file) => file.flush(),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// `[time level target] message fields`, which is what the log viewer on the debug page expects
+struct LineFormat;
+
+impl<S, N> FormatEvent<S, N> for LineFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> std::fmt::Result {
+        // Records from the `log` crate, e.g. those of gpui, keep their own target instead of `log`
+        let normalized = event.normalized_metadata();
+        let metadata = normalized.as_ref().unwrap_or_else(|| event.metadata());
+        write!(writer, "[{} {} {}] ", humantime::format_rfc3339_seconds(SystemTime::now()), metadata.level(), metadata.target())?;
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+/// Logs to stdout and to the launcher log, debug for the launcher's own crates and info for everything else.
+/// Records of the `log` crate are forwarded as well
+pub fn setup() -> Result<(), tracing_subscriber::util::TryInitError> {
+    let targets = Targets::new()
+        .with_default(Level::INFO)
+        .with_target("pandora_launcher", Level::DEBUG)
+        .with_target("auth", Level::DEBUG)
+        .with_target("backend", Level::DEBUG)
+        .with_target("frontend", Level::DEBUG)
+        .with_target("bridge", Level::DEBUG);
+
+    tracing_subscriber::registry()
+        .with(targets)
+        .with(tracing_subscriber::fmt::layer().event_format(LineFormat).with_ansi(false).with_writer(|| LogFileWriter))
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stdout))
+        .try_init()
+}
+
+/// Rotates the logs of previous sessions and starts writing the launcher log, including what was logged before.
+/// Called once this process owns the launcher directory
+pub fn open_file(launcher_dir: &Path) {
+    let mut log_file = LOG_FILE.lock().unwrap();
+    let LogFile::Buffering(buffer) = &mut *log_file else {
+        return;
+    };
+    let buffer = std::mem::take(buffer);
+
+    _ = std::fs::create_dir_all(launcher_dir);
+    backend::launcher_log::rotate(launcher_dir);
+    match File::create(launcher_dir.join(backend::launcher_log::FILE_NAME)) {
+        Ok(mut file) => {
+            _ = file.write_all(&buffer);
+            *log_file = LogFile::Open(file);
+        },
+        Err(error) => {
+            *log_file = LogFile::Disabled;
+            // Logging goes through the same lock
+            drop(log_file);
+            tracing::warn!("Unable to open the launcher log: {error}");
+        },
+    }
+}
+
+/// Stops buffering lines for the launcher log, for processes that don't own the launcher directory and leave the
+/// log to the launcher that does
+pub fn disable_file() {
+    let mut log_file = LOG_FILE.lock().unwrap();
+    if matches!(*log_file, LogFile::Buffering(_)) {
+        *log_file = LogFile::Disabled;
+    }
+}
@@ -5,12 +5,9 @@ use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::fmt::Write;
-use std::time::SystemTime;
 
-use bridge::message::MessageToFrontend;
 use bridge::modal_action::{ModalAction, ProgressTrackerFinishType};
 use clap::Parser;
-use fern::colors::ColoredLevelConfig;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use native_dialog::DialogBuilder;
 use parking_lot::RwLock;
@@ -18,11 +15,20 @@ use parking_lot::RwLock;
 #[derive(Parser, Debug)]
 #[command()]
 struct Args {
-    /// Instance to launch, instead of opening the launcher
-    #[arg(long)]
-    run_instance: Option<String>,
+    /// Instance to launch, instead of opening the launcher. Shortcuts created before `--launch` existed use
+    /// `--run-instance`
+    #[arg(long, value_name = "INSTANCE", alias = "run-instance")]
+    launch: Option<String>,
+    /// Check that downloading and starting Java works by going through the launch of a throwaway instance,
+    /// using the latest release unless a version is given
+    #[arg(long, value_name = "VERSION", num_args = 0..=1)]
+    smoke_test: Option<Option<String>>,
+    #[command(subcommand)]
+    command: Option<cli::Command>,
 }
 
+mod cli;
+mod logging;
 pub mod panic;
 
 fn main() {
@@ -38,52 +44,120 @@ fn main() {
     let launcher_dir = data_dir.join("PandoraLauncher");
     _ = std::env::set_current_dir(&launcher_dir);
 
-    let log_path = launcher_dir.join("launcher.log");
-    if log_path.exists() {
-        let old_log_path = launcher_dir.join("launcher.log.old");
-        _ = std::fs::rename(log_path, old_log_path);
-    }
-
-    if let Err(error) = setup_logging(log::LevelFilter::Debug) {
+    if let Err(error) = logging::setup() {
         eprintln!("Unable to enable logging: {error:?}");
     }
 
-    log::debug!("DEBUG logging enabled");
-    log::trace!("TRACE logging enabled");
+    tracing::debug!("DEBUG logging enabled");
+    tracing::trace!("TRACE logging enabled");
 
     panic::install_logging_hook();
 
-    if let Some(run_instance) = args.run_instance {
-        let (backend_recv, backend_handle, mut frontend_recv, frontend_handle) = bridge::handle::create_pair();
+    if let Some(minecraft_version) = args.smoke_test {
+        require_launcher_dir(&launcher_dir);
+        let (backend_recv, backend_handle, _frontend_recv, frontend_handle) = bridge::handle::create_pair();
 
         backend::start(launcher_dir.clone(), frontend_handle, backend_handle.clone(), backend_recv);
 
-        while let Some(message) = frontend_recv.try_recv() {
-            if let MessageToFrontend::InstanceAdded { id, name, .. } = message {
-                if name.as_str() == run_instance.as_str() {
-                    println!("Starting instance {}", run_instance);
-                    let modal_action = ModalAction::default();
-                    backend_handle.send(bridge::message::MessageToBackend::StartInstance {
-                        id,
-                        quick_play: None,
-                        modal_action: modal_action.clone()
-                    });
-                    run_modal_action(modal_action);
-                    std::thread::sleep(std::time::Duration::from_millis(100));
-                    return;
+        let modal_action = ModalAction::default();
+        backend_handle.send(bridge::message::MessageToBackend::SmokeTest {
+            minecraft_version: minecraft_version.map(|version| version.as_str().into()),
+            modal_action: modal_action.clone(),
+        });
+        run_modal_action(modal_action.clone());
+        if modal_action.error.read().unwrap().is_some() {
+            std::process::exit(1);
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    } else if let Some(command) = args.command {
+        match &command {
+            cli::Command::Launch { name } => forward_launch(&launcher_dir, name),
+            // Listing only reads, so it works alongside a running launcher
+            cli::Command::List => {
+                if !lock_launcher_dir(&launcher_dir) {
+                    backend::data_lock::set_read_only(&launcher_dir);
+                    logging::disable_file();
                 }
-            }
+            },
+            cli::Command::Create { .. } => {},
         }
-
-        show_error(format!("Unable to find instance {}", run_instance));
-        std::process::exit(1);
+        require_launcher_dir(&launcher_dir);
+        std::process::exit(cli::run(launcher_dir, command));
+    } else if let Some(name) = args.launch {
+        forward_launch(&launcher_dir, &name);
+        require_launcher_dir(&launcher_dir);
+        std::process::exit(cli::run(launcher_dir, cli::Command::Launch { name }));
     } else {
+        if backend::ipc::forward(&launcher_dir, &backend::ipc::IpcRequest::Focus).is_some() {
+            tracing::info!("Launcher is already running, focused its window instead");
+            return;
+        }
+        if !lock_launcher_dir(&launcher_dir) {
+            let open_read_only = DialogBuilder::message()
+                .set_level(native_dialog::MessageLevel::Warning)
+                .set_title("Pandora Launcher is already running")
+                .set_text(format!("Another Pandora Launcher is using {}. Running two at once can corrupt instances and \
+                    downloads.\n\nOpen it read-only instead? Nothing will be saved and instances can't be launched \
+                    until the other launcher is closed.", launcher_dir.display()))
+                .confirm()
+                .show()
+                .unwrap_or(false);
+            if !open_read_only {
+                return;
+            }
+            backend::data_lock::set_read_only(&launcher_dir);
+            logging::disable_file();
+        }
+        backend::ipc::enable_server();
         run_gui(launcher_dir);
     }
 }
 
+/// Returns false if another launcher process is using the launcher directory. Otherwise the directory is ours, so
+/// the launcher log is started
+fn lock_launcher_dir(launcher_dir: &std::path::Path) -> bool {
+    let locked = match backend::data_lock::lock_launcher_dir(launcher_dir) {
+        Ok(locked) => locked,
+        Err(error) => {
+            // Not being able to create the lock file shouldn't stop the launcher from working
+            tracing::warn!("Unable to lock launcher directory: {error}");
+            true
+        },
+    };
+    if locked {
+        logging::open_file(launcher_dir);
+    }
+    locked
+}
+
+/// Exits if another launcher process is using the launcher directory, for commands that need to write to it
+fn require_launcher_dir(launcher_dir: &std::path::Path) {
+    if backend::data_lock::is_read_only() || lock_launcher_dir(launcher_dir) {
+        return;
+    }
+    show_error(format!("Another Pandora Launcher is using {}, close it and try again", launcher_dir.display()));
+    std::process::exit(1);
+}
+
+/// Lets a launcher that's already running start the instance, so its files aren't used by two processes at once.
+/// Returns if there's no running launcher
+fn forward_launch(launcher_dir: &std::path::Path, name: &str) {
+    let request = backend::ipc::IpcRequest::Launch { name: name.into() };
+    match backend::ipc::forward(launcher_dir, &request) {
+        Some(backend::ipc::IpcResponse::Ok) => {
+            println!("Starting instance {} in the running launcher", name);
+            std::process::exit(0);
+        },
+        Some(backend::ipc::IpcResponse::Error { message }) => {
+            show_error(message);
+            std::process::exit(1);
+        },
+        None => {},
+    }
+}
+
 fn show_error(error: String) {
-    log::error!("{}", error);
+    tracing::error!("{}", error);
     _ = DialogBuilder::message()
         .set_level(native_dialog::MessageLevel::Error)
         .set_title("An error occurred")
@@ -151,8 +225,8 @@ fn run_modal_action(modal_action: ModalAction) {
             }
 
             let (count, total) = tracker.get();
-            pb.set_length(total as u64);
-            pb.set_position(count as u64);
+            pb.set_length(total);
+            pb.set_position(count);
             pb.set_message(tracker.get_title().to_string());
 
             if tracker.get_finished_at().is_some() {
@@ -169,7 +243,7 @@ fn run_gui(launcher_dir: PathBuf) {
 
     let (backend_recv, backend_handle, frontend_recv, frontend_handle) = bridge::handle::create_pair();
 
-    crate::panic::install_hook(panic_message.clone(), frontend_handle.clone());
+    crate::panic::install_hook(panic_message.clone(), frontend_handle.clone(), backend_handle.clone());
 
     // Start deadlock detection
     std::thread::spawn({
@@ -193,7 +267,7 @@ fn run_gui(launcher_dir: PathBuf) {
                     }
                 }
 
-                log::error!("{}", message);
+                tracing::error!("{}", message);
                 *deadlock_message.write() = Some(message);
                 frontend_handle.send(bridge::message::MessageToFrontend::Refresh);
                 return;
@@ -202,54 +276,7 @@ fn run_gui(launcher_dir: PathBuf) {
     });
 
     backend::start(launcher_dir.clone(), frontend_handle, backend_handle.clone(), backend_recv);
-    frontend::start(launcher_dir.clone(), panic_message, deadlock_message, backend_handle, frontend_recv);
-}
-
-fn setup_logging(level: log::LevelFilter) -> Result<(), fern::InitError> {
-    let base_config = fern::Dispatch::new()
-        .level_for("pandora_launcher", level)
-        .level_for("auth", level)
-        .level_for("backend", level)
-        .level_for("frontend", level)
-        .level_for("bridge", level)
-        .level(log::LevelFilter::Info);
-
-    let colors_line = ColoredLevelConfig::new().info(fern::colors::Color::BrightWhite);
-
-    let file_config = fern::Dispatch::new()
-        .format(|out, message, record| {
-            out.finish(format_args!(
-                "[{time} {level} {target}] {message}",
-                time = humantime::format_rfc3339_seconds(SystemTime::now()),
-                level = record.level(),
-                target = record.target(),
-                message = message
-            ))
-        })
-        .chain(fern::log_file("launcher.log")?);
-
-    let stdout_config = fern::Dispatch::new()
-        .format(move |out, message, record| {
-            out.finish(format_args!(
-                "{color_line}[{time} {level} {target}{color_line}] {message}\x1B[0m",
-                color_line = format_args!(
-                    "\x1B[{}m",
-                    colors_line.get_color(&record.level()).to_fg_str()
-                ),
-                time = humantime::format_rfc3339_seconds(SystemTime::now()),
-                level = record.level(),
-                target = record.target(),
-                message = message
-            ))
-        })
-        .chain(std::io::stdout());
-
-    base_config
-        .chain(file_config)
-        .chain(stdout_config)
-        .apply()?;
-
-    Ok(())
+    frontend::start(launcher_dir.clone(), panic_message, deadlock_message, backend_handle, frontend_recv, backend::data_lock::is_read_only());
 }
 
 fn get_portable_dir() -> Option<PathBuf> {
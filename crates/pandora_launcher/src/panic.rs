@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use bridge::handle::FrontendHandle;
+use bridge::handle::{BackendHandle, FrontendHandle};
 use parking_lot::RwLock;
 
 pub fn install_logging_hook() {
@@ -32,11 +32,12 @@ pub fn install_logging_hook() {
             },
             None => format!("Thread {} panicked\n{}\n{:?}", thread_name, payload, PrettyBacktrace(backtrace)),
         };
-        log::error!("{}", message);
+        tracing::error!(thread = thread_name, panic = payload, "{}", message);
+        write_last_panic(&message);
     }));
 }
 
-pub fn install_hook(panic_message: Arc<RwLock<Option<String>>>, frontend_handle: FrontendHandle) {
+pub fn install_hook(panic_message: Arc<RwLock<Option<String>>>, frontend_handle: FrontendHandle, backend_handle: BackendHandle) {
     let old_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |info| {
         let thread = std::thread::current();
@@ -65,15 +66,24 @@ pub fn install_hook(panic_message: Arc<RwLock<Option<String>>>, frontend_handle:
                 None => format!("Backend panicked\n{}\n{:?}", payload, PrettyBacktrace(backtrace)),
             };
 
-            log::error!("{}", message);
+            tracing::error!(thread = "backend", panic = payload, "{}", message);
+            write_last_panic(&message);
             *panic_message.write() = Some(message);
             frontend_handle.send(bridge::message::MessageToFrontend::Refresh);
+            backend_handle.report_crash();
         } else {
             (old_hook)(info);
         }
     }));
 }
 
+/// Keeps the most recent panic next to the launcher log, so it's still around after the log has been rotated
+/// away and can be included in diagnostics bundles
+fn write_last_panic(message: &str) {
+    let time = humantime::format_rfc3339_seconds(std::time::SystemTime::now());
+    _ = std::fs::write("last_panic.txt", format!("[{time}] {message}\n"));
+}
+
 struct PrettyBacktrace(backtrace::Backtrace);
 
 impl std::fmt::Debug for PrettyBacktrace {
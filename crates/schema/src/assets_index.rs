@@ -3,7 +3,6 @@ use serde::Deserialize;
 use ustr::Ustr;
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct AssetsIndex {
     pub objects: IndexMap<Ustr, AssetObject>,
     // Used for 1.7 and below, indicates that the objects should be stored
@@ -15,7 +14,6 @@ pub struct AssetsIndex {
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct AssetObject {
     pub hash: Ustr,
     pub size: u32,
@@ -1,6 +1,9 @@
+use std::{path::Path, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
 use enumset::{EnumSet, EnumSetType};
 use serde::{Deserialize, Serialize};
-use crate::instance::{InstanceMemoryConfiguration, InstanceJvmFlagsConfiguration, InstanceJvmBinaryConfiguration};
+use crate::{instance::{InstanceMemoryConfiguration, InstanceJvmFlagsConfiguration, InstanceJvmBinaryConfiguration}, pandora_update::{UpdateChannel, UpdateCheckFrequency}};
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct BackendConfig {
@@ -8,6 +11,16 @@ pub struct BackendConfig {
     pub sync_targets: EnumSet<SyncTarget>,
     #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
     pub dont_open_game_output_when_launching: bool,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub kill_games_on_launcher_exit: bool,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub offline_fallback: bool,
+    /// Never uses the network, only what has been downloaded before
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub offline_mode: bool,
+    /// Skips the translations of languages other than the one picked in game
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub download_only_selected_language: bool,
     #[serde(default)]
     pub global_memory_enabled: bool,
     #[serde(default)]
@@ -20,6 +33,158 @@ pub struct BackendConfig {
     pub global_jvm_binary_enabled: bool,
     #[serde(default)]
     pub global_jvm_binary: Option<InstanceJvmBinaryConfiguration>,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub custom_user_agent: Option<Arc<str>>,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub endpoint_overrides: EndpointOverrides,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub proxy: ProxySettings,
+    /// PEM file with certificates trusted in addition to the system's, e.g. for a proxy that inspects TLS
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub custom_ca_certificates: Option<Arc<Path>>,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub download_limits: DownloadLimits,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub launch_limits: LaunchLimits,
+    /// Required by the CurseForge API, the CurseForge content source is unavailable without one
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub curseforge_api_key: Option<Arc<str>>,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub launcher_lock: Option<LauncherLock>,
+    /// Minutes that can be played per day across all instances
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub daily_playtime_limit: Option<u32>,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub refuse_launch_over_playtime_limit: bool,
+    /// Fails loading metadata that contains fields the schemas don't know about, instead of only reporting them
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub strict_schema: bool,
+    /// Set once the user was asked whether to reuse the official launcher's assets and libraries
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub offered_official_launcher_files: bool,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub update_channel: UpdateChannel,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub update_check_frequency: UpdateCheckFrequency,
+    /// When the launcher last checked for updates on its own
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub last_update_check: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub metadata_refresh_interval: MetadataRefreshInterval,
+    /// Also announces Minecraft versions found by a background refresh with a desktop notification
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub new_version_desktop_notification: bool,
+    /// Newest Minecraft version a background refresh has seen, versions listed above it are announced as new
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub announced_minecraft_version: Option<Arc<str>>,
+}
+
+/// How often the version manifest and the loader manifests instances use are refreshed in the background
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MetadataRefreshInterval {
+    /// Only fetched when needed and the cached copy has expired
+    #[default]
+    Never,
+    Hourly,
+    EverySixHours,
+    Daily,
+}
+
+impl MetadataRefreshInterval {
+    pub fn duration(self) -> Option<Duration> {
+        match self {
+            MetadataRefreshInterval::Never => None,
+            MetadataRefreshInterval::Hourly => Some(Duration::from_secs(60 * 60)),
+            MetadataRefreshInterval::EverySixHours => Some(Duration::from_secs(6 * 60 * 60)),
+            MetadataRefreshInterval::Daily => Some(Duration::from_secs(24 * 60 * 60)),
+        }
+    }
+}
+
+/// PIN that has to be entered before accounts can be used or changed and before instances can
+/// be launched, created or deleted
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LauncherLock {
+    pub salt: Arc<str>,
+    /// Hex encoded SHA-256 of the salt followed by the PIN
+    pub pin_sha256: Arc<str>,
+}
+
+/// Base URLs that replace Mojang's servers, e.g. to go through a caching meta proxy or a mirror. Downloads that fail
+/// through them are retried from Mojang's servers
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct EndpointOverrides {
+    /// Replaces piston-meta.mojang.com, launchermeta.mojang.com and piston-data.mojang.com, which serve the version
+    /// manifest, version info and Java runtimes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Arc<str>>,
+    /// Replaces resources.download.minecraft.net
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assets: Option<Arc<str>>,
+    /// Replaces libraries.minecraft.net
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub libraries: Option<Arc<str>>,
+}
+
+impl EndpointOverrides {
+    /// BMCLAPI, a mirror of Mojang's servers for places where they are slow or unreachable
+    pub fn bmclapi() -> Self {
+        Self {
+            meta: Some("https://bmclapi2.bangbang93.com".into()),
+            assets: Some("https://bmclapi2.bangbang93.com/assets".into()),
+            libraries: Some("https://bmclapi2.bangbang93.com/maven".into()),
+        }
+    }
+}
+
+/// Proxy every request goes through
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ProxySettings {
+    /// http://, https://, socks5:// or socks5h:// URL of the proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<Arc<str>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<Arc<str>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<Arc<str>>,
+}
+
+/// Limits shared by every download the launcher makes
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadLimits {
+    /// Files downloaded at the same time, 8 when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<u32>,
+    /// Combined download speed in KiB/s, unlimited when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_kib_per_second: Option<u32>,
+    /// Times a failed download is retried before giving up, 3 when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_retries: Option<u32>,
+}
+
+/// Limits on running several instances at once, so the system doesn't start swapping
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct LaunchLimits {
+    /// Instances that can run at the same time, unlimited when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_running: Option<u32>,
+    /// Percentage of physical memory that the max heaps of running instances may add up to, unlimited when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_budget_percent: Option<u32>,
+    /// Refuses launching over the memory budget instead of only warning
+    #[serde(default, skip_serializing_if = "crate::skip_if_default")]
+    pub refuse_over_memory_budget: bool,
+}
+
+/// Written into a duplicated or exported instance, records which synced folders it was linked
+/// to so they can be linked again when the instance is loaded
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncMembership {
+    pub targets: EnumSet<SyncTarget>,
+    /// The synced directory of the launcher the instance came from
+    pub synced_dir: Arc<Path>,
 }
 
 #[derive(Debug, enum_map::Enum, EnumSetType, strum::EnumIter)]
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+pub const CURSEFORGE_API_URL: &str = "https://api.curseforge.com/v1";
+pub const CURSEFORGE_MINECRAFT_GAME_ID: u32 = 432;
+
+/// Category classes of Minecraft content
+pub const CURSEFORGE_CLASS_MODS: u32 = 6;
+pub const CURSEFORGE_CLASS_MODPACKS: u32 = 4471;
+pub const CURSEFORGE_CLASS_RESOURCEPACKS: u32 = 12;
+pub const CURSEFORGE_CLASS_SHADERS: u32 = 6552;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CurseForgeResponse<T> {
+    pub data: T,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CurseForgeSearchResult {
+    pub data: Arc<[CurseForgeMod]>,
+    pub pagination: CurseForgePagination,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgePagination {
+    pub index: usize,
+    pub page_size: usize,
+    pub total_count: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeMod {
+    pub id: u32,
+    pub name: Arc<str>,
+    pub summary: Option<Arc<str>>,
+    pub download_count: f64,
+    pub logo: Option<CurseForgeLogo>,
+    #[serde(default)]
+    pub authors: Arc<[CurseForgeAuthor]>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeLogo {
+    pub thumbnail_url: Option<Arc<str>>,
+    pub url: Option<Arc<str>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CurseForgeAuthor {
+    pub name: Arc<str>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeFile {
+    pub id: u32,
+    pub mod_id: u32,
+    pub display_name: Option<Arc<str>>,
+    pub file_name: Arc<str>,
+    /// Missing when the author doesn't allow downloads from outside of CurseForge
+    pub download_url: Option<Arc<str>>,
+    pub file_length: usize,
+    pub file_fingerprint: u32,
+    #[serde(default)]
+    pub hashes: Arc<[CurseForgeFileHash]>,
+    /// Minecraft versions mixed with loader names, e.g. `["1.20.1", "Fabric", "Client"]`
+    #[serde(default)]
+    pub game_versions: Arc<[Arc<str>]>,
+}
+
+impl CurseForgeFile {
+    pub fn sha1(&self) -> Option<&Arc<str>> {
+        self.hashes.iter().find(|hash| hash.algo == CURSEFORGE_HASH_SHA1).map(|hash| &hash.value)
+    }
+}
+
+/// Value of [`CurseForgeFileHash::algo`] for SHA-1, the other one is MD5
+pub const CURSEFORGE_HASH_SHA1: u32 = 1;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CurseForgeFileHash {
+    pub value: Arc<str>,
+    pub algo: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurseForgeModLoader {
+    Forge = 1,
+    Fabric = 4,
+    Quilt = 5,
+    NeoForge = 6,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CurseForgeFingerprintsRequest<'a> {
+    pub fingerprints: &'a [u32],
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeFingerprintMatches {
+    #[serde(default)]
+    pub exact_matches: Arc<[CurseForgeFingerprintMatch]>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CurseForgeFingerprintMatch {
+    pub id: u32,
+    pub file: CurseForgeFile,
+}
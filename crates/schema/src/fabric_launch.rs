@@ -6,7 +6,6 @@ use ustr::Ustr;
 use crate::fabric_loader_manifest::FabricLoaderVersion;
 
 #[derive(Deserialize, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct FabricLaunch {
     pub loader: Option<FabricLoaderVersion>,
     pub intermediary: Option<FabricIntermediaryVersion>,
@@ -15,7 +14,6 @@ pub struct FabricLaunch {
 }
 
 #[derive(Deserialize, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct FabricIntermediaryVersion {
     pub maven: Ustr,
     pub version: Ustr,
@@ -23,7 +21,6 @@ pub struct FabricIntermediaryVersion {
 }
 
 #[derive(Deserialize, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct FabricLaunchLauncherMeta {
     pub version: u32,
     pub min_java_version: u32,
@@ -33,7 +30,6 @@ pub struct FabricLaunchLauncherMeta {
 }
 
 #[derive(Deserialize, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct FabricLaunchLibraries {
     pub client: Arc<[FabricLaunchLibrary]>,
     pub common: Arc<[FabricLaunchLibrary]>,
@@ -50,7 +46,6 @@ pub struct FabricLaunchLibrary {
 }
 
 #[derive(Deserialize, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct FabricLaunchMainClasses {
     pub client: Ustr,
     pub server: Ustr,
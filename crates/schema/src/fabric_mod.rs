@@ -11,17 +11,18 @@ pub struct FabricModJson {
     // pub description: Option<Arc<str>>,
     pub authors: Option<Vec<Person>>,
     pub icon: Option<Icon>,
-    // #[serde(alias = "requires")]
-    // pub depends: Option<HashMap<Arc<str>, Dependency>>,
+    #[serde(default, alias = "requires", deserialize_with = "crate::try_deserialize")]
+    pub depends: Option<HashMap<Arc<str>, Dependency>>,
     // pub breaks: Option<HashMap<Arc<str>, Dependency>>,
 }
 
-// #[derive(Deserialize, Debug)]
-// #[serde(untagged)]
-// enum Dependency {
-//     Single(Arc<str>),
-//     Multiple(Vec<Arc<str>>)
-// }
+/// Version requirement of a dependency, only the mod id is used at the moment
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum Dependency {
+    Single(Arc<str>),
+    Multiple(Vec<Arc<str>>)
+}
 
 #[derive(Deserialize, Debug)]
 #[serde(untagged)]
@@ -19,7 +19,6 @@ pub struct ForgeInstallProfile {
 }
 
 #[derive(Debug, Deserialize)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct ForgeSidedData {
     pub client: Arc<str>,
     pub server: Arc<str>,
@@ -33,7 +32,6 @@ pub enum ForgeSide {
 }
 
 #[derive(Debug, Deserialize)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct ForgeInstallProcessor {
     pub sides: Option<Arc<[ForgeSide]>>,
     pub jar: Arc<str>,
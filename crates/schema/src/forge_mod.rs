@@ -1,10 +1,12 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug)]
 pub struct ModsToml {
-    pub mods: Vec<ModsTomlMod>
+    pub mods: Vec<ModsTomlMod>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub dependencies: Option<HashMap<Arc<str>, Vec<ModsTomlDependency>>>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -17,6 +19,24 @@ pub struct ModsTomlMod {
     pub authors: Option<Arc<str>>,
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModsTomlDependency {
+    pub mod_id: Arc<str>,
+    /// Used by Forge, NeoForge uses `type` instead
+    pub mandatory: Option<bool>,
+    pub r#type: Option<Arc<str>>,
+}
+
+impl ModsTomlDependency {
+    pub fn is_required(&self) -> bool {
+        match &self.r#type {
+            Some(r#type) => r#type.eq_ignore_ascii_case("required"),
+            None => self.mandatory.unwrap_or(true),
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 pub struct JarJarMetadata {
     pub jars: Vec<JarJarMetadataJar>
@@ -1,12 +1,16 @@
-use std::{path::Path, sync::Arc};
+use std::{collections::{BTreeMap, HashSet}, path::Path, sync::Arc};
 
 use serde::{Deserialize, Serialize};
 use ustr::Ustr;
+use uuid::Uuid;
 
 use crate::loader::Loader;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct InstanceConfiguration {
+    /// Identifies the instance across renames, assigned the first time the instance is loaded
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<Uuid>,
     pub minecraft_version: Ustr,
     pub loader: Loader,
     #[serde(default)]
@@ -19,6 +23,153 @@ pub struct InstanceConfiguration {
     pub jvm_binary: Option<InstanceJvmBinaryConfiguration>,
     #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_linux_wrapper_configuration")]
     pub linux_wrapper: Option<InstanceLinuxWrapperConfiguration>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_branding_configuration")]
+    pub branding: Option<InstanceBrandingConfiguration>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_environment_configuration")]
+    pub environment: Option<InstanceEnvironmentConfiguration>,
+    /// Account used to launch this instance instead of the globally selected one
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Option::is_none")]
+    pub account: Option<Uuid>,
+    /// Minutes this instance can be played per day
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Option::is_none")]
+    pub daily_playtime_limit: Option<u32>,
+    /// Prevents changes to the version, loader and content as well as deletion, launching is still allowed
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub locked: bool,
+    /// Reads seeds and death coordinates from the log after the game closes and adds them to the timeline
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub capture_log_events: bool,
+    /// Folder outside the instance the game runs in instead of its own .minecraft, e.g. another launcher's
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Option::is_none")]
+    pub game_directory: Option<Arc<Path>>,
+    /// Initial window size, the game's default is used if unset
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<InstanceResolutionConfiguration>,
+    /// Shown next to the name in the instance list
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Option::is_none")]
+    pub icon: Option<InstanceIcon>,
+    /// Instances can be grouped and sorted by their tag in the instance list
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Option::is_none")]
+    pub color_tag: Option<InstanceColorTag>,
+    /// When the game was last started, in milliseconds since the unix epoch
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Option::is_none")]
+    pub last_played: Option<i64>,
+    /// Playtime recorded by earlier versions, moved into the playtime ledger when the instance is loaded
+    #[serde(default, skip_serializing, deserialize_with = "crate::try_deserialize")]
+    pub total_playtime: u64,
+    /// Local packwiz or unpacked .mrpack folder that is mirrored into the instance whenever it changes
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Option::is_none")]
+    pub pack_source: Option<Arc<Path>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceIcon {
+    /// One of the icons bundled with the launcher, by name
+    Bundled(Ustr),
+    /// Image copied into the launcher's instance icons folder, named by its SHA-1
+    Image(Arc<Path>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceColorTag {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+    Pink,
+    Gray,
+}
+
+impl InstanceColorTag {
+    pub const ALL: [Self; 8] = [Self::Red, Self::Orange, Self::Yellow, Self::Green, Self::Blue, Self::Purple, Self::Pink, Self::Gray];
+
+    pub fn translation_key(self) -> &'static str {
+        match self {
+            Self::Red => "color_tag_red",
+            Self::Orange => "color_tag_orange",
+            Self::Yellow => "color_tag_yellow",
+            Self::Green => "color_tag_green",
+            Self::Blue => "color_tag_blue",
+            Self::Purple => "color_tag_purple",
+            Self::Pink => "color_tag_pink",
+            Self::Gray => "color_tag_gray",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstanceTimelineEventKind {
+    Seed,
+    Death,
+    Coordinates,
+}
+
+/// Something noteworthy that happened while playing, recovered from the game log
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InstanceTimelineEvent {
+    /// Unix time in milliseconds
+    pub time: i64,
+    pub kind: InstanceTimelineEventKind,
+    pub text: Arc<str>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSnapshotReason {
+    Launch,
+    ModpackUpdate,
+    /// Taken before rolling back to another snapshot, so the rollback can be undone
+    Rollback,
+    Manual,
+}
+
+/// The config files of an instance at some point in time. The contents are stored once per distinct file, named by
+/// their SHA-1
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ConfigSnapshot {
+    /// Unix time in milliseconds, unique within the instance
+    pub time: i64,
+    pub reason: ConfigSnapshotReason,
+    /// Hex encoded SHA-1 of each file, by its path relative to .minecraft
+    pub files: BTreeMap<Arc<str>, Arc<str>>,
+}
+
+impl InstanceConfiguration {
+    pub fn new(minecraft_version: Ustr, loader: Loader) -> Self {
+        Self {
+            uuid: None,
+            minecraft_version,
+            loader,
+            preferred_loader_version: None,
+            memory: None,
+            jvm_flags: None,
+            jvm_binary: None,
+            linux_wrapper: None,
+            branding: None,
+            environment: None,
+            account: None,
+            daily_playtime_limit: None,
+            locked: false,
+            capture_log_events: false,
+            game_directory: None,
+            resolution: None,
+            icon: None,
+            color_tag: None,
+            last_played: None,
+            total_playtime: 0,
+            pack_source: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InstanceResolutionConfiguration {
+    pub width: u32,
+    pub height: u32,
 }
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
@@ -31,6 +182,41 @@ pub struct InstanceMemoryConfiguration {
 impl InstanceMemoryConfiguration {
     pub const DEFAULT_MIN: u32 = 512;
     pub const DEFAULT_MAX: u32 = 4096;
+
+    /// Picks a heap size suited to a machine with `total_mib` of physical memory, leaving
+    /// the rest for the OS, the launcher and the game's native allocations
+    pub fn recommended(total_mib: u64) -> Self {
+        let max = (total_mib / 4).clamp(1024, 8192) as u32 / 256 * 256;
+        Self {
+            enabled: false,
+            min: Self::DEFAULT_MIN.min(max),
+            max,
+        }
+    }
+
+    /// Like [`Self::recommended`], but sized for the mods that will be loaded. Large modpacks need more heap
+    /// regardless of how much memory is installed, though never more than half of it
+    pub fn recommended_for_mods(total_mib: u64, mod_count: usize, mods_mib: u64) -> Self {
+        let by_count = match mod_count {
+            0 => 2048,
+            1..50 => 3072,
+            50..150 => 4096,
+            150..250 => 6144,
+            _ => 8192,
+        };
+        let by_size = match mods_mib {
+            0..200 => 2048,
+            200..500 => 4096,
+            500..1000 => 6144,
+            _ => 8192,
+        };
+        let max = (by_count.max(by_size) as u64).min(total_mib / 2).max(1024) as u32 / 256 * 256;
+        Self {
+            enabled: false,
+            min: Self::DEFAULT_MIN.min(max),
+            max,
+        }
+    }
 }
 
 impl Default for InstanceMemoryConfiguration {
@@ -59,6 +245,168 @@ pub struct InstanceJvmFlagsConfiguration {
     pub flags: Arc<str>,
 }
 
+impl InstanceJvmFlagsConfiguration {
+    /// Splits the flags like a shell would, falling back to whitespace if the quoting is unbalanced
+    pub fn split(&self) -> Vec<String> {
+        shell_words::split(&self.flags).unwrap_or_else(|_| {
+            self.flags.split_whitespace().map(str::to_string).collect()
+        })
+    }
+
+    /// Combines the global and instance flags. A flag set by the instance replaces any global
+    /// flag for the same option, e.g. an instance `-Xss4m` replaces a global `-Xss2m`
+    pub fn merge(global: Option<&Self>, instance: Option<&Self>) -> Option<Self> {
+        let global = global.filter(|flags| flags.enabled).map(Self::split).unwrap_or_default();
+        let instance = instance.filter(|flags| flags.enabled).map(Self::split).unwrap_or_default();
+        if global.is_empty() && instance.is_empty() {
+            return None;
+        }
+
+        let instance_options: HashSet<&str> = instance.iter().map(|flag| jvm_flag_option(flag)).collect();
+        let merged = global.iter()
+            .filter(|flag| !instance_options.contains(jvm_flag_option(flag)))
+            .chain(instance.iter());
+
+        Some(Self {
+            enabled: true,
+            flags: shell_words::join(merged).into(),
+        })
+    }
+}
+
+/// Returns the part of a JVM flag that identifies which option it sets, so that
+/// `-Xmx2G` and `-Xmx4G` or `-XX:+UseG1GC` and `-XX:-UseG1GC` are considered the same option
+pub fn jvm_flag_option(flag: &str) -> &str {
+    const SIZE_OPTIONS: &[&str] = &["-Xmx", "-Xms", "-Xss", "-Xmn"];
+
+    if let Some(option) = SIZE_OPTIONS.iter().find(|option| flag.starts_with(**option)) {
+        return *option;
+    }
+    if let Some(option) = flag.strip_prefix("-XX:") {
+        let option = option.trim_start_matches(['+', '-']);
+        return option.split_once('=').map_or(option, |(key, _)| key);
+    }
+    if flag.starts_with("-D") {
+        return flag.split_once('=').map_or(flag, |(key, _)| key);
+    }
+    flag
+}
+
+/// Finds heap size flags that are set more than once, in which case only the last one takes effect
+pub fn jvm_flag_warnings(flags: &[String], memory_enabled: bool) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for option in ["-Xmx", "-Xms"] {
+        let count = flags.iter().filter(|flag| jvm_flag_option(flag) == option).count();
+        if count > 1 {
+            warnings.push(format!("{option} is specified {count} times, only the last one will be used"));
+        } else if count == 1 && memory_enabled {
+            warnings.push(format!("{option} overrides the memory setting"));
+        }
+    }
+    warnings
+}
+
+/// Overrides what the game is told about the launcher, some servers and anticheat mods check these
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct InstanceBrandingConfiguration {
+    pub enabled: bool,
+    /// Replaces `${launcher_name}` in the version's arguments, the default is used if empty
+    #[serde(default)]
+    pub launcher_name: Arc<str>,
+    /// Replaces `${launcher_version}` in the version's arguments, the default is used if empty
+    #[serde(default)]
+    pub launcher_version: Arc<str>,
+    /// Extra `-D` system properties, one `key=value` per line
+    #[serde(default)]
+    pub system_properties: Arc<str>,
+}
+
+impl InstanceBrandingConfiguration {
+    /// Parses each non-empty line of the system properties into its key and value
+    pub fn system_properties(&self) -> Vec<Result<(&str, &str), String>> {
+        self.system_properties.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                let line = line.trim().strip_prefix("-D").unwrap_or(line.trim());
+                match line.split_once('=') {
+                    Some((key, _)) if key.is_empty() || key.contains(char::is_whitespace) => {
+                        Err(format!("Line {}: '{key}' isn't a valid property name", index + 1))
+                    },
+                    Some((key, value)) => Ok((key, value)),
+                    None => Err(format!("Line {}: expected key=value", index + 1)),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Extra environment variables for the game process, e.g. `MESA_GL_VERSION_OVERRIDE` to work around driver issues
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct InstanceEnvironmentConfiguration {
+    pub enabled: bool,
+    /// One `KEY=value` per line
+    #[serde(default)]
+    pub variables: Arc<str>,
+}
+
+impl InstanceEnvironmentConfiguration {
+    /// Parses each non-empty line into the variable's name and value
+    pub fn variables(&self) -> Vec<Result<(&str, &str), String>> {
+        self.variables.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                match line.trim().split_once('=') {
+                    Some((key, value)) => Self::check_variable(key, value)
+                        .map(|()| (key, value))
+                        .map_err(|error| format!("Line {}: {error}", index + 1)),
+                    None => Err(format!("Line {}: expected KEY=value", index + 1)),
+                }
+            })
+            .collect()
+    }
+
+    /// Checks that a variable can be set on the game process and stored on its own line
+    pub fn check_variable(key: &str, value: &str) -> Result<(), String> {
+        if key.is_empty() || key.contains(|c: char| c.is_whitespace() || c == '\0' || c == '=') {
+            Err(format!("'{key}' isn't a valid variable name"))
+        } else if value.contains('\0') {
+            Err("values can't contain NUL characters".into())
+        } else if value.contains(['\n', '\r']) {
+            Err("values can't contain line breaks".into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Stores the variables as one `KEY=value` per line, the format read by [`Self::variables`]
+    pub fn format_variables<'a>(variables: impl IntoIterator<Item = (&'a str, &'a str)>) -> Arc<str> {
+        variables.into_iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .into()
+    }
+}
+
+fn is_default_environment_configuration(config: &Option<InstanceEnvironmentConfiguration>) -> bool {
+    if let Some(config) = config {
+        !config.enabled && config.variables.trim_ascii().is_empty()
+    } else {
+        true
+    }
+}
+
+fn is_default_branding_configuration(config: &Option<InstanceBrandingConfiguration>) -> bool {
+    if let Some(config) = config {
+        !config.enabled && config.launcher_name.is_empty() && config.launcher_version.is_empty()
+            && config.system_properties.trim_ascii().is_empty()
+    } else {
+        true
+    }
+}
+
 fn is_default_jvm_flags_configuration(config: &Option<InstanceJvmFlagsConfiguration>) -> bool {
     if let Some(config) = config {
         !config.enabled && config.flags.trim_ascii().is_empty()
@@ -71,6 +419,9 @@ fn is_default_jvm_flags_configuration(config: &Option<InstanceJvmFlagsConfigurat
 pub struct InstanceJvmBinaryConfiguration {
     pub enabled: bool,
     pub path: Option<Arc<Path>>,
+    /// Major version reported by `java -version` when the binary was picked, None if it couldn't be run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub major_version: Option<u32>,
 }
 
 fn is_default_jvm_binary_configuration(config: &Option<InstanceJvmBinaryConfiguration>) -> bool {
@@ -81,7 +432,7 @@ fn is_default_jvm_binary_configuration(config: &Option<InstanceJvmBinaryConfigur
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct InstanceLinuxWrapperConfiguration {
     #[serde(default, deserialize_with = "crate::try_deserialize")]
     pub use_mangohud: bool,
@@ -89,6 +440,32 @@ pub struct InstanceLinuxWrapperConfiguration {
     pub use_gamemode: bool,
     #[serde(default = "crate::default_true", deserialize_with = "crate::try_deserialize")]
     pub use_discrete_gpu: bool,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub use_prime_run: bool,
+    /// Free-form wrapper command run after the presets, e.g. `obs-gamecapture` or `nice -n 5`
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub custom_command: Arc<str>,
+}
+
+impl InstanceLinuxWrapperConfiguration {
+    /// Programs and arguments put in front of the Java invocation. The custom command is split like a shell would,
+    /// falling back to whitespace if the quoting is unbalanced
+    pub fn wrapper_command(&self) -> Vec<String> {
+        let mut command = Vec::new();
+        if self.use_gamemode {
+            command.push("gamemoderun".to_string());
+        }
+        if self.use_mangohud {
+            command.push("mangohud".to_string());
+        }
+        if self.use_prime_run {
+            command.push("prime-run".to_string());
+        }
+        command.extend(shell_words::split(&self.custom_command).unwrap_or_else(|_| {
+            self.custom_command.split_whitespace().map(str::to_string).collect()
+        }));
+        command
+    }
 }
 
 impl Default for InstanceLinuxWrapperConfiguration {
@@ -97,14 +474,61 @@ impl Default for InstanceLinuxWrapperConfiguration {
             use_mangohud: false,
             use_gamemode: false,
             use_discrete_gpu: true,
+            use_prime_run: false,
+            custom_command: Arc::from(""),
         }
     }
 }
 
 fn is_default_linux_wrapper_configuration(config: &Option<InstanceLinuxWrapperConfiguration>) -> bool {
     if let Some(config) = config {
-        !config.use_mangohud && !config.use_gamemode && config.use_discrete_gpu
+        !config.use_mangohud && !config.use_gamemode && config.use_discrete_gpu && !config.use_prime_run
+            && config.custom_command.trim().is_empty()
     } else {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn environment(variables: &str) -> InstanceEnvironmentConfiguration {
+        InstanceEnvironmentConfiguration { enabled: true, variables: variables.into() }
+    }
+
+    #[test]
+    fn parses_environment_variables() {
+        let config = environment("MESA_GL_VERSION_OVERRIDE=4.5\n\n  __GL_THREADED_OPTIMIZATIONS=1  \nJAVA_OPTS=-Da=b");
+        assert_eq!(config.variables(), vec![
+            Ok(("MESA_GL_VERSION_OVERRIDE", "4.5")),
+            Ok(("__GL_THREADED_OPTIMIZATIONS", "1")),
+            Ok(("JAVA_OPTS", "-Da=b")),
+        ]);
+    }
+
+    #[test]
+    fn reports_invalid_environment_variables() {
+        let config = environment("MESA_DEBUG\nMY VAR=1\n=1\nNUL=a\0b");
+        assert_eq!(config.variables(), vec![
+            Err("Line 1: expected KEY=value".to_string()),
+            Err("Line 2: 'MY VAR' isn't a valid variable name".to_string()),
+            Err("Line 3: '' isn't a valid variable name".to_string()),
+            Err("Line 4: values can't contain NUL characters".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn environment_variables_round_trip() {
+        let variables = [("MESA_GL_VERSION_OVERRIDE", "4.5"), ("JAVA_OPTS", "-Da=b"), ("EMPTY", "")];
+        let config = environment(&InstanceEnvironmentConfiguration::format_variables(variables));
+        assert_eq!(config.variables(), variables.map(Ok::<_, String>).to_vec());
+    }
+
+    #[test]
+    fn rejects_variables_that_would_not_round_trip() {
+        assert!(InstanceEnvironmentConfiguration::check_variable("A=B", "c").is_err());
+        assert!(InstanceEnvironmentConfiguration::check_variable("A", "b\nC=d").is_err());
+        assert!(InstanceEnvironmentConfiguration::check_variable("A", "b=c").is_ok());
+    }
+}
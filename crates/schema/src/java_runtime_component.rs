@@ -5,7 +5,6 @@ use serde::Deserialize;
 use ustr::Ustr;
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct JavaRuntimeComponentManifest {
     pub files: IndexMap<Arc<Path>, JavaRuntimeComponentFile>,
 }
@@ -13,7 +12,6 @@ pub struct JavaRuntimeComponentManifest {
 #[derive(Deserialize, Clone, Debug)]
 #[serde(tag = "type")]
 #[serde(rename_all = "lowercase")]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub enum JavaRuntimeComponentFile {
     Directory,
     File {
@@ -26,7 +24,6 @@ pub enum JavaRuntimeComponentFile {
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct JavaRuntimeComponentFileDownloads {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub lzma: Option<JavaRuntimeComponentFileDownload>,
@@ -34,7 +31,6 @@ pub struct JavaRuntimeComponentFileDownloads {
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct JavaRuntimeComponentFileDownload {
     pub sha1: Ustr,
     pub size: u32,
@@ -0,0 +1,43 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use serde::Deserialize;
+
+/// `launcher_profiles.json` of the official Minecraft Launcher
+#[derive(Deserialize, Debug)]
+pub struct LauncherProfilesJson {
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub profiles: HashMap<Arc<str>, LauncherProfile>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LauncherProfile {
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub name: Option<Arc<str>>,
+    /// "latest-release", "latest-snapshot" or "custom"
+    #[serde(default, rename = "type", deserialize_with = "crate::try_deserialize")]
+    pub profile_type: Option<Arc<str>>,
+    /// Version folder the profile launches, e.g. "1.21.1" or "fabric-loader-0.16.5-1.21.1"
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub last_version_id: Option<Arc<str>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub game_dir: Option<Arc<Path>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub java_args: Option<Arc<str>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub resolution: Option<LauncherProfileResolution>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct LauncherProfileResolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// The part of `versions/<id>/<id>.json` needed to find which Minecraft version a modded version is based on
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LauncherVersionJson {
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub inherits_from: Option<Arc<str>>,
+}
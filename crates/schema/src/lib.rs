@@ -4,6 +4,7 @@ pub mod assets_index;
 pub mod auxiliary;
 pub mod backend_config;
 pub mod content;
+pub mod curseforge;
 pub mod fabric_launch;
 pub mod fabric_loader_manifest;
 pub mod fabric_mod;
@@ -12,13 +13,16 @@ pub mod forge_mod;
 pub mod instance;
 pub mod java_runtime_component;
 pub mod java_runtimes;
+pub mod launcher_profiles;
 pub mod loader;
 pub mod maven;
 pub mod modification;
 pub mod modrinth;
 pub mod mrpack;
+pub mod multimc;
 pub mod pandora_update;
 pub mod resourcepack;
+pub mod speedrun_mods;
 pub mod version;
 pub mod version_manifest;
 
@@ -39,6 +39,16 @@ impl Loader {
         }
     }
 
+    /// Converts a loader version as other launchers and modpacks write it into the form used for preferred loader
+    /// versions, Forge's maven prefixes its versions with the Minecraft version
+    pub fn maven_version(self, minecraft_version: &str, loader_version: &str) -> String {
+        if self == Loader::Forge && !loader_version.starts_with(&format!("{minecraft_version}-")) {
+            format!("{minecraft_version}-{loader_version}")
+        } else {
+            loader_version.to_string()
+        }
+    }
+
     pub fn as_modrinth_loader(self) -> ModrinthLoader {
         match self {
             Loader::Vanilla => ModrinthLoader::Unknown,
@@ -1,8 +1,8 @@
-use std::sync::Arc;
+use std::{collections::{BTreeMap, HashMap}, sync::Arc};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::{fabric_mod::Person, modification::ModrinthModpackFileDownload};
+use crate::{fabric_mod::Person, loader::Loader, modification::ModrinthModpackFileDownload};
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -10,6 +10,9 @@ pub struct ModrinthIndexJson {
     pub version_id: Arc<str>,
     pub name: Arc<str>,
     pub files: Arc<[ModrinthModpackFileDownload]>,
+    /// Minecraft and loader versions, keyed by "minecraft", "fabric-loader", "forge", "neoforge" or "quilt-loader"
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub dependencies: HashMap<Arc<str>, Arc<str>>,
 
     // Unofficial
     #[serde(default, deserialize_with = "crate::try_deserialize")]
@@ -17,3 +20,55 @@ pub struct ModrinthIndexJson {
     #[serde(default, deserialize_with = "crate::try_deserialize")]
     pub author: Option<Person>,
 }
+
+impl ModrinthIndexJson {
+    pub fn minecraft_version(&self) -> Option<&str> {
+        self.dependencies.get("minecraft").map(|version| &**version)
+    }
+
+    /// The loader the pack was made for and its version
+    pub fn loader(&self) -> (Loader, Option<&str>) {
+        for (key, loader) in MRPACK_LOADERS {
+            if let Some(version) = self.dependencies.get(*key) {
+                return (*loader, Some(version));
+            }
+        }
+        if self.dependencies.contains_key("quilt-loader") {
+            return (Loader::Unknown, None);
+        }
+        (Loader::Vanilla, None)
+    }
+}
+
+pub const MRPACK_LOADERS: &[(&str, Loader)] = &[
+    ("fabric-loader", Loader::Fabric),
+    ("forge", Loader::Forge),
+    ("neoforge", Loader::NeoForge),
+];
+
+/// `modrinth.index.json` as written when exporting an instance
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModrinthIndexJsonExport {
+    pub format_version: u32,
+    pub game: &'static str,
+    pub version_id: Arc<str>,
+    pub name: Arc<str>,
+    pub files: Vec<ModrinthModpackFileExport>,
+    pub dependencies: BTreeMap<&'static str, Arc<str>>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModrinthModpackFileExport {
+    pub path: Arc<str>,
+    pub hashes: ModrinthModpackFileHashes,
+    pub downloads: Vec<Arc<str>>,
+    pub file_size: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ModrinthModpackFileHashes {
+    pub sha1: Arc<str>,
+    pub sha512: Arc<str>,
+}
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use crate::loader::Loader;
+
+/// `mmc-pack.json` of a MultiMC or Prism Launcher instance, listing the components it's built from
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MmcPack {
+    pub components: Vec<MmcPackComponent>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MmcPackComponent {
+    pub uid: Arc<str>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub version: Option<Arc<str>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub cached_version: Option<Arc<str>>,
+}
+
+pub const MMC_MINECRAFT_UID: &str = "net.minecraft";
+
+pub const MMC_LOADER_UIDS: &[(&str, Loader)] = &[
+    ("net.fabricmc.fabric-loader", Loader::Fabric),
+    ("net.minecraftforge", Loader::Forge),
+    ("net.neoforged", Loader::NeoForge),
+    ("org.quiltmc.quilt-loader", Loader::Unknown),
+];
+
+impl MmcPackComponent {
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref().or(self.cached_version.as_deref())
+    }
+}
+
+impl MmcPack {
+    pub fn minecraft_version(&self) -> Option<&str> {
+        self.components.iter().find(|component| &*component.uid == MMC_MINECRAFT_UID)?.version()
+    }
+
+    /// The loader component and its version, `Loader::Vanilla` if there is none
+    pub fn loader(&self) -> (Loader, Option<&str>) {
+        for component in &self.components {
+            if let Some((_, loader)) = MMC_LOADER_UIDS.iter().find(|(uid, _)| **uid == *component.uid) {
+                return (*loader, component.version());
+            }
+        }
+        (Loader::Vanilla, None)
+    }
+}
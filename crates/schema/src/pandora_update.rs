@@ -1,6 +1,38 @@
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+/// Which releases the launcher updates to
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    /// Also offers releases marked as pre-releases on GitHub
+    Prerelease,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateCheckFrequency {
+    #[default]
+    OnStartup,
+    Daily,
+    Weekly,
+    /// Only checks when asked to in the settings
+    Never,
+}
+
+impl UpdateCheckFrequency {
+    /// Time that has to pass between checks, None if there are no automatic checks after startup
+    pub fn interval(self) -> Option<Duration> {
+        match self {
+            UpdateCheckFrequency::OnStartup | UpdateCheckFrequency::Never => None,
+            UpdateCheckFrequency::Daily => Some(Duration::from_secs(24 * 60 * 60)),
+            UpdateCheckFrequency::Weekly => Some(Duration::from_secs(7 * 24 * 60 * 60)),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum UpdateInstallType {
@@ -25,6 +57,28 @@ pub struct UpdatePrompt {
     pub new_version: Arc<str>,
     pub install_type: UpdateInstallType,
     pub exe: UpdateManifestExe,
+    /// Release notes of the new version, with the markdown markup stripped
+    pub changelog: Option<Arc<str>>,
+}
+
+/// Release returned by the GitHub releases API
+#[derive(Deserialize, Debug, Clone)]
+pub struct GithubRelease {
+    pub tag_name: Arc<str>,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub body: Option<Arc<str>>,
+    #[serde(default)]
+    pub assets: Vec<GithubReleaseAsset>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct GithubReleaseAsset {
+    pub name: Arc<str>,
+    pub browser_download_url: Arc<str>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+/// Community-maintained list of mods that are legal for Minecraft speedrunning
+pub const SPEEDRUN_MODS_URL: &str = "https://raw.githubusercontent.com/tildejustin/mcsr-meta/schema-6/mods.json";
+
+/// Version that almost all speedrun categories are run on
+pub const SPEEDRUN_MINECRAFT_VERSION: &str = "1.16.1";
+
+#[derive(Deserialize, Debug)]
+pub struct SpeedrunModsManifest {
+    pub mods: Vec<SpeedrunMod>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SpeedrunMod {
+    pub modid: Arc<str>,
+    pub name: Arc<str>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub versions: Vec<SpeedrunModVersion>,
+    /// Restrictions such as "ssg-only", "rsg-only" or "mac-only"
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub traits: Vec<Arc<str>>,
+    #[serde(default = "crate::default_true")]
+    pub recommended: bool,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub obsolete: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SpeedrunModVersion {
+    pub target_version: Vec<Arc<str>>,
+    pub version: Arc<str>,
+    pub url: Arc<str>,
+    /// sha1 of the jar
+    pub hash: Arc<str>,
+}
+
+impl SpeedrunModsManifest {
+    /// Recommended random-seed mods for `minecraft_version` that can be used on the current OS
+    pub fn recommended_for(&self, minecraft_version: &str) -> Vec<(&SpeedrunMod, &SpeedrunModVersion)> {
+        self.mods.iter()
+            .filter(|m| m.recommended && !m.obsolete)
+            .filter(|m| !m.has_trait("ssg-only"))
+            .filter(|m| !m.has_trait("mac-only") || cfg!(target_os = "macos"))
+            .filter_map(|m| {
+                let version = m.versions.iter().find(|version| version.target_version.iter().any(|target| &**target == minecraft_version))?;
+                Some((m, version))
+            })
+            .collect()
+    }
+}
+
+impl SpeedrunMod {
+    pub fn has_trait(&self, name: &str) -> bool {
+        self.traits.iter().any(|t| &**t == name)
+    }
+}
@@ -8,7 +8,6 @@ use ustr::Ustr;
 use crate::version_manifest::MinecraftVersionType;
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct MinecraftVersion {
     pub arguments: Option<LaunchArguments>,
@@ -30,7 +29,6 @@ pub struct MinecraftVersion {
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct LaunchArguments {
     pub game: Arc<[LaunchArgument]>,
     pub jvm: Arc<[LaunchArgument]>,
@@ -56,7 +54,6 @@ impl<'de> Deserialize<'de> for LaunchArgument {
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct LaunchArgumentRuled {
     pub rules: Arc<[Rule]>,
     pub value: LaunchArgumentValue,
@@ -70,7 +67,6 @@ pub enum LaunchArgumentValue {
 }
 
 #[derive(Deserialize, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct Rule {
     pub action: RuleAction,
     pub features: Option<RuleFeatures>,
@@ -85,7 +81,6 @@ pub enum RuleAction {
 }
 
 #[derive(Deserialize, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct RuleFeatures {
     #[serde(default)]
     pub is_demo_user: bool,
@@ -102,7 +97,6 @@ pub struct RuleFeatures {
 }
 
 #[derive(Deserialize, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct RuleOs {
     pub name: Option<OsName>,
     pub arch: Option<OsArch>,
@@ -126,7 +120,6 @@ pub enum OsArch {
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct AssetIndexLink {
     pub id: Ustr,
@@ -137,7 +130,6 @@ pub struct AssetIndexLink {
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct GameDownloads {
     pub client: VersionDownloadLink,
     pub client_mappings: Option<VersionDownloadLink>,
@@ -148,7 +140,6 @@ pub struct GameDownloads {
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct VersionDownloadLink {
     pub sha1: Ustr,
     pub size: u32,
@@ -156,7 +147,6 @@ pub struct VersionDownloadLink {
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct JavaVersion {
     pub component: Ustr,
@@ -164,7 +154,6 @@ pub struct JavaVersion {
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct GameLibrary {
     pub downloads: GameLibraryDownloads,
     pub name: Ustr,
@@ -179,7 +168,6 @@ pub struct GameLibrary {
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct GameLibraryDownloads {
     pub artifact: Option<GameLibraryArtifact>,
 
@@ -188,7 +176,6 @@ pub struct GameLibraryDownloads {
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct GameLibraryArtifact {
     pub path: Ustr, // todo: this should be a safepath to avoid traversal?
     pub sha1: Option<Ustr>,
@@ -196,20 +183,17 @@ pub struct GameLibraryArtifact {
     pub url: Ustr,
 }
 
-#[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
+#[derive(Deserialize, Clone, Debug, Default)]
 pub struct GameLibraryExtractOptions {
     pub exclude: Option<Arc<[RelativePathBuf]>>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct GameLogging {
     pub client: Option<GameLoggingTarget>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct GameLoggingTarget {
     pub argument: Ustr,
     pub file: GameLoggingFile,
@@ -217,7 +201,6 @@ pub struct GameLoggingTarget {
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct GameLoggingFile {
     pub id: Ustr,
     pub sha1: Ustr,
@@ -226,7 +209,6 @@ pub struct GameLoggingFile {
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub enum GameLoggingType {
     #[serde(rename = "log4j2-xml")]
     Log4j2Xml,
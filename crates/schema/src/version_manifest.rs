@@ -5,21 +5,18 @@ use ustr::Ustr;
 pub const MOJANG_VERSION_MANIFEST_URL: &str = "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct MinecraftVersionManifest {
     pub latest: LatestMinecraftVersions,
     pub versions: Vec<MinecraftVersionLink>,
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct LatestMinecraftVersions {
     pub release: Ustr,
     pub snapshot: Ustr,
 }
 
 #[derive(Deserialize, Clone, Debug)]
-#[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct MinecraftVersionLink {
     pub id: Ustr,
@@ -31,7 +28,7 @@ pub struct MinecraftVersionLink {
     pub compliance_level: u32,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum MinecraftVersionType {
     Release,